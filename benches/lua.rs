@@ -1,5 +1,5 @@
 use criterion::{criterion_group, BatchSize, Criterion, Throughput};
-use futures::{stream, SinkExt, Stream, StreamExt};
+use futures::{future, stream, SinkExt, Stream, StreamExt};
 use indexmap::IndexMap;
 use indoc::indoc;
 use std::pin::Pin;
@@ -74,6 +74,11 @@ fn bench_add_fields(c: &mut Criterion) {
                 }))
             }
             Transform::Task(t) => t.transform(Box::pin(rx)),
+            // ignoring multiple outputs for now
+            Transform::FallibleTask(t) => Box::pin(
+                t.transform(Box::pin(rx))
+                    .filter_map(|result| future::ready(result.ok())),
+            ),
         };
 
         group.bench_function(name.to_owned(), |b| {
@@ -169,6 +174,11 @@ fn bench_field_filter(c: &mut Criterion) {
                 }))
             }
             Transform::Task(t) => t.transform(Box::pin(rx)),
+            // ignoring multiple outputs for now
+            Transform::FallibleTask(t) => Box::pin(
+                t.transform(Box::pin(rx))
+                    .filter_map(|result| future::ready(result.ok())),
+            ),
         };
 
         group.bench_function(name.to_owned(), |b| {
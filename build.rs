@@ -102,6 +102,7 @@ fn main() {
         println!("cargo:rerun-if-changed=proto/vector.proto");
         println!("cargo:rerun-if-changed=proto/dnstap.proto");
         println!("cargo:rerun-if-changed=proto/ddsketch.proto");
+        println!("cargo:rerun-if-changed=proto/google/pubsub/v1/pubsub.proto");
 
         let mut prost_build = prost_build::Config::new();
         prost_build.btree_map(&["."]);
@@ -114,6 +115,7 @@ fn main() {
                     "proto/vector.proto",
                     "proto/dnstap.proto",
                     "proto/ddsketch.proto",
+                    "proto/google/pubsub/v1/pubsub.proto",
                 ],
                 &["proto/", "lib/vector-core/proto/"],
             )
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+
+/// The export protocol of a flow packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowProtocol {
+    NetflowV5,
+    NetflowV9,
+    Ipfix,
+    SflowV5,
+}
+
+impl FlowProtocol {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            FlowProtocol::NetflowV5 => "netflow_v5",
+            FlowProtocol::NetflowV9 => "netflow_v9",
+            FlowProtocol::Ipfix => "ipfix",
+            FlowProtocol::SflowV5 => "sflow_v5",
+        }
+    }
+}
+
+impl fmt::Display for FlowProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A decoded field of a flow record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlowValue {
+    Unsigned(u64),
+    Float(f64),
+    Ip(IpAddr),
+    Mac([u8; 6]),
+    String(String),
+    Bytes(Vec<u8>),
+    DateTime(DateTime<Utc>),
+}
+
+/// Flow fields keyed by the snake cased name of their IPFIX information
+/// element, or a descriptive name for fields without one.
+pub type FlowRecord = BTreeMap<String, FlowValue>;
+
+/// The content of a single flow packet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowPacket {
+    pub protocol: FlowProtocol,
+    /// The fields of the packet header, shared by all records.
+    pub header: FlowRecord,
+    /// The flow records, or samples for sFlow.
+    pub records: Vec<FlowRecord>,
+    /// The IDs of templates referenced by data sets of this packet, which
+    /// haven't been received from the exporter yet. Those sets are skipped.
+    pub missing_templates: Vec<u16>,
+}
+
+impl FlowPacket {
+    pub(crate) fn new(protocol: FlowProtocol, header: FlowRecord) -> Self {
+        Self {
+            protocol,
+            header,
+            records: Vec::new(),
+            missing_templates: Vec::new(),
+        }
+    }
+
+    /// The time the packet was exported at, if the protocol records it.
+    pub fn export_time(&self) -> Option<DateTime<Utc>> {
+        match self.header.get("export_time") {
+            Some(FlowValue::DateTime(time)) => Some(*time),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn insert(record: &mut FlowRecord, name: &str, value: FlowValue) {
+    record.insert(name.to_owned(), value);
+}
+
+pub(crate) fn insert_unsigned(record: &mut FlowRecord, name: &str, value: impl Into<u64>) {
+    insert(record, name, FlowValue::Unsigned(value.into()));
+}
@@ -0,0 +1,111 @@
+use crate::{
+    flow_message::FlowPacket,
+    netflow_v5, sflow,
+    template::{self, TemplateCache},
+};
+use std::net::SocketAddr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The number of templates kept across all exporters by default.
+pub const DEFAULT_MAX_TEMPLATES: usize = 10_000;
+
+/// Error type for flow packet parsing
+#[derive(Error, Debug)]
+pub enum FlowParserError {
+    #[error(
+        "Packet is truncated, {} bytes needed but only {} available",
+        needed,
+        available
+    )]
+    Truncated { needed: usize, available: usize },
+    #[error("Unsupported flow export version {}", version)]
+    UnsupportedVersion { version: u32 },
+    #[error("Invalid length {} of {}", length, context)]
+    InvalidLength {
+        context: &'static str,
+        length: usize,
+    },
+    #[error("Invalid address type {}", address_type)]
+    InvalidAddressType { address_type: u32 },
+}
+
+/// Result alias for parsing
+pub type FlowParserResult<T> = Result<T, FlowParserError>;
+
+/// A parser for NetFlow v5, NetFlow v9, IPFIX and sFlow v5 packets.
+///
+/// NetFlow v9 and IPFIX data records can only be decoded with the templates
+/// the exporter sent earlier, so the parser keeps the templates of the
+/// exporters it has seen packets from, up to a limit.
+#[derive(Debug, Default)]
+pub struct FlowParser {
+    templates: TemplateCache,
+}
+
+impl FlowParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a parser that keeps at most `max_templates` templates, evicting
+    /// the least recently used ones, and forgets templates that were not sent
+    /// again within `template_expiry`.
+    pub fn with_template_limits(max_templates: usize, template_expiry: Option<Duration>) -> Self {
+        Self {
+            templates: TemplateCache::new(max_templates, template_expiry),
+        }
+    }
+
+    /// Parse a packet received from `exporter`.
+    pub fn parse(&mut self, exporter: SocketAddr, data: &[u8]) -> FlowParserResult<FlowPacket> {
+        if data.len() < 4 {
+            return Err(FlowParserError::Truncated {
+                needed: 4,
+                available: data.len(),
+            });
+        }
+
+        // NetFlow and IPFIX start with a 16 bit version, sFlow with a 32 bit one.
+        match u16::from_be_bytes([data[0], data[1]]) {
+            5 => netflow_v5::parse(data),
+            9 => template::parse_netflow_v9(&mut self.templates, exporter, data),
+            10 => template::parse_ipfix(&mut self.templates, exporter, data),
+            0 => match u32::from_be_bytes([data[0], data[1], data[2], data[3]]) {
+                5 => sflow::parse(data),
+                version => Err(FlowParserError::UnsupportedVersion { version }),
+            },
+            version => Err(FlowParserError::UnsupportedVersion {
+                version: u32::from(version),
+            }),
+        }
+    }
+
+    /// The number of templates known across all exporters.
+    pub fn template_count(&self) -> usize {
+        self.templates.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let exporter = "127.0.0.1:2055".parse().unwrap();
+        let mut parser = FlowParser::new();
+        assert!(matches!(
+            parser.parse(exporter, &[0, 7, 0, 0]),
+            Err(FlowParserError::UnsupportedVersion { version: 7 })
+        ));
+        assert!(matches!(
+            parser.parse(exporter, &[0, 0, 0, 4]),
+            Err(FlowParserError::UnsupportedVersion { version: 4 })
+        ));
+        assert!(matches!(
+            parser.parse(exporter, &[0, 5]),
+            Err(FlowParserError::Truncated { .. })
+        ));
+    }
+}
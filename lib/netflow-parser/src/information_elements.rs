@@ -0,0 +1,228 @@
+//! The IPFIX information elements registered with IANA, which also cover
+//! the field types of NetFlow v9.
+
+use crate::{flow_message::FlowValue, reader::unsigned};
+use chrono::{DateTime, TimeZone, Utc};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DataType {
+    Unsigned,
+    Float,
+    Ipv4,
+    Ipv6,
+    Mac,
+    String,
+    OctetArray,
+    DateTimeSeconds,
+    DateTimeMilliseconds,
+    DateTimeMicroseconds,
+    DateTimeNanoseconds,
+}
+
+/// The name and type of an information element without enterprise number.
+pub(crate) const fn lookup(id: u16) -> Option<(&'static str, DataType)> {
+    use DataType::*;
+
+    Some(match id {
+        1 => ("octet_delta_count", Unsigned),
+        2 => ("packet_delta_count", Unsigned),
+        3 => ("delta_flow_count", Unsigned),
+        4 => ("protocol_identifier", Unsigned),
+        5 => ("ip_class_of_service", Unsigned),
+        6 => ("tcp_control_bits", Unsigned),
+        7 => ("source_transport_port", Unsigned),
+        8 => ("source_ipv4_address", Ipv4),
+        9 => ("source_ipv4_prefix_length", Unsigned),
+        10 => ("ingress_interface", Unsigned),
+        11 => ("destination_transport_port", Unsigned),
+        12 => ("destination_ipv4_address", Ipv4),
+        13 => ("destination_ipv4_prefix_length", Unsigned),
+        14 => ("egress_interface", Unsigned),
+        15 => ("ip_next_hop_ipv4_address", Ipv4),
+        16 => ("bgp_source_as_number", Unsigned),
+        17 => ("bgp_destination_as_number", Unsigned),
+        18 => ("bgp_next_hop_ipv4_address", Ipv4),
+        19 => ("post_mcast_packet_delta_count", Unsigned),
+        20 => ("post_mcast_octet_delta_count", Unsigned),
+        21 => ("flow_end_sys_up_time", Unsigned),
+        22 => ("flow_start_sys_up_time", Unsigned),
+        23 => ("post_octet_delta_count", Unsigned),
+        24 => ("post_packet_delta_count", Unsigned),
+        25 => ("minimum_ip_total_length", Unsigned),
+        26 => ("maximum_ip_total_length", Unsigned),
+        27 => ("source_ipv6_address", Ipv6),
+        28 => ("destination_ipv6_address", Ipv6),
+        29 => ("source_ipv6_prefix_length", Unsigned),
+        30 => ("destination_ipv6_prefix_length", Unsigned),
+        31 => ("flow_label_ipv6", Unsigned),
+        32 => ("icmp_type_code_ipv4", Unsigned),
+        33 => ("igmp_type", Unsigned),
+        34 => ("sampling_interval", Unsigned),
+        35 => ("sampling_algorithm", Unsigned),
+        36 => ("flow_active_timeout", Unsigned),
+        37 => ("flow_idle_timeout", Unsigned),
+        38 => ("engine_type", Unsigned),
+        39 => ("engine_id", Unsigned),
+        40 => ("exported_octet_total_count", Unsigned),
+        41 => ("exported_message_total_count", Unsigned),
+        42 => ("exported_flow_record_total_count", Unsigned),
+        44 => ("source_ipv4_prefix", Ipv4),
+        45 => ("destination_ipv4_prefix", Ipv4),
+        46 => ("mpls_top_label_type", Unsigned),
+        47 => ("mpls_top_label_ipv4_address", Ipv4),
+        52 => ("minimum_ttl", Unsigned),
+        53 => ("maximum_ttl", Unsigned),
+        54 => ("fragment_identification", Unsigned),
+        55 => ("post_ip_class_of_service", Unsigned),
+        56 => ("source_mac_address", Mac),
+        57 => ("post_destination_mac_address", Mac),
+        58 => ("vlan_id", Unsigned),
+        59 => ("post_vlan_id", Unsigned),
+        60 => ("ip_version", Unsigned),
+        61 => ("flow_direction", Unsigned),
+        62 => ("ip_next_hop_ipv6_address", Ipv6),
+        63 => ("bgp_next_hop_ipv6_address", Ipv6),
+        64 => ("ipv6_extension_headers", Unsigned),
+        70 => ("mpls_top_label_stack_section", OctetArray),
+        80 => ("destination_mac_address", Mac),
+        81 => ("post_source_mac_address", Mac),
+        82 => ("interface_name", String),
+        83 => ("interface_description", String),
+        85 => ("octet_total_count", Unsigned),
+        86 => ("packet_total_count", Unsigned),
+        88 => ("fragment_offset", Unsigned),
+        89 => ("forwarding_status", Unsigned),
+        90 => ("mpls_vpn_route_distinguisher", OctetArray),
+        94 => ("application_description", String),
+        95 => ("application_id", OctetArray),
+        96 => ("application_name", String),
+        130 => ("exporter_ipv4_address", Ipv4),
+        131 => ("exporter_ipv6_address", Ipv6),
+        136 => ("flow_end_reason", Unsigned),
+        144 => ("exporting_process_id", Unsigned),
+        148 => ("flow_id", Unsigned),
+        150 => ("flow_start_seconds", DateTimeSeconds),
+        151 => ("flow_end_seconds", DateTimeSeconds),
+        152 => ("flow_start_milliseconds", DateTimeMilliseconds),
+        153 => ("flow_end_milliseconds", DateTimeMilliseconds),
+        154 => ("flow_start_microseconds", DateTimeMicroseconds),
+        155 => ("flow_end_microseconds", DateTimeMicroseconds),
+        156 => ("flow_start_nanoseconds", DateTimeNanoseconds),
+        157 => ("flow_end_nanoseconds", DateTimeNanoseconds),
+        160 => ("system_init_time_milliseconds", DateTimeMilliseconds),
+        161 => ("flow_duration_milliseconds", Unsigned),
+        176 => ("icmp_type_ipv4", Unsigned),
+        177 => ("icmp_code_ipv4", Unsigned),
+        178 => ("icmp_type_ipv6", Unsigned),
+        179 => ("icmp_code_ipv6", Unsigned),
+        180 => ("udp_source_port", Unsigned),
+        181 => ("udp_destination_port", Unsigned),
+        182 => ("tcp_source_port", Unsigned),
+        183 => ("tcp_destination_port", Unsigned),
+        192 => ("ip_ttl", Unsigned),
+        193 => ("next_header_ipv6", Unsigned),
+        225 => ("post_nat_source_ipv4_address", Ipv4),
+        226 => ("post_nat_destination_ipv4_address", Ipv4),
+        227 => ("post_napt_source_transport_port", Unsigned),
+        228 => ("post_napt_destination_transport_port", Unsigned),
+        234 => ("ingress_vrf_id", Unsigned),
+        235 => ("egress_vrf_id", Unsigned),
+        320 => ("absolute_error", Float),
+        _ => return None,
+    })
+}
+
+/// Decode a field of the given type. Fields whose length doesn't fit the
+/// type are kept as raw bytes.
+pub(crate) fn decode(data_type: DataType, bytes: &[u8]) -> FlowValue {
+    let value = match data_type {
+        DataType::Unsigned => unsigned(bytes).map(FlowValue::Unsigned),
+        DataType::Float => match bytes.len() {
+            4 => Some(f64::from(f32::from_bits(
+                unsigned(bytes).unwrap_or(0) as u32
+            ))),
+            8 => unsigned(bytes).map(f64::from_bits),
+            _ => None,
+        }
+        .map(FlowValue::Float),
+        DataType::Ipv4 => <[u8; 4]>::try_from(bytes)
+            .ok()
+            .map(|octets| FlowValue::Ip(IpAddr::V4(Ipv4Addr::from(octets)))),
+        DataType::Ipv6 => <[u8; 16]>::try_from(bytes)
+            .ok()
+            .map(|octets| FlowValue::Ip(IpAddr::V6(Ipv6Addr::from(octets)))),
+        DataType::Mac => <[u8; 6]>::try_from(bytes).ok().map(FlowValue::Mac),
+        DataType::String => Some(FlowValue::String(
+            String::from_utf8_lossy(bytes)
+                .trim_end_matches('\0')
+                .to_owned(),
+        )),
+        DataType::OctetArray => None,
+        DataType::DateTimeSeconds => unsigned(bytes)
+            .and_then(|secs| Utc.timestamp_opt(secs as i64, 0).single())
+            .map(FlowValue::DateTime),
+        DataType::DateTimeMilliseconds => unsigned(bytes)
+            .and_then(|millis| Utc.timestamp_millis_opt(millis as i64).single())
+            .map(FlowValue::DateTime),
+        DataType::DateTimeMicroseconds | DataType::DateTimeNanoseconds => {
+            ntp_timestamp(bytes).map(FlowValue::DateTime)
+        }
+    };
+    value.unwrap_or_else(|| FlowValue::Bytes(bytes.to_vec()))
+}
+
+/// Decode the NTP timestamp format used for the microsecond and nanosecond
+/// precision time fields.
+fn ntp_timestamp(bytes: &[u8]) -> Option<DateTime<Utc>> {
+    if bytes.len() != 8 {
+        return None;
+    }
+    let secs = unsigned(&bytes[..4])? as i64 - NTP_UNIX_OFFSET;
+    let fraction = unsigned(&bytes[4..])?;
+    let nanos = (fraction * 1_000_000_000) >> 32;
+    Utc.timestamp_opt(secs, nanos as u32).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_reduced_size_and_mismatched_fields() {
+        assert_eq!(
+            decode(DataType::Unsigned, &[0, 0, 1, 0]),
+            FlowValue::Unsigned(256)
+        );
+        assert_eq!(
+            decode(DataType::Ipv4, &[10, 0, 0]),
+            FlowValue::Bytes(vec![10, 0, 0])
+        );
+        assert_eq!(
+            decode(DataType::String, b"eth0\0\0"),
+            FlowValue::String("eth0".into())
+        );
+    }
+
+    #[test]
+    fn decodes_timestamps() {
+        assert_eq!(
+            decode(
+                DataType::DateTimeMilliseconds,
+                &1_635_756_032_500u64.to_be_bytes()
+            ),
+            FlowValue::DateTime(Utc.timestamp_millis(1_635_756_032_500))
+        );
+
+        let secs = (1_635_756_032 + NTP_UNIX_OFFSET) as u32;
+        let mut ntp = secs.to_be_bytes().to_vec();
+        ntp.extend_from_slice(&0x8000_0000u32.to_be_bytes());
+        assert_eq!(
+            decode(DataType::DateTimeMicroseconds, &ntp),
+            FlowValue::DateTime(Utc.timestamp(1_635_756_032, 500_000_000))
+        );
+    }
+}
@@ -0,0 +1,15 @@
+#![warn(
+    missing_debug_implementations,
+    rust_2018_idioms,
+    unreachable_pub,
+    non_snake_case,
+    non_upper_case_globals
+)]
+
+pub mod flow_message;
+pub mod flow_parser;
+mod information_elements;
+mod netflow_v5;
+mod reader;
+mod sflow;
+mod template;
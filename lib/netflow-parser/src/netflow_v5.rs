@@ -0,0 +1,146 @@
+use crate::{
+    flow_message::{insert, insert_unsigned, FlowPacket, FlowProtocol, FlowRecord, FlowValue},
+    flow_parser::{FlowParserError, FlowParserResult},
+    reader::Reader,
+};
+use chrono::{TimeZone, Utc};
+
+const HEADER_LENGTH: usize = 24;
+const RECORD_LENGTH: usize = 48;
+const MAX_RECORDS: usize = 30;
+
+pub(crate) fn parse(data: &[u8]) -> FlowParserResult<FlowPacket> {
+    let mut reader = Reader::new(data);
+    let version = reader.u16()?;
+    let count = usize::from(reader.u16()?);
+    if count > MAX_RECORDS {
+        return Err(FlowParserError::InvalidLength {
+            context: "NetFlow v5 record count",
+            length: count,
+        });
+    }
+
+    let mut header = FlowRecord::new();
+    insert_unsigned(&mut header, "version", version);
+    insert_unsigned(&mut header, "sys_up_time", reader.u32()?);
+    let secs = reader.u32()?;
+    let nsecs = reader.u32()?;
+    insert(
+        &mut header,
+        "export_time",
+        FlowValue::DateTime(Utc.timestamp(i64::from(secs), nsecs.min(999_999_999))),
+    );
+    insert_unsigned(&mut header, "sequence_number", reader.u32()?);
+    insert_unsigned(&mut header, "engine_type", reader.u8()?);
+    insert_unsigned(&mut header, "engine_id", reader.u8()?);
+    // The two most significant bits hold the sampling mode.
+    let sampling = reader.u16()?;
+    insert_unsigned(&mut header, "sampling_algorithm", sampling >> 14);
+    insert_unsigned(&mut header, "sampling_interval", sampling & 0x3fff);
+
+    let expected = HEADER_LENGTH + count * RECORD_LENGTH;
+    if data.len() < expected {
+        return Err(FlowParserError::Truncated {
+            needed: expected,
+            available: data.len(),
+        });
+    }
+
+    let mut packet = FlowPacket::new(FlowProtocol::NetflowV5, header);
+    for _ in 0..count {
+        packet.records.push(parse_record(&mut reader)?);
+    }
+    Ok(packet)
+}
+
+fn parse_record(reader: &mut Reader<'_>) -> FlowParserResult<FlowRecord> {
+    let mut record = FlowRecord::new();
+    let ip = |reader: &mut Reader<'_>| reader.ipv4().map(|ip| FlowValue::Ip(ip.into()));
+
+    insert(&mut record, "source_ipv4_address", ip(reader)?);
+    insert(&mut record, "destination_ipv4_address", ip(reader)?);
+    insert(&mut record, "ip_next_hop_ipv4_address", ip(reader)?);
+    insert_unsigned(&mut record, "ingress_interface", reader.u16()?);
+    insert_unsigned(&mut record, "egress_interface", reader.u16()?);
+    insert_unsigned(&mut record, "packet_delta_count", reader.u32()?);
+    insert_unsigned(&mut record, "octet_delta_count", reader.u32()?);
+    insert_unsigned(&mut record, "flow_start_sys_up_time", reader.u32()?);
+    insert_unsigned(&mut record, "flow_end_sys_up_time", reader.u32()?);
+    insert_unsigned(&mut record, "source_transport_port", reader.u16()?);
+    insert_unsigned(&mut record, "destination_transport_port", reader.u16()?);
+    reader.skip(1)?;
+    insert_unsigned(&mut record, "tcp_control_bits", reader.u8()?);
+    insert_unsigned(&mut record, "protocol_identifier", reader.u8()?);
+    insert_unsigned(&mut record, "ip_class_of_service", reader.u8()?);
+    insert_unsigned(&mut record, "bgp_source_as_number", reader.u16()?);
+    insert_unsigned(&mut record, "bgp_destination_as_number", reader.u16()?);
+    insert_unsigned(&mut record, "source_ipv4_prefix_length", reader.u8()?);
+    insert_unsigned(&mut record, "destination_ipv4_prefix_length", reader.u8()?);
+    reader.skip(2)?;
+
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn packet() -> Vec<u8> {
+        let mut data = vec![
+            0, 5, 0, 1, // version, count
+            0, 0, 0x03, 0xe8, // sys_up_time
+            0x61, 0x7f, 0xa8, 0x00, // unix_secs
+            0, 0, 0, 0, // unix_nsecs
+            0, 0, 0, 42, // flow_sequence
+            1, 2, // engine_type, engine_id
+            0x40, 0x64, // sampling
+        ];
+        data.extend_from_slice(&[
+            10, 0, 0, 1, // srcaddr
+            10, 0, 0, 2, // dstaddr
+            0, 0, 0, 0, // nexthop
+            0, 3, 0, 4, // input, output
+            0, 0, 0, 10, // dPkts
+            0, 0, 0x05, 0xdc, // dOctets
+            0, 0, 0, 100, // First
+            0, 0, 0, 200, // Last
+            0x30, 0x39, 0, 80, // srcport, dstport
+            0, 0x12, 6, 0, // pad1, tcp_flags, prot, tos
+            0xfd, 0xe8, 0, 0, // src_as, dst_as
+            24, 16, 0, 0, // src_mask, dst_mask, pad2
+        ]);
+        data
+    }
+
+    #[test]
+    fn parses_netflow_v5() {
+        let packet = parse(&packet()).unwrap();
+        assert_eq!(packet.protocol, FlowProtocol::NetflowV5);
+        assert_eq!(packet.export_time(), Some(Utc.timestamp(1_635_756_032, 0)));
+        assert_eq!(packet.header["sampling_algorithm"], FlowValue::Unsigned(1));
+        assert_eq!(packet.header["sampling_interval"], FlowValue::Unsigned(100));
+        assert_eq!(packet.header["sequence_number"], FlowValue::Unsigned(42));
+
+        assert_eq!(packet.records.len(), 1);
+        let record = &packet.records[0];
+        assert_eq!(
+            record["source_ipv4_address"],
+            FlowValue::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)))
+        );
+        assert_eq!(record["octet_delta_count"], FlowValue::Unsigned(1500));
+        assert_eq!(record["source_transport_port"], FlowValue::Unsigned(12345));
+        assert_eq!(record["tcp_control_bits"], FlowValue::Unsigned(0x12));
+        assert_eq!(record["bgp_source_as_number"], FlowValue::Unsigned(65000));
+        assert_eq!(record["source_ipv4_prefix_length"], FlowValue::Unsigned(24));
+    }
+
+    #[test]
+    fn rejects_truncated_records() {
+        let data = packet();
+        assert!(matches!(
+            parse(&data[..data.len() - 1]),
+            Err(FlowParserError::Truncated { .. })
+        ));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::flow_parser::{FlowParserError, FlowParserResult};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// A cursor over the big endian fields of a flow packet.
+#[derive(Debug, Clone)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(crate) const fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> FlowParserResult<&'a [u8]> {
+        if len > self.data.len() {
+            return Err(FlowParserError::Truncated {
+                needed: len,
+                available: self.data.len(),
+            });
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    /// Split off the next `len` bytes into a reader of their own.
+    pub(crate) fn sub(&mut self, len: usize) -> FlowParserResult<Reader<'a>> {
+        self.bytes(len).map(Reader::new)
+    }
+
+    pub(crate) fn skip(&mut self, len: usize) -> FlowParserResult<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub(crate) fn u8(&mut self) -> FlowParserResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> FlowParserResult<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub(crate) fn u32(&mut self) -> FlowParserResult<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn u64(&mut self) -> FlowParserResult<u64> {
+        Ok((u64::from(self.u32()?) << 32) | u64::from(self.u32()?))
+    }
+
+    pub(crate) fn ipv4(&mut self) -> FlowParserResult<Ipv4Addr> {
+        self.u32().map(Ipv4Addr::from)
+    }
+
+    pub(crate) fn ipv6(&mut self) -> FlowParserResult<Ipv6Addr> {
+        let mut octets = [0; 16];
+        octets.copy_from_slice(self.bytes(16)?);
+        Ok(Ipv6Addr::from(octets))
+    }
+
+    pub(crate) fn mac(&mut self) -> FlowParserResult<[u8; 6]> {
+        let mut octets = [0; 6];
+        octets.copy_from_slice(self.bytes(6)?);
+        Ok(octets)
+    }
+}
+
+/// Decode a big endian unsigned integer of up to eight bytes, as used by
+/// the reduced size encoding of IPFIX.
+pub(crate) fn unsigned(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .fold(0u64, |value, byte| (value << 8) | u64::from(*byte)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_big_endian_fields() {
+        let mut reader = Reader::new(&[0, 5, 0, 0, 0, 42, 192, 168, 0, 1, 7]);
+        assert_eq!(reader.u16().unwrap(), 5);
+        assert_eq!(reader.u32().unwrap(), 42);
+        assert_eq!(reader.ipv4().unwrap(), Ipv4Addr::new(192, 168, 0, 1));
+        assert_eq!(reader.remaining(), 1);
+        assert!(matches!(
+            reader.u16(),
+            Err(FlowParserError::Truncated {
+                needed: 2,
+                available: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn decodes_reduced_size_unsigned() {
+        assert_eq!(unsigned(&[1, 0]), Some(256));
+        assert_eq!(unsigned(&[0, 0, 0, 0, 0, 0, 0, 0, 1]), None);
+        assert_eq!(unsigned(&[]), None);
+    }
+}
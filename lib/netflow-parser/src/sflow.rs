@@ -0,0 +1,454 @@
+use crate::{
+    flow_message::{insert, insert_unsigned, FlowPacket, FlowProtocol, FlowRecord, FlowValue},
+    flow_parser::{FlowParserError, FlowParserResult},
+    reader::Reader,
+};
+use std::net::IpAddr;
+
+const FLOW_SAMPLE: u32 = 1;
+const COUNTER_SAMPLE: u32 = 2;
+const EXPANDED_FLOW_SAMPLE: u32 = 3;
+const EXPANDED_COUNTER_SAMPLE: u32 = 4;
+
+const RAW_PACKET_HEADER: u32 = 1;
+const ETHERNET_FRAME_DATA: u32 = 2;
+const IPV4_DATA: u32 = 3;
+const IPV6_DATA: u32 = 4;
+const EXTENDED_SWITCH_DATA: u32 = 1001;
+const EXTENDED_ROUTER_DATA: u32 = 1002;
+
+const GENERIC_INTERFACE_COUNTERS: u32 = 1;
+
+const HEADER_PROTOCOL_ETHERNET: u32 = 1;
+const HEADER_PROTOCOL_IPV4: u32 = 11;
+const HEADER_PROTOCOL_IPV6: u32 = 12;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+
+/// Parse an sFlow v5 datagram. Each flow or counter sample becomes a record,
+/// combining the data of all its records. Samples and records of formats
+/// that aren't supported are skipped.
+pub(crate) fn parse(data: &[u8]) -> FlowParserResult<FlowPacket> {
+    let mut reader = Reader::new(data);
+    let mut header = FlowRecord::new();
+    insert_unsigned(&mut header, "version", reader.u32()?);
+    insert(
+        &mut header,
+        "agent_address",
+        FlowValue::Ip(address(&mut reader)?),
+    );
+    insert_unsigned(&mut header, "sub_agent_id", reader.u32()?);
+    insert_unsigned(&mut header, "sequence_number", reader.u32()?);
+    insert_unsigned(&mut header, "sys_up_time", reader.u32()?);
+    let count = reader.u32()?;
+
+    let mut packet = FlowPacket::new(FlowProtocol::SflowV5, header);
+    for _ in 0..count {
+        let format = reader.u32()?;
+        let length = reader.u32()? as usize;
+        let mut sample = reader.sub(length)?;
+
+        // Formats of other enterprises than sFlow.org are skipped.
+        let record = match format {
+            FLOW_SAMPLE => flow_sample(&mut sample, false)?,
+            EXPANDED_FLOW_SAMPLE => flow_sample(&mut sample, true)?,
+            COUNTER_SAMPLE => counter_sample(&mut sample, false)?,
+            EXPANDED_COUNTER_SAMPLE => counter_sample(&mut sample, true)?,
+            _ => continue,
+        };
+        packet.records.push(record);
+    }
+    Ok(packet)
+}
+
+fn address(reader: &mut Reader<'_>) -> FlowParserResult<IpAddr> {
+    match reader.u32()? {
+        1 => reader.ipv4().map(IpAddr::V4),
+        2 => reader.ipv6().map(IpAddr::V6),
+        address_type => Err(FlowParserError::InvalidAddressType { address_type }),
+    }
+}
+
+/// Read the source ID, which the compact formats pack into a single word.
+fn source_id(
+    reader: &mut Reader<'_>,
+    expanded: bool,
+    record: &mut FlowRecord,
+) -> FlowParserResult<()> {
+    let (source_id_type, source_id_index) = if expanded {
+        (reader.u32()?, reader.u32()?)
+    } else {
+        let source_id = reader.u32()?;
+        (source_id >> 24, source_id & 0x00ff_ffff)
+    };
+    insert_unsigned(record, "source_id_type", source_id_type);
+    insert_unsigned(record, "source_id_index", source_id_index);
+    Ok(())
+}
+
+/// Read an interface, which the compact formats pack into a single word.
+fn interface(
+    reader: &mut Reader<'_>,
+    expanded: bool,
+    name: &str,
+    record: &mut FlowRecord,
+) -> FlowParserResult<()> {
+    let (format, value) = if expanded {
+        (reader.u32()?, reader.u32()?)
+    } else {
+        let interface = reader.u32()?;
+        (interface >> 30, interface & 0x3fff_ffff)
+    };
+    insert_unsigned(record, &format!("{}_format", name), format);
+    insert_unsigned(record, name, value);
+    Ok(())
+}
+
+fn flow_sample(sample: &mut Reader<'_>, expanded: bool) -> FlowParserResult<FlowRecord> {
+    let mut record = FlowRecord::new();
+    insert(&mut record, "sample_type", FlowValue::String("flow".into()));
+    insert_unsigned(&mut record, "sample_sequence_number", sample.u32()?);
+    source_id(sample, expanded, &mut record)?;
+    insert_unsigned(&mut record, "sampling_rate", sample.u32()?);
+    insert_unsigned(&mut record, "sample_pool", sample.u32()?);
+    insert_unsigned(&mut record, "drops", sample.u32()?);
+    interface(sample, expanded, "ingress_interface", &mut record)?;
+    interface(sample, expanded, "egress_interface", &mut record)?;
+
+    let count = sample.u32()?;
+    for _ in 0..count {
+        let format = sample.u32()?;
+        let length = sample.u32()? as usize;
+        let mut data = sample.sub(length)?;
+        match format {
+            RAW_PACKET_HEADER => raw_packet_header(&mut data, &mut record)?,
+            ETHERNET_FRAME_DATA => {
+                insert_unsigned(&mut record, "frame_length", data.u32()?);
+                // MAC addresses are padded to eight bytes.
+                let source = data.mac()?;
+                data.skip(2)?;
+                let destination = data.mac()?;
+                data.skip(2)?;
+                insert(&mut record, "source_mac_address", FlowValue::Mac(source));
+                insert(
+                    &mut record,
+                    "destination_mac_address",
+                    FlowValue::Mac(destination),
+                );
+                insert_unsigned(&mut record, "ethernet_type", data.u32()?);
+            }
+            IPV4_DATA => {
+                insert_unsigned(&mut record, "ip_total_length", data.u32()?);
+                insert_unsigned(&mut record, "protocol_identifier", data.u32()?);
+                let ip = |data: &mut Reader<'_>| data.ipv4().map(|ip| FlowValue::Ip(ip.into()));
+                insert(&mut record, "source_ipv4_address", ip(&mut data)?);
+                insert(&mut record, "destination_ipv4_address", ip(&mut data)?);
+                insert_unsigned(&mut record, "source_transport_port", data.u32()?);
+                insert_unsigned(&mut record, "destination_transport_port", data.u32()?);
+                insert_unsigned(&mut record, "tcp_control_bits", data.u32()?);
+                insert_unsigned(&mut record, "ip_class_of_service", data.u32()?);
+            }
+            IPV6_DATA => {
+                insert_unsigned(&mut record, "ip_total_length", data.u32()?);
+                insert_unsigned(&mut record, "protocol_identifier", data.u32()?);
+                let ip = |data: &mut Reader<'_>| data.ipv6().map(|ip| FlowValue::Ip(ip.into()));
+                insert(&mut record, "source_ipv6_address", ip(&mut data)?);
+                insert(&mut record, "destination_ipv6_address", ip(&mut data)?);
+                insert_unsigned(&mut record, "source_transport_port", data.u32()?);
+                insert_unsigned(&mut record, "destination_transport_port", data.u32()?);
+                insert_unsigned(&mut record, "tcp_control_bits", data.u32()?);
+                insert_unsigned(&mut record, "ip_class_of_service", data.u32()?);
+            }
+            EXTENDED_SWITCH_DATA => {
+                insert_unsigned(&mut record, "vlan_id", data.u32()?);
+                insert_unsigned(&mut record, "dot1q_priority", data.u32()?);
+                insert_unsigned(&mut record, "post_vlan_id", data.u32()?);
+                insert_unsigned(&mut record, "post_dot1q_priority", data.u32()?);
+            }
+            EXTENDED_ROUTER_DATA => {
+                let next_hop = address(&mut data)?;
+                let (next_hop_name, source_name, destination_name) = match next_hop {
+                    IpAddr::V4(_) => (
+                        "ip_next_hop_ipv4_address",
+                        "source_ipv4_prefix_length",
+                        "destination_ipv4_prefix_length",
+                    ),
+                    IpAddr::V6(_) => (
+                        "ip_next_hop_ipv6_address",
+                        "source_ipv6_prefix_length",
+                        "destination_ipv6_prefix_length",
+                    ),
+                };
+                insert(&mut record, next_hop_name, FlowValue::Ip(next_hop));
+                insert_unsigned(&mut record, source_name, data.u32()?);
+                insert_unsigned(&mut record, destination_name, data.u32()?);
+            }
+            _ => {}
+        }
+    }
+    Ok(record)
+}
+
+fn raw_packet_header(data: &mut Reader<'_>, record: &mut FlowRecord) -> FlowParserResult<()> {
+    let protocol = data.u32()?;
+    insert_unsigned(record, "header_protocol", protocol);
+    insert_unsigned(record, "frame_length", data.u32()?);
+    insert_unsigned(record, "stripped", data.u32()?);
+    let length = data.u32()? as usize;
+    let mut header = data.sub(length)?;
+
+    // The sampled header is usually cut off somewhere in the packet, so
+    // decoding stops quietly at its end.
+    let _ = match protocol {
+        HEADER_PROTOCOL_ETHERNET => ethernet_header(&mut header, record),
+        HEADER_PROTOCOL_IPV4 => ip_header(&mut header, ETHERTYPE_IPV4, record),
+        HEADER_PROTOCOL_IPV6 => ip_header(&mut header, ETHERTYPE_IPV6, record),
+        _ => Ok(()),
+    };
+    Ok(())
+}
+
+fn ethernet_header(header: &mut Reader<'_>, record: &mut FlowRecord) -> FlowParserResult<()> {
+    insert(
+        record,
+        "destination_mac_address",
+        FlowValue::Mac(header.mac()?),
+    );
+    insert(record, "source_mac_address", FlowValue::Mac(header.mac()?));
+    let mut ethernet_type = header.u16()?;
+    if ethernet_type == ETHERTYPE_VLAN {
+        insert_unsigned(record, "vlan_id", header.u16()? & 0x0fff);
+        ethernet_type = header.u16()?;
+    }
+    insert_unsigned(record, "ethernet_type", ethernet_type);
+    ip_header(header, ethernet_type, record)
+}
+
+fn ip_header(
+    header: &mut Reader<'_>,
+    ethernet_type: u16,
+    record: &mut FlowRecord,
+) -> FlowParserResult<()> {
+    let protocol = match ethernet_type {
+        ETHERTYPE_IPV4 => {
+            let version_ihl = header.u8()?;
+            let header_length = usize::from(version_ihl & 0x0f) * 4;
+            insert_unsigned(record, "ip_version", version_ihl >> 4);
+            insert_unsigned(record, "ip_class_of_service", header.u8()?);
+            insert_unsigned(record, "ip_total_length", header.u16()?);
+            header.skip(4)?;
+            insert_unsigned(record, "ip_ttl", header.u8()?);
+            let protocol = header.u8()?;
+            insert_unsigned(record, "protocol_identifier", protocol);
+            header.skip(2)?;
+            let ip = |header: &mut Reader<'_>| header.ipv4().map(|ip| FlowValue::Ip(ip.into()));
+            insert(record, "source_ipv4_address", ip(header)?);
+            insert(record, "destination_ipv4_address", ip(header)?);
+            header.skip(header_length.saturating_sub(20))?;
+            protocol
+        }
+        ETHERTYPE_IPV6 => {
+            let first = header.u32()?;
+            insert_unsigned(record, "ip_version", first >> 28);
+            insert_unsigned(record, "ip_class_of_service", (first >> 20) & 0xff);
+            insert_unsigned(record, "flow_label_ipv6", first & 0x000f_ffff);
+            insert_unsigned(record, "ip_payload_length", header.u16()?);
+            // Extension headers aren't followed.
+            let protocol = header.u8()?;
+            insert_unsigned(record, "protocol_identifier", protocol);
+            insert_unsigned(record, "ip_ttl", header.u8()?);
+            let ip = |header: &mut Reader<'_>| header.ipv6().map(|ip| FlowValue::Ip(ip.into()));
+            insert(record, "source_ipv6_address", ip(header)?);
+            insert(record, "destination_ipv6_address", ip(header)?);
+            protocol
+        }
+        _ => return Ok(()),
+    };
+
+    if protocol == PROTOCOL_TCP || protocol == PROTOCOL_UDP {
+        insert_unsigned(record, "source_transport_port", header.u16()?);
+        insert_unsigned(record, "destination_transport_port", header.u16()?);
+    }
+    if protocol == PROTOCOL_TCP {
+        header.skip(9)?;
+        insert_unsigned(record, "tcp_control_bits", header.u8()?);
+    }
+    Ok(())
+}
+
+fn counter_sample(sample: &mut Reader<'_>, expanded: bool) -> FlowParserResult<FlowRecord> {
+    let mut record = FlowRecord::new();
+    insert(
+        &mut record,
+        "sample_type",
+        FlowValue::String("counter".into()),
+    );
+    insert_unsigned(&mut record, "sample_sequence_number", sample.u32()?);
+    source_id(sample, expanded, &mut record)?;
+
+    let count = sample.u32()?;
+    for _ in 0..count {
+        let format = sample.u32()?;
+        let length = sample.u32()? as usize;
+        let mut data = sample.sub(length)?;
+        if format == GENERIC_INTERFACE_COUNTERS {
+            generic_interface_counters(&mut data, &mut record)?;
+        }
+    }
+    Ok(record)
+}
+
+fn generic_interface_counters(
+    data: &mut Reader<'_>,
+    record: &mut FlowRecord,
+) -> FlowParserResult<()> {
+    insert_unsigned(record, "if_index", data.u32()?);
+    insert_unsigned(record, "if_type", data.u32()?);
+    insert_unsigned(record, "if_speed", data.u64()?);
+    insert_unsigned(record, "if_direction", data.u32()?);
+    insert_unsigned(record, "if_status", data.u32()?);
+    insert_unsigned(record, "if_in_octets", data.u64()?);
+    for name in &[
+        "if_in_ucast_pkts",
+        "if_in_multicast_pkts",
+        "if_in_broadcast_pkts",
+        "if_in_discards",
+        "if_in_errors",
+        "if_in_unknown_protos",
+    ] {
+        insert_unsigned(record, name, data.u32()?);
+    }
+    insert_unsigned(record, "if_out_octets", data.u64()?);
+    for name in &[
+        "if_out_ucast_pkts",
+        "if_out_multicast_pkts",
+        "if_out_broadcast_pkts",
+        "if_out_discards",
+        "if_out_errors",
+        "if_promiscuous_mode",
+    ] {
+        insert_unsigned(record, name, data.u32()?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    fn datagram(samples: &[u8], count: u32) -> Vec<u8> {
+        let mut data = words(&[5, 1]);
+        data.extend(&[192, 0, 2, 10]);
+        data.extend(words(&[0, 17, 360_000, count]));
+        data.extend(samples);
+        data
+    }
+
+    fn tcp_packet_header() -> Vec<u8> {
+        let mut header = vec![
+            0, 1, 2, 3, 4, 5, // destination MAC
+            6, 7, 8, 9, 10, 11, // source MAC
+            0x81, 0x00, 0, 42, // VLAN 42
+            0x08, 0x00, // IPv4
+            0x45, 0x10, 0, 40, 0, 0, 0, 0, 64, 6, 0, 0, // IPv4 header
+            10, 0, 0, 1, 10, 0, 0, 2, // addresses
+            0x30, 0x39, 0, 80, // ports
+            0, 0, 0, 0, 0, 0, 0, 0, 0x50, 0x02, // TCP up to flags
+        ];
+        // Padded to four bytes.
+        header.extend(&[0, 0]);
+        header
+    }
+
+    #[test]
+    fn parses_flow_samples() {
+        let header = tcp_packet_header();
+        let mut raw = words(&[RAW_PACKET_HEADER, 16 + header.len() as u32]);
+        raw.extend(words(&[
+            HEADER_PROTOCOL_ETHERNET,
+            1514,
+            4,
+            header.len() as u32 - 2,
+        ]));
+        raw.extend(&header);
+
+        let mut sample = words(&[7, 0x0000_0003, 512, 1024, 0, 3, 4, 1]);
+        sample.extend(&raw);
+        let mut samples = words(&[FLOW_SAMPLE, sample.len() as u32]);
+        samples.extend(&sample);
+
+        let packet = parse(&datagram(&samples, 1)).unwrap();
+        assert_eq!(packet.protocol, FlowProtocol::SflowV5);
+        assert_eq!(
+            packet.header["agent_address"],
+            FlowValue::Ip(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10)))
+        );
+
+        assert_eq!(packet.records.len(), 1);
+        let record = &packet.records[0];
+        assert_eq!(record["sample_type"], FlowValue::String("flow".into()));
+        assert_eq!(record["sampling_rate"], FlowValue::Unsigned(512));
+        assert_eq!(record["source_id_index"], FlowValue::Unsigned(3));
+        assert_eq!(record["ingress_interface"], FlowValue::Unsigned(3));
+        assert_eq!(
+            record["source_mac_address"],
+            FlowValue::Mac([6, 7, 8, 9, 10, 11])
+        );
+        assert_eq!(record["vlan_id"], FlowValue::Unsigned(42));
+        assert_eq!(
+            record["destination_ipv4_address"],
+            FlowValue::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)))
+        );
+        assert_eq!(
+            record["destination_transport_port"],
+            FlowValue::Unsigned(80)
+        );
+        assert_eq!(record["tcp_control_bits"], FlowValue::Unsigned(0x02));
+    }
+
+    #[test]
+    fn parses_counter_samples_and_skips_unknown_formats() {
+        let mut counters = words(&[1, 6]);
+        counters.extend(&1_000_000_000u64.to_be_bytes());
+        counters.extend(words(&[1, 3]));
+        counters.extend(&123_456u64.to_be_bytes());
+        counters.extend(words(&[1, 2, 3, 4, 5, 6]));
+        counters.extend(&654_321u64.to_be_bytes());
+        counters.extend(words(&[7, 8, 9, 10, 11, 0]));
+
+        let mut sample = words(&[9, 0x0000_0001, 1, GENERIC_INTERFACE_COUNTERS]);
+        sample.extend(words(&[counters.len() as u32]));
+        sample.extend(&counters);
+
+        let mut samples = words(&[(4413 << 12) | 1, 4, 0]);
+        samples.extend(words(&[COUNTER_SAMPLE, sample.len() as u32]));
+        samples.extend(&sample);
+
+        let packet = parse(&datagram(&samples, 2)).unwrap();
+        assert_eq!(packet.records.len(), 1);
+        let record = &packet.records[0];
+        assert_eq!(record["sample_type"], FlowValue::String("counter".into()));
+        assert_eq!(record["if_speed"], FlowValue::Unsigned(1_000_000_000));
+        assert_eq!(record["if_in_octets"], FlowValue::Unsigned(123_456));
+        assert_eq!(record["if_out_errors"], FlowValue::Unsigned(11));
+    }
+
+    #[test]
+    fn rejects_invalid_agent_address() {
+        let data = words(&[5, 3, 0, 0]);
+        assert!(matches!(
+            parse(&data),
+            Err(FlowParserError::InvalidAddressType { address_type: 3 })
+        ));
+    }
+}
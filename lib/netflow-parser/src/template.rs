@@ -0,0 +1,589 @@
+use crate::{
+    flow_message::{insert, insert_unsigned, FlowPacket, FlowProtocol, FlowRecord, FlowValue},
+    flow_parser::{FlowParserError, FlowParserResult},
+    information_elements::{decode, lookup},
+    reader::{unsigned, Reader},
+};
+use chrono::{TimeZone, Utc};
+use lru::LruCache;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+const IPFIX_HEADER_LENGTH: usize = 16;
+
+const NETFLOW_V9_TEMPLATE_SET: u16 = 0;
+const NETFLOW_V9_OPTIONS_TEMPLATE_SET: u16 = 1;
+const IPFIX_TEMPLATE_SET: u16 = 2;
+const IPFIX_OPTIONS_TEMPLATE_SET: u16 = 3;
+/// Set IDs from this one on carry data records.
+const MIN_DATA_SET: u16 = 256;
+
+/// Marks a variable length field of an IPFIX template.
+const VARIABLE_LENGTH: u16 = 65535;
+
+/// Templates are scoped to the exporter, its observation domain (the source
+/// ID of NetFlow v9) and their template ID.
+type TemplateKey = (SocketAddr, u32, u16);
+
+/// The templates of all exporters. Exporters and their observation domains
+/// are chosen by whoever sends packets, so the number of templates is bounded
+/// by evicting the least recently used ones, and templates that were not sent
+/// again within their expiry are dropped.
+#[derive(Debug)]
+pub(crate) struct TemplateCache {
+    templates: LruCache<TemplateKey, (Template, Instant)>,
+    expiry: Option<Duration>,
+}
+
+impl Default for TemplateCache {
+    fn default() -> Self {
+        Self::new(crate::flow_parser::DEFAULT_MAX_TEMPLATES, None)
+    }
+}
+
+impl TemplateCache {
+    pub(crate) fn new(max_templates: usize, expiry: Option<Duration>) -> Self {
+        Self {
+            templates: LruCache::new(max_templates),
+            expiry,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    fn insert(&mut self, key: TemplateKey, template: Template) {
+        self.templates.put(key, (template, Instant::now()));
+    }
+
+    fn get(&mut self, key: &TemplateKey) -> Option<&Template> {
+        let expired = match (self.templates.peek(key), self.expiry) {
+            (Some((_, received)), Some(expiry)) => received.elapsed() >= expiry,
+            _ => false,
+        };
+        if expired {
+            self.templates.pop(key);
+            return None;
+        }
+        self.templates.get(key).map(|(template, _)| template)
+    }
+
+    fn remove(&mut self, key: &TemplateKey) {
+        self.templates.pop(key);
+    }
+
+    /// Removes all templates of the observation `domain` of `exporter`.
+    fn remove_domain(&mut self, exporter: SocketAddr, domain: u32) {
+        let keys = self
+            .templates
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|(e, d, _)| (*e, *d) == (exporter, domain))
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.templates.pop(&key);
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Template {
+    fields: Vec<TemplateField>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TemplateField {
+    id: u16,
+    enterprise: Option<u32>,
+    length: u16,
+    /// NetFlow v9 scope fields have their own type numbers.
+    netflow_v9_scope: bool,
+}
+
+impl Template {
+    /// The smallest possible length of a record, which tells trailing
+    /// padding apart from another record.
+    fn min_record_length(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| match field.length {
+                VARIABLE_LENGTH => 1,
+                length => usize::from(length),
+            })
+            .sum()
+    }
+
+    fn decode_record(&self, reader: &mut Reader<'_>) -> FlowParserResult<FlowRecord> {
+        let mut record = FlowRecord::new();
+        for field in &self.fields {
+            let length = match field.length {
+                VARIABLE_LENGTH => match reader.u8()? {
+                    255 => usize::from(reader.u16()?),
+                    length => usize::from(length),
+                },
+                length => usize::from(length),
+            };
+            let bytes = reader.bytes(length)?;
+
+            match (field.netflow_v9_scope, field.enterprise, lookup(field.id)) {
+                (true, _, _) => {
+                    let value = unsigned(bytes)
+                        .map(FlowValue::Unsigned)
+                        .unwrap_or_else(|| FlowValue::Bytes(bytes.to_vec()));
+                    insert(&mut record, &netflow_v9_scope_name(field.id), value);
+                }
+                (false, Some(enterprise), _) => insert(
+                    &mut record,
+                    &format!("enterprise_{}_{}", enterprise, field.id),
+                    FlowValue::Bytes(bytes.to_vec()),
+                ),
+                (false, None, Some((name, data_type))) => {
+                    insert(&mut record, name, decode(data_type, bytes))
+                }
+                (false, None, None) => insert(
+                    &mut record,
+                    &format!("field_{}", field.id),
+                    FlowValue::Bytes(bytes.to_vec()),
+                ),
+            }
+        }
+        Ok(record)
+    }
+}
+
+fn netflow_v9_scope_name(id: u16) -> String {
+    match id {
+        1 => "scope_system".to_owned(),
+        2 => "scope_interface".to_owned(),
+        3 => "scope_line_card".to_owned(),
+        4 => "scope_cache".to_owned(),
+        5 => "scope_template".to_owned(),
+        id => format!("scope_{}", id),
+    }
+}
+
+pub(crate) fn parse_netflow_v9(
+    cache: &mut TemplateCache,
+    exporter: SocketAddr,
+    data: &[u8],
+) -> FlowParserResult<FlowPacket> {
+    let mut reader = Reader::new(data);
+    let mut header = FlowRecord::new();
+    insert_unsigned(&mut header, "version", reader.u16()?);
+    // The record count of the header is unreliable, the sets are read
+    // until the end of the packet instead.
+    reader.skip(2)?;
+    insert_unsigned(&mut header, "sys_up_time", reader.u32()?);
+    insert(
+        &mut header,
+        "export_time",
+        FlowValue::DateTime(Utc.timestamp(i64::from(reader.u32()?), 0)),
+    );
+    insert_unsigned(&mut header, "sequence_number", reader.u32()?);
+    let source_id = reader.u32()?;
+    insert_unsigned(&mut header, "source_id", source_id);
+
+    let mut packet = FlowPacket::new(FlowProtocol::NetflowV9, header);
+    parse_sets(cache, exporter, source_id, reader, &mut packet)?;
+    Ok(packet)
+}
+
+pub(crate) fn parse_ipfix(
+    cache: &mut TemplateCache,
+    exporter: SocketAddr,
+    data: &[u8],
+) -> FlowParserResult<FlowPacket> {
+    let mut reader = Reader::new(data);
+    let mut header = FlowRecord::new();
+    insert_unsigned(&mut header, "version", reader.u16()?);
+    let length = usize::from(reader.u16()?);
+    if length < IPFIX_HEADER_LENGTH || length > data.len() {
+        return Err(FlowParserError::InvalidLength {
+            context: "IPFIX message",
+            length,
+        });
+    }
+    insert(
+        &mut header,
+        "export_time",
+        FlowValue::DateTime(Utc.timestamp(i64::from(reader.u32()?), 0)),
+    );
+    insert_unsigned(&mut header, "sequence_number", reader.u32()?);
+    let observation_domain_id = reader.u32()?;
+    insert_unsigned(&mut header, "observation_domain_id", observation_domain_id);
+
+    let mut packet = FlowPacket::new(FlowProtocol::Ipfix, header);
+    let sets = reader.sub(length - IPFIX_HEADER_LENGTH)?;
+    parse_sets(cache, exporter, observation_domain_id, sets, &mut packet)?;
+    Ok(packet)
+}
+
+/// Both protocols share the layout of sets, and differ in the set IDs of
+/// templates and the layout of options templates.
+fn parse_sets(
+    cache: &mut TemplateCache,
+    exporter: SocketAddr,
+    domain: u32,
+    mut reader: Reader<'_>,
+    packet: &mut FlowPacket,
+) -> FlowParserResult<()> {
+    let ipfix = packet.protocol == FlowProtocol::Ipfix;
+
+    while reader.remaining() >= 4 {
+        let set_id = reader.u16()?;
+        let length = usize::from(reader.u16()?);
+        if length < 4 {
+            return Err(FlowParserError::InvalidLength {
+                context: "set",
+                length,
+            });
+        }
+        let mut set = reader.sub(length - 4)?;
+
+        match set_id {
+            NETFLOW_V9_TEMPLATE_SET if !ipfix => {
+                while let Some((id, template)) = netflow_v9_template(&mut set)? {
+                    cache.insert((exporter, domain, id), template);
+                }
+            }
+            NETFLOW_V9_OPTIONS_TEMPLATE_SET if !ipfix => {
+                while let Some((id, template)) = netflow_v9_options_template(&mut set)? {
+                    cache.insert((exporter, domain, id), template);
+                }
+            }
+            IPFIX_TEMPLATE_SET | IPFIX_OPTIONS_TEMPLATE_SET if ipfix => {
+                let options = set_id == IPFIX_OPTIONS_TEMPLATE_SET;
+                while let Some((id, template)) = ipfix_template(&mut set, options)? {
+                    match template {
+                        Some(template) => cache.insert((exporter, domain, id), template),
+                        // Withdrawing the ID of the set withdraws all its templates.
+                        None if id == set_id => cache.remove_domain(exporter, domain),
+                        None => cache.remove(&(exporter, domain, id)),
+                    }
+                }
+            }
+            id if id >= MIN_DATA_SET => match cache.get(&(exporter, domain, id)) {
+                Some(template) => {
+                    let min_length = template.min_record_length();
+                    while min_length > 0 && set.remaining() >= min_length {
+                        packet.records.push(template.decode_record(&mut set)?);
+                    }
+                }
+                None => packet.missing_templates.push(id),
+            },
+            // Reserved set IDs are skipped.
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the next template of a NetFlow v9 template set, if the rest isn't
+/// padding.
+fn netflow_v9_template(set: &mut Reader<'_>) -> FlowParserResult<Option<(u16, Template)>> {
+    if set.remaining() < 4 {
+        return Ok(None);
+    }
+    let id = set.u16()?;
+    if id < MIN_DATA_SET {
+        return Ok(None);
+    }
+    let count = set.u16()?;
+    let fields = (0..count)
+        .map(|_| netflow_v9_field(set, false))
+        .collect::<FlowParserResult<_>>()?;
+    Ok(Some((id, Template { fields })))
+}
+
+fn netflow_v9_options_template(set: &mut Reader<'_>) -> FlowParserResult<Option<(u16, Template)>> {
+    if set.remaining() < 6 {
+        return Ok(None);
+    }
+    let id = set.u16()?;
+    if id < MIN_DATA_SET {
+        return Ok(None);
+    }
+    // Unlike the field count of templates, these are lengths in bytes.
+    let scope_count = set.u16()? / 4;
+    let option_count = set.u16()? / 4;
+    let mut fields = Vec::with_capacity(usize::from(scope_count + option_count));
+    for _ in 0..scope_count {
+        fields.push(netflow_v9_field(set, true)?);
+    }
+    for _ in 0..option_count {
+        fields.push(netflow_v9_field(set, false)?);
+    }
+    Ok(Some((id, Template { fields })))
+}
+
+fn netflow_v9_field(set: &mut Reader<'_>, scope: bool) -> FlowParserResult<TemplateField> {
+    Ok(TemplateField {
+        id: set.u16()?,
+        enterprise: None,
+        length: set.u16()?,
+        netflow_v9_scope: scope,
+    })
+}
+
+/// Read the next template of an IPFIX (options) template set, if the rest
+/// isn't padding. Withdrawn templates have no fields and are returned as
+/// `None`.
+#[allow(clippy::type_complexity)]
+fn ipfix_template(
+    set: &mut Reader<'_>,
+    options: bool,
+) -> FlowParserResult<Option<(u16, Option<Template>)>> {
+    if set.remaining() < 4 {
+        return Ok(None);
+    }
+    let id = set.u16()?;
+    if id < MIN_DATA_SET && id != IPFIX_TEMPLATE_SET && id != IPFIX_OPTIONS_TEMPLATE_SET {
+        return Ok(None);
+    }
+    let count = set.u16()?;
+    if count == 0 {
+        return Ok(Some((id, None)));
+    }
+    if options {
+        // Scope fields are regular information elements in IPFIX.
+        set.skip(2)?;
+    }
+
+    let mut fields = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let id = set.u16()?;
+        let length = set.u16()?;
+        let enterprise = if id & 0x8000 != 0 {
+            Some(set.u32()?)
+        } else {
+            None
+        };
+        fields.push(TemplateField {
+            id: id & 0x7fff,
+            enterprise,
+            length,
+            netflow_v9_scope: false,
+        });
+    }
+    Ok(Some((id, Some(Template { fields }))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn exporter() -> SocketAddr {
+        "192.0.2.1:2055".parse().unwrap()
+    }
+
+    fn netflow_v9_header() -> Vec<u8> {
+        vec![
+            0, 9, 0, 2, // version, count
+            0, 0, 0x03, 0xe8, // sys_up_time
+            0x61, 0x7f, 0xa8, 0x00, // unix_secs
+            0, 0, 0, 7, // sequence
+            0, 0, 0, 1, // source_id
+        ]
+    }
+
+    fn netflow_v9_template_set() -> Vec<u8> {
+        vec![
+            0, 0, 0, 20, // template set, length
+            1, 0, 0, 3, // template 256, 3 fields
+            0, 8, 0, 4, // source_ipv4_address
+            0, 2, 0, 4, // packet_delta_count
+            0, 82, 0, 4, // interface_name
+        ]
+    }
+
+    fn netflow_v9_data_set() -> Vec<u8> {
+        vec![
+            1, 0, 0, 32, // data set 256, length
+            10, 0, 0, 1, 0, 0, 0, 5, b'e', b't', b'h', b'0', // record
+            10, 0, 0, 2, 0, 0, 0, 6, b'e', b't', b'h', b'1', // record
+            0, 0, 0, 0, // padding
+        ]
+    }
+
+    #[test]
+    fn parses_netflow_v9_with_template() {
+        let mut cache = TemplateCache::default();
+        let mut data = netflow_v9_header();
+        data.extend(netflow_v9_template_set());
+        data.extend(netflow_v9_data_set());
+
+        let packet = parse_netflow_v9(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.protocol, FlowProtocol::NetflowV9);
+        assert_eq!(packet.header["source_id"], FlowValue::Unsigned(1));
+        assert!(packet.missing_templates.is_empty());
+        assert_eq!(packet.records.len(), 2);
+        assert_eq!(
+            packet.records[1]["source_ipv4_address"],
+            FlowValue::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)))
+        );
+        assert_eq!(
+            packet.records[1]["packet_delta_count"],
+            FlowValue::Unsigned(6)
+        );
+        assert_eq!(
+            packet.records[1]["interface_name"],
+            FlowValue::String("eth1".into())
+        );
+    }
+
+    #[test]
+    fn netflow_v9_templates_are_kept_per_exporter() {
+        let mut cache = TemplateCache::default();
+        let mut data = netflow_v9_header();
+        data.extend(netflow_v9_data_set());
+
+        let packet = parse_netflow_v9(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.missing_templates, vec![256]);
+        assert!(packet.records.is_empty());
+
+        let mut templates = netflow_v9_header();
+        templates.extend(netflow_v9_template_set());
+        parse_netflow_v9(&mut cache, exporter(), &templates).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let packet = parse_netflow_v9(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.records.len(), 2);
+
+        let other = "192.0.2.2:2055".parse().unwrap();
+        let packet = parse_netflow_v9(&mut cache, other, &data).unwrap();
+        assert_eq!(packet.missing_templates, vec![256]);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_templates() {
+        let mut cache = TemplateCache::new(1, None);
+        let mut templates = netflow_v9_header();
+        templates.extend(netflow_v9_template_set());
+        let mut data = netflow_v9_header();
+        data.extend(netflow_v9_data_set());
+
+        let other = "192.0.2.2:2055".parse().unwrap();
+        parse_netflow_v9(&mut cache, exporter(), &templates).unwrap();
+        parse_netflow_v9(&mut cache, other, &templates).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let packet = parse_netflow_v9(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.missing_templates, vec![256]);
+        let packet = parse_netflow_v9(&mut cache, other, &data).unwrap();
+        assert_eq!(packet.records.len(), 2);
+    }
+
+    #[test]
+    fn expires_templates() {
+        let mut cache = TemplateCache::new(10, Some(Duration::from_secs(0)));
+        let mut templates = netflow_v9_header();
+        templates.extend(netflow_v9_template_set());
+        parse_netflow_v9(&mut cache, exporter(), &templates).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let mut data = netflow_v9_header();
+        data.extend(netflow_v9_data_set());
+        let packet = parse_netflow_v9(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.missing_templates, vec![256]);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn parses_netflow_v9_options() {
+        let mut cache = TemplateCache::default();
+        let mut data = netflow_v9_header();
+        data.extend(vec![
+            0, 1, 0, 20, // options template set, length
+            1, 1, 0, 4, 0, 4, // template 257, scope length, options length
+            0, 2, 0, 2, // scope_interface
+            0, 34, 0, 4, // sampling_interval
+            0, 0, // padding
+            1, 1, 0, 10, // data set 257, length
+            0, 3, 0, 0, 0, 100, // record
+        ]);
+
+        let packet = parse_netflow_v9(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.records.len(), 1);
+        assert_eq!(packet.records[0]["scope_interface"], FlowValue::Unsigned(3));
+        assert_eq!(
+            packet.records[0]["sampling_interval"],
+            FlowValue::Unsigned(100)
+        );
+    }
+
+    fn ipfix_message(sets: &[u8]) -> Vec<u8> {
+        let mut data = vec![0, 10];
+        data.extend(&((IPFIX_HEADER_LENGTH + sets.len()) as u16).to_be_bytes());
+        data.extend(&[
+            0x61, 0x7f, 0xa8, 0x00, // export_time
+            0, 0, 0, 3, // sequence
+            0, 0, 0, 9, // observation_domain_id
+        ]);
+        data.extend(sets);
+        data
+    }
+
+    #[test]
+    fn parses_ipfix_variable_length_and_enterprise_fields() {
+        let mut cache = TemplateCache::default();
+        let data = ipfix_message(&[
+            0, 2, 0, 24, // template set, length
+            1, 0, 0, 3, // template 256, 3 fields
+            0, 152, 0, 8, // flow_start_milliseconds
+            0, 96, 0xff, 0xff, // application_name, variable length
+            0x80, 1, 0, 2, 0, 0, 0x71, 0x48, // enterprise 29000 field 1
+            1, 0, 0, 18, // data set 256, length
+            0, 0, 0x01, 0x7c, 0xda, 0x5d, 0x44, 0x00, // flow_start_milliseconds
+            3, b'd', b'n', b's', // application_name
+            0, 42, // enterprise field
+        ]);
+
+        let packet = parse_ipfix(&mut cache, exporter(), &data).unwrap();
+        assert_eq!(packet.protocol, FlowProtocol::Ipfix);
+        assert_eq!(
+            packet.header["observation_domain_id"],
+            FlowValue::Unsigned(9)
+        );
+        assert_eq!(packet.records.len(), 1);
+        let record = &packet.records[0];
+        assert_eq!(
+            record["flow_start_milliseconds"],
+            FlowValue::DateTime(Utc.timestamp_millis(0x017c_da5d_4400))
+        );
+        assert_eq!(record["application_name"], FlowValue::String("dns".into()));
+        assert_eq!(record["enterprise_29000_1"], FlowValue::Bytes(vec![0, 42]));
+    }
+
+    #[test]
+    fn ipfix_templates_can_be_withdrawn() {
+        let mut cache = TemplateCache::default();
+        let template = ipfix_message(&[
+            0, 2, 0, 12, // template set, length
+            1, 0, 0, 1, // template 256, 1 field
+            0, 4, 0, 1, // protocol_identifier
+        ]);
+        parse_ipfix(&mut cache, exporter(), &template).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let withdrawal = ipfix_message(&[
+            0, 2, 0, 8, // template set, length
+            1, 0, 0, 0, // template 256 withdrawn
+        ]);
+        parse_ipfix(&mut cache, exporter(), &withdrawal).unwrap();
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_ipfix_length() {
+        let mut cache = TemplateCache::default();
+        let mut data = ipfix_message(&[]);
+        data[3] = 200;
+        assert!(matches!(
+            parse_ipfix(&mut cache, exporter(), &data),
+            Err(FlowParserError::InvalidLength { .. })
+        ));
+    }
+}
@@ -5,13 +5,11 @@ use crate::ByteSizeOf;
 use getset::{Getters, Setters};
 use serde::{Deserialize, Serialize};
 use shared::EventDataEq;
-use std::sync::Arc;
+use std::{cmp::Ordering, sync::Arc};
 
 /// The top-level metadata structure contained by both `struct Metric`
 /// and `struct LogEvent` types.
-#[derive(
-    Clone, Debug, Default, Deserialize, Getters, PartialEq, PartialOrd, Serialize, Setters,
-)]
+#[derive(Clone, Debug, Default, Deserialize, Getters, Serialize, Setters)]
 pub struct EventMetadata {
     /// Used to store the datadog API from sources to sinks
     #[getset(get = "pub", set = "pub")]
@@ -19,6 +17,28 @@ pub struct EventMetadata {
     datadog_api_key: Option<Arc<str>>,
     #[serde(default, skip)]
     finalizers: EventFinalizers,
+    /// The component output the event was received from, set by the
+    /// topology when the event is sent to a transform or a sink.
+    #[getset(get = "pub", set = "pub")]
+    #[serde(default, skip)]
+    upstream_id: Option<Arc<str>>,
+}
+
+// The upstream component only describes how an event was routed, so it is
+// left out of comparisons.
+impl PartialEq for EventMetadata {
+    fn eq(&self, other: &Self) -> bool {
+        self.datadog_api_key == other.datadog_api_key && self.finalizers == other.finalizers
+    }
+}
+
+impl PartialOrd for EventMetadata {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.datadog_api_key.partial_cmp(&other.datadog_api_key) {
+            Some(Ordering::Equal) => self.finalizers.partial_cmp(&other.finalizers),
+            ordering => ordering,
+        }
+    }
 }
 
 impl ByteSizeOf for EventMetadata {
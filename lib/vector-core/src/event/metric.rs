@@ -852,6 +852,19 @@ impl MetricValue {
                 *sum += sum2;
                 true
             }
+            (
+                Self::Sketch {
+                    sketch: MetricSketch::AgentDDSketch(ref mut ddsketch),
+                },
+                Self::Sketch {
+                    sketch: MetricSketch::AgentDDSketch(ddsketch2),
+                },
+            ) => {
+                if !ddsketch2.is_empty() {
+                    ddsketch.merge(ddsketch2.clone());
+                }
+                true
+            }
 
             _ => false,
         }
@@ -1240,6 +1253,45 @@ mod test {
         assert_eq!(dist, expected);
     }
 
+    #[test]
+    fn merge_sketches() {
+        let sketch = |values: &[f64]| {
+            let mut sketch = AgentDDSketch::with_agent_defaults();
+            sketch.insert_many(values);
+            sketch
+        };
+        let low = (1..=50).map(f64::from).collect::<Vec<_>>();
+        let high = (51..=100).map(f64::from).collect::<Vec<_>>();
+        let all = (1..=100).map(f64::from).collect::<Vec<_>>();
+
+        let mut value = MetricValue::Sketch {
+            sketch: MetricSketch::AgentDDSketch(sketch(&low)),
+        };
+        let delta = MetricValue::Sketch {
+            sketch: MetricSketch::AgentDDSketch(sketch(&high)),
+        };
+
+        assert!(value.add(&delta));
+
+        let merged = match value {
+            MetricValue::Sketch {
+                sketch: MetricSketch::AgentDDSketch(sketch),
+            } => sketch,
+            value => panic!("unexpected value: {:?}", value),
+        };
+        let expected = sketch(&all);
+
+        assert_eq!(merged.count(), 100);
+        assert_eq!(merged.sum(), Some(5050.0));
+        assert_eq!(merged.min(), Some(1.0));
+        assert_eq!(merged.max(), Some(100.0));
+        for q in &[0.5, 0.9, 0.99] {
+            let quantile = merged.quantile(*q).unwrap();
+            assert_eq!(Some(quantile), expected.quantile(*q));
+            assert!((quantile - q * 100.0).abs() <= 2.0, "q{}: {}", q, quantile);
+        }
+    }
+
     #[test]
     fn subtract_counters() {
         // Make sure a newer/higher value counter can subtract an older/lesser value counter:
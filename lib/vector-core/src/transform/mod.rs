@@ -15,6 +15,7 @@ pub enum Transform {
     Function(Box<dyn FunctionTransform>),
     FallibleFunction(Box<dyn FallibleFunctionTransform>),
     Task(Box<dyn TaskTransform>),
+    FallibleTask(Box<dyn FallibleTaskTransform>),
 }
 
 impl Transform {
@@ -131,6 +132,28 @@ impl Transform {
             }
         }
     }
+
+    /// Create a new fallible task transform.
+    ///
+    /// These are similar to `TaskTransform`, but with a second output for events that could not
+    /// be processed.
+    pub fn fallible_task(v: impl FallibleTaskTransform + 'static) -> Self {
+        Transform::FallibleTask(Box::new(v))
+    }
+
+    /// Transmute the inner transform into a fallible task transform.
+    ///
+    /// # Panics
+    ///
+    /// If the transform is not a [`FallibleTaskTransform`] this will panic.
+    pub fn into_fallible_task(self) -> Box<dyn FallibleTaskTransform> {
+        match self {
+            Transform::FallibleTask(t) => t,
+            _ => panic!(
+                "Called `Transform::into_fallible_task` on something that was not a fallible task variant."
+            ),
+        }
+    }
 }
 
 /// Transforms that are simple, and don't require attention to coordination.
@@ -182,3 +205,16 @@ pub trait TaskTransform: Send {
     where
         Self: 'static;
 }
+
+/// Similar to `TaskTransform`, but with a second output for events that could not be processed.
+///
+/// Events yielded as `Ok` are sent to the default output, while events yielded as `Err` are sent
+/// to the named output declared by the transform configuration.
+pub trait FallibleTaskTransform: Send {
+    fn transform(
+        self: Box<Self>,
+        task: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Event, Event>> + Send>>
+    where
+        Self: 'static;
+}
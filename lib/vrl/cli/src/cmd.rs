@@ -4,11 +4,12 @@ use super::Error;
 use shared::TimeZone;
 use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::iter::IntoIterator;
 use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
-use vrl::{diagnostic::Formatter, state, Program, Runtime, Target, Value};
+use vrl::{diagnostic::Formatter, state, Profiler, Program, Runtime, Target, Value};
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "VRL", about = "Vector Remap Language CLI")]
@@ -34,6 +35,39 @@ pub struct Opts {
     /// The timezone used to parse dates.
     #[structopt(short = "tz", long)]
     timezone: Option<String>,
+
+    /// Profile the program while processing the event object(s), and print the execution count
+    /// and cumulative time of each root expression and function call once all events are
+    /// processed. In the REPL, the profile of each entered program is printed after its result.
+    #[structopt(long)]
+    profile: bool,
+
+    /// The format of the profile. Either "annotated" (an annotated listing of the program) or
+    /// "folded" (folded stacks, as used by flamegraph tooling).
+    #[structopt(long, default_value = "annotated", possible_values = &["annotated", "folded"])]
+    profile_format: ProfileFormat,
+
+    /// The file to write the profile to. Defaults to stderr.
+    #[structopt(long, parse(from_os_str))]
+    profile_output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProfileFormat {
+    Annotated,
+    Folded,
+}
+
+impl FromStr for ProfileFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "annotated" => Ok(ProfileFormat::Annotated),
+            "folded" => Ok(ProfileFormat::Folded),
+            _ => Err(Error::Parse(format!("unknown profile format: {}", s))),
+        }
+    }
 }
 
 impl Opts {
@@ -74,6 +108,20 @@ impl Opts {
     fn should_open_repl(&self) -> bool {
         self.program.is_none() && self.program_file.is_none()
     }
+
+    fn write_profile(&self, profiler: &Profiler, source: &str) -> Result<(), Error> {
+        let profile = match self.profile_format {
+            ProfileFormat::Annotated => profiler.render_annotated(source),
+            ProfileFormat::Folded => profiler.render_folded(source),
+        };
+
+        match self.profile_output.as_ref() {
+            Some(path) => File::create(path)?.write_all(profile.as_bytes())?,
+            None => io::stderr().write_all(profile.as_bytes())?,
+        }
+
+        Ok(())
+    }
 }
 
 pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
@@ -101,7 +149,15 @@ fn run(opts: &Opts) -> Result<(), Error> {
             default_objects()
         };
 
-        repl(repl_objects, &tz)
+        let write_profile =
+            |profiler: &Profiler, source: &str| opts.write_profile(profiler, source);
+        let profile = if opts.profile {
+            Some(&write_profile as &dyn Fn(&Profiler, &str) -> Result<(), Error>)
+        } else {
+            None
+        };
+
+        repl(repl_objects, &tz, profile)
     } else {
         let objects = opts.read_into_objects()?;
         let source = opts.read_program()?;
@@ -109,8 +165,10 @@ fn run(opts: &Opts) -> Result<(), Error> {
             Error::Parse(Formatter::new(&source, diagnostics).colored().to_string())
         })?;
 
+        let mut profiler = opts.profile.then(Profiler::new);
+
         for mut object in objects {
-            let result = execute(&mut object, &program, &tz).map(|v| {
+            let result = execute(&mut object, &program, &tz, profiler.as_mut()).map(|v| {
                 if opts.print_object {
                     object.to_string()
                 } else {
@@ -126,18 +184,30 @@ fn run(opts: &Opts) -> Result<(), Error> {
             }
         }
 
+        if let Some(profiler) = profiler {
+            opts.write_profile(&profiler, &source)?;
+        }
+
         Ok(())
     }
 }
 
 #[cfg(feature = "repl")]
-fn repl(objects: Vec<Value>, timezone: &TimeZone) -> Result<(), Error> {
-    repl::run(objects, timezone);
+fn repl(
+    objects: Vec<Value>,
+    timezone: &TimeZone,
+    profile: Option<&dyn Fn(&Profiler, &str) -> Result<(), Error>>,
+) -> Result<(), Error> {
+    repl::run(objects, timezone, profile);
     Ok(())
 }
 
 #[cfg(not(feature = "repl"))]
-fn repl(_objects: Vec<Value>, _timezone: &TimeZone) -> Result<(), Error> {
+fn repl(
+    _objects: Vec<Value>,
+    _timezone: &TimeZone,
+    _profile: Option<&dyn Fn(&Profiler, &str) -> Result<(), Error>>,
+) -> Result<(), Error> {
     Err(Error::ReplFeature)
 }
 
@@ -145,13 +215,16 @@ fn execute(
     object: &mut impl Target,
     program: &Program,
     timezone: &TimeZone,
+    profiler: Option<&mut Profiler>,
 ) -> Result<Value, Error> {
     let state = state::Runtime::default();
     let mut runtime = Runtime::new(state);
 
-    runtime
-        .resolve(object, program, timezone)
-        .map_err(Error::Runtime)
+    match profiler {
+        Some(profiler) => runtime.resolve_with_profiler(object, program, timezone, profiler),
+        None => runtime.resolve(object, program, timezone),
+    }
+    .map_err(Error::Runtime)
 }
 
 fn serde_to_vrl(value: serde_json::Value) -> Value {
@@ -1,3 +1,4 @@
+use super::Error;
 use indoc::indoc;
 use lazy_static::lazy_static;
 use prettytable::{format, Cell, Row, Table};
@@ -10,7 +11,7 @@ use rustyline::validate::{self, ValidationResult, Validator};
 use rustyline::{Context, Editor, Helper};
 use shared::TimeZone;
 use std::borrow::Cow::{self, Borrowed, Owned};
-use vrl::{diagnostic::Formatter, state, value, Runtime, Target, Value};
+use vrl::{diagnostic::Formatter, state, value, Profiler, Runtime, Target, Value};
 
 // Create a list of all possible error values for potential docs lookup
 lazy_static! {
@@ -38,13 +39,18 @@ const RESERVED_TERMS: &[&str] = &[
     "help docs",
 ];
 
-pub(crate) fn run(mut objects: Vec<Value>, timezone: &TimeZone) {
+pub(crate) fn run(
+    mut objects: Vec<Value>,
+    timezone: &TimeZone,
+    profile: Option<&dyn Fn(&Profiler, &str) -> Result<(), Error>>,
+) {
     let mut index = 0;
     let func_docs_regex = Regex::new(r"^help\sdocs\s(\w{1,})$").unwrap();
     let error_docs_regex = Regex::new(r"^help\serror\s(\w{1,})$").unwrap();
 
     let mut compiler_state = state::Compiler::default();
     let mut rt = Runtime::new(state::Runtime::default());
+    let mut profiler = profile.map(|_| Profiler::new());
     let mut rl = Editor::<Repl>::new();
     rl.set_helper(Some(Repl::new()));
 
@@ -103,6 +109,7 @@ pub(crate) fn run(mut objects: Vec<Value>, timezone: &TimeZone) {
                     command,
                     &mut compiler_state,
                     timezone,
+                    profiler.as_mut(),
                 );
 
                 let string = match result {
@@ -114,6 +121,18 @@ pub(crate) fn run(mut objects: Vec<Value>, timezone: &TimeZone) {
                 {
                     println!("{}\n", string);
                 }
+
+                if let (Some(profile), Some(profiler)) = (profile, profiler.as_mut()) {
+                    if !profiler.is_empty() {
+                        if let Err(err) = profile(profiler, command) {
+                            #[allow(clippy::print_stdout)]
+                            {
+                                println!("unable to write profile: {}", err);
+                            }
+                        }
+                    }
+                    profiler.clear();
+                }
             }
             Err(ReadlineError::Interrupted) => break,
             Err(ReadlineError::Eof) => break,
@@ -134,6 +153,7 @@ fn resolve(
     program: &str,
     state: &mut state::Compiler,
     timezone: &TimeZone,
+    profiler: Option<&mut Profiler>,
 ) -> Result<Value, String> {
     let mut empty = value!({});
     let object = match object {
@@ -146,9 +166,11 @@ fn resolve(
         Err(diagnostics) => return Err(Formatter::new(program, diagnostics).colored().to_string()),
     };
 
-    runtime
-        .resolve(object, &program, timezone)
-        .map_err(|err| err.to_string())
+    match profiler {
+        Some(profiler) => runtime.resolve_with_profiler(object, &program, timezone, profiler),
+        None => runtime.resolve(object, &program, timezone),
+    }
+    .map_err(|err| err.to_string())
 }
 
 struct Repl {
@@ -252,7 +274,14 @@ impl Validator for Repl {
         let mut rt = Runtime::new(state::Runtime::default());
         let target: Option<&mut Value> = None;
 
-        let result = match resolve(target, &mut rt, ctx.input(), &mut compiler_state, &timezone) {
+        let result = match resolve(
+            target,
+            &mut rt,
+            ctx.input(),
+            &mut compiler_state,
+            &timezone,
+            None,
+        ) {
             Err(error) => {
                 // TODO: Ideally we'd used typed errors for this, but
                 // that requires some more work to the VRL compiler.
@@ -28,11 +28,11 @@ impl<'a> Compiler<'a> {
     }
 
     pub(super) fn compile(mut self, ast: parser::Program) -> Result<Program, Errors> {
-        let expressions = self
+        let (roots, expressions) = self
             .compile_root_exprs(ast)
             .into_iter()
-            .map(|expr| Box::new(expr) as _)
-            .collect();
+            .map(|(span, expr)| ((span, expr.as_str()), Box::new(expr) as _))
+            .unzip();
 
         if !self.errors.is_empty() {
             return Err(self.errors);
@@ -40,6 +40,7 @@ impl<'a> Compiler<'a> {
 
         Ok(Program {
             expressions,
+            roots,
             fallible: self.fallible,
             abortable: self.abortable,
         })
@@ -48,7 +49,7 @@ impl<'a> Compiler<'a> {
     fn compile_root_exprs(
         &mut self,
         nodes: impl IntoIterator<Item = Node<ast::RootExpr>>,
-    ) -> Vec<Expr> {
+    ) -> Vec<(diagnostic::Span, Expr)> {
         use ast::RootExpr::*;
 
         nodes
@@ -65,7 +66,7 @@ impl<'a> Compiler<'a> {
                             self.errors.push(Box::new(err));
                         }
 
-                        Some(expr)
+                        Some((span, expr))
                     }
                     Error(err) => {
                         self.handle_parser_error(err);
@@ -1,10 +1,11 @@
-use crate::{state::Runtime, Target};
+use crate::{state::Runtime, Profiler, Target};
 use shared::TimeZone;
 
 pub struct Context<'a> {
     target: &'a mut dyn Target,
     state: &'a mut Runtime,
     timezone: &'a TimeZone,
+    profiler: Option<&'a mut Profiler>,
 }
 
 impl<'a> Context<'a> {
@@ -14,9 +15,17 @@ impl<'a> Context<'a> {
             target,
             state,
             timezone,
+            profiler: None,
         }
     }
 
+    /// Attach a [`Profiler`] to the context, to record the execution of the
+    /// resolved expressions.
+    pub fn with_profiler(mut self, profiler: &'a mut Profiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
     /// Get a reference to the [`Target`].
     pub fn target(&self) -> &dyn Target {
         self.target
@@ -41,4 +50,9 @@ impl<'a> Context<'a> {
     pub fn timezone(&self) -> &TimeZone {
         self.timezone
     }
+
+    /// Get a mutable reference to the [`Profiler`], if profiling is enabled.
+    pub fn profiler_mut(&mut self) -> Option<&mut Profiler> {
+        self.profiler.as_deref_mut()
+    }
 }
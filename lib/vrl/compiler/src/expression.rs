@@ -101,7 +101,7 @@ pub enum Expr {
 }
 
 impl Expr {
-    pub fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &'static str {
         use container::Variant::*;
         use Expr::*;
 
@@ -212,7 +212,17 @@ impl FunctionCall {
 
 impl Expression for FunctionCall {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
-        self.expr.resolve(ctx).map_err(|err| match err {
+        if let Some(profiler) = ctx.profiler_mut() {
+            profiler.enter_function_call(self.span, self.ident);
+        }
+
+        let resolved = self.expr.resolve(ctx);
+
+        if let Some(profiler) = ctx.profiler_mut() {
+            profiler.exit();
+        }
+
+        resolved.map_err(|err| match err {
             ExpressionError::Abort { .. } => {
                 panic!("abort errors must only be defined by `abort` statement")
             }
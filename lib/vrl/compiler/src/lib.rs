@@ -6,6 +6,7 @@ mod test_util;
 
 pub mod expression;
 pub mod function;
+pub mod profiler;
 pub mod state;
 pub mod type_def;
 pub mod value;
@@ -16,6 +17,7 @@ pub(crate) use state::Compiler as State;
 pub use context::Context;
 pub use expression::{Expression, ExpressionError, Resolved};
 pub use function::{Function, Parameter};
+pub use profiler::Profiler;
 pub use program::Program;
 pub use target::Target;
 pub use type_def::TypeDef;
@@ -0,0 +1,382 @@
+use crate::Span;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Records execution counts and timings of a running VRL program.
+///
+/// The profiler is attached to a [`Context`](crate::Context) by the runtime,
+/// after which root expressions and function calls report when they are
+/// entered and exited. The collected data spans every resolved program until
+/// [`Profiler::clear`] is called, and can be rendered as an annotated source
+/// listing, or as a "folded stacks" file as consumed by flamegraph tooling.
+///
+/// Only root expressions and function calls are tracked. Time spent in any
+/// other nested expression, such as the operands of an arithmetic expression
+/// or the statements of an `if` block, is attributed to the closest enclosing
+/// tracked frame. This keeps the overhead of profiling proportional to the
+/// number of function calls, rather than to the size of the program.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    /// The frames currently being resolved, outermost first.
+    frames: Vec<Frame>,
+
+    /// Aggregated statistics, keyed by the span of the profiled expression.
+    stats: BTreeMap<Span, Stats>,
+
+    /// Every unique stack of frames seen so far. A stack is identified by its
+    /// index, and refers to the stack it was entered from through `parent`.
+    stacks: Vec<Stack>,
+
+    /// Look up the identifier of a stack by its parent stack and the span of
+    /// its innermost frame.
+    stack_ids: HashMap<(Option<usize>, Span), usize>,
+}
+
+#[derive(Debug)]
+struct Frame {
+    span: Span,
+    stack: usize,
+    start: Instant,
+
+    /// Time spent in nested frames, used to calculate the exclusive time of
+    /// this frame.
+    children: Duration,
+}
+
+#[derive(Debug)]
+struct Stack {
+    parent: Option<usize>,
+    span: Span,
+
+    /// Exclusive ("self") time spent in the innermost frame of this stack.
+    duration: Duration,
+}
+
+/// The statistics collected for a single profiled expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// The kind of expression, or the function identifier for function calls.
+    pub name: &'static str,
+
+    /// Whether the expression is a function call.
+    pub function_call: bool,
+
+    /// The number of times the expression was resolved.
+    pub count: u64,
+
+    /// The cumulative time spent resolving the expression, including the time
+    /// spent in any nested profiled expressions.
+    pub total: Duration,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if no expressions have been profiled yet.
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+
+    /// Discard all collected statistics.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.stats.clear();
+        self.stacks.clear();
+        self.stack_ids.clear();
+    }
+
+    /// Iterate over the collected statistics, ordered by their location in
+    /// the source.
+    pub fn stats(&self) -> impl Iterator<Item = (Span, &Stats)> {
+        self.stats.iter().map(|(span, stats)| (*span, stats))
+    }
+
+    /// Start tracking the resolution of a root expression.
+    pub fn enter_expression(&mut self, span: Span, name: &'static str) {
+        self.enter(span, name, false)
+    }
+
+    /// Start tracking the resolution of a function call.
+    pub fn enter_function_call(&mut self, span: Span, ident: &'static str) {
+        self.enter(span, ident, true)
+    }
+
+    /// Stop tracking the most recently entered expression.
+    pub fn exit(&mut self) {
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let elapsed = frame.start.elapsed();
+
+        if let Some(parent) = self.frames.last_mut() {
+            parent.children += elapsed;
+        }
+
+        if let Some(stats) = self.stats.get_mut(&frame.span) {
+            stats.count += 1;
+            stats.total += elapsed;
+        }
+
+        self.stacks[frame.stack].duration += elapsed.saturating_sub(frame.children);
+    }
+
+    fn enter(&mut self, span: Span, name: &'static str, function_call: bool) {
+        self.stats.entry(span).or_insert(Stats {
+            name,
+            function_call,
+            count: 0,
+            total: Duration::default(),
+        });
+
+        let parent = self.frames.last().map(|frame| frame.stack);
+        let stacks = &mut self.stacks;
+        let stack = *self.stack_ids.entry((parent, span)).or_insert_with(|| {
+            stacks.push(Stack {
+                parent,
+                span,
+                duration: Duration::default(),
+            });
+            stacks.len() - 1
+        });
+
+        self.frames.push(Frame {
+            span,
+            stack,
+            start: Instant::now(),
+            children: Duration::default(),
+        });
+    }
+
+    /// Render the collected statistics as an annotated listing of the given
+    /// program source.
+    ///
+    /// Each line that started a root expression is prefixed with the number
+    /// of times it was resolved and the cumulative time spent. Function calls
+    /// are listed below the line in which they start.
+    pub fn render_annotated(&self, source: &str) -> String {
+        let lines = LineIndex::new(source);
+        let mut roots = BTreeMap::<usize, (u64, Duration)>::new();
+        let mut calls = BTreeMap::<usize, Vec<(usize, &Stats)>>::new();
+
+        for (span, stats) in &self.stats {
+            let (line, column) = lines.position(span.start());
+
+            if stats.function_call {
+                calls.entry(line).or_default().push((column, stats));
+            } else {
+                let (count, total) = roots.entry(line).or_default();
+                *count += stats.count;
+                *total += stats.total;
+            }
+        }
+
+        let width = source.lines().count().to_string().len();
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "{:>10} {:>12}  {:>width$} | source",
+            "count",
+            "total",
+            "",
+            width = width
+        );
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+
+            let (count, total) = match roots.get(&line) {
+                Some((count, total)) => (count.to_string(), format_duration(*total)),
+                None => (String::new(), String::new()),
+            };
+
+            let _ = writeln!(
+                output,
+                "{:>10} {:>12}  {:>width$} | {}",
+                count,
+                total,
+                line,
+                text,
+                width = width
+            );
+
+            for (column, stats) in calls.get(&line).into_iter().flatten() {
+                let _ = writeln!(
+                    output,
+                    "{:>10} {:>12}  {:>width$} | {:indent$}└ {}",
+                    stats.count,
+                    format_duration(stats.total),
+                    "",
+                    "",
+                    stats.name,
+                    width = width,
+                    indent = column - 1,
+                );
+            }
+        }
+
+        output
+    }
+
+    /// Render the collected statistics in the "folded stacks" format.
+    ///
+    /// Each line contains a semicolon-separated stack of frames, followed by
+    /// the exclusive time spent in the innermost frame in microseconds. The
+    /// output can be passed to tools such as `flamegraph.pl` or `inferno`.
+    pub fn render_folded(&self, source: &str) -> String {
+        let lines = LineIndex::new(source);
+
+        let mut folded = self
+            .stacks
+            .iter()
+            .map(|stack| {
+                let mut spans = vec![stack.span];
+                let mut parent = stack.parent;
+                while let Some(index) = parent {
+                    spans.push(self.stacks[index].span);
+                    parent = self.stacks[index].parent;
+                }
+
+                let frames = spans
+                    .iter()
+                    .rev()
+                    .map(|span| self.frame_label(*span, &lines))
+                    .collect::<Vec<_>>()
+                    .join(";");
+
+                (frames, stack.duration.as_micros())
+            })
+            .collect::<Vec<_>>();
+
+        folded.sort();
+
+        folded
+            .into_iter()
+            .fold(String::new(), |mut output, (frames, micros)| {
+                let _ = writeln!(output, "{} {}", frames, micros);
+                output
+            })
+    }
+
+    fn frame_label(&self, span: Span, lines: &LineIndex) -> String {
+        let (line, column) = lines.position(span.start());
+        let name = self.stats.get(&span).map(|stats| stats.name).unwrap_or("");
+
+        // Semicolons separate frames in the folded format, and are therefore
+        // not allowed in a frame label.
+        format!("{} ({}:{})", name, line, column).replace(';', "_")
+    }
+}
+
+/// Converts byte offsets into one-based line and column numbers.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self { starts }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+
+        (line + 1, offset - self.starts[line] + 1)
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diagnostic::span;
+
+    #[test]
+    fn line_index() {
+        let lines = LineIndex::new("foo\nbar\n\nbaz");
+
+        assert_eq!(lines.position(0), (1, 1));
+        assert_eq!(lines.position(2), (1, 3));
+        assert_eq!(lines.position(4), (2, 1));
+        assert_eq!(lines.position(8), (3, 1));
+        assert_eq!(lines.position(10), (4, 2));
+    }
+
+    #[test]
+    fn nested_frames() {
+        let mut profiler = Profiler::new();
+
+        for _ in 0..3 {
+            profiler.enter_expression(span(0, 20), "assignment");
+            profiler.enter_function_call(span(7, 20), "upcase");
+            profiler.exit();
+            profiler.exit();
+        }
+
+        // Repeated stacks are recorded only once.
+        assert_eq!(profiler.stacks.len(), 2);
+
+        let stats = profiler.stats().collect::<Vec<_>>();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].1.name, "assignment");
+        assert_eq!(stats[0].1.count, 3);
+        assert_eq!(stats[1].1.name, "upcase");
+        assert_eq!(stats[1].1.count, 3);
+        assert!(stats[1].1.function_call);
+        assert!(stats[0].1.total >= stats[1].1.total);
+
+        let folded = profiler.render_folded(".foo = upcase(.bar)");
+        let stacks = folded
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            stacks,
+            vec!["assignment (1:1)", "assignment (1:1);upcase (1:8)"]
+        );
+    }
+
+    #[test]
+    fn annotated_listing() {
+        let mut profiler = Profiler::new();
+        let source = ".foo = 1\n.bar = upcase(.baz)";
+
+        profiler.enter_expression(span(9, 28), "assignment");
+        profiler.enter_function_call(span(16, 28), "upcase");
+        profiler.exit();
+        profiler.exit();
+
+        let listing = profiler.render_annotated(source);
+        let lines = listing.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].trim_start().starts_with("1 | .foo = 1"));
+        assert!(lines[2].trim_start().starts_with("1 "));
+        assert!(lines[2].ends_with("2 | .bar = upcase(.baz)"));
+        assert!(lines[3].ends_with("|        └ upcase"));
+    }
+
+    #[test]
+    fn unbalanced_exit() {
+        let mut profiler = Profiler::new();
+        profiler.exit();
+
+        assert!(profiler.is_empty());
+    }
+}
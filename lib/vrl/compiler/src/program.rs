@@ -1,10 +1,11 @@
-use crate::Expression;
+use crate::{Expression, Span};
 use std::iter::IntoIterator;
 use std::ops::Deref;
 
 #[derive(Debug, Clone)]
 pub struct Program {
     pub(crate) expressions: Vec<Box<dyn Expression>>,
+    pub(crate) roots: Vec<(Span, &'static str)>,
     pub(crate) fallible: bool,
     pub(crate) abortable: bool,
 }
@@ -25,6 +26,15 @@ impl Program {
     pub fn can_abort(&self) -> bool {
         self.abortable
     }
+
+    /// Returns the source location and expression kind of each root
+    /// expression, in the same order as the expressions themselves.
+    ///
+    /// This is used to attribute runtime statistics to the program source when
+    /// profiling.
+    pub fn roots(&self) -> &[(Span, &'static str)] {
+        &self.roots
+    }
 }
 
 impl IntoIterator for Program {
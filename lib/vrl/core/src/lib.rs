@@ -2,7 +2,8 @@ pub mod prelude;
 mod runtime;
 
 pub use compiler::{
-    function, state, type_def::Index, value, Context, Expression, Function, Program, Target, Value,
+    function, profiler, state, type_def::Index, value, Context, Expression, Function, Profiler,
+    Program, Target, Value,
 };
 pub use diagnostic;
 pub use runtime::{Runtime, RuntimeResult, Terminate};
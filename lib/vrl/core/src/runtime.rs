@@ -1,4 +1,4 @@
-use crate::{state, Context, Program, Target, Value};
+use crate::{state, Context, Profiler, Program, Target, Value};
 use compiler::ExpressionError;
 use lookup::LookupBuf;
 use shared::TimeZone;
@@ -64,6 +64,28 @@ impl Runtime {
         target: &mut dyn Target,
         program: &Program,
         timezone: &TimeZone,
+    ) -> RuntimeResult {
+        self.resolve_inner(target, program, timezone, None)
+    }
+
+    /// Similar to [`Runtime::resolve`], except that the execution of the
+    /// program is recorded by the provided [`Profiler`].
+    pub fn resolve_with_profiler(
+        &mut self,
+        target: &mut dyn Target,
+        program: &Program,
+        timezone: &TimeZone,
+        profiler: &mut Profiler,
+    ) -> RuntimeResult {
+        self.resolve_inner(target, program, timezone, Some(profiler))
+    }
+
+    fn resolve_inner(
+        &mut self,
+        target: &mut dyn Target,
+        program: &Program,
+        timezone: &TimeZone,
+        profiler: Option<&mut Profiler>,
     ) -> RuntimeResult {
         // Validate that the path is an object.
         //
@@ -94,11 +116,25 @@ impl Runtime {
         };
 
         let mut context = Context::new(target, &mut self.state, timezone);
+        if let Some(profiler) = profiler {
+            context = context.with_profiler(profiler);
+        }
 
         let mut values = program
             .iter()
-            .map(|expr| {
-                expr.resolve(&mut context).map_err(|err| match err {
+            .zip(program.roots())
+            .map(|(expr, (span, kind))| {
+                if let Some(profiler) = context.profiler_mut() {
+                    profiler.enter_expression(*span, kind);
+                }
+
+                let resolved = expr.resolve(&mut context);
+
+                if let Some(profiler) = context.profiler_mut() {
+                    profiler.exit();
+                }
+
+                resolved.map_err(|err| match err {
                     ExpressionError::Abort { .. } => Terminate::Abort(err),
                     err @ ExpressionError::Error { .. } => Terminate::Error(err),
                 })
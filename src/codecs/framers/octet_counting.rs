@@ -1,8 +1,8 @@
 use crate::codecs::{BoxedFramer, BoxedFramingError, FramingConfig};
-use bytes::{Buf, Bytes, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use serde::{Deserialize, Serialize};
 use std::io;
-use tokio_util::codec::{LinesCodec, LinesCodecError};
+use tokio_util::codec::{Encoder, LinesCodec, LinesCodecError};
 
 /// Config used to build a `OctetCountingCodec`.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -281,14 +281,41 @@ impl tokio_util::codec::Decoder for OctetCountingCodec {
     }
 }
 
+impl<T> Encoder<T> for OctetCountingCodec
+where
+    T: AsRef<[u8]>,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let item = item.as_ref();
+        let length = item.len().to_string();
+        buf.reserve(length.len() + 1 + item.len());
+        buf.put(length.as_bytes());
+        buf.put_u8(b' ');
+        buf.put(item);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::print_stdout)]
 
     use super::*;
-    use bytes::BufMut;
     use tokio_util::codec::Decoder;
 
+    #[test]
+    fn octet_encode() {
+        let mut codec = OctetCountingCodec::new();
+
+        let mut buf = BytesMut::new();
+        codec.encode(b"abc", &mut buf).unwrap();
+        codec.encode(b"", &mut buf).unwrap();
+
+        assert_eq!(b"3 abc0 ", &buf[..]);
+    }
+
     #[test]
     fn non_octet_decode_works_with_multiple_frames() {
         let mut decoder = OctetCountingCodec::new_with_max_length(128);
@@ -174,6 +174,20 @@ fn walk(
                     next: target.next
                 });
             }
+            Transform::FallibleTask(t) => {
+                let in_stream = futures::stream::iter(inputs.clone());
+                let out_stream = t.transform(Box::pin(in_stream));
+                // unit tests don't currently support multiple outputs, so just throw the errors away
+                let out_iter = futures::executor::block_on_stream(out_stream).filter_map(Result::ok);
+                results.extend(out_iter);
+                targets = target.next.clone();
+                transforms.insert(key, UnitTestTransform {
+                    transform:  futures::executor::block_on(target.config.clone().build(&TransformContext::new_with_globals(globals.clone())))
+                        .expect("Failed to build a known valid transform config. Things may have changed during runtime."),
+                    config: target.config,
+                    next: target.next
+                });
+            }
         }
     }
 
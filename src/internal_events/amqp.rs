@@ -0,0 +1,147 @@
+// ## skip check-events ##
+
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct AmqpEventsReceived {
+    pub byte_size: usize,
+    pub count: usize,
+}
+
+impl InternalEvent for AmqpEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", self.count as u64);
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpConsumeFailed {
+    pub error: lapin::Error,
+}
+
+impl InternalEvent for AmqpConsumeFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to read message.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_failed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpConnectFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for AmqpConnectFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to reconnect to AMQP broker.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpAckFailed {
+    pub error: lapin::Error,
+}
+
+impl InternalEvent for AmqpAckFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to acknowledge delivery.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("amqp_acknowledgement_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpDeliveryRejected {
+    pub requeue: bool,
+}
+
+impl InternalEvent for AmqpDeliveryRejected {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Rejecting delivery that was not delivered downstream.",
+            requeue = %self.requeue,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "amqp_deliveries_rejected_total", 1,
+            "requeue" => self.requeue.to_string(),
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpEventSendSuccess {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for AmqpEventSendSuccess {
+    fn emit_logs(&self) {
+        trace!(message = "Processed one event.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpEventSendFail {
+    pub error: lapin::Error,
+}
+
+impl InternalEvent for AmqpEventSendFail {
+    fn emit_logs(&self) {
+        error!(message = "Failed to send message.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AmqpPublishNacked;
+
+impl InternalEvent for AmqpPublishNacked {
+    fn emit_logs(&self) {
+        error!(
+            message = "Broker did not confirm message.",
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
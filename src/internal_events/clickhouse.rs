@@ -0,0 +1,39 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct ClickhouseColumnsDiscovered<'a> {
+    pub table: &'a str,
+    pub count: usize,
+}
+
+impl<'a> InternalEvent for ClickhouseColumnsDiscovered<'a> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Discovered table columns.",
+            table = %self.table,
+            count = %self.count,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ClickhouseColumnConversionFailed<'a> {
+    pub column: &'a str,
+    pub error: String,
+}
+
+impl<'a> InternalEvent for ClickhouseColumnConversionFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Field can't be converted to the type of its column; using the column's default instead.",
+            column = %self.column,
+            error = %self.error,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "conversion_failed");
+    }
+}
@@ -16,3 +16,60 @@ impl InternalEvent for ElasticSearchEventEncoded {
         counter!("processed_bytes_total", self.byte_size as u64);
     }
 }
+
+#[derive(Debug)]
+pub struct ElasticSearchNodeEvicted<'a> {
+    pub endpoint: &'a str,
+    pub error: &'a crate::Error,
+}
+
+impl<'a> InternalEvent for ElasticSearchNodeEvicted<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Node is unreachable, sending requests to the other nodes.",
+            endpoint = %self.endpoint,
+            error = %self.error,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ElasticSearchNodeRestored<'a> {
+    pub endpoint: &'a str,
+}
+
+impl<'a> InternalEvent for ElasticSearchNodeRestored<'a> {
+    fn emit_logs(&self) {
+        info!(message = "Node is reachable again.", endpoint = %self.endpoint);
+    }
+}
+
+#[derive(Debug)]
+pub struct ElasticSearchNodesSniffed {
+    pub count: usize,
+}
+
+impl InternalEvent for ElasticSearchNodesSniffed {
+    fn emit_logs(&self) {
+        debug!(message = "Sniffed cluster nodes.", count = %self.count);
+    }
+}
+
+#[derive(Debug)]
+pub struct ElasticSearchSniffFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for ElasticSearchSniffFailed {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to sniff cluster nodes, keeping the current ones.",
+            error = %self.error,
+            internal_log_rate_secs = 60,
+        );
+    }
+}
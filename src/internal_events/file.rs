@@ -1,5 +1,5 @@
 use metrics::{counter, gauge};
-use std::borrow::Cow;
+use std::{borrow::Cow, path::Path};
 use vector_core::internal_event::InternalEvent;
 
 #[cfg(any(feature = "sources-file", feature = "sources-kubernetes_logs"))]
@@ -36,6 +36,33 @@ impl InternalEvent for FileBytesSent<'_> {
     }
 }
 
+#[derive(Debug)]
+pub struct FileRotated<'a> {
+    pub file: &'a Path,
+    pub rotated: &'a Path,
+}
+
+impl InternalEvent for FileRotated<'_> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "File rotated.",
+            file = %self.file.display(),
+            rotated = %self.rotated.display(),
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct FilePruned<'a> {
+    pub file: &'a Path,
+}
+
+impl InternalEvent for FilePruned<'_> {
+    fn emit_logs(&self) {
+        debug!(message = "Rotated file removed by retention policy.", file = %self.file.display());
+    }
+}
+
 #[cfg(any(feature = "sources-file", feature = "sources-kubernetes_logs"))]
 mod source {
     use super::{FileOpen, InternalEvent};
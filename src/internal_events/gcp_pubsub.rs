@@ -0,0 +1,45 @@
+// ## skip check-events ##
+
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct GcpPubsubEventsReceived {
+    pub byte_size: usize,
+    pub count: usize,
+}
+
+impl InternalEvent for GcpPubsubEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", self.count as u64);
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GcpPubsubStreamingPullFailed {
+    pub error: tonic::Status,
+}
+
+impl InternalEvent for GcpPubsubStreamingPullFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Streaming pull from subscription failed, reconnecting.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
@@ -0,0 +1,88 @@
+// ## skip check-events ##
+
+use bytes::Bytes;
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct GraphiteEventReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GraphiteEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received events.", count = %self.count, byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", self.count as u64);
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GraphiteInvalidRecord<'a> {
+    pub error: &'a crate::sources::graphite::parser::ParseError,
+    pub bytes: Bytes,
+}
+
+impl<'a> InternalEvent for GraphiteInvalidRecord<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Invalid record from graphite, discarding.",
+            error = %self.error,
+            bytes = %String::from_utf8_lossy(&self.bytes),
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("invalid_record_total", 1);
+        counter!("invalid_record_bytes_total", self.bytes.len() as u64);
+    }
+}
+
+#[derive(Debug)]
+enum GraphiteSocketErrorType {
+    Bind,
+    Read,
+}
+
+#[derive(Debug)]
+pub struct GraphiteSocketError<T> {
+    r#type: GraphiteSocketErrorType,
+    pub error: T,
+}
+
+impl<T> GraphiteSocketError<T> {
+    pub const fn bind(error: T) -> Self {
+        Self {
+            r#type: GraphiteSocketErrorType::Bind,
+            error,
+        }
+    }
+
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    pub fn read(error: T) -> Self {
+        Self {
+            r#type: GraphiteSocketErrorType::Read,
+            error,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug + std::fmt::Display> InternalEvent for GraphiteSocketError<T> {
+    fn emit_logs(&self) {
+        let message = match self.r#type {
+            GraphiteSocketErrorType::Bind => "Failed to bind to UDP listener socket.",
+            GraphiteSocketErrorType::Read => "Failed to read UDP datagram.",
+        };
+        error!(message, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
@@ -0,0 +1,24 @@
+// ## skip check-events ##
+
+use crate::sources::influxdb::parser::ParseError;
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct InfluxdbLineParseError<'a> {
+    pub error: &'a ParseError,
+}
+
+impl<'a> InternalEvent for InfluxdbLineParseError<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Invalid line protocol point.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "parse_failed");
+    }
+}
@@ -0,0 +1,51 @@
+use metrics::{counter, gauge};
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct JoinEventsMatched;
+
+impl InternalEvent for JoinEventsMatched {
+    fn emit_metrics(&self) {
+        counter!("join_events_matched_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct JoinEventUnmatched {
+    pub reason: &'static str,
+    pub event_dropped: bool,
+}
+
+impl InternalEvent for JoinEventUnmatched {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Event could not be joined.",
+            reason = self.reason,
+            event_dropped = self.event_dropped,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "join_events_unmatched_total", 1,
+            "reason" => self.reason
+        );
+        if self.event_dropped {
+            counter!("events_discarded_total", 1);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct JoinPendingKeys {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+impl InternalEvent for JoinPendingKeys {
+    fn emit_metrics(&self) {
+        gauge!("join_pending_keys", self.count as f64);
+        gauge!("join_pending_bytes", self.bytes as f64);
+    }
+}
@@ -0,0 +1,19 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct KubernetesEventsEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for KubernetesEventsEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", 1);
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
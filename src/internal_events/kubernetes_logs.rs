@@ -72,6 +72,42 @@ impl InternalEvent for KubernetesLogsEventNamespaceAnnotationFailed<'_> {
     }
 }
 
+#[derive(Debug)]
+pub struct KubernetesLogsEventNodeAnnotationFailed<'a> {
+    pub event: &'a Event,
+}
+
+impl InternalEvent for KubernetesLogsEventNodeAnnotationFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to annotate event with node metadata.",
+            event = ?self.event
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("k8s_event_node_annotation_failures_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct KubernetesLogsEventOwnerAnnotationFailed<'a> {
+    pub event: &'a Event,
+}
+
+impl InternalEvent for KubernetesLogsEventOwnerAnnotationFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to annotate event with pod controller.",
+            event = ?self.event
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("k8s_event_owner_annotation_failures_total", 1);
+    }
+}
+
 #[derive(Debug)]
 pub struct KubernetesLogsFormatPickerEdgeCase {
     pub what: &'static str,
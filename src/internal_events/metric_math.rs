@@ -0,0 +1,33 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct MetricMathEvaluationFailed<'a> {
+    pub name: &'a str,
+}
+
+impl<'a> InternalEvent for MetricMathEvaluationFailed<'a> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Expression did not evaluate to a finite number; skipping derived metric.",
+            name = %self.name,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+                 "error_type" => "value_invalid");
+    }
+}
+
+#[derive(Debug)]
+pub struct MetricMathSeriesExpired {
+    pub count: usize,
+}
+
+impl InternalEvent for MetricMathSeriesExpired {
+    fn emit_metrics(&self) {
+        counter!("metric_math_series_expired_total", self.count as u64);
+    }
+}
@@ -55,3 +55,62 @@ impl InternalEvent for NatsEventSendFail {
         counter!("send_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct NatsFetchFailed {
+    pub error: Error,
+}
+
+impl InternalEvent for NatsFetchFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to fetch messages from JetStream.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct NatsAckFailed {
+    pub error: Error,
+}
+
+impl InternalEvent for NatsAckFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to acknowledge JetStream message.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("nats_acknowledgement_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct NatsPublishRetried {
+    pub error: Error,
+    pub attempt: usize,
+}
+
+impl InternalEvent for NatsPublishRetried {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Retrying JetStream publish.",
+            error = %self.error,
+            attempt = self.attempt,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("nats_publish_retries_total", 1);
+    }
+}
@@ -0,0 +1,73 @@
+// ## skip check-events ##
+
+use metrics::counter;
+use netflow_parser::flow_parser::FlowParserError;
+use std::net::SocketAddr;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct NetflowEventsReceived {
+    pub byte_size: usize,
+    pub count: usize,
+}
+
+impl InternalEvent for NetflowEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", self.count as u64);
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct NetflowParseError<'a> {
+    pub error: &'a FlowParserError,
+    pub exporter: SocketAddr,
+}
+
+impl<'a> InternalEvent for NetflowParseError<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to parse flow packet.",
+            error = %self.error,
+            exporter = %self.exporter,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("parse_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct NetflowTemplatesMissing<'a> {
+    pub template_ids: &'a [u16],
+    pub exporter: SocketAddr,
+}
+
+impl<'a> InternalEvent for NetflowTemplatesMissing<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Dropped flow records with unknown templates.",
+            template_ids = ?self.template_ids,
+            exporter = %self.exporter,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "netflow_missing_templates_total",
+            self.template_ids.len() as u64
+        );
+    }
+}
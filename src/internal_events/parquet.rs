@@ -0,0 +1,28 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct ParquetFieldTypeMismatch<'a> {
+    pub field: &'a str,
+    pub expected: &'static str,
+    pub drop_event: bool,
+}
+
+impl<'a> InternalEvent for ParquetFieldTypeMismatch<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Field doesn't match the type of its Parquet column.",
+            field = %self.field,
+            expected = %self.expected,
+            drop_event = %self.drop_event,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "type_mismatch");
+        if self.drop_event {
+            counter!("events_discarded_total", 1, "reason" => "type_mismatch");
+        }
+    }
+}
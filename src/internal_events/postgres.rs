@@ -0,0 +1,58 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct PostgresColumnsDiscovered<'a> {
+    pub table: &'a str,
+    pub count: usize,
+}
+
+impl<'a> InternalEvent for PostgresColumnsDiscovered<'a> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Discovered table columns.",
+            table = %self.table,
+            count = %self.count,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresConnectionFailed {
+    pub error: String,
+}
+
+impl InternalEvent for PostgresConnectionFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to connect to PostgreSQL.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresWriteFailed<'a> {
+    pub error: String,
+    pub table: &'a str,
+}
+
+impl<'a> InternalEvent for PostgresWriteFailed<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to write batch; transaction rolled back.",
+            error = %self.error,
+            table = %self.table,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
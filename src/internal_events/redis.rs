@@ -57,3 +57,65 @@ impl InternalEvent for RedisSendEventFailed {
         counter!("send_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct RedisEventsReceived {
+    pub byte_size: usize,
+    pub count: usize,
+}
+
+impl InternalEvent for RedisEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", self.count as u64);
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct RedisAckFailed {
+    pub error: redis::RedisError,
+}
+
+impl InternalEvent for RedisAckFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to acknowledge stream entry.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("redis_acknowledgement_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct RedisStreamFieldMissing<'a> {
+    pub id: &'a str,
+    pub field: &'a str,
+}
+
+impl<'a> InternalEvent for RedisStreamFieldMissing<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Stream entry is missing the payload field, skipping it.",
+            id = %self.id,
+            field = %self.field,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "field_missing");
+    }
+}
@@ -9,3 +9,36 @@ impl InternalEvent for ReduceStaleEventFlushed {
         counter!("stale_events_flushed_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct ReduceLateEventDropped;
+
+impl InternalEvent for ReduceLateEventDropped {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Event arrived after all of its windows were closed; discarding event.",
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", 1, "reason" => "late");
+    }
+}
+
+#[derive(Debug)]
+pub struct ReduceEventMissingTimestamp;
+
+impl InternalEvent for ReduceEventMissingTimestamp {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Event has no valid timestamp to assign it to a window; discarding event.",
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+                 "error_type" => "field_missing");
+    }
+}
@@ -1,6 +1,7 @@
 // ## skip check-events ##
 
-use metrics::counter;
+use metrics::{counter, histogram};
+use std::time::Duration;
 use vector_core::internal_event::InternalEvent;
 
 #[derive(Debug)]
@@ -50,3 +51,21 @@ impl InternalEvent for RemapMappingAbort {
         debug!(message, internal_log_rate_secs = 30)
     }
 }
+
+#[derive(Debug)]
+pub struct RemapFunctionCallProfiled {
+    pub function: &'static str,
+    /// The number of calls made while mapping a single event.
+    pub count: u64,
+    /// The cumulative time spent in those calls.
+    pub duration: Duration,
+}
+
+impl InternalEvent for RemapFunctionCallProfiled {
+    fn emit_metrics(&self) {
+        counter!("vrl_function_calls_total", self.count,
+                 "function" => self.function);
+        histogram!("vrl_function_call_duration_seconds", self.duration,
+                   "function" => self.function);
+    }
+}
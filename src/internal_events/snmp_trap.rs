@@ -0,0 +1,86 @@
+// ## skip check-events ##
+
+use crate::sources::snmp_trap::SnmpError;
+use metrics::counter;
+use std::net::SocketAddr;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct SnmpTrapEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for SnmpTrapEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received trap.", byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", 1);
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct SnmpTrapParseError<'a> {
+    pub error: &'a SnmpError,
+    pub peer: SocketAddr,
+}
+
+impl<'a> InternalEvent for SnmpTrapParseError<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to parse SNMP message.",
+            error = %self.error,
+            peer = %self.peer,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("parse_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct SnmpTrapAuthenticationFailed<'a> {
+    pub error: &'a SnmpError,
+    pub peer: SocketAddr,
+}
+
+impl<'a> InternalEvent for SnmpTrapAuthenticationFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Rejected SNMP message.",
+            error = %self.error,
+            peer = %self.peer,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("snmp_trap_authentication_failures_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct SnmpTrapResponseFailed {
+    pub error: std::io::Error,
+    pub peer: SocketAddr,
+}
+
+impl InternalEvent for SnmpTrapResponseFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to acknowledge inform.",
+            error = %self.error,
+            peer = %self.peer,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
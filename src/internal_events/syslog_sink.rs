@@ -0,0 +1,24 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct SyslogSinkInvalidField {
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl InternalEvent for SyslogSinkInvalidField {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Invalid syslog field value, using the default.",
+            field = %self.field,
+            value = %self.value,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+            "error_type" => "value_invalid");
+    }
+}
@@ -0,0 +1,105 @@
+// ## skip check-events ##
+
+use metrics::counter;
+use std::{fmt::Display, net::SocketAddr};
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct WebSocketConnectionEstablished {
+    pub peer_addr: Option<SocketAddr>,
+}
+
+impl InternalEvent for WebSocketConnectionEstablished {
+    fn emit_logs(&self) {
+        match self.peer_addr {
+            Some(peer_addr) => debug!(message = "Connected.", %peer_addr),
+            None => debug!(message = "Connected.", peer_addr = "unknown"),
+        }
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_established_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketConnectionFailed<E> {
+    pub error: E,
+}
+
+impl<E: Display> InternalEvent for WebSocketConnectionFailed<E> {
+    fn emit_logs(&self) {
+        error!(message = "Unable to connect.", error = %self.error, internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_failed_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketConnectionShutdown;
+
+impl InternalEvent for WebSocketConnectionShutdown {
+    fn emit_logs(&self) {
+        debug!(message = "Connection closed by the peer.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_shutdown_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketConnectionError<E> {
+    pub error: E,
+}
+
+impl<E: Display> InternalEvent for WebSocketConnectionError<E> {
+    fn emit_logs(&self) {
+        warn!(message = "WebSocket connection error.", error = %self.error, internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketEventSent {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for WebSocketEventSent {
+    fn emit_logs(&self) {
+        trace!(message = "Event sent.", byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketEventsReceived {
+    pub byte_size: usize,
+    pub count: usize,
+    pub peer_addr: SocketAddr,
+}
+
+impl InternalEvent for WebSocketEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            count = %self.count,
+            peer_addr = %self.peer_addr,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("component_received_events_total", self.count as u64);
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
@@ -33,6 +33,8 @@ pub mod expiring_hash_map;
 pub mod generate;
 #[macro_use]
 pub mod internal_events;
+#[cfg(any(feature = "sources-amqp", feature = "sinks-amqp"))]
+pub(crate) mod amqp;
 #[cfg(feature = "api")]
 pub mod api;
 pub mod app;
@@ -51,6 +53,10 @@ pub(crate) mod kafka;
 pub mod kubernetes;
 pub mod line_agg;
 pub mod list;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+pub(crate) mod mqtt;
+#[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
+pub(crate) mod nats;
 pub(crate) mod pipeline;
 pub(crate) mod proto;
 pub mod providers;
@@ -0,0 +1,166 @@
+use crate::tls::{TlsConfig, TlsError, TlsSettings};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use rumqttc::{AsyncClient, EventLoop, Key, MqttOptions, QoS, TlsConfiguration, Transport};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+
+/// Capacity of the request channel between the client handle and its event loop.
+const CLIENT_CAPACITY: usize = 100;
+
+#[derive(Debug, Snafu)]
+pub enum MqttError {
+    #[snafu(display("MQTT TLS configuration error: {}", source))]
+    Tls { source: TlsError },
+    #[snafu(display("MQTT over TLS requires `tls.ca_file` to verify the broker"))]
+    MissingCaFile,
+    #[snafu(display("MQTT does not support disabling `tls.verify_certificate`"))]
+    UnverifiedCertificate,
+    #[snafu(display("A fixed `client_id` is required when `clean_session` is disabled"))]
+    MissingClientId,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttQos {
+    AtMostOnce,
+    #[derivative(Default)]
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+impl From<MqttQos> for QoS {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => QoS::AtMostOnce,
+            MqttQos::AtLeastOnce => QoS::AtLeastOnce,
+            MqttQos::ExactlyOnce => QoS::ExactlyOnce,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+pub struct MqttConnectionConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    #[derivative(Default(value = "default_port()"))]
+    pub port: u16,
+    pub client_id: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    #[serde(default = "default_keep_alive_secs")]
+    #[derivative(Default(value = "default_keep_alive_secs()"))]
+    pub keep_alive_secs: u16,
+    pub tls: Option<TlsConfig>,
+}
+
+const fn default_port() -> u16 {
+    1883
+}
+
+const fn default_keep_alive_secs() -> u16 {
+    60
+}
+
+impl MqttConnectionConfig {
+    /// Build a client and its event loop. Nothing is sent to the broker
+    /// until the event loop is polled.
+    pub fn build_client(&self, clean_session: bool) -> Result<(AsyncClient, EventLoop), MqttError> {
+        let client_id = match &self.client_id {
+            Some(client_id) => client_id.clone(),
+            None if clean_session => format!(
+                "vector-{}",
+                thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(8)
+                    .map(char::from)
+                    .collect::<String>()
+            ),
+            None => return Err(MqttError::MissingClientId),
+        };
+
+        let mut options = MqttOptions::new(client_id, self.host.clone(), self.port);
+        options
+            .set_keep_alive(self.keep_alive_secs)
+            .set_clean_session(clean_session);
+
+        if let (Some(user), Some(password)) = (&self.user, &self.password) {
+            options.set_credentials(user.clone(), password.clone());
+        }
+
+        if let Some(tls) = self.tls.as_ref().filter(|tls| tls.enabled.unwrap_or(false)) {
+            options.set_transport(Transport::Tls(tls_configuration(tls)?));
+        }
+
+        Ok(AsyncClient::new(options, CLIENT_CAPACITY))
+    }
+}
+
+// `rumqttc` is built on `rustls`, so the certificates loaded through our
+// OpenSSL based settings are handed over as PEM.
+fn tls_configuration(config: &TlsConfig) -> Result<TlsConfiguration, MqttError> {
+    if config.options.verify_certificate == Some(false) {
+        return Err(MqttError::UnverifiedCertificate);
+    }
+
+    let settings = TlsSettings::from_options(&Some(config.options.clone())).context(Tls)?;
+    let ca = settings.authorities_pem().context(Tls)?;
+    if ca.is_empty() {
+        return Err(MqttError::MissingCaFile);
+    }
+
+    // `Key::ECC` makes `rumqttc` read the key as PKCS#8, which is the format
+    // the identity is exported in, regardless of the key algorithm.
+    let client_auth = settings
+        .identity_pem()
+        .context(Tls)?
+        .map(|(cert, key)| (cert, Key::ECC(key)));
+
+    Ok(TlsConfiguration::Simple {
+        ca,
+        alpn: None,
+        client_auth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persistent_session_requires_client_id() {
+        let mut config = MqttConnectionConfig {
+            host: "localhost".into(),
+            ..Default::default()
+        };
+        assert!(config.build_client(true).is_ok());
+        assert!(matches!(
+            config.build_client(false),
+            Err(MqttError::MissingClientId)
+        ));
+
+        config.client_id = Some("vector".into());
+        assert!(config.build_client(false).is_ok());
+    }
+
+    #[test]
+    fn tls_requires_ca_file() {
+        let config = MqttConnectionConfig {
+            host: "localhost".into(),
+            tls: Some(TlsConfig::enabled()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.build_client(true),
+            Err(MqttError::MissingCaFile)
+        ));
+
+        let config = MqttConnectionConfig {
+            host: "localhost".into(),
+            tls: Some(TlsConfig::test_config()),
+            ..Default::default()
+        };
+        assert!(config.build_client(true).is_ok());
+    }
+}
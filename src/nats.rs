@@ -0,0 +1,214 @@
+use crate::tls::TlsConfig;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Snafu)]
+pub enum NatsError {
+    #[snafu(display("NATS NKey seed is invalid: {}", source))]
+    NkeySeed { source: nkeys::error::Error },
+    #[snafu(display("NATS does not support disabling `tls.verify_certificate`"))]
+    UnverifiedCertificate,
+    #[snafu(display("NATS does not support encrypted keys, `tls.key_pass` must be unset"))]
+    EncryptedKey,
+    #[snafu(display("NATS client certificates require both `tls.crt_file` and `tls.key_file`"))]
+    IncompleteIdentity,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case", tag = "strategy")]
+pub enum NatsAuthConfig {
+    UserPassword { user: String, password: String },
+    Token { token: String },
+    CredentialsFile { path: PathBuf },
+    Nkey { nkey: String, seed: String },
+}
+
+/// The blocking and async clients of `nats` have distinct option types with
+/// identical builders. The blocking client is needed for JetStream, so the
+/// shared configuration is applied through this trait.
+pub trait NatsOptions: Sized {
+    fn new() -> Self;
+    fn with_user_pass(user: &str, password: &str) -> Self;
+    fn with_token(token: &str) -> Self;
+    fn with_credentials(path: &Path) -> Self;
+    fn with_nkey<F>(nkey: &str, sig_cb: F) -> Self
+    where
+        F: Fn(&[u8]) -> Vec<u8> + Send + Sync + 'static;
+    fn with_name(self, name: &str) -> Self;
+    fn reconnect_buffer_size(self, size: usize) -> Self;
+    fn tls_required(self, tls_required: bool) -> Self;
+    fn add_root_certificate(self, path: &Path) -> Self;
+    fn client_cert(self, cert: &Path, key: &Path) -> Self;
+}
+
+macro_rules! impl_nats_options {
+    ($options:ty) => {
+        impl NatsOptions for $options {
+            fn new() -> Self {
+                <$options>::new()
+            }
+
+            fn with_user_pass(user: &str, password: &str) -> Self {
+                <$options>::with_user_pass(user, password)
+            }
+
+            fn with_token(token: &str) -> Self {
+                <$options>::with_token(token)
+            }
+
+            fn with_credentials(path: &Path) -> Self {
+                <$options>::with_credentials(path)
+            }
+
+            fn with_nkey<F>(nkey: &str, sig_cb: F) -> Self
+            where
+                F: Fn(&[u8]) -> Vec<u8> + Send + Sync + 'static,
+            {
+                <$options>::with_nkey(nkey, sig_cb)
+            }
+
+            fn with_name(self, name: &str) -> Self {
+                <$options>::with_name(self, name)
+            }
+
+            fn reconnect_buffer_size(self, size: usize) -> Self {
+                <$options>::reconnect_buffer_size(self, size)
+            }
+
+            fn tls_required(self, tls_required: bool) -> Self {
+                <$options>::tls_required(self, tls_required)
+            }
+
+            fn add_root_certificate(self, path: &Path) -> Self {
+                <$options>::add_root_certificate(self, path)
+            }
+
+            fn client_cert(self, cert: &Path, key: &Path) -> Self {
+                <$options>::client_cert(self, cert, key)
+            }
+        }
+    };
+}
+
+impl_nats_options!(nats::Options);
+impl_nats_options!(nats::asynk::Options);
+
+/// Build client options from the settings shared by the `nats` source and sink.
+pub fn nats_options<O: NatsOptions>(
+    connection_name: &str,
+    auth: Option<&NatsAuthConfig>,
+    tls: Option<&TlsConfig>,
+) -> Result<O, NatsError> {
+    let options = match auth {
+        None => O::new(),
+        Some(NatsAuthConfig::UserPassword { user, password }) => O::with_user_pass(user, password),
+        Some(NatsAuthConfig::Token { token }) => O::with_token(token),
+        Some(NatsAuthConfig::CredentialsFile { path }) => O::with_credentials(path),
+        Some(NatsAuthConfig::Nkey { nkey, seed }) => {
+            let key_pair = nkeys::KeyPair::from_seed(seed).context(NkeySeed)?;
+            // Signing with a key pair created from a seed can't fail.
+            O::with_nkey(nkey, move |nonce| key_pair.sign(nonce).unwrap_or_default())
+        }
+    };
+
+    // Set reconnect_buffer_size on the nats client to 0 bytes so that the
+    // client doesn't buffer internally (to avoid message loss).
+    let options = options.with_name(connection_name).reconnect_buffer_size(0);
+
+    match tls.filter(|tls| tls.enabled.unwrap_or(false)) {
+        Some(tls) => tls_options(options, tls),
+        None => Ok(options),
+    }
+}
+
+// `nats` is built on `rustls` and reads the certificates itself, so only
+// the options it can honor are accepted.
+fn tls_options<O: NatsOptions>(options: O, tls: &TlsConfig) -> Result<O, NatsError> {
+    let tls = &tls.options;
+    if tls.verify_certificate == Some(false) {
+        return Err(NatsError::UnverifiedCertificate);
+    }
+    if tls.key_pass.is_some() {
+        return Err(NatsError::EncryptedKey);
+    }
+
+    let mut options = options.tls_required(true);
+    if let Some(ca_file) = &tls.ca_file {
+        options = options.add_root_certificate(ca_file);
+    }
+
+    match (&tls.crt_file, &tls.key_file) {
+        (Some(crt_file), Some(key_file)) => Ok(options.client_cert(crt_file, key_file)),
+        (None, None) => Ok(options),
+        _ => Err(NatsError::IncompleteIdentity),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::TlsOptions;
+
+    fn tls_config(options: TlsOptions) -> TlsConfig {
+        TlsConfig {
+            enabled: Some(true),
+            options,
+        }
+    }
+
+    #[test]
+    fn parses_auth_strategies() {
+        let auth: NatsAuthConfig = toml::from_str(
+            r#"
+            strategy = "nkey"
+            nkey = "UDXU4RCSJNZOIQHZNWXHXORDPRTGNJAHAHFRGZNEEJCPQTT2M7NLCNF4"
+            seed = "SUACSSL3UAHUDXKFSNVUZRF5UHPMWZ6BFDTJ7M6USDXIEDNPPQYYYCU3VY"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(auth, NatsAuthConfig::Nkey { .. }));
+
+        let auth: NatsAuthConfig = toml::from_str(
+            r#"
+            strategy = "credentials_file"
+            path = "/etc/nats/vector.creds"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(auth, NatsAuthConfig::CredentialsFile { .. }));
+    }
+
+    #[test]
+    fn rejects_invalid_nkey_seed() {
+        let auth = NatsAuthConfig::Nkey {
+            nkey: "UDXU4RCSJNZOIQHZNWXHXORDPRTGNJAHAHFRGZNEEJCPQTT2M7NLCNF4".into(),
+            seed: "not a seed".into(),
+        };
+        assert!(matches!(
+            nats_options::<nats::Options>("vector", Some(&auth), None),
+            Err(NatsError::NkeySeed { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_tls_options() {
+        let tls = tls_config(TlsOptions {
+            verify_certificate: Some(false),
+            ..Default::default()
+        });
+        assert!(matches!(
+            nats_options::<nats::Options>("vector", None, Some(&tls)),
+            Err(NatsError::UnverifiedCertificate)
+        ));
+
+        let tls = tls_config(TlsOptions {
+            crt_file: Some("tests/data/localhost.crt".into()),
+            ..Default::default()
+        });
+        assert!(matches!(
+            nats_options::<nats::asynk::Options>("vector", None, Some(&tls)),
+            Err(NatsError::IncompleteIdentity)
+        ));
+    }
+}
@@ -3,3 +3,14 @@ use crate::event::proto as event;
 
 #[cfg(any(feature = "sources-vector", feature = "sinks-vector"))]
 pub mod vector;
+
+#[cfg(feature = "sources-gcp_pubsub")]
+pub mod google {
+    pub mod pubsub {
+        pub mod v1 {
+            #![allow(clippy::clone_on_ref_ptr)]
+
+            tonic::include_proto!("google.pubsub.v1");
+        }
+    }
+}
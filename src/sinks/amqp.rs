@@ -0,0 +1,391 @@
+use crate::{
+    amqp::{reconnect_backoff, AmqpConfig, AmqpError},
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, EventStatus, Finalizable},
+    internal_events::{
+        AmqpConnectFailed, AmqpEventSendFail, AmqpEventSendSuccess, AmqpPublishNacked,
+        TemplateRenderingFailed,
+    },
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        StreamSink,
+    },
+    template::{Template, TemplateParseError},
+};
+use async_trait::async_trait;
+use futures::{
+    future::{self, BoxFuture},
+    stream::{BoxStream, FuturesOrdered},
+    FutureExt, StreamExt,
+};
+use lapin::{
+    options::{BasicPublishOptions, ConfirmSelectOptions},
+    publisher_confirm::Confirmation,
+    BasicProperties, Channel, Connection,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::convert::TryFrom;
+use vector_core::buffers::Acker;
+
+/// Maximum number of published messages awaiting a confirmation from the
+/// broker at any time.
+const MAX_PENDING_CONFIRMS: usize = 1024;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("invalid routing key template: {}", source))]
+    RoutingKeyTemplate { source: TemplateParseError },
+}
+
+#[derive(Debug, Snafu)]
+enum ConnectError {
+    #[snafu(display("Could not connect to AMQP broker: {}", source))]
+    AmqpConnectError { source: AmqpError },
+    #[snafu(display("Could not enable AMQP publisher confirms: {}", source))]
+    AmqpConfirmSelectError { source: lapin::Error },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AmqpSinkConfig {
+    #[serde(flatten)]
+    connection: AmqpConfig,
+    exchange: String,
+    routing_key: Option<String>,
+    encoding: EncodingConfig<Encoding>,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Text,
+    Json,
+}
+
+inventory::submit! {
+    SinkDescription::new::<AmqpSinkConfig>("amqp")
+}
+
+impl GenerateConfig for AmqpSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            connection_string = "amqp://127.0.0.1:5672/%2f"
+            exchange = "vector"
+            routing_key = "{{ application }}"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "amqp")]
+impl SinkConfig for AmqpSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let sink = AmqpSink::new(self.clone(), cx.acker())?;
+        let healthcheck = healthcheck(self.connection.clone()).boxed();
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "amqp"
+    }
+}
+
+async fn healthcheck(connection: AmqpConfig) -> crate::Result<()> {
+    let (connection, _channel) = connection.connect().await?;
+    connection.close(200, "healthcheck").await?;
+    Ok(())
+}
+
+pub struct AmqpSink {
+    connection: AmqpConfig,
+    exchange: String,
+    routing_key: Option<Template>,
+    encoding: EncodingConfig<Encoding>,
+    acker: Acker,
+}
+
+impl AmqpSink {
+    fn new(config: AmqpSinkConfig, acker: Acker) -> crate::Result<Self> {
+        let routing_key = config
+            .routing_key
+            .map(Template::try_from)
+            .transpose()
+            .context(RoutingKeyTemplate)?;
+
+        Ok(AmqpSink {
+            connection: config.connection,
+            exchange: config.exchange,
+            routing_key,
+            encoding: config.encoding,
+            acker,
+        })
+    }
+
+    /// Connects to the broker, retrying with backoff until it succeeds.
+    async fn connect(&self) -> (Connection, Channel) {
+        let mut backoff = reconnect_backoff();
+        loop {
+            match self.try_connect().await {
+                Ok(connected) => return connected,
+                Err(error) => {
+                    emit!(&AmqpConnectFailed {
+                        error: error.into()
+                    });
+                    tokio::time::sleep(backoff.next().unwrap()).await;
+                }
+            }
+        }
+    }
+
+    async fn try_connect(&self) -> Result<(Connection, Channel), ConnectError> {
+        let (connection, channel) = self.connection.connect().await.context(AmqpConnectError)?;
+        // Publisher confirms make the broker acknowledge every message once
+        // it has taken responsibility for it.
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .context(AmqpConfirmSelectError)?;
+        Ok((connection, channel))
+    }
+
+    /// Publish an event, returning a future that resolves once the broker
+    /// has confirmed it. Events that can't be published resolve immediately,
+    /// so that acknowledgements are still made in order.
+    ///
+    /// The broker is connected to first when `connected` is unset or its
+    /// channel was closed, and the event is published again after
+    /// reconnecting when publishing fails because the channel was closed.
+    /// Confirmations still pending on a lost channel resolve as errors, ahead
+    /// of those for the events published after reconnecting.
+    async fn publish(
+        &self,
+        connected: &mut Option<(Connection, Channel)>,
+        mut event: Event,
+    ) -> BoxFuture<'static, ()> {
+        let routing_key = match &self.routing_key {
+            None => String::new(),
+            Some(template) => match template.render_string(&event) {
+                Ok(routing_key) => routing_key,
+                Err(error) => {
+                    emit!(&TemplateRenderingFailed {
+                        error,
+                        field: Some("routing_key"),
+                        drop_event: true,
+                    });
+                    event.metadata().update_status(EventStatus::Errored);
+                    return future::ready(()).boxed();
+                }
+            },
+        };
+
+        let finalizers = event.take_finalizers();
+        let payload = encode_event(event, &self.encoding);
+        let byte_size = payload.len();
+
+        let confirm = loop {
+            let (_, channel) = match connected.take() {
+                Some((connection, channel)) if channel.status().connected() => {
+                    connected.insert((connection, channel))
+                }
+                _ => connected.insert(self.connect().await),
+            };
+
+            let result = channel
+                .basic_publish(
+                    &self.exchange,
+                    &routing_key,
+                    BasicPublishOptions::default(),
+                    payload.as_bytes(),
+                    BasicProperties::default(),
+                )
+                .await;
+            match result {
+                Ok(confirm) => break Ok(confirm),
+                Err(error) if channel.status().connected() => break Err(error),
+                Err(error) => {
+                    emit!(&AmqpEventSendFail { error });
+                    *connected = None;
+                }
+            }
+        };
+
+        match confirm {
+            Ok(confirm) => async move {
+                match confirm.await {
+                    Ok(Confirmation::Nack(_)) => {
+                        finalizers.update_status(EventStatus::Errored);
+                        emit!(&AmqpPublishNacked);
+                    }
+                    Ok(_) => {
+                        finalizers.update_status(EventStatus::Delivered);
+                        emit!(&AmqpEventSendSuccess { byte_size });
+                    }
+                    Err(error) => {
+                        finalizers.update_status(EventStatus::Errored);
+                        emit!(&AmqpEventSendFail { error });
+                    }
+                }
+            }
+            .boxed(),
+            Err(error) => {
+                finalizers.update_status(EventStatus::Errored);
+                emit!(&AmqpEventSendFail { error });
+                future::ready(()).boxed()
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl StreamSink for AmqpSink {
+    async fn run(self: Box<Self>, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let mut pending = FuturesOrdered::new();
+        let mut connected = None;
+
+        loop {
+            tokio::select! {
+                Some(()) = pending.next(), if !pending.is_empty() => self.acker.ack(1),
+                event = input.next(), if pending.len() < MAX_PENDING_CONFIRMS => match event {
+                    Some(event) => pending.push(self.publish(&mut connected, event).await),
+                    None => break,
+                },
+            }
+        }
+
+        while pending.next().await.is_some() {
+            self.acker.ack(1);
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> String {
+    encoding.apply_rules(&mut event);
+
+    match encoding.codec() {
+        Encoding::Json => serde_json::to_string(event.as_log()).unwrap(),
+        Encoding::Text => event
+            .as_log()
+            .get(crate::config::log_schema().message_key())
+            .map(|v| v.to_string_lossy())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AmqpSinkConfig>();
+    }
+
+    #[test]
+    fn encodes_raw_logs() {
+        let event = Event::from("foo");
+        assert_eq!(
+            "foo",
+            encode_event(event, &EncodingConfig::from(Encoding::Text))
+        );
+    }
+}
+
+#[cfg(feature = "amqp-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{random_lines_with_stream, random_string, trace_init};
+    use lapin::{
+        options::{BasicGetOptions, ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions},
+        types::FieldTable,
+        ExchangeKind,
+    };
+
+    fn connection() -> AmqpConfig {
+        AmqpConfig {
+            connection_string: std::env::var("AMQP_ADDR")
+                .unwrap_or_else(|_| "amqp://127.0.0.1:5672/%2f".into()),
+            tls: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn amqp_happy() {
+        // Publish `N` messages through an exchange and verify that they
+        // all arrive in the queue bound to it with the rendered routing key.
+        trace_init();
+
+        let exchange = format!("test-{}-exchange", random_string(10));
+        let queue = format!("test-{}-queue", random_string(10));
+
+        let (_connection, channel) = connection().connect().await.unwrap();
+        channel
+            .exchange_declare(
+                &exchange,
+                ExchangeKind::Direct,
+                ExchangeDeclareOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .unwrap();
+        channel
+            .queue_declare(
+                &queue,
+                QueueDeclareOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .unwrap();
+        channel
+            .queue_bind(
+                &queue,
+                &exchange,
+                "logs",
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .unwrap();
+
+        let config = AmqpSinkConfig {
+            connection: connection(),
+            exchange,
+            routing_key: Some("logs".into()),
+            encoding: EncodingConfig::from(Encoding::Text),
+        };
+        healthcheck(config.connection.clone()).await.unwrap();
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+        let sink = Box::new(AmqpSink::new(config, acker).await.unwrap());
+        let num_events = 1_000;
+        let (input, events) = random_lines_with_stream(100, num_events, None);
+        sink.run(Box::pin(events)).await.unwrap();
+
+        let mut output = Vec::new();
+        while let Some(message) = channel
+            .basic_get(&queue, BasicGetOptions { no_ack: true })
+            .await
+            .unwrap()
+        {
+            output.push(String::from_utf8_lossy(&message.delivery.data).to_string());
+        }
+
+        assert_eq!(output, input);
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+    }
+}
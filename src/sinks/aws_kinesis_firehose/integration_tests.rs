@@ -67,7 +67,7 @@ async fn firehose_put_records() {
 
     let config = ElasticSearchConfig {
         auth: Some(ElasticSearchAuth::Aws(AwsAuthentication::Default {})),
-        endpoint: "http://localhost:4571".into(),
+        endpoints: vec!["http://localhost:4571".into()],
         index: Some(stream.clone()),
         ..Default::default()
     };
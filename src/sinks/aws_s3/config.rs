@@ -1,7 +1,8 @@
 use crate::aws::rusoto::{AwsAuthentication, RegionOrEndpoint};
 use crate::config::SinkContext;
 use crate::sinks::s3_common::sink::S3Sink;
-use crate::sinks::util::encoding::StandardEncodings;
+use crate::sinks::util::object_store::{ObjectStoreEncoder, ObjectStoreEncodings};
+use crate::sinks::util::parquet::ParquetConfig;
 use crate::sinks::util::BulkSizeBasedDefaultBatchSettings;
 use crate::{
     config::{DataType, GenerateConfig, ProxyConfig, SinkConfig},
@@ -43,7 +44,8 @@ pub struct S3SinkConfig {
     pub options: S3Options,
     #[serde(flatten)]
     pub region: RegionOrEndpoint,
-    pub encoding: EncodingConfig<StandardEncodings>,
+    pub encoding: EncodingConfig<ObjectStoreEncodings>,
+    pub parquet: Option<ParquetConfig>,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
     #[serde(default)]
@@ -66,7 +68,8 @@ impl GenerateConfig for S3SinkConfig {
             filename_extension: None,
             options: S3Options::default(),
             region: RegionOrEndpoint::default(),
-            encoding: StandardEncodings::Text.into(),
+            encoding: ObjectStoreEncodings::Text.into(),
+            parquet: None,
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
@@ -131,14 +134,24 @@ impl S3SinkConfig {
             .filename_append_uuid
             .unwrap_or(DEFAULT_FILENAME_APPEND_UUID);
 
+        let encoder = ObjectStoreEncoder::new(
+            self.encoding.clone(),
+            self.parquet.as_ref(),
+            &batch_settings,
+        )?;
+        let mut api_options = self.options.clone();
+        if api_options.content_type.is_none() && encoder.is_parquet() {
+            api_options.content_type = Some(encoder.content_type().to_owned());
+        }
+
         let request_options = S3RequestOptions {
             bucket: self.bucket.clone(),
-            api_options: self.options.clone(),
+            api_options,
             filename_extension: self.filename_extension.clone(),
             filename_time_format,
             filename_append_uuid,
-            encoding: self.encoding.clone(),
-            compression: self.compression,
+            compression: encoder.compression(self.compression),
+            encoder,
         };
 
         let sink = S3Sink::new(cx, service, request_options, partitioner, batch_settings);
@@ -4,10 +4,7 @@ use crate::{
     event::Event,
     sinks::{
         s3_common::{config::S3Options, service::S3Metadata, service::S3Request},
-        util::{
-            encoding::{EncodingConfig, StandardEncodings},
-            Compression, RequestBuilder,
-        },
+        util::{object_store::ObjectStoreEncoder, Compression, RequestBuilder},
     },
 };
 use bytes::Bytes;
@@ -22,14 +19,14 @@ pub struct S3RequestOptions {
     pub filename_append_uuid: bool,
     pub filename_extension: Option<String>,
     pub api_options: S3Options,
-    pub encoding: EncodingConfig<StandardEncodings>,
+    pub encoder: ObjectStoreEncoder,
     pub compression: Compression,
 }
 
 impl RequestBuilder<(String, Vec<Event>)> for S3RequestOptions {
     type Metadata = S3Metadata;
     type Events = Vec<Event>;
-    type Encoder = EncodingConfig<StandardEncodings>;
+    type Encoder = ObjectStoreEncoder;
     type Payload = Bytes;
     type Request = S3Request;
     type Error = io::Error; // TODO: this is ugly.
@@ -39,11 +36,12 @@ impl RequestBuilder<(String, Vec<Event>)> for S3RequestOptions {
     }
 
     fn encoder(&self) -> &Self::Encoder {
-        &self.encoding
+        &self.encoder
     }
 
     fn split_input(&self, input: (String, Vec<Event>)) -> (Self::Metadata, Self::Events) {
         let (partition_key, mut events) = input;
+        self.encoder.prepare(&mut events);
         let finalizers = events.take_finalizers();
         let metadata = S3Metadata {
             partition_key,
@@ -68,7 +66,7 @@ impl RequestBuilder<(String, Vec<Event>)> for S3RequestOptions {
             .filename_extension
             .as_ref()
             .cloned()
-            .unwrap_or_else(|| self.compression.extension().into());
+            .unwrap_or_else(|| self.encoder.extension(self.compression).into());
         metadata.partition_key = format!("{}{}.{}", metadata.partition_key, filename, extension);
 
         // TODO: move this into `.request_builder(...)` closure?
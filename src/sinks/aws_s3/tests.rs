@@ -5,7 +5,7 @@ mod integration_tests {
     use crate::config::SinkContext;
     use crate::sinks::aws_s3::S3SinkConfig;
     use crate::sinks::s3_common::config::S3Options;
-    use crate::sinks::util::encoding::StandardEncodings;
+    use crate::sinks::util::object_store::ObjectStoreEncodings;
     use crate::sinks::util::BatchConfig;
     use crate::sinks::util::Compression;
     use crate::sinks::util::TowerRequestConfig;
@@ -314,7 +314,8 @@ mod integration_tests {
             filename_extension: None,
             options: S3Options::default(),
             region: RegionOrEndpoint::with_endpoint("http://localhost:4566".to_owned()),
-            encoding: StandardEncodings::Text.into(),
+            encoding: ObjectStoreEncodings::Text.into(),
+            parquet: None,
             compression: Compression::None,
             batch,
             request: TowerRequestConfig::default(),
@@ -20,7 +20,10 @@ use crate::{
             sink::AzureBlobSink,
         },
         util::{
-            encoding::EncodingConfig, encoding::StandardEncodings, partitioner::KeyPartitioner,
+            encoding::EncodingConfig,
+            object_store::{ObjectStoreEncoder, ObjectStoreEncodings},
+            parquet::ParquetConfig,
+            partitioner::KeyPartitioner,
             BatchConfig, BulkSizeBasedDefaultBatchSettings, Compression, RequestBuilder,
             ServiceBuilderExt, TowerRequestConfig,
         },
@@ -37,7 +40,8 @@ pub struct AzureBlobSinkConfig {
     pub blob_prefix: Option<String>,
     pub blob_time_format: Option<String>,
     pub blob_append_uuid: Option<bool>,
-    pub encoding: EncodingConfig<StandardEncodings>,
+    pub encoding: EncodingConfig<ObjectStoreEncodings>,
+    pub parquet: Option<ParquetConfig>,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
     #[serde(default)]
@@ -54,7 +58,8 @@ impl GenerateConfig for AzureBlobSinkConfig {
             blob_prefix: Some(String::from("blob")),
             blob_time_format: Some(String::from("%s")),
             blob_append_uuid: Some(true),
-            encoding: StandardEncodings::Ndjson.into(),
+            encoding: ObjectStoreEncodings::Ndjson.into(),
+            parquet: None,
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
@@ -118,12 +123,17 @@ impl AzureBlobSinkConfig {
             .blob_append_uuid
             .unwrap_or(DEFAULT_FILENAME_APPEND_UUID);
 
+        let encoder = ObjectStoreEncoder::new(
+            self.encoding.clone(),
+            self.parquet.as_ref(),
+            &batcher_settings,
+        )?;
         let request_options = AzureBlobRequestOptions {
             container_name: self.container_name.clone(),
             blob_time_format,
             blob_append_uuid,
-            encoding: self.encoding.clone(),
-            compression: self.compression,
+            compression: encoder.compression(self.compression),
+            encoder,
         };
 
         let sink = AzureBlobSink::new(
@@ -153,14 +163,14 @@ pub struct AzureBlobRequestOptions {
     pub container_name: String,
     pub blob_time_format: String,
     pub blob_append_uuid: bool,
-    pub encoding: EncodingConfig<StandardEncodings>,
+    pub encoder: ObjectStoreEncoder,
     pub compression: Compression,
 }
 
 impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
     type Metadata = AzureBlobMetadata;
     type Events = Vec<Event>;
-    type Encoder = EncodingConfig<StandardEncodings>;
+    type Encoder = ObjectStoreEncoder;
     type Payload = Bytes;
     type Request = AzureBlobRequest;
     type Error = io::Error;
@@ -170,11 +180,12 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
     }
 
     fn encoder(&self) -> &Self::Encoder {
-        &self.encoding
+        &self.encoder
     }
 
     fn split_input(&self, input: (String, Vec<Event>)) -> (Self::Metadata, Self::Events) {
         let (partition_key, mut events) = input;
+        self.encoder.prepare(&mut events);
         let finalizers = events.take_finalizers();
         let metadata = AzureBlobMetadata {
             partition_key,
@@ -195,7 +206,7 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
                 .unwrap_or_else(|| formatted_ts.to_string())
         };
 
-        let extension = self.compression.extension();
+        let extension = self.encoder.extension(self.compression);
         metadata.partition_key = format!("{}{}.{}", metadata.partition_key, blob_name, extension);
 
         debug!(
@@ -209,7 +220,11 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
         AzureBlobRequest {
             blob_data: payload,
             content_encoding: self.compression.content_encoding(),
-            content_type: self.compression.content_type(),
+            content_type: if self.encoder.is_parquet() {
+                self.encoder.content_type()
+            } else {
+                self.compression.content_type()
+            },
             metadata,
         }
     }
@@ -225,7 +240,7 @@ impl Compression {
 }
 
 #[cfg(test)]
-fn default_config(e: StandardEncodings) -> AzureBlobSinkConfig {
+fn default_config(e: ObjectStoreEncodings) -> AzureBlobSinkConfig {
     AzureBlobSinkConfig {
         connection_string: Default::default(),
         container_name: Default::default(),
@@ -233,6 +248,7 @@ fn default_config(e: StandardEncodings) -> AzureBlobSinkConfig {
         blob_time_format: Default::default(),
         blob_append_uuid: Default::default(),
         encoding: e.into(),
+        parquet: Default::default(),
         compression: Compression::gzip_default(),
         batch: Default::default(),
         request: Default::default(),
@@ -245,6 +261,11 @@ mod tests {
 
     use super::*;
 
+    fn encoder(codec: ObjectStoreEncodings) -> ObjectStoreEncoder {
+        let batch_settings = default_config(codec).batch.into_batcher_settings().unwrap();
+        ObjectStoreEncoder::new(codec.into(), None, &batch_settings).unwrap()
+    }
+
     #[test]
     fn generate_config() {
         crate::test_util::test_generate_config::<AzureBlobSinkConfig>();
@@ -258,7 +279,7 @@ mod tests {
         let sink_config = AzureBlobSinkConfig {
             blob_prefix: Some("blob".into()),
             container_name: container_name.clone(),
-            ..default_config(StandardEncodings::Text)
+            ..default_config(ObjectStoreEncodings::Text)
         };
         let blob_time_format = String::from("");
         let blob_append_uuid = false;
@@ -273,7 +294,7 @@ mod tests {
             container_name,
             blob_time_format,
             blob_append_uuid,
-            encoding: StandardEncodings::Text.into(),
+            encoder: encoder(ObjectStoreEncodings::Text),
             compression,
         };
 
@@ -293,7 +314,7 @@ mod tests {
         let sink_config = AzureBlobSinkConfig {
             blob_prefix: Some("blob".into()),
             container_name: container_name.clone(),
-            ..default_config(StandardEncodings::Text)
+            ..default_config(ObjectStoreEncodings::Text)
         };
         let blob_time_format = String::from("");
         let blob_append_uuid = false;
@@ -308,7 +329,7 @@ mod tests {
             container_name,
             blob_time_format,
             blob_append_uuid,
-            encoding: StandardEncodings::Text.into(),
+            encoder: encoder(ObjectStoreEncodings::Text),
             compression,
         };
 
@@ -328,7 +349,7 @@ mod tests {
         let sink_config = AzureBlobSinkConfig {
             blob_prefix: Some("blob".into()),
             container_name: container_name.clone(),
-            ..default_config(StandardEncodings::Text)
+            ..default_config(ObjectStoreEncodings::Text)
         };
         let blob_time_format = String::from("%F");
         let blob_append_uuid = false;
@@ -343,7 +364,7 @@ mod tests {
             container_name,
             blob_time_format,
             blob_append_uuid,
-            encoding: StandardEncodings::Text.into(),
+            encoder: encoder(ObjectStoreEncodings::Text),
             compression,
         };
 
@@ -366,7 +387,7 @@ mod tests {
         let sink_config = AzureBlobSinkConfig {
             blob_prefix: Some("blob".into()),
             container_name: container_name.clone(),
-            ..default_config(StandardEncodings::Text)
+            ..default_config(ObjectStoreEncodings::Text)
         };
         let blob_time_format = String::from("");
         let blob_append_uuid = true;
@@ -381,7 +402,7 @@ mod tests {
             container_name,
             blob_time_format,
             blob_append_uuid,
-            encoding: StandardEncodings::Text.into(),
+            encoder: encoder(ObjectStoreEncodings::Text),
             compression,
         };
 
@@ -392,6 +413,26 @@ mod tests {
         assert_eq!(request.content_encoding, None);
         assert_eq!(request.content_type, "text/plain");
     }
+
+    #[test]
+    fn azure_blob_build_request_with_parquet() {
+        let log = Event::from("test message");
+        let encoder = encoder(ObjectStoreEncodings::Parquet);
+        let request_options = AzureBlobRequestOptions {
+            container_name: String::from("logs"),
+            blob_time_format: String::from(""),
+            blob_append_uuid: false,
+            compression: encoder.compression(Compression::gzip_default()),
+            encoder,
+        };
+
+        let (metadata, _events) = request_options.split_input((String::from("blob"), vec![log]));
+        let request = request_options.build_request(metadata, Bytes::new());
+
+        assert_eq!(request.metadata.partition_key, "blob.parquet".to_string());
+        assert_eq!(request.content_encoding, None);
+        assert_eq!(request.content_type, "application/vnd.apache.parquet");
+    }
 }
 
 #[cfg(feature = "azure-blob-integration-tests")]
@@ -478,7 +519,7 @@ mod integration_tests {
         let config = AzureBlobSinkConfig::new_emulator().await;
         let config = AzureBlobSinkConfig {
             blob_prefix: Some(blob_prefix.clone()),
-            encoding: StandardEncodings::Ndjson.into(),
+            encoding: ObjectStoreEncodings::Ndjson.into(),
             ..config
         };
         let sink = config.to_sink();
@@ -535,7 +576,7 @@ mod integration_tests {
         let config = AzureBlobSinkConfig::new_emulator().await;
         let config = AzureBlobSinkConfig {
             blob_prefix: Some(blob_prefix.clone()),
-            encoding: StandardEncodings::Ndjson.into(),
+            encoding: ObjectStoreEncodings::Ndjson.into(),
             compression: Compression::gzip_default(),
             ..config
         };
@@ -602,7 +643,8 @@ mod integration_tests {
                 blob_prefix: None,
                 blob_time_format: None,
                 blob_append_uuid: None,
-                encoding: StandardEncodings::Text.into(),
+                encoding: ObjectStoreEncodings::Text.into(),
+                parquet: None,
                 compression: Compression::None,
                 batch: Default::default(),
                 request: TowerRequestConfig::default(),
@@ -0,0 +1,628 @@
+//! Conversion of events into rows of a table, in the `JSONEachRow` or `RowBinary` format.
+
+use super::schema::{Column, ColumnType, Schema};
+use crate::{
+    event::{LogEvent, Value},
+    internal_events::ClickhouseColumnConversionFailed,
+};
+use chrono::{DateTime, NaiveDate, TimeZone as _, Utc};
+use serde_json::Value as JsonValue;
+use shared::TimeZone;
+use snafu::Snafu;
+use std::{borrow::Cow, io};
+
+/// Formats of naive timestamps, which are read in the time zone of their column.
+const NAIVE_TIMESTAMP_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+#[derive(Debug, Snafu)]
+pub enum ConversionError {
+    #[snafu(display("expected {}, found {}", expected, found))]
+    Mismatch {
+        expected: &'static str,
+        found: String,
+    },
+    #[snafu(display("unable to parse {:?} as {}", value, expected))]
+    Parse {
+        value: String,
+        expected: &'static str,
+    },
+    #[snafu(display("{} is out of the range of {}", value, target))]
+    OutOfRange { value: String, target: &'static str },
+    #[snafu(display("{} bytes don't fit in FixedString({})", length, size))]
+    TooLong { length: usize, size: usize },
+}
+
+fn mismatch(expected: &'static str, value: &Value) -> ConversionError {
+    ConversionError::Mismatch {
+        expected,
+        found: value.kind().to_owned(),
+    }
+}
+
+/// Writes the events as `JSONEachRow`. The fields of the table's columns are converted to the
+/// type of their column, other fields are written as is.
+pub fn encode_json(
+    schema: &Schema,
+    events: Vec<LogEvent>,
+    writer: &mut dyn io::Write,
+) -> io::Result<()> {
+    for log in events {
+        let (fields, _) = log.into_parts();
+        let mut row = serde_json::Map::new();
+        for (name, value) in fields {
+            let value = match schema.get(&name) {
+                Some(column) => match json_column(column, &value) {
+                    Some(value) => value,
+                    // Omitted fields get the default value of their column.
+                    None => continue,
+                },
+                None => serde_json::to_value(&value)?,
+            };
+            row.insert(name, value);
+        }
+        serde_json::to_writer(&mut *writer, &row)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn json_column(column: &Column, value: &Value) -> Option<JsonValue> {
+    if matches!(value, Value::Null) && !column.r#type.is_nullable() {
+        return None;
+    }
+
+    json_value(&column.r#type, value)
+        .map_err(|error| {
+            emit!(&ClickhouseColumnConversionFailed {
+                column: &column.name,
+                error: error.to_string(),
+            })
+        })
+        .ok()
+}
+
+fn json_value(r#type: &ColumnType, value: &Value) -> Result<JsonValue, ConversionError> {
+    if let Some((target, min, max, _)) = integer_type(r#type) {
+        let integer = integer(value, target, min, max)?;
+        return Ok(if integer < 0 {
+            JsonValue::from(integer as i64)
+        } else {
+            JsonValue::from(integer as u64)
+        });
+    }
+
+    Ok(match r#type {
+        ColumnType::String | ColumnType::FixedString(_) => {
+            JsonValue::String(String::from_utf8_lossy(&string(r#type, value)?).into_owned())
+        }
+        ColumnType::Float32 | ColumnType::Float64 => {
+            let float = float(value)?;
+            serde_json::Number::from_f64(float)
+                .map(JsonValue::Number)
+                .ok_or_else(|| ConversionError::OutOfRange {
+                    value: float.to_string(),
+                    target: "JSON",
+                })?
+        }
+        ColumnType::Bool => JsonValue::Bool(boolean(value)?),
+        ColumnType::Date => JsonValue::String(date(value)?.format("%Y-%m-%d").to_string()),
+        ColumnType::DateTime(timezone) => JsonValue::from(datetime(value, timezone)?),
+        ColumnType::DateTime64(precision, timezone) => {
+            JsonValue::from(datetime64(value, *precision, timezone)?)
+        }
+        ColumnType::Array(item) => match value {
+            Value::Array(items) => JsonValue::Array(
+                items
+                    .iter()
+                    .map(|value| json_value(item, value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(mismatch("array", value)),
+        },
+        ColumnType::Map(key, item) => match value {
+            Value::Map(map) => {
+                let mut object = serde_json::Map::new();
+                for (name, value) in map {
+                    // JSON objects only have string keys, which ClickHouse parses as the
+                    // type of the map's keys.
+                    let name = match json_value(key, &Value::from(name.as_str()))? {
+                        JsonValue::String(name) => name,
+                        name => name.to_string(),
+                    };
+                    object.insert(name, json_value(item, value)?);
+                }
+                JsonValue::Object(object)
+            }
+            _ => return Err(mismatch("map", value)),
+        },
+        ColumnType::Nullable(inner) => match value {
+            Value::Null => JsonValue::Null,
+            _ => json_value(inner, value)?,
+        },
+        ColumnType::LowCardinality(inner) => json_value(inner, value)?,
+        ColumnType::Other(_) => serde_json::to_value(value).unwrap_or(JsonValue::Null),
+        _ => unreachable!("Integer types are handled above."),
+    })
+}
+
+/// Writes the events as `RowBinary`, with the columns of the table in order. Fields without
+/// a column are not written, and missing fields get the zero value of their column's type.
+pub fn encode_row_binary(
+    schema: &Schema,
+    events: Vec<LogEvent>,
+    writer: &mut dyn io::Write,
+) -> io::Result<()> {
+    let mut row = Vec::new();
+    for log in events {
+        row.clear();
+        for column in schema.columns() {
+            let value = log
+                .as_map()
+                .get(&column.name)
+                .filter(|value| !matches!(value, Value::Null));
+            let start = row.len();
+            if let Err(error) = write_binary(&column.r#type, value, &mut row) {
+                emit!(&ClickhouseColumnConversionFailed {
+                    column: &column.name,
+                    error: error.to_string(),
+                });
+                row.truncate(start);
+                write_binary(&column.r#type, None, &mut row)
+                    .expect("Zero values are always valid.");
+            }
+        }
+        writer.write_all(&row)?;
+    }
+    Ok(())
+}
+
+fn write_binary(
+    r#type: &ColumnType,
+    value: Option<&Value>,
+    buffer: &mut Vec<u8>,
+) -> Result<(), ConversionError> {
+    match (r#type, value) {
+        (ColumnType::Nullable(_), None) | (ColumnType::Nullable(_), Some(Value::Null)) => {
+            buffer.push(1)
+        }
+        (ColumnType::Nullable(inner), Some(value)) => {
+            buffer.push(0);
+            write_binary(inner, Some(value), buffer)?;
+        }
+        (ColumnType::LowCardinality(inner), value) => write_binary(inner, value, buffer)?,
+        (r#type, None) => write_zero(r#type, buffer),
+        (r#type, Some(value)) => write_value(r#type, value, buffer)?,
+    }
+    Ok(())
+}
+
+fn write_value(
+    r#type: &ColumnType,
+    value: &Value,
+    buffer: &mut Vec<u8>,
+) -> Result<(), ConversionError> {
+    if let Some((target, min, max, size)) = integer_type(r#type) {
+        // Integers in range are truncated to their little-endian two's complement.
+        let integer = integer(value, target, min, max)?;
+        buffer.extend_from_slice(&integer.to_le_bytes()[..size]);
+        return Ok(());
+    }
+
+    match r#type {
+        ColumnType::String => {
+            let string = string(r#type, value)?;
+            write_varint(string.len(), buffer);
+            buffer.extend_from_slice(&string);
+        }
+        ColumnType::FixedString(size) => {
+            let start = buffer.len();
+            buffer.extend_from_slice(&string(r#type, value)?);
+            buffer.resize(start + size, 0);
+        }
+        ColumnType::Float32 => buffer.extend_from_slice(&(float(value)? as f32).to_le_bytes()),
+        ColumnType::Float64 => buffer.extend_from_slice(&float(value)?.to_le_bytes()),
+        ColumnType::Bool => buffer.push(boolean(value)? as u8),
+        ColumnType::Date => {
+            let days = (date(value)? - NaiveDate::from_ymd(1970, 1, 1)).num_days();
+            let days = u16::try_from(days).map_err(|_| ConversionError::OutOfRange {
+                value: days.to_string(),
+                target: "Date",
+            })?;
+            buffer.extend_from_slice(&days.to_le_bytes());
+        }
+        ColumnType::DateTime(timezone) => {
+            buffer.extend_from_slice(&datetime(value, timezone)?.to_le_bytes())
+        }
+        ColumnType::DateTime64(precision, timezone) => {
+            buffer.extend_from_slice(&datetime64(value, *precision, timezone)?.to_le_bytes())
+        }
+        ColumnType::Array(item) => match value {
+            Value::Array(items) => {
+                write_varint(items.len(), buffer);
+                for value in items {
+                    write_binary(item, Some(value), buffer)?;
+                }
+            }
+            _ => return Err(mismatch("array", value)),
+        },
+        ColumnType::Map(key, item) => match value {
+            Value::Map(map) => {
+                write_varint(map.len(), buffer);
+                for (name, value) in map {
+                    write_binary(key, Some(&Value::from(name.as_str())), buffer)?;
+                    write_binary(item, Some(value), buffer)?;
+                }
+            }
+            _ => return Err(mismatch("map", value)),
+        },
+        ColumnType::Nullable(_) | ColumnType::LowCardinality(_) => {
+            write_binary(r#type, Some(value), buffer)?
+        }
+        ColumnType::Other(_) => unreachable!("Unsupported types are rejected with the schema."),
+        _ => unreachable!("Integer types are handled above."),
+    }
+    Ok(())
+}
+
+fn write_zero(r#type: &ColumnType, buffer: &mut Vec<u8>) {
+    let size = match r#type {
+        // Empty strings, arrays and maps only hold their length.
+        ColumnType::String | ColumnType::Array(_) | ColumnType::Map(_, _) => 1,
+        ColumnType::FixedString(size) => *size,
+        ColumnType::Bool => 1,
+        ColumnType::Float32 => 4,
+        ColumnType::Float64 => 8,
+        ColumnType::Date => 2,
+        ColumnType::DateTime(_) => 4,
+        ColumnType::DateTime64(_, _) => 8,
+        ColumnType::Nullable(_) | ColumnType::LowCardinality(_) => {
+            return write_binary(r#type, None, buffer).expect("Zero values are always valid.")
+        }
+        ColumnType::Other(_) => unreachable!("Unsupported types are rejected with the schema."),
+        r#type => {
+            integer_type(r#type)
+                .expect("Remaining types are integers.")
+                .3
+        }
+    };
+    buffer.resize(buffer.len() + size, 0);
+}
+
+fn write_varint(mut value: usize, buffer: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// The name, bounds and size in bytes of integer types.
+fn integer_type(r#type: &ColumnType) -> Option<(&'static str, i128, i128, usize)> {
+    Some(match r#type {
+        ColumnType::Int8 => ("Int8", i8::MIN.into(), i8::MAX.into(), 1),
+        ColumnType::Int16 => ("Int16", i16::MIN.into(), i16::MAX.into(), 2),
+        ColumnType::Int32 => ("Int32", i32::MIN.into(), i32::MAX.into(), 4),
+        ColumnType::Int64 => ("Int64", i64::MIN.into(), i64::MAX.into(), 8),
+        ColumnType::UInt8 => ("UInt8", 0, u8::MAX.into(), 1),
+        ColumnType::UInt16 => ("UInt16", 0, u16::MAX.into(), 2),
+        ColumnType::UInt32 => ("UInt32", 0, u32::MAX.into(), 4),
+        ColumnType::UInt64 => ("UInt64", 0, u64::MAX.into(), 8),
+        _ => return None,
+    })
+}
+
+fn integer(
+    value: &Value,
+    target: &'static str,
+    min: i128,
+    max: i128,
+) -> Result<i128, ConversionError> {
+    let integer = match value {
+        Value::Integer(integer) => i128::from(*integer),
+        Value::Float(float) if float.is_finite() && float.fract() == 0.0 => *float as i128,
+        Value::Boolean(boolean) => i128::from(*boolean),
+        Value::Bytes(bytes) => {
+            let string = String::from_utf8_lossy(bytes);
+            string.trim().parse().map_err(|_| ConversionError::Parse {
+                value: string.to_string(),
+                expected: target,
+            })?
+        }
+        _ => return Err(mismatch(target, value)),
+    };
+
+    if (min..=max).contains(&integer) {
+        Ok(integer)
+    } else {
+        Err(ConversionError::OutOfRange {
+            value: integer.to_string(),
+            target,
+        })
+    }
+}
+
+fn float(value: &Value) -> Result<f64, ConversionError> {
+    match value {
+        Value::Float(float) => Ok(*float),
+        Value::Integer(integer) => Ok(*integer as f64),
+        Value::Bytes(bytes) => {
+            let string = String::from_utf8_lossy(bytes);
+            string.trim().parse().map_err(|_| ConversionError::Parse {
+                value: string.to_string(),
+                expected: "float",
+            })
+        }
+        _ => Err(mismatch("float", value)),
+    }
+}
+
+fn boolean(value: &Value) -> Result<bool, ConversionError> {
+    match value {
+        Value::Boolean(boolean) => Ok(*boolean),
+        Value::Integer(0) => Ok(false),
+        Value::Integer(1) => Ok(true),
+        Value::Bytes(bytes) => match bytes.as_ref() {
+            b"true" | b"1" => Ok(true),
+            b"false" | b"0" => Ok(false),
+            _ => Err(ConversionError::Parse {
+                value: String::from_utf8_lossy(bytes).into_owned(),
+                expected: "boolean",
+            }),
+        },
+        _ => Err(mismatch("boolean", value)),
+    }
+}
+
+/// Strings are written as is, other values as their text representation; maps and arrays
+/// as JSON.
+fn string<'a>(r#type: &ColumnType, value: &'a Value) -> Result<Cow<'a, [u8]>, ConversionError> {
+    let string = match value {
+        Value::Bytes(bytes) => Cow::Borrowed(bytes.as_ref()),
+        Value::Null => return Err(mismatch("string", value)),
+        _ => Cow::Owned(value.to_string_lossy().into_bytes()),
+    };
+
+    match r#type {
+        ColumnType::FixedString(size) if string.len() > *size => Err(ConversionError::TooLong {
+            length: string.len(),
+            size: *size,
+        }),
+        _ => Ok(string),
+    }
+}
+
+fn timestamp(value: &Value, timezone: &Option<TimeZone>) -> Result<DateTime<Utc>, ConversionError> {
+    let out_of_range = |value: &dyn ToString| ConversionError::OutOfRange {
+        value: value.to_string(),
+        target: "timestamp",
+    };
+
+    match value {
+        Value::Timestamp(timestamp) => Ok(*timestamp),
+        // Numbers are seconds since the Unix epoch.
+        Value::Integer(seconds) => Utc
+            .timestamp_opt(*seconds, 0)
+            .single()
+            .ok_or_else(|| out_of_range(seconds)),
+        Value::Float(seconds) if seconds.is_finite() => {
+            let whole = seconds.floor();
+            let nanos = ((seconds - whole) * 1e9) as u32;
+            Utc.timestamp_opt(whole as i64, nanos)
+                .single()
+                .ok_or_else(|| out_of_range(seconds))
+        }
+        Value::Bytes(bytes) => {
+            let string = String::from_utf8_lossy(bytes);
+            parse_timestamp(string.trim(), timezone).ok_or_else(|| ConversionError::Parse {
+                value: string.to_string(),
+                expected: "timestamp",
+            })
+        }
+        _ => Err(mismatch("timestamp", value)),
+    }
+}
+
+/// Parses RFC 3339 timestamps, or naive ones in the time zone of the column, UTC if it has none.
+fn parse_timestamp(string: &str, timezone: &Option<TimeZone>) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(string) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    NAIVE_TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| match timezone {
+            Some(timezone) => timezone.datetime_from_str(string, format).ok(),
+            None => Utc.datetime_from_str(string, format).ok(),
+        })
+}
+
+fn date(value: &Value) -> Result<NaiveDate, ConversionError> {
+    if let Value::Bytes(bytes) = value {
+        if let Ok(date) =
+            NaiveDate::parse_from_str(String::from_utf8_lossy(bytes).trim(), "%Y-%m-%d")
+        {
+            return Ok(date);
+        }
+    }
+    Ok(timestamp(value, &None)?.naive_utc().date())
+}
+
+/// Seconds since the Unix epoch, which doesn't depend on the time zone of the column.
+fn datetime(value: &Value, timezone: &Option<TimeZone>) -> Result<u32, ConversionError> {
+    let seconds = timestamp(value, timezone)?.timestamp();
+    u32::try_from(seconds).map_err(|_| ConversionError::OutOfRange {
+        value: seconds.to_string(),
+        target: "DateTime",
+    })
+}
+
+/// Ticks of `10^-precision` seconds since the Unix epoch.
+fn datetime64(
+    value: &Value,
+    precision: u32,
+    timezone: &Option<TimeZone>,
+) -> Result<i64, ConversionError> {
+    let timestamp = timestamp(value, timezone)?;
+    timestamp
+        .timestamp()
+        .checked_mul(10i64.pow(precision))
+        .and_then(|ticks| {
+            ticks.checked_add(
+                i64::from(timestamp.timestamp_subsec_nanos()) / 10i64.pow(9 - precision),
+            )
+        })
+        .ok_or_else(|| ConversionError::OutOfRange {
+            value: timestamp.to_rfc3339(),
+            target: "DateTime64",
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone as _;
+    use serde_json::json;
+    use shared::btreemap;
+
+    fn schema(columns: &[(&str, &str)]) -> Schema {
+        Schema::new(
+            columns
+                .iter()
+                .map(|(name, r#type)| Column {
+                    name: (*name).to_owned(),
+                    r#type: ColumnType::parse(r#type),
+                })
+                .collect(),
+        )
+    }
+
+    fn event(fields: Vec<(&str, Value)>) -> LogEvent {
+        let mut log = LogEvent::default();
+        for (name, value) in fields {
+            log.insert(name, value);
+        }
+        log
+    }
+
+    fn encode(
+        encode: fn(&Schema, Vec<LogEvent>, &mut dyn io::Write) -> io::Result<()>,
+        schema: &Schema,
+        log: LogEvent,
+    ) -> Vec<u8> {
+        let mut output = Vec::new();
+        encode(schema, vec![log], &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn converts_json_columns() {
+        let schema = schema(&[
+            ("timestamp", "DateTime64(3, 'UTC')"),
+            ("local", "DateTime('Europe/Paris')"),
+            ("status", "LowCardinality(Nullable(UInt16))"),
+            ("tags", "Map(String, Array(Int32))"),
+            ("missing", "Nullable(String)"),
+        ]);
+        let log = event(vec![
+            (
+                "timestamp",
+                Value::from(Utc.ymd(2021, 2, 3).and_hms_milli(4, 5, 6, 789)),
+            ),
+            ("local", Value::from("2021-02-03 04:05:06")),
+            ("status", Value::from("200")),
+            ("tags", Value::Map(btreemap! { "a" => vec![1, 2] })),
+            ("message", Value::from("hello")),
+        ]);
+
+        let output = encode(encode_json, &schema, log);
+        assert_eq!(
+            serde_json::from_slice::<JsonValue>(&output).unwrap(),
+            json!({
+                "local": 1612321506,
+                "message": "hello",
+                "status": 200,
+                "tags": { "a": [1, 2] },
+                "timestamp": 1612325106789i64,
+            })
+        );
+        assert!(output.ends_with(b"\n"));
+    }
+
+    #[test]
+    fn omits_json_conversion_failures() {
+        let schema = schema(&[("status", "UInt8"), ("empty", "String")]);
+        let log = event(vec![
+            ("status", Value::from(300)),
+            ("empty", Value::Null),
+            ("message", Value::from("hello")),
+        ]);
+
+        let output = encode(encode_json, &schema, log);
+        assert_eq!(
+            serde_json::from_slice::<JsonValue>(&output).unwrap(),
+            json!({ "message": "hello" })
+        );
+    }
+
+    #[test]
+    fn encodes_row_binary() {
+        let schema = schema(&[
+            ("message", "String"),
+            ("code", "FixedString(4)"),
+            ("status", "Nullable(Int32)"),
+            ("items", "Array(UInt8)"),
+            ("timestamp", "DateTime"),
+            ("day", "Date"),
+            ("missing", "Int16"),
+        ]);
+        let log = event(vec![
+            ("message", Value::from("hi")),
+            ("code", Value::from("ab")),
+            ("status", Value::from(-2)),
+            ("items", Value::from(vec![1, 2])),
+            ("timestamp", Value::from(1612321506)),
+            ("day", Value::from("1970-01-03")),
+            ("unknown", Value::from("ignored")),
+        ]);
+
+        let mut expected = vec![2, b'h', b'i', b'a', b'b', 0, 0, 0, 0];
+        expected.extend_from_slice(&(-2i32).to_le_bytes());
+        expected.extend_from_slice(&[2, 1, 2]);
+        expected.extend_from_slice(&1612321506u32.to_le_bytes());
+        expected.extend_from_slice(&[2, 0, 0, 0]);
+        assert_eq!(encode(encode_row_binary, &schema, log), expected);
+    }
+
+    #[test]
+    fn writes_zero_values_on_binary_conversion_failures() {
+        let schema = schema(&[
+            ("code", "FixedString(2)"),
+            ("status", "Nullable(UInt8)"),
+            ("items", "Array(Int8)"),
+        ]);
+        let log = event(vec![
+            ("code", Value::from("too long")),
+            ("status", Value::from("unknown")),
+            ("items", Value::from(vec![1, 1000])),
+        ]);
+
+        assert_eq!(encode(encode_row_binary, &schema, log), vec![0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn converts_timestamps() {
+        let expected = Utc.ymd(2021, 2, 3).and_hms_milli(3, 5, 6, 500);
+        let paris = TimeZone::parse("Europe/Paris");
+        for value in [
+            Value::from("2021-02-03T03:05:06.5Z"),
+            Value::from("2021-02-03 04:05:06.5"),
+            Value::from(1612321506.5),
+        ] {
+            assert_eq!(timestamp(&value, &paris).unwrap(), expected);
+        }
+        assert_eq!(
+            datetime64(&Value::from(expected), 6, &None).unwrap(),
+            1612321506500000
+        );
+    }
+}
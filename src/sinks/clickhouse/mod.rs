@@ -1,13 +1,20 @@
+mod encoder;
+mod schema;
+
+use self::schema::{Schema, SchemaCache, SchemaError};
 use crate::{
     config::{DataType, SinkConfig, SinkContext, SinkDescription},
-    event::Event,
+    event::{Event, LogEvent},
     http::{Auth, HttpClient, HttpError, MaybeAuth},
+    internal_events::TemplateRenderingFailed,
     sinks::util::{
         encoding::{EncodingConfigWithDefault, EncodingConfiguration},
-        http::{BatchedHttpSink, HttpRetryLogic, HttpSink},
+        http::{HttpRetryLogic, HttpSink, PartitionHttpSink},
         retries::{RetryAction, RetryLogic},
-        sink, BatchConfig, Buffer, Compression, TowerRequestConfig, UriSerde,
+        BatchConfig, Compression, Compressor, PartitionBuffer, PartitionInnerBuffer,
+        TowerRequestConfig, UriSerde, VecBuffer,
     },
+    template::Template,
     tls::{TlsOptions, TlsSettings},
 };
 use bytes::Bytes;
@@ -16,6 +23,7 @@ use http::{Request, StatusCode, Uri};
 use hyper::Body;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
+use std::sync::Arc;
 
 use super::util::batch::RealtimeSizeBasedDefaultBatchSettings;
 
@@ -25,9 +33,11 @@ pub struct ClickhouseConfig {
     // Deprecated name
     #[serde(alias = "host")]
     pub endpoint: UriSerde,
-    pub table: String,
+    pub table: Template,
     pub database: Option<String>,
     #[serde(default)]
+    pub format: Format,
+    #[serde(default)]
     pub skip_unknown_fields: bool,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
@@ -58,6 +68,25 @@ pub enum Encoding {
     Default,
 }
 
+/// The formats rows are inserted in.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Format {
+    #[derivative(Default)]
+    JsonEachRow,
+    RowBinary,
+}
+
+impl Format {
+    const fn content_type(self) -> &'static str {
+        match self {
+            Self::JsonEachRow => "application/x-ndjson",
+            Self::RowBinary => "application/octet-stream",
+        }
+    }
+}
+
 #[async_trait::async_trait]
 #[typetag::serde(name = "clickhouse")]
 impl SinkConfig for ClickhouseConfig {
@@ -70,25 +99,48 @@ impl SinkConfig for ClickhouseConfig {
         let tls_settings = TlsSettings::from_options(&self.tls)?;
         let client = HttpClient::new(tls_settings, &cx.proxy)?;
 
-        let config = ClickhouseConfig {
-            auth: self.auth.choose_one(&self.endpoint.auth)?,
-            ..self.clone()
+        let auth = self.auth.choose_one(&self.endpoint.auth)?;
+        let database = self
+            .database
+            .clone()
+            .unwrap_or_else(|| "default".to_owned());
+        let schemas = SchemaCache::new(
+            client.clone(),
+            self.endpoint.uri.clone(),
+            database.clone(),
+            auth.clone(),
+        );
+
+        let schemas = Arc::new(schemas);
+
+        let sink = ClickhouseSink {
+            endpoint: self.endpoint.clone(),
+            database,
+            table: self.table.clone(),
+            format: self.format,
+            skip_unknown_fields: self.skip_unknown_fields,
+            compression: self.compression,
+            encoding: self.encoding.clone(),
+            auth,
+            schemas: Arc::clone(&schemas),
         };
 
-        let sink = BatchedHttpSink::with_logic(
-            config.clone(),
-            Buffer::new(batch.size, self.compression),
-            ClickhouseRetryLogic::default(),
+        let healthcheck = healthcheck(client.clone(), sink.clone()).boxed();
+
+        let sink = PartitionHttpSink::with_retry_logic(
+            sink,
+            PartitionBuffer::new(VecBuffer::new(batch.size)),
+            ClickhouseRetryLogic {
+                inner: HttpRetryLogic,
+                schemas,
+            },
             request,
             batch.timeout,
-            client.clone(),
+            client,
             cx.acker(),
-            sink::StdServiceLogic::default(),
         )
         .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
 
-        let healthcheck = healthcheck(client, config).boxed();
-
         Ok((super::VectorSink::Sink(Box::new(sink)), healthcheck))
     }
 
@@ -101,43 +153,90 @@ impl SinkConfig for ClickhouseConfig {
     }
 }
 
+#[derive(Clone)]
+struct ClickhouseSink {
+    endpoint: UriSerde,
+    database: String,
+    table: Template,
+    format: Format,
+    skip_unknown_fields: bool,
+    compression: Compression,
+    encoding: EncodingConfigWithDefault<Encoding>,
+    auth: Option<Auth>,
+    schemas: Arc<SchemaCache>,
+}
+
+impl ClickhouseSink {
+    async fn schema(&self, table: &str) -> crate::Result<Arc<Schema>> {
+        let schema = match self.schemas.get(table).await {
+            Ok(schema) => schema,
+            // Connection errors are retried like those of inserts.
+            Err(SchemaError::QueryFailed { source, .. }) => return Err(source.into()),
+            Err(error) => return Err(error.into()),
+        };
+
+        if self.format == Format::RowBinary {
+            if let Some(column) = schema
+                .columns()
+                .iter()
+                .find(|column| !column.r#type.supports_row_binary())
+            {
+                return Err(SchemaError::UnsupportedType {
+                    table: table.to_owned(),
+                    column: column.name.clone(),
+                }
+                .into());
+            }
+        }
+
+        Ok(schema)
+    }
+}
+
 #[async_trait::async_trait]
-impl HttpSink for ClickhouseConfig {
-    type Input = Vec<u8>;
-    type Output = Vec<u8>;
+impl HttpSink for ClickhouseSink {
+    type Input = PartitionInnerBuffer<LogEvent, String>;
+    type Output = PartitionInnerBuffer<Vec<LogEvent>, String>;
 
     fn encode_event(&self, mut event: Event) -> Option<Self::Input> {
-        self.encoding.apply_rules(&mut event);
-        let log = event.into_log();
+        let table = self
+            .table
+            .render_string(&event)
+            .map_err(|error| {
+                emit!(&TemplateRenderingFailed {
+                    error,
+                    field: Some("table"),
+                    drop_event: true,
+                });
+            })
+            .ok()?;
 
-        let mut body = serde_json::to_vec(&log).expect("Events should be valid json!");
-        body.push(b'\n');
+        self.encoding.apply_rules(&mut event);
 
-        Some(body)
+        Some(PartitionInnerBuffer::new(event.into_log(), table))
     }
 
-    async fn build_request(&self, events: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
-        let database = if let Some(database) = &self.database {
-            database.as_str()
-        } else {
-            "default"
-        };
+    async fn build_request(&self, output: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
+        let (events, table) = output.into_parts();
+        let schema = self.schema(&table).await?;
 
-        let uri = set_uri_query(
-            &self.endpoint.uri,
-            database,
-            &self.table,
-            self.skip_unknown_fields,
-        )
-        .expect("Unable to encode uri");
+        let mut body = Compressor::from(self.compression);
+        match self.format {
+            Format::JsonEachRow => encoder::encode_json(&schema, events, &mut body)?,
+            Format::RowBinary => encoder::encode_row_binary(&schema, events, &mut body)?,
+        }
 
-        let mut builder = Request::post(&uri).header("Content-Type", "application/x-ndjson");
+        let query = insert_query(&self.database, &table, self.format, &schema);
+        let uri = set_uri_query(&self.endpoint.uri, &query, self.skip_unknown_fields)
+            .expect("Unable to encode uri");
+
+        let mut builder = Request::post(&uri).header("Content-Type", self.format.content_type());
 
         if let Some(ce) = self.compression.content_encoding() {
             builder = builder.header("Content-Encoding", ce);
         }
 
-        let mut request = builder.body(events).unwrap();
+        let mut request = builder.body(body.finish()?).unwrap();
 
         if let Some(auth) = &self.auth {
             auth.apply(&mut request);
@@ -147,34 +246,59 @@ impl HttpSink for ClickhouseConfig {
     }
 }
 
-async fn healthcheck(client: HttpClient, config: ClickhouseConfig) -> crate::Result<()> {
-    // TODO: check if table exists?
-    let uri = format!("{}/?query=SELECT%201", config.endpoint);
+async fn healthcheck(client: HttpClient, sink: ClickhouseSink) -> crate::Result<()> {
+    let uri = format!("{}/?query=SELECT%201", sink.endpoint);
     let mut request = Request::get(uri).body(Body::empty()).unwrap();
 
-    if let Some(auth) = &config.auth {
+    if let Some(auth) = &sink.auth {
         auth.apply(&mut request);
     }
 
     let response = client.send(request).await?;
 
     match response.status() {
-        StatusCode::OK => Ok(()),
-        status => Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
+        StatusCode::OK => {}
+        status => return Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
+    }
+
+    // Tables rendered from events are only known once events come in.
+    if !sink.table.is_dynamic() {
+        sink.schema(sink.table.get_ref()).await?;
     }
+
+    Ok(())
 }
 
-fn set_uri_query(uri: &Uri, database: &str, table: &str, skip_unknown: bool) -> crate::Result<Uri> {
-    let query = url::form_urlencoded::Serializer::new(String::new())
-        .append_pair(
-            "query",
+fn quote_identifier(identifier: &str) -> String {
+    format!(
+        "\"{}\"",
+        identifier.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn insert_query(database: &str, table: &str, format: Format, schema: &Schema) -> String {
+    let table = format!("{}.{}", quote_identifier(database), quote_identifier(table));
+    match format {
+        Format::JsonEachRow => format!("INSERT INTO {} FORMAT JSONEachRow", table),
+        // Binary rows hold the columns of the schema, in order.
+        Format::RowBinary => {
+            let columns = schema
+                .columns()
+                .iter()
+                .map(|column| quote_identifier(&column.name))
+                .collect::<Vec<_>>();
             format!(
-                "INSERT INTO \"{}\".\"{}\" FORMAT JSONEachRow",
-                database,
-                table.replace("\"", "\\\"")
+                "INSERT INTO {} ({}) FORMAT RowBinary",
+                table,
+                columns.join(", ")
             )
-            .as_str(),
-        )
+        }
+    }
+}
+
+fn set_uri_query(uri: &Uri, query: &str, skip_unknown: bool) -> crate::Result<Uri> {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("query", query)
         .finish();
 
     let mut uri = uri.to_string();
@@ -192,9 +316,10 @@ fn set_uri_query(uri: &Uri, database: &str, table: &str, skip_unknown: bool) ->
         .map_err(Into::into)
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Clone)]
 struct ClickhouseRetryLogic {
     inner: HttpRetryLogic,
+    schemas: Arc<SchemaCache>,
 }
 
 impl RetryLogic for ClickhouseRetryLogic {
@@ -206,6 +331,11 @@ impl RetryLogic for ClickhouseRetryLogic {
     }
 
     fn should_retry_response(&self, response: &Self::Response) -> RetryAction {
+        if !response.status().is_success() {
+            // Inserts may have failed because a table has been altered.
+            self.schemas.clear();
+        }
+
         match response.status() {
             StatusCode::INTERNAL_SERVER_ERROR => {
                 let body = response.body();
@@ -233,6 +363,7 @@ impl RetryLogic for ClickhouseRetryLogic {
 
 #[cfg(test)]
 mod tests {
+    use super::schema::{Column, ColumnType};
     use super::*;
 
     #[test]
@@ -242,10 +373,10 @@ mod tests {
 
     #[test]
     fn encode_valid() {
+        let schema = Schema::new(vec![]);
         let uri = set_uri_query(
             &"http://localhost:80".parse().unwrap(),
-            "my_database",
-            "my_table",
+            &insert_query("my_database", "my_table", Format::JsonEachRow, &schema),
             false,
         )
         .unwrap();
@@ -253,24 +384,77 @@ mod tests {
 
         let uri = set_uri_query(
             &"http://localhost:80".parse().unwrap(),
-            "my_database",
-            "my_\"table\"",
+            &insert_query("my_database", "my_\"table\"", Format::JsonEachRow, &schema),
             false,
         )
         .unwrap();
         assert_eq!(uri.to_string(), "http://localhost:80/?input_format_import_nested_json=1&query=INSERT+INTO+%22my_database%22.%22my_%5C%22table%5C%22%22+FORMAT+JSONEachRow");
+
+        assert_eq!(
+            insert_query(r#"my"db\"#, "my_table", Format::JsonEachRow, &schema),
+            r#"INSERT INTO "my\"db\\"."my_table" FORMAT JSONEachRow"#
+        );
     }
 
     #[test]
     fn encode_invalid() {
         set_uri_query(
             &"localhost:80".parse().unwrap(),
-            "my_database",
-            "my_table",
+            &insert_query(
+                "my_database",
+                "my_table",
+                Format::JsonEachRow,
+                &Schema::new(vec![]),
+            ),
             false,
         )
         .unwrap_err();
     }
+
+    #[test]
+    fn row_binary_insert_lists_columns() {
+        let schema = Schema::new(vec![
+            Column {
+                name: "timestamp".to_owned(),
+                r#type: ColumnType::parse("DateTime64(3)"),
+            },
+            Column {
+                name: "message".to_owned(),
+                r#type: ColumnType::String,
+            },
+        ]);
+        assert_eq!(
+            insert_query("default", "logs", Format::RowBinary, &schema),
+            r#"INSERT INTO "default"."logs" ("timestamp", "message") FORMAT RowBinary"#
+        );
+    }
+
+    #[test]
+    fn renders_table_per_event() {
+        let sink = ClickhouseSink {
+            endpoint: "http://localhost:8123".parse().unwrap(),
+            database: "default".to_owned(),
+            table: Template::try_from("logs_{{ service }}").unwrap(),
+            format: Format::JsonEachRow,
+            skip_unknown_fields: false,
+            compression: Compression::None,
+            encoding: Default::default(),
+            auth: None,
+            schemas: Arc::new(SchemaCache::new(
+                HttpClient::new(None, &Default::default()).unwrap(),
+                "http://localhost:8123".parse().unwrap(),
+                "default".to_owned(),
+                None,
+            )),
+        };
+
+        let mut event = Event::from("hello");
+        event.as_mut_log().insert("service", "api");
+        let (_, table) = sink.encode_event(event).unwrap().into_parts();
+        assert_eq!(table, "logs_api");
+
+        assert!(sink.encode_event(Event::from("hello")).is_none());
+    }
 }
 
 #[cfg(test)]
@@ -309,7 +493,7 @@ mod integration_tests {
 
         let config = ClickhouseConfig {
             endpoint: host.parse().unwrap(),
-            table: table.clone(),
+            table: table.as_str().try_into().unwrap(),
             compression: Compression::None,
             batch,
             request: TowerRequestConfig {
@@ -345,6 +529,68 @@ mod integration_tests {
         assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
     }
 
+    #[tokio::test]
+    async fn insert_events_row_binary() {
+        trace_init();
+
+        let table = gen_table();
+        let host = String::from("http://localhost:8123");
+
+        let mut batch = BatchConfig::default();
+        batch.max_events = Some(1);
+
+        let config = ClickhouseConfig {
+            endpoint: host.parse().unwrap(),
+            table: "{{ table }}".try_into().unwrap(),
+            format: Format::RowBinary,
+            batch,
+            request: TowerRequestConfig {
+                retry_attempts: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let client = ClickhouseClient::new(host);
+        client
+            .create_table(
+                &table,
+                "host String, timestamp DateTime64(3, 'UTC'), message String, \
+                 status Nullable(UInt16), tags Map(String, String)",
+            )
+            .await;
+
+        let (sink, _hc) = config.build(SinkContext::new_test()).await.unwrap();
+
+        let (mut input_event, mut receiver) = make_event();
+        let log = input_event.as_mut_log();
+        log.insert("table", table.clone());
+        log.insert("status", "200");
+        log.insert("tags.region", "eu");
+
+        components::run_sink_event(sink, input_event.clone(), &HTTP_SINK_TAGS).await;
+
+        let output = client.select_all(&table).await;
+        assert_eq!(1, output.rows);
+
+        let timestamp = input_event
+            .as_log()
+            .get(log_schema().timestamp_key())
+            .unwrap()
+            .as_timestamp()
+            .unwrap()
+            .format("%Y-%m-%d %H:%M:%S%.3f")
+            .to_string();
+        let row = &output.data[0];
+        assert_eq!(row["host"], "example.com");
+        assert_eq!(row["message"], "raw log line");
+        assert_eq!(row["timestamp"], timestamp.as_str());
+        assert_eq!(row["status"], 200);
+        assert_eq!(row["tags"]["region"], "eu");
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+    }
+
     #[tokio::test]
     async fn skip_unknown_fields() {
         trace_init();
@@ -357,7 +603,7 @@ mod integration_tests {
 
         let config = ClickhouseConfig {
             endpoint: host.parse().unwrap(),
-            table: table.clone(),
+            table: table.as_str().try_into().unwrap(),
             skip_unknown_fields: true,
             compression: Compression::None,
             batch,
@@ -406,7 +652,7 @@ mod integration_tests {
 
         let config = ClickhouseConfig {
             endpoint: host.parse().unwrap(),
-            table: table.clone(),
+            table: table.as_str().try_into().unwrap(),
             compression: Compression::None,
             encoding,
             batch,
@@ -521,7 +767,7 @@ timestamp_format = "unix""#,
 
         let config = ClickhouseConfig {
             endpoint: host.parse().unwrap(),
-            table: table.clone(),
+            table: table.as_str().try_into().unwrap(),
             compression: Compression::None,
             batch,
             ..Default::default()
@@ -575,7 +821,7 @@ timestamp_format = "unix""#,
 
         let config = ClickhouseConfig {
             endpoint: host.parse().unwrap(),
-            table: gen_table(),
+            table: gen_table().try_into().unwrap(),
             batch,
             ..Default::default()
         };
@@ -0,0 +1,419 @@
+//! Discovery of the columns of the tables the sink inserts into.
+
+use crate::{
+    http::{Auth, HttpClient, HttpError},
+    internal_events::ClickhouseColumnsDiscovered,
+};
+use http::{Request, StatusCode, Uri};
+use hyper::Body;
+use serde::Deserialize;
+use shared::TimeZone;
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::OnceCell;
+
+#[derive(Debug, Snafu)]
+pub enum SchemaError {
+    #[snafu(display("invalid uri for the columns of table {:?}: {}", table, source))]
+    InvalidUri {
+        source: http::uri::InvalidUri,
+        table: String,
+    },
+    #[snafu(display("failed to query columns of table {:?}: {}", table, source))]
+    QueryFailed { source: HttpError, table: String },
+    #[snafu(display("failed to read columns of table {:?}: {}", table, source))]
+    ReadBody { source: hyper::Error, table: String },
+    #[snafu(display(
+        "failed to query columns of table {:?}: unexpected status {}",
+        table,
+        status
+    ))]
+    UnexpectedStatus { status: StatusCode, table: String },
+    #[snafu(display("invalid columns of table {:?}: {}", table, source))]
+    InvalidColumns {
+        source: serde_json::Error,
+        table: String,
+    },
+    #[snafu(display("table {:?} does not exist or has no columns", table))]
+    UnknownTable { table: String },
+    #[snafu(display(
+        "column {:?} of table {:?} has a type which can't be written in the `row_binary` format",
+        column,
+        table
+    ))]
+    UnsupportedType { table: String, column: String },
+}
+
+/// The types of ClickHouse columns. Types that need no conversion, like enums, decimals or
+/// UUIDs, are kept as `Other`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    String,
+    FixedString(usize),
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float32,
+    Float64,
+    Bool,
+    Date,
+    DateTime(Option<TimeZone>),
+    DateTime64(u32, Option<TimeZone>),
+    Array(Box<ColumnType>),
+    Map(Box<ColumnType>, Box<ColumnType>),
+    Nullable(Box<ColumnType>),
+    LowCardinality(Box<ColumnType>),
+    Other(String),
+}
+
+impl ColumnType {
+    pub fn parse(s: &str) -> Self {
+        let s = s.trim();
+        Self::parse_known(s).unwrap_or_else(|| Self::Other(s.to_owned()))
+    }
+
+    fn parse_known(s: &str) -> Option<Self> {
+        let (name, args) = match s.find('(') {
+            Some(start) if s.ends_with(')') => (&s[..start], Some(&s[start + 1..s.len() - 1])),
+            _ => (s, None),
+        };
+
+        let r#type = match (name, args) {
+            ("String", None) => Self::String,
+            ("FixedString", Some(length)) => Self::FixedString(length.trim().parse().ok()?),
+            ("Int8", None) => Self::Int8,
+            ("Int16", None) => Self::Int16,
+            ("Int32", None) => Self::Int32,
+            ("Int64", None) => Self::Int64,
+            ("UInt8", None) => Self::UInt8,
+            ("UInt16", None) => Self::UInt16,
+            ("UInt32", None) => Self::UInt32,
+            ("UInt64", None) => Self::UInt64,
+            ("Float32", None) => Self::Float32,
+            ("Float64", None) => Self::Float64,
+            ("Bool", None) | ("Boolean", None) => Self::Bool,
+            ("Date", None) => Self::Date,
+            ("DateTime", None) => Self::DateTime(None),
+            ("DateTime", Some(timezone)) => Self::DateTime(Some(parse_timezone(timezone)?)),
+            ("DateTime64", Some(args)) => {
+                let args = split_args(args);
+                let precision = args.first()?.parse().ok().filter(|p| *p <= 9)?;
+                let timezone = match args.get(1) {
+                    Some(timezone) => Some(parse_timezone(timezone)?),
+                    None => None,
+                };
+                Self::DateTime64(precision, timezone)
+            }
+            ("Array", Some(item)) => Self::Array(Box::new(Self::parse(item))),
+            ("Map", Some(args)) => match split_args(args).as_slice() {
+                [key, value] => Self::Map(Box::new(Self::parse(key)), Box::new(Self::parse(value))),
+                _ => return None,
+            },
+            ("Nullable", Some(inner)) => Self::Nullable(Box::new(Self::parse(inner))),
+            ("LowCardinality", Some(inner)) => Self::LowCardinality(Box::new(Self::parse(inner))),
+            _ => return None,
+        };
+        Some(r#type)
+    }
+
+    pub const fn is_nullable(&self) -> bool {
+        matches!(self, Self::Nullable(_))
+    }
+
+    /// Whether values of this type can be encoded in the `RowBinary` format.
+    pub fn supports_row_binary(&self) -> bool {
+        match self {
+            Self::Array(inner) | Self::Nullable(inner) | Self::LowCardinality(inner) => {
+                inner.supports_row_binary()
+            }
+            Self::Map(key, value) => key.supports_row_binary() && value.supports_row_binary(),
+            Self::Other(_) => false,
+            _ => true,
+        }
+    }
+}
+
+fn parse_timezone(s: &str) -> Option<TimeZone> {
+    let name = s.trim().strip_prefix('\'')?.strip_suffix('\'')?;
+    match TimeZone::parse(name)? {
+        TimeZone::Local => None,
+        timezone => Some(timezone),
+    }
+}
+
+/// Splits the arguments of a type on the commas which are neither nested nor quoted.
+fn split_args(s: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth = depth.saturating_sub(1),
+            ',' if !quoted && depth == 0 => {
+                args.push(s[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(s[start..].trim());
+    args
+}
+
+#[derive(Debug)]
+pub struct Column {
+    pub name: String,
+    pub r#type: ColumnType,
+}
+
+/// The columns of a table which can be inserted into, in the order of the table.
+#[derive(Debug)]
+pub struct Schema {
+    columns: Vec<Column>,
+    indices: HashMap<String, usize>,
+}
+
+impl Schema {
+    pub fn new(columns: Vec<Column>) -> Self {
+        let indices = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| (column.name.clone(), index))
+            .collect();
+        Self { columns, indices }
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Column> {
+        self.indices.get(name).map(|index| &self.columns[*index])
+    }
+}
+
+#[derive(Deserialize)]
+struct ColumnDescription {
+    name: String,
+    r#type: String,
+    default_kind: String,
+}
+
+/// Fetches the schemas of tables on their first use and keeps them for the lifetime of the sink.
+pub struct SchemaCache {
+    client: HttpClient,
+    endpoint: Uri,
+    database: String,
+    auth: Option<Auth>,
+    /// The schema of each table, fetched by the first request needing it while the
+    /// concurrent ones wait for it.
+    schemas: Mutex<HashMap<String, Arc<OnceCell<Arc<Schema>>>>>,
+}
+
+impl SchemaCache {
+    pub fn new(client: HttpClient, endpoint: Uri, database: String, auth: Option<Auth>) -> Self {
+        Self {
+            client,
+            endpoint,
+            database,
+            auth,
+            schemas: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get(&self, table: &str) -> Result<Arc<Schema>, SchemaError> {
+        let cell = Arc::clone(
+            self.schemas
+                .lock()
+                .unwrap()
+                .entry(table.to_owned())
+                .or_default(),
+        );
+        // Failed fetches leave the cell empty, so the next request tries again.
+        let schema = cell
+            .get_or_try_init(|| async {
+                let schema = self.fetch(table).await?;
+                emit!(&ClickhouseColumnsDiscovered {
+                    table,
+                    count: schema.columns().len(),
+                });
+                Ok::<_, SchemaError>(Arc::new(schema))
+            })
+            .await?;
+        Ok(Arc::clone(schema))
+    }
+
+    /// Forgets the cached schemas, in case their tables have been altered.
+    pub fn clear(&self) {
+        self.schemas.lock().unwrap().clear();
+    }
+
+    async fn fetch(&self, table: &str) -> Result<Schema, SchemaError> {
+        let uri = columns_uri(&self.endpoint, &self.database, table)
+            .with_context(|| InvalidUri { table })?;
+        let mut request = Request::get(uri).body(Body::empty()).unwrap();
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+
+        let response = self
+            .client
+            .send(request)
+            .await
+            .with_context(|| QueryFailed { table })?;
+        if response.status() != StatusCode::OK {
+            return Err(SchemaError::UnexpectedStatus {
+                status: response.status(),
+                table: table.to_owned(),
+            });
+        }
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .with_context(|| ReadBody { table })?;
+
+        let columns = parse_columns(&body).with_context(|| InvalidColumns { table })?;
+        if columns.is_empty() {
+            return Err(SchemaError::UnknownTable {
+                table: table.to_owned(),
+            });
+        }
+        Ok(Schema::new(columns))
+    }
+}
+
+fn columns_uri(endpoint: &Uri, database: &str, table: &str) -> Result<Uri, http::uri::InvalidUri> {
+    let query = format!(
+        "SELECT name, type, default_kind FROM system.columns \
+         WHERE database = {} AND table = {} ORDER BY position FORMAT JSONEachRow",
+        quote_string(database),
+        quote_string(table)
+    );
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("query", &query)
+        .finish();
+
+    let mut uri = endpoint.to_string();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+    uri.push('?');
+    uri.push_str(&query);
+    uri.parse()
+}
+
+fn quote_string(string: &str) -> String {
+    format!("'{}'", string.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Parses the columns described by the `system.columns` query, leaving out the materialized
+/// and alias columns which can't be inserted into.
+fn parse_columns(body: &[u8]) -> Result<Vec<Column>, serde_json::Error> {
+    serde_json::Deserializer::from_slice(body)
+        .into_iter::<ColumnDescription>()
+        .filter(|description| {
+            description.as_ref().map_or(true, |description| {
+                !matches!(description.default_kind.as_str(), "MATERIALIZED" | "ALIAS")
+            })
+        })
+        .map(|description| {
+            description.map(|description| Column {
+                r#type: ColumnType::parse(&description.r#type),
+                name: description.name,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_types() {
+        use ColumnType::*;
+
+        assert_eq!(ColumnType::parse("String"), String);
+        assert_eq!(ColumnType::parse("FixedString(16)"), FixedString(16));
+        assert_eq!(
+            ColumnType::parse("DateTime64(3, 'Europe/Paris')"),
+            DateTime64(3, TimeZone::parse("Europe/Paris"))
+        );
+        assert_eq!(ColumnType::parse("DateTime64(6)"), DateTime64(6, None));
+        assert_eq!(
+            ColumnType::parse("DateTime('UTC')"),
+            DateTime(TimeZone::parse("UTC"))
+        );
+        assert_eq!(
+            ColumnType::parse("Array(Nullable(Int64))"),
+            Array(Box::new(Nullable(Box::new(Int64))))
+        );
+        assert_eq!(
+            ColumnType::parse("Map(LowCardinality(String), Array(Float64))"),
+            Map(
+                Box::new(LowCardinality(Box::new(String))),
+                Box::new(Array(Box::new(Float64)))
+            )
+        );
+        assert_eq!(
+            ColumnType::parse("Enum8('a, b' = 1, 'c' = 2)"),
+            Other("Enum8('a, b' = 1, 'c' = 2)".to_owned())
+        );
+        assert_eq!(
+            ColumnType::parse("DateTime64(3, 'Nowhere/Unknown')"),
+            Other("DateTime64(3, 'Nowhere/Unknown')".to_owned())
+        );
+    }
+
+    #[test]
+    fn row_binary_support() {
+        assert!(ColumnType::parse("Map(String, Array(Nullable(UInt8)))").supports_row_binary());
+        assert!(!ColumnType::parse("Array(Decimal(10, 2))").supports_row_binary());
+    }
+
+    #[test]
+    fn parses_insertable_columns() {
+        let body = br#"{"name":"timestamp","type":"DateTime64(3, 'UTC')","default_kind":""}
+{"name":"day","type":"Date","default_kind":"MATERIALIZED"}
+{"name":"message","type":"String","default_kind":"DEFAULT"}
+"#;
+        let schema = Schema::new(parse_columns(body).unwrap());
+
+        let names = schema
+            .columns()
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["timestamp", "message"]);
+        assert_eq!(schema.get("message").unwrap().r#type, ColumnType::String);
+        assert!(schema.get("day").is_none());
+    }
+
+    #[test]
+    fn builds_columns_uri() {
+        let uri = columns_uri(&"http://localhost:8123".parse().unwrap(), "db", "it's").unwrap();
+        let query = url::form_urlencoded::parse(uri.query().unwrap().as_bytes())
+            .find(|(name, _)| name == "query")
+            .unwrap()
+            .1;
+        assert_eq!(
+            query,
+            "SELECT name, type, default_kind FROM system.columns \
+             WHERE database = 'db' AND table = 'it\\'s' ORDER BY position FORMAT JSONEachRow"
+        );
+    }
+}
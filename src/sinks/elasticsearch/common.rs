@@ -42,15 +42,33 @@ pub struct ElasticSearchCommon {
 }
 
 impl ElasticSearchCommon {
+    /// Parses the configuration of a sink having a single endpoint.
     pub fn parse_config(config: &ElasticSearchConfig) -> crate::Result<Self> {
+        let mut commons = Self::parse_many(config)?;
+        match commons.len() {
+            1 => Ok(commons.remove(0)),
+            count => Err(ParseError::MultipleEndpoints { count }.into()),
+        }
+    }
+
+    /// Parses the configuration once for each of the endpoints.
+    pub fn parse_many(config: &ElasticSearchConfig) -> crate::Result<Vec<Self>> {
+        config
+            .endpoints()?
+            .iter()
+            .map(|endpoint| Self::parse_endpoint(config, endpoint))
+            .collect()
+    }
+
+    fn parse_endpoint(config: &ElasticSearchConfig, endpoint: &str) -> crate::Result<Self> {
         // Test the configured host, but ignore the result
-        let uri = format!("{}/_test", &config.endpoint);
-        let uri = uri.parse::<Uri>().with_context(|| InvalidHost {
-            host: &config.endpoint,
-        })?;
+        let uri = format!("{}/_test", endpoint);
+        let uri = uri
+            .parse::<Uri>()
+            .with_context(|| InvalidHost { host: endpoint })?;
         if uri.host().is_none() {
             return Err(ParseError::HostMustIncludeHostname {
-                host: config.endpoint.clone(),
+                host: endpoint.to_owned(),
             }
             .into());
         }
@@ -62,7 +80,7 @@ impl ElasticSearchCommon {
             }),
             _ => None,
         };
-        let uri = config.endpoint.parse::<UriSerde>()?;
+        let uri = endpoint.parse::<UriSerde>()?;
         let authorization = authorization.choose_one(&uri.auth)?;
         let base_url = uri.uri.to_string().trim_end_matches('/').to_owned();
 
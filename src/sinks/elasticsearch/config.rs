@@ -8,7 +8,8 @@ use crate::sinks::elasticsearch::request_builder::ElasticsearchRequestBuilder;
 use crate::sinks::elasticsearch::sink::ElasticSearchSink;
 use crate::sinks::elasticsearch::{BatchActionTemplate, IndexTemplate};
 use crate::sinks::elasticsearch::{
-    ElasticSearchAuth, ElasticSearchCommon, ElasticSearchCommonMode, ElasticSearchMode,
+    DistributionConfig, ElasticSearchAuth, ElasticSearchCommon, ElasticSearchCommonMode,
+    ElasticSearchMode, ParseError,
 };
 use crate::sinks::util::encoding::EncodingConfigFixed;
 use crate::sinks::util::http::RequestConfig;
@@ -20,7 +21,7 @@ use crate::sinks::{Healthcheck, VectorSink};
 use crate::template::Template;
 use crate::tls::TlsOptions;
 use crate::transforms::metric_to_log::MetricToLogConfig;
-use futures::FutureExt;
+use futures::{future, FutureExt, TryFutureExt};
 use indexmap::map::IndexMap;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
@@ -29,7 +30,7 @@ use std::convert::TryFrom;
 
 use crate::sinks::elasticsearch::encoder::ElasticSearchEncoder;
 use crate::sinks::elasticsearch::retry::ElasticSearchRetryLogic;
-use crate::sinks::elasticsearch::service::{ElasticSearchService, HttpRequestBuilder};
+use crate::sinks::elasticsearch::service::{ElasticSearchService, Endpoint, HttpRequestBuilder};
 use tower::ServiceBuilder;
 
 /// The field name for the timestamp required by data stream mode
@@ -38,8 +39,12 @@ pub const DATA_STREAM_TIMESTAMP_KEY: &str = "@timestamp";
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ElasticSearchConfig {
+    // Deprecated, use `endpoints` instead
     #[serde(alias = "host")]
-    pub endpoint: String,
+    pub endpoint: Option<String>,
+
+    #[serde(default)]
+    pub endpoints: Vec<String>,
 
     // Deprecated, use `bulk.action` instead
     pub bulk_action: Option<String>,
@@ -77,6 +82,9 @@ pub struct ElasticSearchConfig {
     pub bulk: Option<BulkConfig>,
     pub data_stream: Option<DataStreamConfig>,
     pub metrics: Option<MetricToLogConfig>,
+
+    #[serde(default)]
+    pub distribution: DistributionConfig,
 }
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
@@ -88,6 +96,18 @@ pub enum Encoding {
 }
 
 impl ElasticSearchConfig {
+    pub fn endpoints(&self) -> crate::Result<Vec<String>> {
+        match (&self.endpoint, self.endpoints.is_empty()) {
+            (Some(endpoint), true) => {
+                warn!("ES sink config option `endpoint` is deprecated. Use `endpoints` instead.");
+                Ok(vec![endpoint.clone()])
+            }
+            (None, false) => Ok(self.endpoints.clone()),
+            (Some(_), false) => Err(ParseError::EndpointsConflict.into()),
+            (None, true) => Err(ParseError::EndpointRequired.into()),
+        }
+    }
+
     pub fn bulk_action(&self) -> crate::Result<Option<Template>> {
         if self.bulk_action.is_some() {
             warn!("ES sink config option `bulk_action` is deprecated. Use `bulk.action` instead.");
@@ -303,7 +323,16 @@ impl DataStreamConfig {
 #[typetag::serde(name = "elasticsearch")]
 impl SinkConfig for ElasticSearchConfig {
     async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
-        let common = ElasticSearchCommon::parse_config(self)?;
+        let commons = ElasticSearchCommon::parse_many(self)?;
+        let endpoints = commons
+            .iter()
+            .map(|common| Endpoint {
+                base_url: common.base_url.clone(),
+                bulk_uri: common.bulk_uri.clone(),
+                auth: common.authorization.clone(),
+            })
+            .collect();
+        let common = commons.into_iter().next().expect("at least one endpoint");
 
         let http_client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
         let batch_settings = self.batch.into_batcher_settings()?;
@@ -323,9 +352,7 @@ impl SinkConfig for ElasticSearchConfig {
             .unwrap_with(&TowerRequestConfig::default());
 
         let http_request_builder = HttpRequestBuilder {
-            bulk_uri: common.bulk_uri,
             http_request_config: self.request.clone(),
-            http_auth: common.authorization,
             query_params: common.query_params,
             region: common.region,
             compression: self.compression,
@@ -334,7 +361,12 @@ impl SinkConfig for ElasticSearchConfig {
 
         let service = ServiceBuilder::new()
             .settings(request_limits, ElasticSearchRetryLogic)
-            .service(ElasticSearchService::new(http_client, http_request_builder));
+            .service(ElasticSearchService::new(
+                http_client,
+                http_request_builder,
+                endpoints,
+                &self.distribution,
+            ));
 
         let sink = ElasticSearchSink {
             batch_settings,
@@ -347,9 +379,16 @@ impl SinkConfig for ElasticSearchConfig {
             id_key_field: self.id_key.clone(),
         };
 
-        let common = ElasticSearchCommon::parse_config(self)?;
-        let client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
-        let healthcheck = common.healthcheck(client).boxed();
+        let commons = ElasticSearchCommon::parse_many(self)?;
+        let client = HttpClient::new(commons[0].tls_settings.clone(), cx.proxy())?;
+        // The sink is healthy as long as one of the endpoints is.
+        let healthcheck = future::select_ok(
+            commons
+                .into_iter()
+                .map(|common| common.healthcheck(client.clone()).boxed()),
+        )
+        .map_ok(|_| ())
+        .boxed();
         let stream = VectorSink::Stream(Box::new(sink));
         Ok((stream, healthcheck))
     }
@@ -405,4 +444,35 @@ mod tests {
         assert!(matches!(config.mode, ElasticSearchMode::DataStream));
         assert!(config.data_stream.is_some());
     }
+
+    #[test]
+    fn parse_endpoints() {
+        let config = toml::from_str::<ElasticSearchConfig>(
+            r#"
+            endpoints = ["http://es-1:9200", "http://es-2:9200"]
+            distribution.sniff = true
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.endpoints().unwrap(),
+            vec!["http://es-1:9200", "http://es-2:9200"]
+        );
+        assert!(config.distribution.sniff);
+        assert_eq!(config.distribution.health_check_interval_secs, 10);
+
+        let config = toml::from_str::<ElasticSearchConfig>(r#"host = "http://es:9200""#).unwrap();
+        assert_eq!(config.endpoints().unwrap(), vec!["http://es:9200"]);
+
+        let config = toml::from_str::<ElasticSearchConfig>(
+            r#"
+            endpoint = "http://es:9200"
+            endpoints = ["http://es-1:9200"]
+        "#,
+        )
+        .unwrap();
+        assert!(config.endpoints().is_err());
+
+        assert!(ElasticSearchConfig::default().endpoints().is_err());
+    }
 }
@@ -0,0 +1,410 @@
+//! Distribution of bulk requests over the nodes of the cluster.
+//!
+//! Requests are sent round-robin to the nodes that are believed to be healthy. A node is evicted
+//! after a connection failure and put back once it answers a health probe again. When sniffing
+//! is enabled, the set of nodes is periodically refreshed from `_nodes/http`.
+
+use crate::{
+    http::HttpClient,
+    internal_events::{
+        ElasticSearchNodeEvicted, ElasticSearchNodeRestored, ElasticSearchNodesSniffed,
+        ElasticSearchSniffFailed,
+    },
+    sinks::{
+        elasticsearch::service::{ElasticSearchRequest, Endpoint, HttpRequestBuilder},
+        util::http::HttpBatchService,
+    },
+};
+use futures::future::BoxFuture;
+use http::uri::{Authority, Uri};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DistributionConfig {
+    /// Discover the nodes of the cluster from the configured endpoints.
+    #[serde(default)]
+    pub sniff: bool,
+    #[serde(default = "DistributionConfig::default_sniff_interval_secs")]
+    pub sniff_interval_secs: u64,
+    #[serde(default = "DistributionConfig::default_health_check_interval_secs")]
+    pub health_check_interval_secs: u64,
+}
+
+impl Default for DistributionConfig {
+    fn default() -> Self {
+        Self {
+            sniff: false,
+            sniff_interval_secs: Self::default_sniff_interval_secs(),
+            health_check_interval_secs: Self::default_health_check_interval_secs(),
+        }
+    }
+}
+
+impl DistributionConfig {
+    const fn default_sniff_interval_secs() -> u64 {
+        300
+    }
+
+    const fn default_health_check_interval_secs() -> u64 {
+        10
+    }
+}
+
+type BatchService = HttpBatchService<
+    BoxFuture<'static, Result<http::Request<Vec<u8>>, crate::Error>>,
+    ElasticSearchRequest,
+>;
+
+pub struct Node {
+    endpoint: Arc<Endpoint>,
+    service: BatchService,
+    healthy: AtomicBool,
+}
+
+impl Node {
+    pub fn service(&self) -> BatchService {
+        self.service.clone()
+    }
+
+    fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Acquire)
+    }
+
+    /// Evicts the node until it passes a health probe.
+    pub fn evict(&self, error: &crate::Error) {
+        if self.healthy.swap(false, Ordering::AcqRel) {
+            emit!(&ElasticSearchNodeEvicted {
+                endpoint: &self.endpoint.base_url,
+                error,
+            });
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NodesResponse {
+    nodes: HashMap<String, NodeInfo>,
+}
+
+#[derive(Deserialize)]
+struct NodeInfo {
+    http: Option<NodeHttp>,
+}
+
+#[derive(Deserialize)]
+struct NodeHttp {
+    publish_address: String,
+}
+
+pub struct NodePool {
+    http_client: HttpClient,
+    request_builder: Arc<HttpRequestBuilder>,
+    nodes: RwLock<Arc<Vec<Arc<Node>>>>,
+    next: AtomicUsize,
+}
+
+impl NodePool {
+    /// Creates a pool of the given endpoints, of which there must be at least one.
+    pub fn new(
+        http_client: HttpClient,
+        request_builder: HttpRequestBuilder,
+        endpoints: Vec<Endpoint>,
+    ) -> Self {
+        assert!(!endpoints.is_empty(), "No Elasticsearch endpoints.");
+
+        let request_builder = Arc::new(request_builder);
+        let nodes = endpoints
+            .into_iter()
+            .map(|endpoint| Self::node(&http_client, &request_builder, endpoint))
+            .collect();
+
+        Self {
+            http_client,
+            request_builder,
+            nodes: RwLock::new(Arc::new(nodes)),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    fn node(
+        http_client: &HttpClient,
+        request_builder: &Arc<HttpRequestBuilder>,
+        endpoint: Endpoint,
+    ) -> Arc<Node> {
+        let endpoint = Arc::new(endpoint);
+        let request_builder = Arc::clone(request_builder);
+        let service_endpoint = Arc::clone(&endpoint);
+        let service = HttpBatchService::new(http_client.clone(), move |req| {
+            let request_builder = Arc::clone(&request_builder);
+            let endpoint = Arc::clone(&service_endpoint);
+            let future: BoxFuture<'static, Result<http::Request<Vec<u8>>, crate::Error>> =
+                Box::pin(async move { request_builder.build_request(&endpoint, req).await });
+            future
+        });
+
+        Arc::new(Node {
+            endpoint,
+            service,
+            healthy: AtomicBool::new(true),
+        })
+    }
+
+    fn nodes(&self) -> Arc<Vec<Arc<Node>>> {
+        Arc::clone(&self.nodes.read().expect("poisoned lock"))
+    }
+
+    /// Picks the node of the next request, skipping evicted nodes unless all of them are.
+    pub fn next(&self) -> Arc<Node> {
+        let nodes = self.nodes();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let node = (0..nodes.len())
+            .map(|offset| &nodes[(start + offset) % nodes.len()])
+            .find(|node| node.is_healthy())
+            .unwrap_or(&nodes[start % nodes.len()]);
+        Arc::clone(node)
+    }
+
+    async fn get(&self, endpoint: &Endpoint, path: &str) -> crate::Result<bytes::Bytes> {
+        let request = self
+            .request_builder
+            .build_get_request(endpoint, path)
+            .await?
+            .map(Body::from);
+        let response = self.http_client.send(request).await?;
+        if !response.status().is_success() {
+            return Err(format!("Unexpected status: {}", response.status()).into());
+        }
+        Ok(hyper::body::to_bytes(response.into_body()).await?)
+    }
+
+    /// Puts evicted nodes back once they answer again.
+    async fn probe(&self) {
+        for node in self.nodes().iter().filter(|node| !node.is_healthy()) {
+            if self.get(&node.endpoint, "/").await.is_ok() {
+                node.healthy.store(true, Ordering::Release);
+                emit!(&ElasticSearchNodeRestored {
+                    endpoint: &node.endpoint.base_url,
+                });
+            }
+        }
+    }
+
+    /// Replaces the nodes with the HTTP nodes of the cluster, as known by the first healthy node
+    /// that answers. Nodes that were already known keep their health.
+    async fn sniff(&self) {
+        let nodes = self.nodes();
+        let mut error = None;
+        for node in nodes.iter().filter(|node| node.is_healthy()) {
+            let endpoints = match self
+                .get(&node.endpoint, "/_nodes/http")
+                .await
+                .and_then(|body| sniffed_endpoints(&node.endpoint, &body))
+            {
+                Ok(endpoints) => endpoints,
+                Err(sniff_error) => {
+                    error = Some(sniff_error);
+                    continue;
+                }
+            };
+            if endpoints.is_empty() {
+                continue;
+            }
+
+            let sniffed = endpoints
+                .into_iter()
+                .map(|endpoint| {
+                    nodes
+                        .iter()
+                        .find(|node| node.endpoint.base_url == endpoint.base_url)
+                        .cloned()
+                        .unwrap_or_else(|| {
+                            Self::node(&self.http_client, &self.request_builder, endpoint)
+                        })
+                })
+                .collect::<Vec<_>>();
+            emit!(&ElasticSearchNodesSniffed {
+                count: sniffed.len(),
+            });
+            *self.nodes.write().expect("poisoned lock") = Arc::new(sniffed);
+            return;
+        }
+
+        if let Some(error) = error {
+            emit!(&ElasticSearchSniffFailed { error });
+        }
+    }
+
+    /// Spawns the task probing evicted nodes and sniffing the cluster. The task ends once the
+    /// pool is dropped.
+    pub fn spawn_maintenance(pool: &Arc<Self>, config: &DistributionConfig) {
+        let pool = Arc::downgrade(pool);
+        let sniff = config.sniff;
+        let mut health_checks = tokio::time::interval(Duration::from_secs(
+            config.health_check_interval_secs.max(1),
+        ));
+        let mut sniffs =
+            tokio::time::interval(Duration::from_secs(config.sniff_interval_secs.max(1)));
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = health_checks.tick() => match pool.upgrade() {
+                        Some(pool) => pool.probe().await,
+                        None => break,
+                    },
+                    _ = sniffs.tick(), if sniff => match pool.upgrade() {
+                        Some(pool) => pool.sniff().await,
+                        None => break,
+                    },
+                }
+            }
+        });
+    }
+}
+
+fn sniffed_endpoints(seed: &Endpoint, body: &[u8]) -> crate::Result<Vec<Endpoint>> {
+    let response = serde_json::from_slice::<NodesResponse>(body)?;
+    let mut endpoints = response
+        .nodes
+        .values()
+        .filter_map(|node| node.http.as_ref())
+        .map(|http| sniffed_endpoint(seed, &http.publish_address))
+        .collect::<crate::Result<Vec<_>>>()?;
+    endpoints.sort_by(|a, b| a.base_url.cmp(&b.base_url));
+    Ok(endpoints)
+}
+
+/// Builds the endpoint of a node from its publish address, which is either `ip:port` or
+/// `hostname/ip:port`, reusing the scheme, path and credentials of the seed endpoint.
+fn sniffed_endpoint(seed: &Endpoint, publish_address: &str) -> crate::Result<Endpoint> {
+    let address = match publish_address.split_once('/') {
+        Some((host, address)) if !host.is_empty() => match address.rsplit_once(':') {
+            Some((_, port)) => format!("{}:{}", host, port),
+            None => host.to_owned(),
+        },
+        Some((_, address)) => address.to_owned(),
+        None => publish_address.to_owned(),
+    };
+    let authority = address.parse::<Authority>()?;
+
+    let base_url = with_authority(&seed.base_url.parse()?, &authority)?
+        .to_string()
+        .trim_end_matches('/')
+        .to_owned();
+    Ok(Endpoint {
+        base_url,
+        bulk_uri: with_authority(&seed.bulk_uri, &authority)?,
+        auth: seed.auth.clone(),
+    })
+}
+
+fn with_authority(uri: &Uri, authority: &Authority) -> crate::Result<Uri> {
+    let mut parts = uri.clone().into_parts();
+    parts.authority = Some(authority.clone());
+    Ok(Uri::from_parts(parts)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Auth;
+
+    fn seed() -> Endpoint {
+        Endpoint {
+            base_url: "https://seed:9200".into(),
+            bulk_uri: "https://seed:9200/_bulk?timeout=60s".parse().unwrap(),
+            auth: Some(Auth::Basic {
+                user: "user".into(),
+                password: "password".into(),
+            }),
+        }
+    }
+
+    #[test]
+    fn parses_sniffed_nodes() {
+        let body = r#"{
+            "nodes": {
+                "b": {"http": {"publish_address": "es-2.local/10.0.0.2:9201"}},
+                "a": {"http": {"publish_address": "10.0.0.1:9200"}},
+                "c": {"roles": ["master"]}
+            }
+        }"#;
+
+        let endpoints = sniffed_endpoints(&seed(), body.as_bytes()).unwrap();
+
+        assert_eq!(
+            endpoints
+                .iter()
+                .map(|endpoint| endpoint.base_url.as_str())
+                .collect::<Vec<_>>(),
+            vec!["https://10.0.0.1:9200", "https://es-2.local:9201"]
+        );
+        assert_eq!(
+            endpoints[1].bulk_uri.to_string(),
+            "https://es-2.local:9201/_bulk?timeout=60s"
+        );
+        assert_eq!(endpoints[1].auth, seed().auth);
+    }
+
+    #[tokio::test]
+    async fn skips_evicted_nodes() {
+        let endpoints = ["http://a:9200", "http://b:9200", "http://c:9200"]
+            .iter()
+            .map(|base_url| Endpoint {
+                base_url: (*base_url).into(),
+                bulk_uri: format!("{}/_bulk", base_url).parse().unwrap(),
+                auth: None,
+            })
+            .collect();
+        let request_builder = HttpRequestBuilder {
+            query_params: HashMap::new(),
+            region: rusoto_core::Region::UsEast1,
+            compression: crate::sinks::util::Compression::None,
+            http_request_config: Default::default(),
+            credentials_provider: None,
+        };
+        let http_client = HttpClient::new(None, &Default::default()).unwrap();
+        let pool = NodePool::new(http_client, request_builder, endpoints);
+
+        let picked = |pool: &NodePool| {
+            (0..4)
+                .map(|_| pool.next().endpoint.base_url.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            picked(&pool),
+            vec![
+                "http://a:9200",
+                "http://b:9200",
+                "http://c:9200",
+                "http://a:9200"
+            ]
+        );
+
+        pool.nodes()[1].evict(&"connection refused".into());
+        assert_eq!(
+            picked(&pool),
+            vec![
+                "http://c:9200",
+                "http://c:9200",
+                "http://a:9200",
+                "http://c:9200"
+            ]
+        );
+
+        for node in pool.nodes().iter() {
+            node.evict(&"connection refused".into());
+        }
+        assert_eq!(pool.next().endpoint.base_url, "http://c:9200");
+    }
+}
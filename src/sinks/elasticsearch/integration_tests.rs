@@ -93,7 +93,7 @@ fn ensure_pipeline_in_params() {
     let pipeline = String::from("test-pipeline");
 
     let config = ElasticSearchConfig {
-        endpoint: "http://localhost:9200".into(),
+        endpoints: vec!["http://localhost:9200".into()],
         index: Some(index),
         pipeline: Some(pipeline.clone()),
         ..config()
@@ -107,7 +107,7 @@ fn ensure_pipeline_in_params() {
 async fn structures_events_correctly() {
     let index = gen_index();
     let config = ElasticSearchConfig {
-        endpoint: "http://localhost:9200".into(),
+        endpoints: vec!["http://localhost:9200".into()],
         index: Some(index.clone()),
         doc_type: Some("log_lines".into()),
         id_key: Some("my_id".into()),
@@ -181,7 +181,7 @@ async fn insert_events_over_http() {
 
     run_insert_tests(
         ElasticSearchConfig {
-            endpoint: "http://localhost:9200".into(),
+            endpoints: vec!["http://localhost:9200".into()],
             doc_type: Some("log_lines".into()),
             compression: Compression::None,
             ..config()
@@ -202,7 +202,7 @@ async fn insert_events_over_https() {
                 user: "elastic".into(),
                 password: "vector".into(),
             }),
-            endpoint: "https://localhost:9201".into(),
+            endpoints: vec!["https://localhost:9201".into()],
             doc_type: Some("log_lines".into()),
             compression: Compression::None,
             tls: Some(TlsOptions {
@@ -224,7 +224,7 @@ async fn insert_events_on_aws() {
     run_insert_tests(
         ElasticSearchConfig {
             auth: Some(ElasticSearchAuth::Aws(AwsAuthentication::Default {})),
-            endpoint: "http://localhost:4571".into(),
+            endpoints: vec!["http://localhost:4571".into()],
             ..config()
         },
         false,
@@ -240,7 +240,7 @@ async fn insert_events_on_aws_with_compression() {
     run_insert_tests(
         ElasticSearchConfig {
             auth: Some(ElasticSearchAuth::Aws(AwsAuthentication::Default {})),
-            endpoint: "http://localhost:4571".into(),
+            endpoints: vec!["http://localhost:4571".into()],
             compression: Compression::gzip_default(),
             ..config()
         },
@@ -256,7 +256,7 @@ async fn insert_events_with_failure() {
 
     run_insert_tests(
         ElasticSearchConfig {
-            endpoint: "http://localhost:9200".into(),
+            endpoints: vec!["http://localhost:9200".into()],
             doc_type: Some("log_lines".into()),
             compression: Compression::None,
             ..config()
@@ -274,7 +274,7 @@ async fn insert_events_in_data_stream() {
     let stream_index = format!("my-stream-{}", gen_index());
 
     let cfg = ElasticSearchConfig {
-        endpoint: "http://localhost:9200".into(),
+        endpoints: vec!["http://localhost:9200".into()],
         mode: ElasticSearchMode::DataStream,
         index: Some(stream_index.clone()),
         ..config()
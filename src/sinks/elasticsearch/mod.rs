@@ -1,5 +1,6 @@
 mod common;
 mod config;
+mod distribution;
 mod encoder;
 mod request_builder;
 mod retry;
@@ -15,6 +16,7 @@ mod integration_tests;
 
 pub use common::*;
 pub use config::*;
+pub use distribution::DistributionConfig;
 pub use encoder::ElasticSearchEncoder;
 
 use crate::{
@@ -168,6 +170,12 @@ pub enum ParseError {
     InvalidHost { host: String, source: InvalidUri },
     #[snafu(display("Host {:?} must include hostname", host))]
     HostMustIncludeHostname { host: String },
+    #[snafu(display("At least one endpoint must be configured"))]
+    EndpointRequired,
+    #[snafu(display("Options `endpoint` and `endpoints` are mutually exclusive"))]
+    EndpointsConflict,
+    #[snafu(display("Expected a single endpoint, found {}", count))]
+    MultipleEndpoints { count: usize },
     #[snafu(display("Could not generate AWS credentials: {:?}", source))]
     AwsCredentialsGenerateFailed { source: CredentialsError },
     #[snafu(display("Index template parse error: {}", source))]
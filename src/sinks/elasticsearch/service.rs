@@ -1,6 +1,7 @@
 use crate::event::{EventFinalizers, EventStatus, Finalizable};
-use crate::http::{Auth, HttpClient};
-use crate::sinks::util::http::{HttpBatchService, RequestConfig};
+use crate::http::{Auth, HttpClient, HttpError};
+use crate::sinks::elasticsearch::distribution::{DistributionConfig, NodePool};
+use crate::sinks::util::http::RequestConfig;
 use bytes::Bytes;
 use futures::future::BoxFuture;
 use http::{Response, Uri};
@@ -57,47 +58,48 @@ impl Finalizable for ElasticSearchRequest {
 
 #[derive(Clone)]
 pub struct ElasticSearchService {
-    batch_service: HttpBatchService<
-        BoxFuture<'static, Result<http::Request<Vec<u8>>, crate::Error>>,
-        ElasticSearchRequest,
-    >,
+    pool: Arc<NodePool>,
 }
 
 impl ElasticSearchService {
     pub fn new(
         http_client: HttpClient<Body>,
         http_request_builder: HttpRequestBuilder,
+        endpoints: Vec<Endpoint>,
+        distribution: &DistributionConfig,
     ) -> ElasticSearchService {
-        let http_request_builder = Arc::new(http_request_builder);
-        let batch_service = HttpBatchService::new(http_client, move |req| {
-            let request_builder = Arc::clone(&http_request_builder);
-            let future: BoxFuture<'static, Result<http::Request<Vec<u8>>, crate::Error>> =
-                Box::pin(async move { request_builder.build_request(req).await });
-            future
-        });
-        ElasticSearchService { batch_service }
+        let pool = Arc::new(NodePool::new(http_client, http_request_builder, endpoints));
+        NodePool::spawn_maintenance(&pool, distribution);
+        ElasticSearchService { pool }
     }
 }
 
-pub struct HttpRequestBuilder {
+/// A node of the cluster that requests can be sent to.
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    pub base_url: String,
     pub bulk_uri: Uri,
+    pub auth: Option<Auth>,
+}
+
+pub struct HttpRequestBuilder {
     pub query_params: HashMap<String, String>,
     pub region: Region,
     pub compression: Compression,
     pub http_request_config: RequestConfig,
-    pub http_auth: Option<Auth>,
     pub credentials_provider: Option<AwsCredentialsProvider>,
 }
 
 impl HttpRequestBuilder {
     pub async fn build_request(
         &self,
+        endpoint: &Endpoint,
         es_req: ElasticSearchRequest,
     ) -> Result<Request<Vec<u8>>, crate::Error> {
-        let mut builder = Request::post(&self.bulk_uri);
+        let mut builder = Request::post(&endpoint.bulk_uri);
 
         let request = if let Some(credentials_provider) = &self.credentials_provider {
-            let mut request = self.create_signed_request("POST", &self.bulk_uri, true);
+            let mut request = self.create_signed_request("POST", &endpoint.bulk_uri, true);
             let aws_credentials = credentials_provider.credentials().await?;
 
             request.add_header("Content-Type", "application/x-ndjson");
@@ -133,7 +135,7 @@ impl HttpRequestBuilder {
                 builder = builder.header(&header[..], &value[..]);
             }
 
-            if let Some(auth) = &self.http_auth {
+            if let Some(auth) = &endpoint.auth {
                 builder = auth.apply_builder(builder);
             }
 
@@ -144,6 +146,26 @@ impl HttpRequestBuilder {
         Ok(request)
     }
 
+    /// Builds a request to the API of a single node, used to probe and sniff the cluster.
+    pub async fn build_get_request(
+        &self,
+        endpoint: &Endpoint,
+        path: &str,
+    ) -> Result<Request<Vec<u8>>, crate::Error> {
+        let uri = format!("{}{}", endpoint.base_url, path).parse::<Uri>()?;
+        let mut builder = Request::get(&uri);
+
+        if let Some(credentials_provider) = &self.credentials_provider {
+            let mut request = self.create_signed_request("GET", &uri, false);
+            let aws_credentials = credentials_provider.credentials().await?;
+            builder = sign_request(&mut request, &aws_credentials, builder);
+        } else if let Some(auth) = &endpoint.auth {
+            builder = auth.apply_builder(builder);
+        }
+
+        Ok(builder.body(Vec::new())?)
+    }
+
     fn create_signed_request(&self, method: &str, uri: &Uri, use_params: bool) -> SignedRequest {
         let mut request = SignedRequest::new(method, "es", &self.region, uri.path());
         request.set_hostname(uri.host().map(|host| host.into()));
@@ -206,12 +228,23 @@ impl Service<ElasticSearchRequest> for ElasticSearchService {
     }
 
     fn call(&mut self, req: ElasticSearchRequest) -> Self::Future {
-        let mut http_service = self.batch_service.clone();
+        let node = self.pool.next();
+        let mut http_service = node.service();
         Box::pin(async move {
             http_service.ready().await?;
             let batch_size = req.batch_size;
             let events_byte_size = req.events_byte_size;
-            let http_response = http_service.call(req).await?;
+            let http_response = match http_service.call(req).await {
+                Ok(http_response) => http_response,
+                Err(error) => {
+                    // The request never made it to the node, so try the other ones until
+                    // this one answers again.
+                    if error.is::<HttpError>() || error.is::<hyper::Error>() {
+                        node.evict(&error);
+                    }
+                    return Err(error);
+                }
+            };
             let event_status = get_event_status(&http_response);
             Ok(ElasticSearchResponse {
                 event_status,
@@ -21,7 +21,7 @@ fn sets_create_action_when_configured() {
     let config = ElasticSearchConfig {
         bulk_action: Some(String::from("{{ action }}te")),
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         ..Default::default()
     };
     let es = ElasticSearchCommon::parse_config(&config).unwrap();
@@ -63,7 +63,7 @@ fn encode_datastream_mode() {
 
     let config = ElasticSearchConfig {
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         mode: ElasticSearchMode::DataStream,
         ..Default::default()
     };
@@ -99,7 +99,7 @@ fn encode_datastream_mode_no_routing() {
 
     let config = ElasticSearchConfig {
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         mode: ElasticSearchMode::DataStream,
         data_stream: Some(DataStreamConfig {
             auto_routing: false,
@@ -137,7 +137,7 @@ fn handle_metrics() {
     let config = ElasticSearchConfig {
         bulk_action: Some(String::from("create")),
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         ..Default::default()
     };
     let es = ElasticSearchCommon::parse_config(&config).unwrap();
@@ -175,7 +175,7 @@ fn decode_bulk_action_error() {
     let config = ElasticSearchConfig {
         bulk_action: Some(String::from("{{ action }}")),
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         ..Default::default()
     };
     let es = ElasticSearchCommon::parse_config(&config).unwrap();
@@ -192,7 +192,7 @@ fn decode_bulk_action() {
     let config = ElasticSearchConfig {
         bulk_action: Some(String::from("create")),
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         ..Default::default()
     };
     let es = ElasticSearchCommon::parse_config(&config).unwrap();
@@ -209,7 +209,7 @@ fn encode_datastream_mode_no_sync() {
 
     let config = ElasticSearchConfig {
         index: Some(String::from("vector")),
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         mode: ElasticSearchMode::DataStream,
         data_stream: Some(DataStreamConfig {
             namespace: Template::try_from("something").unwrap(),
@@ -252,7 +252,7 @@ fn allows_using_excepted_fields() {
             except_fields: Some(vec!["idx".to_string(), "timestamp".to_string()]),
             ..Default::default()
         },
-        endpoint: String::from("https://example.com"),
+        endpoints: vec![String::from("https://example.com")],
         ..Default::default()
     };
     let es = ElasticSearchCommon::parse_config(&config).unwrap();
@@ -284,7 +284,7 @@ fn validate_host_header_on_aws_requests() {
 
     let config = ElasticSearchConfig {
         auth: Some(ElasticSearchAuth::Aws(AwsAuthentication::Default {})),
-        endpoint: "http://abc-123.us-east-1.es.amazonaws.com".into(),
+        endpoints: vec!["http://abc-123.us-east-1.es.amazonaws.com".into()],
         batch,
         ..Default::default()
     };
@@ -1,7 +1,7 @@
 use crate::expiring_hash_map::ExpiringHashMap;
 use crate::{
     config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
-    event::{Event, EventStatus, Finalizable},
+    event::{Event, EventFinalizers, EventStatus, Finalizable},
     internal_events::{FileBytesSent, FileOpen, TemplateRenderingFailed},
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
@@ -18,7 +18,10 @@ use futures::{
     FutureExt,
 };
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
 use vector_core::buffers::Acker;
 use vector_core::internal_event::EventsSent;
 use vector_core::ByteSizeOf;
@@ -28,9 +31,15 @@ use tokio::{
     io::AsyncWriteExt,
 };
 mod bytes_path;
+mod rotation;
 use bytes_path::BytesPath;
+pub use rotation::{RetentionConfig, RotationConfig, RotationSuffix};
 use std::convert::TryFrom;
 
+/// The most events written as one batch, after which files are synced when
+/// `fsync` is enabled.
+const MAX_BATCH_EVENTS: usize = 1024;
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct FileSinkConfig {
@@ -42,6 +51,18 @@ pub struct FileSinkConfig {
         skip_serializing_if = "crate::serde::skip_serializing_if_default"
     )]
     pub compression: Compression,
+    pub rotation: Option<RotationConfig>,
+    pub retention: Option<RetentionConfig>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub fsync: bool,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub atomic: bool,
 }
 
 inventory::submit! {
@@ -55,6 +76,10 @@ impl GenerateConfig for FileSinkConfig {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Default::default(),
+            rotation: None,
+            retention: None,
+            fsync: false,
+            atomic: false,
         })
         .unwrap()
     }
@@ -93,6 +118,13 @@ impl OutFile {
         }
     }
 
+    async fn flush(&mut self) -> Result<(), std::io::Error> {
+        match self {
+            OutFile::Regular(file) => file.flush().await,
+            OutFile::Gzip(gzip) => gzip.flush().await,
+        }
+    }
+
     async fn sync_all(&mut self) -> Result<(), std::io::Error> {
         match self {
             OutFile::Regular(file) => file.sync_all().await,
@@ -122,6 +154,23 @@ impl OutFile {
     }
 }
 
+/// A file the sink is currently writing to.
+struct ActiveFile {
+    out: OutFile,
+    /// Where the events are written in `atomic` mode, until the file is
+    /// renamed to its final path.
+    temp_path: Option<PathBuf>,
+    opened_at: Instant,
+    /// Bytes in the file, counted before compression for data written by
+    /// this sink.
+    size: u64,
+    /// Whether data was written since the last `fsync`.
+    unsynced: bool,
+    /// Finalizers of the events written since the last `fsync`, which are
+    /// only acknowledged once the file is synced when `fsync` is enabled.
+    pending: EventFinalizers,
+}
+
 #[async_trait::async_trait]
 #[typetag::serde(name = "file")]
 impl SinkConfig for FileSinkConfig {
@@ -151,8 +200,12 @@ pub struct FileSink {
     path: Template,
     encoding: EncodingConfig<Encoding>,
     idle_timeout: Duration,
-    files: ExpiringHashMap<Bytes, OutFile>,
+    files: ExpiringHashMap<Bytes, ActiveFile>,
     compression: Compression,
+    rotation: Option<RotationConfig>,
+    retention: Option<RetentionConfig>,
+    fsync: bool,
+    atomic: bool,
 }
 
 impl FileSink {
@@ -164,6 +217,10 @@ impl FileSink {
             idle_timeout: Duration::from_secs(config.idle_timeout_secs.unwrap_or(30)),
             files: ExpiringHashMap::default(),
             compression: config.compression,
+            rotation: config.rotation,
+            retention: config.retention,
+            fsync: config.fsync,
+            atomic: config.atomic,
         }
     }
 
@@ -191,14 +248,21 @@ impl FileSink {
             .expect("unable to compute next deadline")
     }
 
-    async fn run(&mut self, mut input: BoxStream<'_, Event>) -> crate::Result<()> {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> crate::Result<()> {
+        let mut input = input.ready_chunks(MAX_BATCH_EVENTS);
         loop {
             tokio::select! {
-                event = input.next() => {
-                    match event {
-                        Some(event) => {
-                            self.process_event(event).await;
-                            self.acker.ack(1);
+                events = input.next() => {
+                    match events {
+                        Some(events) => {
+                            let count = events.len();
+                            for event in events {
+                                self.process_event(event).await;
+                            }
+                            if self.fsync {
+                                self.sync_files().await;
+                            }
+                            self.acker.ack(count);
                         },
                         None => {
                             // If we got `None` - terminate the processing.
@@ -206,11 +270,14 @@ impl FileSink {
 
                             // Close all the open files.
                             debug!(message = "Closing all the open files.");
-                            for (path, file) in self.files.iter_mut() {
-                                if let Err(error) = file.close().await {
-                                    error!(message = "Failed to close file.", path = ?path, %error);
-                                } else{
-                                    trace!(message = "Successfully closed file.", path = ?path);
+                            let paths = self.files.iter_mut().map(|(path, _)| path.clone()).collect::<Vec<_>>();
+                            for path in paths {
+                                if let Some((file, _)) = self.files.remove(&path) {
+                                    if let Err(error) = self.close_file(&path, file, false).await {
+                                        error!(message = "Failed to close file.", path = ?path, %error);
+                                    } else {
+                                        trace!(message = "Successfully closed file.", path = ?path);
+                                    }
                                 }
                             }
 
@@ -227,13 +294,13 @@ impl FileSink {
                         // We do not poll map when it's empty, so we should
                         // never reach this branch.
                         None => unreachable!(),
-                        Some(Ok((mut expired_file, path))) => {
+                        Some(Ok((expired_file, path))) => {
                             // We got an expired file. All we really want is to
                             // flush and close it.
-                            if let Err(error) = expired_file.close().await {
+                            let path = path.into_inner();
+                            if let Err(error) = self.close_file(&path, expired_file, false).await {
                                 error!(message = "Failed to close file.", path = ?path, %error);
                             }
-                            drop(expired_file); // ignore close error
                             emit!(&FileOpen {
                                 count: self.files.len()
                             });
@@ -263,47 +330,55 @@ impl FileSink {
             }
         };
 
+        let event_size = event.size_of();
+        let finalizers = event.take_finalizers();
+        let mut buf = encode_event(&self.encoding, event);
+        buf.push(b'\n');
+
         let next_deadline = self.deadline_at();
         trace!(message = "Computed next deadline.", next_deadline = ?next_deadline, path = ?path);
 
-        let file = if let Some(file) = self.files.reset_at(&path, next_deadline) {
+        if self.files.reset_at(&path, next_deadline).is_some() {
             trace!(message = "Working with an already opened file.", path = ?path);
-            file
-        } else {
-            trace!(message = "Opening new file.", ?path);
-            let file = match open_file(BytesPath::new(path.clone())).await {
-                Ok(file) => file,
-                Err(error) => {
-                    // We couldn't open the file for this event.
-                    // Maybe other events will work though! Just log
-                    // the error and skip this event.
-                    error!(message = "Unable to open the file.", path = ?path, %error);
-                    event.metadata().update_status(EventStatus::Errored);
-                    return;
-                }
-            };
-
-            let outfile = OutFile::new(file, self.compression);
+        } else if let Err(error) = self.open(&path, next_deadline).await {
+            // We couldn't open the file for this event.
+            // Maybe other events will work though! Just log
+            // the error and skip this event.
+            error!(message = "Unable to open the file.", path = ?path, %error);
+            finalizers.update_status(EventStatus::Errored);
+            return;
+        }
 
-            self.files.insert_at(path.clone(), outfile, next_deadline);
-            emit!(&FileOpen {
-                count: self.files.len()
-            });
-            self.files.get_mut(&path).unwrap()
-        };
+        if self.should_rotate(&path, buf.len()) {
+            let (file, _) = self.files.remove(&path).unwrap();
+            trace!(message = "Rotating file.", path = ?path);
+            if let Err(error) = self.close_file(&path, file, true).await {
+                error!(message = "Failed to rotate file.", path = ?path, %error);
+            }
+            if let Err(error) = self.open(&path, next_deadline).await {
+                error!(message = "Unable to open the file.", path = ?path, %error);
+                finalizers.update_status(EventStatus::Errored);
+                return;
+            }
+        }
 
+        let file = self.files.get_mut(&path).unwrap();
         trace!(message = "Writing an event to file.", path = ?path);
-        let event_size = event.size_of();
-        let finalizers = event.take_finalizers();
-        match write_event_to_file(file, event, &self.encoding).await {
-            Ok(byte_size) => {
-                finalizers.update_status(EventStatus::Delivered);
+        match file.out.write_all(&buf).await {
+            Ok(()) => {
+                file.size += buf.len() as u64;
+                file.unsynced = true;
+                if self.fsync {
+                    file.pending.merge(finalizers);
+                } else {
+                    finalizers.update_status(EventStatus::Delivered);
+                }
                 emit!(&EventsSent {
                     count: 1,
                     byte_size: event_size,
                 });
                 emit!(&FileBytesSent {
-                    byte_size,
+                    byte_size: buf.len(),
                     file: String::from_utf8_lossy(&path),
                 });
             }
@@ -313,6 +388,143 @@ impl FileSink {
             }
         }
     }
+
+    /// Whether the file open for `path` must be rotated before another
+    /// `len` bytes are written to it.
+    fn should_rotate(&self, path: &Bytes, len: usize) -> bool {
+        let (rotation, file) = match (&self.rotation, self.files.get(path)) {
+            (Some(rotation), Some(file)) if file.size > 0 => (rotation, file),
+            _ => return false,
+        };
+
+        let too_large = rotation
+            .max_size_bytes
+            .map_or(false, |max_size| file.size + len as u64 > max_size);
+        let too_old = rotation.max_age_secs.map_or(false, |max_age| {
+            file.opened_at.elapsed() >= Duration::from_secs(max_age)
+        });
+
+        too_large || too_old
+    }
+
+    /// Opens the file for `path`, creating it when needed, and tracks it
+    /// until `deadline`.
+    async fn open(&mut self, key: &Bytes, deadline: Instant) -> std::io::Result<()> {
+        trace!(message = "Opening new file.", path = ?key);
+        let path = BytesPath::new(key.clone());
+        let temp_path = if self.atomic {
+            Some(rotation::temp_path(path.as_ref()))
+        } else {
+            None
+        };
+
+        let file = match &temp_path {
+            Some(temp_path) => open_file(temp_path).await?,
+            None => open_file(&path).await?,
+        };
+        let size = file.metadata().await?.len();
+        self.prune(path.as_ref()).await;
+
+        let file = ActiveFile {
+            out: OutFile::new(file, self.compression),
+            temp_path,
+            opened_at: Instant::now(),
+            size,
+            unsynced: false,
+            pending: EventFinalizers::default(),
+        };
+        self.files.insert_at(key.clone(), file, deadline);
+        emit!(&FileOpen {
+            count: self.files.len()
+        });
+        Ok(())
+    }
+
+    /// Flushes, syncs and closes `file`, then acknowledges the events still
+    /// pending on it. In `atomic` mode it is then renamed to `path`, moving
+    /// any previous file at `path` aside first. Otherwise the file is only
+    /// moved aside when `rotate` is set.
+    async fn close_file(
+        &self,
+        path: &Bytes,
+        mut file: ActiveFile,
+        rotate: bool,
+    ) -> std::io::Result<()> {
+        let pending = std::mem::take(&mut file.pending);
+        let result = self.finish_file(path, file, rotate).await;
+        pending.update_status(match result {
+            Ok(()) => EventStatus::Delivered,
+            Err(_) => EventStatus::Errored,
+        });
+
+        self.prune(BytesPath::new(path.clone()).as_ref()).await;
+        result
+    }
+
+    async fn finish_file(
+        &self,
+        path: &Bytes,
+        mut file: ActiveFile,
+        rotate: bool,
+    ) -> std::io::Result<()> {
+        file.out.close().await?;
+
+        let path = BytesPath::new(path.clone());
+        match file.temp_path {
+            Some(temp_path) => {
+                self.rotate(path.as_ref()).await?;
+                fs::rename(temp_path, path).await
+            }
+            None if rotate => self.rotate(path.as_ref()).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn rotate(&self, path: &Path) -> std::io::Result<()> {
+        let suffix = self
+            .rotation
+            .map(|rotation| rotation.suffix)
+            .unwrap_or_default();
+        rotation::rotate(path, suffix).await.map(|_| ())
+    }
+
+    /// Removes the files rotated from `path` that fall outside of the
+    /// retention limits. This runs whenever a file is opened or closed, so
+    /// `max_age_secs` applies even when no file is rotated.
+    async fn prune(&self, path: &Path) {
+        if let Some(retention) = &self.retention {
+            if let Err(error) = rotation::prune(path, retention, SystemTime::now()).await {
+                error!(message = "Failed to prune rotated files.", path = ?path, %error);
+            }
+        }
+    }
+
+    /// Flushes the files written since the last call to disk, then
+    /// acknowledges the events written to them, as errored when the file
+    /// could not be synced.
+    async fn sync_files(&mut self) {
+        for (path, file) in self.files.iter_mut() {
+            if !file.unsynced {
+                continue;
+            }
+
+            let result = match file.out.flush().await {
+                Ok(()) => file.out.sync_all().await,
+                Err(error) => Err(error),
+            };
+            let pending = std::mem::take(&mut file.pending);
+            match result {
+                Ok(()) => {
+                    file.unsynced = false;
+                    pending.update_status(EventStatus::Delivered);
+                }
+                Err(error) => {
+                    error!(message = "Failed to sync file.", path = ?path, %error);
+                    pending.update_status(EventStatus::Errored);
+                }
+            }
+        }
+    }
 }
 
 async fn open_file(path: impl AsRef<std::path::Path>) -> std::io::Result<File> {
@@ -343,16 +555,6 @@ pub fn encode_event(encoding: &EncodingConfig<Encoding>, mut event: Event) -> Ve
     }
 }
 
-async fn write_event_to_file(
-    file: &mut OutFile,
-    event: Event,
-    encoding: &EncodingConfig<Encoding>,
-) -> Result<usize, std::io::Error> {
-    let mut buf = encode_event(encoding, event);
-    buf.push(b'\n');
-    file.write_all(&buf[..]).await.map(|()| buf.len())
-}
-
 #[async_trait]
 impl StreamSink for FileSink {
     async fn run(mut self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
@@ -366,6 +568,7 @@ impl StreamSink for FileSink {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::{BatchNotifier, BatchStatus, LogEvent};
     use crate::test_util::{
         components::{self, FILE_SINK_TAGS, SINK_TESTS},
         lines_from_file, lines_from_gzip_file, random_events_with_stream, random_lines_with_stream,
@@ -392,6 +595,10 @@ mod tests {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Compression::None,
+            rotation: None,
+            retention: None,
+            fsync: false,
+            atomic: false,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -419,6 +626,10 @@ mod tests {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Compression::Gzip,
+            rotation: None,
+            retention: None,
+            fsync: false,
+            atomic: false,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -451,6 +662,10 @@ mod tests {
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
             compression: Compression::None,
+            rotation: None,
+            retention: None,
+            fsync: false,
+            atomic: false,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -532,6 +747,10 @@ mod tests {
             idle_timeout_secs: Some(1),
             encoding: Encoding::Text.into(),
             compression: Compression::None,
+            rotation: None,
+            retention: None,
+            fsync: false,
+            atomic: false,
         };
 
         let mut sink = FileSink::new(&config, Acker::Null);
@@ -563,4 +782,166 @@ mod tests {
 
         SINK_TESTS.assert(&FILE_SINK_TAGS);
     }
+
+    #[tokio::test]
+    async fn rotates_by_size() {
+        trace_init();
+
+        let directory = temp_dir();
+        let path = directory.join("app.log");
+
+        let config = FileSinkConfig {
+            path: path.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: Some(RotationConfig {
+                max_size_bytes: Some(100),
+                max_age_secs: None,
+                suffix: RotationSuffix::Numbered,
+            }),
+            retention: Some(RetentionConfig {
+                max_files: Some(1),
+                max_age_secs: None,
+            }),
+            fsync: true,
+            atomic: false,
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        // Each line takes 21 bytes, so four of them fit in a file.
+        let (input, _events) = random_lines_with_stream(20, 10, None);
+
+        let events = Box::pin(stream::iter(input.clone().into_iter().map(Event::from)));
+        sink.run(events).await.unwrap();
+
+        // The first rotated file was pruned by the second rotation.
+        assert!(!directory.join("app.log.1").exists());
+        assert_eq!(
+            lines_from_file(directory.join("app.log.2")),
+            input[4..8].to_vec()
+        );
+        assert_eq!(lines_from_file(&path), input[8..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn atomic_finalization() {
+        trace_init();
+
+        let directory = temp_dir();
+        let path = directory.join("app.log");
+        let temp_path = directory.join(".app.log.tmp");
+
+        let config = FileSinkConfig {
+            path: path.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: None,
+            retention: None,
+            fsync: false,
+            atomic: true,
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let (input, _events) = random_lines_with_stream(10, 10, None);
+
+        let (mut tx, rx) = futures::channel::mpsc::channel(0);
+        let handle = tokio::spawn(async move { sink.run(Box::pin(rx)).await });
+
+        // The batch is only finalized once every event was written.
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        for line in input.clone() {
+            let event = LogEvent::from(line).with_batch_notifier(&batch);
+            tx.send(event.into()).await.unwrap();
+        }
+        drop(batch);
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+
+        // Nothing is visible at the final path until the file is closed.
+        assert!(!path.exists());
+        assert!(temp_path.exists());
+
+        drop(tx);
+        handle.await.unwrap().unwrap();
+
+        assert!(!temp_path.exists());
+        assert_eq!(lines_from_file(&path), input);
+
+        // A file finalized later moves the previous one aside.
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let events = Box::pin(stream::iter(vec![Event::from("last line")]));
+        sink.run(events).await.unwrap();
+
+        assert_eq!(lines_from_file(directory.join("app.log.1")), input);
+        assert_eq!(lines_from_file(&path), vec!["last line"]);
+    }
+
+    #[tokio::test]
+    async fn fsync_finalizes_after_sync() {
+        trace_init();
+
+        let directory = temp_dir();
+        let path = directory.join("app.log");
+
+        let config = FileSinkConfig {
+            path: path.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: None,
+            retention: None,
+            fsync: true,
+            atomic: false,
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let (input, _events) = random_lines_with_stream(10, 10, None);
+
+        let (mut tx, rx) = futures::channel::mpsc::channel(0);
+        let handle = tokio::spawn(async move { sink.run(Box::pin(rx)).await });
+
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        for line in input.clone() {
+            let event = LogEvent::from(line).with_batch_notifier(&batch);
+            tx.send(event.into()).await.unwrap();
+        }
+        drop(batch);
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+        // Events are only acknowledged once synced to disk.
+        assert_eq!(lines_from_file(&path), input);
+
+        drop(tx);
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn prunes_by_age_without_rotation() {
+        trace_init();
+
+        let directory = temp_dir();
+        let path = directory.join("app.log");
+        std::fs::write(directory.join("app.log.1"), "old line\n").unwrap();
+
+        let config = FileSinkConfig {
+            path: path.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Text.into(),
+            compression: Compression::None,
+            rotation: None,
+            retention: Some(RetentionConfig {
+                max_files: None,
+                max_age_secs: Some(0),
+            }),
+            fsync: false,
+            atomic: false,
+        };
+
+        let mut sink = FileSink::new(&config, Acker::Null);
+        let events = Box::pin(stream::iter(vec![Event::from("line")]));
+        sink.run(events).await.unwrap();
+
+        assert!(!directory.join("app.log.1").exists());
+        assert_eq!(lines_from_file(&path), vec!["line"]);
+    }
 }
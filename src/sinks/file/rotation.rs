@@ -0,0 +1,299 @@
+//! Rotation and retention of the files written by the sink.
+//!
+//! A rotated file keeps the name of the file it was rotated from, followed by
+//! a dot and either an increasing number or the UTC time of the rotation.
+
+use crate::internal_events::{FilePruned, FileRotated};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::fs;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RotationConfig {
+    pub max_size_bytes: Option<u64>,
+    pub max_age_secs: Option<u64>,
+    #[serde(default)]
+    pub suffix: RotationSuffix,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationSuffix {
+    Numbered,
+    Timestamp,
+}
+
+impl Default for RotationSuffix {
+    fn default() -> Self {
+        RotationSuffix::Numbered
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionConfig {
+    pub max_files: Option<usize>,
+    pub max_age_secs: Option<u64>,
+}
+
+/// The hidden file events are written to before being renamed to `path`.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Moves the file at `path` aside, returning the name it was given, or `None`
+/// if there was no file to rotate.
+pub async fn rotate(path: &Path, suffix: RotationSuffix) -> io::Result<Option<PathBuf>> {
+    if fs::metadata(path).await.is_err() {
+        return Ok(None);
+    }
+
+    let rotated = match suffix {
+        RotationSuffix::Numbered => {
+            let next = rotated_files(path)
+                .await?
+                .iter()
+                .filter_map(|(rotated, _)| rotated_suffix(path, rotated)?.parse::<u64>().ok())
+                .max()
+                .unwrap_or(0)
+                + 1;
+            with_suffix(path, &next.to_string())
+        }
+        RotationSuffix::Timestamp => {
+            let timestamp = Utc::now().format(TIMESTAMP_FORMAT).to_string();
+            let mut rotated = with_suffix(path, &timestamp);
+            let mut attempt = 0;
+            while fs::metadata(&rotated).await.is_ok() {
+                attempt += 1;
+                rotated = with_suffix(path, &format!("{}-{}", timestamp, attempt));
+            }
+            rotated
+        }
+    };
+
+    fs::rename(path, &rotated).await?;
+    emit!(&FileRotated {
+        file: path,
+        rotated: &rotated,
+    });
+    Ok(Some(rotated))
+}
+
+/// Removes the files rotated from `path` that fall outside of `retention`,
+/// keeping the most recently modified ones.
+pub async fn prune(path: &Path, retention: &RetentionConfig, now: SystemTime) -> io::Result<()> {
+    let mut files = rotated_files(path).await?;
+    // Newest first.
+    files.sort_by(|(a_path, a_modified), (b_path, b_modified)| {
+        b_modified.cmp(a_modified).then_with(|| b_path.cmp(a_path))
+    });
+
+    let max_age = retention.max_age_secs.map(Duration::from_secs);
+    for (index, (file, modified)) in files.iter().enumerate() {
+        let too_many = retention.max_files.map_or(false, |max| index >= max);
+        let too_old = max_age.map_or(false, |max_age| {
+            now.duration_since(*modified)
+                .map_or(false, |age| age > max_age)
+        });
+
+        if too_many || too_old {
+            fs::remove_file(file).await?;
+            emit!(&FilePruned { file });
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the files rotated from `path` along with their modification time.
+async fn rotated_files(path: &Path) -> io::Result<Vec<(PathBuf, SystemTime)>> {
+    let dir = match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+
+    let mut files = Vec::new();
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let rotated = path.with_file_name(entry.file_name());
+        if rotated_suffix(path, &rotated).is_some() {
+            let metadata = entry.metadata().await?;
+            if metadata.is_file() {
+                files.push((rotated, metadata.modified()?));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns the rotation suffix of `rotated` if it was rotated from `path`.
+fn rotated_suffix<'a>(path: &Path, rotated: &'a Path) -> Option<&'a str> {
+    let name = path.file_name()?.to_str()?;
+    let suffix = rotated
+        .file_name()?
+        .to_str()?
+        .strip_prefix(name)?
+        .strip_prefix('.')?;
+
+    let timestamp = suffix
+        .split_once('-')
+        .filter(|(_, attempt)| attempt.parse::<u64>().is_ok())
+        .map_or(suffix, |(timestamp, _)| timestamp);
+    let numbered = !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_digit());
+
+    if numbered || NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).is_ok() {
+        Some(suffix)
+    } else {
+        None
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    async fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).await.unwrap();
+        fs::write(path, b"data\n").await.unwrap();
+    }
+
+    #[test]
+    fn temp_path_is_hidden() {
+        assert_eq!(
+            temp_path(Path::new("/var/log/app.log")),
+            Path::new("/var/log/.app.log.tmp")
+        );
+    }
+
+    #[test]
+    fn recognizes_rotated_files() {
+        let path = Path::new("/var/log/app.log");
+        let suffix =
+            |name: &str| rotated_suffix(path, &path.with_file_name(name)).map(str::to_owned);
+
+        assert_eq!(suffix("app.log.1"), Some("1".to_owned()));
+        assert_eq!(suffix("app.log.12"), Some("12".to_owned()));
+        assert_eq!(
+            suffix("app.log.20211019T120000.123Z"),
+            Some("20211019T120000.123Z".to_owned())
+        );
+        assert_eq!(
+            suffix("app.log.20211019T120000.123Z-2"),
+            Some("20211019T120000.123Z-2".to_owned())
+        );
+        assert_eq!(suffix("app.log"), None);
+        assert_eq!(suffix("app.log."), None);
+        assert_eq!(suffix("app.log.gz"), None);
+        assert_eq!(suffix("app.log.1.bak"), None);
+        assert_eq!(suffix("other.log.1"), None);
+    }
+
+    #[tokio::test]
+    async fn rotates_with_increasing_numbers() {
+        let path = temp_dir().join("app.log");
+
+        assert_eq!(rotate(&path, RotationSuffix::Numbered).await.unwrap(), None);
+
+        touch(&path).await;
+        let first = rotate(&path, RotationSuffix::Numbered).await.unwrap();
+        assert_eq!(first, Some(with_suffix(&path, "1")));
+
+        touch(&path).await;
+        let second = rotate(&path, RotationSuffix::Numbered).await.unwrap();
+        assert_eq!(second, Some(with_suffix(&path, "2")));
+
+        assert!(fs::metadata(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rotates_with_timestamps() {
+        let path = temp_dir().join("app.log");
+
+        touch(&path).await;
+        let first = rotate(&path, RotationSuffix::Timestamp)
+            .await
+            .unwrap()
+            .unwrap();
+        touch(&path).await;
+        let second = rotate(&path, RotationSuffix::Timestamp)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(first, second);
+        assert!(rotated_suffix(&path, &first).is_some());
+        assert!(rotated_suffix(&path, &second).is_some());
+    }
+
+    #[tokio::test]
+    async fn prunes_beyond_max_files() {
+        let path = temp_dir().join("app.log");
+        for _ in 0..4 {
+            touch(&path).await;
+            rotate(&path, RotationSuffix::Numbered).await.unwrap();
+            // Keep modification times apart.
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        touch(&path).await;
+
+        let retention = RetentionConfig {
+            max_files: Some(2),
+            max_age_secs: None,
+        };
+        prune(&path, &retention, SystemTime::now()).await.unwrap();
+
+        let mut remaining = rotated_files(&path)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(file, _)| file)
+            .collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![with_suffix(&path, "3"), with_suffix(&path, "4")]
+        );
+        // The active file is never pruned.
+        assert!(fs::metadata(&path).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn prunes_beyond_max_age() {
+        let path = temp_dir().join("app.log");
+        touch(&path).await;
+        rotate(&path, RotationSuffix::Numbered).await.unwrap();
+
+        let retention = RetentionConfig {
+            max_files: None,
+            max_age_secs: Some(60),
+        };
+
+        prune(&path, &retention, SystemTime::now()).await.unwrap();
+        assert_eq!(rotated_files(&path).await.unwrap().len(), 1);
+
+        let later = SystemTime::now() + Duration::from_secs(120);
+        prune(&path, &retention, later).await.unwrap();
+        assert!(rotated_files(&path).await.unwrap().is_empty());
+    }
+}
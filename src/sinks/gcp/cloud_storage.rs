@@ -14,11 +14,12 @@ use crate::{
             service::{GcsRequest, GcsRequestSettings, GcsService},
             sink::GcsSink,
         },
-        util::encoding::StandardEncodings,
         util::RequestBuilder,
         util::{
             batch::BatchConfig,
             encoding::{EncodingConfig, EncodingConfiguration},
+            object_store::{ObjectStoreEncoder, ObjectStoreEncodings},
+            parquet::ParquetConfig,
             Compression, ServiceBuilderExt, TowerRequestConfig,
         },
         Healthcheck, VectorSink,
@@ -39,7 +40,7 @@ use std::{
 };
 use tower::ServiceBuilder;
 use uuid::Uuid;
-use vector_core::{event::Finalizable, ByteSizeOf};
+use vector_core::{event::Finalizable, stream::BatcherSettings, ByteSizeOf};
 
 const NAME: &str = "gcp_cloud_storage";
 
@@ -54,7 +55,8 @@ pub struct GcsSinkConfig {
     filename_time_format: Option<String>,
     filename_append_uuid: Option<bool>,
     filename_extension: Option<String>,
-    encoding: EncodingConfig<StandardEncodings>,
+    encoding: EncodingConfig<ObjectStoreEncodings>,
+    parquet: Option<ParquetConfig>,
     #[serde(default)]
     compression: Compression,
     #[serde(default)]
@@ -67,7 +69,7 @@ pub struct GcsSinkConfig {
 }
 
 #[cfg(test)]
-fn default_config(e: StandardEncodings) -> GcsSinkConfig {
+fn default_config(e: ObjectStoreEncodings) -> GcsSinkConfig {
     GcsSinkConfig {
         bucket: Default::default(),
         acl: Default::default(),
@@ -78,6 +80,7 @@ fn default_config(e: StandardEncodings) -> GcsSinkConfig {
         filename_append_uuid: Default::default(),
         filename_extension: Default::default(),
         encoding: e.into(),
+        parquet: Default::default(),
         compression: Compression::gzip_default(),
         batch: Default::default(),
         request: Default::default(),
@@ -153,7 +156,7 @@ impl GcsSinkConfig {
             .settings(request, GcsRetryLogic)
             .service(GcsService::new(client, base_url, creds));
 
-        let request_settings = RequestSettings::new(self)?;
+        let request_settings = RequestSettings::new(self, &batch_settings)?;
 
         let sink = GcsSink::new(cx, svc, request_settings, partitioner, batch_settings);
 
@@ -181,14 +184,14 @@ struct RequestSettings {
     extension: String,
     time_format: String,
     append_uuid: bool,
-    encoding: EncodingConfig<StandardEncodings>,
+    encoder: ObjectStoreEncoder,
     compression: Compression,
 }
 
 impl RequestBuilder<(String, Vec<Event>)> for RequestSettings {
     type Metadata = GcsMetadata;
     type Events = Vec<Event>;
-    type Encoder = EncodingConfig<StandardEncodings>;
+    type Encoder = ObjectStoreEncoder;
     type Payload = Bytes;
     type Request = GcsRequest;
     type Error = io::Error; // TODO: this is ugly.
@@ -198,11 +201,12 @@ impl RequestBuilder<(String, Vec<Event>)> for RequestSettings {
     }
 
     fn encoder(&self) -> &Self::Encoder {
-        &self.encoding
+        &self.encoder
     }
 
     fn split_input(&self, input: (String, Vec<Event>)) -> (Self::Metadata, Self::Events) {
         let (partition_key, mut events) = input;
+        self.encoder.prepare(&mut events);
         let finalizers = events.take_finalizers();
 
         let metadata = GcsMetadata {
@@ -246,13 +250,18 @@ impl RequestBuilder<(String, Vec<Event>)> for RequestSettings {
 }
 
 impl RequestSettings {
-    fn new(config: &GcsSinkConfig) -> crate::Result<Self> {
+    fn new(config: &GcsSinkConfig, batch_settings: &BatcherSettings) -> crate::Result<Self> {
+        let encoder = ObjectStoreEncoder::new(
+            config.encoding.clone(),
+            config.parquet.as_ref(),
+            batch_settings,
+        )?;
+        let compression = encoder.compression(config.compression);
         let acl = config
             .acl
             .map(|acl| HeaderValue::from_str(&to_string(acl)).unwrap());
         let content_type = HeaderValue::from_str(config.encoding.codec().content_type()).unwrap();
-        let content_encoding = config
-            .compression
+        let content_encoding = compression
             .content_encoding()
             .map(|ce| HeaderValue::from_str(&to_string(ce)).unwrap());
         let storage_class = config.storage_class.unwrap_or_default();
@@ -270,7 +279,7 @@ impl RequestSettings {
         let extension = config
             .filename_extension
             .clone()
-            .unwrap_or_else(|| config.compression.extension().into());
+            .unwrap_or_else(|| encoder.extension(compression).into());
         let time_format = config
             .filename_time_format
             .clone()
@@ -285,8 +294,8 @@ impl RequestSettings {
             extension,
             time_format,
             append_uuid,
-            compression: config.compression,
-            encoding: config.encoding.clone(),
+            compression,
+            encoder,
         })
     }
 }
@@ -319,7 +328,7 @@ mod tests {
 
         let sink_config = GcsSinkConfig {
             key_prefix: Some("key: {{ key }}".into()),
-            ..default_config(StandardEncodings::Text)
+            ..default_config(ObjectStoreEncodings::Text)
         };
         let key = sink_config
             .key_partitioner()
@@ -331,7 +340,9 @@ mod tests {
     }
 
     fn request_settings(sink_config: &GcsSinkConfig) -> RequestSettings {
-        RequestSettings::new(sink_config).expect("Could not create request settings")
+        let batch_settings = sink_config.batch.into_batcher_settings().unwrap();
+        RequestSettings::new(sink_config, &batch_settings)
+            .expect("Could not create request settings")
     }
 
     fn build_request(extension: Option<&str>, uuid: bool, compression: Compression) -> GcsRequest {
@@ -342,7 +353,7 @@ mod tests {
             filename_extension: extension.map(Into::into),
             filename_append_uuid: Some(uuid),
             compression,
-            ..default_config(StandardEncodings::Ndjson)
+            ..default_config(ObjectStoreEncodings::Ndjson)
         };
         let key = sink_config
             .key_partitioner()
@@ -368,4 +379,18 @@ mod tests {
         let req = build_request(None, true, Compression::gzip_default());
         assert_ne!(req.metadata.key, "key/date.log.gz".to_string());
     }
+
+    #[test]
+    fn gcs_parquet_request_settings() {
+        let sink_config = GcsSinkConfig {
+            filename_append_uuid: Some(false),
+            ..default_config(ObjectStoreEncodings::Parquet)
+        };
+        let settings = request_settings(&sink_config);
+
+        assert_eq!(settings.compression, Compression::None);
+        assert_eq!(settings.content_encoding, None);
+        assert_eq!(settings.content_type, "application/vnd.apache.parquet");
+        assert_eq!(settings.extension, "parquet");
+    }
 }
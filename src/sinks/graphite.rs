@@ -0,0 +1,425 @@
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{
+        metric::{Metric, MetricKind, MetricValue},
+        Event,
+    },
+    internal_events::{GraphiteInvalidMetricReceived, TemplateRenderingFailed},
+    sinks::util::{
+        buffer::metrics::{MetricNormalize, MetricNormalizer, MetricSet},
+        encode_namespace,
+        tcp::TcpSinkConfig,
+        udp::UdpSinkConfig,
+    },
+    template::Template,
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt::Write, sync::Mutex};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+// TODO: add back when serde-rs/serde#1358 is addressed
+// #[serde(deny_unknown_fields)]
+pub struct GraphiteSinkConfig {
+    #[serde(flatten)]
+    pub mode: Mode,
+    pub default_namespace: Option<String>,
+    pub path: Option<Template>,
+    #[serde(default = "crate::serde::default_true")]
+    pub tags: bool,
+    #[serde(default)]
+    pub protocol: Protocol,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Mode {
+    Tcp(TcpSinkConfig),
+    Udp(UdpSinkConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    Plaintext,
+    Pickle,
+}
+
+impl Default for Protocol {
+    fn default() -> Self {
+        Self::Plaintext
+    }
+}
+
+inventory::submit! {
+    SinkDescription::new::<GraphiteSinkConfig>("graphite")
+}
+
+impl GenerateConfig for GraphiteSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"address = "127.0.0.1:2003"
+            mode = "tcp""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "graphite")]
+impl SinkConfig for GraphiteSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.protocol == Protocol::Pickle && matches!(self.mode, Mode::Udp(_)) {
+            return Err("The pickle protocol can only be sent over TCP.".into());
+        }
+
+        let encoder = GraphiteEncoder {
+            default_namespace: self.default_namespace.clone(),
+            path: self.path.clone(),
+            tags: self.tags,
+            protocol: self.protocol,
+            normalizer: Mutex::new(MetricNormalizer::default()),
+        };
+        let encode_event = move |event| encoder.encode_event(event);
+
+        match &self.mode {
+            Mode::Tcp(config) => config.build(cx, encode_event),
+            Mode::Udp(config) => config.build(cx, encode_event),
+        }
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "graphite"
+    }
+}
+
+struct GraphiteEncoder {
+    default_namespace: Option<String>,
+    path: Option<Template>,
+    tags: bool,
+    protocol: Protocol,
+    normalizer: Mutex<MetricNormalizer<GraphiteMetricNormalize>>,
+}
+
+impl GraphiteEncoder {
+    /// Encode a metric as a line of the plaintext protocol,
+    /// `<path>[;<tag>=<value>...] <value> <timestamp>`, or as a pickle of the
+    /// same path, value and timestamp.
+    fn encode_event(&self, event: Event) -> Option<Bytes> {
+        let event = Event::from(self.normalizer.lock().unwrap().apply(event)?);
+        let path = match &self.path {
+            Some(template) => template
+                .render_string(&event)
+                .map_err(|error| {
+                    emit!(&TemplateRenderingFailed {
+                        error,
+                        field: Some("path"),
+                        drop_event: true,
+                    })
+                })
+                .ok()?,
+            None => {
+                let metric = event.as_metric();
+                encode_namespace(
+                    metric
+                        .namespace()
+                        .or_else(|| self.default_namespace.as_deref()),
+                    '.',
+                    metric.name(),
+                )
+            }
+        };
+
+        let metric = event.as_metric();
+        let value = match (metric.value(), metric.kind()) {
+            (MetricValue::Counter { value }, MetricKind::Absolute) => *value,
+            (MetricValue::Gauge { value }, MetricKind::Absolute) => *value,
+            _ => {
+                emit!(&GraphiteInvalidMetricReceived {
+                    value: metric.value(),
+                    kind: &metric.kind(),
+                });
+                return None;
+            }
+        };
+
+        let mut path = sanitize(&path, &[';']);
+        if self.tags {
+            write_tags(&mut path, metric);
+        }
+        let timestamp = metric.timestamp().unwrap_or_else(Utc::now).timestamp();
+
+        match self.protocol {
+            Protocol::Plaintext => {
+                let _ = writeln!(path, " {} {}", value, timestamp);
+                Some(path.into())
+            }
+            Protocol::Pickle => Some(encode_pickle(&path, timestamp, value)),
+        }
+    }
+}
+
+/// Encode a data point as a frame of the pickle protocol, a pickle of the
+/// list `[(path, (timestamp, value))]` prefixed by its length as a 32-bit
+/// big-endian integer.
+fn encode_pickle(path: &str, timestamp: i64, value: f64) -> Bytes {
+    let mut pickle = BytesMut::new();
+    // PROTO 2, EMPTY_LIST
+    pickle.put_slice(b"\x80\x02]");
+    // BINUNICODE
+    pickle.put_u8(b'X');
+    pickle.put_u32_le(path.len() as u32);
+    pickle.put_slice(path.as_bytes());
+    match i32::try_from(timestamp) {
+        // BININT
+        Ok(timestamp) => {
+            pickle.put_u8(b'J');
+            pickle.put_i32_le(timestamp);
+        }
+        // LONG1
+        Err(_) => {
+            pickle.put_slice(&[0x8a, 8]);
+            pickle.put_i64_le(timestamp);
+        }
+    }
+    // BINFLOAT
+    pickle.put_u8(b'G');
+    pickle.put_f64(value);
+    // TUPLE2, TUPLE2, APPEND, STOP
+    pickle.put_slice(b"\x86\x86a.");
+
+    let mut frame = BytesMut::with_capacity(4 + pickle.len());
+    frame.put_u32(pickle.len() as u32);
+    frame.put(pickle);
+    frame.freeze()
+}
+
+/// Graphite stores each data point as is, so incremental counters and gauges
+/// are accumulated into their absolute values.
+struct GraphiteMetricNormalize;
+
+impl MetricNormalize for GraphiteMetricNormalize {
+    fn apply_state(state: &mut MetricSet, metric: Metric) -> Option<Metric> {
+        match metric.value() {
+            MetricValue::Counter { .. } | MetricValue::Gauge { .. } => state.make_absolute(metric),
+            _ => Some(metric),
+        }
+    }
+}
+
+/// Append the tags with a non-empty value, replacing the characters Graphite
+/// doesn't allow in them.
+fn write_tags(line: &mut String, metric: &Metric) {
+    let tags = metric.tags().into_iter().flatten();
+    for (key, value) in tags.filter(|(_, value)| !value.is_empty()) {
+        let _ = write!(
+            line,
+            ";{}={}",
+            sanitize(key, &[';', '!', '^', '=']),
+            sanitize(value, &[';', '~'])
+        );
+    }
+}
+
+fn sanitize(s: &str, forbidden: &[char]) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_whitespace() || forbidden.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::metric::{MetricTags, StatisticKind},
+        test_util::{next_addr, trace_init, CountReceiver},
+    };
+    use chrono::TimeZone;
+    use futures::stream;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GraphiteSinkConfig>();
+    }
+
+    fn encoder(path: Option<&str>, tags: bool) -> GraphiteEncoder {
+        GraphiteEncoder {
+            default_namespace: Some("vector".into()),
+            path: path.map(|path| Template::try_from(path).unwrap()),
+            tags,
+            protocol: Protocol::Plaintext,
+            normalizer: Mutex::new(MetricNormalizer::default()),
+        }
+    }
+
+    fn tags() -> MetricTags {
+        vec![
+            ("host".to_owned(), "web 01".to_owned()),
+            ("mount".to_owned(), "/var;tmp".to_owned()),
+            ("empty".to_owned(), "".to_owned()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn metric(value: MetricValue) -> Event {
+        Metric::new("disk.used", MetricKind::Absolute, value)
+            .with_tags(Some(tags()))
+            .with_timestamp(Some(Utc.ymd(2021, 11, 7).and_hms(16, 0, 0)))
+            .into()
+    }
+
+    fn encode(encoder: &GraphiteEncoder, event: Event) -> Option<String> {
+        encoder
+            .encode_event(event)
+            .map(|bytes| String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn encodes_tagged_metrics() {
+        assert_eq!(
+            encode(
+                &encoder(None, true),
+                metric(MetricValue::Gauge { value: 0.75 })
+            )
+            .unwrap(),
+            "vector.disk.used;host=web_01;mount=/var_tmp 0.75 1636300800\n"
+        );
+        assert_eq!(
+            encode(
+                &encoder(None, false),
+                metric(MetricValue::Counter { value: 12.0 })
+            )
+            .unwrap(),
+            "vector.disk.used 12 1636300800\n"
+        );
+    }
+
+    #[test]
+    fn encodes_path_templates() {
+        assert_eq!(
+            encode(
+                &encoder(Some("servers.{{ tags.host }}.{{ name }}"), false),
+                metric(MetricValue::Gauge { value: 1.0 })
+            )
+            .unwrap(),
+            "servers.web_01.disk.used 1 1636300800\n"
+        );
+        // Events missing fields of the template are dropped.
+        assert!(encode(
+            &encoder(Some("{{ tags.missing }}.{{ name }}"), false),
+            metric(MetricValue::Gauge { value: 1.0 })
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn encodes_pickles() {
+        let encoder = GraphiteEncoder {
+            protocol: Protocol::Pickle,
+            ..encoder(None, true)
+        };
+        // `pickle.dumps([("vector.disk.used;host=web_01;mount=/var_tmp",
+        // (1636300800, 0.75))], protocol=2)`
+        let pickle: &[u8] = b"\x80\x02]X+\x00\x00\x00vector.disk.used;host=web_01;mount=/var_tmpJ\x00\xf8\x87aG?\xe8\x00\x00\x00\x00\x00\x00\x86\x86a.";
+        let mut frame = (pickle.len() as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(pickle);
+
+        assert_eq!(
+            encoder
+                .encode_event(metric(MetricValue::Gauge { value: 0.75 }))
+                .unwrap(),
+            frame
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_pickles_over_udp() {
+        let config: GraphiteSinkConfig = toml::from_str(
+            r#"address = "127.0.0.1:2003"
+            mode = "udp"
+            protocol = "pickle""#,
+        )
+        .unwrap();
+        assert!(config.build(SinkContext::new_test()).await.is_err());
+    }
+
+    #[test]
+    fn drops_unsupported_metrics() {
+        let encoder = encoder(None, true);
+        assert!(encode(
+            &encoder,
+            metric(MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 1],
+                statistic: StatisticKind::Histogram,
+            })
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn accumulates_incremental_metrics() {
+        let encoder = encoder(None, false);
+        let incremental =
+            |value| -> Event { metric(value).into_metric().into_incremental().into() };
+
+        for (value, expected) in [(1.0, 1.0), (2.0, 3.0), (0.5, 3.5)] {
+            assert_eq!(
+                encode(&encoder, incremental(MetricValue::Counter { value })).unwrap(),
+                format!("vector.disk.used {} 1636300800\n", expected)
+            );
+        }
+        // Absolute metrics are sent as is.
+        assert_eq!(
+            encode(&encoder, metric(MetricValue::Counter { value: 2.0 })).unwrap(),
+            "vector.disk.used 2 1636300800\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_over_tcp() {
+        trace_init();
+
+        let address = next_addr();
+        let config: GraphiteSinkConfig = toml::from_str(&format!(
+            r#"address = "{}"
+            mode = "tcp"
+            default_namespace = "vector""#,
+            address
+        ))
+        .unwrap();
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let mut receiver = CountReceiver::receive_lines(address);
+
+        let events = (0..3)
+            .map(|i| metric(MetricValue::Counter { value: i as f64 }))
+            .collect::<Vec<_>>();
+        sink.run(stream::iter(events)).await.unwrap();
+
+        receiver.connected().await;
+        let lines = receiver.await;
+        assert_eq!(
+            lines,
+            (0..3)
+                .map(|i| format!(
+                    "vector.disk.used;host=web_01;mount=/var_tmp {} 1636300800",
+                    i
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, EventFinalizers, EventStatus, Finalizable},
+    internal_events::{
+        MqttConnectionFailed, MqttEventNotAcknowledged, MqttEventSendFail, MqttEventSendSuccess,
+        TemplateRenderingFailed,
+    },
+    mqtt::{MqttConnectionConfig, MqttError, MqttQos},
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        StreamSink,
+    },
+    template::{Template, TemplateParseError},
+};
+use async_trait::async_trait;
+use futures::{
+    future::{self, BoxFuture},
+    stream::{BoxStream, FuturesOrdered},
+    FutureExt, StreamExt,
+};
+use rumqttc::{AsyncClient, Event as MqttEvent, EventLoop, Outgoing, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+use vector_core::buffers::Acker;
+
+/// Maximum number of events waiting to be acknowledged by the broker.
+const MAX_PENDING_ACKS: usize = 100;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("invalid topic template: {}", source))]
+    TopicTemplate { source: TemplateParseError },
+    #[snafu(display("Could not create MQTT client: {}", source))]
+    MqttClientError { source: MqttError },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MqttSinkConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topic: String,
+    #[serde(default)]
+    qos: MqttQos,
+    #[serde(default)]
+    retain: bool,
+    encoding: EncodingConfig<Encoding>,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Text,
+    Json,
+}
+
+inventory::submit! {
+    SinkDescription::new::<MqttSinkConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            port = 1883
+            topic = "vector"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SinkConfig for MqttSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let sink = MqttSink::new(self.clone(), cx.acker())?;
+        let healthcheck = healthcheck(self.connection.clone()).boxed();
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "mqtt"
+    }
+}
+
+async fn healthcheck(connection: MqttConnectionConfig) -> crate::Result<()> {
+    let (_client, mut eventloop) = connection.build_client(true)?;
+    loop {
+        if let MqttEvent::Incoming(Packet::ConnAck(_)) = eventloop.poll().await? {
+            return Ok(());
+        }
+    }
+}
+
+pub struct MqttSink {
+    connection: MqttConnectionConfig,
+    topic: Template,
+    qos: QoS,
+    retain: bool,
+    encoding: EncodingConfig<Encoding>,
+    acker: Acker,
+}
+
+impl MqttSink {
+    fn new(config: MqttSinkConfig, acker: Acker) -> crate::Result<Self> {
+        // Validate the connection settings up front rather than on the
+        // first event.
+        config
+            .connection
+            .build_client(true)
+            .context(MqttClientError)?;
+
+        Ok(MqttSink {
+            topic: Template::try_from(config.topic).context(TopicTemplate)?,
+            qos: config.qos.into(),
+            retain: config.retain,
+            encoding: config.encoding,
+            connection: config.connection,
+            acker,
+        })
+    }
+
+    /// Publish an event, returning a future that resolves once the broker
+    /// has acknowledged it, or once it was sent for QoS 0.
+    async fn publish(
+        &self,
+        client: &AsyncClient,
+        inflight: &Mutex<Inflight>,
+        mut event: Event,
+    ) -> BoxFuture<'static, ()> {
+        let topic = match self.topic.render_string(&event) {
+            Ok(topic) => topic,
+            Err(error) => {
+                emit!(&TemplateRenderingFailed {
+                    error,
+                    field: Some("topic"),
+                    drop_event: true,
+                });
+                event.metadata().update_status(EventStatus::Errored);
+                return future::ready(()).boxed();
+            }
+        };
+
+        let finalizers = event.take_finalizers();
+        let payload = encode_event(event, &self.encoding);
+        let byte_size = payload.len();
+
+        // The event loop sends publishes in the order they were requested,
+        // so the confirmation must be queued before the request.
+        let (tx, rx) = oneshot::channel();
+        inflight.lock().unwrap().queued.push_back(tx);
+
+        match client.publish(topic, self.qos, self.retain, payload).await {
+            Ok(()) => async move {
+                match rx.await {
+                    Ok(()) => {
+                        finalizers.update_status(EventStatus::Delivered);
+                        emit!(&MqttEventSendSuccess { byte_size });
+                    }
+                    Err(_) => {
+                        finalizers.update_status(EventStatus::Errored);
+                        emit!(&MqttEventNotAcknowledged);
+                    }
+                }
+            }
+            .boxed(),
+            Err(error) => {
+                inflight.lock().unwrap().queued.pop_back();
+                finalizers.update_status(EventStatus::Errored);
+                emit!(&MqttEventSendFail { error });
+                future::ready(()).boxed()
+            }
+        }
+    }
+}
+
+/// The publishes of the sink that were not acknowledged yet.
+#[derive(Default)]
+struct Inflight {
+    /// Publishes requested from the client but not sent by the event loop
+    /// yet, in the order they were requested.
+    queued: VecDeque<oneshot::Sender<()>>,
+
+    /// Publishes with QoS 1 or 2 that were sent, by packet identifier.
+    sent: HashMap<u16, oneshot::Sender<()>>,
+}
+
+impl Inflight {
+    fn on_publish(&mut self, pkid: u16) {
+        if pkid == 0 {
+            // QoS 0 publishes are never acknowledged.
+            if let Some(tx) = self.queued.pop_front() {
+                let _ = tx.send(());
+            }
+        } else if !self.sent.contains_key(&pkid) {
+            // Publishes that are already known are resent after a reconnect.
+            if let Some(tx) = self.queued.pop_front() {
+                self.sent.insert(pkid, tx);
+            }
+        }
+    }
+
+    fn on_ack(&mut self, pkid: u16) {
+        if let Some(tx) = self.sent.remove(&pkid) {
+            let _ = tx.send(());
+        }
+    }
+
+    /// Fails every publish that was not acknowledged yet.
+    fn clear(&mut self) {
+        self.queued.clear();
+        self.sent.clear();
+    }
+}
+
+/// Drives the client event loop, which performs the actual network I/O and
+/// reconnects after errors, until the client disconnects. Any publish still
+/// in flight once it returns is considered failed.
+async fn drive_eventloop(mut eventloop: EventLoop, inflight: Arc<Mutex<Inflight>>) {
+    loop {
+        match eventloop.poll().await {
+            Ok(MqttEvent::Outgoing(Outgoing::Disconnect)) => break,
+            Ok(MqttEvent::Outgoing(Outgoing::Publish(pkid))) => {
+                inflight.lock().unwrap().on_publish(pkid)
+            }
+            // QoS 1 publishes are complete once acknowledged, QoS 2 ones once
+            // the release was confirmed.
+            Ok(MqttEvent::Incoming(Packet::PubAck(ack))) => {
+                inflight.lock().unwrap().on_ack(ack.pkid)
+            }
+            Ok(MqttEvent::Incoming(Packet::PubComp(comp))) => {
+                inflight.lock().unwrap().on_ack(comp.pkid)
+            }
+            Ok(_) => {}
+            Err(error) => {
+                emit!(&MqttConnectionFailed { error });
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+
+    inflight.lock().unwrap().clear();
+}
+
+#[async_trait]
+impl StreamSink for MqttSink {
+    async fn run(self: Box<Self>, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let (client, eventloop) = self.connection.build_client(true).map_err(|error| {
+            error!(message = "Could not create MQTT client.", %error);
+        })?;
+        let inflight = Arc::new(Mutex::new(Inflight::default()));
+        let mut driver = tokio::spawn(drive_eventloop(eventloop, Arc::clone(&inflight)));
+
+        let mut pending = FuturesOrdered::new();
+
+        loop {
+            tokio::select! {
+                Some(()) = pending.next(), if !pending.is_empty() => self.acker.ack(1),
+                event = input.next(), if pending.len() < MAX_PENDING_ACKS => match event {
+                    Some(event) => pending.push(self.publish(&client, &inflight, event).await),
+                    None => break,
+                },
+            }
+        }
+
+        // Wait for the broker to acknowledge the remaining events before
+        // disconnecting, but don't hold up shutdown indefinitely if the
+        // broker is gone.
+        let drain = async {
+            while pending.next().await.is_some() {
+                self.acker.ack(1);
+            }
+        };
+        let _ = tokio::time::timeout(Duration::from_secs(5), drain).await;
+
+        if client.disconnect().await.is_err()
+            || tokio::time::timeout(Duration::from_secs(5), &mut driver)
+                .await
+                .is_err()
+        {
+            driver.abort();
+        }
+
+        // Events still pending at this point were not acknowledged.
+        inflight.lock().unwrap().clear();
+        while pending.next().await.is_some() {
+            self.acker.ack(1);
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> String {
+    encoding.apply_rules(&mut event);
+
+    match encoding.codec() {
+        Encoding::Json => serde_json::to_string(event.as_log()).unwrap(),
+        Encoding::Text => event
+            .as_log()
+            .get(crate::config::log_schema().message_key())
+            .map(|v| v.to_string_lossy())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Value;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSinkConfig>();
+    }
+
+    #[test]
+    fn encodes_raw_logs() {
+        let event = Event::from("foo");
+        assert_eq!(
+            "foo",
+            encode_event(event, &EncodingConfig::from(Encoding::Text))
+        );
+    }
+
+    #[test]
+    fn encodes_log_events() {
+        let mut event = Event::new_empty_log();
+        let log = event.as_mut_log();
+        log.insert("x", Value::from("23"));
+        log.insert("z", Value::from(25));
+
+        let encoded = encode_event(event, &EncodingConfig::from(Encoding::Json));
+        assert_eq!(encoded, r#"{"x":"23","z":25}"#);
+    }
+
+    #[test]
+    fn rejects_invalid_topic_template() {
+        let config: MqttSinkConfig = toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            topic = "sensors/%E"
+            encoding.codec = "text"
+            "#,
+        )
+        .unwrap();
+        let (acker, _) = Acker::new_for_testing();
+        assert!(MqttSink::new(config, acker).is_err());
+    }
+
+    #[test]
+    fn confirms_publishes_in_flight() {
+        let mut inflight = Inflight::default();
+        let mut receivers = (0..3)
+            .map(|_| {
+                let (tx, rx) = oneshot::channel();
+                inflight.queued.push_back(tx);
+                rx
+            })
+            .collect::<Vec<_>>();
+
+        // A QoS 0 publish is confirmed once sent.
+        inflight.on_publish(0);
+        assert_eq!(receivers[0].try_recv(), Ok(()));
+
+        // A resent publish does not claim another event.
+        inflight.on_publish(5);
+        inflight.on_publish(5);
+        inflight.on_publish(6);
+        assert!(inflight.queued.is_empty());
+
+        inflight.on_ack(6);
+        assert_eq!(receivers[2].try_recv(), Ok(()));
+        assert!(receivers[1].try_recv().is_err());
+
+        inflight.clear();
+        assert_eq!(
+            receivers[1].try_recv(),
+            Err(oneshot::error::TryRecvError::Closed)
+        );
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::{
+        event::{BatchNotifier, BatchStatus},
+        test_util::{random_lines_with_stream, random_string, trace_init},
+    };
+    use rumqttc::SubscribeFilter;
+
+    fn broker_host() -> String {
+        std::env::var("MQTT_HOST").unwrap_or_else(|_| "127.0.0.1".into())
+    }
+
+    #[tokio::test]
+    async fn mqtt_happy() {
+        // Publish `N` messages to the broker and verify with a separate
+        // subscriber that they were all received in order.
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let connection = MqttConnectionConfig {
+            host: broker_host(),
+            ..Default::default()
+        };
+
+        let (subscriber, mut subscriber_loop) = connection.build_client(true).unwrap();
+        subscriber
+            .subscribe_many(vec![SubscribeFilter::new(topic.clone(), QoS::AtLeastOnce)])
+            .await
+            .unwrap();
+        // Wait for the subscription to be acknowledged before publishing.
+        loop {
+            if let MqttEvent::Incoming(Packet::SubAck(_)) = subscriber_loop.poll().await.unwrap() {
+                break;
+            }
+        }
+
+        let config = MqttSinkConfig {
+            connection,
+            topic: topic.clone(),
+            qos: MqttQos::AtLeastOnce,
+            retain: false,
+            encoding: EncodingConfig::from(Encoding::Text),
+        };
+        healthcheck(config.connection.clone()).await.unwrap();
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+        let sink = Box::new(MqttSink::new(config, acker).unwrap());
+        let num_events = 100;
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let (input, events) = random_lines_with_stream(100, num_events, Some(batch));
+        sink.run(Box::pin(events)).await.unwrap();
+
+        let mut output = Vec::new();
+        while output.len() < num_events {
+            let notification = tokio::time::timeout(Duration::from_secs(5), subscriber_loop.poll())
+                .await
+                .expect("Timed out waiting for messages")
+                .unwrap();
+            if let MqttEvent::Incoming(Packet::Publish(publish)) = notification {
+                output.push(String::from_utf8_lossy(&publish.payload).to_string());
+            }
+        }
+
+        assert_eq!(output, input);
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+    }
+}
@@ -1,24 +1,39 @@
 use crate::{
     config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
-    event::Event,
-    internal_events::{NatsEventSendFail, NatsEventSendSuccess, TemplateRenderingFailed},
+    event::{Event, EventFinalizers, EventStatus, Finalizable},
+    internal_events::{
+        NatsEventSendFail, NatsEventSendSuccess, NatsPublishRetried, TemplateRenderingFailed,
+    },
+    nats::{nats_options, NatsAuthConfig, NatsError},
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
+        retries::ExponentialBackoff,
         StreamSink,
     },
     template::{Template, TemplateParseError},
+    tls::TlsConfig,
 };
 use async_trait::async_trait;
-use futures::{stream::BoxStream, FutureExt, StreamExt, TryFutureExt};
+use futures::{
+    future::{self, BoxFuture},
+    stream::{BoxStream, FuturesOrdered},
+    FutureExt, StreamExt, TryFutureExt,
+};
+use nats::jetstream::{JetStream, JetStreamOptions, PublishAck, PublishOptions};
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::convert::TryFrom;
+use std::{convert::TryFrom, io, time::Duration};
 use vector_core::buffers::Acker;
 
+/// Maximum number of events waiting on a JetStream publish acknowledgement.
+const MAX_PENDING_ACKS: usize = 32;
+
 #[derive(Debug, Snafu)]
 enum BuildError {
     #[snafu(display("invalid subject template: {}", source))]
     SubjectTemplate { source: TemplateParseError },
+    #[snafu(display("invalid connection options: {}", source))]
+    ConnectionOptions { source: NatsError },
 }
 
 /**
@@ -32,12 +47,38 @@ pub struct NatsSinkConfig {
     connection_name: String,
     subject: String,
     url: String,
+    auth: Option<NatsAuthConfig>,
+    tls: Option<TlsConfig>,
+    jetstream: Option<JetStreamConfig>,
 }
 
 fn default_name() -> String {
     String::from("vector")
 }
 
+/// Publish to JetStream, waiting for the stream to acknowledge every
+/// message before the event is acknowledged.
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct JetStreamConfig {
+    expected_stream: Option<String>,
+    #[serde(default = "default_timeout_secs")]
+    #[derivative(Default(value = "default_timeout_secs()"))]
+    timeout_secs: u64,
+    #[serde(default = "default_retry_attempts")]
+    #[derivative(Default(value = "default_retry_attempts()"))]
+    retry_attempts: usize,
+}
+
+const fn default_timeout_secs() -> u64 {
+    5
+}
+
+const fn default_retry_attempts() -> usize {
+    5
+}
+
 #[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Encoding {
@@ -84,67 +125,162 @@ impl SinkConfig for NatsSinkConfig {
 }
 
 impl NatsSinkConfig {
-    fn to_nats_options(&self) -> async_nats::Options {
-        // Set reconnect_buffer_size on the nats client to 0 bytes so that the
-        // client doesn't buffer internally (to avoid message loss).
-        async_nats::Options::new()
-            .with_name(&self.connection_name)
-            .reconnect_buffer_size(0)
+    async fn connect(&self) -> crate::Result<nats::asynk::Connection> {
+        let options: nats::asynk::Options =
+            nats_options(&self.connection_name, self.auth.as_ref(), self.tls.as_ref())
+                .context(ConnectionOptions)?;
+        options.connect(&self.url).map_err(|e| e.into()).await
     }
 
-    async fn connect(&self) -> crate::Result<async_nats::Connection> {
-        self.to_nats_options()
-            .connect(&self.url)
-            .map_err(|e| e.into())
-            .await
+    /// The JetStream API of `nats` is only available on the blocking client.
+    async fn connect_jetstream(&self) -> crate::Result<JetStream> {
+        let options: nats::Options =
+            nats_options(&self.connection_name, self.auth.as_ref(), self.tls.as_ref())
+                .context(ConnectionOptions)?;
+        let url = self.url.clone();
+        let connection = tokio::task::spawn_blocking(move || options.connect(&url)).await??;
+        Ok(JetStream::new(connection, JetStreamOptions::new()))
     }
 }
 
 async fn healthcheck(config: NatsSinkConfig) -> crate::Result<()> {
-    config.connect().map_ok(|_| ()).await
+    match &config.jetstream {
+        None => config.connect().map_ok(|_| ()).await,
+        Some(_) => {
+            // Fails if JetStream isn't enabled for the account.
+            let context = config.connect_jetstream().await?;
+            tokio::task::spawn_blocking(move || context.account_info()).await??;
+            Ok(())
+        }
+    }
 }
 
 /**
  * Code dealing with the Sink struct.
  */
 
-#[derive(Clone)]
-struct NatsOptions {
-    connection_name: String,
-}
-
 pub struct NatsSink {
+    config: NatsSinkConfig,
     encoding: EncodingConfig<Encoding>,
-    options: NatsOptions,
     subject: Template,
-    url: String,
     acker: Acker,
 }
 
+enum Publisher {
+    Core(nats::asynk::Connection),
+    JetStream(JetStreamPublisher),
+}
+
+#[derive(Clone)]
+struct JetStreamPublisher {
+    context: JetStream,
+    options: PublishOptions,
+    retry_attempts: usize,
+}
+
 impl NatsSink {
     fn new(config: NatsSinkConfig, acker: Acker) -> crate::Result<Self> {
         Ok(NatsSink {
-            options: (&config).into(),
-            encoding: config.encoding,
-            subject: Template::try_from(config.subject).context(SubjectTemplate)?,
-            url: config.url,
+            encoding: config.encoding.clone(),
+            subject: Template::try_from(config.subject.clone()).context(SubjectTemplate)?,
+            config,
             acker,
         })
     }
+
+    async fn connect(&self) -> crate::Result<Publisher> {
+        Ok(match &self.config.jetstream {
+            None => Publisher::Core(self.config.connect().await?),
+            Some(jetstream) => Publisher::JetStream(JetStreamPublisher {
+                context: self.config.connect_jetstream().await?,
+                options: PublishOptions {
+                    timeout: Some(Duration::from_secs(jetstream.timeout_secs)),
+                    expected_stream: jetstream.expected_stream.clone(),
+                    ..Default::default()
+                },
+                retry_attempts: jetstream.retry_attempts,
+            }),
+        })
+    }
+
+    /// Publish an event, returning a future that resolves once JetStream has
+    /// acknowledged it. Core NATS has no acknowledgements, so those events
+    /// resolve once they are written to the connection.
+    async fn publish(&self, publisher: &Publisher, mut event: Event) -> BoxFuture<'static, ()> {
+        let subject = match self.subject.render_string(&event) {
+            Ok(subject) => subject,
+            Err(error) => {
+                emit!(&TemplateRenderingFailed {
+                    error,
+                    field: Some("subject"),
+                    drop_event: true,
+                });
+                event.metadata().update_status(EventStatus::Errored);
+                return future::ready(()).boxed();
+            }
+        };
+
+        let finalizers = event.take_finalizers();
+        let payload = encode_event(event, &self.encoding);
+
+        match publisher {
+            Publisher::Core(connection) => {
+                let result = connection.publish(&subject, &payload).await;
+                complete(result, payload.len(), finalizers);
+                future::ready(()).boxed()
+            }
+            Publisher::JetStream(publisher) => {
+                let publisher = publisher.clone();
+                async move {
+                    let byte_size = payload.len();
+                    let result = publisher.publish(subject, payload).await;
+                    complete(result, byte_size, finalizers);
+                }
+                .boxed()
+            }
+        }
+    }
 }
 
-impl From<NatsOptions> for async_nats::Options {
-    fn from(options: NatsOptions) -> Self {
-        async_nats::Options::new()
-            .with_name(&options.connection_name)
-            .reconnect_buffer_size(0)
+impl JetStreamPublisher {
+    async fn publish(self, subject: String, payload: String) -> io::Result<PublishAck> {
+        let mut backoff = ExponentialBackoff::from_millis(2)
+            .factor(250)
+            .max_delay(Duration::from_secs(60));
+        let mut attempt = 0;
+
+        loop {
+            let context = self.context.clone();
+            let options = self.options.clone();
+            let subject = subject.clone();
+            let payload = payload.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                context.publish_with_options(&subject, payload, &options)
+            })
+            .await
+            .unwrap_or_else(|error| Err(io::Error::new(io::ErrorKind::Other, error)));
+
+            match result {
+                Err(error) if attempt < self.retry_attempts => {
+                    attempt += 1;
+                    emit!(&NatsPublishRetried { error, attempt });
+                    tokio::time::sleep(backoff.next().unwrap()).await;
+                }
+                result => return result,
+            }
+        }
     }
 }
 
-impl From<&NatsSinkConfig> for NatsOptions {
-    fn from(options: &NatsSinkConfig) -> Self {
-        Self {
-            connection_name: options.connection_name.clone(),
+fn complete<T>(result: io::Result<T>, byte_size: usize, finalizers: EventFinalizers) {
+    match result {
+        Ok(_) => {
+            finalizers.update_status(EventStatus::Delivered);
+            emit!(&NatsEventSendSuccess { byte_size });
+        }
+        Err(error) => {
+            finalizers.update_status(EventStatus::Errored);
+            emit!(&NatsEventSendFail { error });
         }
     }
 }
@@ -152,38 +288,23 @@ impl From<&NatsSinkConfig> for NatsOptions {
 #[async_trait]
 impl StreamSink for NatsSink {
     async fn run(self: Box<Self>, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
-        let nats_options: async_nats::Options = self.options.into();
-
-        let nc = nats_options.connect(&self.url).await.map_err(|_| ())?;
-
-        while let Some(event) = input.next().await {
-            let subject = match self.subject.render_string(&event) {
-                Ok(subject) => subject,
-                Err(error) => {
-                    emit!(&TemplateRenderingFailed {
-                        error,
-                        field: Some("subject"),
-                        drop_event: true,
-                    });
-                    self.acker.ack(1);
-                    continue;
-                }
-            };
-
-            let log = encode_event(event, &self.encoding);
-            let message_len = log.len();
-
-            match nc.publish(&subject, log).await {
-                Ok(_) => {
-                    emit!(&NatsEventSendSuccess {
-                        byte_size: message_len,
-                    });
-                }
-                Err(error) => {
-                    emit!(&NatsEventSendFail { error });
-                }
+        let publisher = self
+            .connect()
+            .await
+            .map_err(|error| error!(message = "Failed to connect to NATS.", %error))?;
+        let mut pending = FuturesOrdered::new();
+
+        loop {
+            tokio::select! {
+                Some(()) = pending.next(), if !pending.is_empty() => self.acker.ack(1),
+                event = input.next(), if pending.len() < MAX_PENDING_ACKS => match event {
+                    Some(event) => pending.push(self.publish(&publisher, event).await),
+                    None => break,
+                },
             }
+        }
 
+        while pending.next().await.is_some() {
             self.acker.ack(1);
         }
 
@@ -215,6 +336,30 @@ mod tests {
         crate::test_util::test_generate_config::<NatsSinkConfig>();
     }
 
+    #[test]
+    fn parses_jetstream_config() {
+        let config: NatsSinkConfig = toml::from_str(
+            r#"
+            encoding.codec = "json"
+            subject = "logs.{{ host }}"
+            url = "nats://127.0.0.1:4222"
+            auth.strategy = "user_password"
+            auth.user = "vector"
+            auth.password = "secret"
+            jetstream.expected_stream = "LOGS"
+            "#,
+        )
+        .unwrap();
+
+        let jetstream = config.jetstream.unwrap();
+        assert_eq!(jetstream.expected_stream.as_deref(), Some("LOGS"));
+        assert_eq!(jetstream.retry_attempts, 5);
+        assert!(matches!(
+            config.auth,
+            Some(NatsAuthConfig::UserPassword { .. })
+        ));
+    }
+
     #[test]
     fn encodes_raw_logs() {
         let event = Event::from("foo");
@@ -261,6 +406,9 @@ mod integration_tests {
             connection_name: "".to_owned(),
             subject: subject.clone(),
             url: "nats://127.0.0.1:4222".to_owned(),
+            auth: None,
+            tls: None,
+            jetstream: None,
         };
 
         // Establish the consumer subscription.
@@ -292,4 +440,52 @@ mod integration_tests {
             num_events
         );
     }
+
+    #[tokio::test]
+    async fn nats_jetstream_happy() {
+        trace_init();
+
+        let stream = format!("test-{}", random_string(10));
+
+        let cnf = NatsSinkConfig {
+            encoding: EncodingConfig::from(Encoding::Text),
+            connection_name: "vector".to_owned(),
+            subject: stream.clone(),
+            url: "nats://127.0.0.1:4222".to_owned(),
+            auth: None,
+            tls: None,
+            jetstream: Some(JetStreamConfig {
+                expected_stream: Some(stream.clone()),
+                ..Default::default()
+            }),
+        };
+
+        // The stream captures the subject of the same name.
+        let context = cnf.connect_jetstream().await.unwrap();
+        let name = stream.clone();
+        let context = tokio::task::spawn_blocking(move || {
+            context.add_stream(name.as_str()).unwrap();
+            context
+        })
+        .await
+        .unwrap();
+        healthcheck(cnf.clone()).await.unwrap();
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+        let sink = Box::new(NatsSink::new(cnf.clone(), acker).unwrap());
+        let num_events = 100;
+        let (_input, events) = random_lines_with_stream(100, num_events, None);
+
+        let _ = sink.run(Box::pin(events)).await.unwrap();
+
+        // Every event was acknowledged by the stream before being acked.
+        let info = tokio::task::spawn_blocking(move || context.stream_info(&stream).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(info.state.messages, num_events as u64);
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+    }
 }
@@ -0,0 +1,334 @@
+//! Rendering of event batches into `COPY` payloads and `INSERT` statements.
+//!
+//! Values are always sent as text and converted by PostgreSQL into the type of the target
+//! column, which keeps the sink independent of the column types of the table.
+
+use super::config::{ConflictAction, OnConflictConfig};
+use crate::event::{Event, Value};
+use chrono::SecondsFormat;
+use std::{borrow::Cow, collections::HashMap, fmt::Write};
+
+/// Quotes a (possibly schema qualified) table name.
+pub fn quote_table(table: &str) -> String {
+    match table.split_once('.') {
+        Some((schema, table)) => {
+            format!("{}.{}", quote_identifier(schema), quote_identifier(table))
+        }
+        None => quote_identifier(table),
+    }
+}
+
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Returns the columns of the table which are set in at least one of the events.
+pub fn batch_columns(table_columns: &[String], events: &[Event]) -> Vec<String> {
+    table_columns
+        .iter()
+        .filter(|column| {
+            events
+                .iter()
+                .any(|event| event.as_log().get_flat(column.as_str()).is_some())
+        })
+        .cloned()
+        .collect()
+}
+
+fn value_to_text(value: &Value) -> Option<Cow<'_, str>> {
+    match value {
+        Value::Bytes(bytes) => Some(String::from_utf8_lossy(bytes)),
+        Value::Integer(integer) => Some(integer.to_string().into()),
+        Value::Float(float) => Some(float.to_string().into()),
+        Value::Boolean(boolean) => Some(if *boolean { "true" } else { "false" }.into()),
+        Value::Timestamp(timestamp) => Some(
+            timestamp
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                .into(),
+        ),
+        Value::Map(_) | Value::Array(_) => Some(
+            serde_json::to_string(value)
+                .expect("Values should be valid json.")
+                .into(),
+        ),
+        Value::Null => None,
+    }
+}
+
+fn column_text<'a>(event: &'a Event, column: &str) -> Option<Cow<'a, str>> {
+    event.as_log().get_flat(column).and_then(value_to_text)
+}
+
+pub fn copy_statement(table: &str, columns: &[String]) -> String {
+    format!(
+        "COPY {} ({}) FROM STDIN",
+        quote_table(table),
+        column_list(columns)
+    )
+}
+
+/// Encodes the events as rows in the `COPY` text format.
+pub fn encode_copy(columns: &[String], events: &[Event]) -> Vec<u8> {
+    let mut buffer = String::new();
+    for event in events {
+        for (index, column) in columns.iter().enumerate() {
+            if index > 0 {
+                buffer.push('\t');
+            }
+            match column_text(event, column) {
+                Some(text) => escape_copy(&text, &mut buffer),
+                None => buffer.push_str("\\N"),
+            }
+        }
+        buffer.push('\n');
+    }
+    buffer.into_bytes()
+}
+
+fn escape_copy(text: &str, buffer: &mut String) {
+    for c in text.chars() {
+        match c {
+            '\\' => buffer.push_str("\\\\"),
+            '\t' => buffer.push_str("\\t"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            c => buffer.push(c),
+        }
+    }
+}
+
+/// Renders the events as a single multi-row `INSERT` statement.
+///
+/// When updating on conflict, rows sharing the same conflict key are collapsed into the
+/// last one, as PostgreSQL refuses to update the same row twice within one statement.
+pub fn insert_statement(
+    table: &str,
+    columns: &[String],
+    events: &[Event],
+    on_conflict: Option<&OnConflictConfig>,
+) -> String {
+    let mut rows = Vec::with_capacity(events.len());
+    let mut keys = HashMap::new();
+    for event in events {
+        let row = columns
+            .iter()
+            .map(|column| column_text(event, column))
+            .collect::<Vec<_>>();
+        match on_conflict {
+            Some(on_conflict) if on_conflict.action == ConflictAction::Update => {
+                let key = on_conflict
+                    .columns
+                    .iter()
+                    .map(|column| column_text(event, column))
+                    .collect::<Vec<_>>();
+                match keys.get(&key) {
+                    Some(&index) => rows[index] = row,
+                    None => {
+                        keys.insert(key, rows.len());
+                        rows.push(row);
+                    }
+                }
+            }
+            _ => rows.push(row),
+        }
+    }
+
+    let mut statement = format!(
+        "INSERT INTO {} ({}) VALUES ",
+        quote_table(table),
+        column_list(columns)
+    );
+    for (index, row) in rows.iter().enumerate() {
+        if index > 0 {
+            statement.push_str(", ");
+        }
+        statement.push('(');
+        for (index, value) in row.iter().enumerate() {
+            if index > 0 {
+                statement.push_str(", ");
+            }
+            match value {
+                Some(text) => quote_literal(text, &mut statement),
+                None => statement.push_str("NULL"),
+            }
+        }
+        statement.push(')');
+    }
+
+    if let Some(on_conflict) = on_conflict {
+        statement.push_str(" ON CONFLICT");
+        if !on_conflict.columns.is_empty() {
+            let _ = write!(statement, " ({})", column_list(&on_conflict.columns));
+        }
+        let updates = columns
+            .iter()
+            .filter(|column| !on_conflict.columns.contains(*column))
+            .map(|column| {
+                let column = quote_identifier(column);
+                format!("{} = EXCLUDED.{}", column, column)
+            })
+            .collect::<Vec<_>>();
+        if on_conflict.action == ConflictAction::Nothing || updates.is_empty() {
+            statement.push_str(" DO NOTHING");
+        } else {
+            let _ = write!(statement, " DO UPDATE SET {}", updates.join(", "));
+        }
+    }
+
+    statement
+}
+
+/// Quotes the text as an escape string constant, whose meaning doesn't depend on the
+/// `standard_conforming_strings` setting of the server.
+fn quote_literal(text: &str, buffer: &mut String) {
+    buffer.push_str("E'");
+    for c in text.chars() {
+        match c {
+            '\'' => buffer.push_str("''"),
+            '\\' => buffer.push_str("\\\\"),
+            c => buffer.push(c),
+        }
+    }
+    buffer.push('\'');
+}
+
+fn column_list(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|column| quote_identifier(column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+    use chrono::{TimeZone, Utc};
+    use std::collections::BTreeMap;
+
+    fn event(fields: &[(&str, Value)]) -> Event {
+        let mut log = LogEvent::default();
+        for (key, value) in fields {
+            log.insert_flat(*key, value.clone());
+        }
+        log.into()
+    }
+
+    fn columns(columns: &[&str]) -> Vec<String> {
+        columns.iter().map(|column| column.to_string()).collect()
+    }
+
+    #[test]
+    fn quotes_tables() {
+        assert_eq!(quote_table("logs"), r#""logs""#);
+        assert_eq!(quote_table("app.logs"), r#""app"."logs""#);
+        assert_eq!(quote_table(r#"we"ird"#), r#""we""ird""#);
+    }
+
+    #[test]
+    fn selects_present_columns() {
+        let events = vec![
+            event(&[("id", 1.into()), ("message", "a".into())]),
+            event(&[("id", 2.into()), ("unknown", "b".into())]),
+        ];
+        assert_eq!(
+            batch_columns(&columns(&["id", "host", "message"]), &events),
+            columns(&["id", "message"])
+        );
+    }
+
+    #[test]
+    fn encodes_copy_rows() {
+        let mut map = BTreeMap::new();
+        map.insert("key".to_string(), Value::from("value"));
+        let events = vec![
+            event(&[
+                ("id", 1.into()),
+                ("message", "tab\there\nnew \\ line".into()),
+                ("tags", Value::Map(map)),
+            ]),
+            event(&[
+                ("id", 2.into()),
+                (
+                    "timestamp",
+                    Utc.ymd(2021, 11, 1).and_hms_milli(12, 30, 0, 500).into(),
+                ),
+                ("ok", true.into()),
+            ]),
+        ];
+
+        let copy = encode_copy(
+            &columns(&["id", "message", "tags", "timestamp", "ok"]),
+            &events,
+        );
+        assert_eq!(
+            String::from_utf8(copy).unwrap(),
+            concat!(
+                "1\ttab\\there\\nnew \\\\ line\t{\"key\":\"value\"}\t\\N\t\\N\n",
+                "2\t\\N\t\\N\t2021-11-01T12:30:00.500Z\ttrue\n",
+            )
+        );
+        assert_eq!(
+            copy_statement("app.logs", &columns(&["id", "message"])),
+            r#"COPY "app"."logs" ("id", "message") FROM STDIN"#
+        );
+    }
+
+    #[test]
+    fn renders_insert() {
+        let events = vec![
+            event(&[("id", 1.into()), ("message", "it's".into())]),
+            event(&[("id", 2.into())]),
+            event(&[
+                ("id", 3.into()),
+                ("message", r"\'); DROP TABLE logs; --".into()),
+            ]),
+        ];
+        assert_eq!(
+            insert_statement("logs", &columns(&["id", "message"]), &events, None),
+            concat!(
+                r#"INSERT INTO "logs" ("id", "message") VALUES "#,
+                r"(E'1', E'it''s'), (E'2', NULL), (E'3', E'\\''); DROP TABLE logs; --')"
+            )
+        );
+    }
+
+    #[test]
+    fn renders_upsert() {
+        let events = vec![
+            event(&[("id", 1.into()), ("message", "first".into())]),
+            event(&[("id", 2.into()), ("message", "second".into())]),
+            event(&[("id", 1.into()), ("message", "third".into())]),
+        ];
+        let on_conflict = OnConflictConfig {
+            columns: columns(&["id"]),
+            action: ConflictAction::Update,
+        };
+        assert_eq!(
+            insert_statement(
+                "logs",
+                &columns(&["id", "message"]),
+                &events,
+                Some(&on_conflict)
+            ),
+            concat!(
+                r#"INSERT INTO "logs" ("id", "message") VALUES (E'1', E'third'), (E'2', E'second')"#,
+                r#" ON CONFLICT ("id") DO UPDATE SET "message" = EXCLUDED."message""#
+            )
+        );
+    }
+
+    #[test]
+    fn renders_insert_ignoring_conflicts() {
+        let events = vec![event(&[("id", 1.into())]), event(&[("id", 1.into())])];
+        let on_conflict = OnConflictConfig {
+            columns: Vec::new(),
+            action: ConflictAction::Nothing,
+        };
+        assert_eq!(
+            insert_statement("logs", &columns(&["id"]), &events, Some(&on_conflict)),
+            r#"INSERT INTO "logs" ("id") VALUES (E'1'), (E'1') ON CONFLICT DO NOTHING"#
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use crate::config::SinkDescription;
+
+mod config;
+mod encoder;
+#[cfg(all(test, feature = "postgres-integration-tests"))]
+mod integration_tests;
+mod service;
+mod sink;
+
+pub use self::config::PostgresSinkConfig;
+
+inventory::submit! {
+    SinkDescription::new::<PostgresSinkConfig>("postgres")
+}
@@ -0,0 +1,361 @@
+use super::{
+    config::{Mode, OnConflictConfig, PostgresTlsConfig},
+    encoder,
+};
+use crate::{
+    event::{Event, EventFinalizers, EventStatus, Finalizable},
+    internal_events::{PostgresColumnsDiscovered, PostgresConnectionFailed, PostgresWriteFailed},
+    sinks::util::retries::RetryLogic,
+};
+use bytes::Bytes;
+use futures::{future::BoxFuture, SinkExt};
+use openssl::{
+    error::ErrorStack,
+    ssl::{SslConnector, SslMethod},
+};
+use postgres_openssl::MakeTlsConnector;
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio_postgres::{error::SqlState, Client, Config, Error as PgError, NoTls};
+use tower::Service;
+use vector_core::{buffers::Ackable, internal_event::EventsSent, stream::DriverResponse};
+
+const COLUMNS_QUERY: &str = "SELECT column_name::text FROM information_schema.columns \
+    WHERE table_schema = COALESCE($1::text, current_schema()) AND table_name = $2::text \
+    ORDER BY ordinal_position";
+
+#[derive(Debug, Snafu)]
+pub enum PostgresError {
+    #[snafu(display("failed to create tls connector: {}", source))]
+    TlsFailed { source: ErrorStack },
+    #[snafu(display("failed to connect: {}", source))]
+    ConnectionFailed { source: PgError },
+    #[snafu(display("failed to query columns of table {:?}: {}", table, source))]
+    ColumnsQueryFailed { source: PgError, table: String },
+    #[snafu(display("table {:?} does not exist or has no columns", table))]
+    UnknownTable { table: String },
+    #[snafu(display("failed to write to table {:?}: {}", table, source))]
+    WriteFailed { source: PgError, table: String },
+}
+
+impl PostgresError {
+    fn pg_error(&self) -> Option<&PgError> {
+        match self {
+            Self::ConnectionFailed { source }
+            | Self::ColumnsQueryFailed { source, .. }
+            | Self::WriteFailed { source, .. } => Some(source),
+            Self::TlsFailed { .. } | Self::UnknownTable { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresRequest {
+    pub table: String,
+    pub events: Vec<Event>,
+    pub on_conflict: Option<OnConflictConfig>,
+    pub finalizers: EventFinalizers,
+    pub events_byte_size: usize,
+}
+
+impl Ackable for PostgresRequest {
+    fn ack_size(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl Finalizable for PostgresRequest {
+    fn take_finalizers(&mut self) -> EventFinalizers {
+        std::mem::take(&mut self.finalizers)
+    }
+}
+
+pub struct PostgresResponse {
+    count: usize,
+    events_byte_size: usize,
+}
+
+impl DriverResponse for PostgresResponse {
+    fn event_status(&self) -> EventStatus {
+        // Responses are only produced once the transaction has been committed.
+        EventStatus::Delivered
+    }
+
+    fn events_sent(&self) -> EventsSent {
+        EventsSent {
+            count: self.count,
+            byte_size: self.events_byte_size,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresRetryLogic;
+
+impl RetryLogic for PostgresRetryLogic {
+    type Error = PostgresError;
+    type Response = PostgresResponse;
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error.pg_error() {
+            // Errors without a SQL state come from the connection itself.
+            Some(error) => match error.code() {
+                None => true,
+                Some(code) => {
+                    let code = code.code();
+                    // Connection exceptions, transaction rollbacks (serialization failures and
+                    // deadlocks), insufficient resources and operator interventions.
+                    ["08", "40", "53", "57"]
+                        .iter()
+                        .any(|class| code.starts_with(class))
+                        && code != SqlState::QUERY_CANCELED.code()
+                }
+            },
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresService {
+    config: Arc<Config>,
+    tls: Option<MakeTlsConnector>,
+    mode: Mode,
+    /// The idle connections, each in-flight request taking one of them or opening a new one.
+    clients: Arc<Mutex<Vec<Client>>>,
+    columns: Arc<Mutex<HashMap<String, Arc<Vec<String>>>>>,
+}
+
+impl PostgresService {
+    pub fn new(
+        config: Config,
+        tls_config: Option<PostgresTlsConfig>,
+        mode: Mode,
+    ) -> Result<Self, PostgresError> {
+        let tls = match tls_config {
+            Some(tls_config) => {
+                let mut builder =
+                    SslConnector::builder(SslMethod::tls_client()).context(TlsFailed)?;
+                builder.set_ca_file(tls_config.ca_file).context(TlsFailed)?;
+                Some(MakeTlsConnector::new(builder.build()))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            config: Arc::new(config),
+            tls,
+            mode,
+            clients: Arc::new(Mutex::new(Vec::new())),
+            columns: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub async fn ping(&self) -> Result<(), PostgresError> {
+        let client = self.client().await?;
+        let result = client.simple_query("SELECT 1").await;
+        self.release(client);
+        result.context(ConnectionFailed)?;
+        Ok(())
+    }
+
+    pub async fn columns(&self, table: &str) -> Result<Arc<Vec<String>>, PostgresError> {
+        let client = self.client().await?;
+        let result = self.table_columns(&client, table).await;
+        self.release(client);
+        result
+    }
+
+    /// Takes an idle connection, or opens a new one if there is none left.
+    async fn client(&self) -> Result<Client, PostgresError> {
+        let mut disconnected = false;
+        loop {
+            let idle = self.clients.lock().unwrap().pop();
+            match idle {
+                Some(client) if client.is_closed() => disconnected = true,
+                Some(client) => return Ok(client),
+                None => break,
+            }
+        }
+        if disconnected {
+            // The schema may have changed while we were disconnected.
+            self.columns.lock().unwrap().clear();
+        }
+
+        match &self.tls {
+            Some(tls) => {
+                let (client, connection) = self
+                    .config
+                    .connect(tls.clone())
+                    .await
+                    .context(ConnectionFailed)?;
+                tokio::spawn(connection);
+                Ok(client)
+            }
+            None => {
+                let (client, connection) =
+                    self.config.connect(NoTls).await.context(ConnectionFailed)?;
+                tokio::spawn(connection);
+                Ok(client)
+            }
+        }
+    }
+
+    /// Returns a connection to the idle ones, unless it has been closed.
+    fn release(&self, client: Client) {
+        if !client.is_closed() {
+            self.clients.lock().unwrap().push(client);
+        }
+    }
+
+    async fn table_columns(
+        &self,
+        client: &Client,
+        table: &str,
+    ) -> Result<Arc<Vec<String>>, PostgresError> {
+        if let Some(columns) = self.columns.lock().unwrap().get(table) {
+            return Ok(Arc::clone(columns));
+        }
+
+        let (schema, name) = match table.split_once('.') {
+            Some((schema, name)) => (Some(schema), name),
+            None => (None, table),
+        };
+        let columns = client
+            .query(COLUMNS_QUERY, &[&schema, &name])
+            .await
+            .with_context(|| ColumnsQueryFailed { table })?
+            .iter()
+            .map(|row| row.get::<_, String>(0))
+            .collect::<Vec<_>>();
+        if columns.is_empty() {
+            return Err(PostgresError::UnknownTable {
+                table: table.to_owned(),
+            });
+        }
+
+        emit!(&PostgresColumnsDiscovered {
+            table,
+            count: columns.len(),
+        });
+        let columns = Arc::new(columns);
+        self.columns
+            .lock()
+            .unwrap()
+            .insert(table.to_owned(), Arc::clone(&columns));
+        Ok(columns)
+    }
+
+    async fn write(&self, request: &PostgresRequest) -> Result<(), PostgresError> {
+        let mut client = self.client().await?;
+        let result = self.write_with(&mut client, request).await;
+        self.release(client);
+        result
+    }
+
+    async fn write_with(
+        &self,
+        client: &mut Client,
+        request: &PostgresRequest,
+    ) -> Result<(), PostgresError> {
+        let table_columns = self.table_columns(client, &request.table).await?;
+
+        let columns = encoder::batch_columns(&table_columns, &request.events);
+        if columns.is_empty() {
+            return Ok(());
+        }
+
+        let result = write_batch(client, self.mode, &columns, request).await;
+        if result.is_err() {
+            // Forget the columns in case the table has been altered.
+            self.columns.lock().unwrap().remove(&request.table);
+        }
+        result.with_context(|| WriteFailed {
+            table: request.table.clone(),
+        })
+    }
+}
+
+async fn write_batch(
+    client: &mut Client,
+    mode: Mode,
+    columns: &[String],
+    request: &PostgresRequest,
+) -> Result<(), PgError> {
+    let transaction = client.transaction().await?;
+    match mode {
+        Mode::Copy => {
+            let sink = transaction
+                .copy_in::<_, Bytes>(encoder::copy_statement(&request.table, columns).as_str())
+                .await?;
+            futures::pin_mut!(sink);
+            sink.send(Bytes::from(encoder::encode_copy(columns, &request.events)))
+                .await?;
+            sink.finish().await?;
+        }
+        Mode::Insert => {
+            let statement = encoder::insert_statement(
+                &request.table,
+                columns,
+                &request.events,
+                request.on_conflict.as_ref(),
+            );
+            transaction.execute(statement.as_str(), &[]).await?;
+        }
+    }
+    transaction.commit().await
+}
+
+impl Service<PostgresRequest> for PostgresService {
+    type Response = PostgresResponse;
+    type Error = PostgresError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: PostgresRequest) -> Self::Future {
+        let service = self.clone();
+
+        Box::pin(async move {
+            match service.write(&request).await {
+                Ok(()) => Ok(PostgresResponse {
+                    count: request.events.len(),
+                    events_byte_size: request.events_byte_size,
+                }),
+                Err(error) => {
+                    match &error {
+                        PostgresError::ConnectionFailed { .. } => {
+                            emit!(&PostgresConnectionFailed {
+                                error: error.to_string()
+                            })
+                        }
+                        _ => emit!(&PostgresWriteFailed {
+                            error: error.to_string(),
+                            table: &request.table,
+                        }),
+                    }
+                    Err(error)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_tables_are_not_retried() {
+        let error = PostgresError::UnknownTable {
+            table: "logs".into(),
+        };
+        assert!(!PostgresRetryLogic.is_retriable_error(&error));
+    }
+}
@@ -0,0 +1,89 @@
+use super::{config::OnConflictConfig, service::PostgresRequest};
+use crate::{
+    event::{Event, Finalizable},
+    internal_events::TemplateRenderingFailed,
+    sinks::util::{SinkBuilderExt, StreamSink},
+    template::Template,
+};
+use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
+use std::fmt;
+use tower::Service;
+use vector_core::{
+    buffers::Acker,
+    partition::Partitioner,
+    stream::{BatcherSettings, DriverResponse},
+    ByteSizeOf,
+};
+
+/// Partitions events by the name of the table they are written to.
+struct TablePartitioner(Template);
+
+impl Partitioner for TablePartitioner {
+    type Item = Event;
+    type Key = Option<String>;
+
+    fn partition(&self, item: &Self::Item) -> Self::Key {
+        self.0
+            .render_string(item)
+            .map_err(|error| {
+                emit!(&TemplateRenderingFailed {
+                    error,
+                    field: Some("table"),
+                    drop_event: true,
+                });
+            })
+            .ok()
+    }
+}
+
+pub struct PostgresSink<S> {
+    pub acker: Acker,
+    pub service: S,
+    pub table: Template,
+    pub on_conflict: Option<OnConflictConfig>,
+    pub batch_settings: BatcherSettings,
+}
+
+impl<S> PostgresSink<S>
+where
+    S: Service<PostgresRequest> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: DriverResponse + Send + 'static,
+    S::Error: fmt::Debug + Into<crate::Error> + Send,
+{
+    async fn run_inner(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let on_conflict = self.on_conflict;
+
+        let sink = input
+            .batched_partitioned(TablePartitioner(self.table), self.batch_settings)
+            .filter_map(|(table, batch)| async move { table.map(move |table| (table, batch)) })
+            .map(move |(table, mut events)| {
+                let finalizers = events.take_finalizers();
+                let events_byte_size = events.size_of();
+                PostgresRequest {
+                    table,
+                    events,
+                    on_conflict: on_conflict.clone(),
+                    finalizers,
+                    events_byte_size,
+                }
+            })
+            .into_driver(self.service, self.acker);
+
+        sink.run().await
+    }
+}
+
+#[async_trait]
+impl<S> StreamSink for PostgresSink<S>
+where
+    S: Service<PostgresRequest> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: DriverResponse + Send + 'static,
+    S::Error: fmt::Debug + Into<crate::Error> + Send,
+{
+    async fn run(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        self.run_inner(input).await
+    }
+}
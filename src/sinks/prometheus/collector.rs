@@ -7,13 +7,22 @@ use indexmap::map::IndexMap;
 use prometheus_parser::{proto, METRIC_NAME_LABEL};
 use std::collections::BTreeMap;
 use std::fmt::Write as _;
-use vector_core::event::metric::{MetricSketch, Quantile};
+use vector_core::event::metric::MetricSketch;
+
+/// The quantiles of sketches, unless the collector uses the configured ones.
+const SKETCH_QUANTILES: [f64; 4] = [0.5, 0.75, 0.9, 0.99];
 
 pub(super) trait MetricCollector {
     type Output;
 
     fn new() -> Self;
 
+    /// Whether sketches are exposed with the configured quantiles, which are unknown (NaN) as
+    /// long as the sketch is empty, rather than with fixed quantiles defaulting to zero.
+    fn sketch_uses_quantiles(&self) -> bool {
+        false
+    }
+
     fn emit_metadata(&mut self, name: &str, fullname: &str, value: &MetricValue);
 
     fn emit_value(
@@ -196,18 +205,19 @@ pub(super) trait MetricCollector {
                 }
                 MetricValue::Sketch { sketch } => match sketch {
                     MetricSketch::AgentDDSketch(ddsketch) => {
-                        for q in [0.5, 0.75, 0.9, 0.99] {
-                            let quantile = Quantile {
-                                quantile: q,
-                                value: ddsketch.quantile(q).unwrap_or(0.0),
-                            };
+                        let (quantiles, unknown) = if self.sketch_uses_quantiles() {
+                            (quantiles, f64::NAN)
+                        } else {
+                            (&SKETCH_QUANTILES[..], 0.0)
+                        };
+                        for quantile in quantiles {
                             self.emit_value(
                                 timestamp,
                                 name,
                                 "",
-                                quantile.value,
+                                ddsketch.quantile(*quantile).unwrap_or(unknown),
                                 tags,
-                                Some(("quantile", quantile.quantile.to_string())),
+                                Some(("quantile", quantile.to_string())),
                             );
                         }
                         self.emit_value(
@@ -233,23 +243,44 @@ pub(super) trait MetricCollector {
     }
 }
 
+/// The text formats of the exposition endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum TextFormat {
+    Prometheus,
+    OpenMetrics,
+}
+
+impl TextFormat {
+    pub(super) const fn content_type(self) -> &'static str {
+        match self {
+            Self::Prometheus => "text/plain; version=0.0.4",
+            Self::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        }
+    }
+}
+
 pub(super) struct StringCollector {
+    format: TextFormat,
     // BTreeMap ensures we get sorted output, which whilst not required is preferable
-    processed: BTreeMap<String, String>,
+    processed: BTreeMap<String, (proto::MetricType, String)>,
 }
 
 impl MetricCollector for StringCollector {
     type Output = String;
 
     fn new() -> Self {
-        let processed = BTreeMap::new();
-        Self { processed }
+        Self::with_format(TextFormat::Prometheus)
+    }
+
+    fn sketch_uses_quantiles(&self) -> bool {
+        true
     }
 
     fn emit_metadata(&mut self, name: &str, fullname: &str, value: &MetricValue) {
         if !self.processed.contains_key(fullname) {
-            let header = Self::encode_header(name, fullname, value);
-            self.processed.insert(fullname.into(), header);
+            let r#type = prometheus_metric_type(value);
+            let header = self.encode_header(name, fullname, r#type);
+            self.processed.insert(fullname.into(), (r#type, header));
         }
     }
 
@@ -262,26 +293,66 @@ impl MetricCollector for StringCollector {
         tags: Option<&BTreeMap<String, String>>,
         extra: Option<(&str, String)>,
     ) {
-        let result = self
+        let (r#type, result) = self
             .processed
             .get_mut(name)
             .expect("metric metadata not encoded");
 
+        if self.format == TextFormat::OpenMetrics
+            && *r#type == proto::MetricType::Summary
+            && !matches!(suffix, "" | "_sum" | "_count")
+        {
+            // OpenMetrics summaries can't hold any other samples, so drop the
+            // `_min`, `_max` and `_avg` of distributions.
+            return;
+        }
+
         result.push_str(name);
-        result.push_str(suffix);
+        match (self.format, *r#type) {
+            // OpenMetrics counter samples always end with `_total`.
+            (TextFormat::OpenMetrics, proto::MetricType::Counter) if !name.ends_with("_total") => {
+                result.push_str("_total")
+            }
+            _ => result.push_str(suffix),
+        }
         Self::encode_tags(result, tags, extra);
-        let _ = match timestamp_millis {
-            None => writeln!(result, " {}", value),
-            Some(timestamp) => writeln!(result, " {} {}", value, timestamp),
+        let _ = match (timestamp_millis, self.format) {
+            (None, _) => writeln!(result, " {}", value),
+            (Some(timestamp), TextFormat::Prometheus) => {
+                writeln!(result, " {} {}", value, timestamp)
+            }
+            // OpenMetrics timestamps are in seconds.
+            (Some(timestamp), TextFormat::OpenMetrics) => writeln!(
+                result,
+                " {} {}.{:03}",
+                value,
+                timestamp.div_euclid(1000),
+                timestamp.rem_euclid(1000)
+            ),
         };
     }
 
     fn finish(self) -> String {
-        self.processed.into_iter().map(|(_, value)| value).collect()
+        let mut output = self
+            .processed
+            .into_iter()
+            .map(|(_, (_, value))| value)
+            .collect::<String>();
+        if self.format == TextFormat::OpenMetrics {
+            output.push_str("# EOF\n");
+        }
+        output
     }
 }
 
 impl StringCollector {
+    pub(super) fn with_format(format: TextFormat) -> Self {
+        Self {
+            format,
+            processed: BTreeMap::new(),
+        }
+    }
+
     fn encode_tags(
         result: &mut String,
         tags: Option<&BTreeMap<String, String>>,
@@ -307,11 +378,20 @@ impl StringCollector {
         .ok();
     }
 
-    fn encode_header(name: &str, fullname: &str, value: &MetricValue) -> String {
-        let r#type = prometheus_metric_type(value).as_str();
+    fn encode_header(&self, name: &str, fullname: &str, r#type: proto::MetricType) -> String {
+        // OpenMetrics counters are named after their family, without the `_total` suffix.
+        let fullname = match (self.format, r#type) {
+            (TextFormat::OpenMetrics, proto::MetricType::Counter) => {
+                fullname.strip_suffix("_total").unwrap_or(fullname)
+            }
+            _ => fullname,
+        };
         format!(
             "# HELP {} {}\n# TYPE {} {}\n",
-            fullname, name, fullname, r#type
+            fullname,
+            name,
+            fullname,
+            r#type.as_str()
         )
     }
 }
@@ -446,6 +526,7 @@ mod tests {
     use chrono::{DateTime, TimeZone};
     use indoc::indoc;
     use pretty_assertions::assert_eq;
+    use vector_core::metrics::AgentDDSketch;
 
     fn encode_one<T: MetricCollector>(
         default_namespace: Option<&str>,
@@ -919,6 +1000,125 @@ mod tests {
         assert!(encoded.timeseries[0].samples[0].timestamp >= now);
     }
 
+    #[test]
+    fn encodes_timestamp_openmetrics() {
+        let metric = Metric::new(
+            "temperature".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Counter { value: 2.0 },
+        )
+        .with_timestamp(Some(timestamp()));
+        let mut s = StringCollector::with_format(TextFormat::OpenMetrics);
+        s.encode_metric(None, &[], &[], false, &metric);
+        assert_eq!(
+            s.finish(),
+            indoc! {r#"
+                # HELP temperature temperature
+                # TYPE temperature counter
+                temperature_total 2 1612325106.789
+                # EOF
+            "#}
+        );
+    }
+
+    #[test]
+    fn encodes_distribution_summary_openmetrics() {
+        let metric = Metric::new(
+            "requests".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 3, 2.0 => 3, 3.0 => 2],
+                statistic: StatisticKind::Summary,
+            },
+        )
+        .with_tags(Some(tags()));
+        let mut s = StringCollector::with_format(TextFormat::OpenMetrics);
+        s.encode_metric(Some("ns"), &[], &[0.5], false, &metric);
+        assert_eq!(
+            s.finish(),
+            indoc! {r#"
+                # HELP ns_requests requests
+                # TYPE ns_requests summary
+                ns_requests{code="200",quantile="0.5"} 2
+                ns_requests_sum{code="200"} 15
+                ns_requests_count{code="200"} 8
+                # EOF
+            "#}
+        );
+    }
+
+    #[test]
+    fn encodes_sketch_text() {
+        let mut ddsketch = AgentDDSketch::with_agent_defaults();
+        ddsketch.insert_many(&[1.0, 2.0, 3.0]);
+        let encoded =
+            encode_one::<StringCollector>(None, &[], &[0.5, 0.99], false, &sketch_metric(ddsketch));
+        let lines = encoded.lines().collect::<Vec<_>>();
+        assert_eq!(lines[1], "# TYPE latency summary");
+        assert!(lines[2].starts_with(r#"latency{code="200",quantile="0.5"} "#));
+        assert!(lines[3].starts_with(r#"latency{code="200",quantile="0.99"} "#));
+        assert_eq!(lines[4], r#"latency_sum{code="200"} 6"#);
+        assert_eq!(lines[5], r#"latency_count{code="200"} 3"#);
+    }
+
+    #[test]
+    fn encodes_empty_sketch_text() {
+        assert_eq!(
+            encode_one::<StringCollector>(
+                None,
+                &[],
+                &[0.5],
+                false,
+                &sketch_metric(AgentDDSketch::with_agent_defaults()),
+            ),
+            indoc! {r#"
+                # HELP latency latency
+                # TYPE latency summary
+                latency{code="200",quantile="0.5"} NaN
+                latency_sum{code="200"} 0
+                latency_count{code="200"} 0
+            "#}
+        );
+    }
+
+    #[test]
+    fn encodes_empty_sketch_request() {
+        // Remote write keeps exposing sketches with fixed quantiles.
+        let encoded = encode_one::<TimeSeries>(
+            None,
+            &[],
+            &[0.95],
+            false,
+            &sketch_metric(AgentDDSketch::with_agent_defaults()),
+        );
+        let quantiles = encoded
+            .timeseries
+            .iter()
+            .filter_map(|series| {
+                let quantile = series
+                    .labels
+                    .iter()
+                    .find(|label| label.name == "quantile")?;
+                Some((quantile.value.as_str(), series.samples[0].value))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            quantiles,
+            vec![("0.5", 0.0), ("0.75", 0.0), ("0.9", 0.0), ("0.99", 0.0)]
+        );
+    }
+
+    fn sketch_metric(ddsketch: AgentDDSketch) -> Metric {
+        Metric::new(
+            "latency".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Sketch {
+                sketch: MetricSketch::AgentDDSketch(ddsketch),
+            },
+        )
+        .with_tags(Some(tags()))
+    }
+
     fn timestamp() -> DateTime<Utc> {
         Utc.ymd(2021, 2, 3).and_hms_milli(4, 5, 6, 789)
     }
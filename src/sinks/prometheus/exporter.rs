@@ -2,6 +2,7 @@ use crate::{
     config::{DataType, GenerateConfig, Resource, SinkConfig, SinkContext, SinkDescription},
     event::metric::{Metric, MetricData, MetricKind, MetricValue},
     event::Event,
+    http::Auth,
     internal_events::PrometheusServerRequestComplete,
     sinks::{
         util::{statistic::validate_quantiles, StreamSink},
@@ -13,7 +14,7 @@ use async_trait::async_trait;
 use chrono::Utc;
 use futures::{future, stream::BoxStream, FutureExt, StreamExt};
 use hyper::{
-    header::HeaderValue,
+    header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE},
     service::{make_service_fn, service_fn},
     Body, Method, Request, Response, Server, StatusCode,
 };
@@ -27,19 +28,23 @@ use std::{
     net::SocketAddr,
     ops::{Deref, DerefMut},
     sync::{Arc, RwLock},
-    time::Instant,
+    time::{Duration, Instant},
 };
 use stream_cancel::{Trigger, Tripwire};
+use subtle::ConstantTimeEq;
 use vector_core::buffers::Acker;
 
-use super::collector::{self, MetricCollector as _};
+use super::collector::{self, MetricCollector as _, TextFormat};
 
 const MIN_FLUSH_PERIOD_SECS: u64 = 1;
+const MIN_SERIES_TTL_SECS: u64 = 1;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
     #[snafu(display("Flush period for sets must be greater or equal to {} secs", min))]
     FlushPeriodTooShort { min: u64 },
+    #[snafu(display("Series TTL must be greater or equal to {} secs", min))]
+    SeriesTtlTooShort { min: u64 },
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -56,6 +61,9 @@ pub struct PrometheusExporterConfig {
     pub quantiles: Vec<f64>,
     #[serde(default = "default_flush_period_secs")]
     pub flush_period_secs: u64,
+    /// How long a series is exposed after its last update. Defaults to `flush_period_secs`.
+    pub series_ttl_secs: Option<u64>,
+    pub auth: Option<Auth>,
 }
 
 impl std::default::Default for PrometheusExporterConfig {
@@ -67,10 +75,18 @@ impl std::default::Default for PrometheusExporterConfig {
             buckets: super::default_histogram_buckets(),
             quantiles: super::default_summary_quantiles(),
             flush_period_secs: default_flush_period_secs(),
+            series_ttl_secs: None,
+            auth: None,
         }
     }
 }
 
+impl PrometheusExporterConfig {
+    fn series_ttl(&self) -> Duration {
+        Duration::from_secs(self.series_ttl_secs.unwrap_or(self.flush_period_secs))
+    }
+}
+
 fn default_address() -> SocketAddr {
     use std::net::{IpAddr, Ipv4Addr};
 
@@ -105,6 +121,12 @@ impl SinkConfig for PrometheusExporterConfig {
             }));
         }
 
+        if matches!(self.series_ttl_secs, Some(ttl) if ttl < MIN_SERIES_TTL_SECS) {
+            return Err(Box::new(BuildError::SeriesTtlTooShort {
+                min: MIN_SERIES_TTL_SECS,
+            }));
+        }
+
         validate_quantiles(&self.quantiles)?;
 
         let sink = PrometheusExporter::new(self.clone(), cx.acker());
@@ -171,37 +193,113 @@ struct MetricMetadata {
     updated_at: Instant,
 }
 
-fn handle(
-    req: Request<Body>,
-    default_namespace: Option<&str>,
-    buckets: &[f64],
-    quantiles: &[f64],
-    expired: bool,
-    metrics: &IndexMap<MetricEntry, MetricMetadata>,
-) -> Response<Body> {
-    let mut response = Response::new(Body::empty());
-
-    match (req.method(), req.uri().path()) {
-        (&Method::GET, "/metrics") => {
-            let mut s = collector::StringCollector::new();
-
-            for (MetricEntry(metric), _) in metrics {
-                s.encode_metric(default_namespace, buckets, quantiles, expired, metric);
-            }
+struct Handler {
+    default_namespace: Option<String>,
+    buckets: Vec<f64>,
+    quantiles: Vec<f64>,
+    series_ttl: Duration,
+    /// The expected authorization header, and the challenge of unauthorized responses.
+    authorization: Option<(HeaderValue, &'static str)>,
+}
 
-            *response.body_mut() = s.finish().into();
+impl Handler {
+    fn new(config: &PrometheusExporterConfig) -> Self {
+        let authorization = config.auth.as_ref().and_then(|auth| {
+            let mut headers = HeaderMap::new();
+            auth.apply_headers_map(&mut headers);
+            let challenge = match auth {
+                Auth::Basic { .. } => r#"Basic realm="vector""#,
+                Auth::Bearer { .. } => r#"Bearer realm="vector""#,
+            };
+            headers
+                .remove(AUTHORIZATION)
+                .map(|authorization| (authorization, challenge))
+        });
 
-            response.headers_mut().insert(
-                "Content-Type",
-                HeaderValue::from_static("text/plain; version=0.0.4"),
-            );
+        Self {
+            default_namespace: config.default_namespace.clone(),
+            buckets: config.buckets.clone(),
+            quantiles: config.quantiles.clone(),
+            series_ttl: config.series_ttl(),
+            authorization,
         }
-        _ => {
-            *response.status_mut() = StatusCode::NOT_FOUND;
+    }
+
+    fn handle(
+        &self,
+        req: &Request<Body>,
+        expired: bool,
+        metrics: &IndexMap<MetricEntry, MetricMetadata>,
+    ) -> Response<Body> {
+        let mut response = Response::new(Body::empty());
+
+        if let Some((expected, challenge)) = &self.authorization {
+            let authorized = req.headers().get(AUTHORIZATION).map_or(false, |provided| {
+                bool::from(provided.as_bytes().ct_eq(expected.as_bytes()))
+            });
+            if !authorized {
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                response
+                    .headers_mut()
+                    .insert(WWW_AUTHENTICATE, HeaderValue::from_static(*challenge));
+                return response;
+            }
         }
+
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/metrics") => {
+                let format = negotiate_format(req.headers());
+                let mut s = collector::StringCollector::with_format(format);
+
+                for (MetricEntry(metric), metadata) in metrics {
+                    // Series which haven't been updated for a while are no longer exposed,
+                    // even if they haven't been removed on flush yet.
+                    if metadata.updated_at.elapsed() >= self.series_ttl {
+                        continue;
+                    }
+                    s.encode_metric(
+                        self.default_namespace.as_deref(),
+                        &self.buckets,
+                        &self.quantiles,
+                        expired,
+                        metric,
+                    );
+                }
+
+                *response.body_mut() = s.finish().into();
+
+                response.headers_mut().insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static(format.content_type()),
+                );
+            }
+            _ => {
+                *response.status_mut() = StatusCode::NOT_FOUND;
+            }
+        }
+
+        response
     }
+}
+
+/// Picks the OpenMetrics text format if the scraper accepts it, the Prometheus one otherwise.
+fn negotiate_format(headers: &HeaderMap) -> TextFormat {
+    let accepts_openmetrics = headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_range| {
+            media_range.split(';').next().map_or(false, |media_type| {
+                media_type.trim() == "application/openmetrics-text"
+            })
+        });
 
-    response
+    if accepts_openmetrics {
+        TextFormat::OpenMetrics
+    } else {
+        TextFormat::Prometheus
+    }
 }
 
 impl PrometheusExporter {
@@ -223,16 +321,12 @@ impl PrometheusExporter {
         }
 
         let metrics = Arc::clone(&self.metrics);
-        let default_namespace = self.config.default_namespace.clone();
-        let buckets = self.config.buckets.clone();
-        let quantiles = self.config.quantiles.clone();
+        let handler = Arc::new(Handler::new(&self.config));
         let flush_period_secs = self.config.flush_period_secs;
 
         let new_service = make_service_fn(move |_| {
             let metrics = Arc::clone(&metrics);
-            let default_namespace = default_namespace.clone();
-            let buckets = buckets.clone();
-            let quantiles = quantiles.clone();
+            let handler = Arc::clone(&handler);
             let flush_period_secs = flush_period_secs;
 
             async move {
@@ -246,16 +340,7 @@ impl PrometheusExporter {
                         method = ?req.method(),
                         path = ?req.uri().path(),
                     )
-                    .in_scope(|| {
-                        handle(
-                            req,
-                            default_namespace.as_deref(),
-                            &buckets,
-                            &quantiles,
-                            expired,
-                            &metrics.map,
-                        )
-                    });
+                    .in_scope(|| handler.handle(&req, expired, &metrics.map));
 
                     emit!(&PrometheusServerRequestComplete {
                         status_code: response.status(),
@@ -310,6 +395,7 @@ impl StreamSink for PrometheusExporter {
             if interval > self.config.flush_period_secs as i64 {
                 metrics.last_flush_timestamp = now;
 
+                let series_ttl = self.config.series_ttl();
                 let now = Instant::now();
                 metrics.map = metrics
                     .map
@@ -321,8 +407,7 @@ impl StreamSink for PrometheusExporter {
                         (entry, metadata)
                     })
                     .filter(|(_metric, metadata)| {
-                        now.duration_since(metadata.updated_at).as_secs()
-                            < self.config.flush_period_secs
+                        now.duration_since(metadata.updated_at) < series_ttl
                     })
                     .collect();
             }
@@ -657,6 +742,137 @@ mod tests {
             &MetricValue::Counter { value: 33. }
         );
     }
+
+    fn gauge_entry(name: &str, age: time::Duration) -> (MetricEntry, MetricMetadata) {
+        let metric = Metric::new(
+            name,
+            MetricKind::Absolute,
+            MetricValue::Gauge { value: 1.0 },
+        );
+        let metadata = MetricMetadata {
+            is_incremental_set: false,
+            updated_at: Instant::now() - age,
+        };
+        (MetricEntry(metric), metadata)
+    }
+
+    async fn body_string(response: Response<Body>) -> String {
+        let bytes = hyper::body::to_bytes(response.into_body())
+            .await
+            .expect("Reading body failed");
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn hides_series_past_ttl() {
+        let config = PrometheusExporterConfig {
+            series_ttl_secs: Some(5),
+            ..Default::default()
+        };
+        let metrics = vec![
+            gauge_entry("fresh", time::Duration::from_secs(1)),
+            gauge_entry("stale", time::Duration::from_secs(10)),
+        ]
+        .into_iter()
+        .collect();
+
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = Handler::new(&config).handle(&request, false, &metrics);
+        let body = body_string(response).await;
+
+        assert!(body.contains("fresh 1"));
+        assert!(!body.contains("stale"));
+    }
+
+    #[tokio::test]
+    async fn requires_authorization() {
+        let auth = Auth::Basic {
+            user: "user".to_owned(),
+            password: "password".to_owned(),
+        };
+        let config = PrometheusExporterConfig {
+            auth: Some(auth.clone()),
+            ..Default::default()
+        };
+        let handler = Handler::new(&config);
+        let metrics = vec![gauge_entry("up", time::Duration::from_secs(0))]
+            .into_iter()
+            .collect();
+
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = handler.handle(&request, false, &metrics);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Basic realm="vector""#
+        );
+
+        let mut request = Request::get("/metrics").body(Body::empty()).unwrap();
+        auth.apply(&mut request);
+        let response = handler.handle(&request, false, &metrics);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_string(response).await.contains("up 1"));
+
+        let config = PrometheusExporterConfig {
+            auth: Some(Auth::Bearer {
+                token: "token".to_owned(),
+            }),
+            ..Default::default()
+        };
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = Handler::new(&config).handle(&request, false, &metrics);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Bearer realm="vector""#
+        );
+    }
+
+    #[tokio::test]
+    async fn negotiates_openmetrics() {
+        let handler = Handler::new(&PrometheusExporterConfig::default());
+        let metrics = vec![gauge_entry("up", time::Duration::from_secs(0))]
+            .into_iter()
+            .collect();
+
+        let request = Request::get("/metrics")
+            .header(
+                ACCEPT,
+                "application/openmetrics-text; version=1.0.0,text/plain;q=0.5",
+            )
+            .body(Body::empty())
+            .unwrap();
+        let response = handler.handle(&request, false, &metrics);
+        assert_eq!(
+            response.headers()[CONTENT_TYPE],
+            "application/openmetrics-text; version=1.0.0; charset=utf-8"
+        );
+        assert!(body_string(response).await.ends_with("# EOF\n"));
+
+        let request = Request::get("/metrics")
+            .header(ACCEPT, "text/plain")
+            .body(Body::empty())
+            .unwrap();
+        let response = handler.handle(&request, false, &metrics);
+        assert_eq!(
+            response.headers()[CONTENT_TYPE],
+            "text/plain; version=0.0.4"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_short_series_ttl() {
+        let config = PrometheusExporterConfig {
+            address: next_addr(),
+            series_ttl_secs: Some(0),
+            ..Default::default()
+        };
+        let error = config.build(SinkContext::new_test()).await.err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Series TTL must be greater or equal to 1 secs"
+        );
+    }
 }
 
 #[cfg(all(test, feature = "prometheus-integration-tests"))]
@@ -64,7 +64,7 @@ impl SinkConfig for SematextLogsConfig {
         };
 
         let (sink, healthcheck) = ElasticSearchConfig {
-            endpoint,
+            endpoints: vec![endpoint],
             compression: Compression::None,
             doc_type: Some("logs".to_string()),
             index: Some(self.token.clone()),
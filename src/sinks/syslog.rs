@@ -0,0 +1,611 @@
+use crate::{
+    codecs::{CharacterDelimitedCodec, OctetCountingCodec},
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, Value},
+    internal_events::{SyslogSinkInvalidField, TemplateRenderingFailed},
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        tcp::TcpSinkConfig,
+        udp::UdpSinkConfig,
+        Encoding,
+    },
+    template::Template,
+};
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write};
+use tokio_util::codec::Encoder;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+// TODO: add back when serde-rs/serde#1358 is addressed
+// #[serde(deny_unknown_fields)]
+pub struct SyslogSinkConfig {
+    #[serde(flatten)]
+    pub mode: Mode,
+    #[serde(default)]
+    pub format: Format,
+    #[serde(default)]
+    pub framing: Framing,
+    pub facility: Option<Template>,
+    pub severity: Option<Template>,
+    pub hostname: Option<Template>,
+    pub app_name: Option<Template>,
+    pub proc_id: Option<Template>,
+    pub msg_id: Option<Template>,
+    #[serde(default)]
+    pub structured_data: BTreeMap<String, BTreeMap<String, Template>>,
+    pub encoding: EncodingConfig<Encoding>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Mode {
+    Tcp(TcpSinkConfig),
+    Udp(UdpSinkConfig),
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[derivative(Default)]
+    Rfc5424,
+    Rfc3164,
+}
+
+/// The framing of messages sent over TCP, see RFC 6587. Datagrams always
+/// contain a single message and are not framed.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Framing {
+    #[derivative(Default)]
+    OctetCounting,
+    NewlineDelimited,
+}
+
+inventory::submit! {
+    SinkDescription::new::<SyslogSinkConfig>("syslog")
+}
+
+impl GenerateConfig for SyslogSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"address = "127.0.0.1:6514"
+            mode = "tcp"
+            encoding.codec = "text""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "syslog")]
+impl SinkConfig for SyslogSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let framing = match self.mode {
+            Mode::Tcp(_) => Some(self.framing),
+            Mode::Udp(_) => None,
+        };
+        let encoder = SyslogEncoder {
+            format: self.format,
+            framing,
+            facility: self.facility.clone(),
+            severity: self.severity.clone(),
+            hostname: self.hostname.clone(),
+            default_hostname: crate::get_hostname().ok(),
+            app_name: self.app_name.clone(),
+            proc_id: self.proc_id.clone(),
+            msg_id: self.msg_id.clone(),
+            structured_data: self.structured_data.clone(),
+            encoding: self.encoding.clone(),
+        };
+        let encode_event = move |event| Some(encoder.encode_event(event));
+
+        match &self.mode {
+            Mode::Tcp(config) => config.build(cx, encode_event),
+            Mode::Udp(config) => config.build(cx, encode_event),
+        }
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "syslog"
+    }
+}
+
+/// The facility names, indexed by their code.
+const FACILITIES: &[&str] = &[
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+/// The severity names, indexed by their code, with their common aliases.
+const SEVERITIES: &[&[&str]] = &[
+    &["emerg", "emergency", "panic"],
+    &["alert"],
+    &["crit", "critical"],
+    &["err", "error"],
+    &["warning", "warn"],
+    &["notice"],
+    &["info", "informational"],
+    &["debug"],
+];
+
+const DEFAULT_FACILITY: u8 = 1;
+const DEFAULT_SEVERITY: u8 = 6;
+const DEFAULT_APP_NAME: &str = "vector";
+const NIL_VALUE: &str = "-";
+
+fn parse_facility(value: &str) -> Option<u8> {
+    match value.parse::<u8>() {
+        Ok(code) => (usize::from(code) < FACILITIES.len()).then(|| code),
+        Err(_) => FACILITIES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+            .map(|code| code as u8),
+    }
+}
+
+fn parse_severity(value: &str) -> Option<u8> {
+    match value.parse::<u8>() {
+        Ok(code) => (usize::from(code) < SEVERITIES.len()).then(|| code),
+        Err(_) => SEVERITIES
+            .iter()
+            .position(|names| names.iter().any(|name| name.eq_ignore_ascii_case(value)))
+            .map(|code| code as u8),
+    }
+}
+
+struct SyslogEncoder {
+    format: Format,
+    framing: Option<Framing>,
+    facility: Option<Template>,
+    severity: Option<Template>,
+    hostname: Option<Template>,
+    default_hostname: Option<String>,
+    app_name: Option<Template>,
+    proc_id: Option<Template>,
+    msg_id: Option<Template>,
+    structured_data: BTreeMap<String, BTreeMap<String, Template>>,
+    encoding: EncodingConfig<Encoding>,
+}
+
+impl SyslogEncoder {
+    fn encode_event(&self, mut event: Event) -> Bytes {
+        let facility = self
+            .render("facility", self.facility.as_ref(), &event)
+            .and_then(|value| Self::parse("facility", value, parse_facility))
+            .unwrap_or(DEFAULT_FACILITY);
+        let severity = self
+            .render("severity", self.severity.as_ref(), &event)
+            .and_then(|value| Self::parse("severity", value, parse_severity))
+            .unwrap_or(DEFAULT_SEVERITY);
+        let priority = u16::from(facility) * 8 + u16::from(severity);
+
+        let log = event.as_log();
+        let timestamp = match log.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => *timestamp,
+            _ => Utc::now(),
+        };
+        let hostname = self
+            .render("hostname", self.hostname.as_ref(), &event)
+            .or_else(|| {
+                log.get(log_schema().host_key())
+                    .map(|host| host.to_string_lossy())
+            })
+            .or_else(|| self.default_hostname.clone());
+        let app_name = self.render("app_name", self.app_name.as_ref(), &event);
+        let proc_id = self.render("proc_id", self.proc_id.as_ref(), &event);
+
+        let mut output = format!("<{}>", priority);
+        match self.format {
+            Format::Rfc5424 => {
+                let msg_id = self.render("msg_id", self.msg_id.as_ref(), &event);
+                let _ = write!(
+                    output,
+                    "1 {} {} {} {} {} ",
+                    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+                    header_field(hostname.as_deref(), 255),
+                    header_field(app_name.as_deref().or(Some(DEFAULT_APP_NAME)), 48),
+                    header_field(proc_id.as_deref(), 128),
+                    header_field(msg_id.as_deref(), 32),
+                );
+                self.write_structured_data(&mut output, &event);
+            }
+            Format::Rfc3164 => {
+                write_rfc3164_header(
+                    &mut output,
+                    timestamp,
+                    hostname.as_deref(),
+                    app_name.as_deref().unwrap_or(DEFAULT_APP_NAME),
+                    proc_id.as_deref().unwrap_or(NIL_VALUE),
+                );
+            }
+        }
+
+        // The header fields are rendered before the encoding rules are
+        // applied, so they can refer to fields excluded from the message.
+        self.encoding.apply_rules(&mut event);
+        let log = event.into_log();
+        let message = match self.encoding.codec() {
+            Encoding::Json => serde_json::to_string(&log).unwrap(),
+            Encoding::Text => log
+                .get(log_schema().message_key())
+                .map(|value| value.to_string_lossy())
+                .unwrap_or_default(),
+        };
+        output.push(' ');
+        output.push_str(&message);
+
+        let mut buffer = BytesMut::new();
+        let _ = match self.framing {
+            Some(Framing::OctetCounting) => OctetCountingCodec::new().encode(output, &mut buffer),
+            Some(Framing::NewlineDelimited) => {
+                CharacterDelimitedCodec::new('\n').encode(output, &mut buffer)
+            }
+            None => return output.into(),
+        };
+        buffer.freeze()
+    }
+
+    fn render(
+        &self,
+        field: &'static str,
+        template: Option<&Template>,
+        event: &Event,
+    ) -> Option<String> {
+        template.and_then(|template| {
+            template
+                .render_string(event)
+                .map_err(|error| {
+                    emit!(&TemplateRenderingFailed {
+                        error,
+                        field: Some(field),
+                        drop_event: false,
+                    })
+                })
+                .ok()
+        })
+    }
+
+    fn parse(field: &'static str, value: String, parse: fn(&str) -> Option<u8>) -> Option<u8> {
+        let code = parse(&value);
+        if code.is_none() {
+            emit!(&SyslogSinkInvalidField { field, value });
+        }
+        code
+    }
+
+    fn write_structured_data(&self, output: &mut String, event: &Event) {
+        let mut elements = 0;
+        for (id, params) in &self.structured_data {
+            let _ = write!(output, "[{}", sd_name(id));
+            for (name, template) in params {
+                if let Some(value) = self.render("structured_data", Some(template), event) {
+                    let _ = write!(
+                        output,
+                        " {}=\"{}\"",
+                        sd_name(name),
+                        escape_param_value(&value)
+                    );
+                }
+            }
+            output.push(']');
+            elements += 1;
+        }
+        if elements == 0 {
+            output.push_str(NIL_VALUE);
+        }
+    }
+}
+
+fn write_rfc3164_header(
+    output: &mut String,
+    timestamp: DateTime<Utc>,
+    hostname: Option<&str>,
+    app_name: &str,
+    proc_id: &str,
+) {
+    // The tag is limited to 32 characters, and ends at the first
+    // character that is not alphanumeric in most parsers.
+    let tag = app_name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+        .take(32)
+        .collect::<String>();
+    let tag = if tag.is_empty() {
+        DEFAULT_APP_NAME
+    } else {
+        &tag
+    };
+    let _ = write!(
+        output,
+        "{} {} {}",
+        timestamp.format("%b %e %H:%M:%S"),
+        header_field(hostname, 255),
+        tag
+    );
+    let proc_id = header_field(Some(proc_id), 128);
+    if proc_id != NIL_VALUE {
+        let _ = write!(output, "[{}]", proc_id);
+    }
+    output.push(':');
+}
+
+/// Header fields only contain printable ASCII characters, and are replaced
+/// by the nil value when empty.
+fn header_field(value: Option<&str>, max_length: usize) -> String {
+    let value = value
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_length)
+        .collect::<String>();
+    if value.is_empty() {
+        NIL_VALUE.to_owned()
+    } else {
+        value
+    }
+}
+
+/// Structured data IDs and parameter names can't contain `=`, `]`, `"` or
+/// spaces, and are limited to 32 characters.
+fn sd_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+        .take(32)
+        .collect()
+}
+
+fn escape_param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{next_addr, random_lines_with_stream, trace_init, CountReceiver};
+    use chrono::TimeZone;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<SyslogSinkConfig>();
+    }
+
+    fn encoder(config: &str) -> SyslogEncoder {
+        let config: SyslogSinkConfig = toml::from_str(&format!(
+            r#"
+            mode = "udp"
+            address = "127.0.0.1:514"
+            {}
+            "#,
+            config
+        ))
+        .unwrap();
+        SyslogEncoder {
+            format: config.format,
+            framing: None,
+            facility: config.facility,
+            severity: config.severity,
+            hostname: config.hostname,
+            default_hostname: None,
+            app_name: config.app_name,
+            proc_id: config.proc_id,
+            msg_id: config.msg_id,
+            structured_data: config.structured_data,
+            encoding: config.encoding,
+        }
+    }
+
+    fn make_event() -> Event {
+        let mut event = Event::from("hello world");
+        let log = event.as_mut_log();
+        log.insert(
+            log_schema().timestamp_key(),
+            Utc.ymd(2021, 11, 3).and_hms_micro(8, 5, 7, 3000),
+        );
+        log.insert(log_schema().host_key(), "web-1");
+        log.insert("level", "error");
+        log.insert("service", "api");
+        log.insert("request_id", "a\"b]c");
+        event
+    }
+
+    fn encode(encoder: &SyslogEncoder, event: Event) -> String {
+        String::from_utf8(encoder.encode_event(event).to_vec()).unwrap()
+    }
+
+    #[test]
+    fn encodes_rfc5424() {
+        let encoder = encoder(
+            r#"
+            facility = "local3"
+            severity = "{{ level }}"
+            app_name = "{{ service }}"
+            proc_id = "1234"
+            msg_id = "request"
+            structured_data.request.id = "{{ request_id }}"
+            structured_data.request.service = "{{ service }}"
+            structured_data."origin@32473".software = "vector"
+            encoding.codec = "text"
+            "#,
+        );
+
+        assert_eq!(
+            encode(&encoder, make_event()),
+            "<155>1 2021-11-03T08:05:07.003000Z web-1 api 1234 request \
+             [origin@32473 software=\"vector\"][request id=\"a\\\"b\\]c\" service=\"api\"] \
+             hello world"
+        );
+    }
+
+    #[test]
+    fn encodes_rfc5424_defaults() {
+        let encoder = encoder(r#"encoding.codec = "text""#);
+        let mut event = make_event();
+        event.as_mut_log().remove(log_schema().host_key());
+
+        assert_eq!(
+            encode(&encoder, event),
+            "<14>1 2021-11-03T08:05:07.003000Z - vector - - - hello world"
+        );
+    }
+
+    #[test]
+    fn encodes_rfc3164() {
+        let encoder = encoder(
+            r#"
+            format = "rfc3164"
+            facility = "daemon"
+            severity = "3"
+            app_name = "my app"
+            proc_id = "1234"
+            encoding.codec = "text"
+            "#,
+        );
+
+        assert_eq!(
+            encode(&encoder, make_event()),
+            "<27>Nov  3 08:05:07 web-1 myapp[1234]: hello world"
+        );
+
+        let encoder = encoder(
+            r#"
+            format = "rfc3164"
+            encoding.codec = "text"
+            "#,
+        );
+        assert!(encode(&encoder, make_event()).ends_with(" web-1 vector: hello world"));
+    }
+
+    #[test]
+    fn invalid_priority_uses_defaults() {
+        let encoder = encoder(
+            r#"
+            facility = "{{ level }}"
+            severity = "unknown"
+            encoding.codec = "text"
+            "#,
+        );
+
+        assert!(encode(&encoder, make_event()).starts_with("<14>1 "));
+    }
+
+    #[test]
+    fn encodes_json_message_with_rules() {
+        let encoder = encoder(
+            r#"
+            app_name = "{{ service }}"
+            encoding.codec = "json"
+            encoding.except_fields = ["service"]
+            "#,
+        );
+
+        let message = encode(&encoder, make_event());
+        let json = &message[message.find('{').unwrap()..];
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert!(message.contains(" api - "));
+        assert_eq!(value["message"], "hello world");
+        assert!(value.get("service").is_none());
+    }
+
+    #[test]
+    fn frames_tcp_messages() {
+        let mut encoder = encoder(r#"encoding.codec = "text""#);
+        encoder.framing = Some(Framing::OctetCounting);
+        let message = encode(&encoder, make_event());
+        let (length, rest) = message.split_at(message.find(' ').unwrap());
+        assert_eq!(length.parse::<usize>().unwrap(), rest.len() - 1);
+
+        encoder.framing = Some(Framing::NewlineDelimited);
+        assert!(encode(&encoder, make_event()).ends_with("hello world\n"));
+    }
+
+    #[test]
+    fn parses_priorities() {
+        assert_eq!(parse_facility("kern"), Some(0));
+        assert_eq!(parse_facility("LOCAL7"), Some(23));
+        assert_eq!(parse_facility("16"), Some(16));
+        assert_eq!(parse_facility("24"), None);
+        assert_eq!(parse_facility("local8"), None);
+
+        assert_eq!(parse_severity("emerg"), Some(0));
+        assert_eq!(parse_severity("warn"), Some(4));
+        assert_eq!(parse_severity("Informational"), Some(6));
+        assert_eq!(parse_severity("8"), None);
+    }
+
+    #[tokio::test]
+    async fn tcp_stream() {
+        trace_init();
+
+        let addr = next_addr();
+        let config = SyslogSinkConfig {
+            mode: Mode::Tcp(TcpSinkConfig::from_address(addr.to_string())),
+            format: Format::Rfc5424,
+            framing: Framing::NewlineDelimited,
+            facility: None,
+            severity: None,
+            hostname: Some(Template::try_from("test-host").unwrap()),
+            app_name: None,
+            proc_id: None,
+            msg_id: None,
+            structured_data: BTreeMap::new(),
+            encoding: Encoding::Text.into(),
+        };
+        let (sink, _healthcheck) = config.build(SinkContext::new_test()).await.unwrap();
+
+        let mut receiver = CountReceiver::receive_lines(addr);
+
+        let (lines, events) = random_lines_with_stream(10, 100, None);
+        sink.run(events).await.unwrap();
+
+        receiver.connected().await;
+
+        let output = receiver.await;
+        assert_eq!(lines.len(), output.len());
+        for (source, received) in lines.iter().zip(output) {
+            assert!(received.starts_with("<14>1 "));
+            assert!(received.contains(" test-host vector "));
+            assert!(received.ends_with(&format!(" - - {}", source)));
+        }
+    }
+}
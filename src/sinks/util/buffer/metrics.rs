@@ -133,7 +133,7 @@ type MetricEntry = (MetricData, EventMetadata);
 
 /// This is a convenience wrapper for HashMap<MetricSeries, MetricData>
 /// that provides some extra functionality.
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct MetricSet(HashMap<MetricSeries, MetricEntry>);
 
 impl MetricSet {
@@ -228,6 +228,11 @@ impl MetricSet {
         }
     }
 
+    /// Discard the state kept for the given series.
+    pub fn remove(&mut self, series: &MetricSeries) {
+        self.0.remove(series);
+    }
+
     fn insert(&mut self, metric: Metric) {
         let (series, data, metadata) = metric.into_parts();
         self.0.insert(series, (data, metadata));
@@ -1,5 +1,7 @@
 use super::{err_event_too_large, Batch, BatchSize, PushResult};
+use crate::event::LogEvent;
 use bytes::Bytes;
+use vector_core::ByteSizeOf;
 
 pub trait EncodedLength {
     fn encoded_length(&self) -> usize;
@@ -68,6 +70,13 @@ impl EncodedLength for Bytes {
     }
 }
 
+/// Events which are only encoded when building requests are estimated by their size in memory.
+impl EncodedLength for LogEvent {
+    fn encoded_length(&self) -> usize {
+        self.size_of()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,6 +6,10 @@ pub mod compressor;
 pub mod encoding;
 pub mod http;
 pub mod normalizer;
+#[cfg(feature = "parquet")]
+pub mod object_store;
+#[cfg(feature = "parquet")]
+pub mod parquet;
 pub mod partitioner;
 pub mod processed_event;
 pub mod request_builder;
@@ -0,0 +1,227 @@
+//! Encoding shared by the sinks writing each batch of events as an object in a bucket.
+
+use super::{
+    encoding::{Encoder, EncodingConfig, EncodingConfiguration, StandardEncodings},
+    parquet::{ParquetConfig, ParquetEncoder},
+    Compression,
+};
+use crate::event::Event;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{io, sync::Arc};
+use vector_core::stream::BatcherSettings;
+
+#[derive(Debug, Snafu)]
+enum ObjectStoreEncodingError {
+    #[snafu(display("`parquet` options require `encoding.codec = \"parquet\"`"))]
+    ParquetOptionsWithoutCodec,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectStoreEncodings {
+    Text,
+    Json,
+    Ndjson,
+    Parquet,
+}
+
+impl ObjectStoreEncodings {
+    pub const fn content_type(&self) -> &'static str {
+        match self {
+            Self::Text => "text/plain",
+            Self::Json => "application/json",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    const fn standard(self) -> Option<StandardEncodings> {
+        match self {
+            Self::Text => Some(StandardEncodings::Text),
+            Self::Json => Some(StandardEncodings::Json),
+            Self::Ndjson => Some(StandardEncodings::Ndjson),
+            Self::Parquet => None,
+        }
+    }
+}
+
+/// Encodes batches with the configured codec, after applying the field rules of the encoding.
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct ObjectStoreEncoder {
+    encoding: EncodingConfig<ObjectStoreEncodings>,
+    #[derivative(Debug = "ignore")]
+    parquet: Option<Arc<ParquetEncoder>>,
+}
+
+impl ObjectStoreEncoder {
+    /// Creates the encoder. Parquet row groups default to the maximum number of events of a
+    /// batch, so that each object holds a single row group.
+    pub fn new(
+        encoding: EncodingConfig<ObjectStoreEncodings>,
+        parquet: Option<&ParquetConfig>,
+        batch_settings: &BatcherSettings,
+    ) -> crate::Result<Self> {
+        let parquet = match (encoding.codec(), parquet) {
+            (ObjectStoreEncodings::Parquet, config) => Some(Arc::new(ParquetEncoder::new(
+                &config.cloned().unwrap_or_default(),
+                batch_settings.item_limit,
+            )?)),
+            (_, Some(_)) => return Err(ObjectStoreEncodingError::ParquetOptionsWithoutCodec.into()),
+            (_, None) => None,
+        };
+
+        Ok(Self { encoding, parquet })
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        self.encoding.codec().content_type()
+    }
+
+    pub const fn is_parquet(&self) -> bool {
+        self.parquet.is_some()
+    }
+
+    /// The compression to apply to encoded batches. Parquet files compress their columns
+    /// themselves, so they are never compressed as a whole.
+    pub const fn compression(&self, compression: Compression) -> Compression {
+        match self.parquet {
+            Some(_) => Compression::None,
+            None => compression,
+        }
+    }
+
+    /// Applies the field rules of the encoding to a batch, and removes the events the Parquet
+    /// schema rejects from it. Must be called before the finalizers of the batch are taken.
+    pub fn prepare(&self, events: &mut Vec<Event>) {
+        for event in events.iter_mut() {
+            self.encoding.apply_rules(event);
+        }
+        if let Some(parquet) = &self.parquet {
+            parquet.reject_mismatched(events);
+        }
+    }
+
+    /// The default extension of the objects' names.
+    pub const fn extension(&self, compression: Compression) -> &'static str {
+        match self.parquet {
+            Some(_) => "parquet",
+            None => compression.extension(),
+        }
+    }
+}
+
+impl Encoder<Vec<Event>> for ObjectStoreEncoder {
+    fn encode_input(&self, input: Vec<Event>, writer: &mut dyn io::Write) -> io::Result<usize> {
+        match (&self.parquet, self.encoding.codec().standard()) {
+            (Some(parquet), _) => parquet.encode_input(input, writer),
+            (None, Some(codec)) => codec.encode_input(input, writer),
+            (None, None) => unreachable!("Parquet encoder is always set for parquet codec."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::time::Duration;
+
+    fn batch_settings() -> BatcherSettings {
+        BatcherSettings {
+            timeout: Duration::from_secs(1),
+            size_limit: 1_000_000,
+            item_limit: 1_000,
+        }
+    }
+
+    #[test]
+    fn parquet_options_require_parquet_codec() {
+        let error = ObjectStoreEncoder::new(
+            ObjectStoreEncodings::Ndjson.into(),
+            Some(&ParquetConfig::default()),
+            &batch_settings(),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "`parquet` options require `encoding.codec = \"parquet\"`"
+        );
+    }
+
+    fn column_compression(encoder: &ObjectStoreEncoder) -> parquet::basic::Compression {
+        let mut file = tempfile::tempfile().unwrap();
+        encoder
+            .encode_input(vec![Event::from("hello")], &mut file)
+            .unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        reader.metadata().row_group(0).column(0).compression()
+    }
+
+    #[test]
+    fn parquet_compresses_columns() {
+        let encoder = ObjectStoreEncoder::new(
+            ObjectStoreEncodings::Parquet.into(),
+            None,
+            &batch_settings(),
+        )
+        .unwrap();
+        assert_eq!(
+            encoder.compression(Compression::gzip_default()),
+            Compression::None
+        );
+        assert_eq!(encoder.extension(Compression::gzip_default()), "parquet");
+        assert_eq!(encoder.content_type(), "application/vnd.apache.parquet");
+        // The compression of the objects doesn't carry over to the columns.
+        assert_eq!(
+            column_compression(&encoder),
+            parquet::basic::Compression::SNAPPY
+        );
+
+        let config: ParquetConfig = toml::from_str(r#"compression = "gzip""#).unwrap();
+        let encoder = ObjectStoreEncoder::new(
+            ObjectStoreEncodings::Parquet.into(),
+            Some(&config),
+            &batch_settings(),
+        )
+        .unwrap();
+        assert_eq!(
+            column_compression(&encoder),
+            parquet::basic::Compression::GZIP
+        );
+
+        let encoder =
+            ObjectStoreEncoder::new(ObjectStoreEncodings::Ndjson.into(), None, &batch_settings())
+                .unwrap();
+        assert_eq!(
+            encoder.compression(Compression::gzip_default()),
+            Compression::gzip_default()
+        );
+        assert_eq!(encoder.extension(Compression::gzip_default()), "log.gz");
+    }
+
+    #[test]
+    fn applies_encoding_rules() {
+        let encoding: EncodingConfig<ObjectStoreEncodings> = toml::from_str(
+            r#"
+            codec = "ndjson"
+            except_fields = ["secret"]
+            "#,
+        )
+        .unwrap();
+        let encoder = ObjectStoreEncoder::new(encoding, None, &batch_settings()).unwrap();
+
+        let mut event = Event::from("hello");
+        event.as_mut_log().insert("secret", "value");
+        let mut events = vec![event];
+        encoder.prepare(&mut events);
+        let mut output = Vec::new();
+        encoder.encode_input(events, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("hello"));
+        assert!(!output.contains("secret"));
+    }
+}
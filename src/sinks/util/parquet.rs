@@ -0,0 +1,655 @@
+//! Encoding of event batches as [Parquet](https://parquet.apache.org/) files.
+//!
+//! Every batch is written as a standalone file. Each top-level field of the events maps to an
+//! optional column of the same name, the set of columns being either configured explicitly or
+//! inferred from the first batch encoded.
+
+use super::encoding::Encoder;
+use crate::{
+    event::{Event, EventStatus, Finalizable, Value},
+    internal_events::ParquetFieldTypeMismatch,
+};
+use chrono::SecondsFormat;
+use parquet::{
+    basic::{Compression, LogicalType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    errors::ParquetError,
+    file::{
+        properties::WriterProperties,
+        writer::{FileWriter, InMemoryWriteableCursor, RowGroupWriter, SerializedFileWriter},
+    },
+    schema::types::{Type, TypePtr},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetType {
+    String,
+    Int64,
+    Double,
+    Boolean,
+    Timestamp,
+    Json,
+}
+
+impl ParquetType {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int64 => "int64",
+            Self::Double => "double",
+            Self::Boolean => "boolean",
+            Self::Timestamp => "timestamp",
+            Self::Json => "json",
+        }
+    }
+
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bytes(_) => Some(Self::String),
+            Value::Integer(_) => Some(Self::Int64),
+            Value::Float(_) => Some(Self::Double),
+            Value::Boolean(_) => Some(Self::Boolean),
+            Value::Timestamp(_) => Some(Self::Timestamp),
+            Value::Map(_) | Value::Array(_) => Some(Self::Json),
+            Value::Null => None,
+        }
+    }
+
+    /// The type of a column holding values of both types: integers and floats are widened to
+    /// doubles, and other conflicting types to strings, or to JSON if either of them is.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Int64, Self::Double) | (Self::Double, Self::Int64) => Self::Double,
+            (Self::Json, _) | (_, Self::Json) => Self::Json,
+            _ => Self::String,
+        }
+    }
+
+    fn column(self, name: &str) -> Result<Type, ParquetError> {
+        let (physical, logical) = match self {
+            Self::String => (PhysicalType::BYTE_ARRAY, LogicalType::UTF8),
+            Self::Int64 => (PhysicalType::INT64, LogicalType::NONE),
+            Self::Double => (PhysicalType::DOUBLE, LogicalType::NONE),
+            Self::Boolean => (PhysicalType::BOOLEAN, LogicalType::NONE),
+            Self::Timestamp => (PhysicalType::INT64, LogicalType::TIMESTAMP_MICROS),
+            Self::Json => (PhysicalType::BYTE_ARRAY, LogicalType::JSON),
+        };
+        Type::primitive_type_builder(name, physical)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_logical_type(logical)
+            .build()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    None,
+    #[derivative(Default)]
+    Snappy,
+    Gzip,
+    Zstd,
+}
+
+impl From<ParquetCompression> for Compression {
+    fn from(compression: ParquetCompression) -> Self {
+        match compression {
+            ParquetCompression::None => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip => Compression::GZIP,
+            ParquetCompression::Zstd => Compression::ZSTD,
+        }
+    }
+}
+
+/// What to do with events having a field whose value doesn't fit the type of its column.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeMismatchAction {
+    /// Write the field as null.
+    #[derivative(Default)]
+    Null,
+    /// Drop the whole event.
+    Reject,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetConfig {
+    pub schema: Option<BTreeMap<String, ParquetType>>,
+    #[serde(default)]
+    pub compression: ParquetCompression,
+    pub row_group_size: Option<usize>,
+    #[serde(default)]
+    pub on_type_mismatch: TypeMismatchAction,
+}
+
+struct Schema {
+    columns: Vec<(String, ParquetType)>,
+    parquet: TypePtr,
+}
+
+impl Schema {
+    fn new(columns: Vec<(String, ParquetType)>) -> Result<Self, ParquetError> {
+        if columns.is_empty() {
+            return Err(ParquetError::General("schema has no columns".into()));
+        }
+
+        let mut fields = columns
+            .iter()
+            .map(|(name, ty)| ty.column(name).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parquet = Type::group_type_builder("vector")
+            .with_fields(&mut fields)
+            .build()?;
+
+        Ok(Self {
+            columns,
+            parquet: Arc::new(parquet),
+        })
+    }
+
+    /// Infers the columns from the non-null values of each top-level field, widening the type
+    /// of fields holding different types so that every value fits its column.
+    fn infer(events: &[Event]) -> Result<Self, ParquetError> {
+        let mut columns = BTreeMap::new();
+        for event in events {
+            for (name, value) in event.as_log().as_map() {
+                if let Some(ty) = ParquetType::of(value) {
+                    let column = columns.entry(name.clone()).or_insert(ty);
+                    *column = column.merge(ty);
+                }
+            }
+        }
+        Self::new(columns.into_iter().collect())
+    }
+}
+
+/// A value converted to the physical type of its column.
+enum Cell {
+    Boolean(bool),
+    Int64(i64),
+    Double(f64),
+    ByteArray(ByteArray),
+}
+
+impl Cell {
+    fn convert(value: &Value, ty: ParquetType) -> Option<Self> {
+        match (ty, value) {
+            (ParquetType::String, Value::Bytes(bytes)) => {
+                Some(Self::ByteArray(bytes.to_vec().into()))
+            }
+            (ParquetType::String, Value::Timestamp(timestamp)) => Some(Self::ByteArray(
+                timestamp
+                    .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                    .into_bytes()
+                    .into(),
+            )),
+            (ParquetType::String, Value::Integer(_) | Value::Float(_) | Value::Boolean(_)) => {
+                Some(Self::ByteArray(value.to_string_lossy().into_bytes().into()))
+            }
+            (ParquetType::Int64, Value::Integer(integer)) => Some(Self::Int64(*integer)),
+            (ParquetType::Double, Value::Float(float)) => Some(Self::Double(*float)),
+            (ParquetType::Double, Value::Integer(integer)) => Some(Self::Double(*integer as f64)),
+            (ParquetType::Boolean, Value::Boolean(boolean)) => Some(Self::Boolean(*boolean)),
+            (ParquetType::Timestamp, Value::Timestamp(timestamp)) => Some(Self::Int64(
+                timestamp.timestamp() * 1_000_000 + i64::from(timestamp.timestamp_subsec_micros()),
+            )),
+            (ParquetType::Json, value) => serde_json::to_vec(value)
+                .ok()
+                .map(|json| Self::ByteArray(json.into())),
+            _ => None,
+        }
+    }
+}
+
+type Row = Vec<Option<Cell>>;
+
+pub struct ParquetEncoder {
+    /// The configured schema, or the one inferred from the first batch.
+    schema: Mutex<Option<Arc<Schema>>>,
+    properties: Arc<WriterProperties>,
+    row_group_size: usize,
+    on_type_mismatch: TypeMismatchAction,
+}
+
+impl ParquetEncoder {
+    /// Creates an encoder writing row groups of at most `row_group_size` rows, unless the
+    /// configuration sets its own size.
+    pub fn new(config: &ParquetConfig, row_group_size: usize) -> crate::Result<Self> {
+        let schema = match &config.schema {
+            Some(schema) => Some(Arc::new(Schema::new(
+                schema
+                    .iter()
+                    .map(|(name, ty)| (name.clone(), *ty))
+                    .collect(),
+            )?)),
+            None => None,
+        };
+        let row_group_size = config.row_group_size.unwrap_or(row_group_size);
+        if row_group_size == 0 {
+            return Err("`parquet.row_group_size` must be greater than zero".into());
+        }
+
+        let properties = WriterProperties::builder()
+            .set_compression(config.compression.into())
+            .set_max_row_group_size(row_group_size)
+            .build();
+
+        Ok(Self {
+            schema: Mutex::new(schema),
+            properties: Arc::new(properties),
+            row_group_size,
+            on_type_mismatch: config.on_type_mismatch,
+        })
+    }
+
+    /// The schema of the files, inferring it from the events if this is the first batch and
+    /// none is configured.
+    fn schema(&self, events: &[Event]) -> Result<Arc<Schema>, ParquetError> {
+        let mut schema = self.schema.lock().expect("lock poisoned");
+        match &*schema {
+            Some(schema) => Ok(Arc::clone(schema)),
+            None => {
+                let inferred = Arc::new(Schema::infer(events)?);
+                *schema = Some(Arc::clone(&inferred));
+                Ok(inferred)
+            }
+        }
+    }
+
+    /// Removes the events the schema rejects from the batch, marking them as errored so that
+    /// they aren't acknowledged along with the rest of it. The first batch fits the schema
+    /// inferred from it, but later ones are checked against it like against a configured one.
+    pub fn reject_mismatched(&self, events: &mut Vec<Event>) {
+        if self.on_type_mismatch != TypeMismatchAction::Reject {
+            return;
+        }
+        let schema = match self.schema(events) {
+            Ok(schema) => schema,
+            // Encoding the batch fails the same way and reports it.
+            Err(_) => return,
+        };
+
+        let (accepted, rejected): (Vec<_>, Vec<_>) = events
+            .drain(..)
+            .partition(|event| self.row(&schema, event).is_some());
+        *events = accepted;
+        for mut event in rejected {
+            event.take_finalizers().update_status(EventStatus::Errored);
+        }
+    }
+
+    fn row(&self, schema: &Schema, event: &Event) -> Option<Row> {
+        let log = event.as_log();
+        let mut row = Vec::with_capacity(schema.columns.len());
+        for (name, ty) in &schema.columns {
+            let cell = match log.get_flat(name) {
+                None | Some(Value::Null) => None,
+                Some(value) => {
+                    let cell = Cell::convert(value, *ty);
+                    if cell.is_none() {
+                        let drop_event = self.on_type_mismatch == TypeMismatchAction::Reject;
+                        emit!(&ParquetFieldTypeMismatch {
+                            field: name,
+                            expected: ty.as_str(),
+                            drop_event,
+                        });
+                        if drop_event {
+                            return None;
+                        }
+                    }
+                    cell
+                }
+            };
+            row.push(cell);
+        }
+        Some(row)
+    }
+
+    /// Encodes the events as a Parquet file.
+    pub fn encode(&self, events: &[Event]) -> Result<Vec<u8>, ParquetError> {
+        let schema = self.schema(events)?;
+        let rows = events
+            .iter()
+            .filter_map(|event| self.row(&schema, event))
+            .collect::<Vec<_>>();
+
+        let cursor = InMemoryWriteableCursor::default();
+        let mut writer = SerializedFileWriter::new(
+            cursor.clone(),
+            Arc::clone(&schema.parquet),
+            Arc::clone(&self.properties),
+        )?;
+        for rows in rows.chunks(self.row_group_size) {
+            let mut row_group = writer.next_row_group()?;
+            write_row_group(&mut *row_group, rows)?;
+            writer.close_row_group(row_group)?;
+        }
+        writer.close()?;
+
+        Ok(cursor.data())
+    }
+}
+
+fn write_row_group(row_group: &mut dyn RowGroupWriter, rows: &[Row]) -> Result<(), ParquetError> {
+    let mut index = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        let cells = rows
+            .iter()
+            .map(|row| row[index].as_ref())
+            .collect::<Vec<_>>();
+        let definition_levels = cells
+            .iter()
+            .map(|cell| i16::from(cell.is_some()))
+            .collect::<Vec<_>>();
+        let levels = Some(definition_levels.as_slice());
+
+        match column {
+            ColumnWriter::BoolColumnWriter(ref mut writer) => {
+                let values = cells
+                    .iter()
+                    .filter_map(|cell| match cell {
+                        Some(Cell::Boolean(value)) => Some(*value),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                writer.write_batch(&values, levels, None)?;
+            }
+            ColumnWriter::Int64ColumnWriter(ref mut writer) => {
+                let values = cells
+                    .iter()
+                    .filter_map(|cell| match cell {
+                        Some(Cell::Int64(value)) => Some(*value),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                writer.write_batch(&values, levels, None)?;
+            }
+            ColumnWriter::DoubleColumnWriter(ref mut writer) => {
+                let values = cells
+                    .iter()
+                    .filter_map(|cell| match cell {
+                        Some(Cell::Double(value)) => Some(*value),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                writer.write_batch(&values, levels, None)?;
+            }
+            ColumnWriter::ByteArrayColumnWriter(ref mut writer) => {
+                let values = cells
+                    .iter()
+                    .filter_map(|cell| match cell {
+                        Some(Cell::ByteArray(value)) => Some(value.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                writer.write_batch(&values, levels, None)?;
+            }
+            _ => unreachable!("Schemas only contain supported column types."),
+        }
+
+        row_group.close_column(column)?;
+        index += 1;
+    }
+    Ok(())
+}
+
+impl Encoder<Vec<Event>> for ParquetEncoder {
+    fn encode_input(&self, input: Vec<Event>, writer: &mut dyn io::Write) -> io::Result<usize> {
+        let file = self
+            .encode(&input)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        writer.write_all(&file).map(|()| file.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{BatchNotifier, BatchStatus, LogEvent};
+    use chrono::{TimeZone, Utc};
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::Field,
+    };
+    use std::io::Write;
+
+    fn event(fields: Vec<(&str, Value)>) -> Event {
+        let mut log = LogEvent::default();
+        for (key, value) in fields {
+            log.insert_flat(key, value);
+        }
+        log.into()
+    }
+
+    fn read(data: Vec<u8>) -> (usize, Vec<Vec<(String, Field)>>) {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&data).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let row_groups = reader.metadata().num_row_groups();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.get_column_iter()
+                    .map(|(name, field)| (name.clone(), field.clone()))
+                    .collect()
+            })
+            .collect();
+        (row_groups, rows)
+    }
+
+    fn field(name: &str, field: Field) -> (String, Field) {
+        (name.to_owned(), field)
+    }
+
+    #[test]
+    fn infers_schema_from_first_batch() {
+        let encoder = ParquetEncoder::new(&ParquetConfig::default(), 100).unwrap();
+        let timestamp = Utc.ymd(2021, 11, 1).and_hms_micro(12, 30, 0, 123_456);
+        let first = vec![
+            event(vec![
+                ("message", "one".into()),
+                ("count", 1.into()),
+                ("timestamp", timestamp.into()),
+            ]),
+            event(vec![
+                ("message", "two".into()),
+                ("count", 2.5.into()),
+                ("ok", true.into()),
+            ]),
+        ];
+        let (_, rows) = read(encoder.encode(&first).unwrap());
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    field("count", Field::Double(1.0)),
+                    field("message", Field::Str("one".into())),
+                    field("ok", Field::Null),
+                    field("timestamp", Field::TimestampMicros(1_635_769_800_123_456)),
+                ],
+                vec![
+                    field("count", Field::Double(2.5)),
+                    field("message", Field::Str("two".into())),
+                    field("ok", Field::Bool(true)),
+                    field("timestamp", Field::Null),
+                ],
+            ]
+        );
+
+        // Later batches keep the schema of the first one.
+        let second = vec![event(vec![("message", "three".into()), ("new", 1.into())])];
+        let (_, rows) = read(encoder.encode(&second).unwrap());
+        assert_eq!(
+            rows,
+            vec![vec![
+                field("count", Field::Null),
+                field("message", Field::Str("three".into())),
+                field("ok", Field::Null),
+                field("timestamp", Field::Null),
+            ]]
+        );
+    }
+
+    #[test]
+    fn checks_later_batches_against_inferred_schema() {
+        let config = ParquetConfig {
+            on_type_mismatch: TypeMismatchAction::Reject,
+            ..ParquetConfig::default()
+        };
+        let encoder = ParquetEncoder::new(&config, 100).unwrap();
+
+        let mut first = vec![event(vec![("id", 1.into())])];
+        encoder.reject_mismatched(&mut first);
+        assert_eq!(first.len(), 1);
+
+        let mut second = vec![
+            event(vec![("id", "two".into())]),
+            event(vec![("id", 3.into())]),
+        ];
+        encoder.reject_mismatched(&mut second);
+        let (_, rows) = read(encoder.encode(&second).unwrap());
+        assert_eq!(rows, vec![vec![field("id", Field::Long(3))]]);
+
+        let encoder = ParquetEncoder::new(&ParquetConfig::default(), 100).unwrap();
+        encoder.encode(&first).unwrap();
+        let (_, rows) = read(
+            encoder
+                .encode(&[event(vec![("id", "two".into())])])
+                .unwrap(),
+        );
+        assert_eq!(rows, vec![vec![field("id", Field::Null)]]);
+    }
+
+    #[test]
+    fn widens_conflicting_types() {
+        let encoder = ParquetEncoder::new(&ParquetConfig::default(), 100).unwrap();
+        let mut tags = BTreeMap::new();
+        tags.insert("env".to_owned(), Value::from("prod"));
+        let events = vec![
+            event(vec![("id", 1.into()), ("tags", "none".into())]),
+            event(vec![("id", "two".into()), ("tags", Value::Map(tags))]),
+        ];
+        let (_, rows) = read(encoder.encode(&events).unwrap());
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    field("id", Field::Str("1".into())),
+                    field("tags", Field::Str(r#""none""#.into())),
+                ],
+                vec![
+                    field("id", Field::Str("two".into())),
+                    field("tags", Field::Str(r#"{"env":"prod"}"#.into())),
+                ],
+            ]
+        );
+    }
+
+    fn explicit_config(on_type_mismatch: TypeMismatchAction) -> ParquetConfig {
+        let mut schema = BTreeMap::new();
+        schema.insert("id".to_owned(), ParquetType::Int64);
+        schema.insert("message".to_owned(), ParquetType::String);
+        schema.insert("tags".to_owned(), ParquetType::Json);
+        ParquetConfig {
+            schema: Some(schema),
+            compression: ParquetCompression::Zstd,
+            row_group_size: None,
+            on_type_mismatch,
+        }
+    }
+
+    fn mismatched_events() -> Vec<Event> {
+        let mut tags = BTreeMap::new();
+        tags.insert("env".to_owned(), Value::from("prod"));
+        vec![
+            event(vec![
+                ("id", 1.into()),
+                ("message", 42.into()),
+                ("tags", Value::Map(tags)),
+            ]),
+            event(vec![("id", "two".into()), ("message", "text".into())]),
+        ]
+    }
+
+    #[test]
+    fn nulls_mismatched_fields() {
+        let encoder = ParquetEncoder::new(&explicit_config(TypeMismatchAction::Null), 100).unwrap();
+        let (_, rows) = read(encoder.encode(&mismatched_events()).unwrap());
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    field("id", Field::Long(1)),
+                    field("message", Field::Str("42".into())),
+                    field("tags", Field::Str(r#"{"env":"prod"}"#.into())),
+                ],
+                vec![
+                    field("id", Field::Null),
+                    field("message", Field::Str("text".into())),
+                    field("tags", Field::Null),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_events() {
+        let encoder =
+            ParquetEncoder::new(&explicit_config(TypeMismatchAction::Reject), 100).unwrap();
+        let (_, rows) = read(encoder.encode(&mismatched_events()).unwrap());
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], field("id", Field::Long(1)));
+    }
+
+    #[test]
+    fn errors_rejected_events() {
+        let encoder =
+            ParquetEncoder::new(&explicit_config(TypeMismatchAction::Reject), 100).unwrap();
+        let (batches, receivers): (Vec<_>, Vec<_>) =
+            (0..2).map(|_| BatchNotifier::new_with_receiver()).unzip();
+        let mut events = mismatched_events()
+            .into_iter()
+            .zip(&batches)
+            .map(|(event, batch)| event.into_log().with_batch_notifier(batch).into())
+            .collect::<Vec<Event>>();
+        drop(batches);
+
+        encoder.reject_mismatched(&mut events);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].as_log()["id"], 1.into());
+
+        let mut receivers = receivers.into_iter();
+        let mut accepted = receivers.next().unwrap();
+        drop(events);
+        assert_eq!(accepted.try_recv(), Ok(BatchStatus::Delivered));
+        assert_eq!(
+            receivers.next().unwrap().try_recv(),
+            Ok(BatchStatus::Errored)
+        );
+    }
+
+    #[test]
+    fn splits_row_groups() {
+        let encoder = ParquetEncoder::new(&ParquetConfig::default(), 2).unwrap();
+        let events = (0..5)
+            .map(|id| event(vec![("id", id.into())]))
+            .collect::<Vec<_>>();
+        let (row_groups, rows) = read(encoder.encode(&events).unwrap());
+        assert_eq!(row_groups, 3);
+        assert_eq!(rows.len(), 5);
+    }
+}
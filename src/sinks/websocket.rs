@@ -0,0 +1,451 @@
+use crate::{
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    dns,
+    event::{Event, EventStatus, Finalizable},
+    http::Auth,
+    internal_events::{
+        ConnectionOpen, OpenGauge, WebSocketConnectionError, WebSocketConnectionEstablished,
+        WebSocketConnectionFailed, WebSocketConnectionShutdown, WebSocketEventSent,
+    },
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        retries::ExponentialBackoff,
+        Encoding, StreamSink,
+    },
+    tls::{MaybeTlsSettings, MaybeTlsStream, TlsConfig, TlsError},
+};
+use async_trait::async_trait;
+use futures::{
+    future,
+    stream::{BoxStream, Peekable},
+    FutureExt, SinkExt, StreamExt,
+};
+use http::{
+    header::{HeaderName, HeaderValue},
+    HeaderMap, Request, Uri,
+};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{convert::TryFrom, net::SocketAddr, pin::Pin, time::Duration};
+use tokio::{
+    net::TcpStream,
+    time::{interval_at, sleep, sleep_until, Instant, Interval},
+};
+use tokio_tungstenite::{client_async, tungstenite::Message, WebSocketStream};
+use vector_core::buffers::Acker;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Invalid URI: {}", source))]
+    InvalidUri { source: http::uri::InvalidUri },
+    #[snafu(display("The URI scheme must be `ws` or `wss`"))]
+    InvalidScheme,
+    #[snafu(display("A host is required for the URI"))]
+    MissingHost,
+    #[snafu(display("Invalid header name {:?}: {}", name, source))]
+    InvalidHeaderName {
+        name: String,
+        source: http::header::InvalidHeaderName,
+    },
+    #[snafu(display("Invalid value for header {:?}: {}", name, source))]
+    InvalidHeaderValue {
+        name: String,
+        source: http::header::InvalidHeaderValue,
+    },
+}
+
+#[derive(Debug, Snafu)]
+enum WebSocketError {
+    #[snafu(display("Connect error: {}", source))]
+    ConnectError { source: TlsError },
+    #[snafu(display("Unable to resolve DNS: {}", source))]
+    DnsError { source: dns::DnsError },
+    #[snafu(display("No addresses returned."))]
+    NoAddresses,
+    #[snafu(display("Handshake failed: {}", source))]
+    HandshakeError {
+        source: tokio_tungstenite::tungstenite::Error,
+    },
+    #[snafu(display("Send error: {}", source))]
+    SendError {
+        source: tokio_tungstenite::tungstenite::Error,
+    },
+    #[snafu(display("Receive error: {}", source))]
+    ReceiveError {
+        source: tokio_tungstenite::tungstenite::Error,
+    },
+    #[snafu(display("No pong received within the ping timeout."))]
+    PongTimeout,
+    #[snafu(display("Connection closed by the server."))]
+    Closed,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketSinkConfig {
+    pub uri: String,
+    pub tls: Option<TlsConfig>,
+    pub encoding: EncodingConfig<Encoding>,
+    pub ping_interval_secs: Option<u64>,
+    pub ping_timeout_secs: Option<u64>,
+    pub auth: Option<Auth>,
+    #[serde(default)]
+    pub headers: IndexMap<String, String>,
+}
+
+inventory::submit! {
+    SinkDescription::new::<WebSocketSinkConfig>("websocket")
+}
+
+impl GenerateConfig for WebSocketSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"uri = "ws://127.0.0.1:9000/endpoint"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "websocket")]
+impl SinkConfig for WebSocketSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let connector = self.build_connector()?;
+        let healthcheck = {
+            let connector = connector.clone();
+            async move { connector.healthcheck().await }.boxed()
+        };
+        let sink = WebSocketSink {
+            connector,
+            encoding: self.encoding.clone(),
+            ping_interval: self.ping_interval_secs.map(Duration::from_secs),
+            ping_timeout: self.ping_timeout_secs.map(Duration::from_secs),
+            acker: cx.acker(),
+        };
+
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "websocket"
+    }
+}
+
+impl WebSocketSinkConfig {
+    fn build_connector(&self) -> crate::Result<WebSocketConnector> {
+        let uri = self.uri.parse::<Uri>().context(InvalidUri)?;
+        let (tls, default_port) = match uri.scheme_str() {
+            Some("ws") => (MaybeTlsSettings::from_config(&self.tls, false)?, 80),
+            Some("wss") => {
+                let tls = self.tls.clone().unwrap_or_else(TlsConfig::enabled);
+                (MaybeTlsSettings::from_config(&Some(tls), false)?, 443)
+            }
+            _ => return Err(BuildError::InvalidScheme.into()),
+        };
+        let host = uri.host().ok_or(BuildError::MissingHost)?.to_string();
+        let port = uri.port_u16().unwrap_or(default_port);
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let header_name = HeaderName::try_from(name.as_str())
+                .with_context(|| InvalidHeaderName { name: name.clone() })?;
+            let header_value = HeaderValue::try_from(value.as_str())
+                .with_context(|| InvalidHeaderValue { name: name.clone() })?;
+            headers.insert(header_name, header_value);
+        }
+        if let Some(auth) = &self.auth {
+            auth.apply_headers_map(&mut headers);
+        }
+
+        Ok(WebSocketConnector {
+            uri,
+            host,
+            port,
+            tls,
+            headers,
+        })
+    }
+}
+
+#[derive(Clone)]
+struct WebSocketConnector {
+    uri: Uri,
+    host: String,
+    port: u16,
+    tls: MaybeTlsSettings,
+    headers: HeaderMap,
+}
+
+impl WebSocketConnector {
+    const fn fresh_backoff() -> ExponentialBackoff {
+        ExponentialBackoff::from_millis(2)
+            .factor(250)
+            .max_delay(Duration::from_secs(60))
+    }
+
+    async fn connect(&self) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, WebSocketError> {
+        let ip = dns::Resolver
+            .lookup_ip(self.host.clone())
+            .await
+            .context(DnsError)?
+            .next()
+            .ok_or(WebSocketError::NoAddresses)?;
+
+        let addr = SocketAddr::new(ip, self.port);
+        let stream = self
+            .tls
+            .connect(&self.host, &addr)
+            .await
+            .context(ConnectError)?;
+
+        let mut request = Request::new(());
+        *request.uri_mut() = self.uri.clone();
+        *request.headers_mut() = self.headers.clone();
+
+        let (ws, _response) = client_async(request, stream)
+            .await
+            .context(HandshakeError)?;
+        Ok(ws)
+    }
+
+    async fn connect_backoff(&self) -> WebSocketStream<MaybeTlsStream<TcpStream>> {
+        let mut backoff = Self::fresh_backoff();
+        loop {
+            match self.connect().await {
+                Ok(ws) => {
+                    emit!(&WebSocketConnectionEstablished {
+                        peer_addr: ws.get_ref().peer_addr().ok(),
+                    });
+                    return ws;
+                }
+                Err(error) => {
+                    emit!(&WebSocketConnectionFailed { error });
+                    sleep(backoff.next().unwrap()).await;
+                }
+            }
+        }
+    }
+
+    async fn healthcheck(&self) -> crate::Result<()> {
+        self.connect().await.map(|_| ()).map_err(Into::into)
+    }
+}
+
+struct WebSocketSink {
+    connector: WebSocketConnector,
+    encoding: EncodingConfig<Encoding>,
+    ping_interval: Option<Duration>,
+    ping_timeout: Option<Duration>,
+    acker: Acker,
+}
+
+impl WebSocketSink {
+    /// Send events over a single connection until the input ends, or an
+    /// error requires reconnecting.
+    async fn handle_connection(
+        &self,
+        mut ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        input: &mut Peekable<BoxStream<'_, Event>>,
+    ) -> Result<(), WebSocketError> {
+        let mut ping_interval = self
+            .ping_interval
+            .map(|period| interval_at(Instant::now() + period, period));
+        let mut pong_deadline: Option<Instant> = None;
+
+        loop {
+            tokio::select! {
+                _ = tick(&mut ping_interval) => {
+                    if pong_deadline.is_none() {
+                        ws.send(Message::Ping(Vec::new())).await.context(SendError)?;
+                        pong_deadline = self.ping_timeout.map(|timeout| Instant::now() + timeout);
+                    }
+                }
+                _ = sleep_until(pong_deadline.unwrap_or_else(Instant::now)), if pong_deadline.is_some() => {
+                    return Err(WebSocketError::PongTimeout);
+                }
+                message = ws.next() => match message {
+                    Some(Ok(Message::Pong(_))) => pong_deadline = None,
+                    Some(Ok(Message::Close(_))) | None => return Err(WebSocketError::Closed),
+                    // Pings are answered by the stream itself, and anything
+                    // else the server sends is ignored.
+                    Some(Ok(_)) => {}
+                    Some(Err(error)) => return Err(WebSocketError::ReceiveError { source: error }),
+                },
+                event = input.next() => match event {
+                    Some(mut event) => {
+                        let finalizers = event.take_finalizers();
+                        let message = encode_event(event, &self.encoding);
+                        let byte_size = message.len();
+                        let result = ws.send(Message::Text(message)).await;
+                        self.acker.ack(1);
+                        match result {
+                            Ok(()) => {
+                                finalizers.update_status(EventStatus::Delivered);
+                                emit!(&WebSocketEventSent { byte_size });
+                            }
+                            Err(error) => {
+                                finalizers.update_status(EventStatus::Errored);
+                                return Err(WebSocketError::SendError { source: error });
+                            }
+                        }
+                    }
+                    None => {
+                        let _ = ws.close(None).await;
+                        return Ok(());
+                    }
+                },
+            }
+        }
+    }
+}
+
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
+
+#[async_trait]
+impl StreamSink for WebSocketSink {
+    async fn run(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let mut input = input.peekable();
+
+        // Only connect when there is something to send.
+        while Pin::new(&mut input).peek().await.is_some() {
+            let ws = self.connector.connect_backoff().await;
+            let _open_token = OpenGauge::new().open(|count| emit!(&ConnectionOpen { count }));
+
+            match self.handle_connection(ws, &mut input).await {
+                Ok(()) => {}
+                Err(WebSocketError::Closed) => emit!(&WebSocketConnectionShutdown),
+                Err(error) => emit!(&WebSocketConnectionError { error }),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> String {
+    encoding.apply_rules(&mut event);
+
+    match encoding.codec() {
+        Encoding::Json => serde_json::to_string(event.as_log()).unwrap(),
+        Encoding::Text => event
+            .as_log()
+            .get(log_schema().message_key())
+            .map(|v| v.to_string_lossy())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{next_addr, random_lines_with_stream, trace_init};
+    use futures::channel::mpsc;
+    use tokio::{net::TcpListener, time::timeout};
+    use tokio_tungstenite::{
+        accept_hdr_async,
+        tungstenite::handshake::server::{Request as ServerRequest, Response},
+    };
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<WebSocketSinkConfig>();
+    }
+
+    #[test]
+    fn rejects_invalid_uris() {
+        let config = |uri: &str| WebSocketSinkConfig {
+            uri: uri.to_owned(),
+            tls: None,
+            encoding: Encoding::Text.into(),
+            ping_interval_secs: None,
+            ping_timeout_secs: None,
+            auth: None,
+            headers: IndexMap::new(),
+        };
+        assert!(config("http://localhost:9000").build_connector().is_err());
+        assert!(config("ws:///path").build_connector().is_err());
+
+        let connector = config("wss://localhost/path").build_connector().unwrap();
+        assert_eq!(connector.port, 443);
+        assert!(matches!(connector.tls, MaybeTlsSettings::Tls(_)));
+    }
+
+    /// Accept connections, checking their headers, and forward the received
+    /// text messages.
+    async fn serve(listener: TcpListener, sender: mpsc::UnboundedSender<String>) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve_connection(stream, sender.clone()));
+        }
+    }
+
+    async fn serve_connection(stream: TcpStream, mut sender: mpsc::UnboundedSender<String>) {
+        let mut ws = accept_hdr_async(stream, |request: &ServerRequest, response: Response| {
+            assert_eq!(request.uri().path(), "/events");
+            assert_eq!(
+                request.headers().get("authorization").unwrap(),
+                "Bearer token"
+            );
+            assert_eq!(request.headers().get("x-source").unwrap(), "vector");
+            Ok(response)
+        })
+        .await
+        .unwrap();
+
+        while let Some(Ok(message)) = ws.next().await {
+            if let Message::Text(text) = message {
+                sender.send(text).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_events() {
+        trace_init();
+
+        let addr = next_addr();
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let (sender, receiver) = mpsc::unbounded();
+        tokio::spawn(serve(listener, sender));
+
+        let config: WebSocketSinkConfig = toml::from_str(&format!(
+            r#"
+            uri = "ws://{}/events"
+            encoding.codec = "text"
+            ping_interval_secs = 1
+            ping_timeout_secs = 1
+            auth.strategy = "bearer"
+            auth.token = "token"
+            headers.X-Source = "vector"
+            "#,
+            addr
+        ))
+        .unwrap();
+        let (sink, healthcheck) = config.build(SinkContext::new_test()).await.unwrap();
+        healthcheck.await.unwrap();
+
+        let (lines, events) = random_lines_with_stream(10, 100, None);
+        sink.run(events).await.unwrap();
+
+        let received = timeout(Duration::from_secs(5), receiver.take(lines.len()).collect())
+            .await
+            .unwrap();
+        assert_eq!(lines, received);
+    }
+}
@@ -0,0 +1,662 @@
+use super::util::finalizer::OrderedFinalizer;
+use crate::{
+    codecs::{self, DecodingConfig, FramingConfig, ParserConfig},
+    config::{
+        log_schema, AcknowledgementsConfig, DataType, GenerateConfig, ProxyConfig, SourceConfig,
+        SourceContext, SourceDescription,
+    },
+    event::{BatchNotifier, BatchStatus, Event, Value},
+    internal_events::{GcpPubsubEventsReceived, GcpPubsubStreamingPullFailed},
+    proto::google::pubsub::v1 as proto,
+    serde::{bool_or_struct, default_decoding, default_framing_message_based},
+    shutdown::ShutdownSignal,
+    sinks::gcp::{GcpAuthConfig, GcpCredentials},
+    tls::{tls_connector_builder, MaybeTlsSettings, TlsOptions, TlsSettings},
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use futures::{future::BoxFuture, SinkExt, StreamExt};
+use goauth::scopes::Scope;
+use http::{
+    header::{HeaderValue, InvalidHeaderValue},
+    uri::{InvalidUri, Scheme},
+    Uri,
+};
+use hyper::client::HttpConnector;
+use hyper_openssl::HttpsConnector;
+use hyper_proxy::ProxyConnector;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashSet,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_util::codec::FramedRead;
+use tonic::body::BoxBody;
+
+const DEFAULT_ENDPOINT: &str = "https://pubsub.googleapis.com";
+
+/// The range of acknowledgement deadlines accepted by Pub/Sub.
+const MIN_ACK_DEADLINE_SECS: u16 = 10;
+const MAX_ACK_DEADLINE_SECS: u16 = 600;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Invalid endpoint: {}", source))]
+    Endpoint { source: InvalidUri },
+    #[snafu(display("Invalid API key: {}", source))]
+    ApiKey { source: InvalidHeaderValue },
+    #[snafu(display(
+        "`ack_deadline_seconds` must be between {} and {}",
+        MIN_ACK_DEADLINE_SECS,
+        MAX_ACK_DEADLINE_SECS
+    ))]
+    AckDeadline,
+}
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+pub struct PubsubSourceConfig {
+    project: String,
+    subscription: String,
+    endpoint: Option<String>,
+    #[serde(default)]
+    skip_authentication: bool,
+    #[serde(flatten)]
+    auth: GcpAuthConfig,
+    tls: Option<TlsOptions>,
+    #[serde(default = "default_ack_deadline_seconds")]
+    #[derivative(Default(value = "default_ack_deadline_seconds()"))]
+    ack_deadline_seconds: u16,
+    #[serde(default = "default_max_outstanding_messages")]
+    #[derivative(Default(value = "default_max_outstanding_messages()"))]
+    max_outstanding_messages: i64,
+    #[serde(default = "default_framing_message_based")]
+    #[derivative(Default(value = "default_framing_message_based()"))]
+    framing: Box<dyn FramingConfig>,
+    #[serde(default = "default_decoding")]
+    #[derivative(Default(value = "default_decoding()"))]
+    decoding: Box<dyn ParserConfig>,
+    #[serde(default, deserialize_with = "bool_or_struct")]
+    acknowledgements: AcknowledgementsConfig,
+}
+
+const fn default_ack_deadline_seconds() -> u16 {
+    600
+}
+
+const fn default_max_outstanding_messages() -> i64 {
+    1000
+}
+
+inventory::submit! {
+    SourceDescription::new::<PubsubSourceConfig>("gcp_pubsub")
+}
+
+impl GenerateConfig for PubsubSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            project = "my-project"
+            subscription = "my-subscription""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "gcp_pubsub")]
+impl SourceConfig for PubsubSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if !(MIN_ACK_DEADLINE_SECS..=MAX_ACK_DEADLINE_SECS).contains(&self.ack_deadline_seconds) {
+            return Err(BuildError::AckDeadline.into());
+        }
+
+        // We only need to load the credentials if we are not targeting an emulator.
+        let creds = if self.skip_authentication {
+            None
+        } else {
+            self.auth.make_credentials(Scope::PubSub).await?
+        };
+        if let Some(creds) = &creds {
+            creds.spawn_regenerate_token();
+        }
+
+        let service = PubsubService::new(self, creds, &cx.proxy)?;
+        let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build()?;
+
+        let source = PubsubSource {
+            client: proto::subscriber_client::SubscriberClient::new(service),
+            subscription: format!(
+                "projects/{}/subscriptions/{}",
+                self.project, self.subscription
+            ),
+            client_id: uuid::Uuid::new_v4().to_string(),
+            ack_deadline_seconds: self.ack_deadline_seconds,
+            max_outstanding_messages: self.max_outstanding_messages,
+            decoder,
+            acknowledgements: self.acknowledgements.enabled,
+            out: cx.out,
+        };
+
+        Ok(Box::pin(source.run(cx.shutdown)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "gcp_pubsub"
+    }
+}
+
+type HyperClient = hyper::Client<ProxyConnector<HttpsConnector<HttpConnector>>, BoxBody>;
+
+/// The gRPC transport, which sends every request to the configured endpoint
+/// with the current credentials.
+#[derive(Clone, Debug)]
+struct PubsubService {
+    uri: Uri,
+    client: HyperClient,
+    creds: Option<GcpCredentials>,
+    api_key: Option<HeaderValue>,
+}
+
+impl PubsubService {
+    fn new(
+        config: &PubsubSourceConfig,
+        creds: Option<GcpCredentials>,
+        proxy: &ProxyConfig,
+    ) -> crate::Result<Self> {
+        let uri: Uri = config
+            .endpoint
+            .as_deref()
+            .unwrap_or(DEFAULT_ENDPOINT)
+            .parse()
+            .context(Endpoint)?;
+        let tls = if uri.scheme() == Some(&Scheme::HTTPS) {
+            MaybeTlsSettings::Tls(TlsSettings::from_options(&config.tls)?)
+        } else {
+            MaybeTlsSettings::Raw(())
+        };
+        let api_key = config
+            .auth
+            .api_key
+            .as_deref()
+            .map(HeaderValue::from_str)
+            .transpose()
+            .context(ApiKey)?;
+
+        Ok(Self {
+            uri,
+            client: new_client(&tls, proxy)?,
+            creds,
+            api_key,
+        })
+    }
+}
+
+fn new_client(
+    tls_settings: &MaybeTlsSettings,
+    proxy_config: &ProxyConfig,
+) -> crate::Result<HyperClient> {
+    let mut http = HttpConnector::new();
+    http.enforce_http(false);
+
+    let tls = tls_connector_builder(tls_settings)?;
+    let mut https = HttpsConnector::with_connector(http, tls)?;
+
+    let settings = tls_settings.tls().cloned();
+    https.set_callback(move |c, _uri| {
+        if let Some(settings) = &settings {
+            settings.apply_connect_configuration(c);
+        }
+
+        Ok(())
+    });
+
+    let mut proxy = ProxyConnector::new(https).unwrap();
+    proxy_config.configure(&mut proxy)?;
+
+    Ok(hyper::Client::builder().http2_only(true).build(proxy))
+}
+
+impl tower::Service<hyper::Request<BoxBody>> for PubsubService {
+    type Response = hyper::Response<hyper::Body>;
+    type Error = hyper::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: hyper::Request<BoxBody>) -> Self::Future {
+        let uri = Uri::builder()
+            .scheme(self.uri.scheme().unwrap().clone())
+            .authority(self.uri.authority().unwrap().clone())
+            .path_and_query(req.uri().path_and_query().unwrap().clone())
+            .build()
+            .unwrap();
+        *req.uri_mut() = uri;
+
+        if let Some(creds) = &self.creds {
+            creds.apply(&mut req);
+        }
+        if let Some(api_key) = &self.api_key {
+            req.headers_mut().insert("x-goog-api-key", api_key.clone());
+        }
+
+        Box::pin(self.client.request(req))
+    }
+}
+
+struct PubsubSource {
+    client: proto::subscriber_client::SubscriberClient<PubsubService>,
+    subscription: String,
+    client_id: String,
+    ack_deadline_seconds: u16,
+    max_outstanding_messages: i64,
+    decoder: codecs::Decoder,
+    acknowledgements: bool,
+    out: Pipeline,
+}
+
+impl PubsubSource {
+    async fn run(mut self, shutdown: ShutdownSignal) -> Result<(), ()> {
+        let shutdown = shutdown.shared();
+        let (ack_tx, mut ack_rx) = mpsc::unbounded_channel();
+        let mut finalizer = if self.acknowledgements {
+            Some(OrderedFinalizer::with_status(
+                shutdown.clone(),
+                move |status, ack_ids: Vec<String>| {
+                    let _ = ack_tx.send((status, ack_ids));
+                },
+            ))
+        } else {
+            None
+        };
+        // Messages waiting on acknowledgements. Their ack IDs remain valid
+        // across streams, so their deadlines keep being extended on a new
+        // stream after a reconnect.
+        let mut pending = HashSet::new();
+
+        loop {
+            let (request_tx, request_rx) = mpsc::unbounded_channel();
+            let _ = request_tx.send(self.initial_request());
+
+            let response = tokio::select! {
+                _ = shutdown.clone() => break,
+                response = self.client.streaming_pull(UnboundedReceiverStream::new(request_rx)) => response,
+            };
+            match response {
+                Ok(response) => {
+                    let mut stream = response.into_inner();
+                    // Extending deadlines at half their length leaves plenty
+                    // of time for the extension to arrive.
+                    let mut extend_deadlines = tokio::time::interval(Duration::from_secs(
+                        u64::from(self.ack_deadline_seconds / 2),
+                    ));
+
+                    loop {
+                        tokio::select! {
+                            _ = shutdown.clone() => {
+                                // Stop taking messages, but report the outcome
+                                // of those already taken before closing the
+                                // stream. Past the ack deadline they are
+                                // redelivered anyway, so there is no point in
+                                // waiting any longer.
+                                drop(finalizer.take());
+                                let drain = async {
+                                    while let Some((status, ack_ids)) = ack_rx.recv().await {
+                                        for ack_id in &ack_ids {
+                                            pending.remove(ack_id);
+                                        }
+                                        let _ = request_tx.send(finish(status, ack_ids));
+                                    }
+                                    // Closing the requests lets the server end
+                                    // the stream once it has received them.
+                                    drop(request_tx);
+                                    while stream.next().await.is_some() {}
+                                };
+                                let deadline =
+                                    Duration::from_secs(u64::from(self.ack_deadline_seconds));
+                                if tokio::time::timeout(deadline, drain).await.is_err() {
+                                    warn!(
+                                        message = "Timed out acknowledging messages on shutdown.",
+                                        pending = pending.len(),
+                                    );
+                                }
+                                return Ok(());
+                            }
+                            response = stream.next() => match response {
+                                Some(Ok(response)) => {
+                                    let ack_ids = self
+                                        .handle_response(response, &finalizer, &mut pending)
+                                        .await?;
+                                    if !ack_ids.is_empty() {
+                                        let _ = request_tx.send(acknowledge(ack_ids));
+                                    }
+                                }
+                                Some(Err(error)) => {
+                                    emit!(&GcpPubsubStreamingPullFailed { error });
+                                    break;
+                                }
+                                // The server closes streams periodically.
+                                None => break,
+                            },
+                            Some((status, ack_ids)) = ack_rx.recv() => {
+                                for ack_id in &ack_ids {
+                                    pending.remove(ack_id);
+                                }
+                                let _ = request_tx.send(finish(status, ack_ids));
+                            }
+                            _ = extend_deadlines.tick(), if !pending.is_empty() => {
+                                let ack_ids = pending.iter().cloned().collect();
+                                let seconds = i32::from(self.ack_deadline_seconds);
+                                let _ = request_tx.send(modify_deadline(ack_ids, seconds));
+                            }
+                        }
+                    }
+                }
+                Err(error) => emit!(&GcpPubsubStreamingPullFailed { error }),
+            }
+
+            tokio::select! {
+                _ = shutdown.clone() => break,
+                _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn initial_request(&self) -> proto::StreamingPullRequest {
+        proto::StreamingPullRequest {
+            subscription: self.subscription.clone(),
+            client_id: self.client_id.clone(),
+            stream_ack_deadline_seconds: i32::from(self.ack_deadline_seconds),
+            max_outstanding_messages: self.max_outstanding_messages,
+            ..Default::default()
+        }
+    }
+
+    /// Forward the messages of a response, returning the ack IDs to
+    /// acknowledge right away when acknowledgements are disabled.
+    async fn handle_response(
+        &mut self,
+        response: proto::StreamingPullResponse,
+        finalizer: &Option<OrderedFinalizer<Vec<String>>>,
+        pending: &mut HashSet<String>,
+    ) -> Result<Vec<String>, ()> {
+        let (batch, receiver) = BatchNotifier::maybe_new_with_receiver(finalizer.is_some());
+        let mut ack_ids = Vec::with_capacity(response.received_messages.len());
+
+        for received in response.received_messages {
+            ack_ids.push(received.ack_id);
+            if let Some(message) = received.message {
+                self.parse_message(message, &batch).await?;
+            }
+        }
+        drop(batch);
+
+        match (finalizer, receiver) {
+            (Some(finalizer), Some(receiver)) => {
+                pending.extend(ack_ids.iter().cloned());
+                finalizer.add(ack_ids, receiver);
+                Ok(Vec::new())
+            }
+            _ => Ok(ack_ids),
+        }
+    }
+
+    async fn parse_message(
+        &mut self,
+        message: proto::PubsubMessage,
+        batch: &Option<std::sync::Arc<BatchNotifier>>,
+    ) -> Result<(), ()> {
+        let timestamp = message
+            .publish_time
+            .and_then(|time| Utc.timestamp_opt(time.seconds, time.nanos as u32).single())
+            .unwrap_or_else(Utc::now);
+        let attributes = Value::Map(
+            message
+                .attributes
+                .into_iter()
+                .map(|(key, value)| (key, Value::from(value)))
+                .collect(),
+        );
+
+        let mut frames = FramedRead::new(message.data.as_slice(), self.decoder.clone());
+        while let Some(next) = frames.next().await {
+            match next {
+                Ok((events, byte_size)) => {
+                    emit!(&GcpPubsubEventsReceived {
+                        byte_size,
+                        count: events.len()
+                    });
+
+                    for mut event in events {
+                        if let Event::Log(ref mut log) = event {
+                            log.try_insert(
+                                log_schema().source_type_key(),
+                                Bytes::from("gcp_pubsub"),
+                            );
+                            log.try_insert(log_schema().timestamp_key(), timestamp);
+                            log.try_insert("message_id", message.message_id.clone());
+                            log.try_insert("attributes", attributes.clone());
+                        }
+
+                        self.out
+                            .send(event.with_batch_notifier_option(batch))
+                            .await
+                            .map_err(|error: crate::pipeline::ClosedError| {
+                                error!(message = "Error sending to sink.", %error);
+                            })?;
+                    }
+                }
+                Err(error) => {
+                    // Error is logged by `crate::codecs::Decoder`, no further
+                    // handling is needed here.
+                    if !error.can_continue() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn acknowledge(ack_ids: Vec<String>) -> proto::StreamingPullRequest {
+    proto::StreamingPullRequest {
+        ack_ids,
+        ..Default::default()
+    }
+}
+
+/// Reports the outcome of delivering the messages with `ack_ids`.
+fn finish(status: BatchStatus, ack_ids: Vec<String>) -> proto::StreamingPullRequest {
+    match status {
+        BatchStatus::Delivered => acknowledge(ack_ids),
+        // A zero deadline makes the messages available for redelivery right
+        // away.
+        BatchStatus::Errored | BatchStatus::Failed => modify_deadline(ack_ids, 0),
+    }
+}
+
+fn modify_deadline(ack_ids: Vec<String>, seconds: i32) -> proto::StreamingPullRequest {
+    proto::StreamingPullRequest {
+        modify_deadline_seconds: vec![seconds; ack_ids.len()],
+        modify_deadline_ack_ids: ack_ids,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<PubsubSourceConfig>();
+    }
+
+    #[test]
+    fn modify_deadline_per_ack_id() {
+        let request = modify_deadline(vec!["a".into(), "b".into()], 60);
+        assert_eq!(request.modify_deadline_ack_ids, vec!["a", "b"]);
+        assert_eq!(request.modify_deadline_seconds, vec![60, 60]);
+        assert!(request.ack_ids.is_empty());
+    }
+
+    #[test]
+    fn finish_by_status() {
+        let request = finish(BatchStatus::Delivered, vec!["a".into()]);
+        assert_eq!(request.ack_ids, vec!["a"]);
+        assert!(request.modify_deadline_ack_ids.is_empty());
+
+        let request = finish(BatchStatus::Errored, vec!["a".into()]);
+        assert!(request.ack_ids.is_empty());
+        assert_eq!(request.modify_deadline_ack_ids, vec!["a"]);
+        assert_eq!(request.modify_deadline_seconds, vec![0]);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_ack_deadline() {
+        let config: PubsubSourceConfig = toml::from_str(
+            r#"
+            project = "my-project"
+            subscription = "my-subscription"
+            skip_authentication = true
+            ack_deadline_seconds = 5
+            "#,
+        )
+        .unwrap();
+        let (tx, _rx) = Pipeline::new_test();
+        assert!(config.build(SourceContext::new_test(tx)).await.is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "gcp-pubsub-integration-tests")]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{collect_n, random_string, trace_init};
+    use reqwest::{Client, Method};
+    use serde_json::{json, Value as JsonValue};
+    use vector_core::event::EventStatus;
+
+    const EMULATOR_HOST: &str = "http://localhost:8681";
+    const PROJECT: &str = "testproject";
+
+    async fn request(method: Method, path: &str, json: JsonValue) -> JsonValue {
+        let url = format!("{}/v1/projects/{}/{}", EMULATOR_HOST, PROJECT, path);
+        Client::new()
+            .request(method.clone(), &url)
+            .json(&json)
+            .send()
+            .await
+            .unwrap_or_else(|_| panic!("Sending {} request to {} failed", method, url))
+            .json()
+            .await
+            .unwrap()
+    }
+
+    async fn create_topic_subscription() -> (String, String) {
+        let topic = format!("topic-{}", random_string(10));
+        let subscription = format!("subscription-{}", random_string(10));
+        request(Method::PUT, &format!("topics/{}", topic), json!({})).await;
+        request(
+            Method::PUT,
+            &format!("subscriptions/{}", subscription),
+            json!({
+                "topic": format!("projects/{}/topics/{}", PROJECT, topic),
+                "ackDeadlineSeconds": 10,
+            }),
+        )
+        .await;
+        (topic, subscription)
+    }
+
+    async fn publish(topic: &str, data: &str) {
+        request(
+            Method::POST,
+            &format!("topics/{}:publish", topic),
+            json!({
+                "messages": [{
+                    "data": base64::encode(data),
+                    "attributes": { "app": "test" },
+                }]
+            }),
+        )
+        .await;
+    }
+
+    async fn start_source(
+        subscription: &str,
+        status: EventStatus,
+    ) -> impl futures::Stream<Item = Event> {
+        let config = PubsubSourceConfig {
+            project: PROJECT.into(),
+            subscription: subscription.into(),
+            endpoint: Some(EMULATOR_HOST.into()),
+            skip_authentication: true,
+            ack_deadline_seconds: 10,
+            acknowledgements: true.into(),
+            ..Default::default()
+        };
+        let (tx, rx) = Pipeline::new_test_finalize(status);
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        rx
+    }
+
+    #[tokio::test]
+    async fn consumes_and_acknowledges() {
+        trace_init();
+
+        let (topic, subscription) = create_topic_subscription().await;
+        let rx = start_source(&subscription, EventStatus::Delivered).await;
+        publish(&topic, "my message").await;
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "my message".into());
+        assert_eq!(log[log_schema().source_type_key()], "gcp_pubsub".into());
+        assert_eq!(log["attributes.app"], "test".into());
+
+        // Acknowledged messages are not redelivered once the deadline passes.
+        tokio::time::sleep(Duration::from_secs(12)).await;
+        let response = request(
+            Method::POST,
+            &format!("subscriptions/{}:pull", subscription),
+            json!({ "returnImmediately": true, "maxMessages": 10 }),
+        )
+        .await;
+        assert!(response.get("receivedMessages").is_none());
+    }
+
+    #[tokio::test]
+    async fn redelivers_rejected_messages() {
+        trace_init();
+
+        let (topic, subscription) = create_topic_subscription().await;
+        let rx = start_source(&subscription, EventStatus::Errored).await;
+        publish(&topic, "my message").await;
+
+        // The rejected message is made available again right away.
+        let events = collect_n(rx, 2).await;
+        for event in events {
+            assert_eq!(
+                event.as_log()[log_schema().message_key()],
+                "my message".into()
+            );
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{
+    codecs::{self, LengthDelimitedCodec, NewlineDelimitedCodec, Parser},
+    config::{self, GenerateConfig, Resource, SourceConfig, SourceContext, SourceDescription},
+    event::Event,
+    internal_events::{GraphiteEventReceived, GraphiteInvalidRecord, GraphiteSocketError},
+    shutdown::ShutdownSignal,
+    sources::util::{SocketListenAddr, TcpSource},
+    tcp::TcpKeepaliveConfig,
+    tls::{MaybeTlsSettings, TlsConfig},
+    udp, Pipeline,
+};
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt, TryFutureExt};
+use serde::{Deserialize, Serialize};
+use smallvec::{smallvec, SmallVec};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use tokio::net::UdpSocket;
+use tokio_util::udp::UdpFramed;
+
+pub mod parser;
+pub mod pickle;
+
+use parser::{parse, ParseError};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum GraphiteConfig {
+    Tcp(TcpConfig),
+    Udp(UdpConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct UdpConfig {
+    address: SocketAddr,
+    receive_buffer_bytes: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct TcpConfig {
+    address: SocketListenAddr,
+    keepalive: Option<TcpKeepaliveConfig>,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+    #[serde(default = "default_shutdown_timeout_secs")]
+    shutdown_timeout_secs: u64,
+    receive_buffer_bytes: Option<usize>,
+    #[serde(default)]
+    protocol: Protocol,
+}
+
+/// The protocols metrics can be received with over TCP.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Protocol {
+    /// Newline delimited `<path> <value> [<timestamp>]` lines.
+    Plaintext,
+    /// Length prefixed pickles of `(path, (timestamp, value))` lists.
+    Pickle,
+}
+
+impl Default for Protocol {
+    fn default() -> Self {
+        Self::Plaintext
+    }
+}
+
+const fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+inventory::submit! {
+    SourceDescription::new::<GraphiteConfig>("graphite")
+}
+
+impl GenerateConfig for GraphiteConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self::Tcp(TcpConfig {
+            address: SocketListenAddr::SocketAddr(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::new(0, 0, 0, 0),
+                2003,
+            ))),
+            keepalive: None,
+            tls: None,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            receive_buffer_bytes: None,
+            protocol: Protocol::Plaintext,
+        }))
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "graphite")]
+impl SourceConfig for GraphiteConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        match self {
+            GraphiteConfig::Udp(config) => {
+                Ok(Box::pin(graphite_udp(config.clone(), cx.shutdown, cx.out)))
+            }
+            GraphiteConfig::Tcp(config) => {
+                let tls = MaybeTlsSettings::from_config(&config.tls, true)?;
+                let source = GraphiteTcpSource {
+                    protocol: config.protocol,
+                };
+                source.run(
+                    config.address,
+                    config.keepalive,
+                    config.shutdown_timeout_secs,
+                    tls,
+                    config.receive_buffer_bytes,
+                    cx.shutdown,
+                    cx.out,
+                )
+            }
+        }
+    }
+
+    fn output_type(&self) -> config::DataType {
+        config::DataType::Metric
+    }
+
+    fn source_type(&self) -> &'static str {
+        "graphite"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        match self.clone() {
+            Self::Tcp(tcp) => vec![tcp.address.into()],
+            Self::Udp(udp) => vec![Resource::udp(udp.address)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphiteParser;
+
+impl Parser for GraphiteParser {
+    fn parse(&self, bytes: Bytes) -> crate::Result<SmallVec<[Event; 1]>> {
+        match std::str::from_utf8(&bytes)
+            .map_err(|source| ParseError::InvalidUtf8 { source })
+            .and_then(parse)
+        {
+            Ok(metric) => {
+                emit!(&GraphiteEventReceived {
+                    count: 1,
+                    byte_size: bytes.len()
+                });
+                Ok(smallvec![Event::Metric(metric)])
+            }
+            Err(error) => {
+                emit!(&GraphiteInvalidRecord {
+                    error: &error,
+                    bytes
+                });
+                Err(Box::new(error))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphitePickleParser;
+
+impl Parser for GraphitePickleParser {
+    fn parse(&self, bytes: Bytes) -> crate::Result<SmallVec<[Event; 1]>> {
+        match pickle::parse(&bytes) {
+            Ok(metrics) => {
+                emit!(&GraphiteEventReceived {
+                    count: metrics.len(),
+                    byte_size: bytes.len()
+                });
+                Ok(metrics.into_iter().map(Event::Metric).collect())
+            }
+            Err(error) => {
+                emit!(&GraphiteInvalidRecord {
+                    error: &error,
+                    bytes
+                });
+                Err(Box::new(error))
+            }
+        }
+    }
+}
+
+fn decoder(protocol: Protocol) -> codecs::Decoder {
+    match protocol {
+        Protocol::Plaintext => codecs::Decoder::new(
+            Box::new(NewlineDelimitedCodec::new()),
+            Box::new(GraphiteParser),
+        ),
+        Protocol::Pickle => codecs::Decoder::new(
+            Box::new(LengthDelimitedCodec::new()),
+            Box::new(GraphitePickleParser),
+        ),
+    }
+}
+
+async fn graphite_udp(
+    config: UdpConfig,
+    shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let socket = UdpSocket::bind(&config.address)
+        .map_err(|error| emit!(&GraphiteSocketError::bind(error)))
+        .await?;
+
+    if let Some(receive_buffer_bytes) = config.receive_buffer_bytes {
+        if let Err(error) = udp::set_receive_buffer_size(&socket, receive_buffer_bytes) {
+            warn!(message = "Failed configuring receive buffer size on UDP socket.", %error);
+        }
+    }
+
+    info!(
+        message = "Listening.",
+        addr = %config.address,
+        r#type = "udp"
+    );
+
+    let mut stream = UdpFramed::new(socket, decoder(Protocol::Plaintext)).take_until(shutdown);
+    while let Some(frame) = stream.next().await {
+        match frame {
+            Ok(((events, _byte_size), _sock)) => {
+                for metric in events {
+                    if let Err(error) = out.send(metric).await {
+                        error!(message = "Error sending metric.", %error);
+                        break;
+                    }
+                }
+            }
+            Err(error) => {
+                emit!(&GraphiteSocketError::read(error));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct GraphiteTcpSource {
+    protocol: Protocol,
+}
+
+impl TcpSource for GraphiteTcpSource {
+    type Error = codecs::Error;
+    type Item = SmallVec<[Event; 1]>;
+    type Decoder = codecs::Decoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        decoder(self.protocol)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        event::metric::{MetricKind, MetricValue},
+        test_util::{collect_n, next_addr, trace_init, wait_for_tcp},
+    };
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GraphiteConfig>();
+    }
+
+    async fn receive(config: GraphiteConfig, n: usize) -> Vec<Event> {
+        let (tx, rx) = Pipeline::new_test();
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        collect_n(rx, n).await
+    }
+
+    fn assert_metrics(events: &[Event]) {
+        let metric = events[0].as_metric();
+        assert_eq!(metric.name(), "servers.web01.cpu");
+        assert_eq!(metric.kind(), MetricKind::Absolute);
+        assert_eq!(metric.value(), &MetricValue::Gauge { value: 0.5 });
+        assert_eq!(metric.timestamp().unwrap().timestamp(), 1636300800);
+
+        let metric = events[1].as_metric();
+        assert_eq!(metric.name(), "disk.used");
+        assert_eq!(metric.tags().unwrap()["host"], "web01");
+        assert_eq!(metric.value(), &MetricValue::Gauge { value: 42.0 });
+    }
+
+    const LINES: &[u8] =
+        b"servers.web01.cpu 0.5 1636300800\nnot a metric line\ndisk.used;host=web01 42 -1\n";
+
+    #[tokio::test]
+    async fn receives_tcp() {
+        trace_init();
+
+        let address = next_addr();
+        let config: GraphiteConfig =
+            toml::from_str(&format!("mode = \"tcp\"\naddress = \"{}\"", address)).unwrap();
+        tokio::spawn(async move {
+            wait_for_tcp(address).await;
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            stream.write_all(LINES).await.unwrap();
+        });
+
+        assert_metrics(&receive(config, 2).await);
+    }
+
+    #[tokio::test]
+    async fn receives_pickle() {
+        trace_init();
+
+        // `pickle.dumps([("servers.web01.cpu", (1636300800, 0.5)),
+        // ("disk.used;host=web01", (1636300800.5, 42))], protocol=2)`
+        const PICKLE: &[u8] = b"\x80\x02]q\x00(X\x11\x00\x00\x00servers.web01.cpuq\x01J\x00\xf8\x87aG?\xe0\x00\x00\x00\x00\x00\x00\x86q\x02\x86q\x03X\x14\x00\x00\x00disk.used;host=web01q\x04GA\xd8a\xfe\x00 \x00\x00K*\x86q\x05\x86q\x06e.";
+
+        let address = next_addr();
+        let config: GraphiteConfig = toml::from_str(&format!(
+            "mode = \"tcp\"\naddress = \"{}\"\nprotocol = \"pickle\"",
+            address
+        ))
+        .unwrap();
+        tokio::spawn(async move {
+            wait_for_tcp(address).await;
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            stream
+                .write_all(&(PICKLE.len() as u32).to_be_bytes())
+                .await
+                .unwrap();
+            stream.write_all(PICKLE).await.unwrap();
+        });
+
+        assert_metrics(&receive(config, 2).await);
+    }
+
+    #[tokio::test]
+    async fn receives_udp() {
+        trace_init();
+
+        let address = next_addr();
+        let config: GraphiteConfig =
+            toml::from_str(&format!("mode = \"udp\"\naddress = \"{}\"", address)).unwrap();
+        tokio::spawn(async move {
+            let socket = UdpSocket::bind(next_addr()).await.unwrap();
+            // The source may not be listening yet, so keep sending until
+            // the events are received.
+            loop {
+                socket.send_to(LINES, address).await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        });
+
+        assert_metrics(&receive(config, 2).await);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::event::metric::{Metric, MetricKind, MetricTags, MetricValue};
+use chrono::{DateTime, TimeZone, Utc};
+use snafu::{ResultExt, Snafu};
+use std::{num::ParseFloatError, str::Utf8Error};
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ParseError {
+    #[snafu(display("Invalid UTF-8: {}", source))]
+    InvalidUtf8 { source: Utf8Error },
+    #[snafu(display("Malformed line: {}", reason))]
+    Malformed { reason: &'static str },
+    #[snafu(display("Invalid value {:?}: {}", value, source))]
+    InvalidValue {
+        value: String,
+        source: ParseFloatError,
+    },
+    #[snafu(display("Invalid timestamp {:?}", timestamp))]
+    InvalidTimestamp { timestamp: String },
+    #[snafu(display("Invalid pickle: {}", reason))]
+    InvalidPickle { reason: &'static str },
+}
+
+shared::impl_event_data_eq!(ParseError);
+
+/// Parse a line of the Graphite plaintext protocol, `<path> <value> [<timestamp>]`,
+/// where the path may carry tags as in `name;tag1=value1;tag2=value2`.
+pub fn parse(line: &str) -> Result<Metric, ParseError> {
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+    if parts.len() < 2 || parts.len() > 3 {
+        return Err(ParseError::Malformed {
+            reason: "should be a path, a value and an optional timestamp separated by spaces",
+        });
+    }
+
+    let (name, tags) = parse_path(parts[0])?;
+    let value = parts[1]
+        .parse::<f64>()
+        .with_context(|| InvalidValue { value: parts[1] })?;
+    let timestamp = match parts.get(2) {
+        Some(timestamp) => parse_timestamp(timestamp)?,
+        None => None,
+    };
+
+    Ok(
+        Metric::new(name, MetricKind::Absolute, MetricValue::Gauge { value })
+            .with_tags(tags)
+            .with_timestamp(timestamp),
+    )
+}
+
+pub(super) fn parse_path(path: &str) -> Result<(&str, Option<MetricTags>), ParseError> {
+    let mut parts = path.split(';');
+    let name = parts.next().unwrap_or_default();
+    if name.is_empty() {
+        return Err(ParseError::Malformed {
+            reason: "metric path is empty",
+        });
+    }
+
+    let mut tags = MetricTags::new();
+    for tag in parts {
+        match tag.split_once('=') {
+            Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+                tags.insert(key.to_owned(), value.to_owned());
+            }
+            _ => {
+                return Err(ParseError::Malformed {
+                    reason: "tags should be non-empty `key=value` pairs",
+                })
+            }
+        }
+    }
+
+    Ok((name, (!tags.is_empty()).then(|| tags)))
+}
+
+fn parse_timestamp(timestamp: &str) -> Result<Option<DateTime<Utc>>, ParseError> {
+    timestamp
+        .parse::<f64>()
+        .map_err(|_| ParseError::InvalidTimestamp {
+            timestamp: timestamp.to_owned(),
+        })
+        .and_then(timestamp_from_seconds)
+}
+
+/// Timestamps are in seconds since the epoch, `-1` standing for the time of
+/// reception.
+pub(super) fn timestamp_from_seconds(seconds: f64) -> Result<Option<DateTime<Utc>>, ParseError> {
+    let invalid = || ParseError::InvalidTimestamp {
+        timestamp: seconds.to_string(),
+    };
+    if seconds == -1.0 {
+        return Ok(None);
+    }
+    if !seconds.is_finite() || seconds < 0.0 || seconds >= i64::MAX as f64 {
+        return Err(invalid());
+    }
+
+    Utc.timestamp_opt(seconds.trunc() as i64, (seconds.fract() * 1e9) as u32)
+        .single()
+        .map(Some)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use shared::assert_event_data_eq;
+
+    fn gauge(name: &str, value: f64) -> Metric {
+        Metric::new(name, MetricKind::Absolute, MetricValue::Gauge { value })
+    }
+
+    #[test]
+    fn basic_line() {
+        assert_event_data_eq!(
+            parse("foo.bar.baz 42.5 1636300800"),
+            Ok(gauge("foo.bar.baz", 42.5)
+                .with_timestamp(Some(Utc.ymd(2021, 11, 7).and_hms(16, 0, 0)))),
+        );
+    }
+
+    #[test]
+    fn without_timestamp() {
+        assert_event_data_eq!(parse("foo 1"), Ok(gauge("foo", 1.0)));
+        assert_event_data_eq!(parse("foo -3 -1"), Ok(gauge("foo", -3.0)));
+    }
+
+    #[test]
+    fn tagged_line() {
+        assert_event_data_eq!(
+            parse("disk.used;host=web01;mount=/var 0.75 1636300800\r"),
+            Ok(gauge("disk.used", 0.75)
+                .with_tags(Some(
+                    vec![
+                        ("host".to_owned(), "web01".to_owned()),
+                        ("mount".to_owned(), "/var".to_owned()),
+                    ]
+                    .into_iter()
+                    .collect()
+                ))
+                .with_timestamp(Some(Utc.ymd(2021, 11, 7).and_hms(16, 0, 0)))),
+        );
+    }
+
+    #[test]
+    fn fractional_timestamp() {
+        assert_eq!(
+            parse("foo 1 1636300800.5").unwrap().timestamp(),
+            Some(Utc.ymd(2021, 11, 7).and_hms_milli(16, 0, 0, 500))
+        );
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert!(matches!(parse("foo"), Err(ParseError::Malformed { .. })));
+        assert!(matches!(
+            parse("foo 1 2 3"),
+            Err(ParseError::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse(";host=a 1"),
+            Err(ParseError::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse("foo;host 1"),
+            Err(ParseError::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse("foo;host= 1"),
+            Err(ParseError::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse("foo bar"),
+            Err(ParseError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse("foo 1 yesterday"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+        assert!(matches!(
+            parse("foo 1 -5"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+        assert!(matches!(
+            parse("foo 1 -1.5"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+        assert!(matches!(
+            parse("foo 1 1e20"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+        assert!(matches!(
+            parse("foo 1 1e15"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+    }
+}
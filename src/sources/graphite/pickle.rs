@@ -0,0 +1,572 @@
+//! The Graphite pickle protocol, where each frame holds a pickled list of
+//! `(path, (timestamp, value))` tuples.
+//!
+//! Only the opcodes needed to represent such lists are supported, so a pickle
+//! can't construct arbitrary objects.
+
+use super::parser::{parse_path, timestamp_from_seconds, ParseError};
+use crate::event::metric::{Metric, MetricKind, MetricValue};
+use std::{collections::HashMap, convert::TryInto};
+
+/// The deepest nesting of lists and tuples accepted, well above the three
+/// levels of a list of metrics.
+const MAX_DEPTH: usize = 16;
+
+/// How many values a pickle may create for each of its bytes, bounding what
+/// references to memoized values can expand to.
+const MAX_VALUES_PER_BYTE: usize = 4;
+
+const MARK: u8 = b'(';
+const STOP: u8 = b'.';
+const POP: u8 = b'0';
+const INT: u8 = b'I';
+const LONG: u8 = b'L';
+const BININT: u8 = b'J';
+const BININT1: u8 = b'K';
+const BININT2: u8 = b'M';
+const NONE: u8 = b'N';
+const FLOAT: u8 = b'F';
+const BINFLOAT: u8 = b'G';
+const STRING: u8 = b'S';
+const BINSTRING: u8 = b'T';
+const SHORT_BINSTRING: u8 = b'U';
+const UNICODE: u8 = b'V';
+const BINUNICODE: u8 = b'X';
+const APPEND: u8 = b'a';
+const APPENDS: u8 = b'e';
+const LIST: u8 = b'l';
+const EMPTY_LIST: u8 = b']';
+const TUPLE: u8 = b't';
+const EMPTY_TUPLE: u8 = b')';
+const GET: u8 = b'g';
+const BINGET: u8 = b'h';
+const LONG_BINGET: u8 = b'j';
+const PUT: u8 = b'p';
+const BINPUT: u8 = b'q';
+const LONG_BINPUT: u8 = b'r';
+const BINBYTES: u8 = b'B';
+const SHORT_BINBYTES: u8 = b'C';
+const PROTO: u8 = 0x80;
+const TUPLE1: u8 = 0x85;
+const TUPLE2: u8 = 0x86;
+const TUPLE3: u8 = 0x87;
+const NEWTRUE: u8 = 0x88;
+const NEWFALSE: u8 = 0x89;
+const LONG1: u8 = 0x8a;
+const SHORT_BINUNICODE: u8 = 0x8c;
+const BINUNICODE8: u8 = 0x8d;
+const BINBYTES8: u8 = 0x8e;
+const MEMOIZE: u8 = 0x94;
+const FRAME: u8 = 0x95;
+
+/// Parse a frame of the pickle protocol into the metrics it lists.
+pub fn parse(bytes: &[u8]) -> Result<Vec<Metric>, ParseError> {
+    match Unpickler::new(bytes).load()? {
+        Value::List(items) | Value::Tuple(items) => items.into_iter().map(metric).collect(),
+        _ => Err(invalid("should be a list of metrics")),
+    }
+}
+
+fn metric(item: Value) -> Result<Metric, ParseError> {
+    let not_a_metric = || invalid("metrics should be `(path, (timestamp, value))` tuples");
+    let (path, datapoint) = item.into_pair().ok_or_else(not_a_metric)?;
+    let (timestamp, value) = datapoint.into_pair().ok_or_else(not_a_metric)?;
+
+    let path = match path {
+        Value::String(path) => path,
+        _ => return Err(invalid("metric path should be a string")),
+    };
+    let (name, tags) = parse_path(&path)?;
+    let value = value
+        .as_f64()
+        .ok_or_else(|| invalid("metric value should be a number"))?;
+    let timestamp = timestamp
+        .as_f64()
+        .ok_or_else(|| invalid("metric timestamp should be a number"))
+        .and_then(timestamp_from_seconds)?;
+
+    Ok(
+        Metric::new(name, MetricKind::Absolute, MetricValue::Gauge { value })
+            .with_tags(tags)
+            .with_timestamp(timestamp),
+    )
+}
+
+const fn invalid(reason: &'static str) -> ParseError {
+    ParseError::InvalidPickle { reason }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Tuple(Vec<Value>),
+}
+
+impl Value {
+    /// Splits a list or tuple of two items.
+    fn into_pair(self) -> Option<(Value, Value)> {
+        match self {
+            Value::List(items) | Value::Tuple(items) if items.len() == 2 => {
+                let mut items = items.into_iter();
+                Some((items.next()?, items.next()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Numbers are converted like Carbon does, accepting numeric strings.
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(value) => Some(*value as f64),
+            Value::Float(value) => Some(*value),
+            Value::String(value) => value.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            Value::List(items) | Value::Tuple(items) => {
+                1 + items.iter().map(Value::depth).max().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    /// The number of values this one is made of, including itself.
+    fn count(&self) -> usize {
+        match self {
+            Value::List(items) | Value::Tuple(items) => {
+                1 + items.iter().map(Value::count).sum::<usize>()
+            }
+            _ => 1,
+        }
+    }
+}
+
+struct Unpickler<'a> {
+    bytes: &'a [u8],
+    stack: Vec<Value>,
+    /// The lengths of `stack` when each pending `MARK` was read.
+    marks: Vec<usize>,
+    memo: HashMap<u32, Value>,
+    /// How many more values can be created.
+    budget: usize,
+}
+
+impl<'a> Unpickler<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            stack: Vec::new(),
+            marks: Vec::new(),
+            memo: HashMap::new(),
+            budget: bytes.len().saturating_mul(MAX_VALUES_PER_BYTE),
+        }
+    }
+
+    fn load(mut self) -> Result<Value, ParseError> {
+        loop {
+            let value = match self.byte()? {
+                STOP => return self.pop(),
+                PROTO => {
+                    self.take(1)?;
+                    continue;
+                }
+                FRAME => {
+                    self.take(8)?;
+                    continue;
+                }
+                MARK => {
+                    self.marks.push(self.stack.len());
+                    continue;
+                }
+                POP => {
+                    self.pop()?;
+                    continue;
+                }
+                APPEND => {
+                    let item = self.pop()?;
+                    self.append(vec![item])?;
+                    continue;
+                }
+                APPENDS => {
+                    let items = self.pop_mark()?;
+                    self.append(items)?;
+                    continue;
+                }
+                PUT => {
+                    let index = self.line_number()?;
+                    self.memoize(index)?;
+                    continue;
+                }
+                BINPUT => {
+                    let index = self.byte()?.into();
+                    self.memoize(index)?;
+                    continue;
+                }
+                LONG_BINPUT => {
+                    let index = self.u32()?;
+                    self.memoize(index)?;
+                    continue;
+                }
+                MEMOIZE => {
+                    let index = self.memo.len() as u32;
+                    self.memoize(index)?;
+                    continue;
+                }
+                GET => {
+                    let index = self.line_number()?;
+                    self.get(index)?
+                }
+                BINGET => {
+                    let index = self.byte()?.into();
+                    self.get(index)?
+                }
+                LONG_BINGET => {
+                    let index = self.u32()?;
+                    self.get(index)?
+                }
+                EMPTY_LIST => Value::List(Vec::new()),
+                EMPTY_TUPLE => Value::Tuple(Vec::new()),
+                LIST => Value::List(self.pop_mark()?),
+                TUPLE => Value::Tuple(self.pop_mark()?),
+                TUPLE1 => Value::Tuple(self.pop_n(1)?),
+                TUPLE2 => Value::Tuple(self.pop_n(2)?),
+                TUPLE3 => Value::Tuple(self.pop_n(3)?),
+                NONE => Value::None,
+                NEWTRUE => Value::Bool(true),
+                NEWFALSE => Value::Bool(false),
+                INT => match self.line()? {
+                    "00" => Value::Bool(false),
+                    "01" => Value::Bool(true),
+                    line => Value::Int(line.parse().map_err(|_| invalid("invalid integer"))?),
+                },
+                LONG => Value::Int(
+                    self.line()?
+                        .trim_end_matches('L')
+                        .parse()
+                        .map_err(|_| invalid("invalid integer"))?,
+                ),
+                BININT => Value::Int(i32::from_le_bytes(self.array()?).into()),
+                BININT1 => Value::Int(self.byte()?.into()),
+                BININT2 => Value::Int(u16::from_le_bytes(self.array()?).into()),
+                LONG1 => {
+                    let len = self.byte()?.into();
+                    Value::Int(long(self.take(len)?)?)
+                }
+                FLOAT => Value::Float(self.line()?.parse().map_err(|_| invalid("invalid float"))?),
+                BINFLOAT => Value::Float(f64::from_be_bytes(self.array()?)),
+                STRING => Value::String(unquote(self.line()?)?),
+                UNICODE => Value::String(unescape_unicode(self.line()?)?),
+                SHORT_BINSTRING | SHORT_BINBYTES | SHORT_BINUNICODE => {
+                    let len = self.byte()?.into();
+                    self.string(len)?
+                }
+                BINSTRING | BINBYTES | BINUNICODE => {
+                    let len = self.u32()? as usize;
+                    self.string(len)?
+                }
+                BINUNICODE8 | BINBYTES8 => {
+                    let len = u64::from_le_bytes(self.array()?)
+                        .try_into()
+                        .map_err(|_| invalid("string is too long"))?;
+                    self.string(len)?
+                }
+                _ => return Err(invalid("unsupported opcode")),
+            };
+            self.push(value)?;
+        }
+    }
+
+    fn push(&mut self, value: Value) -> Result<(), ParseError> {
+        if value.depth() > MAX_DEPTH {
+            return Err(invalid("nested too deeply"));
+        }
+        self.spend(1)?;
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn spend(&mut self, count: usize) -> Result<(), ParseError> {
+        self.budget = self
+            .budget
+            .checked_sub(count)
+            .ok_or_else(|| invalid("too many values"))?;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Value, ParseError> {
+        if self.at_mark() {
+            return Err(invalid("unexpected mark"));
+        }
+        self.stack.pop().ok_or_else(|| invalid("stack is empty"))
+    }
+
+    /// Whether the top of the stack is a mark rather than a value.
+    fn at_mark(&self) -> bool {
+        self.marks.last() == Some(&self.stack.len())
+    }
+
+    fn pop_n(&mut self, n: usize) -> Result<Vec<Value>, ParseError> {
+        let mut items = (0..n).map(|_| self.pop()).collect::<Result<Vec<_>, _>>()?;
+        items.reverse();
+        Ok(items)
+    }
+
+    /// Pops the items pushed since the last mark, along with the mark.
+    fn pop_mark(&mut self) -> Result<Vec<Value>, ParseError> {
+        let mark = self.marks.pop().ok_or_else(|| invalid("missing mark"))?;
+        Ok(self.stack.split_off(mark))
+    }
+
+    fn append(&mut self, items: Vec<Value>) -> Result<(), ParseError> {
+        if items.iter().any(|item| item.depth() >= MAX_DEPTH) {
+            return Err(invalid("nested too deeply"));
+        }
+        if self.at_mark() {
+            return Err(invalid("can only append to a list"));
+        }
+        match self.stack.last_mut() {
+            Some(Value::List(list)) => {
+                list.extend(items);
+                Ok(())
+            }
+            _ => Err(invalid("can only append to a list")),
+        }
+    }
+
+    fn memoize(&mut self, index: u32) -> Result<(), ParseError> {
+        let value = match self.stack.last() {
+            Some(value) if !self.at_mark() => value.clone(),
+            _ => return Err(invalid("stack is empty")),
+        };
+        self.spend(value.count())?;
+        self.memo.insert(index, value);
+        Ok(())
+    }
+
+    fn get(&mut self, index: u32) -> Result<Value, ParseError> {
+        let value = self
+            .memo
+            .get(&index)
+            .cloned()
+            .ok_or_else(|| invalid("unknown memo"))?;
+        self.spend(value.count())?;
+        Ok(value)
+    }
+
+    fn byte(&mut self) -> Result<u8, ParseError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ParseError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ParseError> {
+        Ok(self
+            .take(N)?
+            .try_into()
+            .expect("slice has the right length"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        if len > self.bytes.len() {
+            return Err(invalid("truncated"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Reads the argument of a text opcode, up to the next newline.
+    fn line(&mut self) -> Result<&'a str, ParseError> {
+        let len = self
+            .bytes
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .ok_or_else(|| invalid("truncated"))?;
+        let line = self.take(len)?;
+        self.take(1)?;
+        std::str::from_utf8(line).map_err(|_| invalid("invalid UTF-8"))
+    }
+
+    fn line_number(&mut self) -> Result<u32, ParseError> {
+        self.line()?
+            .parse()
+            .map_err(|_| invalid("invalid memo index"))
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value, ParseError> {
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes)
+            .map(|s| Value::String(s.to_owned()))
+            .map_err(|_| invalid("invalid UTF-8"))
+    }
+}
+
+/// Decodes a little-endian two's complement integer.
+fn long(bytes: &[u8]) -> Result<i64, ParseError> {
+    if bytes.len() > 8 {
+        return Err(invalid("integer is too large"));
+    }
+    let fill = match bytes.last() {
+        Some(last) if last & 0x80 != 0 => 0xff,
+        _ => 0,
+    };
+    let mut buf = [fill; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    Ok(i64::from_le_bytes(buf))
+}
+
+/// Decodes the quoted string literal of the `STRING` opcode.
+fn unquote(line: &str) -> Result<String, ParseError> {
+    let invalid_string = || invalid("invalid string");
+    let quote = line.chars().next().filter(|c| *c == '\'' || *c == '"');
+    let inner = match quote {
+        Some(quote) if line.len() >= 2 && line.ends_with(quote) => &line[1..line.len() - 1],
+        _ => return Err(invalid_string()),
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next().ok_or_else(invalid_string)? {
+            'n' => unquoted.push('\n'),
+            't' => unquoted.push('\t'),
+            'r' => unquoted.push('\r'),
+            'x' => {
+                let hex = chars.by_ref().take(2).collect::<String>();
+                let byte = u8::from_str_radix(&hex, 16).map_err(|_| invalid_string())?;
+                unquoted.push(char::from(byte));
+            }
+            c @ ('\\' | '\'' | '"') => unquoted.push(c),
+            _ => return Err(invalid_string()),
+        }
+    }
+    Ok(unquoted)
+}
+
+/// Decodes the `raw-unicode-escape` argument of the `UNICODE` opcode, where
+/// only `\u` and `\U` escapes are special.
+fn unescape_unicode(line: &str) -> Result<String, ParseError> {
+    let mut unescaped = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let len = match rest.as_bytes().get(1) {
+            Some(b'u') => 4,
+            Some(b'U') => 8,
+            _ => {
+                unescaped.push('\\');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let c = rest
+            .get(2..2 + len)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| invalid("invalid string"))?;
+        unescaped.push(c);
+        rest = &rest[2 + len..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use shared::assert_event_data_eq;
+
+    fn metrics() -> Vec<Metric> {
+        vec![
+            Metric::new(
+                "servers.web01.cpu",
+                MetricKind::Absolute,
+                MetricValue::Gauge { value: 0.5 },
+            )
+            .with_timestamp(Some(Utc.ymd(2021, 11, 7).and_hms(16, 0, 0))),
+            Metric::new(
+                "disk.used",
+                MetricKind::Absolute,
+                MetricValue::Gauge { value: 42.0 },
+            )
+            .with_tags(Some(
+                vec![("host".to_owned(), "web01".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ))
+            .with_timestamp(Some(Utc.ymd(2021, 11, 7).and_hms_milli(16, 0, 0, 500))),
+        ]
+    }
+
+    // `pickle.dumps([("servers.web01.cpu", (1636300800, 0.5)),
+    // ("disk.used;host=web01", (1636300800.5, 42))], protocol=N)`
+    const PROTOCOL_0: &[u8] = b"(lp0\n(Vservers.web01.cpu\np1\n(I1636300800\nF0.5\ntp2\ntp3\na(Vdisk.used;host=web01\np4\n(F1636300800.5\nI42\ntp5\ntp6\na.";
+    const PROTOCOL_2: &[u8] = b"\x80\x02]q\x00(X\x11\x00\x00\x00servers.web01.cpuq\x01J\x00\xf8\x87aG?\xe0\x00\x00\x00\x00\x00\x00\x86q\x02\x86q\x03X\x14\x00\x00\x00disk.used;host=web01q\x04GA\xd8a\xfe\x00 \x00\x00K*\x86q\x05\x86q\x06e.";
+    const PROTOCOL_4: &[u8] = b"\x80\x04\x95Q\x00\x00\x00\x00\x00\x00\x00]\x94(\x8c\x11servers.web01.cpu\x94J\x00\xf8\x87aG?\xe0\x00\x00\x00\x00\x00\x00\x86\x94\x86\x94\x8c\x14disk.used;host=web01\x94GA\xd8a\xfe\x00 \x00\x00K*\x86\x94\x86\x94e.";
+    // The same list pickled by Python 2, with byte strings.
+    const PYTHON_2: &[u8] = b"(lp0\n(S'servers.web01.cpu'\np1\n(I1636300800\nF0.5\ntp2\ntp3\na(S'disk.used;host=web01'\np4\n(F1636300800.5\nI42\ntp5\ntp6\na.";
+
+    #[test]
+    fn parses_protocols() {
+        for pickle in [PROTOCOL_0, PROTOCOL_2, PROTOCOL_4, PYTHON_2] {
+            assert_event_data_eq!(parse(pickle).unwrap(), metrics());
+        }
+    }
+
+    #[test]
+    fn parses_memoized_values() {
+        // `dp = (1636300800, 1); pickle.dumps([("a", dp), ("b", dp)], protocol=2)`
+        let pickle = b"\x80\x02]q\x00(X\x01\x00\x00\x00aq\x01J\x00\xf8\x87aK\x01\x86q\x02\x86q\x03X\x01\x00\x00\x00bq\x04h\x02\x86q\x05e.";
+        let metrics = parse(pickle).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[1].name(), "b");
+        assert_eq!(metrics[1].value(), &MetricValue::Gauge { value: 1.0 });
+    }
+
+    #[test]
+    fn rejects_objects() {
+        // `pickle.dumps([("a", (0, datetime.date(2021, 11, 7)))], protocol=2)`
+        let pickle = b"\x80\x02]q\x00X\x01\x00\x00\x00aq\x01K\x00cdatetime\ndate\nq\x02c_codecs\nencode\nq\x03X\x05\x00\x00\x00\x07\xc3\xa5\x0b\x07q\x04X\x06\x00\x00\x00latin1q\x05\x86q\x06Rq\x07\x85q\x08Rq\x09\x86q\x0a\x86q\x0ba.";
+        assert_eq!(parse(pickle), Err(invalid("unsupported opcode")));
+    }
+
+    #[test]
+    fn rejects_malformed_pickles() {
+        assert!(parse(b"").is_err());
+        assert!(parse(b"\x80\x02]q\x00").is_err());
+        assert!(parse(b"\x80\x02K\x01.").is_err());
+        // A metric without a timestamp.
+        assert!(parse(b"\x80\x02]X\x01\x00\x00\x00aK\x01\x86a.").is_err());
+        // A negative timestamp.
+        assert!(parse(b"\x80\x02]X\x01\x00\x00\x00aJ\xfe\xff\xff\xffK\x01\x86\x86a.").is_err());
+    }
+
+    #[test]
+    fn limits_expansion() {
+        // Each step doubles the size of the memoized tuple.
+        let mut pickle = b"\x80\x02K\x00q\x00".to_vec();
+        for _ in 0..40 {
+            pickle.extend_from_slice(b"h\x00h\x00\x86q\x00");
+        }
+        assert!(parse(&pickle).is_err());
+
+        let nested = [&b"\x80\x02K\x00"[..], &[TUPLE1; 100], b"."].concat();
+        assert_eq!(parse(&nested), Err(invalid("nested too deeply")));
+    }
+}
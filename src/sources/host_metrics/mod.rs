@@ -33,6 +33,8 @@ mod disk;
 mod filesystem;
 mod memory;
 mod network;
+#[cfg(target_os = "linux")]
+mod process;
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -46,6 +48,8 @@ enum Collector {
     Host,
     Memory,
     Network,
+    #[cfg(target_os = "linux")]
+    Process,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -86,6 +90,9 @@ pub struct HostMetricsConfig {
     filesystem: filesystem::FilesystemConfig,
     #[serde(default)]
     network: network::NetworkConfig,
+    #[cfg(target_os = "linux")]
+    #[serde(default)]
+    process: process::ProcessConfig,
 }
 
 const fn default_scrape_interval() -> u64 {
@@ -154,6 +161,10 @@ impl HostMetricsConfig {
 
     fn has_collector(&self, collector: Collector) -> bool {
         match &self.collectors {
+            // The process collector can generate a lot of series, so it is
+            // only enabled explicitly.
+            #[cfg(target_os = "linux")]
+            None if collector == Collector::Process => false,
             None => true,
             Some(collectors) => collectors.iter().any(|&c| c == collector),
         }
@@ -213,6 +224,10 @@ impl HostMetrics {
         if self.config.has_collector(Collector::Network) {
             metrics.extend(add_collector("network", self.network_metrics().await));
         }
+        #[cfg(target_os = "linux")]
+        if self.config.has_collector(Collector::Process) {
+            metrics.extend(add_collector("process", self.process_metrics().await));
+        }
         if let Ok(hostname) = &hostname {
             for metric in &mut metrics {
                 metric.insert_tag("host".into(), hostname.into());
@@ -0,0 +1,441 @@
+use super::{filter_result_sync, FilterList, HostMetrics};
+use crate::event::metric::Metric;
+use chrono::Utc;
+use nix::unistd::{sysconf, SysconfVar, Uid, User};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared::btreemap;
+use snafu::{ResultExt, Snafu};
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(default)]
+pub(super) struct ProcessConfig {
+    names: RegexList,
+    cmdlines: RegexList,
+    users: FilterList,
+    #[derivative(Default(value = "100"))]
+    max_processes: usize,
+}
+
+/// Lists of regular expressions for including and excluding processes,
+/// following the semantics of [`FilterList`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct RegexList {
+    includes: Option<Vec<RegexWrapper>>,
+    excludes: Option<Vec<RegexWrapper>>,
+}
+
+impl RegexList {
+    const fn is_empty(&self) -> bool {
+        self.includes.is_none() && self.excludes.is_none()
+    }
+
+    fn contains(&self, value: &str) -> bool {
+        self.includes.as_ref().map_or(true, |includes| {
+            includes.iter().any(|re| re.0.is_match(value))
+        }) && !self.excludes.as_ref().map_or(false, |excludes| {
+            excludes.iter().any(|re| re.0.is_match(value))
+        })
+    }
+}
+
+// Same as `PatternWrapper`, `Regex` can't be (de)serialized directly.
+#[derive(Clone, Debug)]
+struct RegexWrapper(Regex);
+
+impl<'de> Deserialize<'de> for RegexWrapper {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(RegexWrapper)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for RegexWrapper {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+#[derive(Debug, Snafu)]
+enum ProcessError {
+    #[snafu(display("Could not read process data file {:?}.", filename))]
+    Reading {
+        filename: PathBuf,
+        source: io::Error,
+    },
+    #[snafu(display("Could not parse process data file {:?}.", filename))]
+    Parsing { filename: PathBuf },
+}
+
+type ProcessResult<T> = Result<T, ProcessError>;
+
+/// The statistics of a single process, gathered from `/proc/<pid>`.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ProcessStats {
+    pid: u32,
+    name: String,
+    user: String,
+    /// CPU time spent in user mode, in clock ticks.
+    utime: u64,
+    /// CPU time spent in kernel mode, in clock ticks.
+    stime: u64,
+    threads: u64,
+    virtual_bytes: u64,
+    /// Resident set size, in pages.
+    rss_pages: u64,
+    open_fds: Option<u64>,
+    io: Option<IoStat>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct IoStat {
+    read_bytes: u64,
+    write_bytes: u64,
+}
+
+impl HostMetrics {
+    pub async fn process_metrics(&self) -> Vec<Metric> {
+        let config = self.config.process.clone();
+        let processes = match tokio::task::spawn_blocking(move || scan_processes(&config)).await {
+            Ok(processes) => processes,
+            Err(error) => {
+                error!(message = "Failed to scan processes.", %error, internal_log_rate_secs = 60);
+                return Vec::new();
+            }
+        };
+
+        let clock_ticks = sysconf_value(SysconfVar::CLK_TCK).unwrap_or(100) as f64;
+        let page_size = sysconf_value(SysconfVar::PAGE_SIZE).unwrap_or(4096);
+
+        let timestamp = Utc::now();
+        let mut metrics = Vec::with_capacity(processes.len() * 8);
+        for process in processes {
+            let tags = btreemap! {
+                "pid" => process.pid.to_string(),
+                "name" => process.name,
+                "user" => process.user,
+            };
+            for (mode, ticks) in &[("user", process.utime), ("system", process.stime)] {
+                let mut tags = tags.clone();
+                tags.insert("mode".into(), (*mode).into());
+                metrics.push(self.counter(
+                    "process_cpu_seconds_total",
+                    timestamp,
+                    *ticks as f64 / clock_ticks,
+                    tags,
+                ));
+            }
+            metrics.push(self.gauge(
+                "process_memory_rss_bytes",
+                timestamp,
+                (process.rss_pages * page_size) as f64,
+                tags.clone(),
+            ));
+            metrics.push(self.gauge(
+                "process_memory_virtual_bytes",
+                timestamp,
+                process.virtual_bytes as f64,
+                tags.clone(),
+            ));
+            metrics.push(self.gauge(
+                "process_threads",
+                timestamp,
+                process.threads as f64,
+                tags.clone(),
+            ));
+            if let Some(open_fds) = process.open_fds {
+                metrics.push(self.gauge(
+                    "process_open_fds",
+                    timestamp,
+                    open_fds as f64,
+                    tags.clone(),
+                ));
+            }
+            if let Some(io) = process.io {
+                metrics.push(self.counter(
+                    "process_io_read_bytes_total",
+                    timestamp,
+                    io.read_bytes as f64,
+                    tags.clone(),
+                ));
+                metrics.push(self.counter(
+                    "process_io_written_bytes_total",
+                    timestamp,
+                    io.write_bytes as f64,
+                    tags,
+                ));
+            }
+        }
+        metrics
+    }
+}
+
+fn sysconf_value(var: SysconfVar) -> Option<u64> {
+    sysconf(var).ok().flatten().map(|value| value as u64)
+}
+
+/// Read the statistics of all the processes that pass the configured
+/// filters. When more than `max_processes` match, only the ones that used
+/// the most CPU time are kept.
+fn scan_processes(config: &ProcessConfig) -> Vec<ProcessStats> {
+    let procfs_root = heim::os::linux::procfs_root();
+    let entries =
+        match filter_result_sync(std::fs::read_dir(&procfs_root), "Failed to list processes.") {
+            Some(entries) => entries,
+            None => return Vec::new(),
+        };
+
+    let mut users = HashMap::new();
+    let mut processes = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+        let dir = entry.path();
+
+        // Processes that exit while being scanned are skipped quietly.
+        let mut process = match load_stat(&dir) {
+            Ok(process) => ProcessStats { pid, ..process },
+            Err(ProcessError::Reading { source, .. })
+                if source.kind() == io::ErrorKind::NotFound =>
+            {
+                continue
+            }
+            Err(error) => {
+                error!(message = "Failed to load process statistics.", %error, internal_log_rate_secs = 60);
+                continue;
+            }
+        };
+        if !config.names.contains(&process.name) {
+            continue;
+        }
+
+        let uid = match std::fs::metadata(&dir) {
+            Ok(metadata) => metadata.uid(),
+            Err(_) => continue,
+        };
+        process.user = users.entry(uid).or_insert_with(|| user_name(uid)).clone();
+        if !config.users.contains_str(Some(&process.user)) {
+            continue;
+        }
+
+        if !config.cmdlines.is_empty() {
+            match load_cmdline(&dir) {
+                Ok(cmdline) if config.cmdlines.contains(&cmdline) => {}
+                _ => continue,
+            }
+        }
+
+        processes.push(process);
+    }
+
+    if processes.len() > config.max_processes {
+        debug!(
+            message = "Too many processes matched, keeping the ones using the most CPU time.",
+            matched = processes.len(),
+            max_processes = config.max_processes,
+        );
+        processes.sort_by_key(|process| std::cmp::Reverse(process.utime + process.stime));
+        processes.truncate(config.max_processes);
+    }
+
+    // The file descriptors and I/O statistics of processes owned by other
+    // users are only readable with elevated privileges, so they are
+    // reported only when available.
+    for process in &mut processes {
+        let dir = procfs_root.join(process.pid.to_string());
+        process.open_fds = count_fds(&dir).ok();
+        process.io = load_io(&dir).ok();
+    }
+
+    processes
+}
+
+fn user_name(uid: u32) -> String {
+    match User::from_uid(Uid::from_raw(uid)) {
+        Ok(Some(user)) => user.name,
+        _ => uid.to_string(),
+    }
+}
+
+fn read_file(dir: &Path, filename: &str) -> ProcessResult<String> {
+    let filename = dir.join(filename);
+    std::fs::read_to_string(&filename).with_context(|| Reading { filename })
+}
+
+fn load_stat(dir: &Path) -> ProcessResult<ProcessStats> {
+    let text = read_file(dir, "stat")?;
+    parse_stat(&text).ok_or_else(|| ProcessError::Parsing {
+        filename: dir.join("stat"),
+    })
+}
+
+/// Parse the contents of `/proc/<pid>/stat`, see `proc(5)`.
+fn parse_stat(text: &str) -> Option<ProcessStats> {
+    // The process name is enclosed in parentheses and may itself contain
+    // spaces and parentheses, so the fields start after the last `)`.
+    let start = text.find('(')?;
+    let end = text.rfind(')')?;
+    let name = text.get(start + 1..end)?.to_owned();
+    let fields = text[end + 1..].split_whitespace().collect::<Vec<_>>();
+    // Fields are numbered from 1 in `proc(5)`, and the first one here is
+    // the third one, `state`.
+    let field = |number: usize| -> Option<u64> { fields.get(number - 3)?.parse().ok() };
+
+    Some(ProcessStats {
+        name,
+        utime: field(14)?,
+        stime: field(15)?,
+        threads: field(20)?,
+        virtual_bytes: field(23)?,
+        rss_pages: field(24)?,
+        ..ProcessStats::default()
+    })
+}
+
+fn load_cmdline(dir: &Path) -> ProcessResult<String> {
+    let text = read_file(dir, "cmdline")?;
+    Ok(text.trim_end_matches('\0').replace('\0', " "))
+}
+
+fn count_fds(dir: &Path) -> io::Result<u64> {
+    Ok(std::fs::read_dir(dir.join("fd"))?.count() as u64)
+}
+
+fn load_io(dir: &Path) -> ProcessResult<IoStat> {
+    let text = read_file(dir, "io")?;
+    parse_io(&text).ok_or_else(|| ProcessError::Parsing {
+        filename: dir.join("io"),
+    })
+}
+
+/// Parse the contents of `/proc/<pid>/io`.
+fn parse_io(text: &str) -> Option<IoStat> {
+    let mut read_bytes = None;
+    let mut write_bytes = None;
+    for line in text.lines() {
+        let mut parts = line.splitn(2, ':');
+        let key = parts.next()?;
+        let value = parts.next()?.trim();
+        match key {
+            "read_bytes" => read_bytes = value.parse().ok(),
+            "write_bytes" => write_bytes = value.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(IoStat {
+        read_bytes: read_bytes?,
+        write_bytes: write_bytes?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{all_counters, all_gauges, count_name, count_tag};
+    use super::super::{HostMetrics, HostMetricsConfig};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_stat() {
+        let stat = "1234 (tmux: server (1)) S 1 1234 1234 0 -1 4194560 5094 0 0 0 \
+                    120 35 0 0 20 0 3 0 2801 12660736 1129 18446744073709551615 \
+                    1 1 0 0 0 0 0 3674112 134433283 0 0 0 17 2 0 0 0 0 0";
+        assert_eq!(
+            parse_stat(stat),
+            Some(ProcessStats {
+                name: "tmux: server (1)".into(),
+                utime: 120,
+                stime: 35,
+                threads: 3,
+                virtual_bytes: 12660736,
+                rss_pages: 1129,
+                ..ProcessStats::default()
+            })
+        );
+        assert_eq!(parse_stat("1234 (truncated) S 1 2"), None);
+    }
+
+    #[test]
+    fn parses_io() {
+        let io = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\n\
+                  read_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 0\n";
+        assert_eq!(
+            parse_io(io),
+            Some(IoStat {
+                read_bytes: 4096,
+                write_bytes: 323932160,
+            })
+        );
+        assert_eq!(parse_io("rchar: 1\n"), None);
+    }
+
+    #[test]
+    fn regex_list_filters() {
+        let config: ProcessConfig = toml::from_str(
+            r#"
+            names.includes = ["^vector", "^nginx$"]
+            names.excludes = ["-test$"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.names.contains("vector"));
+        assert!(config.names.contains("nginx"));
+        assert!(!config.names.contains("vector-test"));
+        assert!(!config.names.contains("nginx-worker"));
+        assert!(config.cmdlines.is_empty());
+        assert!(config.cmdlines.contains("anything"));
+
+        assert!(toml::from_str::<ProcessConfig>(r#"names.includes = ["("]"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn generates_process_metrics() {
+        let name = std::fs::read_to_string("/proc/self/comm").unwrap();
+        let config: HostMetricsConfig = toml::from_str(&format!(
+            r#"
+            collectors = ["process"]
+            process.names.includes = ["^{}$"]
+            "#,
+            regex::escape(name.trim())
+        ))
+        .unwrap();
+        let metrics = HostMetrics::new(config).process_metrics().await;
+
+        assert!(!metrics.is_empty());
+        assert_eq!(count_tag(&metrics, "pid"), metrics.len());
+        assert_eq!(count_tag(&metrics, "user"), metrics.len());
+        assert_eq!(count_name(&metrics, "process_cpu_seconds_total"), 2);
+        assert_eq!(count_name(&metrics, "process_memory_rss_bytes"), 1);
+        assert_eq!(count_name(&metrics, "process_memory_virtual_bytes"), 1);
+        assert_eq!(count_name(&metrics, "process_threads"), 1);
+        assert_eq!(count_name(&metrics, "process_open_fds"), 1);
+
+        let (counters, gauges): (Vec<_>, Vec<_>) = metrics
+            .into_iter()
+            .partition(|metric| metric.name().ends_with("_total"));
+        assert!(all_counters(&counters));
+        assert!(all_gauges(&gauges));
+    }
+
+    #[tokio::test]
+    async fn limits_processes() {
+        let config: HostMetricsConfig =
+            toml::from_str("collectors = [\"process\"]\nprocess.max_processes = 1").unwrap();
+        let metrics = HostMetrics::new(config).process_metrics().await;
+
+        assert_eq!(count_name(&metrics, "process_threads"), 1);
+    }
+}
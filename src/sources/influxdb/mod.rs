@@ -0,0 +1,558 @@
+use crate::{
+    config::{
+        log_schema, AcknowledgementsConfig, DataType, GenerateConfig, Resource, SourceConfig,
+        SourceContext, SourceDescription,
+    },
+    event::{
+        metric::{Metric, MetricKind, MetricValue},
+        Event, LogEvent, Value,
+    },
+    internal_events::{InfluxdbLineParseError, SocketEventsReceived, SocketMode, UdpSocketError},
+    serde::bool_or_struct,
+    shutdown::ShutdownSignal,
+    sources::util::{ErrorMessage, HttpSource},
+    tls::TlsConfig,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{stream, SinkExt, StreamExt};
+use headers::{authorization::Basic, Authorization, HeaderMapExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+};
+use subtle::ConstantTimeEq;
+use tokio::net::UdpSocket;
+use warp::http::{HeaderMap, StatusCode};
+
+pub mod parser;
+
+use parser::{parse_lines, FieldValue, Line, ParseError};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InfluxdbSourceConfig {
+    address: SocketAddr,
+    udp_address: Option<SocketAddr>,
+    #[serde(default)]
+    udp_precision: Precision,
+    tls: Option<TlsConfig>,
+    token: Option<String>,
+    #[serde(default)]
+    output: Output,
+    #[serde(default, deserialize_with = "bool_or_struct")]
+    acknowledgements: AcknowledgementsConfig,
+}
+
+/// The type of events the points are converted to.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Output {
+    #[derivative(Default)]
+    Metrics,
+    Logs,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Precision {
+    #[derivative(Default)]
+    #[serde(alias = "n")]
+    Ns,
+    #[serde(alias = "u")]
+    Us,
+    Ms,
+    S,
+    M,
+    H,
+}
+
+impl Precision {
+    fn from_query(precision: &str) -> Option<Self> {
+        match precision {
+            "n" | "ns" => Some(Self::Ns),
+            "u" | "us" | "µ" => Some(Self::Us),
+            "ms" => Some(Self::Ms),
+            "s" => Some(Self::S),
+            "m" => Some(Self::M),
+            "h" => Some(Self::H),
+            _ => None,
+        }
+    }
+
+    const fn nanoseconds(self) -> i64 {
+        match self {
+            Self::Ns => 1,
+            Self::Us => 1_000,
+            Self::Ms => 1_000_000,
+            Self::S => 1_000_000_000,
+            Self::M => 60_000_000_000,
+            Self::H => 3_600_000_000_000,
+        }
+    }
+}
+
+inventory::submit! {
+    SourceDescription::new::<InfluxdbSourceConfig>("influxdb")
+}
+
+impl GenerateConfig for InfluxdbSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            address: "0.0.0.0:8086".parse().unwrap(),
+            udp_address: None,
+            udp_precision: Precision::default(),
+            tls: None,
+            token: None,
+            output: Output::default(),
+            acknowledgements: AcknowledgementsConfig::default(),
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "influxdb")]
+impl SourceConfig for InfluxdbSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let source = InfluxdbSource {
+            token: self.token.clone(),
+            output: self.output,
+        };
+        let udp = self.udp_address.map(|address| {
+            influxdb_udp(
+                address,
+                self.udp_precision,
+                source.clone(),
+                cx.shutdown.clone(),
+                cx.out.clone(),
+            )
+        });
+        let http = source.run(
+            self.address,
+            "",
+            false,
+            &self.tls,
+            &None,
+            cx,
+            self.acknowledgements,
+        )?;
+
+        Ok(match udp {
+            Some(udp) => Box::pin(async move {
+                let (http, udp) = futures::join!(http, udp);
+                http.and(udp)
+            }),
+            None => http,
+        })
+    }
+
+    fn output_type(&self) -> DataType {
+        match self.output {
+            Output::Metrics => DataType::Metric,
+            Output::Logs => DataType::Log,
+        }
+    }
+
+    fn source_type(&self) -> &'static str {
+        "influxdb"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        let mut resources = vec![Resource::tcp(self.address)];
+        resources.extend(self.udp_address.map(Resource::udp));
+        resources
+    }
+}
+
+#[derive(Clone)]
+struct InfluxdbSource {
+    token: Option<String>,
+    output: Output,
+}
+
+impl InfluxdbSource {
+    /// Convert a point to a gauge for each of its numeric and boolean fields,
+    /// or to a single log event.
+    fn convert(
+        &self,
+        line: Line,
+        precision: Precision,
+        now: DateTime<Utc>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), ParseError> {
+        let timestamp = match line.timestamp {
+            Some(timestamp) => timestamp
+                .checked_mul(precision.nanoseconds())
+                .map(|nanoseconds| Utc.timestamp_nanos(nanoseconds))
+                .ok_or_else(|| ParseError::InvalidTimestamp {
+                    timestamp: timestamp.to_string(),
+                })?,
+            None => now,
+        };
+
+        match self.output {
+            Output::Metrics => {
+                let tags = (!line.tags.is_empty()).then(|| line.tags);
+                for (key, value) in line.fields {
+                    let value = match value {
+                        FieldValue::Float(value) => value,
+                        FieldValue::Integer(value) => value as f64,
+                        FieldValue::UnsignedInteger(value) => value as f64,
+                        FieldValue::Boolean(value) => f64::from(u8::from(value)),
+                        FieldValue::String(_) => continue,
+                    };
+                    let metric =
+                        Metric::new(key, MetricKind::Absolute, MetricValue::Gauge { value })
+                            .with_namespace(Some(line.measurement.clone()))
+                            .with_tags(tags.clone())
+                            .with_timestamp(Some(timestamp));
+                    events.push(metric.into());
+                }
+            }
+            Output::Logs => {
+                let tags = line
+                    .tags
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect::<BTreeMap<_, _>>();
+                let fields = line
+                    .fields
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = match value {
+                            FieldValue::Float(value) => Value::from(value),
+                            FieldValue::Integer(value) => Value::from(value),
+                            FieldValue::UnsignedInteger(value) => match i64::try_from(value) {
+                                Ok(value) => Value::from(value),
+                                Err(_) => Value::from(value as f64),
+                            },
+                            FieldValue::Boolean(value) => Value::from(value),
+                            FieldValue::String(value) => Value::from(value),
+                        };
+                        (key, value)
+                    })
+                    .collect::<BTreeMap<_, _>>();
+
+                let mut log = LogEvent::default();
+                log.insert("measurement", line.measurement);
+                log.insert("tags", tags);
+                log.insert("fields", fields);
+                log.insert(log_schema().timestamp_key(), timestamp);
+                log.insert(log_schema().source_type_key(), Bytes::from("influxdb"));
+                events.push(log.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl HttpSource for InfluxdbSource {
+    /// Check the token of the request, either given as `Token <token>` in the
+    /// authorization header as in the v2 API, or as the password of the basic
+    /// authentication or of the `p` query parameter as in the v1 API. The
+    /// tokens are compared in constant time.
+    fn authorize(
+        &self,
+        header_map: &HeaderMap,
+        query_parameters: &HashMap<String, String>,
+    ) -> Result<(), ErrorMessage> {
+        let token = match &self.token {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+
+        let provided = header_map
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Token "))
+            .map(str::to_owned)
+            .or_else(|| {
+                header_map
+                    .typed_get::<Authorization<Basic>>()
+                    .map(|auth| auth.password().to_owned())
+            })
+            .or_else(|| query_parameters.get("p").cloned());
+
+        match provided {
+            Some(provided) if bool::from(provided.as_bytes().ct_eq(token.as_bytes())) => Ok(()),
+            Some(_) => Err(ErrorMessage::new(
+                StatusCode::UNAUTHORIZED,
+                "Invalid token".to_owned(),
+            )),
+            None => Err(ErrorMessage::new(
+                StatusCode::UNAUTHORIZED,
+                "No token".to_owned(),
+            )),
+        }
+    }
+
+    fn build_events(
+        &self,
+        body: Bytes,
+        _header_map: HeaderMap,
+        query_parameters: HashMap<String, String>,
+        path: &str,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        if path != "/write" && path != "/api/v2/write" {
+            return Err(ErrorMessage::new(
+                StatusCode::NOT_FOUND,
+                "Not found".to_owned(),
+            ));
+        }
+
+        let precision = match query_parameters.get("precision") {
+            Some(precision) => Precision::from_query(precision).ok_or_else(|| {
+                ErrorMessage::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid precision {:?}", precision),
+                )
+            })?,
+            None => Precision::Ns,
+        };
+        let body = std::str::from_utf8(&body).map_err(|error| {
+            ErrorMessage::new(
+                StatusCode::BAD_REQUEST,
+                format!("Body is not valid UTF-8: {}", error),
+            )
+        })?;
+
+        let now = Utc::now();
+        let mut events = Vec::new();
+        for line in parse_lines(body) {
+            line.and_then(|line| self.convert(line, precision, now, &mut events))
+                .map_err(|error| {
+                    emit!(&InfluxdbLineParseError { error: &error });
+                    ErrorMessage::new(StatusCode::BAD_REQUEST, error.to_string())
+                })?;
+        }
+
+        Ok(events)
+    }
+
+    fn success_status(&self) -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+}
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+async fn influxdb_udp(
+    address: SocketAddr,
+    precision: Precision,
+    source: InfluxdbSource,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let socket = UdpSocket::bind(&address).await.map_err(|error| {
+        error!(message = "Failed to bind to UDP listener socket.", %address, %error);
+    })?;
+    info!(message = "Listening.", %address, r#type = "udp");
+
+    let mut buf = vec![0; MAX_DATAGRAM_SIZE];
+    loop {
+        let byte_size = tokio::select! {
+            result = socket.recv_from(&mut buf) => match result {
+                Ok((byte_size, _)) => byte_size,
+                Err(error) => {
+                    emit!(&UdpSocketError { error });
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        // Invalid lines are skipped, as there is no way to report them.
+        let now = Utc::now();
+        let mut events = Vec::new();
+        for line in parse_lines(&String::from_utf8_lossy(&buf[..byte_size])) {
+            if let Err(error) =
+                line.and_then(|line| source.convert(line, precision, now, &mut events))
+            {
+                emit!(&InfluxdbLineParseError { error: &error });
+            }
+        }
+        emit!(&SocketEventsReceived {
+            mode: SocketMode::Udp,
+            byte_size,
+            count: events.len(),
+        });
+
+        if let Err(error) = out.send_all(&mut stream::iter(events).map(Ok)).await {
+            error!(message = "Error sending events.", %error);
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_n, next_addr, trace_init, wait_for_tcp};
+    use flate2::{write::GzEncoder, Compression};
+    use futures::Stream;
+    use std::io::Write;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<InfluxdbSourceConfig>();
+    }
+
+    async fn source(config: &str) -> (impl Stream<Item = Event>, SocketAddr) {
+        let address = next_addr();
+        let config: InfluxdbSourceConfig =
+            toml::from_str(&format!("address = \"{}\"\n{}", address, config)).unwrap();
+        let (tx, rx) = Pipeline::new_test();
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        wait_for_tcp(address).await;
+        (rx, address)
+    }
+
+    async fn send(request: reqwest::RequestBuilder, body: impl Into<reqwest::Body>) -> u16 {
+        request.body(body).send().await.unwrap().status().as_u16()
+    }
+
+    #[tokio::test]
+    async fn receives_v2_writes() {
+        trace_init();
+
+        let (rx, address) = source(r#"token = "secret""#).await;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(b"cpu,host=web01 usage_idle=98.5,count=3i,label=\"a\" 1636300800\n")
+            .unwrap();
+        let request = reqwest::Client::new()
+            .post(&format!(
+                "http://{}/api/v2/write?org=vector&bucket=metrics&precision=s",
+                address
+            ))
+            .header("Authorization", "Token secret")
+            .header("Content-Encoding", "gzip");
+        assert_eq!(send(request, encoder.finish().unwrap()).await, 204);
+
+        let events = collect_n(rx, 2).await;
+        let metric = events[0].as_metric();
+        assert_eq!(metric.namespace(), Some("cpu"));
+        assert_eq!(metric.name(), "usage_idle");
+        assert_eq!(metric.value(), &MetricValue::Gauge { value: 98.5 });
+        assert_eq!(metric.tags().unwrap()["host"], "web01");
+        assert_eq!(metric.timestamp(), Some(Utc.timestamp(1636300800, 0)));
+        let metric = events[1].as_metric();
+        assert_eq!(metric.name(), "count");
+        assert_eq!(metric.value(), &MetricValue::Gauge { value: 3.0 });
+    }
+
+    #[tokio::test]
+    async fn receives_v1_writes_as_logs() {
+        trace_init();
+
+        let (rx, address) = source("token = \"secret\"\noutput = \"logs\"").await;
+
+        let request = reqwest::Client::new().post(&format!(
+            "http://{}/write?db=telegraf&u=user&p=secret&precision=ms",
+            address
+        ));
+        let status = send(
+            request,
+            "disk,host=web01,path=/var free=12i,ok=true,label=\"a b\" 1636300800000",
+        )
+        .await;
+        assert_eq!(status, 204);
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log["measurement"], "disk".into());
+        assert_eq!(log["tags.path"], "/var".into());
+        assert_eq!(log["fields.free"], 12.into());
+        assert_eq!(log["fields.ok"], true.into());
+        assert_eq!(log["fields.label"], "a b".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.timestamp(1636300800, 0).into()
+        );
+        assert_eq!(log[log_schema().source_type_key()], "influxdb".into());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_requests() {
+        trace_init();
+
+        let (_rx, address) = source(r#"token = "secret""#).await;
+        let client = reqwest::Client::new();
+        let url = |path: &str| format!("http://{}{}", address, path);
+
+        let request = client.post(&url("/api/v2/write"));
+        assert_eq!(send(request, "cpu value=1").await, 401);
+
+        let request = client
+            .post(&url("/api/v2/write"))
+            .header("Authorization", "Token wrong");
+        assert_eq!(send(request, "cpu value=1").await, 401);
+
+        // The token is checked before the body is decoded.
+        let request = client
+            .post(&url("/api/v2/write"))
+            .header("Content-Encoding", "gzip");
+        assert_eq!(send(request, "not gzip").await, 401);
+
+        let request = client
+            .post(&url("/write"))
+            .basic_auth("user", Some("secret"));
+        assert_eq!(send(request, "cpu value=1\ncpu value=oops").await, 400);
+
+        let request = client.post(&url("/write?p=secret&precision=d"));
+        assert_eq!(send(request, "cpu value=1").await, 400);
+
+        let request = client.post(&url("/query?p=secret"));
+        assert_eq!(send(request, "cpu value=1").await, 404);
+    }
+
+    #[tokio::test]
+    async fn receives_udp_datagrams() {
+        trace_init();
+
+        let udp_address = next_addr();
+        let (rx, _) = source(&format!(
+            "udp_address = \"{}\"\nudp_precision = \"s\"",
+            udp_address
+        ))
+        .await;
+
+        tokio::spawn(async move {
+            let socket = UdpSocket::bind(next_addr()).await.unwrap();
+            // The UDP listener may not be bound yet, so keep sending until
+            // the events are received.
+            loop {
+                socket
+                    .send_to(
+                        b"mem used=1024u 1636300800\nmem used=oops\nmem free=2048u 1636300800\n",
+                        udp_address,
+                    )
+                    .await
+                    .unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        });
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_metric().name(), "used");
+        assert_eq!(
+            events[1].as_metric().value(),
+            &MetricValue::Gauge { value: 2048.0 }
+        );
+        assert_eq!(
+            events[1].as_metric().timestamp(),
+            Some(Utc.timestamp(1636300800, 0))
+        );
+    }
+}
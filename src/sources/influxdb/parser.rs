@@ -0,0 +1,331 @@
+use snafu::Snafu;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ParseError {
+    #[snafu(display("Malformed line: {}", reason))]
+    Malformed { reason: &'static str },
+    #[snafu(display("Invalid value {:?} of field {:?}", value, field))]
+    InvalidFieldValue { field: String, value: String },
+    #[snafu(display("Invalid timestamp {:?}", timestamp))]
+    InvalidTimestamp { timestamp: String },
+}
+
+/// A point of the line protocol.
+#[derive(Debug, PartialEq)]
+pub struct Line {
+    pub measurement: String,
+    pub tags: BTreeMap<String, String>,
+    pub fields: Vec<(String, FieldValue)>,
+    /// The timestamp, in the precision of the request.
+    pub timestamp: Option<i64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FieldValue {
+    Float(f64),
+    Integer(i64),
+    UnsignedInteger(u64),
+    String(String),
+    Boolean(bool),
+}
+
+const KEY_STOPS: &[u8] = &[b'=', b',', b' '];
+/// The characters to escape in tag keys, tag values and field keys.
+const ESCAPES: &[u8] = &[b',', b'=', b' '];
+
+/// Parse the lines of a line protocol body, skipping the empty lines and the
+/// comments.
+pub fn parse_lines(body: &str) -> impl Iterator<Item = Result<Line, ParseError>> + '_ {
+    body.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_line)
+}
+
+/// Parse a line, `<measurement>[,<tag>=<value>...] <field>=<value>[,<field>=<value>...] [<timestamp>]`.
+pub fn parse_line(line: &str) -> Result<Line, ParseError> {
+    let mut cursor = Cursor {
+        bytes: line.as_bytes(),
+        position: 0,
+    };
+
+    let (measurement, mut stop) = cursor.read_escaped(&[b',', b' '], &[b',', b' ']);
+    if measurement.is_empty() {
+        return Err(ParseError::Malformed {
+            reason: "measurement is empty",
+        });
+    }
+
+    let mut tags = BTreeMap::new();
+    while stop == Some(b',') {
+        let (key, key_stop) = cursor.read_escaped(KEY_STOPS, ESCAPES);
+        if key_stop != Some(b'=') {
+            return Err(ParseError::Malformed {
+                reason: "tags should be `key=value` pairs",
+            });
+        }
+        let (value, value_stop) = cursor.read_escaped(&[b',', b' '], ESCAPES);
+        if key.is_empty() || value.is_empty() {
+            return Err(ParseError::Malformed {
+                reason: "tag keys and values should not be empty",
+            });
+        }
+        tags.insert(key, value);
+        stop = value_stop;
+    }
+    if stop.is_none() {
+        return Err(ParseError::Malformed {
+            reason: "line has no fields",
+        });
+    }
+    cursor.skip_spaces();
+
+    let mut fields = Vec::new();
+    loop {
+        let (key, key_stop) = cursor.read_escaped(KEY_STOPS, ESCAPES);
+        if key.is_empty() || key_stop != Some(b'=') {
+            return Err(ParseError::Malformed {
+                reason: "fields should be `key=value` pairs",
+            });
+        }
+        let value = if cursor.peek() == Some(b'"') {
+            FieldValue::String(cursor.read_string()?)
+        } else {
+            let raw = cursor.read_raw(&[b',', b' ']);
+            parse_field_value(raw).ok_or_else(|| ParseError::InvalidFieldValue {
+                field: key.clone(),
+                value: raw.to_owned(),
+            })?
+        };
+        fields.push((key, value));
+
+        match cursor.next() {
+            Some(b',') => continue,
+            Some(b' ') | None => break,
+            Some(_) => {
+                return Err(ParseError::Malformed {
+                    reason: "fields should be separated by commas",
+                })
+            }
+        }
+    }
+
+    let rest = cursor.rest().trim();
+    let timestamp = if rest.is_empty() {
+        None
+    } else {
+        Some(
+            rest.parse::<i64>()
+                .map_err(|_| ParseError::InvalidTimestamp {
+                    timestamp: rest.to_owned(),
+                })?,
+        )
+    };
+
+    Ok(Line {
+        measurement,
+        tags,
+        fields,
+        timestamp,
+    })
+}
+
+fn parse_field_value(raw: &str) -> Option<FieldValue> {
+    match raw {
+        "t" | "T" | "true" | "True" | "TRUE" => Some(FieldValue::Boolean(true)),
+        "f" | "F" | "false" | "False" | "FALSE" => Some(FieldValue::Boolean(false)),
+        _ if raw.ends_with('i') => raw[..raw.len() - 1].parse().ok().map(FieldValue::Integer),
+        _ if raw.ends_with('u') => raw[..raw.len() - 1]
+            .parse()
+            .ok()
+            .map(FieldValue::UnsignedInteger),
+        _ => raw
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .map(FieldValue::Float),
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek();
+        if byte.is_some() {
+            self.position += 1;
+        }
+        byte
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.position += 1;
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        // Stops are ASCII, so the position is always at a character boundary.
+        std::str::from_utf8(&self.bytes[self.position..]).unwrap_or_default()
+    }
+
+    /// Read up to one of the stop bytes, which is consumed and returned, or to
+    /// the end of the line. A backslash is dropped before the escaped bytes,
+    /// and kept as is before any other character.
+    fn read_escaped(&mut self, stops: &[u8], escapes: &[u8]) -> (String, Option<u8>) {
+        let mut value = Vec::new();
+        while let Some(byte) = self.next() {
+            match byte {
+                b'\\' => match self.peek() {
+                    Some(escaped) if escapes.contains(&escaped) => {
+                        value.push(escaped);
+                        self.position += 1;
+                    }
+                    _ => value.push(byte),
+                },
+                _ if stops.contains(&byte) => {
+                    return (String::from_utf8_lossy(&value).into_owned(), Some(byte))
+                }
+                _ => value.push(byte),
+            }
+        }
+        (String::from_utf8_lossy(&value).into_owned(), None)
+    }
+
+    /// Read up to one of the stop bytes, which is left in place, or to the end
+    /// of the line.
+    fn read_raw(&mut self, stops: &[u8]) -> &'a str {
+        let start = self.position;
+        while matches!(self.peek(), Some(byte) if !stops.contains(&byte)) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.position]).unwrap_or_default()
+    }
+
+    /// Read a double quoted string, in which quotes and backslashes are
+    /// escaped with a backslash.
+    fn read_string(&mut self) -> Result<String, ParseError> {
+        self.position += 1;
+        let mut value = Vec::new();
+        while let Some(byte) = self.next() {
+            match byte {
+                b'"' => return Ok(String::from_utf8_lossy(&value).into_owned()),
+                b'\\' if matches!(self.peek(), Some(b'"') | Some(b'\\')) => {
+                    value.extend(self.next());
+                }
+                _ => value.push(byte),
+            }
+        }
+        Err(ParseError::Malformed {
+            reason: "string field value is not terminated",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tags(tags: &[(&str, &str)]) -> BTreeMap<String, String> {
+        tags.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_simple_line() {
+        assert_eq!(
+            parse_line("cpu usage_idle=98.5 1636300800000000000"),
+            Ok(Line {
+                measurement: "cpu".into(),
+                tags: BTreeMap::new(),
+                fields: vec![("usage_idle".into(), FieldValue::Float(98.5))],
+                timestamp: Some(1636300800000000000),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_tags_and_field_types() {
+        assert_eq!(
+            parse_line(
+                r#"disk,host=web01,path=/var free=12i,total=64u,ok=t,down=FALSE,label="a \"b\" c\\",ratio=-1.5e3"#
+            ),
+            Ok(Line {
+                measurement: "disk".into(),
+                tags: tags(&[("host", "web01"), ("path", "/var")]),
+                fields: vec![
+                    ("free".into(), FieldValue::Integer(12)),
+                    ("total".into(), FieldValue::UnsignedInteger(64)),
+                    ("ok".into(), FieldValue::Boolean(true)),
+                    ("down".into(), FieldValue::Boolean(false)),
+                    ("label".into(), FieldValue::String(r#"a "b" c\"#.into())),
+                    ("ratio".into(), FieldValue::Float(-1500.0)),
+                ],
+                timestamp: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_escapes() {
+        assert_eq!(
+            parse_line(r#"my\ measure\,ment,tag\ key=tag\,value\=1 field\=key="a b,c=d" 42"#),
+            Ok(Line {
+                measurement: "my measure,ment".into(),
+                tags: tags(&[("tag key", "tag,value=1")]),
+                fields: vec![("field=key".into(), FieldValue::String("a b,c=d".into()))],
+                timestamp: Some(42),
+            })
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_empty_lines() {
+        let lines = parse_lines("# comment\n\ncpu value=1\r\nmem value=2\n")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].measurement, "mem");
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for line in &[
+            "cpu",
+            ",host=a value=1",
+            "cpu,host value=1",
+            "cpu,host= value=1",
+            "cpu value",
+            "cpu =1",
+            "cpu value=\"open",
+            "cpu value=\"a\"b",
+        ] {
+            assert!(
+                matches!(parse_line(line), Err(ParseError::Malformed { .. })),
+                "{:?}",
+                line
+            );
+        }
+        assert!(matches!(
+            parse_line("cpu value=abc"),
+            Err(ParseError::InvalidFieldValue { .. })
+        ));
+        assert!(matches!(
+            parse_line("cpu value=1.5i"),
+            Err(ParseError::InvalidFieldValue { .. })
+        ));
+        assert!(matches!(
+            parse_line("cpu value=1 yesterday"),
+            Err(ParseError::InvalidTimestamp { .. })
+        ));
+    }
+}
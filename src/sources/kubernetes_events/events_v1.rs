@@ -0,0 +1,185 @@
+//! The `events.k8s.io/v1` API, which is newer than the API version the
+//! `k8s-openapi` types are generated for.
+
+use crate::kubernetes::watch_request_builder::WatchRequestBuilder;
+use k8s_openapi::{
+    api::core::v1::ObjectReference,
+    apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta, Time},
+    http::Request,
+    Metadata, NamespaceResourceScope, RequestError, Resource, WatchOptional,
+};
+use serde::Deserialize;
+
+/// An `events.k8s.io/v1` `Event`, with only the fields the source reads.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub metadata: ObjectMeta,
+    pub action: Option<String>,
+    pub deprecated_count: Option<i32>,
+    pub deprecated_first_timestamp: Option<Time>,
+    pub deprecated_last_timestamp: Option<Time>,
+    pub event_time: Option<MicroTime>,
+    pub note: Option<String>,
+    pub reason: Option<String>,
+    pub regarding: Option<ObjectReference>,
+    pub reporting_controller: Option<String>,
+    pub reporting_instance: Option<String>,
+    pub series: Option<EventSeries>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSeries {
+    pub count: i32,
+    pub last_observed_time: MicroTime,
+}
+
+impl Resource for Event {
+    const API_VERSION: &'static str = "events.k8s.io/v1";
+    const GROUP: &'static str = "events.k8s.io";
+    const KIND: &'static str = "Event";
+    const VERSION: &'static str = "v1";
+    const URL_PATH_SEGMENT: &'static str = "events";
+    type Scope = NamespaceResourceScope;
+}
+
+impl Metadata for Event {
+    type Ty = ObjectMeta;
+
+    fn metadata(&self) -> &Self::Ty {
+        &self.metadata
+    }
+
+    fn metadata_mut(&mut self) -> &mut Self::Ty {
+        &mut self.metadata
+    }
+}
+
+/// Builds watch requests for `events.k8s.io/v1` events, in a single
+/// namespace or in all of them.
+pub struct WatchEvents {
+    pub namespace: Option<String>,
+}
+
+impl WatchRequestBuilder for WatchEvents {
+    type Object = Event;
+
+    fn build(&self, watch_optional: WatchOptional<'_>) -> Result<Request<Vec<u8>>, RequestError> {
+        let path = match &self.namespace {
+            Some(namespace) => format!(
+                "/apis/events.k8s.io/v1/namespaces/{}/events?",
+                percent_encoding::utf8_percent_encode(
+                    namespace,
+                    percent_encoding::NON_ALPHANUMERIC
+                )
+            ),
+            None => "/apis/events.k8s.io/v1/events?".to_owned(),
+        };
+
+        let mut query = url::form_urlencoded::Serializer::new(path);
+        let WatchOptional {
+            allow_watch_bookmarks,
+            field_selector,
+            label_selector,
+            pretty,
+            resource_version,
+            timeout_seconds,
+        } = watch_optional;
+        if let Some(allow_watch_bookmarks) = allow_watch_bookmarks {
+            query.append_pair("allowWatchBookmarks", &allow_watch_bookmarks.to_string());
+        }
+        if let Some(field_selector) = field_selector {
+            query.append_pair("fieldSelector", field_selector);
+        }
+        if let Some(label_selector) = label_selector {
+            query.append_pair("labelSelector", label_selector);
+        }
+        if let Some(pretty) = pretty {
+            query.append_pair("pretty", pretty);
+        }
+        if let Some(resource_version) = resource_version {
+            query.append_pair("resourceVersion", resource_version);
+        }
+        if let Some(timeout_seconds) = timeout_seconds {
+            query.append_pair("timeoutSeconds", &timeout_seconds.to_string());
+        }
+        query.append_pair("watch", "true");
+
+        Request::get(query.finish())
+            .body(Vec::new())
+            .map_err(RequestError::Http)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_watch_requests() {
+        let watch_optional = WatchOptional {
+            field_selector: None,
+            label_selector: Some("app=vector"),
+            pretty: None,
+            resource_version: Some("42"),
+            timeout_seconds: Some(290),
+            allow_watch_bookmarks: Some(true),
+        };
+
+        let request = WatchEvents { namespace: None }
+            .build(watch_optional)
+            .unwrap();
+        assert_eq!(
+            request.uri(),
+            "/apis/events.k8s.io/v1/events?allowWatchBookmarks=true&labelSelector=app%3Dvector&resourceVersion=42&timeoutSeconds=290&watch=true"
+        );
+
+        let request = WatchEvents {
+            namespace: Some("kube-system".to_owned()),
+        }
+        .build(WatchOptional::default())
+        .unwrap();
+        assert_eq!(
+            request.uri(),
+            "/apis/events.k8s.io/v1/namespaces/kube%2Dsystem/events?watch=true"
+        );
+    }
+
+    #[test]
+    fn deserializes_events() {
+        let event: Event = serde_json::from_str(
+            r#"{
+                "apiVersion": "events.k8s.io/v1",
+                "kind": "Event",
+                "metadata": {
+                    "name": "vector-0.16b5e3c5d3b7a3f1",
+                    "namespace": "default",
+                    "uid": "c1c8b2a4-2e2c-4a4e-9b38-6e6f1a1f4a7d",
+                    "resourceVersion": "1234"
+                },
+                "eventTime": "2021-11-01T08:40:32.123456Z",
+                "reportingController": "kubelet",
+                "reportingInstance": "node-1",
+                "action": "Pulling",
+                "reason": "Pulling",
+                "regarding": {"kind": "Pod", "name": "vector-0", "namespace": "default"},
+                "note": "Pulling image \"timberio/vector\"",
+                "type": "Normal",
+                "series": {"count": 2, "lastObservedTime": "2021-11-01T08:41:32.123456Z"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(event.metadata.resource_version.as_deref(), Some("1234"));
+        assert_eq!(event.reporting_controller.as_deref(), Some("kubelet"));
+        assert_eq!(event.type_.as_deref(), Some("Normal"));
+        assert_eq!(
+            event.regarding.and_then(|regarding| regarding.name),
+            Some("vector-0".to_owned())
+        );
+        assert_eq!(event.series.map(|series| series.count), Some(2));
+    }
+}
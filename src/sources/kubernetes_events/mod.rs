@@ -0,0 +1,486 @@
+//! This mod implements `kubernetes_events` source.
+//! The source watches the `Event` objects of the Kubernetes API, which report
+//! what's happening to the objects of a cluster, and turns every new version
+//! of an event into a log event.
+
+#![deny(missing_docs)]
+
+use crate::event::{Event, LogEvent};
+use crate::internal_events::KubernetesEventsEventReceived;
+use crate::kubernetes as k8s;
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
+    },
+    shutdown::ShutdownSignal,
+    sources, Pipeline,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{channel::mpsc, future::BoxFuture, SinkExt, StreamExt};
+use k8s_openapi::{
+    api::core::v1::{Event as CoreEvent, ObjectReference},
+    apimachinery::pkg::apis::meta::v1::ObjectMeta,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf, time::Duration};
+use vector_core::ByteSizeOf;
+
+mod events_v1;
+mod writer;
+
+use events_v1::WatchEvents;
+use k8s::watch_request_builder::{Namespaced, WatchRequestBuilder};
+
+const COMPONENT_ID: &str = "kubernetes_events";
+
+/// Configuration for the `kubernetes_events` source.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct Config {
+    /// The API version the events are watched with.
+    api_version: ApiVersion,
+
+    /// The namespaces to watch the events of, all namespaces if empty.
+    namespaces: Vec<String>,
+
+    /// Specifies the field selector to filter the events with.
+    field_selector: Option<String>,
+
+    /// Specifies the label selector to filter the events with.
+    label_selector: Option<String>,
+
+    /// Optional path to a kubeconfig file readable by Vector. If not set,
+    /// Vector will try to connect to Kubernetes using in-cluster configuration.
+    kube_config_file: Option<PathBuf>,
+}
+
+/// The APIs that serve the `Event` objects.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ApiVersion {
+    /// The core API, available in every Kubernetes version.
+    #[serde(rename = "v1")]
+    CoreV1,
+    /// The `events.k8s.io` API, available since Kubernetes 1.19.
+    #[serde(rename = "events.k8s.io/v1")]
+    EventsV1,
+}
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        Self::CoreV1
+    }
+}
+
+inventory::submit! {
+    SourceDescription::new::<Config>(COMPONENT_ID)
+}
+
+impl GenerateConfig for Config {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(&Self::default()).unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "kubernetes_events")]
+impl SourceConfig for Config {
+    async fn build(&self, cx: SourceContext) -> crate::Result<sources::Source> {
+        let k8s_config = match &self.kube_config_file {
+            Some(kc) => k8s::client::config::Config::kubeconfig(kc)?,
+            None => k8s::client::config::Config::in_cluster()?,
+        };
+        let client = k8s::client::Client::new(k8s_config, &cx.proxy)?;
+
+        Ok(Box::pin(run(self.clone(), client, cx.out, cx.shutdown)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        COMPONENT_ID
+    }
+}
+
+async fn run(
+    config: Config,
+    client: k8s::client::Client,
+    out: Pipeline,
+    shutdown: ShutdownSignal,
+) -> Result<(), ()> {
+    let (sender, receiver) = mpsc::channel(100);
+    // Events that occurred before the source started are skipped.
+    let start = Utc::now();
+
+    // Every namespace gets a watch of its own, as a field selector can't
+    // select more than one.
+    let namespaces = if config.namespaces.is_empty() {
+        vec![None]
+    } else {
+        config.namespaces.iter().cloned().map(Some).collect()
+    };
+    let reflectors = namespaces.into_iter().map(|namespace| {
+        let field_selector = config.field_selector.clone();
+        let label_selector = config.label_selector.clone();
+        let sender = sender.clone();
+        match (config.api_version, namespace) {
+            (ApiVersion::CoreV1, Some(namespace)) => reflect(
+                client.clone(),
+                Namespaced(namespace, CoreEvent::watch_namespaced_event),
+                field_selector,
+                label_selector,
+                sender,
+                start,
+            ),
+            (ApiVersion::CoreV1, None) => reflect(
+                client.clone(),
+                CoreEvent::watch_event_for_all_namespaces,
+                field_selector,
+                label_selector,
+                sender,
+                start,
+            ),
+            (ApiVersion::EventsV1, namespace) => reflect(
+                client.clone(),
+                WatchEvents { namespace },
+                field_selector,
+                label_selector,
+                sender,
+                start,
+            ),
+        }
+    });
+    let reflectors = futures::future::join_all(reflectors.collect::<Vec<_>>());
+    drop(sender);
+
+    let out = out.sink_map_err(|error| error!(message = "Error sending event.", %error));
+    let events = receiver
+        .map(|log| {
+            emit!(&KubernetesEventsEventReceived {
+                byte_size: log.size_of(),
+            });
+            Ok(Event::from(log))
+        })
+        .forward(out);
+
+    tokio::select! {
+        _ = reflectors => {},
+        _ = events => {},
+        _ = shutdown => {},
+    }
+    info!(message = "Done.");
+    Ok(())
+}
+
+/// Watch the event objects built by `request_builder`, and send the new
+/// versions of them that occurred from `start` on as log events to `sender`.
+fn reflect<B>(
+    client: k8s::client::Client,
+    request_builder: B,
+    field_selector: Option<String>,
+    label_selector: Option<String>,
+    sender: mpsc::Sender<LogEvent>,
+    start: DateTime<Utc>,
+) -> BoxFuture<'static, ()>
+where
+    B: WatchRequestBuilder + Send + 'static,
+    <B as WatchRequestBuilder>::Object: Into<EventFields> + Send + Unpin + fmt::Debug + 'static,
+{
+    let watcher = k8s::api_watcher::ApiWatcher::new(client, request_builder);
+    let watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(watcher);
+    let state_writer = writer::Writer::new(sender, start);
+    let state_writer = k8s::state::instrumenting::Writer::new(state_writer);
+
+    Box::pin(async move {
+        let mut reflector = k8s::reflector::Reflector::new(
+            watcher,
+            state_writer,
+            field_selector,
+            label_selector,
+            Duration::from_secs(1),
+        );
+        if let Err(error) = reflector.run().await {
+            error!(message = "Reflector process exited with an error.", %error);
+        }
+    })
+}
+
+/// The fields of an event that are common to all the APIs.
+#[derive(Debug, Default)]
+pub struct EventFields {
+    metadata: ObjectMeta,
+    message: Option<String>,
+    reason: Option<String>,
+    type_: Option<String>,
+    action: Option<String>,
+    count: Option<i32>,
+    reporting_component: Option<String>,
+    reporting_instance: Option<String>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    involved_object: Option<ObjectReference>,
+}
+
+impl From<CoreEvent> for EventFields {
+    fn from(event: CoreEvent) -> Self {
+        let event_time = event.event_time.map(|time| time.0);
+        let series = event.series.unwrap_or_default();
+        let source = event.source.unwrap_or_default();
+        Self {
+            metadata: event.metadata,
+            message: event.message,
+            reason: event.reason,
+            type_: event.type_,
+            action: event.action,
+            count: series.count.or(event.count),
+            // Events reported through the older fields leave the newer ones
+            // empty rather than unset.
+            reporting_component: non_empty(event.reporting_component).or(source.component),
+            reporting_instance: non_empty(event.reporting_instance).or(source.host),
+            first_timestamp: event.first_timestamp.map(|time| time.0).or(event_time),
+            last_timestamp: series
+                .last_observed_time
+                .map(|time| time.0)
+                .or_else(|| event.last_timestamp.map(|time| time.0))
+                .or(event_time),
+            involved_object: Some(event.involved_object),
+        }
+    }
+}
+
+impl From<events_v1::Event> for EventFields {
+    fn from(event: events_v1::Event) -> Self {
+        let event_time = event.event_time.map(|time| time.0);
+        Self {
+            metadata: event.metadata,
+            message: event.note,
+            reason: event.reason,
+            type_: event.type_,
+            action: event.action,
+            count: event
+                .series
+                .as_ref()
+                .map(|series| series.count)
+                .or(event.deprecated_count),
+            reporting_component: non_empty(event.reporting_controller),
+            reporting_instance: non_empty(event.reporting_instance),
+            first_timestamp: event
+                .deprecated_first_timestamp
+                .map(|time| time.0)
+                .or(event_time),
+            last_timestamp: event
+                .series
+                .map(|series| series.last_observed_time.0)
+                .or_else(|| event.deprecated_last_timestamp.map(|time| time.0))
+                .or(event_time),
+            involved_object: event.regarding,
+        }
+    }
+}
+
+impl EventFields {
+    /// The time the event last occurred at.
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.last_timestamp
+            .or(self.first_timestamp)
+            .or_else(|| self.metadata.creation_timestamp.as_ref().map(|time| time.0))
+            .unwrap_or_else(Utc::now)
+    }
+
+    fn into_log(self) -> LogEvent {
+        let mut log = LogEvent::default();
+
+        log.insert(log_schema().timestamp_key(), self.timestamp());
+        if let Some(message) = self.message {
+            log.insert(log_schema().message_key(), message);
+        }
+        log.insert(log_schema().source_type_key(), Bytes::from(COMPONENT_ID));
+
+        let fields = vec![
+            ("event.name", self.metadata.name),
+            ("event.namespace", self.metadata.namespace),
+            ("event.uid", self.metadata.uid),
+            ("event.type", self.type_),
+            ("event.reason", self.reason),
+            ("event.action", self.action),
+            ("event.reporting_component", self.reporting_component),
+            ("event.reporting_instance", self.reporting_instance),
+        ];
+        let object = self.involved_object.unwrap_or_default();
+        let object_fields = vec![
+            ("involved_object.api_version", object.api_version),
+            ("involved_object.kind", object.kind),
+            ("involved_object.name", object.name),
+            ("involved_object.namespace", object.namespace),
+            ("involved_object.uid", object.uid),
+            ("involved_object.field_path", object.field_path),
+        ];
+        for (key, value) in fields.into_iter().chain(object_fields) {
+            if let Some(value) = value {
+                log.insert(format!("kubernetes.{}", key), value);
+            }
+        }
+        if let Some(count) = self.count {
+            log.insert("kubernetes.event.count", i64::from(count));
+        }
+        if let Some(first_timestamp) = self.first_timestamp {
+            log.insert("kubernetes.event.first_timestamp", first_timestamp);
+        }
+
+        log
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use k8s_openapi::{
+        api::core::v1::{EventSeries, EventSource},
+        apimachinery::pkg::apis::meta::v1::{MicroTime, Time},
+    };
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<Config>();
+    }
+
+    #[test]
+    fn parses_api_versions() {
+        let config: Config = toml::from_str(r#"api_version = "events.k8s.io/v1""#).unwrap();
+        assert_eq!(config.api_version, ApiVersion::EventsV1);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.api_version, ApiVersion::CoreV1);
+    }
+
+    fn pod_reference() -> ObjectReference {
+        ObjectReference {
+            api_version: Some("v1".to_owned()),
+            kind: Some("Pod".to_owned()),
+            name: Some("vector-0".to_owned()),
+            namespace: Some("default".to_owned()),
+            uid: Some("9b8c2ba3-5d0f-4b3b-a3a6-0f9a0f2b6f3e".to_owned()),
+            field_path: Some("spec.containers{vector}".to_owned()),
+            ..ObjectReference::default()
+        }
+    }
+
+    fn event_metadata() -> ObjectMeta {
+        ObjectMeta {
+            name: Some("vector-0.16b5e3c5d3b7a3f1".to_owned()),
+            namespace: Some("default".to_owned()),
+            uid: Some("c1c8b2a4-2e2c-4a4e-9b38-6e6f1a1f4a7d".to_owned()),
+            resource_version: Some("1234".to_owned()),
+            ..ObjectMeta::default()
+        }
+    }
+
+    #[test]
+    fn converts_core_events() {
+        let first = Utc.ymd(2021, 11, 1).and_hms(8, 40, 32);
+        let last = Utc.ymd(2021, 11, 1).and_hms(8, 45, 32);
+        let event = CoreEvent {
+            metadata: event_metadata(),
+            message: Some("Back-off restarting failed container".to_owned()),
+            reason: Some("BackOff".to_owned()),
+            type_: Some("Warning".to_owned()),
+            count: Some(5),
+            first_timestamp: Some(Time(first)),
+            last_timestamp: Some(Time(last)),
+            reporting_component: Some("".to_owned()),
+            source: Some(EventSource {
+                component: Some("kubelet".to_owned()),
+                host: Some("node-1".to_owned()),
+            }),
+            involved_object: pod_reference(),
+            ..CoreEvent::default()
+        };
+
+        let log = EventFields::from(event).into_log();
+        assert_eq!(
+            log[log_schema().message_key()],
+            "Back-off restarting failed container".into()
+        );
+        assert_eq!(log[log_schema().timestamp_key()], last.into());
+        assert_eq!(log[log_schema().source_type_key()], COMPONENT_ID.into());
+        assert_eq!(log["kubernetes.event.reason"], "BackOff".into());
+        assert_eq!(log["kubernetes.event.type"], "Warning".into());
+        assert_eq!(log["kubernetes.event.count"], 5.into());
+        assert_eq!(log["kubernetes.event.first_timestamp"], first.into());
+        assert_eq!(
+            log["kubernetes.event.reporting_component"],
+            "kubelet".into()
+        );
+        assert_eq!(log["kubernetes.event.reporting_instance"], "node-1".into());
+        assert_eq!(log["kubernetes.event.namespace"], "default".into());
+        assert_eq!(log["kubernetes.involved_object.kind"], "Pod".into());
+        assert_eq!(log["kubernetes.involved_object.name"], "vector-0".into());
+        assert_eq!(
+            log["kubernetes.involved_object.field_path"],
+            "spec.containers{vector}".into()
+        );
+        assert!(!log.contains("kubernetes.event.action"));
+    }
+
+    #[test]
+    fn converts_series_of_core_events() {
+        let time = Utc.ymd(2021, 11, 1).and_hms(8, 40, 32);
+        let last = Utc.ymd(2021, 11, 1).and_hms(8, 50, 32);
+        let event = CoreEvent {
+            metadata: event_metadata(),
+            event_time: Some(MicroTime(time)),
+            series: Some(EventSeries {
+                count: Some(3),
+                last_observed_time: Some(MicroTime(last)),
+                ..EventSeries::default()
+            }),
+            ..CoreEvent::default()
+        };
+
+        let log = EventFields::from(event).into_log();
+        assert_eq!(log[log_schema().timestamp_key()], last.into());
+        assert_eq!(log["kubernetes.event.first_timestamp"], time.into());
+        assert_eq!(log["kubernetes.event.count"], 3.into());
+    }
+
+    #[test]
+    fn converts_events_v1() {
+        let time = Utc.ymd(2021, 11, 1).and_hms(8, 40, 32);
+        let event = events_v1::Event {
+            metadata: event_metadata(),
+            note: Some("Pulling image \"timberio/vector\"".to_owned()),
+            reason: Some("Pulling".to_owned()),
+            action: Some("Pulling".to_owned()),
+            type_: Some("Normal".to_owned()),
+            event_time: Some(MicroTime(time)),
+            reporting_controller: Some("kubelet".to_owned()),
+            reporting_instance: Some("node-1".to_owned()),
+            regarding: Some(pod_reference()),
+            ..events_v1::Event::default()
+        };
+
+        let log = EventFields::from(event).into_log();
+        assert_eq!(
+            log[log_schema().message_key()],
+            "Pulling image \"timberio/vector\"".into()
+        );
+        assert_eq!(log[log_schema().timestamp_key()], time.into());
+        assert_eq!(log["kubernetes.event.action"], "Pulling".into());
+        assert_eq!(
+            log["kubernetes.event.reporting_component"],
+            "kubelet".into()
+        );
+        assert_eq!(
+            log["kubernetes.involved_object.uid"],
+            "9b8c2ba3-5d0f-4b3b-a3a6-0f9a0f2b6f3e".into()
+        );
+        assert!(!log.contains("kubernetes.event.count"));
+    }
+}
@@ -0,0 +1,207 @@
+//! A state writer that forwards the watched events instead of caching them.
+
+use super::EventFields;
+use crate::{event::LogEvent, kubernetes::state};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{channel::mpsc, future::BoxFuture, SinkExt};
+use k8s_openapi::{apimachinery::pkg::apis::meta::v1::ObjectMeta, Metadata};
+use std::{collections::HashMap, marker::PhantomData};
+
+/// A [`state::Write`] implementation that converts every new version of an
+/// event object to a log event and sends it down a channel.
+///
+/// A watch lists all the existing objects first. Events that last occurred
+/// before `start` are skipped, so that starting the source doesn't replay the
+/// events still kept by the cluster.
+///
+/// The watch is restarted from scratch after a desync, which lists all the
+/// objects again. The last seen resource version of every object is kept so
+/// that only actual updates are forwarded. After a resync the versions are
+/// collected anew from the listed objects, and those of objects that weren't
+/// listed again are dropped at the next resync.
+pub struct Writer<T> {
+    sender: mpsc::Sender<LogEvent>,
+    start: DateTime<Utc>,
+    resource_versions: HashMap<String, String>,
+    /// The resource versions seen before the last resync.
+    previous_versions: HashMap<String, String>,
+    _object: PhantomData<fn() -> T>,
+}
+
+impl<T> Writer<T> {
+    pub fn new(sender: mpsc::Sender<LogEvent>, start: DateTime<Utc>) -> Self {
+        Self {
+            sender,
+            start,
+            resource_versions: HashMap::new(),
+            previous_versions: HashMap::new(),
+            _object: PhantomData,
+        }
+    }
+}
+
+impl<T> Writer<T>
+where
+    T: Metadata<Ty = ObjectMeta> + Into<EventFields> + Send,
+{
+    async fn forward(&mut self, item: T) {
+        let metadata = item.metadata();
+        if let (Some(uid), Some(resource_version)) = (&metadata.uid, &metadata.resource_version) {
+            let seen = self
+                .previous_versions
+                .remove(uid)
+                .or_else(|| self.resource_versions.remove(uid));
+            self.resource_versions
+                .insert(uid.clone(), resource_version.clone());
+            if seen.as_ref() == Some(resource_version) {
+                return;
+            }
+        }
+
+        let fields = item.into();
+        if fields.timestamp() < self.start {
+            return;
+        }
+        // The receiver only goes away when the source shuts down.
+        let _ = self.sender.send(fields.into_log()).await;
+    }
+}
+
+#[async_trait]
+impl<T> state::Write for Writer<T>
+where
+    T: Metadata<Ty = ObjectMeta> + Into<EventFields> + Send,
+{
+    type Item = T;
+
+    async fn add(&mut self, item: Self::Item) {
+        self.forward(item).await
+    }
+
+    async fn update(&mut self, item: Self::Item) {
+        self.forward(item).await
+    }
+
+    async fn delete(&mut self, item: Self::Item) {
+        if let Some(uid) = &item.metadata().uid {
+            self.resource_versions.remove(uid);
+            self.previous_versions.remove(uid);
+        }
+    }
+
+    async fn resync(&mut self) {
+        // Keep the resource versions to recognize the listed objects.
+        self.previous_versions = std::mem::take(&mut self.resource_versions);
+    }
+}
+
+#[async_trait]
+impl<T> state::MaintainedWrite for Writer<T>
+where
+    T: Metadata<Ty = ObjectMeta> + Into<EventFields> + Send,
+{
+    fn maintenance_request(&mut self) -> Option<BoxFuture<'_, ()>> {
+        None
+    }
+
+    async fn perform_maintenance(&mut self) {
+        // noop
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kubernetes::state::Write;
+    use chrono::Duration;
+    use futures::StreamExt;
+    use k8s_openapi::{api::core::v1::Event, apimachinery::pkg::apis::meta::v1::Time};
+
+    fn make_event(uid: &str, resource_version: &str, message: &str) -> Event {
+        Event {
+            metadata: ObjectMeta {
+                uid: Some(uid.to_owned()),
+                resource_version: Some(resource_version.to_owned()),
+                ..ObjectMeta::default()
+            },
+            message: Some(message.to_owned()),
+            ..Event::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_new_resource_versions_only() {
+        let (sender, receiver) = mpsc::channel(10);
+        let mut writer = Writer::new(sender, Utc::now());
+
+        writer.add(make_event("a", "1", "first")).await;
+        writer.update(make_event("a", "2", "second")).await;
+        writer.add(make_event("b", "3", "third")).await;
+
+        // Replayed after a desync.
+        writer.resync().await;
+        writer.add(make_event("a", "2", "second")).await;
+        writer.add(make_event("b", "3", "third")).await;
+
+        // Deleted and created again.
+        writer.delete(make_event("a", "2", "second")).await;
+        writer.add(make_event("a", "2", "fourth")).await;
+
+        drop(writer);
+        let messages = receiver
+            .map(|log| log["message"].to_string_lossy())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(messages, vec!["first", "second", "third", "fourth"]);
+    }
+
+    #[tokio::test]
+    async fn skips_events_before_start() {
+        let (sender, receiver) = mpsc::channel(10);
+        let start = Utc::now();
+        let mut writer = Writer::new(sender, start);
+
+        let at = |mut event: Event, timestamp| {
+            event.last_timestamp = Some(Time(timestamp));
+            event
+        };
+        writer
+            .add(at(
+                make_event("a", "1", "old"),
+                start - Duration::minutes(5),
+            ))
+            .await;
+        writer
+            .update(at(
+                make_event("a", "2", "new"),
+                start + Duration::seconds(1),
+            ))
+            .await;
+
+        drop(writer);
+        let messages = receiver
+            .map(|log| log["message"].to_string_lossy())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(messages, vec!["new"]);
+    }
+
+    #[tokio::test]
+    async fn forgets_objects_not_listed_after_resync() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let mut writer = Writer::new(sender, Utc::now());
+
+        writer.add(make_event("a", "1", "first")).await;
+        writer.add(make_event("b", "2", "second")).await;
+
+        // Only "a" is listed again, "b" was deleted while desynced.
+        writer.resync().await;
+        writer.add(make_event("a", "1", "first")).await;
+        writer.resync().await;
+
+        assert_eq!(writer.previous_versions.len(), 1);
+        assert!(writer.previous_versions.contains_key("a"));
+        assert!(writer.resource_versions.is_empty());
+    }
+}
@@ -8,7 +8,8 @@
 use crate::event::{Event, LogEvent};
 use crate::internal_events::{
     FileSourceInternalEventsEmitter, KubernetesLogsEventAnnotationFailed,
-    KubernetesLogsEventNamespaceAnnotationFailed, KubernetesLogsEventReceived,
+    KubernetesLogsEventNamespaceAnnotationFailed, KubernetesLogsEventNodeAnnotationFailed,
+    KubernetesLogsEventOwnerAnnotationFailed, KubernetesLogsEventReceived,
 };
 use crate::kubernetes as k8s;
 use crate::kubernetes::hash_value::HashKey;
@@ -27,7 +28,10 @@ use file_source::{
     Checkpointer, FileServer, FileServerShutdown, FingerprintStrategy, Fingerprinter, Line,
     ReadFrom,
 };
-use k8s_openapi::api::core::v1::{Namespace, Pod};
+use k8s_openapi::api::{
+    apps::v1::ReplicaSet,
+    core::v1::{Namespace, Node, Pod},
+};
 use serde::{Deserialize, Serialize};
 use shared::TimeZone;
 use std::convert::TryInto;
@@ -37,6 +41,8 @@ use std::time::Duration;
 mod k8s_paths_provider;
 mod lifecycle;
 mod namespace_metadata_annotator;
+mod node_metadata_annotator;
+mod owner_metadata_annotator;
 mod parser;
 mod partial_events_merger;
 mod path_helpers;
@@ -48,6 +54,8 @@ use futures::{future::FutureExt, sink::Sink, stream::StreamExt};
 use k8s_paths_provider::K8sPathsProvider;
 use lifecycle::Lifecycle;
 use namespace_metadata_annotator::NamespaceMetadataAnnotator;
+use node_metadata_annotator::NodeMetadataAnnotator;
+use owner_metadata_annotator::OwnerMetadataAnnotator;
 use pod_metadata_annotator::PodMetadataAnnotator;
 
 /// The key we use for `file` field.
@@ -86,6 +94,21 @@ pub struct Config {
     /// Specifies the field names for Namespace metadata annotation.
     namespace_annotation_fields: namespace_metadata_annotator::FieldsSpec,
 
+    /// Annotate events with the labels of the `Node` that Vector runs at.
+    /// Requires watching the `Node`, so it's disabled by default.
+    annotate_node_labels: bool,
+
+    /// Specifies the field names for Node metadata annotation.
+    node_annotation_fields: node_metadata_annotator::FieldsSpec,
+
+    /// Resolve the controller of every `Pod` through the `ReplicaSet`s it
+    /// may be managed by. Requires watching all the `ReplicaSet`s of the
+    /// cluster, so it's disabled by default.
+    resolve_pod_controllers: bool,
+
+    /// Specifies the field names for the `Pod` controller annotation.
+    owner_annotation_fields: owner_metadata_annotator::FieldsSpec,
+
     /// A list of glob patterns to exclude from reading the files.
     exclude_paths_glob_patterns: Vec<PathBuf>,
 
@@ -153,6 +176,10 @@ impl Default for Config {
             data_dir: None,
             pod_annotation_fields: pod_metadata_annotator::FieldsSpec::default(),
             namespace_annotation_fields: namespace_metadata_annotator::FieldsSpec::default(),
+            annotate_node_labels: false,
+            node_annotation_fields: node_metadata_annotator::FieldsSpec::default(),
+            resolve_pod_controllers: false,
+            owner_annotation_fields: owner_metadata_annotator::FieldsSpec::default(),
             exclude_paths_glob_patterns: default_path_exclusion(),
             max_read_bytes: default_max_read_bytes(),
             max_line_bytes: default_max_line_bytes(),
@@ -196,6 +223,9 @@ struct Source {
     auto_partial_merge: bool,
     pod_fields_spec: pod_metadata_annotator::FieldsSpec,
     namespace_fields_spec: namespace_metadata_annotator::FieldsSpec,
+    node_fields_spec: Option<node_metadata_annotator::FieldsSpec>,
+    owner_fields_spec: Option<owner_metadata_annotator::FieldsSpec>,
+    self_node_name: String,
     field_selector: String,
     label_selector: String,
     exclude_paths: Vec<glob::Pattern>,
@@ -215,7 +245,8 @@ impl Source {
         key: &ComponentKey,
         proxy: &ProxyConfig,
     ) -> crate::Result<Self> {
-        let field_selector = prepare_field_selector(config)?;
+        let self_node_name = prepare_self_node_name(config)?;
+        let field_selector = prepare_field_selector(config, &self_node_name);
         let label_selector = prepare_label_selector(config);
 
         let k8s_config = match &config.kube_config_file {
@@ -247,6 +278,17 @@ impl Source {
             auto_partial_merge: config.auto_partial_merge,
             pod_fields_spec: config.pod_annotation_fields.clone(),
             namespace_fields_spec: config.namespace_annotation_fields.clone(),
+            node_fields_spec: if config.annotate_node_labels {
+                Some(config.node_annotation_fields.clone())
+            } else {
+                None
+            },
+            owner_fields_spec: if config.resolve_pod_controllers {
+                Some(config.owner_annotation_fields.clone())
+            } else {
+                None
+            },
+            self_node_name,
             field_selector,
             label_selector,
             exclude_paths,
@@ -271,6 +313,9 @@ impl Source {
             auto_partial_merge,
             pod_fields_spec,
             namespace_fields_spec,
+            node_fields_spec,
+            owner_fields_spec,
+            self_node_name,
             field_selector,
             label_selector,
             exclude_paths,
@@ -328,6 +373,80 @@ impl Source {
         );
         let ns_reflector_process = ns_reflector.run();
 
+        // -----------------------------------------------------------------
+
+        // The `Node` is only watched when its labels are annotated.
+        let node_annotation = node_fields_spec.map(|node_fields_spec| {
+            let node_watcher = k8s::api_watcher::ApiWatcher::new(client.clone(), Node::watch_node);
+            let node_watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(node_watcher);
+            let (node_state_reader, node_state_writer) = evmap::new();
+            let node_state_writer = k8s::state::evmap::Writer::new(
+                node_state_writer,
+                Some(Duration::from_millis(10)),
+                HashKey::Name,
+            );
+            let node_state_writer = k8s::state::instrumenting::Writer::new(node_state_writer);
+            let node_state_writer =
+                k8s::state::delayed_delete::Writer::new(node_state_writer, delay_deletion);
+
+            let node_reflector = k8s::reflector::Reflector::new(
+                node_watcher,
+                node_state_writer,
+                Some(prepare_node_field_selector(&self_node_name)),
+                None,
+                Duration::from_secs(1),
+            );
+            let node_annotator = NodeMetadataAnnotator::new(node_state_reader, node_fields_spec);
+            (node_reflector, node_annotator)
+        });
+        let (mut node_reflector, node_annotator) = match node_annotation {
+            Some((node_reflector, node_annotator)) => (Some(node_reflector), Some(node_annotator)),
+            None => (None, None),
+        };
+        let node_reflector_process = node_reflector
+            .as_mut()
+            .map(|node_reflector| node_reflector.run());
+
+        // -----------------------------------------------------------------
+
+        // `ReplicaSet`s are only watched when the controllers are resolved, as
+        // they can't be narrowed down to the ones of the pods at this node.
+        let owner_annotation = owner_fields_spec.map(|owner_fields_spec| {
+            let rs_watcher = k8s::api_watcher::ApiWatcher::new(
+                client.clone(),
+                ReplicaSet::watch_replica_set_for_all_namespaces,
+            );
+            let rs_watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(rs_watcher);
+            let (rs_state_reader, rs_state_writer) = evmap::new();
+            let rs_state_writer = k8s::state::evmap::Writer::new(
+                rs_state_writer,
+                Some(Duration::from_millis(10)),
+                HashKey::Uid,
+            );
+            let rs_state_writer = k8s::state::instrumenting::Writer::new(rs_state_writer);
+            let rs_state_writer =
+                k8s::state::delayed_delete::Writer::new(rs_state_writer, delay_deletion);
+
+            let rs_reflector = k8s::reflector::Reflector::new(
+                rs_watcher,
+                rs_state_writer,
+                None,
+                None,
+                Duration::from_secs(1),
+            );
+            let owner_annotator = OwnerMetadataAnnotator::new(
+                state_reader.clone(),
+                rs_state_reader,
+                owner_fields_spec,
+            );
+            (rs_reflector, owner_annotator)
+        });
+        let (mut rs_reflector, owner_annotator) = match owner_annotation {
+            Some((rs_reflector, owner_annotator)) => (Some(rs_reflector), Some(owner_annotator)),
+            None => (None, None),
+        };
+        let rs_reflector_process = rs_reflector.as_mut().map(|rs_reflector| rs_reflector.run());
+
         let paths_provider =
             K8sPathsProvider::new(state_reader.clone(), ns_state_reader.clone(), exclude_paths);
         let annotator = PodMetadataAnnotator::new(state_reader, pod_fields_spec);
@@ -428,6 +547,21 @@ impl Source {
                         emit!(&KubernetesLogsEventNamespaceAnnotationFailed { event: &event });
                     }
                 }
+
+                if let (Some(owner_annotator), Some(info)) = (&owner_annotator, &file_info) {
+                    if owner_annotator.annotate(&mut event, info.pod_uid).is_none() {
+                        emit!(&KubernetesLogsEventOwnerAnnotationFailed { event: &event });
+                    }
+                }
+            }
+
+            if let Some(node_annotator) = &node_annotator {
+                if node_annotator
+                    .annotate(&mut event, &self_node_name)
+                    .is_none()
+                {
+                    emit!(&KubernetesLogsEventNodeAnnotationFailed { event: &event });
+                }
             }
 
             checkpoints.update(line.file_id, line.offset);
@@ -467,6 +601,31 @@ impl Source {
                 });
             slot.bind(Box::pin(fut));
         }
+        if let Some(node_reflector_process) = node_reflector_process {
+            let (slot, shutdown) = lifecycle.add();
+            let fut =
+                util::cancel_on_signal(node_reflector_process, shutdown).map(
+                    |result| match result {
+                        Ok(()) => info!(message = "Node reflector process completed gracefully."),
+                        Err(error) => {
+                            error!(message = "Node reflector process exited with an error.", %error)
+                        }
+                    },
+                );
+            slot.bind(Box::pin(fut));
+        }
+        if let Some(rs_reflector_process) = rs_reflector_process {
+            let (slot, shutdown) = lifecycle.add();
+            let fut = util::cancel_on_signal(rs_reflector_process, shutdown).map(|result| {
+                match result {
+                    Ok(()) => info!(message = "ReplicaSet reflector process completed gracefully."),
+                    Err(error) => {
+                        error!(message = "ReplicaSet reflector process exited with an error.", %error)
+                    }
+                }
+            });
+            slot.bind(Box::pin(fut));
+        }
         {
             let (slot, shutdown) = lifecycle.add();
             let fut = util::run_file_server(file_server, file_source_tx, shutdown, checkpointer)
@@ -588,9 +747,9 @@ fn prepare_exclude_paths(config: &Config) -> crate::Result<Vec<glob::Pattern>> {
     Ok(exclude_paths)
 }
 
-// This function constructs the effective field selector to use, based on
-// the specified configuration.
-fn prepare_field_selector(config: &Config) -> crate::Result<String> {
+// This function obtains the name of the `Node` Vector runs at, based on the
+// specified configuration.
+fn prepare_self_node_name(config: &Config) -> crate::Result<String> {
     let self_node_name = if config.self_node_name.is_empty()
         || config.self_node_name == default_self_node_name_env_template()
     {
@@ -608,16 +767,24 @@ fn prepare_field_selector(config: &Config) -> crate::Result<String> {
         ?self_node_name
     );
 
+    Ok(self_node_name)
+}
+
+// This function constructs the effective field selector to use, based on
+// the specified configuration.
+fn prepare_field_selector(config: &Config, self_node_name: &str) -> String {
     let field_selector = format!("spec.nodeName={}", self_node_name);
 
     if config.extra_field_selector.is_empty() {
-        return Ok(field_selector);
+        return field_selector;
     }
 
-    Ok(format!(
-        "{},{}",
-        field_selector, config.extra_field_selector
-    ))
+    format!("{},{}", field_selector, config.extra_field_selector)
+}
+
+// This function constructs the field selector of the `Node` Vector runs at.
+fn prepare_node_field_selector(self_node_name: &str) -> String {
+    format!("metadata.name={}", self_node_name)
 }
 
 // This function constructs the effective label selector to use, based on
@@ -714,7 +881,7 @@ mod tests {
         ];
 
         for (input, expected) in cases {
-            let output = super::prepare_field_selector(&input).unwrap();
+            let output = super::prepare_field_selector(&input, &input.self_node_name);
             assert_eq!(expected, output, "expected left, actual right");
         }
     }
@@ -0,0 +1,138 @@
+//! Annotates events with node metadata.
+
+#![deny(missing_docs)]
+
+use crate::{
+    event::{Event, LogEvent, PathComponent, PathIter},
+    kubernetes as k8s,
+};
+use evmap::ReadHandle;
+use k8s_openapi::{api::core::v1::Node, apimachinery::pkg::apis::meta::v1::ObjectMeta};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct FieldsSpec {
+    pub node_labels: String,
+}
+
+impl Default for FieldsSpec {
+    fn default() -> Self {
+        Self {
+            node_labels: "kubernetes.node_labels".to_owned(),
+        }
+    }
+}
+
+/// Annotate the event with node metadata.
+pub struct NodeMetadataAnnotator {
+    node_state_reader: ReadHandle<String, k8s::state::evmap::Value<Node>>,
+    fields_spec: FieldsSpec,
+}
+
+impl NodeMetadataAnnotator {
+    /// Create a new [`NodeMetadataAnnotator`].
+    pub fn new(
+        node_state_reader: ReadHandle<String, k8s::state::evmap::Value<Node>>,
+        fields_spec: FieldsSpec,
+    ) -> Self {
+        Self {
+            node_state_reader,
+            fields_spec,
+        }
+    }
+}
+
+impl NodeMetadataAnnotator {
+    /// Annotates an event with the information from the [`Node::metadata`]
+    /// of the node the pod runs at.
+    pub fn annotate(&self, event: &mut Event, node_name: &str) -> Option<()> {
+        let log = event.as_mut_log();
+        let guard = self.node_state_reader.get(node_name)?;
+        let entry = guard.get_one()?;
+        let node: &Node = entry.as_ref();
+
+        annotate_from_metadata(log, &self.fields_spec, &node.metadata);
+        Some(())
+    }
+}
+
+fn annotate_from_metadata(log: &mut LogEvent, fields_spec: &FieldsSpec, metadata: &ObjectMeta) {
+    // Calculate and cache the prefix path.
+    let prefix_path = PathIter::new(fields_spec.node_labels.as_ref()).collect::<Vec<_>>();
+    if let Some(labels) = &metadata.labels {
+        for (key, val) in labels.iter() {
+            let mut path = prefix_path.clone();
+            path.push(PathComponent::Key(key.clone().into()));
+            log.insert_path(path, val.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::assert_event_data_eq;
+
+    #[test]
+    fn test_annotate_from_metadata() {
+        let cases = vec![
+            (
+                FieldsSpec::default(),
+                ObjectMeta::default(),
+                LogEvent::default(),
+            ),
+            (
+                FieldsSpec::default(),
+                ObjectMeta {
+                    name: Some("sandbox0-name".to_owned()),
+                    uid: Some("sandbox0-uid".to_owned()),
+                    labels: Some(
+                        vec![
+                            ("sandbox0-label0".to_owned(), "val0".to_owned()),
+                            ("sandbox0-label1".to_owned(), "val1".to_owned()),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..ObjectMeta::default()
+                },
+                {
+                    let mut log = LogEvent::default();
+                    log.insert("kubernetes.node_labels.sandbox0-label0", "val0");
+                    log.insert("kubernetes.node_labels.sandbox0-label1", "val1");
+                    log
+                },
+            ),
+            (
+                FieldsSpec {
+                    node_labels: "node_labels".to_owned(),
+                },
+                ObjectMeta {
+                    name: Some("sandbox0-name".to_owned()),
+                    uid: Some("sandbox0-uid".to_owned()),
+                    labels: Some(
+                        vec![(
+                            "topology.kubernetes.io/zone".to_owned(),
+                            "us-east-1a".to_owned(),
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..ObjectMeta::default()
+                },
+                {
+                    let mut log = LogEvent::default();
+                    log.insert(r#"node_labels.topology\.kubernetes\.io/zone"#, "us-east-1a");
+                    log
+                },
+            ),
+        ];
+
+        for (fields_spec, metadata, expected) in cases.into_iter() {
+            let mut log = LogEvent::default();
+            annotate_from_metadata(&mut log, &fields_spec, &metadata);
+            assert_event_data_eq!(log, expected);
+        }
+    }
+}
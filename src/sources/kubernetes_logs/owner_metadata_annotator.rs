@@ -0,0 +1,209 @@
+//! Annotates events with the controller that owns the pod.
+
+#![deny(missing_docs)]
+
+use crate::{event::Event, kubernetes as k8s};
+use evmap::ReadHandle;
+use k8s_openapi::{
+    api::{apps::v1::ReplicaSet, core::v1::Pod},
+    apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct FieldsSpec {
+    pub pod_controller: String,
+}
+
+impl Default for FieldsSpec {
+    fn default() -> Self {
+        Self {
+            pod_controller: "kubernetes.pod_controller".to_owned(),
+        }
+    }
+}
+
+/// Annotate the event with the controller of the pod, following the
+/// `ReplicaSet`s managed by `Deployment`s up to the `Deployment`.
+pub struct OwnerMetadataAnnotator {
+    pods_state_reader: ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+    replica_sets_state_reader: ReadHandle<String, k8s::state::evmap::Value<ReplicaSet>>,
+    fields_spec: FieldsSpec,
+}
+
+impl OwnerMetadataAnnotator {
+    /// Create a new [`OwnerMetadataAnnotator`].
+    pub fn new(
+        pods_state_reader: ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+        replica_sets_state_reader: ReadHandle<String, k8s::state::evmap::Value<ReplicaSet>>,
+        fields_spec: FieldsSpec,
+    ) -> Self {
+        Self {
+            pods_state_reader,
+            replica_sets_state_reader,
+            fields_spec,
+        }
+    }
+}
+
+impl OwnerMetadataAnnotator {
+    /// Annotates an event with the controller of the pod with the `pod_uid`.
+    /// Pods without a controller are left as is.
+    pub fn annotate(&self, event: &mut Event, pod_uid: &str) -> Option<()> {
+        let guard = self.pods_state_reader.get(pod_uid)?;
+        let entry = guard.get_one()?;
+        let pod: &Pod = entry.as_ref();
+
+        let owner = match controller_of(&pod.metadata) {
+            Some(owner) => owner,
+            None => return Some(()),
+        };
+        let owner = if owner.kind == "ReplicaSet" {
+            self.replica_set_controller(&owner.uid)?
+        } else {
+            format_owner(owner)
+        };
+
+        event
+            .as_mut_log()
+            .insert(&self.fields_spec.pod_controller, owner);
+        Some(())
+    }
+
+    /// The controller of the `ReplicaSet` with the `uid`, or the `ReplicaSet`
+    /// itself if it's not controlled by anything.
+    fn replica_set_controller(&self, uid: &str) -> Option<String> {
+        let guard = self.replica_sets_state_reader.get(uid)?;
+        let entry = guard.get_one()?;
+        let replica_set: &ReplicaSet = entry.as_ref();
+
+        let owner = controller_of(&replica_set.metadata).map(format_owner);
+        Some(owner.unwrap_or_else(|| {
+            format!(
+                "ReplicaSet/{}",
+                replica_set.metadata.name.as_deref().unwrap_or_default()
+            )
+        }))
+    }
+}
+
+/// The managing controller of an object, as opposed to any other owner.
+fn controller_of(metadata: &ObjectMeta) -> Option<&OwnerReference> {
+    metadata
+        .owner_references
+        .as_ref()?
+        .iter()
+        .find(|owner| owner.controller == Some(true))
+}
+
+fn format_owner(owner: &OwnerReference) -> String {
+    format!("{}/{}", owner.kind, owner.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+    use k8s::{hash_value::HashKey, state::Write};
+    use shared::assert_event_data_eq;
+
+    fn owner(kind: &str, name: &str, uid: &str, controller: bool) -> OwnerReference {
+        OwnerReference {
+            api_version: "apps/v1".to_owned(),
+            kind: kind.to_owned(),
+            name: name.to_owned(),
+            uid: uid.to_owned(),
+            controller: Some(controller),
+            ..OwnerReference::default()
+        }
+    }
+
+    fn metadata(name: &str, uid: &str, owners: Vec<OwnerReference>) -> ObjectMeta {
+        ObjectMeta {
+            name: Some(name.to_owned()),
+            uid: Some(uid.to_owned()),
+            owner_references: Some(owners),
+            ..ObjectMeta::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_annotate() {
+        let (pods_reader, pods_writer) = evmap::new();
+        let mut pods_writer = k8s::state::evmap::Writer::new(pods_writer, None, HashKey::Uid);
+        let (replica_sets_reader, replica_sets_writer) = evmap::new();
+        let mut replica_sets_writer =
+            k8s::state::evmap::Writer::new(replica_sets_writer, None, HashKey::Uid);
+
+        let pods = vec![
+            (
+                "deployment-pod",
+                vec![owner("ReplicaSet", "vector-abc", "rs0", true)],
+            ),
+            (
+                "replica-set-pod",
+                vec![owner("ReplicaSet", "standalone", "rs1", true)],
+            ),
+            (
+                "stateful-set-pod",
+                vec![owner("StatefulSet", "vector", "ss0", true)],
+            ),
+            (
+                "unknown-replica-set-pod",
+                vec![owner("ReplicaSet", "gone", "rs2", true)],
+            ),
+            (
+                "unmanaged-pod",
+                vec![owner("ConfigMap", "config", "cm0", false)],
+            ),
+        ];
+        for (uid, owners) in pods {
+            pods_writer
+                .add(Pod {
+                    metadata: metadata(uid, uid, owners),
+                    ..Pod::default()
+                })
+                .await;
+        }
+        replica_sets_writer
+            .add(ReplicaSet {
+                metadata: metadata(
+                    "vector-abc",
+                    "rs0",
+                    vec![owner("Deployment", "vector", "d0", true)],
+                ),
+                ..ReplicaSet::default()
+            })
+            .await;
+        replica_sets_writer
+            .add(ReplicaSet {
+                metadata: metadata("standalone", "rs1", vec![]),
+                ..ReplicaSet::default()
+            })
+            .await;
+
+        let annotator =
+            OwnerMetadataAnnotator::new(pods_reader, replica_sets_reader, FieldsSpec::default());
+        let cases = vec![
+            ("deployment-pod", Some(Some("Deployment/vector"))),
+            ("replica-set-pod", Some(Some("ReplicaSet/standalone"))),
+            ("stateful-set-pod", Some(Some("StatefulSet/vector"))),
+            ("unknown-replica-set-pod", None),
+            ("unmanaged-pod", Some(None)),
+            ("unknown-pod", None),
+        ];
+
+        for (pod_uid, expected) in cases {
+            let mut event = Event::from(LogEvent::default());
+            let result = annotator.annotate(&mut event, pod_uid);
+            assert_eq!(result.is_some(), expected.is_some(), "{}", pod_uid);
+
+            let mut expected_log = LogEvent::default();
+            if let Some(Some(controller)) = expected {
+                expected_log.insert("kubernetes.pod_controller", controller);
+            }
+            assert_event_data_eq!(event.into_log(), expected_log);
+        }
+    }
+}
@@ -29,6 +29,7 @@ pub struct FieldsSpec {
     pub container_name: String,
     pub container_id: String,
     pub container_image: String,
+    pub container_image_id: String,
 }
 
 impl Default for FieldsSpec {
@@ -46,6 +47,7 @@ impl Default for FieldsSpec {
             container_name: "kubernetes.container_name".to_owned(),
             container_id: "kubernetes.container_id".to_owned(),
             container_image: "kubernetes.container_image".to_owned(),
+            container_image_id: "kubernetes.container_image_id".to_owned(),
         }
     }
 }
@@ -198,6 +200,15 @@ fn annotate_from_container_status(
             log.insert(key, val.to_owned());
         }
     }
+
+    // The image ID carries the digest of the image the container runs, unlike
+    // the image of the container spec that usually refers to a mutable tag.
+    if !container_status.image_id.is_empty() {
+        log.insert(
+            &fields_spec.container_image_id,
+            container_status.image_id.to_owned(),
+        );
+    }
 }
 
 fn annotate_from_container(log: &mut LogEvent, fields_spec: &FieldsSpec, container: &Container) {
@@ -532,6 +543,26 @@ mod tests {
                     log
                 },
             ),
+            (
+                FieldsSpec {
+                    container_image_id: "image_id".to_owned(),
+                    ..FieldsSpec::default()
+                },
+                ContainerStatus {
+                    container_id: Some("container_id_foo".to_owned()),
+                    image_id: "docker-pullable://timberio/vector@sha256:2b1e4c5f0a9d".to_owned(),
+                    ..ContainerStatus::default()
+                },
+                {
+                    let mut log = LogEvent::default();
+                    log.insert("kubernetes.container_id", "container_id_foo");
+                    log.insert(
+                        "image_id",
+                        "docker-pullable://timberio/vector@sha256:2b1e4c5f0a9d",
+                    );
+                    log
+                },
+            ),
         ];
         for (fields_spec, container_status, expected) in cases.into_iter() {
             let mut log = LogEvent::default();
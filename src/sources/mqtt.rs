@@ -0,0 +1,291 @@
+use crate::{
+    codecs::{self, DecodingConfig, FramingConfig, ParserConfig},
+    config::{
+        log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
+    },
+    event::Event,
+    internal_events::{MqttConnectionFailed, MqttEventsReceived},
+    mqtt::{MqttConnectionConfig, MqttError, MqttQos},
+    serde::{default_decoding, default_framing_message_based, default_true},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use rumqttc::{AsyncClient, Event as MqttEvent, EventLoop, Packet, SubscribeFilter};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::time::Duration;
+use tokio_util::codec::FramedRead;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Could not create MQTT client: {}", source))]
+    MqttClientError { source: MqttError },
+    #[snafu(display("At least one topic must be configured"))]
+    NoTopics,
+}
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+pub struct MqttSourceConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topics: Vec<String>,
+    #[serde(default)]
+    qos: MqttQos,
+    #[serde(default = "default_true")]
+    #[derivative(Default(value = "true"))]
+    clean_session: bool,
+    #[serde(default = "default_topic_key")]
+    #[derivative(Default(value = "default_topic_key()"))]
+    topic_key: String,
+    #[serde(default = "default_framing_message_based")]
+    #[derivative(Default(value = "default_framing_message_based()"))]
+    framing: Box<dyn FramingConfig>,
+    #[serde(default = "default_decoding")]
+    #[derivative(Default(value = "default_decoding()"))]
+    decoding: Box<dyn ParserConfig>,
+}
+
+fn default_topic_key() -> String {
+    "topic".into()
+}
+
+inventory::submit! {
+    SourceDescription::new::<MqttSourceConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            port = 1883
+            topics = ["vector/#"]"#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SourceConfig for MqttSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.topics.is_empty() {
+            return Err(BuildError::NoTopics.into());
+        }
+
+        let (client, eventloop) = self
+            .connection
+            .build_client(self.clean_session)
+            .context(MqttClientError)?;
+        let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build()?;
+
+        Ok(Box::pin(mqtt_source(
+            client,
+            eventloop,
+            self.subscribe_filters(),
+            self.topic_key.clone(),
+            decoder,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "mqtt"
+    }
+}
+
+impl MqttSourceConfig {
+    fn subscribe_filters(&self) -> Vec<SubscribeFilter> {
+        self.topics
+            .iter()
+            .map(|topic| SubscribeFilter::new(topic.clone(), self.qos.into()))
+            .collect()
+    }
+}
+
+async fn mqtt_source(
+    client: AsyncClient,
+    mut eventloop: EventLoop,
+    filters: Vec<SubscribeFilter>,
+    topic_key: String,
+    decoder: codecs::Decoder,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    loop {
+        let notification = tokio::select! {
+            _ = &mut shutdown => break,
+            notification = eventloop.poll() => notification,
+        };
+
+        match notification {
+            Ok(MqttEvent::Incoming(Packet::ConnAck(ack))) => {
+                // Persistent sessions keep their subscriptions on the broker,
+                // so only subscribe when no session was resumed.
+                if !ack.session_present {
+                    client
+                        .subscribe_many(filters.clone())
+                        .await
+                        .map_err(|error| {
+                            error!(message = "Failed to subscribe to MQTT topics.", %error);
+                        })?;
+                }
+            }
+            Ok(MqttEvent::Incoming(Packet::Publish(publish))) => {
+                let mut stream = FramedRead::new(publish.payload.as_ref(), decoder.clone());
+                while let Some(next) = stream.next().await {
+                    match next {
+                        Ok((events, byte_size)) => {
+                            emit!(&MqttEventsReceived {
+                                byte_size,
+                                count: events.len()
+                            });
+
+                            let now = Utc::now();
+
+                            for mut event in events {
+                                if let Event::Log(ref mut log) = event {
+                                    log.try_insert(
+                                        log_schema().source_type_key(),
+                                        Bytes::from("mqtt"),
+                                    );
+                                    log.try_insert(log_schema().timestamp_key(), now);
+                                    log.try_insert(topic_key.as_str(), publish.topic.clone());
+                                }
+
+                                out.send(event).await.map_err(
+                                    |error: crate::pipeline::ClosedError| {
+                                        error!(message = "Error sending to sink.", %error);
+                                    },
+                                )?;
+                            }
+                        }
+                        Err(error) => {
+                            // Error is logged by `crate::codecs::Decoder`, no further
+                            // handling is needed here.
+                            if !error.can_continue() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(error) => {
+                emit!(&MqttConnectionFailed { error });
+                // The next poll reconnects, so back off to avoid spinning
+                // while the broker is unreachable.
+                tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSourceConfig>();
+    }
+
+    #[test]
+    fn parses_config() {
+        let config: MqttSourceConfig = toml::from_str(
+            r#"
+            host = "broker.example.com"
+            topics = ["sensors/+/temperature", "alerts/#"]
+            qos = "exactly_once"
+            clean_session = false
+            client_id = "vector-1"
+            topic_key = "mqtt_topic"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.connection.port, 1883);
+        assert_eq!(config.qos, MqttQos::ExactlyOnce);
+        assert!(!config.clean_session);
+        assert_eq!(config.topic_key, "mqtt_topic");
+        assert_eq!(config.subscribe_filters().len(), 2);
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{collect_n, random_string, trace_init};
+    use rumqttc::QoS;
+
+    fn broker_host() -> String {
+        std::env::var("MQTT_HOST").unwrap_or_else(|_| "127.0.0.1".into())
+    }
+
+    #[tokio::test]
+    async fn mqtt_happy() {
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let config = MqttSourceConfig {
+            connection: MqttConnectionConfig {
+                host: broker_host(),
+                ..Default::default()
+            },
+            topics: vec![format!("{}/#", topic)],
+            ..Default::default()
+        };
+
+        let (client, eventloop) = config.connection.build_client(true).unwrap();
+        let decoder = DecodingConfig::new(config.framing.clone(), config.decoding.clone())
+            .build()
+            .unwrap();
+        let (tx, rx) = Pipeline::new_test();
+        tokio::spawn(mqtt_source(
+            client,
+            eventloop,
+            config.subscribe_filters(),
+            config.topic_key.clone(),
+            decoder,
+            ShutdownSignal::noop(),
+            tx,
+        ));
+
+        // Give the source time to connect and subscribe.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let (publisher, mut publisher_loop) = MqttConnectionConfig {
+            host: broker_host(),
+            ..Default::default()
+        }
+        .build_client(true)
+        .unwrap();
+        let sensor_topic = format!("{}/sensor-1", topic);
+        publisher
+            .publish(&sensor_topic, QoS::AtLeastOnce, false, "my message")
+            .await
+            .unwrap();
+        tokio::spawn(async move { while publisher_loop.poll().await.is_ok() {} });
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "my message".into());
+        assert_eq!(log["topic"], sensor_topic.into());
+        assert_eq!(log[log_schema().source_type_key()], "mqtt".into());
+    }
+}
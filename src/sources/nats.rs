@@ -1,28 +1,48 @@
 use crate::{
     codecs::{self, DecodingConfig, FramingConfig, ParserConfig},
     config::{
-        log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
+        log_schema, AcknowledgementsConfig, DataType, GenerateConfig, SourceConfig, SourceContext,
+        SourceDescription,
     },
-    event::Event,
-    internal_events::NatsEventsReceived,
-    serde::{default_decoding, default_framing_message_based},
+    event::{BatchNotifier, BatchStatus, Event},
+    internal_events::{NatsAckFailed, NatsEventsReceived, NatsFetchFailed},
+    nats::{nats_options, NatsAuthConfig, NatsError},
+    serde::{bool_or_struct, default_decoding, default_framing_message_based},
     shutdown::ShutdownSignal,
-    sources::util::TcpError,
+    sources::util::finalizer::OrderedFinalizer,
+    tls::TlsConfig,
     Pipeline,
 };
 use bytes::Bytes;
-use chrono::Utc;
-use futures::{pin_mut, stream, SinkExt, Stream, StreamExt};
+use chrono::{DateTime, Utc};
+use futures::{pin_mut, stream, FutureExt, SinkExt, Stream, StreamExt};
+use nats::jetstream::{
+    AckKind, AckPolicy, BatchOptions, ConsumerConfig, DeliverPolicy, JetStream, JetStreamOptions,
+    PullSubscibeOptions, PullSubscription,
+};
 use serde::{Deserialize, Serialize};
-use snafu::Snafu;
+use snafu::{ResultExt, Snafu};
+use std::{io, sync::Arc, time::Duration};
 use tokio_util::codec::FramedRead;
 
+/// How long a JetStream pull request waits for messages, which bounds how
+/// long shutdown can be delayed.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Snafu)]
 enum BuildError {
     #[snafu(display("Could not create Nats subscriber: {}", source))]
     NatsCreateError { source: std::io::Error },
     #[snafu(display("Could not subscribe to Nats topics: {}", source))]
     NatsSubscribeError { source: std::io::Error },
+    #[snafu(display("Invalid Nats connection options: {}", source))]
+    NatsOptionsError { source: NatsError },
+    #[snafu(display("`queue` can't be used together with `jetstream`"))]
+    QueueWithJetStream,
+    #[snafu(display("`jetstream.start_sequence` is required by the `by_start_sequence` policy"))]
+    MissingStartSequence,
+    #[snafu(display("`jetstream.start_time` is required by the `by_start_time` policy"))]
+    MissingStartTime,
 }
 
 #[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
@@ -34,12 +54,110 @@ pub struct NatsSourceConfig {
     connection_name: String,
     subject: String,
     queue: Option<String>,
+    auth: Option<NatsAuthConfig>,
+    tls: Option<TlsConfig>,
+    jetstream: Option<JetStreamConfig>,
     #[serde(default = "default_framing_message_based")]
     #[derivative(Default(value = "default_framing_message_based()"))]
     framing: Box<dyn FramingConfig>,
     #[serde(default = "default_decoding")]
     #[derivative(Default(value = "default_decoding()"))]
     decoding: Box<dyn ParserConfig>,
+    #[serde(default, deserialize_with = "bool_or_struct")]
+    acknowledgements: AcknowledgementsConfig,
+}
+
+/// Consume from a JetStream stream through a durable pull consumer, instead
+/// of subscribing to the subject directly.
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct JetStreamConfig {
+    stream: String,
+    #[serde(default = "default_durable_name")]
+    #[derivative(Default(value = "default_durable_name()"))]
+    durable_name: String,
+    #[serde(default)]
+    deliver_policy: JetStreamDeliverPolicy,
+    start_sequence: Option<u64>,
+    start_time: Option<DateTime<Utc>>,
+    #[serde(default = "default_ack_wait_secs")]
+    #[derivative(Default(value = "default_ack_wait_secs()"))]
+    ack_wait_secs: u64,
+    #[serde(default = "default_max_ack_pending")]
+    #[derivative(Default(value = "default_max_ack_pending()"))]
+    max_ack_pending: i64,
+    #[serde(default = "default_batch_size")]
+    #[derivative(Default(value = "default_batch_size()"))]
+    batch_size: usize,
+}
+
+fn default_durable_name() -> String {
+    "vector".to_owned()
+}
+
+const fn default_ack_wait_secs() -> u64 {
+    30
+}
+
+const fn default_max_ack_pending() -> i64 {
+    1000
+}
+
+const fn default_batch_size() -> usize {
+    100
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JetStreamDeliverPolicy {
+    #[derivative(Default)]
+    All,
+    Last,
+    LastPerSubject,
+    New,
+    ByStartSequence,
+    ByStartTime,
+}
+
+impl JetStreamConfig {
+    /// The durable consumer created on the stream. The deliver policy only
+    /// applies when the consumer is created, afterwards it resumes from its
+    /// last acknowledged message.
+    fn consumer_config(&self, subject: &str) -> Result<ConsumerConfig, BuildError> {
+        let (deliver_policy, opt_start_seq, opt_start_time) = match self.deliver_policy {
+            JetStreamDeliverPolicy::All => (DeliverPolicy::All, None, None),
+            JetStreamDeliverPolicy::Last => (DeliverPolicy::Last, None, None),
+            JetStreamDeliverPolicy::LastPerSubject => (DeliverPolicy::LastPerSubject, None, None),
+            JetStreamDeliverPolicy::New => (DeliverPolicy::New, None, None),
+            JetStreamDeliverPolicy::ByStartSequence => (
+                DeliverPolicy::ByStartSeq,
+                Some(
+                    self.start_sequence
+                        .ok_or(BuildError::MissingStartSequence)?,
+                ),
+                None,
+            ),
+            JetStreamDeliverPolicy::ByStartTime => (
+                DeliverPolicy::ByStartTime,
+                None,
+                Some(self.start_time.ok_or(BuildError::MissingStartTime)?),
+            ),
+        };
+
+        Ok(ConsumerConfig {
+            durable_name: Some(self.durable_name.clone()),
+            deliver_policy,
+            opt_start_seq,
+            opt_start_time,
+            ack_policy: AckPolicy::Explicit,
+            ack_wait: Duration::from_secs(self.ack_wait_secs),
+            filter_subject: subject.to_owned(),
+            max_ack_pending: self.max_ack_pending,
+            ..Default::default()
+        })
+    }
 }
 
 inventory::submit! {
@@ -62,16 +180,31 @@ impl GenerateConfig for NatsSourceConfig {
 #[typetag::serde(name = "nats")]
 impl SourceConfig for NatsSourceConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
-        let (connection, subscription) = create_subscription(self).await?;
         let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build()?;
 
-        Ok(Box::pin(nats_source(
-            connection,
-            subscription,
-            decoder,
-            cx.shutdown,
-            cx.out,
-        )))
+        match &self.jetstream {
+            None => {
+                let (connection, subscription) = create_subscription(self).await?;
+                Ok(Box::pin(nats_source(
+                    connection,
+                    subscription,
+                    decoder,
+                    cx.shutdown,
+                    cx.out,
+                )))
+            }
+            Some(jetstream) => {
+                let subscription = create_pull_subscription(self, jetstream).await?;
+                Ok(Box::pin(jetstream_source(
+                    subscription,
+                    jetstream.batch_size,
+                    decoder,
+                    self.acknowledgements.enabled,
+                    cx.shutdown,
+                    cx.out,
+                )))
+            }
+        }
     }
 
     fn output_type(&self) -> DataType {
@@ -84,33 +217,17 @@ impl SourceConfig for NatsSourceConfig {
 }
 
 impl NatsSourceConfig {
-    fn to_nats_options(&self) -> async_nats::Options {
-        // Set reconnect_buffer_size on the nats client to 0 bytes so that the
-        // client doesn't buffer internally (to avoid message loss).
-        async_nats::Options::new()
-            .with_name(&self.connection_name)
-            .reconnect_buffer_size(0)
-    }
-
-    async fn connect(&self) -> crate::Result<async_nats::Connection> {
-        self.to_nats_options()
-            .connect(&self.url)
-            .await
-            .map_err(|e| e.into())
-    }
-}
-
-impl From<NatsSourceConfig> for async_nats::Options {
-    fn from(config: NatsSourceConfig) -> Self {
-        async_nats::Options::new()
-            .with_name(&config.connection_name)
-            .reconnect_buffer_size(0)
+    async fn connect(&self) -> crate::Result<nats::asynk::Connection> {
+        let options: nats::asynk::Options =
+            nats_options(&self.connection_name, self.auth.as_ref(), self.tls.as_ref())
+                .context(NatsOptionsError)?;
+        options.connect(&self.url).await.map_err(|e| e.into())
     }
 }
 
 fn get_subscription_stream(
-    subscription: async_nats::Subscription,
-) -> impl Stream<Item = async_nats::Message> {
+    subscription: nats::asynk::Subscription,
+) -> impl Stream<Item = nats::asynk::Message> {
     stream::unfold(subscription, |subscription| async move {
         subscription.next().await.map(|msg| (msg, subscription))
     })
@@ -118,8 +235,8 @@ fn get_subscription_stream(
 
 async fn nats_source(
     // Take ownership of the connection so it doesn't get dropped.
-    _connection: async_nats::Connection,
-    subscription: async_nats::Subscription,
+    _connection: nats::asynk::Connection,
+    subscription: nats::asynk::Subscription,
     decoder: codecs::Decoder,
     shutdown: ShutdownSignal,
     mut out: Pipeline,
@@ -127,36 +244,138 @@ async fn nats_source(
     let stream = get_subscription_stream(subscription).take_until(shutdown);
     pin_mut!(stream);
     while let Some(msg) = stream.next().await {
-        let mut stream = FramedRead::new(msg.data.as_ref(), decoder.clone());
-        while let Some(next) = stream.next().await {
-            match next {
-                Ok((events, byte_size)) => {
-                    emit!(&NatsEventsReceived {
-                        byte_size,
-                        count: events.len()
-                    });
-
-                    let now = Utc::now();
-
-                    for mut event in events {
-                        if let Event::Log(ref mut log) = event {
-                            log.try_insert(log_schema().source_type_key(), Bytes::from("nats"));
-                            log.try_insert(log_schema().timestamp_key(), now);
-                        }
-
-                        out.send(event)
-                            .await
-                            .map_err(|error: crate::pipeline::ClosedError| {
-                                error!(message = "Error sending to sink.", %error);
-                            })?;
+        handle_message(&msg.data, Utc::now(), &decoder, &None, &mut out).await?;
+    }
+    Ok(())
+}
+
+async fn jetstream_source(
+    subscription: PullSubscription,
+    batch_size: usize,
+    decoder: codecs::Decoder,
+    acknowledgements: bool,
+    shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let shutdown = shutdown.shared();
+    let finalizer = if acknowledgements {
+        Some(OrderedFinalizer::with_status(
+            shutdown.clone(),
+            |status, message: nats::Message| {
+                // Errored events are redelivered right away, while events
+                // rejected by a sink would fail again and are not redelivered.
+                let kind = match status {
+                    BatchStatus::Delivered => AckKind::Ack,
+                    BatchStatus::Errored => AckKind::Nak,
+                    BatchStatus::Failed => AckKind::Term,
+                };
+                tokio::spawn(ack(message, kind));
+            },
+        ))
+    } else {
+        None
+    };
+
+    while shutdown.clone().now_or_never().is_none() {
+        let fetched = {
+            let subscription = subscription.clone();
+            tokio::task::spawn_blocking(move || fetch(&subscription, batch_size)).await
+        };
+        let messages = match fetched {
+            Ok(Ok(messages)) => messages,
+            Ok(Err(error)) => {
+                emit!(&NatsFetchFailed { error });
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            Err(error) => {
+                error!(message = "Fetching from JetStream panicked.", %error);
+                return Err(());
+            }
+        };
+
+        for message in messages {
+            let timestamp = message
+                .jetstream_message_info()
+                .map(|info| info.published)
+                .unwrap_or_else(Utc::now);
+
+            let (batch, receiver) = BatchNotifier::maybe_new_with_receiver(finalizer.is_some());
+            handle_message(&message.data, timestamp, &decoder, &batch, &mut out).await?;
+            drop(batch);
+
+            match (&finalizer, receiver) {
+                (Some(finalizer), Some(receiver)) => finalizer.add(message, receiver),
+                _ => ack(message, AckKind::Ack).await,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Request a batch of messages. Fewer messages are returned when the
+/// request expires before the batch could be filled.
+fn fetch(subscription: &PullSubscription, batch: usize) -> io::Result<Vec<nats::Message>> {
+    let options = BatchOptions {
+        batch,
+        expires: Some(FETCH_TIMEOUT.as_nanos() as usize),
+        no_wait: false,
+    };
+    // The iterator yields an error once no further message arrives in time,
+    // or when the server reports the request as expired.
+    Ok(subscription
+        .timeout_fetch(options, FETCH_TIMEOUT)?
+        .take_while(Result::is_ok)
+        .flatten()
+        .collect())
+}
+
+/// Acknowledges `message` on a blocking thread, as the synchronous client
+/// blocks while publishing the acknowledgement.
+async fn ack(message: nats::Message, kind: AckKind) {
+    let result = tokio::task::spawn_blocking(move || message.ack_kind(kind))
+        .await
+        .unwrap_or_else(|error| Err(io::Error::new(io::ErrorKind::Other, error)));
+    if let Err(error) = result {
+        emit!(&NatsAckFailed { error });
+    }
+}
+
+async fn handle_message(
+    data: &[u8],
+    timestamp: DateTime<Utc>,
+    decoder: &codecs::Decoder,
+    batch: &Option<Arc<BatchNotifier>>,
+    out: &mut Pipeline,
+) -> Result<(), ()> {
+    let mut stream = FramedRead::new(data, decoder.clone());
+    while let Some(next) = stream.next().await {
+        match next {
+            Ok((events, byte_size)) => {
+                emit!(&NatsEventsReceived {
+                    byte_size,
+                    count: events.len()
+                });
+
+                for mut event in events {
+                    if let Event::Log(ref mut log) = event {
+                        log.try_insert(log_schema().source_type_key(), Bytes::from("nats"));
+                        log.try_insert(log_schema().timestamp_key(), timestamp);
                     }
+
+                    out.send(event.with_batch_notifier_option(batch))
+                        .await
+                        .map_err(|error: crate::pipeline::ClosedError| {
+                            error!(message = "Error sending to sink.", %error);
+                        })?;
                 }
-                Err(error) => {
-                    // Error is logged by `crate::codecs::Decoder`, no further
-                    // handling is needed here.
-                    if !error.can_continue() {
-                        break;
-                    }
+            }
+            Err(error) => {
+                // Error is logged by `crate::codecs::Decoder`, no further
+                // handling is needed here.
+                if !error.can_continue() {
+                    break;
                 }
             }
         }
@@ -166,7 +385,7 @@ async fn nats_source(
 
 async fn create_subscription(
     config: &NatsSourceConfig,
-) -> crate::Result<(async_nats::Connection, async_nats::Subscription)> {
+) -> crate::Result<(nats::asynk::Connection, nats::asynk::Subscription)> {
     let nc = config.connect().await?;
 
     let subscription = match &config.queue {
@@ -179,16 +398,111 @@ async fn create_subscription(
     Ok((nc, subscription))
 }
 
+/// Bind to the durable consumer, creating it on the first start.
+async fn create_pull_subscription(
+    config: &NatsSourceConfig,
+    jetstream: &JetStreamConfig,
+) -> crate::Result<PullSubscription> {
+    if config.queue.is_some() {
+        return Err(BuildError::QueueWithJetStream.into());
+    }
+    let consumer_config = jetstream.consumer_config(&config.subject)?;
+    let options: nats::Options = nats_options(
+        &config.connection_name,
+        config.auth.as_ref(),
+        config.tls.as_ref(),
+    )
+    .context(NatsOptionsError)?;
+    let url = config.url.clone();
+    let subject = config.subject.clone();
+    let jetstream = jetstream.clone();
+
+    // The JetStream API of `nats` is only available on the blocking client.
+    tokio::task::spawn_blocking(move || {
+        let connection = options.connect(&url).context(NatsCreateError)?;
+        let context = JetStream::new(connection, JetStreamOptions::new());
+
+        if context
+            .consumer_info(&jetstream.stream, &jetstream.durable_name)
+            .is_err()
+        {
+            context
+                .add_consumer(&jetstream.stream, consumer_config)
+                .context(NatsSubscribeError)?;
+        }
+
+        let options = PullSubscibeOptions::new()
+            .bind_stream(jetstream.stream)
+            .durable_name(jetstream.durable_name);
+        context
+            .pull_subscribe_with_options(&subject, &options)
+            .context(NatsSubscribeError)
+    })
+    .await?
+    .map_err(Into::into)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::print_stdout)] //tests
 
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
     fn generate_config() {
         crate::test_util::test_generate_config::<NatsSourceConfig>();
     }
+
+    #[test]
+    fn parses_jetstream_config() {
+        let config: NatsSourceConfig = toml::from_str(
+            r#"
+            url = "nats://127.0.0.1:4222"
+            connection_name = "vector"
+            subject = "logs.>"
+            acknowledgements = true
+            auth.strategy = "credentials_file"
+            auth.path = "/etc/nats/vector.creds"
+            jetstream.stream = "LOGS"
+            jetstream.deliver_policy = "by_start_time"
+            jetstream.start_time = "2021-11-01T00:00:00Z"
+            "#,
+        )
+        .unwrap();
+
+        let jetstream = config.jetstream.unwrap();
+        assert_eq!(jetstream.durable_name, "vector");
+        let consumer = jetstream.consumer_config("logs.>").unwrap();
+        assert_eq!(consumer.deliver_policy, DeliverPolicy::ByStartTime);
+        assert_eq!(
+            consumer.opt_start_time,
+            Some(Utc.ymd(2021, 11, 1).and_hms(0, 0, 0))
+        );
+        assert_eq!(consumer.ack_policy, AckPolicy::Explicit);
+        assert_eq!(consumer.filter_subject, "logs.>");
+    }
+
+    #[test]
+    fn start_sequence_is_required() {
+        let jetstream = JetStreamConfig {
+            stream: "LOGS".into(),
+            deliver_policy: JetStreamDeliverPolicy::ByStartSequence,
+            ..Default::default()
+        };
+        assert!(matches!(
+            jetstream.consumer_config("logs"),
+            Err(BuildError::MissingStartSequence)
+        ));
+
+        let jetstream = JetStreamConfig {
+            start_sequence: Some(42),
+            ..jetstream
+        };
+        let consumer = jetstream.consumer_config("logs").unwrap();
+        assert_eq!(consumer.deliver_policy, DeliverPolicy::ByStartSeq);
+        assert_eq!(consumer.opt_start_seq, Some(42));
+    }
 }
 
 #[cfg(feature = "nats-integration-tests")]
@@ -197,7 +511,10 @@ mod integration_tests {
     #![allow(clippy::print_stdout)] //tests
 
     use super::*;
-    use crate::test_util::{collect_n, random_string};
+    use crate::test_util::{collect_n, random_string, trace_init};
+    use vector_core::event::EventStatus;
+
+    const NATS_URL: &str = "nats://127.0.0.1:4222";
 
     #[tokio::test]
     async fn nats_happy() {
@@ -206,10 +523,8 @@ mod integration_tests {
         let conf = NatsSourceConfig {
             connection_name: "".to_owned(),
             subject: subject.clone(),
-            url: "nats://127.0.0.1:4222".to_owned(),
-            queue: None,
-            framing: default_framing_message_based(),
-            decoding: default_decoding(),
+            url: NATS_URL.to_owned(),
+            ..Default::default()
         };
 
         let (nc, sub) = create_subscription(&conf).await.unwrap();
@@ -227,4 +542,120 @@ mod integration_tests {
         println!("Received event  {:?}", events[0].as_log());
         assert_eq!(events[0].as_log()[log_schema().message_key()], msg.into());
     }
+
+    /// Create a stream capturing its own name as subject, holding `count` messages.
+    async fn create_stream(count: usize) -> (String, JetStream) {
+        let stream = format!("test-{}", random_string(10));
+        let name = stream.clone();
+        let context = tokio::task::spawn_blocking(move || {
+            let context = nats::jetstream::new(nats::connect(NATS_URL).unwrap());
+            context.add_stream(name.as_str()).unwrap();
+            for i in 0..count {
+                context.publish(&name, format!("message {}", i)).unwrap();
+            }
+            context
+        })
+        .await
+        .unwrap();
+        (stream, context)
+    }
+
+    async fn num_ack_pending(context: &JetStream, stream: &str) -> usize {
+        let context = context.clone();
+        let stream = stream.to_owned();
+        tokio::task::spawn_blocking(move || context.consumer_info(&stream, "vector").unwrap())
+            .await
+            .unwrap()
+            .num_ack_pending
+    }
+
+    fn make_config(stream: &str, deliver_policy: JetStreamDeliverPolicy) -> NatsSourceConfig {
+        NatsSourceConfig {
+            url: NATS_URL.to_owned(),
+            connection_name: "vector".to_owned(),
+            subject: stream.to_owned(),
+            jetstream: Some(JetStreamConfig {
+                stream: stream.to_owned(),
+                deliver_policy,
+                start_sequence: Some(3),
+                ack_wait_secs: 1,
+                ..Default::default()
+            }),
+            acknowledgements: true.into(),
+            ..Default::default()
+        }
+    }
+
+    async fn consume(config: &NatsSourceConfig, status: EventStatus, count: usize) -> Vec<Event> {
+        let jetstream = config.jetstream.as_ref().unwrap();
+        let subscription = create_pull_subscription(config, jetstream).await.unwrap();
+        let decoder = DecodingConfig::new(config.framing.clone(), config.decoding.clone())
+            .build()
+            .unwrap();
+        let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+        let (tx, rx) = Pipeline::new_test_finalize(status);
+        let source = tokio::spawn(jetstream_source(
+            subscription,
+            jetstream.batch_size,
+            decoder,
+            true,
+            shutdown,
+            tx,
+        ));
+
+        let events = collect_n(rx, count).await;
+        // Give the finalizer a moment to acknowledge the messages.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        drop(trigger);
+        // Messages redelivered in the meantime can't be sent anymore, which
+        // ends the source with an error.
+        let _ = source.await.unwrap();
+        events
+    }
+
+    #[tokio::test]
+    async fn jetstream_acks_delivered_messages() {
+        trace_init();
+
+        let (stream, context) = create_stream(5).await;
+        let config = make_config(&stream, JetStreamDeliverPolicy::All);
+
+        let events = consume(&config, EventStatus::Delivered, 5).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "message 0".into()
+        );
+        assert_eq!(num_ack_pending(&context, &stream).await, 0);
+    }
+
+    #[tokio::test]
+    async fn jetstream_redelivers_errored_messages() {
+        trace_init();
+
+        let (stream, _context) = create_stream(2).await;
+        let config = make_config(&stream, JetStreamDeliverPolicy::All);
+
+        consume(&config, EventStatus::Errored, 2).await;
+        let events = consume(&config, EventStatus::Delivered, 2).await;
+        let mut messages = events
+            .iter()
+            .map(|event| event.as_log()[log_schema().message_key()].to_string_lossy())
+            .collect::<Vec<_>>();
+        messages.sort();
+        assert_eq!(messages, vec!["message 0", "message 1"]);
+    }
+
+    #[tokio::test]
+    async fn jetstream_replays_from_sequence() {
+        trace_init();
+
+        let (stream, _context) = create_stream(5).await;
+        let config = make_config(&stream, JetStreamDeliverPolicy::ByStartSequence);
+
+        let events = consume(&config, EventStatus::Delivered, 3).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "message 2".into()
+        );
+    }
 }
@@ -0,0 +1,303 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, Resource, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{Event, LogEvent, Value},
+    internal_events::{NetflowEventsReceived, NetflowParseError, NetflowTemplatesMissing},
+    shutdown::ShutdownSignal,
+    sources::util::bind_udp_socket,
+    Pipeline,
+};
+use bytes::{Bytes, BytesMut};
+use chrono::Utc;
+use futures::SinkExt;
+use netflow_parser::{
+    flow_message::{FlowPacket, FlowValue},
+    flow_parser::{FlowParser, DEFAULT_MAX_TEMPLATES},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NetflowConfig {
+    address: SocketAddr,
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+    host_key: Option<String>,
+    receive_buffer_bytes: Option<usize>,
+    #[serde(default = "default_max_templates")]
+    max_templates: usize,
+    #[serde(default = "default_template_expiry_secs")]
+    template_expiry_secs: u64,
+}
+
+/// Flow packets are limited to the size of a UDP datagram.
+const fn default_max_length() -> usize {
+    65_535
+}
+
+const fn default_max_templates() -> usize {
+    DEFAULT_MAX_TEMPLATES
+}
+
+/// The default template lifetime of RFC 7011 for UDP.
+const fn default_template_expiry_secs() -> u64 {
+    1800
+}
+
+inventory::submit! {
+    SourceDescription::new::<NetflowConfig>("netflow")
+}
+
+impl GenerateConfig for NetflowConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(r#"address = "0.0.0.0:2055""#).unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "netflow")]
+impl SourceConfig for NetflowConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.max_templates == 0 {
+            return Err("`max_templates` must be greater than zero.".into());
+        }
+
+        let host_key = self
+            .host_key
+            .clone()
+            .unwrap_or_else(|| log_schema().host_key().to_string());
+
+        Ok(Box::pin(netflow_source(
+            self.clone(),
+            host_key,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "netflow"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::udp(self.address)]
+    }
+}
+
+async fn netflow_source(
+    config: NetflowConfig,
+    host_key: String,
+    mut shutdown: ShutdownSignal,
+    out: Pipeline,
+) -> Result<(), ()> {
+    let mut out = out.sink_map_err(|error| error!(message = "Error sending event.", %error));
+
+    let socket = bind_udp_socket(config.address, config.receive_buffer_bytes)
+        .await
+        .map_err(|error| {
+            error!(message = "Failed to bind to UDP listener socket.", %error);
+        })?;
+
+    info!(message = "Listening.", address = %config.address);
+
+    // Templates of NetFlow v9 and IPFIX are only sent every so often, so a
+    // single parser keeps them for all exporters.
+    let mut parser = FlowParser::with_template_limits(
+        config.max_templates,
+        Some(Duration::from_secs(config.template_expiry_secs)),
+    );
+    let mut buf = BytesMut::with_capacity(config.max_length);
+    loop {
+        buf.resize(config.max_length, 0);
+        let (byte_size, exporter) = tokio::select! {
+            recv = socket.recv_from(&mut buf) => match recv {
+                Ok(recv) => recv,
+                Err(error) => {
+                    error!(message = "Failed to read UDP datagram.", %error);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => return Ok(()),
+        };
+
+        let packet = match parser.parse(exporter, &buf[..byte_size]) {
+            Ok(packet) => packet,
+            Err(error) => {
+                emit!(&NetflowParseError {
+                    error: &error,
+                    exporter,
+                });
+                continue;
+            }
+        };
+
+        if !packet.missing_templates.is_empty() {
+            emit!(&NetflowTemplatesMissing {
+                template_ids: &packet.missing_templates,
+                exporter,
+            });
+        }
+
+        let events = flow_events(&packet, exporter.ip(), &host_key);
+        emit!(&NetflowEventsReceived {
+            byte_size,
+            count: events.len(),
+        });
+
+        for event in events {
+            tokio::select! {
+                result = out.send(event) => if result.is_err() {
+                    return Ok(());
+                },
+                _ = &mut shutdown => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Create an event per flow record, each carrying the fields of the packet
+/// header as well.
+fn flow_events(packet: &FlowPacket, exporter: IpAddr, host_key: &str) -> Vec<Event> {
+    let timestamp = packet.export_time().unwrap_or_else(Utc::now);
+
+    packet
+        .records
+        .iter()
+        .map(|record| {
+            let mut log = LogEvent::default();
+            for (name, value) in packet.header.iter().chain(record) {
+                log.insert(name.as_str(), flow_value(value));
+            }
+            log.insert("flow_type", packet.protocol.as_str());
+            log.try_insert(log_schema().source_type_key(), Bytes::from("netflow"));
+            log.try_insert(log_schema().timestamp_key(), timestamp);
+            log.try_insert(host_key, exporter.to_string());
+            Event::Log(log)
+        })
+        .collect()
+}
+
+fn flow_value(value: &FlowValue) -> Value {
+    match value {
+        // Counters can exceed the range of an integer, those are kept exact
+        // as strings.
+        FlowValue::Unsigned(value) => i64::try_from(*value)
+            .map(Value::Integer)
+            .unwrap_or_else(|_| value.to_string().into()),
+        FlowValue::Float(value) => Value::Float(*value),
+        FlowValue::Ip(ip) => ip.to_string().into(),
+        FlowValue::Mac(mac) => mac
+            .iter()
+            .map(|octet| format!("{:02x}", octet))
+            .collect::<Vec<_>>()
+            .join(":")
+            .into(),
+        FlowValue::String(value) => value.clone().into(),
+        FlowValue::Bytes(bytes) => bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+            .into(),
+        FlowValue::DateTime(timestamp) => Value::Timestamp(*timestamp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_ready, next_addr, trace_init};
+    use chrono::TimeZone;
+    use std::time::Duration;
+    use tokio::net::UdpSocket;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<NetflowConfig>();
+    }
+
+    fn netflow_v5_packet() -> Vec<u8> {
+        let mut data = vec![
+            0, 5, 0, 1, // version, count
+            0, 0, 0x03, 0xe8, // sys_up_time
+            0x61, 0x7f, 0xa8, 0x00, // export_time
+            0, 0, 0, 0, // nanoseconds
+            0, 0, 0, 42, // sequence_number
+            0, 0, 0, 0, // engine, sampling
+        ];
+        data.extend(&[
+            10, 0, 0, 1, 10, 0, 0, 2, 0, 0, 0, 0, // addresses, next hop
+            0, 1, 0, 2, // interfaces
+            0, 0, 0, 10, 0, 0, 0x05, 0xdc, // packets, octets
+            0, 0, 0, 0, 0, 0, 0x03, 0xe8, // first, last
+            0x30, 0x39, 0, 80, // ports
+            0, 0x1b, 6, 0, // pad, tcp flags, protocol, tos
+            0, 0, 0, 0, 24, 24, 0, 0, // AS, masks, pad
+        ]);
+        data
+    }
+
+    #[test]
+    fn converts_flow_values() {
+        assert_eq!(flow_value(&FlowValue::Unsigned(42)), Value::Integer(42));
+        assert_eq!(
+            flow_value(&FlowValue::Unsigned(u64::MAX)),
+            Value::from("18446744073709551615")
+        );
+        assert_eq!(
+            flow_value(&FlowValue::Mac([0, 0x1b, 0x21, 0x3c, 0x4d, 0x5e])),
+            Value::from("00:1b:21:3c:4d:5e")
+        );
+        assert_eq!(
+            flow_value(&FlowValue::Bytes(vec![0xde, 0xad])),
+            Value::from("dead")
+        );
+    }
+
+    #[tokio::test]
+    async fn receives_netflow_v5() {
+        trace_init();
+
+        let address = next_addr();
+        let config = NetflowConfig {
+            address,
+            max_length: default_max_length(),
+            host_key: None,
+            receive_buffer_bytes: None,
+            max_templates: default_max_templates(),
+            template_expiry_secs: default_template_expiry_secs(),
+        };
+        let (tx, rx) = Pipeline::new_test();
+        let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+        tokio::spawn(netflow_source(config, "host".into(), shutdown, tx));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.send_to(&netflow_v5_packet(), address).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(trigger);
+
+        let events = collect_ready(rx).await;
+        assert_eq!(events.len(), 1);
+        let log = events[0].as_log();
+        assert_eq!(log["flow_type"], "netflow_v5".into());
+        assert_eq!(log["sequence_number"], 42.into());
+        assert_eq!(log["source_ipv4_address"], "10.0.0.1".into());
+        assert_eq!(log["destination_transport_port"], 80.into());
+        assert_eq!(log["host"], "127.0.0.1".into());
+        assert_eq!(log[log_schema().source_type_key()], "netflow".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Value::Timestamp(Utc.timestamp(0x617f_a800, 0))
+        );
+    }
+}
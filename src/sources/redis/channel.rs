@@ -0,0 +1,66 @@
+use super::InputHandler;
+use crate::{internal_events::RedisReceiveEventFailed, shutdown::ShutdownSignal};
+use chrono::Utc;
+use futures::StreamExt;
+use redis::{aio::PubSub, Client, RedisResult};
+use std::time::Duration;
+
+pub(super) async fn subscribe(
+    client: Client,
+    key: String,
+    pattern: bool,
+    mut handler: InputHandler,
+    mut shutdown: ShutdownSignal,
+) -> Result<(), ()> {
+    loop {
+        let mut pubsub = match connect(&client, &key, pattern).await {
+            Ok(pubsub) => pubsub,
+            Err(error) => {
+                emit!(&RedisReceiveEventFailed { error });
+                tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = tokio::time::sleep(Duration::from_secs(1)) => continue,
+                }
+            }
+        };
+
+        let mut messages = pubsub.on_message();
+        loop {
+            let message = tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                message = messages.next() => message,
+            };
+
+            match message {
+                Some(message) => {
+                    handler
+                        .handle(
+                            message.get_channel_name(),
+                            message.get_payload_bytes(),
+                            Utc::now(),
+                            &None,
+                        )
+                        .await?;
+                }
+                // The connection was lost. Messages published until we
+                // have resubscribed are missed, as pub/sub has no backlog.
+                None => {
+                    warn!(message = "Redis subscription connection closed, reconnecting.");
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn connect(client: &Client, key: &str, pattern: bool) -> RedisResult<PubSub> {
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    if pattern {
+        pubsub.psubscribe(key).await?;
+    } else {
+        pubsub.subscribe(key).await?;
+    }
+    Ok(pubsub)
+}
@@ -0,0 +1,41 @@
+use super::{InputHandler, Method};
+use crate::{internal_events::RedisReceiveEventFailed, shutdown::ShutdownSignal};
+use chrono::Utc;
+use futures::FutureExt;
+use redis::{aio::ConnectionManager, AsyncCommands, RedisResult};
+use std::time::Duration;
+
+/// Seconds a blocking pop waits for an element, which bounds how long
+/// shutdown can be delayed.
+const POP_TIMEOUT_SECS: usize = 1;
+
+pub(super) async fn watch(
+    mut connection: ConnectionManager,
+    key: String,
+    method: Method,
+    mut handler: InputHandler,
+    mut shutdown: ShutdownSignal,
+) -> Result<(), ()> {
+    // A pop can't be cancelled without losing the element it may have
+    // already removed, so shutdown is only checked between pops.
+    while (&mut shutdown).now_or_never().is_none() {
+        let result: RedisResult<Option<(String, Vec<u8>)>> = match method {
+            Method::LPop => connection.blpop(&key, POP_TIMEOUT_SECS).await,
+            Method::RPop => connection.brpop(&key, POP_TIMEOUT_SECS).await,
+        };
+
+        match result {
+            Ok(Some((key, payload))) => {
+                handler.handle(&key, &payload, Utc::now(), &None).await?;
+            }
+            // The pop timed out without an element.
+            Ok(None) => {}
+            Err(error) => {
+                emit!(&RedisReceiveEventFailed { error });
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,440 @@
+use crate::{
+    codecs::{self, DecodingConfig, FramingConfig, ParserConfig},
+    config::{
+        log_schema, AcknowledgementsConfig, DataType, GenerateConfig, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{BatchNotifier, Event},
+    internal_events::RedisEventsReceived,
+    serde::{bool_or_struct, default_decoding, default_framing_message_based},
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use redis::RedisError;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::sync::Arc;
+use tokio_util::codec::FramedRead;
+
+mod channel;
+mod list;
+mod stream;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Failed to create Redis client: {}", source))]
+    Client { source: RedisError },
+    #[snafu(display("Failed to connect to Redis: {}", source))]
+    Connection { source: RedisError },
+    #[snafu(display("Failed to create Redis consumer group: {}", source))]
+    ConsumerGroup { source: RedisError },
+}
+
+#[derive(Copy, Clone, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DataTypeConfig {
+    #[derivative(Default)]
+    List,
+    Channel,
+    Stream,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ListOption {
+    #[serde(default)]
+    method: Method,
+}
+
+#[derive(Copy, Clone, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    #[derivative(Default)]
+    LPop,
+    RPop,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelOption {
+    #[serde(default)]
+    pattern: bool,
+}
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct StreamOption {
+    #[serde(default = "default_group")]
+    #[derivative(Default(value = "default_group()"))]
+    group: String,
+    consumer: Option<String>,
+    #[serde(default = "default_start_id")]
+    #[derivative(Default(value = "default_start_id()"))]
+    start_id: String,
+    #[serde(default = "default_field")]
+    #[derivative(Default(value = "default_field()"))]
+    field: String,
+    #[serde(default = "default_count")]
+    #[derivative(Default(value = "default_count()"))]
+    count: usize,
+}
+
+fn default_group() -> String {
+    "vector".into()
+}
+
+fn default_start_id() -> String {
+    "$".into()
+}
+
+fn default_field() -> String {
+    "message".into()
+}
+
+const fn default_count() -> usize {
+    100
+}
+
+impl StreamOption {
+    /// Consumers are identified by the host name by default, so that each
+    /// instance in a consumer group reads its own share of the stream.
+    fn consumer(&self) -> String {
+        self.consumer
+            .clone()
+            .or_else(|| crate::get_hostname().ok())
+            .unwrap_or_else(|| "vector".into())
+    }
+}
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct RedisSourceConfig {
+    #[serde(default)]
+    data_type: DataTypeConfig,
+    #[serde(alias = "list")]
+    list_option: Option<ListOption>,
+    #[serde(alias = "channel")]
+    channel_option: Option<ChannelOption>,
+    #[serde(alias = "stream")]
+    stream_option: Option<StreamOption>,
+    url: String,
+    key: String,
+    redis_key: Option<String>,
+    #[serde(default = "default_framing_message_based")]
+    #[derivative(Default(value = "default_framing_message_based()"))]
+    framing: Box<dyn FramingConfig>,
+    #[serde(default = "default_decoding")]
+    #[derivative(Default(value = "default_decoding()"))]
+    decoding: Box<dyn ParserConfig>,
+    #[serde(default, deserialize_with = "bool_or_struct")]
+    acknowledgements: AcknowledgementsConfig,
+}
+
+inventory::submit! {
+    SourceDescription::new::<RedisSourceConfig>("redis")
+}
+
+impl GenerateConfig for RedisSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            url = "redis://127.0.0.1:6379/0"
+            key = "vector"
+            data_type = "list"
+            list.method = "lpop"
+            "#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "redis")]
+impl SourceConfig for RedisSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.key.is_empty() {
+            return Err("`key` cannot be empty.".into());
+        }
+
+        let client = redis::Client::open(self.url.as_str()).context(Client)?;
+        let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build()?;
+        let handler = InputHandler {
+            redis_key: self.redis_key.clone(),
+            decoder,
+            out: cx.out,
+        };
+
+        match self.data_type {
+            DataTypeConfig::List => {
+                let connection = client
+                    .get_tokio_connection_manager()
+                    .await
+                    .context(Connection)?;
+                let method = self.list_option.unwrap_or_default().method;
+                Ok(Box::pin(list::watch(
+                    connection,
+                    self.key.clone(),
+                    method,
+                    handler,
+                    cx.shutdown,
+                )))
+            }
+            DataTypeConfig::Channel => {
+                let pattern = self.channel_option.unwrap_or_default().pattern;
+                Ok(Box::pin(channel::subscribe(
+                    client,
+                    self.key.clone(),
+                    pattern,
+                    handler,
+                    cx.shutdown,
+                )))
+            }
+            DataTypeConfig::Stream => {
+                let options = self.stream_option.clone().unwrap_or_default();
+                let mut connection = client
+                    .get_tokio_connection_manager()
+                    .await
+                    .context(Connection)?;
+                stream::create_group(&mut connection, &self.key, &options)
+                    .await
+                    .context(ConsumerGroup)?;
+                // Blocking reads hold up every other command on their
+                // connection, so acknowledgements are sent on a second one.
+                let ack_connection = client
+                    .get_tokio_connection_manager()
+                    .await
+                    .context(Connection)?;
+                Ok(Box::pin(stream::consume(
+                    connection,
+                    ack_connection,
+                    self.key.clone(),
+                    options,
+                    handler,
+                    cx.shutdown,
+                    self.acknowledgements.enabled,
+                )))
+            }
+        }
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "redis"
+    }
+}
+
+/// Decodes payloads read by each of the data types and forwards the
+/// resulting events.
+struct InputHandler {
+    redis_key: Option<String>,
+    decoder: codecs::Decoder,
+    out: Pipeline,
+}
+
+impl InputHandler {
+    async fn handle(
+        &mut self,
+        key: &str,
+        payload: &[u8],
+        timestamp: DateTime<Utc>,
+        batch: &Option<Arc<BatchNotifier>>,
+    ) -> Result<(), ()> {
+        let mut frames = FramedRead::new(payload, self.decoder.clone());
+        while let Some(next) = frames.next().await {
+            match next {
+                Ok((events, byte_size)) => {
+                    emit!(&RedisEventsReceived {
+                        byte_size,
+                        count: events.len()
+                    });
+
+                    for mut event in events {
+                        if let Event::Log(ref mut log) = event {
+                            log.try_insert(log_schema().source_type_key(), Bytes::from("redis"));
+                            log.try_insert(log_schema().timestamp_key(), timestamp);
+                            if let Some(redis_key) = &self.redis_key {
+                                log.try_insert(redis_key.as_str(), key.to_owned());
+                            }
+                        }
+
+                        self.out
+                            .send(event.with_batch_notifier_option(batch))
+                            .await
+                            .map_err(|error: crate::pipeline::ClosedError| {
+                                error!(message = "Error sending to sink.", %error);
+                            })?;
+                    }
+                }
+                Err(error) => {
+                    // Error is logged by `crate::codecs::Decoder`, no further
+                    // handling is needed here.
+                    if !error.can_continue() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<RedisSourceConfig>();
+    }
+
+    #[test]
+    fn parses_stream_config() {
+        let config: RedisSourceConfig = toml::from_str(
+            r#"
+            url = "redis://127.0.0.1:6379/0"
+            key = "events"
+            data_type = "stream"
+            stream.group = "processors"
+            stream.consumer = "worker-1"
+            acknowledgements = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.data_type, DataTypeConfig::Stream);
+        let options = config.stream_option.unwrap();
+        assert_eq!(options.group, "processors");
+        assert_eq!(options.consumer(), "worker-1");
+        assert_eq!(options.start_id, "$");
+        assert_eq!(options.field, "message");
+        assert!(config.acknowledgements.enabled);
+    }
+
+    #[test]
+    fn parses_channel_config() {
+        let config: RedisSourceConfig = toml::from_str(
+            r#"
+            url = "redis://127.0.0.1:6379/0"
+            key = "logs.*"
+            data_type = "channel"
+            channel.pattern = true
+            redis_key = "channel"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.data_type, DataTypeConfig::Channel);
+        assert!(config.channel_option.unwrap().pattern);
+        assert_eq!(config.redis_key.as_deref(), Some("channel"));
+    }
+}
+
+#[cfg(feature = "redis-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::{
+        shutdown::ShutdownSignal,
+        test_util::{collect_n, random_string, trace_init},
+    };
+    use redis::AsyncCommands;
+
+    const REDIS_SERVER: &str = "redis://127.0.0.1:6379/0";
+
+    pub(super) async fn connection() -> redis::aio::ConnectionManager {
+        redis::Client::open(REDIS_SERVER)
+            .unwrap()
+            .get_tokio_connection_manager()
+            .await
+            .unwrap()
+    }
+
+    async fn run_source(config: RedisSourceConfig, count: usize) -> Vec<Event> {
+        let (tx, rx) = Pipeline::new_test();
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        collect_n(rx, count).await
+    }
+
+    #[tokio::test]
+    async fn redis_source_list_lpop() {
+        trace_init();
+
+        let key = format!("test-{}", random_string(10));
+        let mut conn = connection().await;
+        let _: () = conn.rpush(&key, &["first", "second"]).await.unwrap();
+
+        let config = RedisSourceConfig {
+            url: REDIS_SERVER.to_owned(),
+            key: key.clone(),
+            redis_key: Some("redis_key".into()),
+            ..Default::default()
+        };
+        let events = run_source(config, 2).await;
+
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "first".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "second".into()
+        );
+        assert_eq!(events[0].as_log()["redis_key"], key.into());
+        assert_eq!(
+            events[0].as_log()[log_schema().source_type_key()],
+            "redis".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn redis_source_channel() {
+        trace_init();
+
+        let key = format!("test-{}", random_string(10));
+        let config = RedisSourceConfig {
+            url: REDIS_SERVER.to_owned(),
+            key: format!("{}.*", key),
+            data_type: DataTypeConfig::Channel,
+            channel_option: Some(ChannelOption { pattern: true }),
+            redis_key: Some("channel".into()),
+            ..Default::default()
+        };
+        let (tx, rx) = Pipeline::new_test();
+        tokio::spawn(channel::subscribe(
+            redis::Client::open(REDIS_SERVER).unwrap(),
+            config.key.clone(),
+            true,
+            InputHandler {
+                redis_key: config.redis_key.clone(),
+                decoder: DecodingConfig::new(config.framing.clone(), config.decoding.clone())
+                    .build()
+                    .unwrap(),
+                out: tx,
+            },
+            ShutdownSignal::noop(),
+        ));
+
+        // Give the source time to subscribe.
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+        let channel = format!("{}.app", key);
+        let mut conn = connection().await;
+        let _: () = conn.publish(&channel, "my message").await.unwrap();
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "my message".into());
+        assert_eq!(log["channel"], channel.into());
+    }
+}
@@ -0,0 +1,256 @@
+use super::{InputHandler, StreamOption};
+use crate::{
+    event::{BatchNotifier, BatchStatus},
+    internal_events::{RedisAckFailed, RedisReceiveEventFailed, RedisStreamFieldMissing},
+    shutdown::ShutdownSignal,
+    sources::util::finalizer::OrderedFinalizer,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use futures::FutureExt;
+use redis::{
+    aio::ConnectionManager,
+    streams::{StreamId, StreamReadOptions, StreamReadReply},
+    AsyncCommands, RedisResult,
+};
+use std::time::Duration;
+
+/// Milliseconds a blocking read waits for new entries, which bounds how
+/// long shutdown can be delayed.
+const READ_BLOCK_MS: usize = 1000;
+
+/// Read position for entries that haven't been delivered to any consumer
+/// of the group yet.
+const NEW_ENTRIES_ID: &str = ">";
+
+/// Create the consumer group, along with the stream if it doesn't exist
+/// yet. An existing group is left as it is.
+pub(super) async fn create_group(
+    connection: &mut ConnectionManager,
+    key: &str,
+    options: &StreamOption,
+) -> RedisResult<()> {
+    let result: RedisResult<()> = connection
+        .xgroup_create_mkstream(key, &options.group, &options.start_id)
+        .await;
+    match result {
+        Err(error) if error.code() == Some("BUSYGROUP") => Ok(()),
+        result => result,
+    }
+}
+
+pub(super) async fn consume(
+    mut connection: ConnectionManager,
+    ack_connection: ConnectionManager,
+    key: String,
+    options: StreamOption,
+    mut handler: InputHandler,
+    shutdown: ShutdownSignal,
+    acknowledgements: bool,
+) -> Result<(), ()> {
+    let shutdown = shutdown.shared();
+    let consumer = options.consumer();
+    let finalizer = if acknowledgements {
+        let key = key.clone();
+        let group = options.group.clone();
+        Some(OrderedFinalizer::with_status(
+            shutdown.clone(),
+            move |status, id: String| {
+                // Entries that were not delivered stay in the pending list
+                // of this consumer, and are read again after a restart.
+                if let BatchStatus::Delivered = status {
+                    let mut connection = ack_connection.clone();
+                    let key = key.clone();
+                    let group = group.clone();
+                    tokio::spawn(async move {
+                        ack(&mut connection, &key, &group, &id).await;
+                    });
+                }
+            },
+        ))
+    } else {
+        None
+    };
+
+    // Entries delivered to this consumer before a restart but never
+    // acknowledged are read first, starting from the beginning of its
+    // pending list, before moving on to new entries.
+    let mut read_id = "0".to_owned();
+
+    while shutdown.clone().now_or_never().is_none() {
+        let mut read_options = StreamReadOptions::default()
+            .group(&options.group, &consumer)
+            .count(options.count);
+        if read_id == NEW_ENTRIES_ID {
+            read_options = read_options.block(READ_BLOCK_MS);
+        }
+
+        let result: RedisResult<Option<StreamReadReply>> = connection
+            .xread_options(&[&key], &[&read_id], &read_options)
+            .await;
+        let entries: Vec<StreamId> = match result {
+            Ok(reply) => reply
+                .map(|reply| reply.keys.into_iter().flat_map(|key| key.ids).collect())
+                .unwrap_or_default(),
+            Err(error) => {
+                emit!(&RedisReceiveEventFailed { error });
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        if read_id != NEW_ENTRIES_ID {
+            match entries.last() {
+                Some(entry) => read_id = entry.id.clone(),
+                None => read_id = NEW_ENTRIES_ID.to_owned(),
+            }
+        }
+
+        for entry in entries {
+            let payload = match entry.get::<Vec<u8>>(&options.field) {
+                Some(payload) => payload,
+                None => {
+                    // The entry can never be decoded, so don't leave it
+                    // pending forever.
+                    emit!(&RedisStreamFieldMissing {
+                        id: &entry.id,
+                        field: &options.field,
+                    });
+                    ack(&mut connection, &key, &options.group, &entry.id).await;
+                    continue;
+                }
+            };
+
+            let (batch, receiver) = BatchNotifier::maybe_new_with_receiver(finalizer.is_some());
+            handler
+                .handle(&key, &payload, entry_timestamp(&entry), &batch)
+                .await?;
+            drop(batch);
+
+            match (&finalizer, receiver) {
+                (Some(finalizer), Some(receiver)) => finalizer.add(entry.id, receiver),
+                _ => ack(&mut connection, &key, &options.group, &entry.id).await,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn ack(connection: &mut ConnectionManager, key: &str, group: &str, id: &str) {
+    let result: RedisResult<usize> = connection.xack(key, group, &[id]).await;
+    if let Err(error) = result {
+        emit!(&RedisAckFailed { error });
+    }
+}
+
+/// Entry IDs start with the time in milliseconds the entry was added at.
+fn entry_timestamp(entry: &StreamId) -> DateTime<Utc> {
+    entry
+        .id
+        .split('-')
+        .next()
+        .and_then(|millis| millis.parse::<i64>().ok())
+        .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn timestamp_from_entry_id() {
+        let entry = StreamId {
+            id: "1526919030474-55".into(),
+            map: HashMap::new(),
+        };
+        assert_eq!(entry_timestamp(&entry), Utc.timestamp_millis(1526919030474));
+    }
+}
+
+#[cfg(feature = "redis-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::{
+        codecs::DecodingConfig,
+        config::log_schema,
+        serde::{default_decoding, default_framing_message_based},
+        sources::redis::integration_tests::connection,
+        test_util::{collect_n, random_string, trace_init},
+        Pipeline,
+    };
+    use redis::streams::StreamPendingReply;
+    use vector_core::event::EventStatus;
+
+    async fn pending(key: &str, group: &str) -> usize {
+        let reply: StreamPendingReply = connection().await.xpending(key, group).await.unwrap();
+        reply.count()
+    }
+
+    async fn run_consumer(key: &str, status: EventStatus, count: usize) {
+        let options = StreamOption {
+            start_id: "0".into(),
+            ..Default::default()
+        };
+        let mut conn = connection().await;
+        create_group(&mut conn, key, &options).await.unwrap();
+
+        let (tx, rx) = Pipeline::new_test_finalize(status);
+        let handler = InputHandler {
+            redis_key: None,
+            decoder: DecodingConfig::new(default_framing_message_based(), default_decoding())
+                .build()
+                .unwrap(),
+            out: tx,
+        };
+        tokio::spawn(consume(
+            conn,
+            connection().await,
+            key.to_owned(),
+            options,
+            handler,
+            ShutdownSignal::noop(),
+            true,
+        ));
+
+        let events = collect_n(rx, count).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "my message".into()
+        );
+        // Leave time for the acknowledgements to be sent.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn redis_source_stream_acks_delivered() {
+        trace_init();
+
+        let key = format!("test-{}", random_string(10));
+        let _: String = connection()
+            .await
+            .xadd(&key, "*", &[("message", "my message")])
+            .await
+            .unwrap();
+
+        run_consumer(&key, EventStatus::Delivered, 1).await;
+        assert_eq!(pending(&key, "vector").await, 0);
+    }
+
+    #[tokio::test]
+    async fn redis_source_stream_keeps_failed_pending() {
+        trace_init();
+
+        let key = format!("test-{}", random_string(10));
+        let _: String = connection()
+            .await
+            .xadd(&key, "*", &[("message", "my message")])
+            .await
+            .unwrap();
+
+        run_consumer(&key, EventStatus::Failed, 1).await;
+        assert_eq!(pending(&key, "vector").await, 1);
+    }
+}
@@ -0,0 +1,254 @@
+use snafu::Snafu;
+use std::fmt;
+
+pub const INTEGER: u8 = 0x02;
+pub const OCTET_STRING: u8 = 0x04;
+pub const NULL: u8 = 0x05;
+pub const OBJECT_IDENTIFIER: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+pub const IP_ADDRESS: u8 = 0x40;
+pub const COUNTER32: u8 = 0x41;
+pub const GAUGE32: u8 = 0x42;
+pub const TIME_TICKS: u8 = 0x43;
+pub const COUNTER64: u8 = 0x46;
+pub const NO_SUCH_OBJECT: u8 = 0x80;
+pub const NO_SUCH_INSTANCE: u8 = 0x81;
+pub const END_OF_MIB_VIEW: u8 = 0x82;
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum BerError {
+    #[snafu(display("Message is truncated"))]
+    Truncated,
+    #[snafu(display("Expected tag {:#04x}, found {:#04x}", expected, found))]
+    UnexpectedTag { expected: u8, found: u8 },
+    #[snafu(display("Invalid {} encoding", context))]
+    InvalidEncoding { context: &'static str },
+}
+
+/// An element of a BER encoded message, borrowing its contents.
+#[derive(Debug)]
+pub struct Tlv<'a> {
+    pub tag: u8,
+    pub value: &'a [u8],
+}
+
+/// A cursor over consecutive BER encoded elements. Only the definite length
+/// form and low tag numbers are supported, which is all SNMP uses.
+#[derive(Clone, Debug)]
+pub struct BerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BerReader<'a> {
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The not yet read part of the data.
+    pub const fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn read(&mut self) -> Result<Tlv<'a>, BerError> {
+        let (&tag, rest) = self.data.split_first().ok_or(BerError::Truncated)?;
+        if tag & 0x1f == 0x1f {
+            return Err(BerError::InvalidEncoding { context: "tag" });
+        }
+
+        let (&first, mut rest) = rest.split_first().ok_or(BerError::Truncated)?;
+        let length = if first & 0x80 == 0 {
+            usize::from(first)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > 4 || rest.len() < count {
+                return Err(BerError::InvalidEncoding { context: "length" });
+            }
+            let (bytes, after) = rest.split_at(count);
+            rest = after;
+            bytes
+                .iter()
+                .fold(0, |length, &byte| (length << 8) | usize::from(byte))
+        };
+
+        if rest.len() < length {
+            return Err(BerError::Truncated);
+        }
+        let (value, rest) = rest.split_at(length);
+        self.data = rest;
+        Ok(Tlv { tag, value })
+    }
+
+    pub fn expect(&mut self, expected: u8) -> Result<&'a [u8], BerError> {
+        let tlv = self.read()?;
+        if tlv.tag == expected {
+            Ok(tlv.value)
+        } else {
+            Err(BerError::UnexpectedTag {
+                expected,
+                found: tlv.tag,
+            })
+        }
+    }
+
+    pub fn sequence(&mut self) -> Result<BerReader<'a>, BerError> {
+        self.expect(SEQUENCE).map(BerReader::new)
+    }
+
+    pub fn integer(&mut self) -> Result<i64, BerError> {
+        self.expect(INTEGER).and_then(decode_integer)
+    }
+
+    pub fn octet_string(&mut self) -> Result<&'a [u8], BerError> {
+        self.expect(OCTET_STRING)
+    }
+
+    pub fn oid(&mut self) -> Result<Oid, BerError> {
+        self.expect(OBJECT_IDENTIFIER).and_then(decode_oid)
+    }
+}
+
+pub fn decode_integer(bytes: &[u8]) -> Result<i64, BerError> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return Err(BerError::InvalidEncoding { context: "integer" });
+    }
+    // Sign extend from the most significant byte.
+    let initial = if bytes[0] & 0x80 == 0 { 0 } else { -1 };
+    Ok(bytes
+        .iter()
+        .fold(initial, |value, &byte| (value << 8) | i64::from(byte)))
+}
+
+/// Decode the unsigned application types, which carry a leading zero byte
+/// when their most significant bit is set.
+pub fn decode_unsigned(bytes: &[u8]) -> Result<u64, BerError> {
+    let bytes = match bytes {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        bytes => bytes,
+    };
+    if bytes.is_empty() || bytes.len() > 8 {
+        return Err(BerError::InvalidEncoding {
+            context: "unsigned",
+        });
+    }
+    Ok(bytes
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | u64::from(byte)))
+}
+
+pub fn decode_oid(bytes: &[u8]) -> Result<Oid, BerError> {
+    let invalid = || BerError::InvalidEncoding {
+        context: "object identifier",
+    };
+
+    let mut arcs = Vec::with_capacity(bytes.len() + 1);
+    let mut arc: u32 = 0;
+    for (index, &byte) in bytes.iter().enumerate() {
+        arc = arc
+            .checked_mul(128)
+            .ok_or_else(invalid)?
+            .checked_add(u32::from(byte & 0x7f))
+            .ok_or_else(invalid)?;
+        if byte & 0x80 != 0 {
+            if index == bytes.len() - 1 {
+                return Err(invalid());
+            }
+            continue;
+        }
+
+        if arcs.is_empty() {
+            // The first two arcs share a subidentifier.
+            let first = (arc / 40).min(2);
+            arcs.push(first);
+            arcs.push(arc - first * 40);
+        } else {
+            arcs.push(arc);
+        }
+        arc = 0;
+    }
+
+    if arcs.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Oid(arcs))
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Oid(pub Vec<u32>);
+
+impl Oid {
+    pub fn arcs(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, arc) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", arc)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_short_and_long_lengths() {
+        let mut data = vec![OCTET_STRING, 0x81, 200];
+        data.extend(vec![b'a'; 200]);
+        data.extend(&[INTEGER, 1, 5]);
+
+        let mut reader = BerReader::new(&data);
+        assert_eq!(reader.octet_string().unwrap().len(), 200);
+        assert_eq!(reader.integer().unwrap(), 5);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_and_unexpected_elements() {
+        let mut reader = BerReader::new(&[OCTET_STRING, 5, b'a']);
+        assert_eq!(reader.read().unwrap_err(), BerError::Truncated);
+
+        let mut reader = BerReader::new(&[NULL, 0]);
+        assert_eq!(
+            reader.integer().unwrap_err(),
+            BerError::UnexpectedTag {
+                expected: INTEGER,
+                found: NULL
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_integers() {
+        assert_eq!(decode_integer(&[0x7f]).unwrap(), 127);
+        assert_eq!(decode_integer(&[0x00, 0x80]).unwrap(), 128);
+        assert_eq!(decode_integer(&[0xff, 0x7f]).unwrap(), -129);
+        assert_eq!(
+            decode_unsigned(&[0x00, 0xff, 0xff, 0xff, 0xff]).unwrap(),
+            u64::from(u32::MAX)
+        );
+        assert!(decode_integer(&[]).is_err());
+    }
+
+    #[test]
+    fn decodes_object_identifiers() {
+        let oid =
+            decode_oid(&[0x2b, 0x06, 0x01, 0x06, 0x03, 0x01, 0x01, 0x04, 0x01, 0x00]).unwrap();
+        assert_eq!(oid.to_string(), "1.3.6.1.6.3.1.1.4.1.0");
+
+        let oid = decode_oid(&[0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37]).unwrap();
+        assert_eq!(oid.to_string(), "1.3.6.1.4.1.311");
+
+        assert!(decode_oid(&[0x2b, 0x86]).is_err());
+    }
+}
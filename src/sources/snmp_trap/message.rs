@@ -0,0 +1,566 @@
+use super::{
+    ber::{self, BerError, BerReader, Oid, Tlv},
+    usm::{ScopedPduData, SecurityParameters, Usm},
+};
+use snafu::Snafu;
+use std::{convert::TryInto, net::Ipv4Addr};
+
+const RESPONSE: u8 = 0xa2;
+const TRAP_V1: u8 = 0xa4;
+const INFORM_REQUEST: u8 = 0xa6;
+const TRAP_V2: u8 = 0xa7;
+
+const USER_BASED_SECURITY_MODEL: i64 = 3;
+const FLAG_AUTH: u8 = 0x01;
+const FLAG_PRIV: u8 = 0x02;
+
+/// `sysUpTime.0`
+const SYS_UP_TIME: [u32; 9] = [1, 3, 6, 1, 2, 1, 1, 3, 0];
+/// `snmpTrapOID.0`
+const SNMP_TRAP_OID: [u32; 11] = [1, 3, 6, 1, 6, 3, 1, 1, 4, 1, 0];
+/// `snmpTraps`, the parent of the generic traps of SNMPv1.
+const SNMP_TRAPS: [u32; 9] = [1, 3, 6, 1, 6, 3, 1, 1, 5];
+const ENTERPRISE_SPECIFIC_TRAP: u32 = 6;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum SnmpError {
+    #[snafu(display("Malformed message: {}", source))]
+    Malformed { source: BerError },
+    #[snafu(display("Unsupported SNMP version {}", version))]
+    UnsupportedVersion { version: i64 },
+    #[snafu(display("Unsupported PDU type {:#04x}", tag))]
+    UnsupportedPdu { tag: u8 },
+    #[snafu(display("Unsupported security model {}", model))]
+    UnsupportedSecurityModel { model: i64 },
+    #[snafu(display("Unknown community {:?}", community))]
+    UnknownCommunity { community: String },
+    #[snafu(display("Unknown user {:?}", user))]
+    UnknownUser { user: String },
+    #[snafu(display("Security level of the message doesn't match user {:?}", user))]
+    SecurityLevelMismatch { user: String },
+    #[snafu(display("Wrong digest for user {:?}", user))]
+    WrongDigest { user: String },
+    #[snafu(display("Message of user {:?} is outside of the time window", user))]
+    NotInTimeWindow { user: String },
+    #[snafu(display("Cryptographic operation failed: {}", source))]
+    Crypto { source: openssl::error::ErrorStack },
+}
+
+impl SnmpError {
+    /// Whether the message was rejected because of its community or user,
+    /// rather than because it couldn't be decoded.
+    pub const fn is_authentication_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::UnknownCommunity { .. }
+                | Self::UnknownUser { .. }
+                | Self::SecurityLevelMismatch { .. }
+                | Self::WrongDigest { .. }
+                | Self::NotInTimeWindow { .. }
+        )
+    }
+}
+
+impl From<BerError> for SnmpError {
+    fn from(source: BerError) -> Self {
+        Self::Malformed { source }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnmpVersion {
+    V1,
+    V2c,
+    V3,
+}
+
+impl SnmpVersion {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "1",
+            Self::V2c => "2c",
+            Self::V3 => "3",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PduType {
+    Trap,
+    Inform,
+}
+
+impl PduType {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Trap => "trap",
+            Self::Inform => "inform",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VarBindValue {
+    Integer(i64),
+    Unsigned(u64),
+    OctetString(Vec<u8>),
+    ObjectIdentifier(Oid),
+    IpAddress(Ipv4Addr),
+    /// `Opaque` values and types unknown to SNMPv2.
+    Opaque(Vec<u8>),
+    /// `NULL` and the exceptions `noSuchObject`, `noSuchInstance` and
+    /// `endOfMibView`.
+    Null,
+}
+
+/// The fields specific to SNMPv1 traps.
+#[derive(Debug)]
+pub struct V1Trap {
+    pub enterprise: Oid,
+    pub agent_address: Ipv4Addr,
+    pub generic_trap: u32,
+    pub specific_trap: u32,
+}
+
+/// The USM fields of SNMPv3 messages.
+#[derive(Debug)]
+pub struct Security {
+    pub user: String,
+    pub engine_id: Vec<u8>,
+    pub context_engine_id: Vec<u8>,
+    pub context_name: String,
+}
+
+#[derive(Debug)]
+pub struct Trap {
+    pub version: SnmpVersion,
+    pub pdu_type: PduType,
+    pub community: Option<String>,
+    pub security: Option<Security>,
+    pub request_id: Option<i64>,
+    pub v1: Option<V1Trap>,
+    pub uptime: Option<u64>,
+    pub trap_oid: Option<Oid>,
+    pub varbinds: Vec<(Oid, VarBindValue)>,
+    /// The response acknowledging an SNMPv2c inform.
+    pub response: Option<Vec<u8>>,
+}
+
+pub async fn parse(data: &[u8], usm: &mut Usm) -> Result<Trap, SnmpError> {
+    let mut outer = BerReader::new(data);
+    let mut message = outer.sequence()?;
+    // Anything after the message is ignored, also for authentication.
+    let data = &data[..data.len() - outer.remaining().len()];
+
+    match message.integer()? {
+        0 => parse_community_message(data, message, SnmpVersion::V1),
+        1 => parse_community_message(data, message, SnmpVersion::V2c),
+        3 => parse_v3_message(data, message, usm).await,
+        version => Err(SnmpError::UnsupportedVersion { version }),
+    }
+}
+
+fn parse_community_message(
+    data: &[u8],
+    mut message: BerReader<'_>,
+    version: SnmpVersion,
+) -> Result<Trap, SnmpError> {
+    let community = String::from_utf8_lossy(message.octet_string()?).into_owned();
+    let pdu_offset = offset(data, message.remaining());
+    let mut trap = parse_pdu(&mut message, version)?;
+    trap.community = Some(community);
+
+    // A response only differs from the inform in its PDU type.
+    if trap.pdu_type == PduType::Inform {
+        let mut response = data.to_vec();
+        response[pdu_offset] = RESPONSE;
+        trap.response = Some(response);
+    }
+    Ok(trap)
+}
+
+async fn parse_v3_message(
+    data: &[u8],
+    mut message: BerReader<'_>,
+    usm: &mut Usm,
+) -> Result<Trap, SnmpError> {
+    let mut global = message.sequence()?;
+    let _message_id = global.integer()?;
+    let _max_size = global.integer()?;
+    let flags = *global
+        .octet_string()?
+        .first()
+        .ok_or(BerError::InvalidEncoding {
+            context: "message flags",
+        })?;
+    let model = global.integer()?;
+    if model != USER_BASED_SECURITY_MODEL {
+        return Err(SnmpError::UnsupportedSecurityModel { model });
+    }
+
+    let mut security = BerReader::new(message.octet_string()?).sequence()?;
+    let engine_id = security.octet_string()?;
+    let engine_boots = unsigned_integer(&mut security, "engine boots")?;
+    let engine_time = unsigned_integer(&mut security, "engine time")?;
+    let user = String::from_utf8_lossy(security.octet_string()?).into_owned();
+    let auth_parameters = security.octet_string()?;
+    let params = SecurityParameters {
+        engine_id,
+        engine_boots,
+        engine_time,
+        user,
+        auth_parameters,
+        auth_offset: offset(data, auth_parameters),
+        privacy_parameters: security.octet_string()?,
+    };
+
+    let authenticated = flags & FLAG_AUTH != 0;
+    let scoped_pdu = if flags & FLAG_PRIV != 0 {
+        if !authenticated {
+            return Err(BerError::InvalidEncoding {
+                context: "message flags",
+            }
+            .into());
+        }
+        ScopedPduData::Encrypted(message.octet_string()?)
+    } else {
+        ScopedPduData::Plaintext(message.remaining())
+    };
+    let scoped_pdu = usm
+        .process(data, &params, authenticated, scoped_pdu)
+        .await?;
+
+    // Decrypted scoped PDUs may be followed by padding.
+    let mut scoped_pdu = BerReader::new(&scoped_pdu).sequence()?;
+    let context_engine_id = scoped_pdu.octet_string()?.to_vec();
+    let context_name = String::from_utf8_lossy(scoped_pdu.octet_string()?).into_owned();
+    let mut trap = parse_pdu(&mut scoped_pdu, SnmpVersion::V3)?;
+    trap.security = Some(Security {
+        user: params.user,
+        engine_id: params.engine_id.to_vec(),
+        context_engine_id,
+        context_name,
+    });
+    Ok(trap)
+}
+
+fn parse_pdu(reader: &mut BerReader<'_>, version: SnmpVersion) -> Result<Trap, SnmpError> {
+    let pdu = reader.read()?;
+    let mut fields = BerReader::new(pdu.value);
+    let mut trap = Trap {
+        version,
+        pdu_type: PduType::Trap,
+        community: None,
+        security: None,
+        request_id: None,
+        v1: None,
+        uptime: None,
+        trap_oid: None,
+        varbinds: Vec::new(),
+        response: None,
+    };
+
+    match (pdu.tag, version) {
+        (TRAP_V1, SnmpVersion::V1) => {
+            let enterprise = fields.oid()?;
+            let agent_address = ipv4(fields.expect(ber::IP_ADDRESS)?)?;
+            let generic_trap = unsigned_integer(&mut fields, "generic trap")?;
+            let specific_trap = unsigned_integer(&mut fields, "specific trap")?;
+            trap.uptime = Some(ber::decode_unsigned(fields.expect(ber::TIME_TICKS)?)?);
+            trap.varbinds = varbinds(&mut fields)?;
+
+            // The translation of SNMPv1 traps to notifications of RFC 3584.
+            let trap_oid = if generic_trap == ENTERPRISE_SPECIFIC_TRAP {
+                let mut arcs = enterprise.arcs().to_vec();
+                arcs.extend(&[0, specific_trap]);
+                arcs
+            } else {
+                let mut arcs = SNMP_TRAPS.to_vec();
+                arcs.push(generic_trap + 1);
+                arcs
+            };
+            trap.trap_oid = Some(Oid(trap_oid));
+            trap.v1 = Some(V1Trap {
+                enterprise,
+                agent_address,
+                generic_trap,
+                specific_trap,
+            });
+        }
+        (TRAP_V2, SnmpVersion::V2c | SnmpVersion::V3)
+        | (INFORM_REQUEST, SnmpVersion::V2c | SnmpVersion::V3) => {
+            if pdu.tag == INFORM_REQUEST {
+                trap.pdu_type = PduType::Inform;
+            }
+            trap.request_id = Some(fields.integer()?);
+            let _error_status = fields.integer()?;
+            let _error_index = fields.integer()?;
+
+            // The uptime and trap OID are the first variable bindings of
+            // every notification, those are kept apart.
+            for (oid, value) in varbinds(&mut fields)? {
+                match value {
+                    VarBindValue::Unsigned(uptime) if oid.arcs() == SYS_UP_TIME => {
+                        trap.uptime = Some(uptime);
+                    }
+                    VarBindValue::ObjectIdentifier(trap_oid) if oid.arcs() == SNMP_TRAP_OID => {
+                        trap.trap_oid = Some(trap_oid);
+                    }
+                    value => trap.varbinds.push((oid, value)),
+                }
+            }
+        }
+        (tag, _) => return Err(SnmpError::UnsupportedPdu { tag }),
+    }
+
+    Ok(trap)
+}
+
+fn varbinds(fields: &mut BerReader<'_>) -> Result<Vec<(Oid, VarBindValue)>, BerError> {
+    let mut list = fields.sequence()?;
+    let mut varbinds = Vec::new();
+    while !list.is_empty() {
+        let mut varbind = list.sequence()?;
+        let oid = varbind.oid()?;
+        let value = value(varbind.read()?)?;
+        varbinds.push((oid, value));
+    }
+    Ok(varbinds)
+}
+
+fn value(tlv: Tlv<'_>) -> Result<VarBindValue, BerError> {
+    Ok(match tlv.tag {
+        ber::INTEGER => VarBindValue::Integer(ber::decode_integer(tlv.value)?),
+        ber::OCTET_STRING => VarBindValue::OctetString(tlv.value.to_vec()),
+        ber::OBJECT_IDENTIFIER => VarBindValue::ObjectIdentifier(ber::decode_oid(tlv.value)?),
+        ber::IP_ADDRESS => VarBindValue::IpAddress(ipv4(tlv.value)?),
+        ber::COUNTER32 | ber::GAUGE32 | ber::TIME_TICKS | ber::COUNTER64 => {
+            VarBindValue::Unsigned(ber::decode_unsigned(tlv.value)?)
+        }
+        ber::NULL | ber::NO_SUCH_OBJECT | ber::NO_SUCH_INSTANCE | ber::END_OF_MIB_VIEW => {
+            VarBindValue::Null
+        }
+        _ => VarBindValue::Opaque(tlv.value.to_vec()),
+    })
+}
+
+fn ipv4(bytes: &[u8]) -> Result<Ipv4Addr, BerError> {
+    let octets: [u8; 4] = bytes.try_into().map_err(|_| BerError::InvalidEncoding {
+        context: "IP address",
+    })?;
+    Ok(octets.into())
+}
+
+fn unsigned_integer(reader: &mut BerReader<'_>, context: &'static str) -> Result<u32, BerError> {
+    let value = reader.integer()?;
+    u32::try_from(value).map_err(|_| BerError::InvalidEncoding { context })
+}
+
+/// The position of a part of the message, as read by a `BerReader`.
+fn offset(message: &[u8], part: &[u8]) -> usize {
+    part.as_ptr() as usize - message.as_ptr() as usize
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::sources::snmp_trap::usm::SnmpUserConfig;
+
+    pub fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut data = vec![tag];
+        if content.len() < 0x80 {
+            data.push(content.len() as u8);
+        } else {
+            data.extend(&[0x82, (content.len() >> 8) as u8, content.len() as u8]);
+        }
+        data.extend(content);
+        data
+    }
+
+    pub fn integer(value: i64) -> Vec<u8> {
+        let bytes = value.to_be_bytes();
+        let skip = bytes
+            .windows(2)
+            .take_while(|pair| {
+                (pair[0] == 0 && pair[1] & 0x80 == 0) || (pair[0] == 0xff && pair[1] & 0x80 != 0)
+            })
+            .count();
+        tlv(ber::INTEGER, &bytes[skip..])
+    }
+
+    pub fn oid(arcs: &[u32]) -> Vec<u8> {
+        let mut content = vec![(arcs[0] * 40 + arcs[1]) as u8];
+        for &arc in &arcs[2..] {
+            let mut groups = vec![(arc & 0x7f) as u8];
+            let mut rest = arc >> 7;
+            while rest > 0 {
+                groups.push((rest & 0x7f) as u8 | 0x80);
+                rest >>= 7;
+            }
+            content.extend(groups.iter().rev());
+        }
+        tlv(ber::OBJECT_IDENTIFIER, &content)
+    }
+
+    pub fn sequence(parts: &[Vec<u8>]) -> Vec<u8> {
+        tlv(ber::SEQUENCE, &parts.concat())
+    }
+
+    pub fn notification(tag: u8, varbinds: &[Vec<u8>]) -> Vec<u8> {
+        let mut all = vec![
+            sequence(&[oid(&SYS_UP_TIME), tlv(ber::TIME_TICKS, &[0x01, 0x00])]),
+            sequence(&[oid(&SNMP_TRAP_OID), oid(&[1, 3, 6, 1, 6, 3, 1, 1, 5, 3])]),
+        ];
+        all.extend(varbinds.iter().cloned());
+        tlv(
+            tag,
+            &[integer(1234), integer(0), integer(0), sequence(&all)].concat(),
+        )
+    }
+
+    fn if_index_varbind() -> Vec<u8> {
+        sequence(&[oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 3]), integer(3)])
+    }
+
+    fn community_message(version: i64, pdu: Vec<u8>) -> Vec<u8> {
+        sequence(&[integer(version), tlv(ber::OCTET_STRING, b"public"), pdu])
+    }
+
+    #[tokio::test]
+    async fn parses_v1_traps() {
+        let pdu = tlv(
+            TRAP_V1,
+            &[
+                oid(&[1, 3, 6, 1, 4, 1, 8072]),
+                tlv(ber::IP_ADDRESS, &[192, 0, 2, 1]),
+                integer(2),
+                integer(0),
+                tlv(ber::TIME_TICKS, &[0x30, 0x39]),
+                sequence(&[if_index_varbind()]),
+            ]
+            .concat(),
+        );
+        let data = community_message(0, pdu);
+
+        let trap = parse(&data, &mut Usm::new(&[])).await.unwrap();
+        assert_eq!(trap.version, SnmpVersion::V1);
+        assert_eq!(trap.community.as_deref(), Some("public"));
+        assert_eq!(trap.uptime, Some(12345));
+        // linkDown
+        assert_eq!(trap.trap_oid.unwrap().to_string(), "1.3.6.1.6.3.1.1.5.3");
+        let v1 = trap.v1.unwrap();
+        assert_eq!(v1.agent_address, Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(v1.enterprise.to_string(), "1.3.6.1.4.1.8072");
+        assert_eq!(trap.varbinds[0].1, VarBindValue::Integer(3));
+    }
+
+    #[tokio::test]
+    async fn translates_enterprise_specific_v1_traps() {
+        let pdu = tlv(
+            TRAP_V1,
+            &[
+                oid(&[1, 3, 6, 1, 4, 1, 8072]),
+                tlv(ber::IP_ADDRESS, &[192, 0, 2, 1]),
+                integer(6),
+                integer(17),
+                tlv(ber::TIME_TICKS, &[0]),
+                sequence(&[]),
+            ]
+            .concat(),
+        );
+        let trap = parse(&community_message(0, pdu), &mut Usm::new(&[]))
+            .await
+            .unwrap();
+        assert_eq!(trap.trap_oid.unwrap().to_string(), "1.3.6.1.4.1.8072.0.17");
+    }
+
+    #[tokio::test]
+    async fn parses_v2c_traps() {
+        let data = community_message(1, notification(TRAP_V2, &[if_index_varbind()]));
+
+        let trap = parse(&data, &mut Usm::new(&[])).await.unwrap();
+        assert_eq!(trap.version, SnmpVersion::V2c);
+        assert_eq!(trap.pdu_type, PduType::Trap);
+        assert_eq!(trap.request_id, Some(1234));
+        assert_eq!(trap.uptime, Some(256));
+        assert_eq!(trap.trap_oid.unwrap().to_string(), "1.3.6.1.6.3.1.1.5.3");
+        assert_eq!(trap.varbinds.len(), 1);
+        assert_eq!(trap.varbinds[0].0.to_string(), "1.3.6.1.2.1.2.2.1.1.3");
+        assert!(trap.response.is_none());
+    }
+
+    #[tokio::test]
+    async fn responds_to_v2c_informs() {
+        let data = community_message(1, notification(INFORM_REQUEST, &[]));
+
+        let trap = parse(&data, &mut Usm::new(&[])).await.unwrap();
+        assert_eq!(trap.pdu_type, PduType::Inform);
+        let response = trap.response.unwrap();
+        let expected = community_message(1, {
+            let mut pdu = notification(INFORM_REQUEST, &[]);
+            pdu[0] = RESPONSE;
+            pdu
+        });
+        assert_eq!(response, expected);
+    }
+
+    #[tokio::test]
+    async fn rejects_v2c_pdus_in_v1_messages() {
+        let data = community_message(0, notification(TRAP_V2, &[]));
+        assert!(matches!(
+            parse(&data, &mut Usm::new(&[])).await,
+            Err(SnmpError::UnsupportedPdu { tag: TRAP_V2 })
+        ));
+    }
+
+    pub fn v3_message(flags: u8, user: &str, auth: &[u8], scoped_pdu: Vec<u8>) -> Vec<u8> {
+        let security = sequence(&[
+            tlv(ber::OCTET_STRING, &[0x80, 0, 0x1f, 0x88, 4]),
+            integer(1),
+            integer(100),
+            tlv(ber::OCTET_STRING, user.as_bytes()),
+            tlv(ber::OCTET_STRING, auth),
+            tlv(ber::OCTET_STRING, &[]),
+        ]);
+        sequence(&[
+            integer(3),
+            sequence(&[
+                integer(1),
+                integer(65507),
+                tlv(ber::OCTET_STRING, &[flags]),
+                integer(3),
+            ]),
+            tlv(ber::OCTET_STRING, &security),
+            scoped_pdu,
+        ])
+    }
+
+    pub fn scoped_pdu() -> Vec<u8> {
+        sequence(&[
+            tlv(ber::OCTET_STRING, &[0x80, 0, 0x1f, 0x88, 4]),
+            tlv(ber::OCTET_STRING, b""),
+            notification(TRAP_V2, &[if_index_varbind()]),
+        ])
+    }
+
+    #[tokio::test]
+    async fn parses_v3_traps_without_authentication() {
+        let mut usm = Usm::new(&[SnmpUserConfig {
+            name: "vector".into(),
+            auth: None,
+            privacy: None,
+        }]);
+        let data = v3_message(0, "vector", &[], scoped_pdu());
+
+        let trap = parse(&data, &mut usm).await.unwrap();
+        assert_eq!(trap.version, SnmpVersion::V3);
+        assert_eq!(trap.security.unwrap().user, "vector");
+        assert_eq!(trap.varbinds.len(), 1);
+
+        let data = v3_message(0, "other", &[], scoped_pdu());
+        let error = parse(&data, &mut usm).await.unwrap_err();
+        assert!(matches!(error, SnmpError::UnknownUser { .. }));
+        assert!(error.is_authentication_failure());
+    }
+}
@@ -0,0 +1,354 @@
+use super::ber::Oid;
+use std::{collections::HashMap, fmt::Write};
+
+/// The macros of SMIv1 and SMIv2 that assign an OID to a descriptor.
+const MACROS: &[&str] = &[
+    "AGENT-CAPABILITIES",
+    "MODULE-COMPLIANCE",
+    "MODULE-IDENTITY",
+    "NOTIFICATION-GROUP",
+    "NOTIFICATION-TYPE",
+    "OBJECT-GROUP",
+    "OBJECT-IDENTITY",
+    "OBJECT-TYPE",
+    "TRAP-TYPE",
+];
+
+/// The nodes of `SNMPv2-SMI` that other modules build upon, so that module
+/// doesn't need to be loaded.
+const SMI_NODES: &[(&str, &[u32])] = &[
+    ("iso", &[1]),
+    ("org", &[1, 3]),
+    ("dod", &[1, 3, 6]),
+    ("internet", &[1, 3, 6, 1]),
+    ("directory", &[1, 3, 6, 1, 1]),
+    ("mgmt", &[1, 3, 6, 1, 2]),
+    ("mib-2", &[1, 3, 6, 1, 2, 1]),
+    ("transmission", &[1, 3, 6, 1, 2, 1, 10]),
+    ("experimental", &[1, 3, 6, 1, 3]),
+    ("private", &[1, 3, 6, 1, 4]),
+    ("enterprises", &[1, 3, 6, 1, 4, 1]),
+    ("security", &[1, 3, 6, 1, 5]),
+    ("snmpV2", &[1, 3, 6, 1, 6]),
+    ("snmpDomains", &[1, 3, 6, 1, 6, 1]),
+    ("snmpProxys", &[1, 3, 6, 1, 6, 2]),
+    ("snmpModules", &[1, 3, 6, 1, 6, 3]),
+];
+
+#[derive(Debug, PartialEq)]
+struct Definition {
+    module: String,
+    name: String,
+    /// The descriptor the OID is relative to, empty for absolute OIDs.
+    parent: String,
+    arcs: Vec<u32>,
+}
+
+/// The names of the OIDs defined by a set of MIB modules. Only the OID
+/// assignments are read from the modules, everything else is skipped.
+#[derive(Debug, Default)]
+pub struct MibTree {
+    names: HashMap<Vec<u32>, String>,
+}
+
+impl MibTree {
+    pub fn new<'a>(modules: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut oids = SMI_NODES
+            .iter()
+            .map(|(name, arcs)| (name.to_string(), arcs.to_vec()))
+            .collect::<HashMap<_, _>>();
+        let mut names = SMI_NODES
+            .iter()
+            .map(|(name, arcs)| (arcs.to_vec(), format!("SNMPv2-SMI::{}", name)))
+            .collect::<HashMap<_, _>>();
+
+        // Modules may refer to descriptors of modules that come later, so
+        // definitions are resolved until no more progress is made.
+        let mut pending = modules
+            .into_iter()
+            .flat_map(definitions)
+            .collect::<Vec<_>>();
+        loop {
+            let before = pending.len();
+            pending.retain(|definition| {
+                let parent = if definition.parent.is_empty() {
+                    Vec::new()
+                } else {
+                    match oids.get(&definition.parent) {
+                        Some(parent) => parent.clone(),
+                        None => return true,
+                    }
+                };
+                let mut arcs = parent;
+                arcs.extend(&definition.arcs);
+                names.insert(
+                    arcs.clone(),
+                    format!("{}::{}", definition.module, definition.name),
+                );
+                oids.insert(definition.name.clone(), arcs);
+                false
+            });
+            if pending.is_empty() || pending.len() == before {
+                break;
+            }
+        }
+
+        Self { names }
+    }
+
+    /// Resolve an OID to the name of its closest defined ancestor, followed
+    /// by the remaining arcs, as in `IF-MIB::ifDescr.3`.
+    pub fn resolve(&self, oid: &Oid) -> Option<String> {
+        let arcs = oid.arcs();
+        (1..=arcs.len()).rev().find_map(|length| {
+            self.names.get(&arcs[..length]).map(|name| {
+                let mut name = name.clone();
+                for arc in &arcs[length..] {
+                    let _ = write!(name, ".{}", arc);
+                }
+                name
+            })
+        })
+    }
+}
+
+fn definitions(module_text: &str) -> Vec<Definition> {
+    let tokens = tokenize(module_text);
+    let mut definitions = Vec::new();
+    let mut module = "";
+    let mut current: Option<(&str, &str)> = None;
+    let mut enterprise = None;
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        let next = tokens.get(index + 1).copied();
+        let starts_definition = token.starts_with(|c: char| c.is_ascii_lowercase())
+            && match next {
+                Some("OBJECT") => tokens.get(index + 2) == Some(&"IDENTIFIER"),
+                Some(next) => MACROS.contains(&next),
+                None => false,
+            };
+
+        if next == Some("DEFINITIONS") {
+            module = token;
+        } else if starts_definition {
+            current = next.map(|r#macro| (token, r#macro));
+            enterprise = None;
+        } else if token == "ENTERPRISE" {
+            enterprise = next;
+        } else if token == "::=" {
+            match (current.take(), next) {
+                (Some((name, _)), Some("{")) => {
+                    let end = tokens[index..]
+                        .iter()
+                        .position(|token| *token == "}")
+                        .map_or(tokens.len(), |end| index + end);
+                    if let Some((parent, arcs)) = oid_value(&tokens[index + 2..end]) {
+                        definitions.push(Definition {
+                            module: module.to_string(),
+                            name: name.to_string(),
+                            parent,
+                            arcs,
+                        });
+                    }
+                    index = end;
+                }
+                // SMIv1 traps are numbered within their enterprise, as
+                // translated by RFC 3584.
+                (Some((name, "TRAP-TYPE")), Some(number)) => {
+                    if let (Some(enterprise), Ok(number)) = (enterprise, number.parse()) {
+                        definitions.push(Definition {
+                            module: module.to_string(),
+                            name: name.to_string(),
+                            parent: enterprise.to_string(),
+                            arcs: vec![0, number],
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        index += 1;
+    }
+
+    definitions
+}
+
+/// Parse the components of an OID value like `{ iso org(3) dod(6) 1 }`.
+fn oid_value(components: &[&str]) -> Option<(String, Vec<u32>)> {
+    let (first, rest) = components.split_first()?;
+    let number = |component: &str| -> Option<u32> {
+        match component.find('(') {
+            Some(open) => component[open + 1..].strip_suffix(')')?.parse().ok(),
+            None => component.parse().ok(),
+        }
+    };
+
+    let mut arcs = Vec::with_capacity(components.len());
+    let parent = match first.parse() {
+        Ok(arc) => {
+            arcs.push(arc);
+            String::new()
+        }
+        Err(_) => first.split('(').next().unwrap_or_default().to_string(),
+    };
+    for component in rest {
+        arcs.push(number(component)?);
+    }
+    Some((parent, arcs))
+}
+
+/// Split a module into tokens, skipping comments and quoted strings.
+fn tokenize(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+        let is_comment = byte == b'-' && bytes.get(index + 1) == Some(&b'-');
+        let is_delimiter = matches!(byte, b'{' | b'}' | b',' | b';');
+        if byte == b'"' || is_comment || is_delimiter || byte.is_ascii_whitespace() {
+            if let Some(start) = start.take() {
+                tokens.push(&text[start..index]);
+            }
+        }
+
+        if byte == b'"' {
+            index += bytes[index + 1..]
+                .iter()
+                .position(|&byte| byte == b'"')
+                .map_or(bytes.len(), |end| end + 2);
+        } else if is_comment {
+            // Comments end at the end of the line or at the next `--`.
+            let rest = &bytes[index + 2..];
+            let line_end = rest
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .unwrap_or(rest.len());
+            let comment_end = rest[..line_end]
+                .windows(2)
+                .position(|pair| pair == b"--")
+                .map_or(line_end, |end| end + 2);
+            index += 2 + comment_end;
+        } else {
+            if is_delimiter {
+                tokens.push(&text[index..=index]);
+            } else if !byte.is_ascii_whitespace() && start.is_none() {
+                start = Some(index);
+            }
+            index += 1;
+        }
+    }
+    if let Some(start) = start {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IF_MIB: &str = r#"
+IF-MIB DEFINITIONS ::= BEGIN
+
+IMPORTS
+    MODULE-IDENTITY, OBJECT-TYPE, NOTIFICATION-TYPE, mib-2 FROM SNMPv2-SMI;
+
+ifMIB MODULE-IDENTITY
+    LAST-UPDATED "200006140000Z" -- June 14, 2000
+    DESCRIPTION
+            "The MIB module to describe generic objects for network
+            interface sub-layers.  ::= { not an assignment }"
+    ::= { mib-2 31 }
+
+ifMIBObjects OBJECT IDENTIFIER ::= { ifMIB 1 }
+interfaces   OBJECT IDENTIFIER ::= { mib-2 2 }
+
+ifTable OBJECT-TYPE
+    SYNTAX      SEQUENCE OF IfEntry
+    MAX-ACCESS  not-accessible
+    STATUS      current
+    ::= { interfaces 2 }
+
+ifEntry OBJECT-TYPE
+    SYNTAX      IfEntry
+    INDEX   { ifIndex }
+    ::= { ifTable 1 }
+
+IfEntry ::=
+    SEQUENCE {
+        ifIndex                 InterfaceIndex,
+        ifDescr                 DisplayString
+    }
+
+ifIndex OBJECT-TYPE
+    SYNTAX      InterfaceIndex
+    ::= { ifEntry 1 }
+
+snmpTraps OBJECT IDENTIFIER ::= { iso(1) org(3) dod(6) internet(1) snmpV2(6) 3 1 1 5 }
+
+linkDown NOTIFICATION-TYPE
+    OBJECTS { ifIndex, ifAdminStatus, ifOperStatus }
+    STATUS  current
+    ::= { snmpTraps 3 }
+
+END
+"#;
+
+    #[test]
+    fn resolves_oids() {
+        let tree = MibTree::new(vec![IF_MIB]);
+        let resolve = |arcs: &[u32]| tree.resolve(&Oid(arcs.to_vec()));
+
+        assert_eq!(
+            resolve(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 3]).as_deref(),
+            Some("IF-MIB::ifIndex.3")
+        );
+        assert_eq!(
+            resolve(&[1, 3, 6, 1, 6, 3, 1, 1, 5, 3]).as_deref(),
+            Some("IF-MIB::linkDown")
+        );
+        assert_eq!(
+            resolve(&[1, 3, 6, 1, 2, 1, 31, 1]).as_deref(),
+            Some("IF-MIB::ifMIBObjects")
+        );
+        assert_eq!(
+            resolve(&[1, 3, 6, 1, 4, 1, 8072]).as_deref(),
+            Some("SNMPv2-SMI::enterprises.8072")
+        );
+        assert_eq!(resolve(&[2, 5]), None);
+    }
+
+    #[test]
+    fn resolves_definitions_across_modules() {
+        let enterprise = r#"
+EXAMPLE-MIB DEFINITIONS ::= BEGIN
+exampleTraps OBJECT IDENTIFIER ::= { example 0 }
+example OBJECT IDENTIFIER ::= { enterprises 32473 }
+exampleAlarm TRAP-TYPE
+    ENTERPRISE example
+    VARIABLES { ifIndex }
+    DESCRIPTION "An alarm."
+    ::= 7
+END
+"#;
+        let tree = MibTree::new(vec![enterprise, IF_MIB]);
+        assert_eq!(
+            tree.resolve(&Oid(vec![1, 3, 6, 1, 4, 1, 32473, 0, 7])),
+            Some("EXAMPLE-MIB::exampleAlarm".into())
+        );
+    }
+
+    #[test]
+    fn tokenizes_comments_and_strings() {
+        assert_eq!(
+            tokenize("a -- comment -- b \"c ::= { d }\" e-f -- rest\ng{h}"),
+            vec!["a", "b", "e-f", "g", "{", "h", "}"]
+        );
+    }
+}
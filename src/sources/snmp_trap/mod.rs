@@ -0,0 +1,495 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, Resource, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{Event, LogEvent, Value},
+    internal_events::{
+        SnmpTrapAuthenticationFailed, SnmpTrapEventReceived, SnmpTrapParseError,
+        SnmpTrapResponseFailed,
+    },
+    shutdown::ShutdownSignal,
+    sources::util::bind_udp_socket,
+    Pipeline,
+};
+use bytes::{Bytes, BytesMut};
+use chrono::Utc;
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+};
+
+mod ber;
+mod message;
+mod mib;
+mod usm;
+
+use ber::Oid;
+pub use message::SnmpError;
+use message::{Trap, VarBindValue};
+use mib::MibTree;
+use usm::{SnmpUserConfig, Usm};
+
+/// The minimum password length of RFC 3414.
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Could not read MIB file {:?}: {}", path, source))]
+    MibFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("User {:?} has privacy configured without authentication", user))]
+    PrivacyWithoutAuth { user: String },
+    #[snafu(display("Passwords of user {:?} must be at least 8 characters long", user))]
+    ShortPassword { user: String },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SnmpTrapConfig {
+    address: SocketAddr,
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+    host_key: Option<String>,
+    receive_buffer_bytes: Option<usize>,
+    #[serde(default)]
+    communities: Vec<String>,
+    #[serde(default)]
+    users: Vec<SnmpUserConfig>,
+    #[serde(default)]
+    mib_files: Vec<PathBuf>,
+}
+
+const fn default_max_length() -> usize {
+    65_535
+}
+
+inventory::submit! {
+    SourceDescription::new::<SnmpTrapConfig>("snmp_trap")
+}
+
+impl GenerateConfig for SnmpTrapConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(r#"address = "0.0.0.0:162""#).unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "snmp_trap")]
+impl SourceConfig for SnmpTrapConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        self.validate_users()?;
+        let mibs = self.load_mibs()?;
+        let host_key = self
+            .host_key
+            .clone()
+            .unwrap_or_else(|| log_schema().host_key().to_string());
+
+        Ok(Box::pin(snmp_trap_source(
+            self.clone(),
+            host_key,
+            mibs,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "snmp_trap"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::udp(self.address)]
+    }
+}
+
+impl SnmpTrapConfig {
+    fn validate_users(&self) -> Result<(), BuildError> {
+        for user in &self.users {
+            if user.privacy.is_some() && user.auth.is_none() {
+                return Err(BuildError::PrivacyWithoutAuth {
+                    user: user.name.clone(),
+                });
+            }
+            let passwords = user
+                .auth
+                .iter()
+                .map(|auth| &auth.password)
+                .chain(user.privacy.iter().map(|privacy| &privacy.password));
+            for password in passwords {
+                if password.len() < MIN_PASSWORD_LENGTH {
+                    return Err(BuildError::ShortPassword {
+                        user: user.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load_mibs(&self) -> Result<Option<MibTree>, BuildError> {
+        if self.mib_files.is_empty() {
+            return Ok(None);
+        }
+
+        // MIB modules are ASCII, but comments and descriptions in the wild
+        // aren't always.
+        let modules = self
+            .mib_files
+            .iter()
+            .map(|path| {
+                std::fs::read(path)
+                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                    .context(MibFile { path })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(MibTree::new(modules.iter().map(String::as_str))))
+    }
+}
+
+async fn snmp_trap_source(
+    config: SnmpTrapConfig,
+    host_key: String,
+    mibs: Option<MibTree>,
+    mut shutdown: ShutdownSignal,
+    out: Pipeline,
+) -> Result<(), ()> {
+    let mut out = out.sink_map_err(|error| error!(message = "Error sending event.", %error));
+
+    let socket = bind_udp_socket(config.address, config.receive_buffer_bytes)
+        .await
+        .map_err(|error| {
+            error!(message = "Failed to bind to UDP listener socket.", %error);
+        })?;
+
+    info!(message = "Listening.", address = %config.address);
+
+    let mut usm = Usm::new(&config.users);
+    let mut buf = BytesMut::with_capacity(config.max_length);
+    loop {
+        buf.resize(config.max_length, 0);
+        let (byte_size, peer) = tokio::select! {
+            recv = socket.recv_from(&mut buf) => match recv {
+                Ok(recv) => recv,
+                Err(error) => {
+                    error!(message = "Failed to read UDP datagram.", %error);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => return Ok(()),
+        };
+
+        let trap = match message::parse(&buf[..byte_size], &mut usm)
+            .await
+            .and_then(|trap| check_community(&config.communities, trap))
+        {
+            Ok(trap) => trap,
+            Err(error) if error.is_authentication_failure() => {
+                emit!(&SnmpTrapAuthenticationFailed {
+                    error: &error,
+                    peer
+                });
+                continue;
+            }
+            Err(error) => {
+                emit!(&SnmpTrapParseError {
+                    error: &error,
+                    peer
+                });
+                continue;
+            }
+        };
+
+        if let Some(response) = &trap.response {
+            if let Err(error) = socket.send_to(response, peer).await {
+                emit!(&SnmpTrapResponseFailed { error, peer });
+            }
+        }
+
+        emit!(&SnmpTrapEventReceived { byte_size });
+        let event = trap_event(trap, peer.ip(), &host_key, mibs.as_ref());
+        tokio::select! {
+            result = out.send(event) => if result.is_err() {
+                return Ok(());
+            },
+            _ = &mut shutdown => return Ok(()),
+        }
+    }
+}
+
+/// Reject SNMPv1 and SNMPv2c messages of other than the configured
+/// communities, if any are configured.
+fn check_community(communities: &[String], trap: Trap) -> Result<Trap, SnmpError> {
+    match &trap.community {
+        Some(community) if !communities.is_empty() && !communities.contains(community) => {
+            Err(SnmpError::UnknownCommunity {
+                community: community.clone(),
+            })
+        }
+        _ => Ok(trap),
+    }
+}
+
+fn trap_event(trap: Trap, peer: IpAddr, host_key: &str, mibs: Option<&MibTree>) -> Event {
+    let resolve = |oid: &Oid| mibs.and_then(|mibs| mibs.resolve(oid));
+
+    let mut log = LogEvent::default();
+    log.insert("version", trap.version.as_str());
+    log.insert("pdu_type", trap.pdu_type.as_str());
+    if let Some(community) = trap.community {
+        log.insert("community", community);
+    }
+    if let Some(security) = trap.security {
+        log.insert("user", security.user);
+        log.insert("engine_id", hex(&security.engine_id));
+        log.insert("context_engine_id", hex(&security.context_engine_id));
+        log.insert("context_name", security.context_name);
+    }
+    if let Some(request_id) = trap.request_id {
+        log.insert("request_id", request_id);
+    }
+    if let Some(v1) = trap.v1 {
+        log.insert("enterprise", v1.enterprise.to_string());
+        log.insert("agent_address", v1.agent_address.to_string());
+        log.insert("generic_trap", i64::from(v1.generic_trap));
+        log.insert("specific_trap", i64::from(v1.specific_trap));
+    }
+    if let Some(uptime) = trap.uptime {
+        log.insert("uptime", unsigned(uptime));
+    }
+    if let Some(trap_oid) = trap.trap_oid {
+        if let Some(name) = resolve(&trap_oid) {
+            log.insert("trap_name", name);
+        }
+        log.insert("trap_oid", trap_oid.to_string());
+    }
+
+    let varbinds = trap
+        .varbinds
+        .into_iter()
+        .map(|(oid, value)| {
+            let name = resolve(&oid).unwrap_or_else(|| oid.to_string());
+            (name, varbind_value(value))
+        })
+        .collect::<BTreeMap<_, _>>();
+    log.insert("varbinds", Value::Map(varbinds));
+
+    log.try_insert(log_schema().source_type_key(), Bytes::from("snmp_trap"));
+    log.try_insert(log_schema().timestamp_key(), Utc::now());
+    log.try_insert(host_key, peer.to_string());
+    Event::Log(log)
+}
+
+fn varbind_value(value: VarBindValue) -> Value {
+    match value {
+        VarBindValue::Integer(value) => Value::Integer(value),
+        VarBindValue::Unsigned(value) => unsigned(value),
+        // Octet strings are mostly text, but also carry addresses and other
+        // binary data, which is kept as hex.
+        VarBindValue::OctetString(bytes) => match std::str::from_utf8(&bytes) {
+            Ok(text)
+                if !text
+                    .trim_end_matches('\0')
+                    .chars()
+                    .any(|c| c.is_control() && !c.is_whitespace()) =>
+            {
+                text.trim_end_matches('\0').into()
+            }
+            _ => hex(&bytes).into(),
+        },
+        VarBindValue::ObjectIdentifier(oid) => oid.to_string().into(),
+        VarBindValue::IpAddress(address) => address.to_string().into(),
+        VarBindValue::Opaque(bytes) => hex(&bytes).into(),
+        VarBindValue::Null => Value::Null,
+    }
+}
+
+/// Counter64 values can exceed the range of an integer, those are kept exact
+/// as strings.
+fn unsigned(value: u64) -> Value {
+    i64::try_from(value)
+        .map(Value::Integer)
+        .unwrap_or_else(|_| value.to_string().into())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::message::tests::{integer, notification, oid, sequence, tlv};
+    use super::*;
+    use crate::test_util::{collect_ready, next_addr, trace_init};
+    use std::time::Duration;
+    use tokio::net::UdpSocket;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<SnmpTrapConfig>();
+    }
+
+    #[test]
+    fn validates_users() {
+        let config: SnmpTrapConfig = toml::from_str(
+            r#"
+            address = "0.0.0.0:162"
+
+            [[users]]
+            name = "vector"
+            auth.protocol = "sha"
+            auth.password = "authpassword"
+            privacy.protocol = "aes"
+            privacy.password = "privpassword"
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate_users().is_ok());
+
+        let config: SnmpTrapConfig = toml::from_str(
+            r#"
+            address = "0.0.0.0:162"
+
+            [[users]]
+            name = "vector"
+            privacy.protocol = "des"
+            privacy.password = "privpassword"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.validate_users(),
+            Err(BuildError::PrivacyWithoutAuth { .. })
+        ));
+
+        let config: SnmpTrapConfig = toml::from_str(
+            r#"
+            address = "0.0.0.0:162"
+
+            [[users]]
+            name = "vector"
+            auth.protocol = "md5"
+            auth.password = "short"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.validate_users(),
+            Err(BuildError::ShortPassword { .. })
+        ));
+    }
+
+    #[test]
+    fn converts_varbind_values() {
+        assert_eq!(
+            varbind_value(VarBindValue::OctetString(b"eth0\0".to_vec())),
+            Value::from("eth0")
+        );
+        assert_eq!(
+            varbind_value(VarBindValue::OctetString(vec![0, 0x1b, 0x21, 0x3c])),
+            Value::from("001b213c")
+        );
+        assert_eq!(
+            varbind_value(VarBindValue::Unsigned(u64::MAX)),
+            Value::from("18446744073709551615")
+        );
+    }
+
+    fn config(address: SocketAddr, communities: Vec<String>) -> SnmpTrapConfig {
+        SnmpTrapConfig {
+            address,
+            max_length: default_max_length(),
+            host_key: None,
+            receive_buffer_bytes: None,
+            communities,
+            users: Vec::new(),
+            mib_files: Vec::new(),
+        }
+    }
+
+    fn link_down(tag: u8) -> Vec<u8> {
+        let varbind = sequence(&[
+            oid(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 3]),
+            tlv(ber::OCTET_STRING, b"eth0"),
+        ]);
+        notification(tag, &[varbind])
+    }
+
+    fn v2c_message(community: &str, pdu: Vec<u8>) -> Vec<u8> {
+        sequence(&[
+            integer(1),
+            tlv(ber::OCTET_STRING, community.as_bytes()),
+            pdu,
+        ])
+    }
+
+    #[tokio::test]
+    async fn receives_traps_and_answers_informs() {
+        trace_init();
+
+        let address = next_addr();
+        let (tx, rx) = Pipeline::new_test();
+        let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+        tokio::spawn(snmp_trap_source(
+            config(address, vec!["public".into()]),
+            "host".into(),
+            None,
+            shutdown,
+            tx,
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        for message in &[
+            v2c_message("public", link_down(0xa7)),
+            v2c_message("private", link_down(0xa7)),
+            v2c_message("public", link_down(0xa6)),
+        ] {
+            socket.send_to(message, address).await.unwrap();
+        }
+
+        let mut response = [0; 1024];
+        let (length, _) =
+            tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut response))
+                .await
+                .unwrap()
+                .unwrap();
+        let mut response_pdu = link_down(0xa6);
+        response_pdu[0] = 0xa2;
+        assert_eq!(
+            &response[..length],
+            &v2c_message("public", response_pdu)[..]
+        );
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(trigger);
+
+        let events = collect_ready(rx).await;
+        assert_eq!(events.len(), 2);
+        let log = events[0].as_log();
+        assert_eq!(log["version"], "2c".into());
+        assert_eq!(log["pdu_type"], "trap".into());
+        assert_eq!(log["community"], "public".into());
+        assert_eq!(log["trap_oid"], "1.3.6.1.6.3.1.1.5.3".into());
+        assert_eq!(log["uptime"], 256.into());
+        assert_eq!(log["host"], "127.0.0.1".into());
+        assert_eq!(log[log_schema().source_type_key()], "snmp_trap".into());
+        match &log["varbinds"] {
+            Value::Map(varbinds) => {
+                assert_eq!(varbinds["1.3.6.1.2.1.2.2.1.2.3"], "eth0".into());
+            }
+            value => panic!("unexpected varbinds {:?}", value),
+        }
+        assert_eq!(events[1].as_log()["pdu_type"], "inform".into());
+    }
+}
@@ -0,0 +1,523 @@
+use super::{
+    ber::BerError,
+    message::{Crypto, SnmpError},
+};
+use lru::LruCache;
+use openssl::{
+    error::ErrorStack,
+    hash::{Hasher, MessageDigest},
+    memcmp,
+    pkey::PKey,
+    sign::Signer,
+    symm::{Cipher, Crypter, Mode},
+};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::{borrow::Cow, collections::HashMap, time::Instant};
+
+/// The length of the truncated HMAC of HMAC-MD5-96 and HMAC-SHA-96.
+const DIGEST_LENGTH: usize = 12;
+/// The amount of repeated password that is hashed into a key.
+const PASSWORD_EXPANSION_LENGTH: usize = 1_048_576;
+/// The number of engines the keys of a user are kept for.
+const MAX_ENGINES_PER_USER: usize = 1_000;
+/// The number of engines whose boots and time are kept.
+const MAX_ENGINES: usize = 10_000;
+/// How far messages may lag behind the time of their engine.
+const TIME_WINDOW_SECS: u64 = 150;
+/// Engines that booted this often have to be reconfigured.
+const MAX_ENGINE_BOOTS: u32 = 2_147_483_647;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnmpUserConfig {
+    pub name: String,
+    pub auth: Option<SnmpAuthConfig>,
+    pub privacy: Option<SnmpPrivacyConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnmpAuthConfig {
+    pub protocol: AuthProtocol,
+    pub password: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnmpPrivacyConfig {
+    pub protocol: PrivacyProtocol,
+    pub password: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthProtocol {
+    Md5,
+    Sha,
+}
+
+impl AuthProtocol {
+    fn digest(self) -> MessageDigest {
+        match self {
+            Self::Md5 => MessageDigest::md5(),
+            Self::Sha => MessageDigest::sha1(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyProtocol {
+    Des,
+    Aes,
+}
+
+/// The USM security parameters of an SNMPv3 message.
+#[derive(Debug)]
+pub struct SecurityParameters<'a> {
+    pub engine_id: &'a [u8],
+    pub engine_boots: u32,
+    pub engine_time: u32,
+    pub user: String,
+    pub auth_parameters: &'a [u8],
+    /// The position of `auth_parameters` in the whole message.
+    pub auth_offset: usize,
+    pub privacy_parameters: &'a [u8],
+}
+
+#[derive(Debug)]
+pub enum ScopedPduData<'a> {
+    Plaintext(&'a [u8]),
+    Encrypted(&'a [u8]),
+}
+
+#[derive(Clone)]
+struct LocalizedKeys {
+    auth: Vec<u8>,
+    privacy: Option<Vec<u8>>,
+}
+
+/// The notion of the boots and time of an authoritative engine, learned from
+/// its authenticated messages.
+#[derive(Clone, Copy, Debug)]
+struct EngineClock {
+    boots: u32,
+    /// The latest engine time received.
+    time: u32,
+    received: Instant,
+}
+
+impl EngineClock {
+    /// The engine time now, as estimated from the latest engine time
+    /// received.
+    fn now(&self, now: Instant) -> u64 {
+        u64::from(self.time) + now.saturating_duration_since(self.received).as_secs()
+    }
+}
+
+/// The User-based Security Model of RFC 3414, on the receiving side of
+/// notifications. Notification senders are authoritative, so the keys of a
+/// user are localized to every engine it sends from and kept once a message
+/// from that engine has been authenticated.
+pub struct Usm {
+    users: HashMap<String, SnmpUserConfig>,
+    keys: HashMap<String, LruCache<Vec<u8>, LocalizedKeys>>,
+    clocks: LruCache<Vec<u8>, EngineClock>,
+}
+
+impl Usm {
+    pub fn new(users: &[SnmpUserConfig]) -> Self {
+        Self {
+            users: users
+                .iter()
+                .map(|user| (user.name.clone(), user.clone()))
+                .collect(),
+            keys: users
+                .iter()
+                .map(|user| (user.name.clone(), LruCache::new(MAX_ENGINES_PER_USER)))
+                .collect(),
+            clocks: LruCache::new(MAX_ENGINES),
+        }
+    }
+
+    /// Verify the digest and timeliness of an authenticated message and
+    /// decrypt its scoped PDU if it is encrypted, checking that both match
+    /// the configured security level of the user.
+    pub async fn process<'a>(
+        &mut self,
+        message: &[u8],
+        params: &SecurityParameters<'_>,
+        authenticated: bool,
+        data: ScopedPduData<'a>,
+    ) -> Result<Cow<'a, [u8]>, SnmpError> {
+        let user = self
+            .users
+            .get(&params.user)
+            .ok_or_else(|| SnmpError::UnknownUser {
+                user: params.user.clone(),
+            })?;
+
+        let encrypted = matches!(data, ScopedPduData::Encrypted(_));
+        if authenticated != user.auth.is_some() || encrypted != user.privacy.is_some() {
+            return Err(SnmpError::SecurityLevelMismatch {
+                user: params.user.clone(),
+            });
+        }
+        let auth = match &user.auth {
+            Some(auth) => auth.clone(),
+            None => match data {
+                ScopedPduData::Plaintext(data) => return Ok(Cow::Borrowed(data)),
+                ScopedPduData::Encrypted(_) => unreachable!("privacy requires authentication"),
+            },
+        };
+        let privacy = user.privacy.clone();
+
+        if params.auth_parameters.len() != DIGEST_LENGTH {
+            return Err(BerError::InvalidEncoding {
+                context: "authentication parameters",
+            }
+            .into());
+        }
+
+        let user_keys = self
+            .keys
+            .get_mut(&params.user)
+            .expect("keys are kept for every user");
+        let cached = user_keys.get(params.engine_id).cloned();
+        let keys = match cached.clone() {
+            Some(keys) => keys,
+            None => {
+                // Localizing hashes a megabyte, so it is kept off the runtime.
+                let engine_id = params.engine_id.to_vec();
+                let privacy = privacy.as_ref().map(|privacy| privacy.password.clone());
+                let auth = auth.clone();
+                tokio::task::spawn_blocking(move || {
+                    localize_keys(&auth, privacy.as_deref(), &engine_id)
+                })
+                .await
+                .expect("localizing keys panicked")
+                .context(Crypto)?
+            }
+        };
+
+        let mut zeroed = message.to_vec();
+        zeroed[params.auth_offset..params.auth_offset + DIGEST_LENGTH].fill(0);
+        let digest = digest(auth.protocol, &keys.auth, &zeroed).context(Crypto)?;
+        if !memcmp::eq(&digest, params.auth_parameters) {
+            return Err(SnmpError::WrongDigest {
+                user: params.user.clone(),
+            });
+        }
+        // Only keys that authenticated a message are kept, so that messages
+        // claiming arbitrary engines can't fill the cache.
+        if cached.is_none() {
+            self.keys
+                .get_mut(&params.user)
+                .expect("keys are kept for every user")
+                .put(params.engine_id.to_vec(), keys.clone());
+        }
+
+        self.check_timeliness(params, Instant::now())?;
+
+        match (data, &privacy, &keys.privacy) {
+            (ScopedPduData::Encrypted(data), Some(privacy), Some(key)) => {
+                decrypt(privacy.protocol, key, params, data).map(Cow::Owned)
+            }
+            (ScopedPduData::Plaintext(data), _, _) => Ok(Cow::Borrowed(data)),
+            _ => unreachable!("privacy keys are localized for users with privacy"),
+        }
+    }
+
+    /// Check that an authenticated message is within the time window of its
+    /// engine, as described in section 3.2 step 7b of RFC 3414 for receivers
+    /// that aren't authoritative. Later boots and times of the engine are
+    /// learned from the message before the check.
+    fn check_timeliness(
+        &mut self,
+        params: &SecurityParameters<'_>,
+        now: Instant,
+    ) -> Result<(), SnmpError> {
+        let latest = self.clocks.get(params.engine_id).copied();
+        let clock = match latest {
+            Some(clock)
+                if params.engine_boots < clock.boots
+                    || (params.engine_boots == clock.boots && params.engine_time <= clock.time) =>
+            {
+                clock
+            }
+            _ => {
+                let clock = EngineClock {
+                    boots: params.engine_boots,
+                    time: params.engine_time,
+                    received: now,
+                };
+                self.clocks.put(params.engine_id.to_vec(), clock);
+                clock
+            }
+        };
+
+        let outdated = params.engine_boots < clock.boots
+            || (params.engine_boots == clock.boots
+                && u64::from(params.engine_time) + TIME_WINDOW_SECS < clock.now(now));
+        if clock.boots == MAX_ENGINE_BOOTS || outdated {
+            return Err(SnmpError::NotInTimeWindow {
+                user: params.user.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn localize_keys(
+    auth: &SnmpAuthConfig,
+    privacy_password: Option<&str>,
+    engine_id: &[u8],
+) -> Result<LocalizedKeys, ErrorStack> {
+    Ok(LocalizedKeys {
+        auth: localize_key(auth.protocol, &auth.password, engine_id)?,
+        privacy: privacy_password
+            .map(|password| localize_key(auth.protocol, password, engine_id))
+            .transpose()?,
+    })
+}
+
+/// Convert a password into a key localized to an engine, as described in
+/// appendix A.2 of RFC 3414.
+fn localize_key(
+    protocol: AuthProtocol,
+    password: &str,
+    engine_id: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let password = password.as_bytes();
+    let mut hasher = Hasher::new(protocol.digest())?;
+    let mut block = [0; 64];
+    let mut index = 0;
+    for _ in 0..PASSWORD_EXPANSION_LENGTH / block.len() {
+        for byte in block.iter_mut() {
+            *byte = password[index % password.len()];
+            index += 1;
+        }
+        hasher.update(&block)?;
+    }
+    let key = hasher.finish()?;
+
+    let mut hasher = Hasher::new(protocol.digest())?;
+    hasher.update(&key)?;
+    hasher.update(engine_id)?;
+    hasher.update(&key)?;
+    Ok(hasher.finish()?.to_vec())
+}
+
+fn digest(protocol: AuthProtocol, key: &[u8], message: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(protocol.digest(), &key)?;
+    signer.update(message)?;
+    let mut digest = signer.sign_to_vec()?;
+    digest.truncate(DIGEST_LENGTH);
+    Ok(digest)
+}
+
+/// Decrypt a scoped PDU with CBC-DES (RFC 3414) or CFB128-AES-128 (RFC 3826).
+fn decrypt(
+    protocol: PrivacyProtocol,
+    key: &[u8],
+    params: &SecurityParameters<'_>,
+    data: &[u8],
+) -> Result<Vec<u8>, SnmpError> {
+    let salt = params.privacy_parameters;
+    if salt.len() != 8 {
+        return Err(BerError::InvalidEncoding {
+            context: "privacy parameters",
+        }
+        .into());
+    }
+
+    let (cipher, key, iv) = match protocol {
+        PrivacyProtocol::Des => {
+            if data.len() % 8 != 0 {
+                return Err(BerError::InvalidEncoding {
+                    context: "encrypted scoped PDU",
+                }
+                .into());
+            }
+            let iv = key[8..16]
+                .iter()
+                .zip(salt)
+                .map(|(pre_iv, salt)| pre_iv ^ salt)
+                .collect::<Vec<_>>();
+            (Cipher::des_cbc(), &key[..8], iv)
+        }
+        PrivacyProtocol::Aes => {
+            let mut iv = Vec::with_capacity(16);
+            iv.extend(&params.engine_boots.to_be_bytes());
+            iv.extend(&params.engine_time.to_be_bytes());
+            iv.extend(salt);
+            (Cipher::aes_128_cfb128(), &key[..16], iv)
+        }
+    };
+
+    let decrypt = || -> Result<Vec<u8>, ErrorStack> {
+        let mut crypter = Crypter::new(cipher, Mode::Decrypt, key, Some(&iv))?;
+        crypter.pad(false);
+        let mut plaintext = vec![0; data.len() + cipher.block_size()];
+        let mut length = crypter.update(data, &mut plaintext)?;
+        length += crypter.finalize(&mut plaintext[length..])?;
+        plaintext.truncate(length);
+        Ok(plaintext)
+    };
+    decrypt().context(Crypto)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // The key localization samples of appendix A.3 of RFC 3414.
+    const ENGINE_ID: [u8; 12] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+
+    #[test]
+    fn localizes_md5_keys() {
+        let key = localize_key(AuthProtocol::Md5, "maplesyrup", &ENGINE_ID).unwrap();
+        assert_eq!(
+            key,
+            [
+                0x52, 0x6f, 0x5e, 0xed, 0x9f, 0xcc, 0xe2, 0x6f, 0x89, 0x64, 0xc2, 0x93, 0x07, 0x87,
+                0xd8, 0x2b
+            ]
+        );
+    }
+
+    #[test]
+    fn localizes_sha_keys() {
+        let key = localize_key(AuthProtocol::Sha, "maplesyrup", &ENGINE_ID).unwrap();
+        assert_eq!(
+            key,
+            [
+                0x66, 0x95, 0xfe, 0xbc, 0x92, 0x88, 0xe3, 0x62, 0x82, 0x23, 0x5f, 0xc7, 0x15, 0x1f,
+                0x12, 0x84, 0x97, 0xb3, 0x8f, 0x3f
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn verifies_digests() {
+        use super::super::message::{
+            self,
+            tests::{scoped_pdu, v3_message},
+        };
+
+        let mut usm = Usm::new(&[SnmpUserConfig {
+            name: "vector".into(),
+            auth: Some(SnmpAuthConfig {
+                protocol: AuthProtocol::Md5,
+                password: "maplesyrup".into(),
+            }),
+            privacy: None,
+        }]);
+        let mut data = v3_message(1, "vector", &[0; DIGEST_LENGTH], scoped_pdu());
+        let position = data
+            .windows(DIGEST_LENGTH)
+            .position(|window| window == [0; DIGEST_LENGTH])
+            .unwrap();
+        let key = localize_key(AuthProtocol::Md5, "maplesyrup", &[0x80, 0, 0x1f, 0x88, 4]).unwrap();
+        let digest = digest(AuthProtocol::Md5, &key, &data).unwrap();
+        data[position..position + DIGEST_LENGTH].copy_from_slice(&digest);
+        assert!(message::parse(&data, &mut usm).await.is_ok());
+        assert_eq!(usm.keys["vector"].len(), 1);
+
+        data[position] ^= 1;
+        assert!(matches!(
+            message::parse(&data, &mut usm).await,
+            Err(SnmpError::WrongDigest { .. })
+        ));
+
+        // The security level must match the configuration of the user.
+        let data = v3_message(0, "vector", &[], scoped_pdu());
+        assert!(matches!(
+            message::parse(&data, &mut usm).await,
+            Err(SnmpError::SecurityLevelMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn keeps_only_verified_keys() {
+        use super::super::message::{
+            self,
+            tests::{scoped_pdu, v3_message},
+        };
+
+        let mut usm = Usm::new(&[SnmpUserConfig {
+            name: "vector".into(),
+            auth: Some(SnmpAuthConfig {
+                protocol: AuthProtocol::Md5,
+                password: "maplesyrup".into(),
+            }),
+            privacy: None,
+        }]);
+        let data = v3_message(1, "vector", &[0; DIGEST_LENGTH], scoped_pdu());
+        assert!(matches!(
+            message::parse(&data, &mut usm).await,
+            Err(SnmpError::WrongDigest { .. })
+        ));
+        assert_eq!(usm.keys["vector"].len(), 0);
+    }
+
+    #[test]
+    fn checks_timeliness() {
+        let mut usm = Usm::new(&[]);
+        let params = |engine_boots, engine_time| SecurityParameters {
+            engine_id: &ENGINE_ID,
+            engine_boots,
+            engine_time,
+            user: "vector".into(),
+            auth_parameters: &[],
+            auth_offset: 0,
+            privacy_parameters: &[],
+        };
+        let start = Instant::now();
+        let later = start + Duration::from_secs(200);
+        let in_window = |usm: &mut Usm, boots, time, now| {
+            usm.check_timeliness(&params(boots, time), now).is_ok()
+        };
+
+        // The first message sets the clock of the engine.
+        assert!(in_window(&mut usm, 2, 1000, start));
+        // Messages may lag behind by up to 150 seconds.
+        assert!(in_window(&mut usm, 2, 900, start));
+        assert!(!in_window(&mut usm, 2, 849, start));
+        assert!(!in_window(&mut usm, 2, 1000, later));
+        // Later times of the engine are learned.
+        assert!(in_window(&mut usm, 2, 1001, later));
+        assert!(in_window(&mut usm, 2, 900, later));
+        // Earlier boots are rejected, later ones reset the clock.
+        assert!(!in_window(&mut usm, 1, 5000, later));
+        assert!(in_window(&mut usm, 3, 10, later));
+        assert!(!in_window(&mut usm, 2, 1200, later));
+        // Engines that reached the maximum boots are never in time.
+        assert!(!in_window(&mut usm, MAX_ENGINE_BOOTS, 0, later));
+    }
+
+    #[test]
+    fn decrypts_aes() {
+        let key = [7; 16];
+        let params = SecurityParameters {
+            engine_id: &ENGINE_ID,
+            engine_boots: 1,
+            engine_time: 2,
+            user: "vector".into(),
+            auth_parameters: &[],
+            auth_offset: 0,
+            privacy_parameters: &[3; 8],
+        };
+        let mut iv = vec![0, 0, 0, 1, 0, 0, 0, 2];
+        iv.extend(&[3; 8]);
+        let encrypted =
+            openssl::symm::encrypt(Cipher::aes_128_cfb128(), &key, Some(&iv), b"scoped pdu")
+                .unwrap();
+
+        let plaintext = decrypt(PrivacyProtocol::Aes, &key, &params, &encrypted).unwrap();
+        assert_eq!(plaintext, b"scoped pdu");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::event::BatchStatusReceiver;
+use crate::event::{BatchStatus, BatchStatusReceiver};
 use crate::shutdown::ShutdownSignal;
 use futures::{future::Shared, stream::FuturesOrdered, FutureExt, StreamExt};
 use std::future::Future;
@@ -19,6 +19,15 @@ impl<T: Send + 'static> OrderedFinalizer<T> {
     pub(crate) fn new(
         shutdown: Shared<ShutdownSignal>,
         apply_done: impl Fn(T) + Send + 'static,
+    ) -> Self {
+        Self::with_status(shutdown, move |_status, entry| apply_done(entry))
+    }
+
+    /// Like `new`, but `apply_done` is also given the delivery status of
+    /// the entry, for sources that report failures back upstream.
+    pub(crate) fn with_status(
+        shutdown: Shared<ShutdownSignal>,
+        apply_done: impl Fn(BatchStatus, T) + Send + 'static,
     ) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
         tokio::spawn(run_finalizer(shutdown, receiver, apply_done));
@@ -39,7 +48,7 @@ impl<T: Send + 'static> OrderedFinalizer<T> {
 async fn run_finalizer<T>(
     shutdown: Shared<ShutdownSignal>,
     mut new_entries: mpsc::UnboundedReceiver<(BatchStatusReceiver, T)>,
-    apply_done: impl Fn(T),
+    apply_done: impl Fn(BatchStatus, T),
 ) {
     let mut status_receivers = FuturesOrdered::default();
 
@@ -56,7 +65,7 @@ async fn run_finalizer<T>(
                 None => break,
             },
             finished = status_receivers.next(), if !status_receivers.is_empty() => match finished {
-                Some((_status, entry)) => apply_done(entry),
+                Some((status, entry)) => apply_done(status, entry),
                 // The is_empty guard above prevents this from being reachable.
                 None => unreachable!(),
             },
@@ -65,8 +74,8 @@ async fn run_finalizer<T>(
     // We've either seen a shutdown signal or the new entry sender was
     // closed. Wait for the last statuses to come in before indicating
     // we are done.
-    while let Some((_status, entry)) = status_receivers.next().await {
-        apply_done(entry);
+    while let Some((status, entry)) = status_receivers.next().await {
+        apply_done(status, entry);
     }
     drop(shutdown);
 }
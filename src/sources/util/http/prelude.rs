@@ -32,6 +32,20 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
         path: &str,
     ) -> Result<Vec<Event>, ErrorMessage>;
 
+    /// Check the request is allowed, before its body is decoded.
+    fn authorize(
+        &self,
+        _header_map: &HeaderMap,
+        _query_parameters: &HashMap<String, String>,
+    ) -> Result<(), ErrorMessage> {
+        Ok(())
+    }
+
+    /// The status of the responses to requests whose events were accepted.
+    fn success_status(&self) -> StatusCode {
+        StatusCode::OK
+    }
+
     fn run(
         self,
         address: SocketAddr,
@@ -46,6 +60,7 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
         let protocol = tls.http_protocol_name();
         let auth = HttpSourceAuth::try_from(auth.as_ref())?;
         let path = path.to_owned();
+        let success_status = self.success_status();
         Ok(Box::pin(async move {
             let span = crate::trace::current_span();
             let mut filter: BoxedFilter<()> = warp::post().boxed();
@@ -89,6 +104,7 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
 
                         let events = auth
                             .is_valid(&auth_header)
+                            .and_then(|()| self.authorize(&headers, &query_parameters))
                             .and_then(|()| decode(&encoding_header, body))
                             .and_then(|body| {
                                 self.build_events(body, headers, query_parameters, path.as_str())
@@ -103,7 +119,12 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
                                 events
                             });
 
-                        handle_request(events, acknowledgements.enabled, cx.out.clone())
+                        handle_request(
+                            events,
+                            acknowledgements.enabled,
+                            success_status,
+                            cx.out.clone(),
+                        )
                     },
                 )
                 .with(warp::trace(move |_info| span.clone()));
@@ -146,6 +167,7 @@ impl warp::reject::Reject for RejectShuttingDown {}
 async fn handle_request(
     events: Result<Vec<Event>, ErrorMessage>,
     acknowledgements: bool,
+    success_status: StatusCode,
     mut out: Pipeline,
 ) -> Result<impl warp::Reply, Rejection> {
     match events {
@@ -160,7 +182,7 @@ async fn handle_request(
                     error!(message = "Tried to send the following event.", %error);
                     warp::reject::custom(RejectShuttingDown)
                 })
-                .and_then(|_| handle_batch_status(receiver))
+                .and_then(|_| handle_batch_status(receiver, success_status))
                 .await
         }
         Err(error) => {
@@ -175,11 +197,12 @@ async fn handle_request(
 
 async fn handle_batch_status(
     receiver: Option<BatchStatusReceiver>,
+    success_status: StatusCode,
 ) -> Result<impl warp::Reply, Rejection> {
     match receiver {
-        None => Ok(warp::reply()),
+        None => Ok(warp::reply::with_status(warp::reply(), success_status)),
         Some(receiver) => match receiver.await {
-            BatchStatus::Delivered => Ok(warp::reply()),
+            BatchStatus::Delivered => Ok(warp::reply::with_status(warp::reply(), success_status)),
             BatchStatus::Errored => Err(warp::reject::custom(ErrorMessage::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Error delivering contents to sink".into(),
@@ -25,6 +25,8 @@ mod tcp;
     feature = "codecs",
 ))]
 mod tcp_error;
+#[cfg(feature = "sources-utils-udp")]
+mod udp;
 #[cfg(all(unix, feature = "sources-socket"))]
 mod unix_datagram;
 #[cfg(all(unix, feature = "sources-utils-unix"))]
@@ -56,6 +58,8 @@ pub use tcp::{SocketListenAddr, TcpSource};
     feature = "codecs",
 ))]
 pub use tcp_error::TcpError;
+#[cfg(feature = "sources-utils-udp")]
+pub use udp::bind_udp_socket;
 #[cfg(all(unix, feature = "sources-socket",))]
 pub use unix_datagram::build_unix_datagram_source;
 #[cfg(all(unix, feature = "sources-utils-unix",))]
@@ -0,0 +1,20 @@
+use crate::udp;
+use std::{io, net::SocketAddr};
+use tokio::net::UdpSocket;
+
+/// Bind the socket of a UDP based source, applying the configured receive
+/// buffer size.
+pub async fn bind_udp_socket(
+    address: SocketAddr,
+    receive_buffer_bytes: Option<usize>,
+) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(&address).await?;
+
+    if let Some(receive_buffer_bytes) = receive_buffer_bytes {
+        if let Err(error) = udp::set_receive_buffer_size(&socket, receive_buffer_bytes) {
+            warn!(message = "Failed configuring receive buffer size on UDP socket.", %error);
+        }
+    }
+
+    Ok(socket)
+}
@@ -0,0 +1,307 @@
+use crate::{
+    codecs::{self, DecodingConfig, FramingConfig, ParserConfig},
+    config::{
+        log_schema, DataType, GenerateConfig, Resource, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::Event,
+    internal_events::{
+        ConnectionOpen, OpenGauge, WebSocketConnectionError, WebSocketConnectionEstablished,
+        WebSocketConnectionShutdown, WebSocketEventsReceived,
+    },
+    serde::{default_decoding, default_framing_message_based},
+    shutdown::ShutdownSignal,
+    tls::{MaybeTlsIncomingStream, MaybeTlsListener, MaybeTlsSettings, TlsConfig},
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_util::codec::FramedRead;
+use tracing_futures::Instrument;
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketSourceConfig {
+    #[derivative(Default(value = "default_address()"))]
+    address: SocketAddr,
+    tls: Option<TlsConfig>,
+    #[serde(default = "default_framing_message_based")]
+    #[derivative(Default(value = "default_framing_message_based()"))]
+    framing: Box<dyn FramingConfig>,
+    #[serde(default = "default_decoding")]
+    #[derivative(Default(value = "default_decoding()"))]
+    decoding: Box<dyn ParserConfig>,
+}
+
+fn default_address() -> SocketAddr {
+    "0.0.0.0:8080".parse().unwrap()
+}
+
+inventory::submit! {
+    SourceDescription::new::<WebSocketSourceConfig>("websocket")
+}
+
+impl GenerateConfig for WebSocketSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self::default()).unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "websocket")]
+impl SourceConfig for WebSocketSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build()?;
+        let tls = MaybeTlsSettings::from_config(&self.tls, true)?;
+        let listener = tls.bind(&self.address).await?;
+
+        Ok(Box::pin(websocket_source(
+            listener,
+            decoder,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "websocket"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::tcp(self.address)]
+    }
+}
+
+async fn websocket_source(
+    listener: MaybeTlsListener,
+    decoder: codecs::Decoder,
+    shutdown: ShutdownSignal,
+    out: Pipeline,
+) -> Result<(), ()> {
+    let mut connections = listener.accept_stream().take_until(shutdown.clone());
+    while let Some(connection) = connections.next().await {
+        match connection {
+            Ok(stream) => {
+                let peer_addr = stream.peer_addr();
+                let span = info_span!("connection", %peer_addr);
+                tokio::spawn(
+                    handle_connection(
+                        stream,
+                        peer_addr,
+                        decoder.clone(),
+                        shutdown.clone(),
+                        out.clone(),
+                    )
+                    .instrument(span),
+                );
+            }
+            Err(error) => emit!(&WebSocketConnectionError { error }),
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_connection(
+    stream: MaybeTlsIncomingStream<TcpStream>,
+    peer_addr: SocketAddr,
+    decoder: codecs::Decoder,
+    shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) {
+    let mut ws = match accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(error) => {
+            emit!(&WebSocketConnectionError { error });
+            return;
+        }
+    };
+    emit!(&WebSocketConnectionEstablished {
+        peer_addr: Some(peer_addr)
+    });
+    let _open_token = OpenGauge::new().open(|count| emit!(&ConnectionOpen { count }));
+
+    let host = Bytes::from(peer_addr.ip().to_string());
+    futures::pin_mut!(shutdown);
+    loop {
+        let message = tokio::select! {
+            _ = &mut shutdown => {
+                let _ = ws.close(None).await;
+                break;
+            }
+            message = ws.next() => message,
+        };
+
+        let result = match message {
+            Some(Ok(Message::Text(text))) => {
+                handle_message(text.as_bytes(), peer_addr, &host, &decoder, &mut out).await
+            }
+            Some(Ok(Message::Binary(data))) => {
+                handle_message(&data, peer_addr, &host, &decoder, &mut out).await
+            }
+            Some(Ok(Message::Close(_))) | None => {
+                emit!(&WebSocketConnectionShutdown);
+                break;
+            }
+            // Pings are answered by the stream itself.
+            Some(Ok(_)) => Ok(()),
+            Some(Err(error)) => {
+                emit!(&WebSocketConnectionError { error });
+                break;
+            }
+        };
+        if result.is_err() {
+            break;
+        }
+    }
+}
+
+async fn handle_message(
+    data: &[u8],
+    peer_addr: SocketAddr,
+    host: &Bytes,
+    decoder: &codecs::Decoder,
+    out: &mut Pipeline,
+) -> Result<(), ()> {
+    let now = Utc::now();
+    let mut stream = FramedRead::new(data, decoder.clone());
+    while let Some(next) = stream.next().await {
+        match next {
+            Ok((events, byte_size)) => {
+                emit!(&WebSocketEventsReceived {
+                    byte_size,
+                    count: events.len(),
+                    peer_addr,
+                });
+
+                for mut event in events {
+                    if let Event::Log(ref mut log) = event {
+                        log.try_insert(log_schema().source_type_key(), Bytes::from("websocket"));
+                        log.try_insert(log_schema().timestamp_key(), now);
+                        log.try_insert(log_schema().host_key(), host.clone());
+                    }
+
+                    out.send(event)
+                        .await
+                        .map_err(|error: crate::pipeline::ClosedError| {
+                            error!(message = "Error sending to sink.", %error);
+                        })?;
+                }
+            }
+            Err(error) => {
+                // Error is logged by `crate::codecs::Decoder`, no further
+                // handling is needed here.
+                if !error.can_continue() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_n, next_addr, trace_init, wait_for_tcp};
+    use tokio_tungstenite::client_async;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<WebSocketSourceConfig>();
+    }
+
+    async fn start_source(
+        address: SocketAddr,
+        decoding: &str,
+    ) -> impl futures::Stream<Item = Event> {
+        let (tx, rx) = Pipeline::new_test();
+        let config: WebSocketSourceConfig = toml::from_str(&format!(
+            r#"
+            address = "{}"
+            decoding.codec = "{}"
+            "#,
+            address, decoding
+        ))
+        .unwrap();
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        wait_for_tcp(address).await;
+        rx
+    }
+
+    #[tokio::test]
+    async fn receives_messages() {
+        trace_init();
+
+        let address = next_addr();
+        let rx = start_source(address, "json").await;
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let (mut ws, _) = client_async(format!("ws://{}/", address), stream)
+            .await
+            .unwrap();
+        ws.send(Message::Text(r#"{"message":"text","id":1}"#.into()))
+            .await
+            .unwrap();
+        ws.send(Message::Binary(br#"{"message":"binary","id":2}"#.to_vec()))
+            .await
+            .unwrap();
+        ws.send(Message::Ping(Vec::new())).await.unwrap();
+
+        let events = collect_n(rx, 2).await;
+        let log = events[0].as_log();
+        assert_eq!(log["message"], "text".into());
+        assert_eq!(log["id"], 1.into());
+        assert_eq!(log[log_schema().source_type_key()], "websocket".into());
+        assert_eq!(log[log_schema().host_key()], "127.0.0.1".into());
+        assert_eq!(events[1].as_log()["message"], "binary".into());
+
+        // The ping sent before the events is answered.
+        assert!(matches!(ws.next().await, Some(Ok(Message::Pong(_)))));
+    }
+
+    #[cfg(feature = "sinks-websocket")]
+    #[tokio::test]
+    async fn receives_from_websocket_sink() {
+        use crate::{
+            config::{SinkConfig, SinkContext},
+            sinks::websocket::WebSocketSinkConfig,
+            test_util::random_lines_with_stream,
+        };
+
+        trace_init();
+
+        let address = next_addr();
+        let rx = start_source(address, "bytes").await;
+
+        let config: WebSocketSinkConfig = toml::from_str(&format!(
+            r#"
+            uri = "ws://{}/"
+            encoding.codec = "text"
+            "#,
+            address
+        ))
+        .unwrap();
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let (lines, events) = random_lines_with_stream(10, 20, None);
+        sink.run(events).await.unwrap();
+
+        let received = collect_n(rx, lines.len()).await;
+        let messages = received
+            .iter()
+            .map(|event| event.as_log()["message"].to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, messages);
+    }
+}
@@ -1,7 +1,8 @@
 use super::{
     AddCertToStore, AddExtraChainCert, CaStackPush, DerExportError, FileOpenFailed, FileReadFailed,
-    MaybeTls, NewCaStack, NewStoreBuilder, ParsePkcs12, Pkcs12Error, PrivateKeyParseError, Result,
-    SetCertificate, SetPrivateKey, SetVerifyCert, TlsError, TlsIdentityError, X509ParseError,
+    MaybeTls, NewCaStack, NewStoreBuilder, ParsePkcs12, PemExportError, Pkcs12Error,
+    PrivateKeyParseError, Result, SetCertificate, SetPrivateKey, SetVerifyCert, TlsError,
+    TlsIdentityError, X509ParseError,
 };
 use openssl::{
     pkcs12::{ParsedPkcs12, Pkcs12},
@@ -178,6 +179,42 @@ impl TlsSettings {
     pub fn apply_connect_configuration(&self, connection: &mut ConnectConfiguration) {
         connection.set_verify_hostname(self.verify_hostname);
     }
+
+    /// Export the configured certificate authorities as a single PEM
+    /// bundle, for clients that are not built on OpenSSL.
+    pub fn authorities_pem(&self) -> Result<Vec<u8>> {
+        let mut pem = Vec::new();
+        for authority in &self.authorities {
+            pem.extend(authority.to_pem().context(PemExportError)?);
+        }
+        Ok(pem)
+    }
+
+    /// Export the configured identity as a DER encoded PKCS#12 archive and
+    /// its password, for clients that load the identity themselves.
+    pub fn identity_pkcs12(&self) -> Option<(Vec<u8>, String)> {
+        self.identity
+            .as_ref()
+            .map(|identity| (identity.0.clone(), identity.1.clone()))
+    }
+
+    /// Export the configured identity as a PEM certificate chain and a
+    /// PKCS#8 PEM private key, for clients that are not built on OpenSSL.
+    pub fn identity_pem(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        self.identity()
+            .map(|identity| {
+                let mut cert = identity.cert.to_pem().context(PemExportError)?;
+                for ca in identity.chain.into_iter().flatten() {
+                    cert.extend(ca.to_pem().context(PemExportError)?);
+                }
+                let key = identity
+                    .pkey
+                    .private_key_to_pem_pkcs8()
+                    .context(PemExportError)?;
+                Ok((cert, key))
+            })
+            .transpose()
+    }
 }
 
 impl TlsOptions {
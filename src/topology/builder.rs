@@ -374,6 +374,54 @@ pub async fn build_pieces(
 
                 outputs.insert(OutputId::from(key), control);
 
+                Task::new(key.clone(), typetag, transform)
+            }
+            Transform::FallibleTask(t) => {
+                let (mut output, control) = Fanout::new();
+                let (mut errors_output, errors_control) = Fanout::new();
+
+                let input_rx = crate::utilization::wrap(Pin::new(input_rx));
+
+                let filtered = input_rx
+                    .filter(move |event| ready(filter_event_type(event, input_type)))
+                    .inspect(|event| {
+                        emit!(&EventsReceived {
+                            count: 1,
+                            byte_size: event.size_of(),
+                        })
+                    });
+                let mut results = t.transform(Box::pin(filtered));
+
+                let transform = async move {
+                    while let Some(result) = results.next().await {
+                        let event = match result {
+                            Ok(event) => event,
+                            Err(event) => {
+                                errors_output.send(event).await?;
+                                continue;
+                            }
+                        };
+
+                        let byte_size = event.size_of();
+                        output.send(event).await?;
+                        emit!(&EventsSent {
+                            count: 1,
+                            byte_size,
+                        });
+                    }
+
+                    debug!("Finished.");
+                    Ok(TaskOutput::Transform)
+                }
+                .boxed();
+
+                outputs.insert(OutputId::from(key), control);
+                assert_eq!(1, named_outputs.len());
+                outputs.insert(
+                    OutputId::from((key, named_outputs.remove(0))),
+                    errors_control,
+                );
+
                 Task::new(key.clone(), typetag, transform)
             }
         };
@@ -1,5 +1,5 @@
 use crate::topology::builder;
-use crate::topology::fanout::{ControlChannel, ControlMessage};
+use crate::topology::fanout::{ControlChannel, ControlMessage, RouterSink};
 use crate::topology::{
     build_or_log_errors, handle_errors, retain, take_healthchecks, BuiltBuffer, Outputs,
     TaskHandle, WatchRx, WatchTx,
@@ -604,7 +604,10 @@ impl RunningTopology {
                     // be present.
                     if let Some(input) = self.inputs.get(sink_key) {
                         let _ = output
-                            .send(ControlMessage::Add(sink_key.clone(), input.get()))
+                            .send(ControlMessage::Add(
+                                sink_key.clone(),
+                                input_sink(input, &id),
+                            ))
                             .await;
                     }
                 }
@@ -615,7 +618,10 @@ impl RunningTopology {
                     // not be present.
                     if let Some(input) = self.inputs.get(transform_key) {
                         let _ = output
-                            .send(ControlMessage::Add(transform_key.clone(), input.get()))
+                            .send(ControlMessage::Add(
+                                transform_key.clone(),
+                                input_sink(input, &id),
+                            ))
                             .await;
                     }
                 }
@@ -634,7 +640,7 @@ impl RunningTopology {
                 .outputs
                 .get_mut(&input)
                 .expect("unknown output")
-                .send(ControlMessage::Add(key.clone(), tx.get()))
+                .send(ControlMessage::Add(key.clone(), input_sink(&tx, &input)))
                 .await;
         }
 
@@ -696,7 +702,7 @@ impl RunningTopology {
                 .outputs
                 .get_mut(input)
                 .unwrap()
-                .send(ControlMessage::Add(key.clone(), tx.get()))
+                .send(ControlMessage::Add(key.clone(), input_sink(&tx, input)))
                 .await;
         }
 
@@ -706,7 +712,10 @@ impl RunningTopology {
                 .outputs
                 .get_mut(input)
                 .unwrap()
-                .send(ControlMessage::Replace(key.clone(), Some(tx.get())))
+                .send(ControlMessage::Replace(
+                    key.clone(),
+                    Some(input_sink(&tx, input)),
+                ))
                 .await;
         }
 
@@ -749,3 +758,15 @@ impl RunningTopology {
         self.watch.1.clone()
     }
 }
+
+/// Returns a sink sending the events of the `input` output to the component
+/// behind `tx`, recording `input` as the events' upstream component.
+fn input_sink(tx: &BufferInputCloner<Event>, input: &OutputId) -> RouterSink {
+    let upstream_id: Arc<str> = input.to_string().into();
+    Box::new(tx.get().with(move |mut event: Event| {
+        event
+            .metadata_mut()
+            .set_upstream_id(Some(Arc::clone(&upstream_id)));
+        future::ok::<_, ()>(event)
+    }))
+}
@@ -2,10 +2,12 @@ use crate::{
     config::{DataType, TransformConfig, TransformContext, TransformDescription},
     event::{metric, Event, EventMetadata},
     internal_events::{AggregateEventRecorded, AggregateFlushed, AggregateUpdateFailed},
+    sinks::util::buffer::metrics::MetricSet,
     transforms::{TaskTransform, Transform},
 };
 use async_stream::stream;
 use futures::{Stream, StreamExt};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{hash_map::Entry, HashMap},
@@ -19,12 +21,66 @@ pub struct AggregateConfig {
     /// The interval between flushes in milliseconds.
     #[serde(default = "default_interval_ms")]
     pub interval_ms: u64,
+
+    /// The tags to keep on aggregated metrics. Series that only differ in
+    /// other tags are aggregated together.
+    pub group_by: Option<Vec<String>>,
+
+    /// The tags to remove from aggregated metrics. Series that only differ in
+    /// these tags are aggregated together.
+    pub remove_tags: Vec<String>,
+
+    /// The functions used to aggregate values, per metric type.
+    pub functions: AggregateFunctions,
+
+    /// The kind of the emitted metrics.
+    pub mode: AggregateMode,
 }
 
 const fn default_interval_ms() -> u64 {
     10 * 1000
 }
 
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy)]
+#[serde(deny_unknown_fields, default)]
+pub struct AggregateFunctions {
+    pub counter: Option<AggregateFunction>,
+    pub gauge: Option<AggregateFunction>,
+    pub distribution: Option<AggregateFunction>,
+    pub histogram: Option<AggregateFunction>,
+    pub set: Option<AggregateFunction>,
+    pub sketch: Option<AggregateFunction>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    Sum,
+    Last,
+    Max,
+    Min,
+    Mean,
+    Merge,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateMode {
+    /// Emit metrics of the same kind as the ones received.
+    Auto,
+    /// Emit absolute metrics, accumulating incremental metrics across flushes.
+    Absolute,
+    /// Emit incremental metrics, calculating the change of absolute metrics
+    /// since the previous flush.
+    Incremental,
+}
+
+impl Default for AggregateMode {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
 inventory::submit! {
     TransformDescription::new::<AggregateConfig>("aggregate")
 }
@@ -51,46 +107,284 @@ impl TransformConfig for AggregateConfig {
     }
 }
 
-type MetricEntry = (metric::MetricData, EventMetadata);
+impl AggregateFunctions {
+    fn validate(&self) -> crate::Result<()> {
+        use AggregateFunction::*;
+
+        let scalar = [Sum, Last, Max, Min, Mean];
+        let distribution = [Merge, Last];
+
+        for (name, function, allowed) in [
+            ("counter", self.counter, &scalar[..]),
+            ("gauge", self.gauge, &scalar[..]),
+            ("distribution", self.distribution, &distribution[..]),
+            ("histogram", self.histogram, &distribution[..]),
+            ("set", self.set, &distribution[..]),
+            ("sketch", self.sketch, &distribution[..]),
+        ] {
+            if let Some(function) = function {
+                if !allowed.contains(&function) {
+                    return Err(format!(
+                        "`functions.{}` does not support the `{}` function",
+                        name,
+                        function.as_str()
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the function used to aggregate the given value. Values are
+    /// added together unless configured otherwise, except for summaries which
+    /// can't be added and always keep the last value.
+    fn get(&self, value: &metric::MetricValue) -> AggregateFunction {
+        use metric::MetricValue;
+
+        match value {
+            MetricValue::Counter { .. } => self.counter.unwrap_or(AggregateFunction::Sum),
+            MetricValue::Gauge { .. } => self.gauge.unwrap_or(AggregateFunction::Sum),
+            MetricValue::Distribution { .. } => {
+                self.distribution.unwrap_or(AggregateFunction::Merge)
+            }
+            MetricValue::AggregatedHistogram { .. } => {
+                self.histogram.unwrap_or(AggregateFunction::Merge)
+            }
+            MetricValue::Set { .. } => self.set.unwrap_or(AggregateFunction::Merge),
+            MetricValue::Sketch { .. } => self.sketch.unwrap_or(AggregateFunction::Merge),
+            MetricValue::AggregatedSummary { .. } => AggregateFunction::Last,
+        }
+    }
+}
+
+impl AggregateFunction {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Last => "last",
+            Self::Max => "max",
+            Self::Min => "min",
+            Self::Mean => "mean",
+            Self::Merge => "merge",
+        }
+    }
+
+    /// Combines `value` into the accumulated `data`, returning `false` if
+    /// both values are not of the same type.
+    fn combine(self, data: &mut metric::MetricData, value: &metric::MetricData) -> bool {
+        use metric::MetricValue::{Counter, Gauge};
+
+        if data.value.as_name() != value.value.as_name() {
+            return false;
+        }
+
+        let updated = match self {
+            Self::Sum | Self::Mean | Self::Merge => data.value.add(&value.value),
+            Self::Last => {
+                data.value = value.value.clone();
+                true
+            }
+            Self::Max | Self::Min => match (&mut data.value, &value.value) {
+                (Counter { value: a }, Counter { value: b })
+                | (Gauge { value: a }, Gauge { value: b }) => {
+                    *a = if self == Self::Max {
+                        a.max(*b)
+                    } else {
+                        a.min(*b)
+                    };
+                    true
+                }
+                _ => false,
+            },
+        };
+
+        if updated {
+            data.timestamp = data.timestamp.max(value.timestamp);
+        }
+        updated
+    }
+
+    /// Finalizes the accumulated `data`, after `count` values were combined
+    /// into it.
+    fn finish(self, data: &mut metric::MetricData, count: usize) {
+        use metric::MetricValue::{Counter, Gauge};
+
+        if let (Self::Mean, Counter { value } | Gauge { value }) = (self, &mut data.value) {
+            *value /= count as f64;
+        }
+    }
+}
 
 //------------------------------------------------------------------------------
 
+type MetricParts = (metric::MetricSeries, metric::MetricData, EventMetadata);
+
+/// The state of a single aggregated series during an interval.
+#[derive(Debug)]
+struct Aggregation {
+    function: AggregateFunction,
+    metadata: EventMetadata,
+    values: AggregationValues,
+}
+
+#[derive(Debug)]
+enum AggregationValues {
+    /// Incremental values are combined as they are received.
+    Incremental {
+        data: metric::MetricData,
+        count: usize,
+    },
+
+    /// Absolute values supersede the previous value of the same series, so
+    /// only the latest value of every original series is kept, and these are
+    /// combined on flush. The map is ordered by the time of the last update.
+    Absolute(IndexMap<metric::MetricSeries, metric::MetricData>),
+}
+
+impl Aggregation {
+    fn new(function: AggregateFunction, (series, data, metadata): MetricParts) -> Self {
+        let values = match data.kind {
+            metric::MetricKind::Incremental => AggregationValues::Incremental { data, count: 1 },
+            metric::MetricKind::Absolute => {
+                let mut values = IndexMap::new();
+                values.insert(series, data);
+                AggregationValues::Absolute(values)
+            }
+        };
+
+        Self {
+            function,
+            metadata,
+            values,
+        }
+    }
+
+    /// Records a new value, handing it back if it conflicts with the kind or
+    /// type of the values recorded so far.
+    fn record(&mut self, (series, data, metadata): MetricParts) -> Result<(), MetricParts> {
+        let recorded = match &mut self.values {
+            AggregationValues::Incremental {
+                data: existing,
+                count,
+            } => {
+                // In order to update (add) the new and old kind's must match
+                let updated = data.kind == metric::MetricKind::Incremental
+                    && self.function.combine(existing, &data);
+                if updated {
+                    *count += 1;
+                }
+                updated
+            }
+            AggregationValues::Absolute(values) => {
+                let compatible = data.kind == metric::MetricKind::Absolute
+                    && values.values().next().map_or(true, |existing| {
+                        existing.value.as_name() == data.value.as_name()
+                    });
+                if compatible {
+                    // Move the series to the end, to track which was updated last
+                    values.shift_remove(&series);
+                    values.insert(series, data);
+                    self.metadata.merge(metadata);
+                    return Ok(());
+                }
+                false
+            }
+        };
+
+        if recorded {
+            self.metadata.merge(metadata);
+            Ok(())
+        } else {
+            Err((series, data, metadata))
+        }
+    }
+
+    fn finish(self) -> Option<(metric::MetricData, EventMetadata)> {
+        let (mut data, count) = match self.values {
+            AggregationValues::Incremental { data, count } => (data, count),
+            AggregationValues::Absolute(values) => {
+                let count = values.len();
+                let mut values = values.into_iter().map(|(_, data)| data);
+                let mut data = values.next()?;
+                for value in values {
+                    if !self.function.combine(&mut data, &value) {
+                        emit!(&AggregateUpdateFailed);
+                    }
+                }
+                (data, count)
+            }
+        };
+
+        self.function.finish(&mut data, count);
+        Some((data, self.metadata))
+    }
+}
+
 #[derive(Debug)]
 pub struct Aggregate {
     interval: Duration,
-    map: HashMap<metric::MetricSeries, MetricEntry>,
+    group_by: Option<Vec<String>>,
+    remove_tags: Vec<String>,
+    functions: AggregateFunctions,
+    mode: AggregateMode,
+    map: HashMap<metric::MetricSeries, Aggregation>,
+    state: MetricSet,
 }
 
 impl Aggregate {
     pub fn new(config: &AggregateConfig) -> crate::Result<Self> {
+        if config.group_by.is_some() && !config.remove_tags.is_empty() {
+            return Err("`group_by` and `remove_tags` cannot be used together".into());
+        }
+        config.functions.validate()?;
+
         Ok(Self {
             interval: Duration::from_millis(config.interval_ms),
+            group_by: config.group_by.clone(),
+            remove_tags: config.remove_tags.clone(),
+            functions: config.functions,
+            mode: config.mode,
             map: HashMap::new(),
+            state: MetricSet::default(),
         })
     }
 
+    /// Returns the series the given series is aggregated into.
+    fn group(&self, series: &metric::MetricSeries) -> metric::MetricSeries {
+        let mut group = series.clone();
+        if let Some(group_by) = &self.group_by {
+            group.tags = group
+                .tags
+                .map(|tags| {
+                    tags.into_iter()
+                        .filter(|(key, _)| group_by.contains(key))
+                        .collect::<metric::MetricTags>()
+                })
+                .filter(|tags| !tags.is_empty());
+        }
+        for tag in &self.remove_tags {
+            group.remove_tag(tag);
+        }
+        group
+    }
+
     fn record(&mut self, event: Event) {
-        let (series, data, metadata) = event.into_metric().into_parts();
-
-        match data.kind {
-            metric::MetricKind::Incremental => match self.map.entry(series) {
-                Entry::Occupied(mut entry) => {
-                    let existing = entry.get_mut();
-                    // In order to update (add) the new and old kind's must match
-                    if existing.0.kind == data.kind && existing.0.update(&data) {
-                        existing.1.merge(metadata);
-                    } else {
-                        emit!(&AggregateUpdateFailed);
-                        *existing = (data, metadata);
-                    }
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert((data, metadata));
+        let parts = event.into_metric().into_parts();
+        let group = self.group(&parts.0);
+        let function = self.functions.get(&parts.1.value);
+
+        match self.map.entry(group) {
+            Entry::Occupied(mut entry) => {
+                let aggregation = entry.get_mut();
+                if let Err(parts) = aggregation.record(parts) {
+                    emit!(&AggregateUpdateFailed);
+                    *aggregation = Aggregation::new(function, parts);
                 }
-            },
-            metric::MetricKind::Absolute => {
-                // Always replace/store
-                self.map.insert(series, (data, metadata));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Aggregation::new(function, parts));
             }
         };
 
@@ -98,9 +392,19 @@ impl Aggregate {
     }
 
     fn flush_into(&mut self, output: &mut Vec<Event>) {
-        for (series, entry) in self.map.drain() {
-            let metric = metric::Metric::from_parts(series, entry.0, entry.1);
-            output.push(Event::Metric(metric));
+        for (series, aggregation) in self.map.drain() {
+            let (data, metadata) = match aggregation.finish() {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let metric = metric::Metric::from_parts(series, data, metadata);
+
+            let metric = match self.mode {
+                AggregateMode::Auto => Some(metric),
+                AggregateMode::Absolute => self.state.make_absolute(metric),
+                AggregateMode::Incremental => self.state.make_incremental(metric),
+            };
+            output.extend(metric.map(Event::Metric));
         }
 
         emit!(&AggregateFlushed);
@@ -148,6 +452,7 @@ mod tests {
     use super::*;
     use crate::{event::metric, event::Event, event::Metric};
     use futures::{stream, SinkExt};
+    use shared::btreemap;
     use std::{collections::BTreeSet, task::Poll};
 
     #[test]
@@ -167,6 +472,7 @@ mod tests {
     fn incremental() {
         let mut agg = Aggregate::new(&AggregateConfig {
             interval_ms: 1000_u64,
+            ..Default::default()
         })
         .unwrap();
 
@@ -237,6 +543,7 @@ mod tests {
     fn absolute() {
         let mut agg = Aggregate::new(&AggregateConfig {
             interval_ms: 1000_u64,
+            ..Default::default()
         })
         .unwrap();
 
@@ -302,6 +609,7 @@ mod tests {
     fn conflicting_value_type() {
         let mut agg = Aggregate::new(&AggregateConfig {
             interval_ms: 1000_u64,
+            ..Default::default()
         })
         .unwrap();
 
@@ -359,6 +667,7 @@ mod tests {
     fn conflicting_kinds() {
         let mut agg = Aggregate::new(&AggregateConfig {
             interval_ms: 1000_u64,
+            ..Default::default()
         })
         .unwrap();
 
@@ -551,4 +860,203 @@ interval_ms = 999999
         // And still nothing there
         assert_eq!(Poll::Ready(None), futures::poll!(out_stream.next()));
     }
+
+    fn tagged_metric(
+        name: &'static str,
+        kind: metric::MetricKind,
+        value: metric::MetricValue,
+        deployment: &str,
+        pod: &str,
+    ) -> Event {
+        Event::Metric(Metric::new(name, kind, value).with_tags(Some(btreemap! {
+            "deployment" => deployment,
+            "pod" => pod,
+        })))
+    }
+
+    fn aggregate(config: &str) -> Aggregate {
+        Aggregate::new(&toml::from_str::<AggregateConfig>(config).unwrap()).unwrap()
+    }
+
+    fn flush(agg: &mut Aggregate) -> Vec<Metric> {
+        let mut out = vec![];
+        agg.flush_into(&mut out);
+        let mut out = out.into_iter().map(Event::into_metric).collect::<Vec<_>>();
+        out.sort_by(|a, b| a.series().partial_cmp(b.series()).unwrap());
+        out
+    }
+
+    #[test]
+    fn remove_tags() {
+        let mut agg = aggregate(r#"remove_tags = ["pod"]"#);
+
+        let counter = |value, pod| {
+            tagged_metric(
+                "requests",
+                metric::MetricKind::Absolute,
+                metric::MetricValue::Counter { value },
+                "api",
+                pod,
+            )
+        };
+
+        // Only the latest value of every pod is summed
+        agg.record(counter(10.0, "a"));
+        agg.record(counter(20.0, "b"));
+        agg.record(counter(15.0, "a"));
+
+        let out = flush(&mut agg);
+        assert_eq!(1, out.len());
+        assert_eq!(out[0].tags(), Some(&btreemap! { "deployment" => "api" }));
+        assert_eq!(out[0].kind(), metric::MetricKind::Absolute);
+        assert_eq!(
+            out[0].value(),
+            &metric::MetricValue::Counter { value: 35.0 }
+        );
+    }
+
+    #[test]
+    fn group_by() {
+        let mut agg = aggregate(r#"group_by = ["deployment"]"#);
+
+        let counter = |value, deployment, pod| {
+            tagged_metric(
+                "requests",
+                metric::MetricKind::Incremental,
+                metric::MetricValue::Counter { value },
+                deployment,
+                pod,
+            )
+        };
+
+        agg.record(counter(1.0, "api", "a"));
+        agg.record(counter(2.0, "api", "b"));
+        agg.record(counter(4.0, "web", "c"));
+
+        let out = flush(&mut agg);
+        assert_eq!(2, out.len());
+        assert_eq!(out[0].tags(), Some(&btreemap! { "deployment" => "api" }));
+        assert_eq!(out[0].value(), &metric::MetricValue::Counter { value: 3.0 });
+        assert_eq!(out[1].tags(), Some(&btreemap! { "deployment" => "web" }));
+        assert_eq!(out[1].value(), &metric::MetricValue::Counter { value: 4.0 });
+    }
+
+    #[test]
+    fn gauge_functions() {
+        let gauge = |value, pod| {
+            tagged_metric(
+                "memory",
+                metric::MetricKind::Absolute,
+                metric::MetricValue::Gauge { value },
+                "api",
+                pod,
+            )
+        };
+
+        for (function, expected) in [
+            ("sum", 12.0),
+            ("last", 4.0),
+            ("max", 6.0),
+            ("min", 2.0),
+            ("mean", 4.0),
+        ] {
+            let mut agg = aggregate(&format!(
+                "remove_tags = [\"pod\"]\nfunctions.gauge = \"{}\"",
+                function
+            ));
+
+            agg.record(gauge(2.0, "a"));
+            agg.record(gauge(6.0, "b"));
+            agg.record(gauge(4.0, "c"));
+
+            let out = flush(&mut agg);
+            assert_eq!(1, out.len(), "{}", function);
+            assert_eq!(
+                out[0].value(),
+                &metric::MetricValue::Gauge { value: expected },
+                "{}",
+                function
+            );
+        }
+    }
+
+    #[test]
+    fn incremental_functions() {
+        let mut agg = aggregate(r#"functions.counter = "max""#);
+
+        for value in [3.0, 5.0, 1.0] {
+            agg.record(make_metric(
+                "counter",
+                metric::MetricKind::Incremental,
+                metric::MetricValue::Counter { value },
+            ));
+        }
+
+        let out = flush(&mut agg);
+        assert_eq!(1, out.len());
+        assert_eq!(out[0].value(), &metric::MetricValue::Counter { value: 5.0 });
+    }
+
+    #[test]
+    fn absolute_mode() {
+        let mut agg = aggregate(r#"mode = "absolute""#);
+
+        let counter = |value| {
+            make_metric(
+                "counter",
+                metric::MetricKind::Incremental,
+                metric::MetricValue::Counter { value },
+            )
+        };
+
+        agg.record(counter(5.0));
+        let out = flush(&mut agg);
+        assert_eq!(out[0].kind(), metric::MetricKind::Absolute);
+        assert_eq!(out[0].value(), &metric::MetricValue::Counter { value: 5.0 });
+
+        agg.record(counter(7.0));
+        let out = flush(&mut agg);
+        assert_eq!(out[0].kind(), metric::MetricKind::Absolute);
+        assert_eq!(
+            out[0].value(),
+            &metric::MetricValue::Counter { value: 12.0 }
+        );
+    }
+
+    #[test]
+    fn incremental_mode() {
+        let mut agg = aggregate(r#"mode = "incremental""#);
+
+        let counter = |value| {
+            make_metric(
+                "counter",
+                metric::MetricKind::Absolute,
+                metric::MetricValue::Counter { value },
+            )
+        };
+
+        // The first value is only used as a reference
+        agg.record(counter(10.0));
+        assert!(flush(&mut agg).is_empty());
+
+        agg.record(counter(25.0));
+        let out = flush(&mut agg);
+        assert_eq!(out[0].kind(), metric::MetricKind::Incremental);
+        assert_eq!(
+            out[0].value(),
+            &metric::MetricValue::Counter { value: 15.0 }
+        );
+    }
+
+    #[test]
+    fn invalid_config() {
+        for config in [
+            r#"functions.histogram = "max""#,
+            r#"functions.gauge = "merge""#,
+            "group_by = [\"deployment\"]\nremove_tags = [\"pod\"]",
+        ] {
+            let config = toml::from_str::<AggregateConfig>(config).unwrap();
+            assert!(Aggregate::new(&config).is_err());
+        }
+    }
 }
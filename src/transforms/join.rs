@@ -0,0 +1,570 @@
+use crate::{
+    config::{DataType, GenerateConfig, TransformConfig, TransformContext, TransformDescription},
+    event::{Event, LogEvent, Value},
+    expiring_hash_map::ExpiringHashMap,
+    internal_events::{
+        JoinEventUnmatched, JoinEventsMatched, JoinPendingKeys, TemplateRenderingFailed,
+    },
+    template::Template,
+    transforms::{FallibleTaskTransform, TaskTransform, Transform},
+};
+use async_stream::stream;
+use futures::{future, stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, pin::Pin, time::Duration};
+use vector_core::ByteSizeOf;
+
+const fn default_ttl_secs() -> u64 {
+    30
+}
+
+const fn default_max_pending_keys() -> usize {
+    10_000
+}
+
+const fn default_max_pending_bytes() -> usize {
+    100 * 1024 * 1024 // 100MiB
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JoinConfig {
+    /// The template rendering the key used to correlate events from both
+    /// sides of the join.
+    pub key: Template,
+
+    pub left: JoinSideConfig,
+    pub right: JoinSideConfig,
+
+    /// The maximum period of time an event waits for its counterpart.
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// The maximum number of keys waiting for their counterpart. Events
+    /// arriving once this limit is reached are treated as unmatched.
+    #[serde(default = "default_max_pending_keys")]
+    pub max_pending_keys: usize,
+
+    /// The maximum size in bytes of the events waiting for their
+    /// counterpart. Events that would exceed this limit are treated as
+    /// unmatched.
+    #[serde(default = "default_max_pending_bytes")]
+    pub max_pending_bytes: usize,
+
+    /// Send unmatched events to the `unmatched` output instead of dropping
+    /// them.
+    #[serde(default)]
+    pub reroute_unmatched: bool,
+}
+
+/// One side of the join.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JoinSideConfig {
+    /// The inputs of the transform whose events belong to this side.
+    pub inputs: Vec<String>,
+
+    /// When set, the fields of this side are nested under this field of the
+    /// joined event, instead of being merged into its root.
+    pub prefix: Option<String>,
+}
+
+inventory::submit! {
+    TransformDescription::new::<JoinConfig>("join")
+}
+
+impl GenerateConfig for JoinConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"key = "{{ request_id }}"
+            left.inputs = ["requests"]
+            right.inputs = ["responses"]"#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "join")]
+impl TransformConfig for JoinConfig {
+    async fn build(&self, _context: &TransformContext) -> crate::Result<Transform> {
+        let join = Join::new(self)?;
+        Ok(if self.reroute_unmatched {
+            Transform::fallible_task(join)
+        } else {
+            Transform::task(join)
+        })
+    }
+
+    fn named_outputs(&self) -> Vec<String> {
+        if self.reroute_unmatched {
+            vec![String::from("unmatched")]
+        } else {
+            vec![]
+        }
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "join"
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug)]
+struct Pending {
+    side: Side,
+    event: LogEvent,
+    /// The size of `event`, as accounted for in `Join::pending_bytes`.
+    size: usize,
+}
+
+struct JoinSide {
+    inputs: Vec<String>,
+    prefix: Option<String>,
+}
+
+impl JoinSide {
+    fn contains(&self, input: &str) -> bool {
+        self.inputs.iter().any(|i| i == input)
+    }
+}
+
+pub struct Join {
+    key: Template,
+    left: JoinSide,
+    right: JoinSide,
+    ttl: Duration,
+    max_pending_keys: usize,
+    max_pending_bytes: usize,
+    reroute_unmatched: bool,
+    pending: ExpiringHashMap<String, Pending>,
+    pending_bytes: usize,
+}
+
+impl Join {
+    pub fn new(config: &JoinConfig) -> crate::Result<Self> {
+        if config.ttl_secs == 0 {
+            return Err("`ttl_secs` must be greater than zero".into());
+        }
+
+        if config.left.inputs.is_empty() || config.right.inputs.is_empty() {
+            return Err("`left.inputs` and `right.inputs` must not be empty".into());
+        }
+
+        if let Some(input) = config
+            .left
+            .inputs
+            .iter()
+            .find(|input| config.right.inputs.contains(input))
+        {
+            return Err(format!("input {:?} is on both sides of the join", input).into());
+        }
+
+        Ok(Self {
+            key: config.key.clone(),
+            left: JoinSide {
+                inputs: config.left.inputs.clone(),
+                prefix: config.left.prefix.clone(),
+            },
+            right: JoinSide {
+                inputs: config.right.inputs.clone(),
+                prefix: config.right.prefix.clone(),
+            },
+            ttl: Duration::from_secs(config.ttl_secs),
+            max_pending_keys: config.max_pending_keys,
+            max_pending_bytes: config.max_pending_bytes,
+            reroute_unmatched: config.reroute_unmatched,
+            pending: ExpiringHashMap::default(),
+            pending_bytes: 0,
+        })
+    }
+
+    fn unmatched(
+        &self,
+        output: &mut Vec<Result<Event, Event>>,
+        event: LogEvent,
+        reason: &'static str,
+    ) {
+        emit!(&JoinEventUnmatched {
+            reason,
+            event_dropped: !self.reroute_unmatched,
+        });
+
+        if self.reroute_unmatched {
+            output.push(Err(event.into()));
+        }
+    }
+
+    fn transform_one(&mut self, output: &mut Vec<Result<Event, Event>>, event: Event) {
+        let side = match event.metadata().upstream_id().as_deref() {
+            Some(input) if self.left.contains(input) => Side::Left,
+            Some(input) if self.right.contains(input) => Side::Right,
+            _ => return self.unmatched(output, event.into_log(), "no_side"),
+        };
+
+        let key = match self.key.render_string(&event) {
+            Ok(key) => key,
+            Err(error) => {
+                emit!(&TemplateRenderingFailed {
+                    error,
+                    field: Some("key"),
+                    drop_event: false,
+                });
+                return self.unmatched(output, event.into_log(), "no_key");
+            }
+        };
+
+        let event = event.into_log();
+        let size = event.size_of();
+
+        match self.remove_pending(&key) {
+            Some(pending) if pending.side != side => {
+                let (left, right) = match side {
+                    Side::Left => (event, pending.event),
+                    Side::Right => (pending.event, event),
+                };
+
+                emit!(&JoinEventsMatched);
+                output.push(Ok(self.merge(left, right).into()));
+            }
+            Some(pending) => {
+                // An event from the same side replaces the one that was
+                // waiting, which will never be matched.
+                self.unmatched(output, pending.event, "replaced");
+                self.insert_pending(output, key, Pending { side, event, size });
+            }
+            None => {
+                self.insert_pending(output, key, Pending { side, event, size });
+            }
+        }
+
+        self.emit_pending();
+    }
+
+    fn insert_pending(
+        &mut self,
+        output: &mut Vec<Result<Event, Event>>,
+        key: String,
+        pending: Pending,
+    ) {
+        if self.pending.len() >= self.max_pending_keys
+            || self.pending_bytes + pending.size > self.max_pending_bytes
+        {
+            self.unmatched(output, pending.event, "pending_limit");
+        } else {
+            self.pending_bytes += pending.size;
+            self.pending.insert(key, pending, self.ttl);
+        }
+    }
+
+    fn remove_pending(&mut self, key: &str) -> Option<Pending> {
+        let (pending, _) = self.pending.remove(key)?;
+        self.pending_bytes -= pending.size;
+        Some(pending)
+    }
+
+    fn expired(&mut self, output: &mut Vec<Result<Event, Event>>, pending: Pending) {
+        self.pending_bytes -= pending.size;
+        self.unmatched(output, pending.event, "expired");
+        self.emit_pending();
+    }
+
+    fn emit_pending(&self) {
+        emit!(&JoinPendingKeys {
+            count: self.pending.len(),
+            bytes: self.pending_bytes,
+        });
+    }
+
+    fn merge(&self, left: LogEvent, right: LogEvent) -> LogEvent {
+        let (left_fields, mut metadata) = left.into_parts();
+        let (right_fields, right_metadata) = right.into_parts();
+        metadata.merge(right_metadata);
+
+        let mut event = LogEvent::new_with_metadata(metadata);
+        insert_fields(&mut event, self.left.prefix.as_deref(), left_fields);
+        insert_fields(&mut event, self.right.prefix.as_deref(), right_fields);
+        event
+    }
+
+    fn flush_all_into(&mut self, output: &mut Vec<Result<Event, Event>>) {
+        let keys = self
+            .pending
+            .iter_mut()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        for key in keys {
+            if let Some(pending) = self.remove_pending(&key) {
+                self.unmatched(output, pending.event, "expired");
+            }
+        }
+        self.emit_pending();
+    }
+}
+
+/// Inserts the fields of one side of the join into the joined event. Fields
+/// of the right side overwrite fields of the left side with the same name.
+fn insert_fields(event: &mut LogEvent, prefix: Option<&str>, fields: BTreeMap<String, Value>) {
+    match prefix {
+        Some(prefix) => {
+            event.insert(prefix, Value::Map(fields));
+        }
+        None => {
+            for (key, value) in fields {
+                event.insert_flat(key, value);
+            }
+        }
+    }
+}
+
+impl FallibleTaskTransform for Join {
+    fn transform(
+        self: Box<Self>,
+        mut input_rx: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Event, Event>> + Send>>
+    where
+        Self: 'static,
+    {
+        let mut me = self;
+
+        Box::pin(
+            stream! {
+              loop {
+                let mut output = Vec::new();
+                let done = tokio::select! {
+                    expired = me.pending.next_expired(), if !me.pending.is_empty() => {
+                      match expired {
+                        Some(Ok((pending, _))) => me.expired(&mut output, pending),
+                        Some(Err(error)) => error!(message = "An expired join key could not be processed.", %error),
+                        None => {}
+                      }
+                      false
+                    }
+                    maybe_event = input_rx.next() => {
+                      match maybe_event {
+                        None => {
+                          me.flush_all_into(&mut output);
+                          true
+                        }
+                        Some(event) => {
+                          me.transform_one(&mut output, event);
+                          false
+                        }
+                      }
+                    }
+                };
+                yield stream::iter(output.into_iter());
+                if done { break }
+              }
+            }
+            .flatten(),
+        )
+    }
+}
+
+impl TaskTransform for Join {
+    fn transform(
+        self: Box<Self>,
+        input_rx: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Event> + Send>>
+    where
+        Self: 'static,
+    {
+        Box::pin(
+            FallibleTaskTransform::transform(self, input_rx)
+                .filter_map(|result| future::ready(result.ok())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<JoinConfig>();
+    }
+
+    fn join(extra: &str) -> Join {
+        let config = toml::from_str::<JoinConfig>(&format!(
+            r#"
+key = "{{{{ id }}}}"
+left.inputs = ["request"]
+right.inputs = ["response"]
+right.prefix = "response"
+{}
+"#,
+            extra
+        ))
+        .unwrap();
+
+        Join::new(&config).unwrap()
+    }
+
+    /// Returns an event as received by the join from the `input` component.
+    fn event(input: &str, id: &str, message: &str) -> Event {
+        let mut event = LogEvent::from(message);
+        event.insert("type", input);
+        event.insert("id", id);
+        event.metadata_mut().set_upstream_id(Some(input.into()));
+        event.into()
+    }
+
+    #[tokio::test]
+    async fn joins_events() {
+        let join = Box::new(join(""));
+
+        let inputs = vec![
+            event("request", "1", "request 1"),
+            event("request", "2", "request 2"),
+            event("response", "1", "response 1"),
+        ];
+        let mut out_stream = FallibleTaskTransform::transform(join, Box::pin(stream::iter(inputs)));
+
+        let output = out_stream.next().await.unwrap().unwrap().into_log();
+        assert_eq!(output["message"], "request 1".into());
+        assert_eq!(output["id"], "1".into());
+        assert_eq!(output["response.message"], "response 1".into());
+        assert_eq!(output["response.type"], "response".into());
+
+        // The pending request is dropped once the input ends.
+        assert!(out_stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reroutes_unmatched_events() {
+        let join = Box::new(join("reroute_unmatched = true"));
+
+        let inputs = vec![
+            event("request", "1", "request 1"),
+            event("request", "1", "request 1 again"),
+            event("other", "1", "other"),
+        ];
+        let mut out_stream = FallibleTaskTransform::transform(join, Box::pin(stream::iter(inputs)));
+
+        let replaced = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(replaced["message"], "request 1".into());
+
+        let other = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(other["message"], "other".into());
+
+        let expired = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(expired["message"], "request 1 again".into());
+
+        assert!(out_stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn pending_limit() {
+        let join = Box::new(join("max_pending_keys = 1\nreroute_unmatched = true"));
+
+        let inputs = vec![
+            event("request", "1", "request 1"),
+            event("request", "2", "request 2"),
+            event("response", "1", "response 1"),
+        ];
+        let mut out_stream = FallibleTaskTransform::transform(join, Box::pin(stream::iter(inputs)));
+
+        let rejected = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(rejected["message"], "request 2".into());
+
+        let joined = out_stream.next().await.unwrap().unwrap().into_log();
+        assert_eq!(joined["message"], "request 1".into());
+    }
+
+    #[tokio::test]
+    async fn pending_bytes_limit() {
+        let size = event("request", "1", "request 1").into_log().size_of();
+        let join = Box::new(join(&format!(
+            "max_pending_bytes = {}\nreroute_unmatched = true",
+            size + size / 2
+        )));
+
+        let inputs = vec![
+            event("request", "1", "request 1"),
+            event("request", "2", "request 2"),
+            event("response", "1", "response 1"),
+            event("request", "3", "request 3"),
+        ];
+        let mut out_stream = FallibleTaskTransform::transform(join, Box::pin(stream::iter(inputs)));
+
+        let rejected = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(rejected["message"], "request 2".into());
+
+        let joined = out_stream.next().await.unwrap().unwrap().into_log();
+        assert_eq!(joined["message"], "request 1".into());
+
+        // The joined event no longer counts towards the limit.
+        let expired = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(expired["message"], "request 3".into());
+        assert!(out_stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn expires_pending_events() {
+        tokio::time::pause();
+
+        let join = Box::new(join("ttl_secs = 5\nreroute_unmatched = true"));
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let mut out_stream = FallibleTaskTransform::transform(join, Box::pin(rx));
+
+        tx.unbounded_send(event("request", "1", "request 1"))
+            .unwrap();
+        tokio::time::advance(Duration::from_secs(6)).await;
+
+        let expired = out_stream.next().await.unwrap().unwrap_err().into_log();
+        assert_eq!(expired["message"], "request 1".into());
+    }
+
+    #[tokio::test]
+    async fn assigns_sides_by_input() {
+        let join = Box::new(join(""));
+
+        // Both events have the same content, only their input differs.
+        let mut request = LogEvent::from("message");
+        request.insert("id", "1");
+        let mut response = request.clone();
+        request
+            .metadata_mut()
+            .set_upstream_id(Some("request".into()));
+        response
+            .metadata_mut()
+            .set_upstream_id(Some("response".into()));
+
+        let inputs = vec![request.into(), response.into()];
+        let mut out_stream = FallibleTaskTransform::transform(join, Box::pin(stream::iter(inputs)));
+
+        let output = out_stream.next().await.unwrap().unwrap().into_log();
+        assert_eq!(output["message"], "message".into());
+        assert_eq!(output["response.message"], "message".into());
+    }
+
+    #[test]
+    fn rejects_inputs_on_both_sides() {
+        let config = toml::from_str::<JoinConfig>(
+            r#"
+key = "{{ id }}"
+left.inputs = ["a", "b"]
+right.inputs = ["b"]
+"#,
+        )
+        .unwrap();
+
+        assert!(Join::new(&config).is_err());
+    }
+}
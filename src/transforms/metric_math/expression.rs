@@ -0,0 +1,271 @@
+use snafu::Snafu;
+use std::{collections::HashMap, iter::Peekable, str::Chars};
+
+/// An arithmetic expression over named variables, supporting `+`, `-`, `*`,
+/// `/`, unary negation and parentheses.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ParseError {
+    #[snafu(display("unexpected character {:?} at position {}", character, position))]
+    UnexpectedCharacter { character: char, position: usize },
+    #[snafu(display("invalid number {:?}", number))]
+    InvalidNumber { number: String },
+    #[snafu(display("unexpected end of expression"))]
+    UnexpectedEnd,
+    #[snafu(display("unexpected {:?}", token))]
+    UnexpectedToken { token: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(Operator),
+    OpenParen,
+    CloseParen,
+}
+
+impl Expression {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut tokens = tokenize(input)?.into_iter().peekable();
+        let expression = parse_sum(&mut tokens)?;
+
+        match tokens.next() {
+            None => Ok(expression),
+            Some(token) => Err(ParseError::UnexpectedToken {
+                token: format!("{:?}", token),
+            }),
+        }
+    }
+
+    /// Returns the names of all variables referenced by the expression.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables<'a>(&'a self, variables: &mut Vec<&'a str>) {
+        match self {
+            Self::Number(_) => {}
+            Self::Variable(name) => variables.push(name),
+            Self::Negate(inner) => inner.collect_variables(variables),
+            Self::Binary(lhs, _, rhs) => {
+                lhs.collect_variables(variables);
+                rhs.collect_variables(variables);
+            }
+        }
+    }
+
+    /// Evaluates the expression, returning `None` if a variable is missing or
+    /// the result is not a finite number, e.g. after a division by zero.
+    pub fn evaluate(&self, variables: &HashMap<String, f64>) -> Option<f64> {
+        let result = match self {
+            Self::Number(number) => *number,
+            Self::Variable(name) => *variables.get(name)?,
+            Self::Negate(inner) => -inner.evaluate(variables)?,
+            Self::Binary(lhs, operator, rhs) => {
+                let lhs = lhs.evaluate(variables)?;
+                let rhs = rhs.evaluate(variables)?;
+                match operator {
+                    Operator::Add => lhs + rhs,
+                    Operator::Subtract => lhs - rhs,
+                    Operator::Multiply => lhs * rhs,
+                    Operator::Divide => lhs / rhs,
+                }
+            }
+        };
+
+        if result.is_finite() {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut position = 0;
+
+    while let Some(&character) = chars.peek() {
+        let token = match character {
+            c if c.is_whitespace() => {
+                chars.next();
+                position += 1;
+                continue;
+            }
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Operator(Operator::Subtract),
+            '*' => Token::Operator(Operator::Multiply),
+            '/' => Token::Operator(Operator::Divide),
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            c if c.is_ascii_digit() || c == '.' => {
+                let number = take_while(&mut chars, &mut position, |c| {
+                    c.is_ascii_digit() || c == '.'
+                });
+                tokens.push(Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| ParseError::InvalidNumber { number })?,
+                ));
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                tokens.push(Token::Identifier(take_while(
+                    &mut chars,
+                    &mut position,
+                    |c| c.is_ascii_alphanumeric() || c == '_',
+                )));
+                continue;
+            }
+            character => {
+                return Err(ParseError::UnexpectedCharacter {
+                    character,
+                    position,
+                })
+            }
+        };
+
+        tokens.push(token);
+        chars.next();
+        position += 1;
+    }
+
+    Ok(tokens)
+}
+
+fn take_while(
+    chars: &mut Peekable<Chars<'_>>,
+    position: &mut usize,
+    predicate: impl Fn(char) -> bool,
+) -> String {
+    let mut taken = String::new();
+    while let Some(&c) = chars.peek() {
+        if !predicate(c) {
+            break;
+        }
+        taken.push(c);
+        chars.next();
+        *position += 1;
+    }
+    taken
+}
+
+type Tokens = Peekable<std::vec::IntoIter<Token>>;
+
+fn parse_sum(tokens: &mut Tokens) -> Result<Expression, ParseError> {
+    let mut lhs = parse_product(tokens)?;
+    while let Some(Token::Operator(operator @ (Operator::Add | Operator::Subtract))) = tokens.peek()
+    {
+        let operator = *operator;
+        tokens.next();
+        let rhs = parse_product(tokens)?;
+        lhs = Expression::Binary(Box::new(lhs), operator, Box::new(rhs));
+    }
+    Ok(lhs)
+}
+
+fn parse_product(tokens: &mut Tokens) -> Result<Expression, ParseError> {
+    let mut lhs = parse_unary(tokens)?;
+    while let Some(Token::Operator(operator @ (Operator::Multiply | Operator::Divide))) =
+        tokens.peek()
+    {
+        let operator = *operator;
+        tokens.next();
+        let rhs = parse_unary(tokens)?;
+        lhs = Expression::Binary(Box::new(lhs), operator, Box::new(rhs));
+    }
+    Ok(lhs)
+}
+
+fn parse_unary(tokens: &mut Tokens) -> Result<Expression, ParseError> {
+    match tokens.next().ok_or(ParseError::UnexpectedEnd)? {
+        Token::Number(number) => Ok(Expression::Number(number)),
+        Token::Identifier(name) => Ok(Expression::Variable(name)),
+        Token::Operator(Operator::Subtract) => {
+            Ok(Expression::Negate(Box::new(parse_unary(tokens)?)))
+        }
+        Token::OpenParen => {
+            let inner = parse_sum(tokens)?;
+            match tokens.next() {
+                Some(Token::CloseParen) => Ok(inner),
+                Some(token) => Err(ParseError::UnexpectedToken {
+                    token: format!("{:?}", token),
+                }),
+                None => Err(ParseError::UnexpectedEnd),
+            }
+        }
+        token => Err(ParseError::UnexpectedToken {
+            token: format!("{:?}", token),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(expression: &str, variables: &[(&str, f64)]) -> Option<f64> {
+        let variables = variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+        Expression::parse(expression).unwrap().evaluate(&variables)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("1 + 2 * 3", &[]), Some(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3", &[]), Some(9.0));
+        assert_eq!(evaluate("10 - 4 - 3", &[]), Some(3.0));
+        assert_eq!(evaluate("-2 * -3", &[]), Some(6.0));
+        assert_eq!(evaluate("1.5 / 0.5", &[]), Some(3.0));
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            evaluate(
+                "errors / requests * 100",
+                &[("errors", 5.0), ("requests", 50.0)]
+            ),
+            Some(10.0)
+        );
+        assert_eq!(evaluate("errors / requests", &[("errors", 5.0)]), None);
+        assert_eq!(
+            evaluate("errors / requests", &[("errors", 5.0), ("requests", 0.0)]),
+            None
+        );
+
+        let expression = Expression::parse("(a + b_2) / a").unwrap();
+        assert_eq!(expression.variables(), vec!["a", "b_2", "a"]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Expression::parse("").is_err());
+        assert!(Expression::parse("1 +").is_err());
+        assert!(Expression::parse("(1 + 2").is_err());
+        assert!(Expression::parse("1 2").is_err());
+        assert!(Expression::parse("a % b").is_err());
+        assert!(Expression::parse("1..2").is_err());
+    }
+}
@@ -0,0 +1,808 @@
+use crate::{
+    config::{DataType, GenerateConfig, TransformConfig, TransformContext, TransformDescription},
+    event::{
+        metric::{Metric, MetricKind, MetricSeries, MetricTags, MetricValue},
+        Event,
+    },
+    internal_events::{MetricMathEvaluationFailed, MetricMathSeriesExpired},
+    sinks::util::buffer::metrics::{MetricNormalize, MetricSet},
+    transforms::{TaskTransform, Transform},
+};
+use async_stream::stream;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+mod expression;
+
+use expression::Expression;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MetricMathConfig {
+    /// The series to derive from the incoming metrics.
+    pub derive: Vec<DeriveConfig>,
+
+    /// The period of time after which the state of a series that stopped
+    /// receiving metrics is discarded.
+    #[serde(default = "default_expire_after_secs")]
+    pub expire_after_secs: u64,
+}
+
+const fn default_expire_after_secs() -> u64 {
+    300
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DeriveConfig {
+    /// The name of the derived metric.
+    pub name: String,
+
+    /// The namespace of the derived metric. Defaults to the namespace of the
+    /// source metric.
+    pub namespace: Option<String>,
+
+    #[serde(flatten)]
+    pub function: DeriveFunction,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum DeriveFunction {
+    /// The per-second rate of change of the source metric.
+    Rate { metric: String },
+
+    /// The change of the source metric since its previous value.
+    Delta { metric: String },
+
+    /// The increase of the source metric since its previous value, treating
+    /// any decrease as a counter reset.
+    Increase { metric: String },
+
+    /// The average of the most recent values of the source metric.
+    MovingAverage { metric: String, samples: usize },
+
+    /// An arithmetic expression over the latest values of several metrics
+    /// sharing the same tags.
+    Expression {
+        expression: String,
+        variables: IndexMap<String, String>,
+    },
+}
+
+inventory::submit! {
+    TransformDescription::new::<MetricMathConfig>("metric_math")
+}
+
+impl GenerateConfig for MetricMathConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"[[derive]]
+            name = "http_requests_per_second"
+            function = "rate"
+            metric = "http_requests_total""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "metric_math")]
+impl TransformConfig for MetricMathConfig {
+    async fn build(&self, _context: &TransformContext) -> crate::Result<Transform> {
+        MetricMath::new(self).map(Transform::task)
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "metric_math"
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Function {
+    Rate,
+    Delta,
+    Increase,
+    MovingAverage(usize),
+    Expression(Expression),
+}
+
+#[derive(Debug)]
+struct Derivation {
+    name: String,
+    namespace: Option<String>,
+    function: Function,
+
+    /// The source metric names, mapped to the variable they are bound to.
+    /// Single-source functions bind their metric to an empty variable name.
+    sources: HashMap<String, String>,
+}
+
+/// The state kept for every derivation and tag set.
+#[derive(Debug)]
+enum State {
+    Previous {
+        value: f64,
+        timestamp: DateTime<Utc>,
+    },
+    Samples(VecDeque<f64>),
+    Variables(HashMap<String, f64>),
+}
+
+#[derive(Debug)]
+struct Entry {
+    state: State,
+    last_seen: Instant,
+}
+
+/// Identifies the state of a derivation by the index of the derivation, and
+/// the namespace and tags shared by its source metrics.
+type StateKey = (usize, Option<String>, Option<MetricTags>);
+
+/// Converts incremental metrics into absolute ones, so that derivations
+/// always work on the running value of their source series.
+struct AbsoluteMetricNormalize;
+
+impl MetricNormalize for AbsoluteMetricNormalize {
+    fn apply_state(state: &mut MetricSet, metric: Metric) -> Option<Metric> {
+        state.make_absolute(metric)
+    }
+}
+
+#[derive(Debug)]
+pub struct MetricMath {
+    derivations: Vec<Derivation>,
+    expire_after: Duration,
+    states: HashMap<StateKey, Entry>,
+    normalizer: MetricSet,
+    /// The last time each source series was received, used to expire its
+    /// state in `normalizer`.
+    series: HashMap<MetricSeries, Instant>,
+}
+
+impl MetricMath {
+    pub fn new(config: &MetricMathConfig) -> crate::Result<Self> {
+        if config.expire_after_secs == 0 {
+            return Err("`expire_after_secs` must be greater than zero".into());
+        }
+
+        let derivations = config
+            .derive
+            .iter()
+            .map(Derivation::new)
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self {
+            derivations,
+            expire_after: Duration::from_secs(config.expire_after_secs),
+            states: HashMap::new(),
+            normalizer: MetricSet::default(),
+            series: HashMap::new(),
+        })
+    }
+
+    fn transform_one(&mut self, output: &mut Vec<Event>, event: Event) {
+        let metric = event.as_metric();
+
+        let derived = matches!(
+            metric.value(),
+            MetricValue::Counter { .. } | MetricValue::Gauge { .. }
+        ) && self
+            .derivations
+            .iter()
+            .any(|derivation| derivation.sources.contains_key(metric.name()));
+
+        if derived {
+            let now = Instant::now();
+            self.series.insert(metric.series().clone(), now);
+
+            let normalized =
+                AbsoluteMetricNormalize::apply_state(&mut self.normalizer, metric.clone());
+            let value = normalized.as_ref().and_then(|metric| match metric.value() {
+                MetricValue::Counter { value } | MetricValue::Gauge { value } => Some(*value),
+                _ => None,
+            });
+
+            if let (Some(metric), Some(value)) = (normalized, value) {
+                for index in 0..self.derivations.len() {
+                    if let Some(derived) = self.derive(index, &metric, value, now) {
+                        output.push(Event::Metric(derived));
+                    }
+                }
+            }
+        }
+
+        output.push(event);
+    }
+
+    fn derive(
+        &mut self,
+        index: usize,
+        metric: &Metric,
+        value: f64,
+        now: Instant,
+    ) -> Option<Metric> {
+        let derivation = &self.derivations[index];
+        let variable = derivation.sources.get(metric.name())?;
+
+        let timestamp = metric.timestamp().unwrap_or_else(Utc::now);
+        let counter = matches!(metric.value(), MetricValue::Counter { .. });
+
+        let entry = self
+            .states
+            .entry((
+                index,
+                metric.namespace().map(String::from),
+                metric.tags().cloned(),
+            ))
+            .or_insert_with(|| Entry {
+                state: derivation.function.initial_state(),
+                last_seen: now,
+            });
+        entry.last_seen = now;
+
+        let derived = match (&derivation.function, &mut entry.state) {
+            (
+                Function::Rate | Function::Delta | Function::Increase,
+                State::Previous {
+                    value: previous,
+                    timestamp: previous_timestamp,
+                },
+            ) => {
+                let first = previous.is_nan();
+                let change = if counter && value < *previous {
+                    // The counter was reset
+                    value
+                } else {
+                    value - *previous
+                };
+                let elapsed = (timestamp - *previous_timestamp).num_milliseconds() as f64 / 1000.0;
+
+                *previous = value;
+                *previous_timestamp = timestamp;
+
+                match derivation.function {
+                    _ if first => None,
+                    Function::Rate if elapsed <= 0.0 => None,
+                    Function::Rate => Some((
+                        MetricValue::Gauge {
+                            value: change / elapsed,
+                        },
+                        MetricKind::Absolute,
+                    )),
+                    Function::Delta => {
+                        Some((MetricValue::Gauge { value: change }, MetricKind::Absolute))
+                    }
+                    _ => Some((
+                        MetricValue::Counter {
+                            value: change.max(0.0),
+                        },
+                        MetricKind::Incremental,
+                    )),
+                }
+            }
+            (Function::MovingAverage(samples), State::Samples(values)) => {
+                if values.len() == *samples {
+                    values.pop_front();
+                }
+                values.push_back(value);
+
+                let average = values.iter().sum::<f64>() / values.len() as f64;
+                Some((MetricValue::Gauge { value: average }, MetricKind::Absolute))
+            }
+            (Function::Expression(expression), State::Variables(values)) => {
+                values.insert(variable.clone(), value);
+
+                if values.len() < derivation.sources.len() {
+                    return None;
+                }
+                match expression.evaluate(values) {
+                    Some(value) => Some((MetricValue::Gauge { value }, MetricKind::Absolute)),
+                    None => {
+                        emit!(&MetricMathEvaluationFailed {
+                            name: &derivation.name,
+                        });
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }?;
+
+        let (value, kind) = derived;
+        let namespace = derivation
+            .namespace
+            .clone()
+            .or_else(|| metric.namespace().map(String::from));
+
+        Some(
+            Metric::new(derivation.name.clone(), kind, value)
+                .with_namespace(namespace)
+                .with_tags(metric.tags().cloned())
+                .with_timestamp(metric.timestamp()),
+        )
+    }
+
+    /// Discards the state of every series that did not receive any metric for
+    /// `expire_after`.
+    fn expire(&mut self, now: Instant) {
+        let expire_after = self.expire_after;
+        let count = self.states.len();
+        self.states
+            .retain(|_, entry| now.duration_since(entry.last_seen) < expire_after);
+
+        let normalizer = &mut self.normalizer;
+        self.series.retain(|series, last_seen| {
+            let retain = now.duration_since(*last_seen) < expire_after;
+            if !retain {
+                normalizer.remove(series);
+            }
+            retain
+        });
+
+        let expired = count - self.states.len();
+        if expired > 0 {
+            emit!(&MetricMathSeriesExpired { count: expired });
+        }
+    }
+}
+
+impl Derivation {
+    fn new(config: &DeriveConfig) -> crate::Result<Self> {
+        let single = |metric: &String| std::iter::once((metric.clone(), String::new())).collect();
+
+        let (function, sources) = match &config.function {
+            DeriveFunction::Rate { metric } => (Function::Rate, single(metric)),
+            DeriveFunction::Delta { metric } => (Function::Delta, single(metric)),
+            DeriveFunction::Increase { metric } => (Function::Increase, single(metric)),
+            DeriveFunction::MovingAverage { metric, samples } => {
+                if *samples == 0 {
+                    return Err(format!(
+                        "`samples` of derived metric {:?} must be greater than zero",
+                        config.name
+                    )
+                    .into());
+                }
+                (Function::MovingAverage(*samples), single(metric))
+            }
+            DeriveFunction::Expression {
+                expression,
+                variables,
+            } => {
+                let expression = Expression::parse(expression).map_err(|error| {
+                    format!(
+                        "Invalid expression for derived metric {:?}: {}",
+                        config.name, error
+                    )
+                })?;
+
+                if let Some(unknown) = expression
+                    .variables()
+                    .into_iter()
+                    .find(|variable| !variables.contains_key(*variable))
+                {
+                    return Err(format!(
+                        "Expression for derived metric {:?} references unknown variable {:?}",
+                        config.name, unknown
+                    )
+                    .into());
+                }
+
+                let sources = variables
+                    .iter()
+                    .map(|(variable, metric)| (metric.clone(), variable.clone()))
+                    .collect::<HashMap<_, _>>();
+                if sources.len() != variables.len() {
+                    return Err(format!(
+                        "Variables of derived metric {:?} must refer to distinct metrics",
+                        config.name
+                    )
+                    .into());
+                }
+
+                (Function::Expression(expression), sources)
+            }
+        };
+
+        Ok(Self {
+            name: config.name.clone(),
+            namespace: config.namespace.clone(),
+            function,
+            sources,
+        })
+    }
+}
+
+impl Function {
+    fn initial_state(&self) -> State {
+        match self {
+            Self::Rate | Self::Delta | Self::Increase => State::Previous {
+                value: f64::NAN,
+                timestamp: Utc::now(),
+            },
+            Self::MovingAverage(samples) => State::Samples(VecDeque::with_capacity(*samples)),
+            Self::Expression(_) => State::Variables(HashMap::new()),
+        }
+    }
+}
+
+impl TaskTransform for MetricMath {
+    fn transform(
+        mut self: Box<Self>,
+        mut input_rx: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Event> + Send>>
+    where
+        Self: 'static,
+    {
+        let mut expire_stream = tokio::time::interval(self.expire_after / 2);
+
+        Box::pin(stream! {
+            let mut output = Vec::new();
+            loop {
+                tokio::select! {
+                    _ = expire_stream.tick() => {
+                        self.expire(Instant::now());
+                    },
+                    maybe_event = input_rx.next() => {
+                        match maybe_event {
+                            None => break,
+                            Some(event) => self.transform_one(&mut output, event),
+                        }
+                    }
+                };
+                for event in output.drain(..) {
+                    yield event;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use shared::btreemap;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MetricMathConfig>();
+    }
+
+    fn metric_math(config: &str) -> MetricMath {
+        MetricMath::new(&toml::from_str::<MetricMathConfig>(config).unwrap()).unwrap()
+    }
+
+    fn metric(name: &str, kind: MetricKind, value: MetricValue, secs: i64) -> Event {
+        Event::Metric(
+            Metric::new(name, kind, value)
+                .with_tags(Some(btreemap! { "host" => "a" }))
+                .with_timestamp(Some(Utc.timestamp(secs, 0))),
+        )
+    }
+
+    fn counter(value: f64, secs: i64) -> Event {
+        metric(
+            "requests",
+            MetricKind::Absolute,
+            MetricValue::Counter { value },
+            secs,
+        )
+    }
+
+    /// Runs the events through the transform, returning the derived metrics.
+    fn derived(math: &mut MetricMath, events: Vec<Event>) -> Vec<Metric> {
+        let mut output = Vec::new();
+        for event in events {
+            math.transform_one(&mut output, event);
+        }
+        output
+            .into_iter()
+            .map(Event::into_metric)
+            .filter(|metric| metric.name() != "requests" && metric.name() != "errors")
+            .collect()
+    }
+
+    #[test]
+    fn rate() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "requests_per_second"
+            function = "rate"
+            metric = "requests""#,
+        );
+
+        let out = derived(
+            &mut math,
+            vec![counter(100.0, 0), counter(120.0, 10), counter(5.0, 15)],
+        );
+
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].name(), "requests_per_second");
+        assert_eq!(out[0].tags(), Some(&btreemap! { "host" => "a" }));
+        assert_eq!(out[0].value(), &MetricValue::Gauge { value: 2.0 });
+        // The counter reset is treated as an increase from zero
+        assert_eq!(out[1].value(), &MetricValue::Gauge { value: 1.0 });
+    }
+
+    #[test]
+    fn incremental_rate() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "requests_per_second"
+            function = "rate"
+            metric = "requests""#,
+        );
+
+        let counter = |value, secs| {
+            metric(
+                "requests",
+                MetricKind::Incremental,
+                MetricValue::Counter { value },
+                secs,
+            )
+        };
+
+        let out = derived(&mut math, vec![counter(3.0, 0), counter(10.0, 5)]);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].value(), &MetricValue::Gauge { value: 2.0 });
+    }
+
+    #[test]
+    fn incremental_delta() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "delta"
+            function = "delta"
+            metric = "requests""#,
+        );
+
+        let gauge = |value, secs| {
+            metric(
+                "requests",
+                MetricKind::Incremental,
+                MetricValue::Gauge { value },
+                secs,
+            )
+        };
+
+        // Incremental metrics are normalized to their absolute value first.
+        let out = derived(
+            &mut math,
+            vec![gauge(3.0, 0), gauge(-1.0, 1), gauge(4.0, 2)],
+        );
+        let values = out.iter().map(Metric::value).collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                &MetricValue::Gauge { value: -1.0 },
+                &MetricValue::Gauge { value: 4.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn series_keyed_by_namespace() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "delta"
+            function = "delta"
+            metric = "requests""#,
+        );
+
+        let namespaced = |namespace: &str, value, secs| {
+            Event::Metric(
+                counter(value, secs)
+                    .into_metric()
+                    .with_namespace(Some(namespace)),
+            )
+        };
+
+        let out = derived(
+            &mut math,
+            vec![
+                namespaced("a", 10.0, 0),
+                namespaced("b", 100.0, 0),
+                namespaced("a", 15.0, 1),
+                namespaced("b", 130.0, 1),
+            ],
+        );
+
+        let values = out
+            .iter()
+            .map(|metric| (metric.namespace(), metric.value().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                (Some("a"), MetricValue::Gauge { value: 5.0 }),
+                (Some("b"), MetricValue::Gauge { value: 30.0 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn delta_and_increase() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "delta"
+            function = "delta"
+            metric = "requests"
+
+            [[derive]]
+            name = "increase"
+            function = "increase"
+            metric = "requests""#,
+        );
+
+        let out = derived(
+            &mut math,
+            vec![counter(10.0, 0), counter(15.0, 1), counter(4.0, 2)],
+        );
+
+        let values = out
+            .iter()
+            .map(|metric| (metric.name(), metric.kind(), metric.value().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                (
+                    "delta",
+                    MetricKind::Absolute,
+                    MetricValue::Gauge { value: 5.0 }
+                ),
+                (
+                    "increase",
+                    MetricKind::Incremental,
+                    MetricValue::Counter { value: 5.0 }
+                ),
+                (
+                    "delta",
+                    MetricKind::Absolute,
+                    MetricValue::Gauge { value: 4.0 }
+                ),
+                (
+                    "increase",
+                    MetricKind::Incremental,
+                    MetricValue::Counter { value: 4.0 }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn moving_average() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "requests_average"
+            function = "moving_average"
+            metric = "requests"
+            samples = 2"#,
+        );
+
+        let out = derived(
+            &mut math,
+            vec![counter(2.0, 0), counter(4.0, 1), counter(8.0, 2)],
+        );
+
+        let values = out.iter().map(Metric::value).collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                &MetricValue::Gauge { value: 2.0 },
+                &MetricValue::Gauge { value: 3.0 },
+                &MetricValue::Gauge { value: 6.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn expression() {
+        let mut math = metric_math(
+            r#"[[derive]]
+            name = "error_ratio"
+            function = "expression"
+            expression = "errors / requests"
+            variables.errors = "errors"
+            variables.requests = "requests""#,
+        );
+
+        let errors = |value, host| {
+            Event::Metric(
+                Metric::new(
+                    "errors",
+                    MetricKind::Absolute,
+                    MetricValue::Counter { value },
+                )
+                .with_tags(Some(btreemap! { "host" => host })),
+            )
+        };
+        let requests = |value, host| {
+            Event::Metric(
+                Metric::new(
+                    "requests",
+                    MetricKind::Absolute,
+                    MetricValue::Counter { value },
+                )
+                .with_tags(Some(btreemap! { "host" => host })),
+            )
+        };
+
+        let out = derived(
+            &mut math,
+            vec![
+                errors(5.0, "a"),
+                requests(20.0, "b"),
+                requests(50.0, "a"),
+                errors(10.0, "a"),
+                requests(0.0, "a"),
+            ],
+        );
+
+        // Host "b" has no errors yet and the division by zero is skipped.
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].name(), "error_ratio");
+        assert_eq!(out[0].tags(), Some(&btreemap! { "host" => "a" }));
+        assert_eq!(out[0].value(), &MetricValue::Gauge { value: 0.1 });
+        assert_eq!(out[1].value(), &MetricValue::Gauge { value: 0.2 });
+    }
+
+    #[test]
+    fn expire() {
+        let mut math = metric_math(
+            r#"expire_after_secs = 10
+
+            [[derive]]
+            name = "delta"
+            function = "delta"
+            metric = "requests""#,
+        );
+
+        assert!(derived(&mut math, vec![counter(10.0, 0)]).is_empty());
+        math.expire(Instant::now() + Duration::from_secs(11));
+
+        // The previous value was discarded, so no delta is calculated.
+        assert!(derived(&mut math, vec![counter(15.0, 1)]).is_empty());
+    }
+
+    #[test]
+    fn invalid_config() {
+        for config in [
+            r#"[[derive]]
+            name = "ratio"
+            function = "expression"
+            expression = "errors / total"
+            variables.errors = "errors""#,
+            r#"[[derive]]
+            name = "ratio"
+            function = "expression"
+            expression = "errors /"
+            variables.errors = "errors""#,
+            r#"[[derive]]
+            name = "average"
+            function = "moving_average"
+            metric = "requests"
+            samples = 0"#,
+        ] {
+            let config = toml::from_str::<MetricMathConfig>(config).unwrap();
+            assert!(MetricMath::new(&config).is_err());
+        }
+    }
+}
@@ -28,6 +28,8 @@ pub mod filter;
 pub mod geoip;
 #[cfg(feature = "transforms-grok_parser")]
 pub mod grok_parser;
+#[cfg(feature = "transforms-join")]
+pub mod join;
 #[cfg(feature = "transforms-json_parser")]
 pub mod json_parser;
 #[cfg(feature = "transforms-key_value_parser")]
@@ -40,6 +42,8 @@ pub mod logfmt_parser;
 pub mod lua;
 #[cfg(feature = "transforms-merge")]
 pub mod merge;
+#[cfg(feature = "transforms-metric_math")]
+pub mod metric_math;
 #[cfg(feature = "transforms-metric_to_log")]
 pub mod metric_to_log;
 pub mod noop;
@@ -71,7 +75,7 @@ pub mod throttle;
 pub mod tokenizer;
 
 pub use vector_core::transform::{
-    FallibleFunctionTransform, FunctionTransform, TaskTransform, Transform,
+    FallibleFunctionTransform, FallibleTaskTransform, FunctionTransform, TaskTransform, Transform,
 };
 
 #[derive(Debug, Snafu)]
@@ -18,6 +18,11 @@ pub enum MergeStrategy {
     ShortestArray,
     LongestArray,
     FlatUnique,
+    Count,
+    CountDistinct,
+    Avg,
+    FirstNonNull,
+    Percentile(f64),
 }
 
 //------------------------------------------------------------------------------
@@ -279,6 +284,87 @@ impl ReduceValueMerger for FlatUniqueMerger {
 
 //------------------------------------------------------------------------------
 
+#[derive(Debug, Clone)]
+struct FirstNonNullMerger {
+    v: Value,
+}
+
+impl FirstNonNullMerger {
+    #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
+    fn new(v: Value) -> Self {
+        Self { v }
+    }
+}
+
+impl ReduceValueMerger for FirstNonNullMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        if Value::Null == self.v {
+            self.v = v;
+        }
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k, self.v);
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct CountMerger {
+    count: i64,
+}
+
+impl CountMerger {
+    const fn new() -> Self {
+        Self { count: 1 }
+    }
+}
+
+impl ReduceValueMerger for CountMerger {
+    fn add(&mut self, _v: Value) -> Result<(), String> {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k, Value::Integer(self.count));
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct CountDistinctMerger {
+    v: HashSet<Value>,
+}
+
+impl CountDistinctMerger {
+    #[allow(clippy::mutable_key_type)] // false positive due to bytes::Bytes
+    fn new(v: Value) -> Self {
+        let mut h = HashSet::default();
+        h.insert(v);
+        Self { v: h }
+    }
+}
+
+impl ReduceValueMerger for CountDistinctMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        self.v.insert(v);
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k, Value::Integer(self.v.len() as i64));
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
 #[derive(Debug, Clone)]
 struct TimestampWindowMerger {
     started: DateTime<Utc>,
@@ -503,6 +589,83 @@ impl ReduceValueMerger for MinNumberMerger {
 
 //------------------------------------------------------------------------------
 
+fn numeric_value(v: &Value) -> Result<f64, String> {
+    match v {
+        Value::Integer(i) => Ok(*i as f64),
+        Value::Float(f) => Ok(*f),
+        _ => Err(format!(
+            "expected numeric value, found: '{}'",
+            v.to_string_lossy()
+        )),
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct AvgMerger {
+    sum: f64,
+    count: usize,
+}
+
+impl AvgMerger {
+    const fn new(v: f64) -> Self {
+        Self { sum: v, count: 1 }
+    }
+}
+
+impl ReduceValueMerger for AvgMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        self.sum += numeric_value(&v)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        v.insert(k, Value::Float(self.sum / self.count as f64));
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct PercentileMerger {
+    percentile: f64,
+    values: Vec<f64>,
+}
+
+impl PercentileMerger {
+    fn new(percentile: f64, v: f64) -> Self {
+        Self {
+            percentile,
+            values: vec![v],
+        }
+    }
+}
+
+impl ReduceValueMerger for PercentileMerger {
+    fn add(&mut self, v: Value) -> Result<(), String> {
+        self.values.push(numeric_value(&v)?);
+        Ok(())
+    }
+
+    fn insert_into(mut self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
+        // Linearly interpolate between the two closest ranks.
+        self.values
+            .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let rank = self.percentile / 100.0 * (self.values.len() - 1) as f64;
+        let lower = self.values[rank.floor() as usize];
+        let upper = self.values[rank.ceil() as usize];
+
+        v.insert(k, Value::Float(lower + (upper - lower) * rank.fract()));
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
 pub trait ReduceValueMerger: std::fmt::Debug + Send + Sync {
     fn add(&mut self, v: Value) -> Result<(), String>;
     fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String>;
@@ -582,6 +745,19 @@ pub fn get_value_merger(v: Value, m: &MergeStrategy) -> Result<Box<dyn ReduceVal
         MergeStrategy::Discard => Ok(Box::new(DiscardMerger::new(v))),
         MergeStrategy::Retain => Ok(Box::new(RetainMerger::new(v))),
         MergeStrategy::FlatUnique => Ok(Box::new(FlatUniqueMerger::new(v))),
+        MergeStrategy::Count => Ok(Box::new(CountMerger::new())),
+        MergeStrategy::CountDistinct => Ok(Box::new(CountDistinctMerger::new(v))),
+        MergeStrategy::FirstNonNull => Ok(Box::new(FirstNonNullMerger::new(v))),
+        MergeStrategy::Avg => Ok(Box::new(AvgMerger::new(numeric_value(&v)?))),
+        MergeStrategy::Percentile(p) => {
+            if !(0.0..=100.0).contains(p) {
+                return Err(format!(
+                    "percentile must be between 0 and 100, found: {}",
+                    p
+                ));
+            }
+            Ok(Box::new(PercentileMerger::new(*p, numeric_value(&v)?)))
+        }
     }
 }
 
@@ -688,6 +864,17 @@ mod test {
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::Concat).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::ConcatNewline).is_err());
         assert!(get_value_merger(json!(null).into(), &MergeStrategy::FlatUnique).is_ok());
+
+        assert!(get_value_merger("foo".into(), &MergeStrategy::Count).is_ok());
+        assert!(get_value_merger("foo".into(), &MergeStrategy::CountDistinct).is_ok());
+        assert!(get_value_merger("foo".into(), &MergeStrategy::FirstNonNull).is_ok());
+        assert!(get_value_merger("foo".into(), &MergeStrategy::Avg).is_err());
+        assert!(get_value_merger("foo".into(), &MergeStrategy::Percentile(50.0)).is_err());
+        assert!(get_value_merger(42.into(), &MergeStrategy::Avg).is_ok());
+        assert!(get_value_merger(4.2.into(), &MergeStrategy::Avg).is_ok());
+        assert!(get_value_merger(42.into(), &MergeStrategy::Percentile(50.0)).is_ok());
+        assert!(get_value_merger(42.into(), &MergeStrategy::Percentile(100.1)).is_err());
+        assert!(get_value_merger(json!(null).into(), &MergeStrategy::FirstNonNull).is_ok());
     }
 
     #[test]
@@ -789,6 +976,48 @@ mod test {
             Ok(json!([42, 43]).into())
         );
 
+        assert_eq!(
+            merge("foo".into(), "foo".into(), &MergeStrategy::Count),
+            Ok(2.into())
+        );
+        assert_eq!(
+            merge("foo".into(), "foo".into(), &MergeStrategy::CountDistinct),
+            Ok(1.into())
+        );
+        assert_eq!(
+            merge("foo".into(), "bar".into(), &MergeStrategy::CountDistinct),
+            Ok(2.into())
+        );
+        assert_eq!(
+            merge(
+                json!(null).into(),
+                "bar".into(),
+                &MergeStrategy::FirstNonNull
+            ),
+            Ok("bar".into())
+        );
+        assert_eq!(
+            merge("foo".into(), "bar".into(), &MergeStrategy::FirstNonNull),
+            Ok("foo".into())
+        );
+        assert_eq!(
+            merge(41.into(), 4.0.into(), &MergeStrategy::Avg),
+            Ok(22.5.into())
+        );
+        assert!(merge(41.into(), "foo".into(), &MergeStrategy::Avg).is_err());
+        assert_eq!(
+            merge(10.into(), 20.into(), &MergeStrategy::Percentile(50.0)),
+            Ok(15.0.into())
+        );
+        assert_eq!(
+            merge(20.into(), 10.into(), &MergeStrategy::Percentile(100.0)),
+            Ok(20.0.into())
+        );
+        assert_eq!(
+            merge(20.into(), 10.into(), &MergeStrategy::Percentile(0.0)),
+            Ok(10.0.into())
+        );
+
         let v = merge(34.into(), 43.into(), &MergeStrategy::FlatUnique).unwrap();
         if let Value::Array(v) = v.clone() {
             let v: Vec<_> = v
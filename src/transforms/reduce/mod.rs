@@ -1,11 +1,14 @@
 use crate::{
     conditions::{AnyCondition, Condition},
-    config::{DataType, TransformConfig, TransformContext, TransformDescription},
-    event::{discriminant::Discriminant, Event, EventMetadata, LogEvent},
-    internal_events::ReduceStaleEventFlushed,
+    config::{log_schema, DataType, TransformConfig, TransformContext, TransformDescription},
+    event::{discriminant::Discriminant, Event, EventMetadata, LogEvent, Value},
+    internal_events::{
+        ReduceEventMissingTimestamp, ReduceLateEventDropped, ReduceStaleEventFlushed,
+    },
     transforms::{TaskTransform, Transform},
 };
 use async_stream::stream;
+use chrono::{DateTime, Utc};
 use futures::{stream, Stream, StreamExt};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
@@ -16,8 +19,10 @@ use std::{
 };
 
 mod merge_strategy;
+mod window;
 
 use merge_strategy::*;
+pub use window::WindowConfig;
 
 //------------------------------------------------------------------------------
 
@@ -40,6 +45,14 @@ pub struct ReduceConfig {
     /// reduce.
     pub ends_when: Option<AnyCondition>,
     pub starts_when: Option<AnyCondition>,
+
+    /// The maximum number of events combined into a single reduce. Once
+    /// reached, the reduce is flushed and the next event starts a new one.
+    pub max_events: Option<usize>,
+
+    /// Groups events into time windows based on their timestamp, in addition
+    /// to `group_by`.
+    pub window: Option<WindowConfig>,
 }
 
 inventory::submit! {
@@ -70,6 +83,7 @@ impl TransformConfig for ReduceConfig {
 
 #[derive(Debug)]
 struct ReduceState {
+    events: usize,
     fields: HashMap<String, Box<dyn ReduceValueMerger>>,
     stale_since: Instant,
     metadata: EventMetadata,
@@ -79,6 +93,7 @@ impl ReduceState {
     fn new(e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) -> Self {
         let (fields, metadata) = e.into_parts();
         Self {
+            events: 1,
             stale_since: Instant::now(),
             fields: fields
                 .into_iter()
@@ -103,6 +118,7 @@ impl ReduceState {
     fn add_event(&mut self, e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) {
         let (fields, metadata) = e.into_parts();
         self.metadata.merge(metadata);
+        self.events += 1;
 
         for (k, v) in fields.into_iter() {
             let strategy = strategies.get(&k);
@@ -144,14 +160,24 @@ impl ReduceState {
 
 //------------------------------------------------------------------------------
 
+/// Identifies a reduce by its `group_by` fields and, when windowing is
+/// enabled, the start of its window in milliseconds since the Unix epoch.
+type ReduceKey = (Discriminant, Option<i64>);
+
 pub struct Reduce {
     expire_after: Duration,
     flush_period: Duration,
     group_by: Vec<String>,
     merge_strategies: IndexMap<String, MergeStrategy>,
-    reduce_merge_states: HashMap<Discriminant, ReduceState>,
+    reduce_merge_states: HashMap<ReduceKey, ReduceState>,
     ends_when: Option<Box<dyn Condition>>,
     starts_when: Option<Box<dyn Condition>>,
+    max_events: Option<usize>,
+    window: Option<WindowConfig>,
+    watermark: Option<DateTime<Utc>>,
+    /// When `watermark` was last advanced, so that it keeps advancing with
+    /// the wall clock while no newer events are received.
+    watermark_at: Instant,
 }
 
 impl Reduce {
@@ -163,6 +189,29 @@ impl Reduce {
             return Err("only one of `ends_when` and `starts_when` can be provided".into());
         }
 
+        if let Some(window) = &config.window {
+            if config.ends_when.is_some() || config.starts_when.is_some() {
+                return Err("`window` cannot be combined with `ends_when` or `starts_when`".into());
+            }
+            window.validate()?;
+        }
+
+        if config.max_events == Some(0) {
+            return Err("`max_events` must be greater than zero".into());
+        }
+
+        for (field, strategy) in &config.merge_strategies {
+            if let MergeStrategy::Percentile(p) = strategy {
+                if !(0.0..=100.0).contains(p) {
+                    return Err(format!(
+                        "percentile for field {:?} must be between 0 and 100, found: {}",
+                        field, p
+                    )
+                    .into());
+                }
+            }
+        }
+
         let ends_when = config
             .ends_when
             .as_ref()
@@ -183,13 +232,19 @@ impl Reduce {
             reduce_merge_states: HashMap::new(),
             ends_when,
             starts_when,
+            max_events: config.max_events,
+            window: config.window,
+            watermark: None,
+            watermark_at: Instant::now(),
         })
     }
 
     fn flush_into(&mut self, output: &mut Vec<Event>) {
         let mut flush_discriminants = Vec::new();
         for (k, t) in &self.reduce_merge_states {
-            if t.stale_since.elapsed() >= self.expire_after {
+            // Windows are flushed according to event time instead, see
+            // `flush_closed_windows_into`.
+            if k.1.is_none() && t.stale_since.elapsed() >= self.expire_after {
                 flush_discriminants.push(k.clone());
             }
         }
@@ -199,6 +254,22 @@ impl Reduce {
                 output.push(Event::from(t.flush()));
             }
         }
+
+        self.advance_watermark();
+        self.flush_closed_windows_into(output);
+    }
+
+    /// Moves the watermark forward by the time elapsed since it was last
+    /// advanced, so the last windows of a stream that went idle are still
+    /// closed once their end has passed.
+    fn advance_watermark(&mut self) {
+        if let Some(watermark) = self.watermark {
+            let now = Instant::now();
+            if let Ok(elapsed) = chrono::Duration::from_std(now.duration_since(self.watermark_at)) {
+                self.watermark = watermark.checked_add_signed(elapsed).or(Some(watermark));
+                self.watermark_at = now;
+            }
+        }
     }
 
     fn flush_all_into(&mut self, output: &mut Vec<Event>) {
@@ -207,15 +278,105 @@ impl Reduce {
             .for_each(|(_, s)| output.push(Event::from(s.flush())));
     }
 
-    fn push_or_new_reduce_state(&mut self, event: LogEvent, discriminant: Discriminant) {
-        match self.reduce_merge_states.entry(discriminant) {
+    /// Flushes every window that ended, including the allowed lateness,
+    /// before the most recent event timestamp.
+    fn flush_closed_windows_into(&mut self, output: &mut Vec<Event>) {
+        let (window, watermark) = match (&self.window, self.watermark) {
+            (Some(window), Some(watermark)) => (window, watermark),
+            _ => return,
+        };
+
+        let closed = self
+            .reduce_merge_states
+            .keys()
+            .filter(
+                |(_, start)| matches!(start, Some(start) if window.is_closed(*start, watermark)),
+            )
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for key in &closed {
+            if let Some(state) = self.reduce_merge_states.remove(key) {
+                output.push(Event::from(state.flush()));
+            }
+        }
+    }
+
+    fn push_or_new_reduce_state(
+        &mut self,
+        output: &mut Vec<Event>,
+        event: LogEvent,
+        key: ReduceKey,
+    ) {
+        let events = match self.reduce_merge_states.entry(key.clone()) {
             hash_map::Entry::Vacant(entry) => {
-                entry.insert(ReduceState::new(event, &self.merge_strategies));
+                entry
+                    .insert(ReduceState::new(event, &self.merge_strategies))
+                    .events
             }
             hash_map::Entry::Occupied(mut entry) => {
-                entry.get_mut().add_event(event, &self.merge_strategies);
+                let state = entry.get_mut();
+                state.add_event(event, &self.merge_strategies);
+                state.events
+            }
+        };
+
+        if matches!(self.max_events, Some(max_events) if events >= max_events) {
+            if let Some(state) = self.reduce_merge_states.remove(&key) {
+                output.push(Event::from(state.flush()));
+            }
+        }
+    }
+
+    fn transform_windowed(
+        &mut self,
+        output: &mut Vec<Event>,
+        event: LogEvent,
+        discriminant: Discriminant,
+        window: WindowConfig,
+    ) {
+        let timestamp = match event.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => *timestamp,
+            _ => {
+                emit!(&ReduceEventMissingTimestamp);
+                return;
             }
+        };
+
+        // Adding an event to a window that was already flushed would emit
+        // another, partial, result for that window.
+        let watermark = self.watermark;
+        let windows = window
+            .windows(timestamp)
+            .into_iter()
+            .filter(|start| {
+                watermark.map_or(true, |watermark| !window.is_closed(*start, watermark))
+            })
+            .collect::<Vec<_>>();
+        if windows.is_empty() {
+            emit!(&ReduceLateEventDropped);
+            return;
         }
+
+        if let Some((last, rest)) = windows.split_last() {
+            for start in rest {
+                self.push_or_new_reduce_state(
+                    output,
+                    event.clone(),
+                    (discriminant.clone(), Some(*start)),
+                );
+            }
+            self.push_or_new_reduce_state(output, event, (discriminant, Some(*last)));
+        }
+
+        if self
+            .watermark
+            .map_or(true, |watermark| timestamp > watermark)
+        {
+            self.watermark = Some(timestamp);
+            self.watermark_at = Instant::now();
+        }
+        self.flush_closed_windows_into(output);
     }
 
     fn transform_one(&mut self, output: &mut Vec<Event>, event: Event) {
@@ -233,24 +394,29 @@ impl Reduce {
         let event = event.into_log();
         let discriminant = Discriminant::from_log_event(&event, &self.group_by);
 
-        if starts_here {
-            if let Some(state) = self.reduce_merge_states.remove(&discriminant) {
+        if let Some(window) = self.window {
+            self.transform_windowed(output, event, discriminant, window);
+        } else if starts_here {
+            let key = (discriminant, None);
+            if let Some(state) = self.reduce_merge_states.remove(&key) {
                 output.push(state.flush().into());
             }
 
-            self.push_or_new_reduce_state(event, discriminant)
+            self.push_or_new_reduce_state(output, event, key)
         } else if ends_here {
-            output.push(match self.reduce_merge_states.remove(&discriminant) {
-                Some(mut state) => {
-                    state.add_event(event, &self.merge_strategies);
-                    state.flush().into()
-                }
-                None => ReduceState::new(event, &self.merge_strategies)
-                    .flush()
-                    .into(),
-            })
+            output.push(
+                match self.reduce_merge_states.remove(&(discriminant, None)) {
+                    Some(mut state) => {
+                        state.add_event(event, &self.merge_strategies);
+                        state.flush().into()
+                    }
+                    None => ReduceState::new(event, &self.merge_strategies)
+                        .flush()
+                        .into(),
+                },
+            )
         } else {
-            self.push_or_new_reduce_state(event, discriminant)
+            self.push_or_new_reduce_state(output, event, (discriminant, None))
         }
 
         self.flush_into(output);
@@ -309,6 +475,7 @@ mod test {
         config::TransformConfig,
         event::{LogEvent, Value},
     };
+    use chrono::TimeZone;
     use serde_json::json;
 
     #[test]
@@ -561,4 +728,210 @@ merge_strategies.bar = "concat"
         assert_eq!(output_2["bar"], json!([2, 4, 6, 8, "done"]).into());
         assert_eq!(output_2.metadata(), &metadata_2);
     }
+
+    #[tokio::test]
+    async fn max_events() {
+        let reduce = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+max_events = 2
+
+merge_strategies.counter = "count"
+"#,
+        )
+        .unwrap()
+        .build(&TransformContext::default())
+        .await
+        .unwrap();
+        let reduce = reduce.into_task();
+
+        let inputs = (1..=3)
+            .map(|i| {
+                let mut e = LogEvent::from(format!("test message {}", i));
+                e.insert("counter", i);
+                e.insert("request_id", "1");
+                e.into()
+            })
+            .collect::<Vec<Event>>();
+        let in_stream = Box::pin(stream::iter(inputs));
+        let mut out_stream = reduce.transform(in_stream);
+
+        let output_1 = out_stream.next().await.unwrap().into_log();
+        assert_eq!(output_1["message"], "test message 1".into());
+        assert_eq!(output_1["counter"], Value::from(2));
+
+        let output_2 = out_stream.next().await.unwrap().into_log();
+        assert_eq!(output_2["message"], "test message 3".into());
+        assert_eq!(output_2["counter"], Value::from(1));
+    }
+
+    #[tokio::test]
+    async fn tumbling_window() {
+        let reduce = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "user_id" ]
+
+merge_strategies.duration = "avg"
+merge_strategies.page = "count_distinct"
+merge_strategies.latency = { percentile = 50 }
+
+[window]
+  type = "tumbling"
+  duration_ms = 60000
+"#,
+        )
+        .unwrap()
+        .build(&TransformContext::default())
+        .await
+        .unwrap();
+        let reduce = reduce.into_task();
+
+        let event = |seconds: i64, page: &str, duration: i64| {
+            let mut e = LogEvent::from("test message");
+            e.insert(log_schema().timestamp_key(), Utc.timestamp(seconds, 0));
+            e.insert("user_id", "1");
+            e.insert("page", page);
+            e.insert("duration", duration);
+            e.insert("latency", duration);
+            Event::from(e)
+        };
+
+        let inputs = vec![
+            event(0, "/", 10),
+            event(10, "/about", 20),
+            event(59, "/", 60),
+            event(60, "/", 100),
+        ];
+        let in_stream = Box::pin(stream::iter(inputs));
+        let mut out_stream = reduce.transform(in_stream);
+
+        let output_1 = out_stream.next().await.unwrap().into_log();
+        assert_eq!(output_1["duration"], Value::from(30.0));
+        assert_eq!(output_1["page"], Value::from(2));
+        assert_eq!(output_1["latency"], Value::from(20.0));
+        assert_eq!(
+            output_1[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp(0, 0))
+        );
+
+        let output_2 = out_stream.next().await.unwrap().into_log();
+        assert_eq!(output_2["duration"], Value::from(100.0));
+        assert_eq!(
+            output_2[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp(60, 0))
+        );
+
+        assert!(out_stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn window_late_events() {
+        let reduce = toml::from_str::<ReduceConfig>(
+            r#"
+[window]
+  type = "tumbling"
+  duration_ms = 60000
+  allowed_lateness_ms = 5000
+"#,
+        )
+        .unwrap()
+        .build(&TransformContext::default())
+        .await
+        .unwrap();
+        let reduce = reduce.into_task();
+
+        let event = |seconds: i64| {
+            let mut e = LogEvent::from("test message");
+            e.insert(log_schema().timestamp_key(), Utc.timestamp(seconds, 0));
+            e.insert("count", 1);
+            Event::from(e)
+        };
+
+        // The event at 30 seconds is within the allowed lateness, the one at
+        // 20 seconds arrives after the first window was closed.
+        let inputs = vec![event(0), event(61), event(30), event(70), event(20)];
+        let in_stream = Box::pin(stream::iter(inputs));
+        let mut out_stream = reduce.transform(in_stream);
+
+        let output_1 = out_stream.next().await.unwrap().into_log();
+        assert_eq!(output_1["count"], Value::from(2));
+        assert_eq!(
+            output_1[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp(0, 0))
+        );
+
+        let output_2 = out_stream.next().await.unwrap().into_log();
+        assert_eq!(output_2["count"], Value::from(2));
+        assert_eq!(
+            output_2[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp(61, 0))
+        );
+
+        assert!(out_stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn window_with_conditions() {
+        let config = toml::from_str::<ReduceConfig>(
+            r#"
+[window]
+  type = "sliding"
+  duration_ms = 60000
+  slide_ms = 10000
+
+[ends_when]
+  type = "check_fields"
+  "test_end.exists" = true
+"#,
+        )
+        .unwrap();
+
+        assert!(config.build(&TransformContext::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn idle_window() {
+        let reduce = toml::from_str::<ReduceConfig>(
+            r#"
+flush_period_ms = 10
+
+[window]
+  type = "tumbling"
+  duration_ms = 100
+"#,
+        )
+        .unwrap()
+        .build(&TransformContext::default())
+        .await
+        .unwrap();
+        let reduce = reduce.into_task();
+
+        let event = |millis: i64| {
+            let mut e = LogEvent::from("test message");
+            e.insert(log_schema().timestamp_key(), Utc.timestamp_millis(millis));
+            e.insert("count", 1);
+            Event::from(e)
+        };
+
+        // The input stays open, so only the passing of time closes the last
+        // window.
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let mut out_stream = reduce.transform(Box::pin(rx));
+        tx.unbounded_send(event(0)).unwrap();
+        tx.unbounded_send(event(50)).unwrap();
+
+        let output = tokio::time::timeout(Duration::from_secs(5), out_stream.next())
+            .await
+            .expect("idle window was not flushed")
+            .unwrap()
+            .into_log();
+        assert_eq!(output["count"], Value::from(2));
+        assert_eq!(
+            output[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp_millis(0))
+        );
+
+        drop(tx);
+        assert!(out_stream.next().await.is_none());
+    }
 }
@@ -0,0 +1,202 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The most windows a single event can belong to, bounding the work done for
+/// each event by sliding windows.
+const MAX_WINDOWS_PER_EVENT: u64 = 1000;
+
+/// Assigns events to time windows based on their timestamp, rather than on
+/// the time they were received.
+///
+/// A window is closed once the watermark, the most recent event timestamp
+/// advanced by the time elapsed since that event was received, is past the
+/// end of the window by more than `allowed_lateness_ms`. Events that only
+/// belong to closed windows are discarded.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum WindowConfig {
+    /// Fixed-size, non-overlapping windows. Every event belongs to exactly
+    /// one window.
+    Tumbling {
+        duration_ms: u64,
+        #[serde(default)]
+        allowed_lateness_ms: u64,
+    },
+
+    /// Fixed-size windows that start every `slide_ms`. An event belongs to
+    /// every window that covers its timestamp.
+    Sliding {
+        duration_ms: u64,
+        slide_ms: u64,
+        #[serde(default)]
+        allowed_lateness_ms: u64,
+    },
+}
+
+impl WindowConfig {
+    pub fn validate(&self) -> crate::Result<()> {
+        match *self {
+            WindowConfig::Tumbling { duration_ms, .. } if duration_ms == 0 => {
+                Err("`window.duration_ms` must be greater than zero".into())
+            }
+            WindowConfig::Sliding {
+                duration_ms,
+                slide_ms,
+                ..
+            } if slide_ms == 0 || slide_ms > duration_ms => Err(
+                "`window.slide_ms` must be greater than zero and at most `window.duration_ms`"
+                    .into(),
+            ),
+            WindowConfig::Sliding {
+                duration_ms,
+                slide_ms,
+                ..
+            } if duration_ms / slide_ms > MAX_WINDOWS_PER_EVENT => Err(format!(
+                "`window.duration_ms` must be at most {} times `window.slide_ms`",
+                MAX_WINDOWS_PER_EVENT
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    const fn duration_ms(&self) -> i64 {
+        match *self {
+            WindowConfig::Tumbling { duration_ms, .. } => duration_ms as i64,
+            WindowConfig::Sliding { duration_ms, .. } => duration_ms as i64,
+        }
+    }
+
+    const fn slide_ms(&self) -> i64 {
+        match *self {
+            WindowConfig::Tumbling { duration_ms, .. } => duration_ms as i64,
+            WindowConfig::Sliding { slide_ms, .. } => slide_ms as i64,
+        }
+    }
+
+    const fn allowed_lateness_ms(&self) -> i64 {
+        match *self {
+            WindowConfig::Tumbling {
+                allowed_lateness_ms,
+                ..
+            } => allowed_lateness_ms as i64,
+            WindowConfig::Sliding {
+                allowed_lateness_ms,
+                ..
+            } => allowed_lateness_ms as i64,
+        }
+    }
+
+    /// Returns the start of every window containing the given timestamp, in
+    /// milliseconds since the Unix epoch.
+    pub fn windows(&self, timestamp: DateTime<Utc>) -> Vec<i64> {
+        let timestamp = timestamp.timestamp_millis();
+        let duration = self.duration_ms();
+        let slide = self.slide_ms();
+
+        let mut start = timestamp - timestamp.rem_euclid(slide);
+        let mut windows = Vec::new();
+        while start > timestamp - duration {
+            windows.push(start);
+            start -= slide;
+        }
+
+        windows.reverse();
+        windows
+    }
+
+    /// Returns `true` once the window starting at `start` can no longer
+    /// receive events, given the current watermark.
+    pub fn is_closed(&self, start: i64, watermark: DateTime<Utc>) -> bool {
+        start + self.duration_ms() + self.allowed_lateness_ms() <= watermark.timestamp_millis()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ts(millis: i64) -> DateTime<Utc> {
+        Utc.timestamp_millis(millis)
+    }
+
+    #[test]
+    fn tumbling_windows() {
+        let window = WindowConfig::Tumbling {
+            duration_ms: 1000,
+            allowed_lateness_ms: 0,
+        };
+
+        assert_eq!(window.windows(ts(0)), vec![0]);
+        assert_eq!(window.windows(ts(999)), vec![0]);
+        assert_eq!(window.windows(ts(1000)), vec![1000]);
+        assert_eq!(window.windows(ts(-1)), vec![-1000]);
+
+        assert!(!window.is_closed(1000, ts(1999)));
+        assert!(window.is_closed(1000, ts(2000)));
+    }
+
+    #[test]
+    fn sliding_windows() {
+        let window = WindowConfig::Sliding {
+            duration_ms: 1000,
+            slide_ms: 250,
+            allowed_lateness_ms: 0,
+        };
+
+        assert_eq!(window.windows(ts(1100)), vec![250, 500, 750, 1000]);
+        assert_eq!(window.windows(ts(1000)), vec![250, 500, 750, 1000]);
+        assert_eq!(window.windows(ts(999)), vec![0, 250, 500, 750]);
+    }
+
+    #[test]
+    fn allowed_lateness() {
+        let window = WindowConfig::Tumbling {
+            duration_ms: 1000,
+            allowed_lateness_ms: 500,
+        };
+
+        assert!(!window.is_closed(0, ts(1000)));
+        assert!(!window.is_closed(0, ts(1499)));
+        assert!(window.is_closed(0, ts(1500)));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(WindowConfig::Tumbling {
+            duration_ms: 0,
+            allowed_lateness_ms: 0
+        }
+        .validate()
+        .is_err());
+        assert!(WindowConfig::Sliding {
+            duration_ms: 1000,
+            slide_ms: 2000,
+            allowed_lateness_ms: 0
+        }
+        .validate()
+        .is_err());
+        assert!(WindowConfig::Sliding {
+            duration_ms: 1000,
+            slide_ms: 0,
+            allowed_lateness_ms: 0
+        }
+        .validate()
+        .is_err());
+        assert!(WindowConfig::Sliding {
+            duration_ms: 1000,
+            slide_ms: 1000,
+            allowed_lateness_ms: 0
+        }
+        .validate()
+        .is_ok());
+        assert!(WindowConfig::Sliding {
+            duration_ms: 1_000_000,
+            slide_ms: 1,
+            allowed_lateness_ms: 0
+        }
+        .validate()
+        .is_err());
+    }
+}
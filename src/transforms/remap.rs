@@ -3,7 +3,7 @@ use crate::{
         log_schema, ComponentKey, DataType, TransformConfig, TransformContext, TransformDescription,
     },
     event::{Event, VrlTarget},
-    internal_events::{RemapMappingAbort, RemapMappingError},
+    internal_events::{RemapFunctionCallProfiled, RemapMappingAbort, RemapMappingError},
     transforms::{FallibleFunctionTransform, Transform},
     Result,
 };
@@ -16,7 +16,7 @@ use std::io::{self, Read};
 use std::path::PathBuf;
 use vrl::diagnostic::Formatter;
 use vrl::prelude::ExpressionError;
-use vrl::{Program, Runtime, Terminate};
+use vrl::{Profiler, Program, Runtime, Terminate};
 
 #[derive(Deserialize, Serialize, Debug, Clone, Derivative)]
 #[serde(deny_unknown_fields, default)]
@@ -30,6 +30,7 @@ pub struct RemapConfig {
     #[serde(default = "crate::serde::default_true")]
     pub drop_on_abort: bool,
     pub reroute_dropped: bool,
+    pub profile: bool,
 }
 
 inventory::submit! {
@@ -80,6 +81,7 @@ pub struct Remap {
     drop_on_error: bool,
     drop_on_abort: bool,
     reroute_dropped: bool,
+    profiler: Option<Profiler>,
 }
 
 impl Remap {
@@ -117,6 +119,7 @@ impl Remap {
             drop_on_error: config.drop_on_error,
             drop_on_abort: config.drop_on_abort,
             reroute_dropped: config.reroute_dropped,
+            profiler: config.profile.then(Profiler::new),
         })
     }
 
@@ -168,6 +171,7 @@ impl Clone for Remap {
             drop_on_error: self.drop_on_error,
             drop_on_abort: self.drop_on_abort,
             reroute_dropped: self.reroute_dropped,
+            profiler: self.profiler.as_ref().map(|_| Profiler::new()),
         }
     }
 }
@@ -196,9 +200,30 @@ impl FallibleFunctionTransform for Remap {
 
         let mut target: VrlTarget = event.into();
 
-        let result = self
-            .runtime
-            .resolve(&mut target, &self.program, &self.timezone);
+        let result = match self.profiler.as_mut() {
+            Some(profiler) => {
+                let result = self.runtime.resolve_with_profiler(
+                    &mut target,
+                    &self.program,
+                    &self.timezone,
+                    profiler,
+                );
+
+                for (_, stats) in profiler.stats().filter(|(_, stats)| stats.function_call) {
+                    emit!(&RemapFunctionCallProfiled {
+                        function: stats.name,
+                        count: stats.count,
+                        duration: stats.total,
+                    });
+                }
+                profiler.clear();
+
+                result
+            }
+            None => self
+                .runtime
+                .resolve(&mut target, &self.program, &self.timezone),
+        };
         self.runtime.clear();
 
         match result {
@@ -343,6 +368,43 @@ mod tests {
         assert!(tform.runtime().is_empty());
     }
 
+    #[test]
+    fn check_remap_profile() {
+        let _ = crate::metrics::init_test();
+        let conf = RemapConfig {
+            source: Some(".foo = upcase!(.sentinel)".to_string()),
+            profile: true,
+            ..Default::default()
+        };
+        let mut tform = Remap::new(conf, &Default::default()).unwrap();
+
+        for sentinel in &["bar", "baz"] {
+            let mut event = LogEvent::from("event");
+            event.insert("sentinel", *sentinel);
+
+            let result = transform_one(&mut tform, event.into()).unwrap();
+            assert_eq!(
+                get_field_string(&result, "foo"),
+                sentinel.to_ascii_uppercase()
+            );
+            assert!(tform.profiler.as_ref().unwrap().is_empty());
+        }
+
+        let calls = crate::metrics::Controller::get()
+            .unwrap()
+            .capture_metrics()
+            .find(|metric| {
+                metric.name() == "vrl_function_calls_total"
+                    && metric
+                        .tags()
+                        .and_then(|tags| tags.get("function"))
+                        .map(String::as_str)
+                        == Some("upcase")
+            })
+            .map(|metric| metric.value().clone());
+        assert_eq!(calls, Some(MetricValue::Counter { value: 2.0 }));
+    }
+
     #[test]
     fn check_remap_adds() {
         let event = {
@@ -402,6 +402,13 @@ cli: {
 						The same result can be achieved by using `.` as the final expression.
 						"""
 				}
+				"profile": {
+					description: """
+						Profile the program while processing the object(s), and print the
+						execution count and cumulative time of each expression and function
+						call once all objects are processed.
+						"""
+				}
 			}
 
 			options: {
@@ -420,6 +427,22 @@ cli: {
 						"""
 					type: "string"
 				}
+
+				"profile-format": {
+					description: "The format of the profile written when `--profile` is set."
+					default:     "annotated"
+					enum: {
+						annotated: "An annotated listing of the program source"
+						folded:    "Folded stacks, as consumed by flamegraph tooling"
+					}
+				}
+
+				"profile-output": {
+					description: """
+						File to write the profile to when `--profile` is set. Defaults to stderr.
+						"""
+					type: "string"
+				}
 			}
 
 			args: {
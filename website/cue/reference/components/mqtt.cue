@@ -0,0 +1,138 @@
+package metadata
+
+components: _mqtt: {
+	features: {
+		collect: from: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT protocol"
+						url:   urls.mqtt
+					}
+					direction: "outgoing"
+					port:      1883
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+
+		send: to: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT protocol"
+						url:   urls.mqtt
+					}
+					direction: "outgoing"
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+
+		tls: {
+			enabled:                true
+			can_enable:             true
+			can_verify_certificate: false
+			can_verify_hostname:    false
+			enabled_default:        false
+		}
+	}
+
+	support: {
+		requirements: []
+		notices: []
+		warnings: []
+	}
+
+	configuration: {
+		host: {
+			description: "The hostname of the MQTT broker."
+			required:    true
+			type: string: {
+				examples: ["127.0.0.1", "mqtt.example.com"]
+			}
+		}
+		port: {
+			common:      true
+			description: "The port of the MQTT broker."
+			required:    false
+			type: uint: {
+				default: 1883
+				unit:    null
+			}
+		}
+		client_id: {
+			common:      false
+			description: "The client identifier presented to the broker. A random identifier prefixed with `vector-` is generated when unset."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["vector-edge-1"]
+			}
+		}
+		user: {
+			common:      false
+			description: "The username used to authenticate with the broker. Only used together with `password`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["vector"]
+			}
+		}
+		password: {
+			common:      false
+			description: "The password used to authenticate with the broker. Only used together with `user`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["${MQTT_PASSWORD}", "password"]
+			}
+		}
+		keep_alive_secs: {
+			common:      false
+			description: "The interval at which the client pings the broker to keep the connection alive."
+			required:    false
+			type: uint: {
+				default: 60
+				unit:    "seconds"
+			}
+		}
+		qos: {
+			common:      true
+			description: "The MQTT quality of service level."
+			required:    false
+			type: string: {
+				default: "at_least_once"
+				enum: {
+					at_most_once:  "QoS 0, messages are delivered at most once and may be lost."
+					at_least_once: "QoS 1, messages are delivered at least once and may be duplicated."
+					exactly_once:  "QoS 2, messages are delivered exactly once."
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		rumqttc: {
+			title: "rumqttc"
+			body:  """
+				The `mqtt` source/sink uses [`rumqttc`](\(urls.rumqttc)) under the hood
+				and speaks MQTT 3.1.1. The client reconnects automatically when the
+				connection to the broker is lost.
+				"""
+		}
+		tls: {
+			title: "TLS"
+			body:  """
+				When TLS is enabled, `tls.ca_file` must point to the certificate
+				authority of the broker, and the broker certificate is always
+				verified. A client certificate can be supplied with `tls.crt_file`
+				and `tls.key_file` for mutual TLS.
+				"""
+		}
+	}
+}
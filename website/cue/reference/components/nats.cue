@@ -32,6 +32,14 @@ components: _nats: {
 				}
 			}
 		}
+
+		tls: {
+			enabled:                true
+			can_enable:             true
+			can_verify_certificate: false
+			can_verify_hostname:    false
+			enabled_default:        false
+		}
 	}
 
 	support: {
@@ -56,6 +64,76 @@ components: _nats: {
 				syntax: "template"
 			}
 		}
+		auth: {
+			common:      false
+			description: "Options for authenticating with the NATS server."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					strategy: {
+						description: "The authentication strategy to use."
+						required:    true
+						type: string: {
+							enum: {
+								user_password:    "Authenticate with `user` and `password`."
+								token:            "Authenticate with a `token`."
+								credentials_file: "Authenticate with the JWT and NKey seed stored in the [credentials file](\(urls.nats_credentials)) at `path`."
+								nkey:             "Authenticate with the public `nkey` by signing the server challenge with its `seed`."
+							}
+						}
+					}
+					user: {
+						description:   "The user name, for the `user_password` strategy."
+						relevant_when: "strategy = \"user_password\""
+						required:      true
+						type: string: {
+							examples: ["vector"]
+						}
+					}
+					password: {
+						description:   "The password, for the `user_password` strategy."
+						relevant_when: "strategy = \"user_password\""
+						required:      true
+						type: string: {
+							examples: ["${NATS_PASSWORD}", "password"]
+						}
+					}
+					token: {
+						description:   "The token, for the `token` strategy."
+						relevant_when: "strategy = \"token\""
+						required:      true
+						type: string: {
+							examples: ["${NATS_TOKEN}"]
+						}
+					}
+					path: {
+						description:   "The path of the credentials file, for the `credentials_file` strategy."
+						relevant_when: "strategy = \"credentials_file\""
+						required:      true
+						type: string: {
+							examples: ["/etc/nats/vector.creds"]
+						}
+					}
+					nkey: {
+						description:   "The public NKey of the user, for the `nkey` strategy."
+						relevant_when: "strategy = \"nkey\""
+						required:      true
+						type: string: {
+							examples: ["UDXU4RCSJNZOIQHZNWXHXORDPRTGNJAHAHFRGZNEEJCPQTT2M7NLCNF4"]
+						}
+					}
+					seed: {
+						description:   "The seed of the NKey, used to sign the server challenge, for the `nkey` strategy."
+						relevant_when: "strategy = \"nkey\""
+						required:      true
+						type: string: {
+							examples: ["${NATS_NKEY_SEED}"]
+						}
+					}
+				}
+			}
+		}
 		connection_name: {
 			common:      false
 			description: "A name assigned to the NATS connection."
@@ -74,5 +152,13 @@ components: _nats: {
 				The `nats` source/sink uses [`nats.rs`](\(urls.nats_rs)) under the hood.
 				"""
 		}
+		tls: {
+			title: "TLS"
+			body:  """
+				When TLS is enabled, the server certificate is always verified, against
+				`tls.ca_file` when it is set. A client certificate can be supplied with
+				`tls.crt_file` and `tls.key_file`. Encrypted keys are not supported.
+				"""
+		}
 	}
 }
@@ -0,0 +1,108 @@
+package metadata
+
+components: _parquet: {
+	configuration: {
+		parquet: {
+			common:      false
+			description: "Options of the `parquet` encoding codec. Only allowed when `encoding.codec` is `parquet`."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					compression: {
+						common:      false
+						description: "The compression applied to the columns of the written files. The sink's `compression` doesn't apply to them."
+						required:    false
+						type: string: {
+							default: "snappy"
+							enum: {
+								none:   "No compression."
+								snappy: "[Snappy](\(urls.snappy)) compression."
+								gzip:   "[Gzip](\(urls.gzip)) compression."
+								zstd:   "[Zstandard](\(urls.zstd)) compression."
+							}
+						}
+					}
+					on_type_mismatch: {
+						common:      false
+						description: "What to do with events having a field whose value doesn't fit the type of its column."
+						required:    false
+						type: string: {
+							default: "null"
+							enum: {
+								"null": "Write the field as null and keep the rest of the event."
+								reject: "Drop the whole event."
+							}
+						}
+					}
+					row_group_size: {
+						common:      false
+						description: "The maximum number of events of a row group. Defaults to `batch.max_events`, so that each object holds a single row group."
+						required:    false
+						type: uint: {
+							default: null
+							unit:    "events"
+						}
+					}
+					schema: {
+						common:      true
+						description: "The columns of the written files, mapping top-level fields to their type. When omitted, the schema is inferred from the first batch and kept for the lifetime of the sink."
+						required:    false
+						type: object: {
+							examples: [
+								{
+									message:   "string"
+									timestamp: "timestamp"
+									status:    "int64"
+									duration:  "double"
+									tags:      "json"
+								},
+							]
+							options: {
+								"*": {
+									description: "The type of the column holding the field."
+									required:    true
+									type: string: {
+										enum: {
+											string:    "UTF-8 string. Numbers, booleans and timestamps are written as text."
+											int64:     "64-bit signed integer."
+											double:    "64-bit float. Integers are converted."
+											boolean:   "Boolean."
+											timestamp: "Timestamp with microsecond precision."
+											json:      "Any value, written as a JSON string."
+										}
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		parquet: {
+			title: "Parquet"
+			body: """
+				With `encoding.codec` set to `parquet`, every batch is written as a single
+				[Parquet](\(urls.parquet)) file. Columns are compressed individually according
+				to `parquet.compression`, so the sink's `compression` option is ignored and
+				objects are named with a `.parquet` extension.
+
+				Each column maps a top-level field of the events. Fields missing from the
+				schema are not written. If no schema is configured, it is inferred from the
+				fields of the first batch; fields that hold both integers and floats become
+				`double` columns, and fields holding other conflicting types become `string`
+				columns, or `json` ones if any of their values is an object or an array.
+
+				Values of later batches that don't fit the type of their column, as well as
+				those that don't fit a configured schema, are either written as null or cause the whole event to be dropped, depending on
+				`parquet.on_type_mismatch`. Both are reported through the
+				`processing_errors_total` metric, dropped events through
+				`events_discarded_total` as well, and dropped events are not acknowledged as
+				delivered.
+				"""
+		}
+	}
+}
@@ -196,6 +196,9 @@ components: sinks: [Name=string]: {
 											if codec == "ndjson" {
 												ndjson: "Newline delimited list of JSON encoded events."
 											}
+											if codec == "parquet" {
+												parquet: "[Parquet](\(urls.parquet)) file holding the events in columns, see the `parquet` options."
+											}
 										}
 									}
 								}
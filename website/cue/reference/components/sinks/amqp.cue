@@ -0,0 +1,87 @@
+package metadata
+
+components: sinks: amqp: {
+	title: "AMQP"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      false
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: components._amqp.features.tls
+			to:  components._amqp.features.send.to
+		}
+	}
+
+	support: components._amqp.support
+
+	configuration: components._amqp.configuration & {
+		exchange: {
+			description: "The exchange to publish messages to. Use an empty string for the default exchange, which routes to the queue named by the routing key."
+			required:    true
+			type: string: {
+				examples: ["logs", ""]
+			}
+		}
+		routing_key: {
+			common:      true
+			description: "The routing key to publish messages with."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["application.{{ service }}", "logs"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: components._amqp.how_it_works & {
+		publisher_confirms: {
+			title: "Publisher confirms"
+			body:  """
+				The sink enables publisher confirms on its channel and only
+				acknowledges events once the broker has confirmed the message. Messages
+				the broker refuses are counted in `send_errors_total`.
+				"""
+		}
+		reconnection: {
+			title: "Reconnection"
+			body:  """
+				The sink connects to the broker once it receives its first event. When the
+				connection or channel is lost, it reconnects with an exponential backoff of up
+				to one minute and publishes the event again. Events still awaiting a confirm
+				on the lost channel are marked as failed, in the order they were published.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		send_errors_total:       components.sources.internal_metrics.output.metrics.send_errors_total
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+	}
+}
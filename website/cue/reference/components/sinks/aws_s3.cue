@@ -33,7 +33,7 @@ components: sinks: aws_s3: components._aws & {
 				codec: {
 					enabled: true
 					batched: true
-					enum: ["ndjson", "text"]
+					enum: ["ndjson", "parquet", "text"]
 				}
 			}
 			proxy: enabled: true
@@ -66,7 +66,7 @@ components: sinks: aws_s3: components._aws & {
 		warnings: []
 	}
 
-	configuration: {
+	configuration: components._parquet.configuration & {
 		acl: {
 			category:    "ACL"
 			common:      false
@@ -246,7 +246,7 @@ components: sinks: aws_s3: components._aws & {
 		metrics: null
 	}
 
-	how_it_works: {
+	how_it_works: components._parquet.how_it_works & {
 		cross_account: {
 			title: "Cross account object writing"
 			body:  """
@@ -33,7 +33,7 @@ components: sinks: azure_blob: {
 				codec: {
 					enabled: true
 					batched: true
-					enum: ["ndjson", "text"]
+					enum: ["ndjson", "parquet", "text"]
 				}
 			}
 			request: {
@@ -66,7 +66,7 @@ components: sinks: azure_blob: {
 		notices: []
 	}
 
-	configuration: {
+	configuration: components._parquet.configuration & {
 		connection_string: {
 			description: "The Azure Blob Storage Account connection string. Only authentication with access key supported."
 			required:    true
@@ -116,7 +116,7 @@ components: sinks: azure_blob: {
 		metrics: null
 	}
 
-	how_it_works: {
+	how_it_works: components._parquet.how_it_works & {
 		object_naming: {
 			title: "Object naming"
 			body:  """
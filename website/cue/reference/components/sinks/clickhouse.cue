@@ -93,11 +93,24 @@ components: sinks: clickhouse: {
 				examples: ["http://localhost:8123"]
 			}
 		}
+		format: {
+			common:      false
+			description: "The format rows are inserted in."
+			required:    false
+			type: string: {
+				default: "json_each_row"
+				enum: {
+					json_each_row: "Rows are sent as [`JSONEachRow`](\(urls.clickhouse_json_each_row)), with the fields of the table's columns converted to the type of their column."
+					row_binary:    "Rows are sent as [`RowBinary`](\(urls.clickhouse_row_binary)), which is cheaper for Clickhouse to parse. Fields without a column are always discarded."
+				}
+			}
+		}
 		table: {
 			description: "The table that data will be inserted into."
 			required:    true
 			type: string: {
-				examples: ["mytable"]
+				examples: ["mytable", "logs_{{ application }}"]
+				syntax: "template"
 			}
 		}
 		skip_unknown_fields: {
@@ -113,10 +126,39 @@ components: sinks: clickhouse: {
 		metrics: null
 	}
 
+	how_it_works: {
+		columns: {
+			title: "Column mapping"
+			body: """
+				The columns of each table are read from `system.columns` the first time the table is
+				written to, and read again after an insert failed, in case the table has been altered.
+				If `table` isn't a template, this happens during the healthcheck. Materialized and alias columns are never written.
+
+				Top-level event fields are converted to the type of the column of the same name:
+
+				* `DateTime` and `DateTime64` columns accept timestamps, Unix timestamps in seconds, and
+				  RFC 3339 strings. Strings without an offset, such as `2021-02-03 04:05:06.789`, are read
+				  in the time zone of the column, or UTC if it has none.
+				* `Array` and `Map` columns accept arrays and objects, whose items are converted to the
+				  type of the column's items.
+				* `Nullable` columns accept `null`, and `LowCardinality` columns the values of their
+				  inner type.
+				* `String` columns accept any value; objects and arrays are written as JSON.
+
+				When a field can't be converted, the error is reported through the
+				`processing_errors_total` metric and the column gets its default value, `NULL` for
+				`Nullable` columns. With the `row_binary` format, columns without a matching field get
+				the zero value of their type rather than their default expression, and tables with
+				columns of other types, like `Decimal` or `Enum8`, are rejected.
+				"""
+		}
+	}
+
 	telemetry: metrics: {
 		component_sent_bytes_total:       components.sources.internal_metrics.output.metrics.component_sent_bytes_total
 		component_sent_events_total:      components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total: components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
 		events_out_total:                 components.sources.internal_metrics.output.metrics.events_out_total
+		processing_errors_total:          components.sources.internal_metrics.output.metrics.processing_errors_total
 	}
 }
@@ -42,6 +42,18 @@ components: sinks: file: {
 	}
 
 	configuration: {
+		atomic: {
+			common:      false
+			description: "Write events to a hidden temporary file next to `path` and only rename it to `path` once the file is closed, so that readers never see a partially written file. See [Atomic Finalization](#atomic-finalization)."
+			required:    false
+			type: bool: default: false
+		}
+		fsync: {
+			common:      false
+			description: "Flush the written files to disk after every batch of events. Events are only acknowledged once their file is synced, and are marked as failed when flushing or syncing it fails."
+			required:    false
+			type: bool: default: false
+		}
 		idle_timeout_secs: {
 			common:      false
 			description: "The amount of time a file can be idle  and stay open. After not receiving any events for this timeout, the file will be flushed and closed.\n"
@@ -59,6 +71,78 @@ components: sinks: file: {
 				syntax: "template"
 			}
 		}
+		retention: {
+			common:      false
+			description: "Limits on the files kept by [rotation](#rotation). The limits are enforced whenever a file is opened, closed or rotated, so `max_age_secs` also applies when no file is rotated. They never apply to the file currently written to."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					max_age_secs: {
+						common:      true
+						description: "Remove rotated files that were last modified longer ago than this."
+						required:    false
+						type: uint: {
+							default: null
+							examples: [604800]
+							unit: "seconds"
+						}
+					}
+					max_files: {
+						common:      true
+						description: "The number of rotated files to keep for each path. The most recently modified files are kept."
+						required:    false
+						type: uint: {
+							default: null
+							examples: [10]
+							unit: null
+						}
+					}
+				}
+			}
+		}
+		rotation: {
+			common:      false
+			description: "Move the file aside and start a new one once it grows too large or too old. See [Rotation](#rotation)."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					max_age_secs: {
+						common:      true
+						description: "Rotate the file once it has been open for this long. The age is checked when events are written to the file."
+						required:    false
+						type: uint: {
+							default: null
+							examples: [3600]
+							unit: "seconds"
+						}
+					}
+					max_size_bytes: {
+						common:      true
+						description: "Rotate the file before writing an event would make it larger than this. With `gzip` compression the size is counted before compression."
+						required:    false
+						type: uint: {
+							default: null
+							examples: [104857600]
+							unit: "bytes"
+						}
+					}
+					suffix: {
+						common:      false
+						description: "The suffix appended to the names of rotated files."
+						required:    false
+						type: string: {
+							default: "numbered"
+							enum: {
+								numbered:  "An increasing number, such as `vector.log.1`, `vector.log.2`. The highest number is the most recent file."
+								timestamp: "The UTC time of the rotation, such as `vector.log.20211019T120000.000Z`."
+							}
+						}
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -76,7 +160,33 @@ components: sinks: file: {
 				to create and write to files in the specified directories.
 				"""
 		}
-	}
+		rotation: {
+			title: "Rotation"
+			body: """
+				With the `rotation` option, Vector closes the file for a path once
+				it reaches `max_size_bytes` or `max_age_secs`, renames it by
+				appending a dot and the configured `suffix` to its name, and
+				continues writing to a new file at the original path. Rotated
+				files are never written to again, so they can be safely shipped
+				or archived. The `retention` option then removes the oldest
+				rotated files.
+
+				Files closed because they were idle for `idle_timeout_secs` are
+				not rotated: Vector appends to them when new events arrive.
+				"""
+		}
+		atomic_finalization: {
+			title: "Atomic Finalization"
+			body: """
+				With `atomic` enabled, events for `/var/log/app.log` are written
+				to `/var/log/.app.log.tmp`, which is renamed to `/var/log/app.log`
+				once the file is rotated, goes idle, or Vector shuts down. Since
+				a file cannot be appended to atomically, a file already at the
+				path is rotated out of the way first, using the `rotation.suffix`
+				naming. A temporary file left behind by a crash is appended to
+				and finalized the next time its path is written.
+				"""
+		}
 
 	telemetry: metrics: {
 		component_sent_bytes_total:       components.sources.internal_metrics.output.metrics.component_sent_bytes_total
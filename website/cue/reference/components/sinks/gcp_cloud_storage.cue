@@ -33,7 +33,7 @@ components: sinks: gcp_cloud_storage: {
 				codec: {
 					enabled: true
 					batched: true
-					enum: ["ndjson", "text"]
+					enum: ["ndjson", "parquet", "text"]
 				}
 			}
 			proxy: enabled: true
@@ -73,7 +73,7 @@ components: sinks: gcp_cloud_storage: {
 		notices: []
 	}
 
-	configuration: {
+	configuration: components._parquet.configuration & {
 		acl: {
 			category:    "ACL"
 			common:      false
@@ -175,7 +175,7 @@ components: sinks: gcp_cloud_storage: {
 		metrics: null
 	}
 
-	how_it_works: {
+	how_it_works: components._parquet.how_it_works & {
 		object_access_control_list: {
 			title: "Object access control list (ACL)"
 			body:  """
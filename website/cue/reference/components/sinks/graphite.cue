@@ -0,0 +1,159 @@
+package metadata
+
+components: sinks: graphite: {
+	title: "Graphite"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: enabled:    false
+			send_buffer_bytes: enabled: true
+			keepalive: {
+				enabled:       true
+				relevant_when: "mode = `tcp`"
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.graphite
+
+				interface: {
+					socket: {
+						api: {
+							title: "Graphite plaintext protocol"
+							url:   urls.graphite_plaintext
+						}
+						direction: "outgoing"
+						protocols: ["tcp", "udp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	input: {
+		logs: false
+		metrics: {
+			counter:      true
+			distribution: false
+			gauge:        true
+			histogram:    false
+			set:          false
+			summary:      false
+		}
+	}
+
+	configuration: {
+		address: {
+			description: "The address of the Graphite server. The address _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["92.12.333.224:2003", "graphite.example.com:2003"]
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			type: string: {
+				enum: {
+					tcp: "TCP socket, optionally with TLS."
+					udp: "UDP socket."
+				}
+			}
+		}
+		default_namespace: {
+			common: true
+			description: """
+				Used as a namespace for metrics that don't have it.
+				A namespace will be prefixed to a metric's name, separated by a `.`.
+				"""
+			required: false
+			type: string: {
+				default: null
+				examples: ["service"]
+			}
+		}
+		path: {
+			common:      false
+			description: "A template rendering the path of the metrics, replacing their namespace and name. Metrics missing a field used by the template are dropped."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["servers.{{ tags.host }}.{{ name }}"]
+				syntax: "template"
+			}
+		}
+		protocol: {
+			common:      false
+			description: "The protocol to send the metrics with. The pickle protocol can only be sent over TCP."
+			required:    false
+			type: string: {
+				default: "plaintext"
+				enum: {
+					plaintext: "The [plaintext protocol](\(urls.graphite_plaintext)), one metric per line."
+					pickle:    "The [pickle protocol](\(urls.graphite_pickle)), each metric in its own length prefixed pickle."
+				}
+			}
+		}
+		tags: {
+			common:      false
+			description: "Whether to append the tags of the metrics to their path, as [tagged series](\(urls.graphite_tags))."
+			required:    false
+			type: bool: default: true
+		}
+	}
+
+	how_it_works: {
+		line_format: {
+			title: "Line format"
+			body:  """
+				Each metric is sent as a line of the [plaintext protocol](\(urls.graphite_plaintext)),
+				`<path> <value> <timestamp>`, its timestamp defaulting to the current time. Counters and gauges are
+				sent with their absolute value, incremental ones being accumulated into a running total, other
+				metrics are dropped. Whitespace in the path and in the
+				tags, as well as the characters Graphite doesn't allow in them, are replaced by `_`. Tags with an
+				empty value are left out.
+				"""
+		}
+		pickle: {
+			title: "Pickle protocol"
+			body:  """
+				With `protocol = "pickle"`, each metric is instead sent as a frame of the
+				[pickle protocol](\(urls.graphite_pickle)): a 4-byte big-endian length followed by the list
+				`[(path, (timestamp, value))]` pickled with protocol 2, which Carbon reads on its pickle receiver
+				port, `2004` by default.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -25,7 +25,7 @@ components: sinks: nats: {
 				}
 			}
 			request: enabled: false
-			tls: enabled:     false
+			tls: components._nats.features.tls
 			to: {
 				service: services.nats
 
@@ -33,7 +33,7 @@ components: sinks: nats: {
 					socket: {
 						direction: "outgoing"
 						protocols: ["tcp"]
-						ssl: "disabled"
+						ssl: "optional"
 					}
 				}
 			}
@@ -46,7 +46,45 @@ components: sinks: nats: {
 		notices: []
 	}
 
-	configuration: components._nats.configuration & {}
+	configuration: components._nats.configuration & {
+		jetstream: {
+			common:      false
+			description: "Publish to [JetStream](\(urls.nats_jetstream)) and wait for the stream to acknowledge every message. Events are only acknowledged once their message has been stored, and publishes without an acknowledgement are retried."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					expected_stream: {
+						common:      false
+						description: "The stream the messages are expected to be stored in. Publishes stored in another stream are rejected."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["LOGS"]
+						}
+					}
+					timeout_secs: {
+						common:      false
+						description: "How long to wait for the publish acknowledgement."
+						required:    false
+						type: uint: {
+							default: 5
+							unit:    "seconds"
+						}
+					}
+					retry_attempts: {
+						common:      false
+						description: "The number of times a publish is retried before the event is dropped."
+						required:    false
+						type: uint: {
+							default: 5
+							unit:    null
+						}
+					}
+				}
+			}
+		}
+	}
 
 	input: {
 		logs:    true
@@ -56,10 +94,11 @@ components: sinks: nats: {
 	how_it_works: components._nats.how_it_works
 
 	telemetry: metrics: {
-		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
-		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
-		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
-		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
-		send_errors_total:       components.sources.internal_metrics.output.metrics.send_errors_total
+		events_discarded_total:     components.sources.internal_metrics.output.metrics.events_discarded_total
+		nats_publish_retries_total: components.sources.internal_metrics.output.metrics.nats_publish_retries_total
+		processing_errors_total:    components.sources.internal_metrics.output.metrics.processing_errors_total
+		processed_bytes_total:      components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:     components.sources.internal_metrics.output.metrics.processed_events_total
+		send_errors_total:          components.sources.internal_metrics.output.metrics.send_errors_total
 	}
 }
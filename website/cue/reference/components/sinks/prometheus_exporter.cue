@@ -59,6 +59,10 @@ components: sinks: prometheus_exporter: {
 	}
 
 	configuration: {
+		auth: configuration._http_auth & {_args: {
+			password_example: "${PROMETHEUS_EXPORTER_PASSWORD}"
+			username_example: "${PROMETHEUS_EXPORTER_USERNAME}"
+		}}
 		address: {
 			description: "The address to expose for scraping."
 			required:    true
@@ -114,6 +118,16 @@ components: sinks: prometheus_exporter: {
 				items: type: float: examples: [0.5, 0.75, 0.9, 0.95, 0.99]
 			}
 		}
+		series_ttl_secs: {
+			common:      false
+			description: "How long a series is exposed after its last update. Series that haven't been updated for this long are no longer exposed, and are removed on the next flush. Defaults to `flush_period_secs`."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [300]
+				unit:    "seconds"
+			}
+		}
 	}
 
 	input: {
@@ -348,6 +362,34 @@ components: sinks: prometheus_exporter: {
 			]
 		}
 
+		openmetrics: {
+			title: "OpenMetrics"
+			body: """
+				Metrics are exposed in the Prometheus text format by default. Scrapers sending an
+				`Accept: application/openmetrics-text` header get the [OpenMetrics](\(urls.openmetrics))
+				text format instead. In this format counter samples carry the `_total` suffix,
+				timestamps are in seconds, and the `_min`, `_max` and `_avg` samples of summaries
+				are left out.
+				"""
+		}
+
+		authentication: {
+			title: "Authentication"
+			body: """
+				When `auth` is configured, scrapers must send the matching `Authorization`
+				header. Other requests are rejected with a `401 Unauthorized` response.
+				"""
+		}
+
+		sketches: {
+			title: "Sketches"
+			body: """
+				Sketch metrics are exposed as summaries of the quantiles configured with the
+				`quantiles` option, along with their `_sum` and `_count`. The quantiles of empty
+				sketches are reported as `NaN`.
+				"""
+		}
+
 		memory_usage: {
 			title: "Memory Usage"
 			body: """
@@ -0,0 +1,235 @@
+package metadata
+
+components: sinks: syslog: {
+	title: "Syslog"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					enum: ["json", "text"]
+				}
+			}
+			send_buffer_bytes: enabled: true
+			keepalive: {
+				enabled:       true
+				relevant_when: "mode = `tcp`"
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.syslog
+
+				interface: {
+					socket: {
+						api: {
+							title: "Syslog"
+							url:   urls.syslog_5424
+						}
+						direction: "outgoing"
+						protocols: ["tcp", "udp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		address: {
+			description: "The address of the syslog server. The address _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["92.12.333.224:514", "rsyslog.example.com:6514"]
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			type: string: {
+				enum: {
+					tcp: "TCP socket, optionally with TLS."
+					udp: "UDP socket, with one message per datagram."
+				}
+			}
+		}
+		format: {
+			common:      true
+			description: "The syslog message format."
+			required:    false
+			type: string: {
+				default: "rfc5424"
+				enum: {
+					rfc5424: "The [RFC 5424](\(urls.syslog_5424)) format."
+					rfc3164: "The legacy BSD [RFC 3164](\(urls.syslog_3164)) format, which has no message ID or structured data."
+				}
+			}
+		}
+		framing: {
+			common:        false
+			description:   "How messages are delimited on TCP connections, as described in [RFC 6587](\(urls.syslog_6587))."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			type: string: {
+				default: "octet_counting"
+				enum: {
+					octet_counting:    "Each message is prefixed by its length in bytes and a space."
+					newline_delimited: "Each message is followed by a newline."
+				}
+			}
+		}
+		facility: {
+			common:      true
+			description: "The facility of the messages, either as a name or as a number between `0` and `23`. Invalid values are replaced by the default."
+			required:    false
+			type: string: {
+				default: "user"
+				examples: ["local0", "{{ facility }}", "16"]
+				syntax: "template"
+			}
+		}
+		severity: {
+			common:      true
+			description: "The severity of the messages, either as a name or as a number between `0` and `7`. Invalid values are replaced by the default."
+			required:    false
+			type: string: {
+				default: "info"
+				examples: ["warning", "{{ level }}", "3"]
+				syntax: "template"
+			}
+		}
+		hostname: {
+			common:      false
+			description: "The hostname of the messages. Defaults to the value of the `host` field, then to the hostname of the machine running Vector."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ kubernetes.pod_node_name }}"]
+				syntax: "template"
+			}
+		}
+		app_name: {
+			common:      true
+			description: "The application name of the messages, used as the tag in the RFC 3164 format."
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["{{ service }}", "nginx"]
+				syntax: "template"
+			}
+		}
+		proc_id: {
+			common:      false
+			description: "The process ID of the messages. Defaults to the nil value (`-`), which is omitted from the RFC 3164 tag."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ pid }}"]
+				syntax: "template"
+			}
+		}
+		msg_id: {
+			common:        false
+			description:   "The message ID of the messages."
+			relevant_when: "format = `rfc5424`"
+			required:      false
+			type: string: {
+				default: null
+				examples: ["{{ event_type }}", "audit"]
+				syntax: "template"
+			}
+		}
+		structured_data: {
+			common: false
+			description: """
+				The structured data elements of the messages, as a table of parameters for each element ID. The
+				parameter values are templates, so they can be taken from event fields.
+				"""
+			relevant_when: "format = `rfc5424`"
+			required:      false
+			type: object: {
+				examples: [
+					{
+						"request": {
+							"id":     "{{ request_id }}"
+							"method": "{{ method }}"
+						}
+						"origin@32473": {
+							"software": "vector"
+						}
+					},
+				]
+				options: {
+					"*": {
+						common:      false
+						description: "A structured data element, with its parameters."
+						required:    false
+						type: object: options: {
+							"*": {
+								common:      false
+								description: "A structured data parameter value."
+								required:    false
+								type: string: {
+									default: null
+									examples: ["{{ request_id }}"]
+									syntax: "template"
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		message_format: {
+			title: "Message format"
+			body: """
+				The header of each message is built from the `facility`, `severity`, `hostname`, `app_name`,
+				`proc_id`, `msg_id` and `structured_data` options, and the timestamp of the event. The message
+				itself is the event encoded with the configured `encoding`. The header fields are rendered before
+				the `encoding.only_fields` and `encoding.except_fields` options are applied, so they can refer to
+				fields that are removed from the message.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -0,0 +1,133 @@
+package metadata
+
+components: sinks: websocket: {
+	title: "WebSocket"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.websocket_server
+
+				interface: {
+					socket: {
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		uri: {
+			description: "The WebSocket URI to connect to. The `wss` scheme enables TLS."
+			required:    true
+			type: string: {
+				examples: ["ws://127.0.0.1:9000/endpoint", "wss://example.com/socket"]
+			}
+		}
+		auth: configuration._http_auth & {_args: {
+			password_example: "${WEBSOCKET_PASSWORD}"
+			username_example: "${WEBSOCKET_USERNAME}"
+		}}
+		headers: {
+			common:      false
+			description: "Additional headers sent with the handshake request."
+			required:    false
+			type: object: {
+				examples: [{"X-Source": "vector"}]
+				options: {
+					"*": {
+						common:      false
+						description: "A header to send with the handshake request."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["vector"]
+						}
+					}
+				}
+			}
+		}
+		ping_interval_secs: {
+			common:      false
+			description: "The interval at which ping frames are sent to keep the connection alive. No pings are sent by default."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [30]
+				unit: "seconds"
+			}
+		}
+		ping_timeout_secs: {
+			common:        false
+			description:   "How long to wait for the pong answering a ping before the connection is considered broken and is reestablished."
+			relevant_when: "ping_interval_secs is set"
+			required:      false
+			type: uint: {
+				default: null
+				examples: [10]
+				unit: "seconds"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		reconnects: {
+			title: "Reconnects"
+			body: """
+				Each event is sent as a text message over a single, persistent connection. When the connection is
+				lost, or when a pong isn't received within `ping_timeout_secs`, the connection is reestablished
+				with an exponential backoff. An event that fails to be sent is dropped, and reported as errored
+				when acknowledgements are enabled.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total:      components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_established_total: components.sources.internal_metrics.output.metrics.connection_established_total
+		connection_failed_total:      components.sources.internal_metrics.output.metrics.connection_failed_total
+		connection_shutdown_total:    components.sources.internal_metrics.output.metrics.connection_shutdown_total
+		processed_bytes_total:        components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
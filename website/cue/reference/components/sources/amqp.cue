@@ -0,0 +1,146 @@
+package metadata
+
+components: sources: amqp: {
+	title: "AMQP"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			tls:  components._amqp.features.tls
+			from: components._amqp.features.collect.from
+		}
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._amqp.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._amqp.configuration & {
+		acknowledgements: configuration._acknowledgements
+		queue: {
+			description: "The queue to consume from."
+			required:    true
+			type: string: {
+				examples: ["vector", "application-logs"]
+			}
+		}
+		consumer: {
+			common:      false
+			description: "The consumer tag to register with the broker."
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["vector-1"]
+			}
+		}
+		prefetch_count: {
+			common:      true
+			description: "The maximum number of unacknowledged deliveries the broker sends to Vector. See [consumer prefetch](\(urls.rabbitmq_prefetch))."
+			required:    false
+			type: uint: {
+				default: 100
+				unit:    null
+			}
+		}
+		routing_key_field: {
+			common:      false
+			description: "The log field name to use for the routing key of the delivery."
+			required:    false
+			type: string: {
+				default: "routing"
+				examples: ["routing"]
+			}
+		}
+		exchange_key: {
+			common:      false
+			description: "The log field name to use for the exchange the delivery was published to."
+			required:    false
+			type: string: {
+				default: "exchange"
+				examples: ["exchange"]
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual AMQP delivery"
+		fields: {
+			message: {
+				description: "The raw body of the delivery."
+				required:    true
+				type: string: {
+					examples: ["53.126.150.246 - - [01/Oct/2020:11:25:58 -0400] \"GET /disintermediate HTTP/2.0\" 401 20308"]
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The timestamp property of the delivery, or the time it was received if unset."
+			}
+			exchange: {
+				description: "The exchange the delivery was published to, empty for the default exchange."
+				required:    true
+				type: string: {
+					examples: ["logs"]
+				}
+			}
+			routing: {
+				description: "The routing key of the delivery."
+				required:    true
+				type: string: {
+					examples: ["application.web"]
+				}
+			}
+		}
+	}
+
+	telemetry: metrics: {
+		amqp_acknowledgement_errors_total: components.sources.internal_metrics.output.metrics.amqp_acknowledgement_errors_total
+		amqp_deliveries_rejected_total:    components.sources.internal_metrics.output.metrics.amqp_deliveries_rejected_total
+		component_received_events_total:   components.sources.internal_metrics.output.metrics.component_received_events_total
+		connection_errors_total:           components.sources.internal_metrics.output.metrics.connection_errors_total
+		events_failed_total:               components.sources.internal_metrics.output.metrics.events_failed_total
+		events_in_total:                   components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:             components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: components._amqp.how_it_works & {
+		acknowledgements: {
+			title: "Acknowledgements"
+			body:  """
+				Deliveries are acknowledged with `basic.ack` once their events have
+				been read. With `acknowledgements` enabled, they are only acknowledged
+				after all of their events have been delivered by the connected sinks.
+				Deliveries that failed with a transient error are requeued with
+				`basic.nack`, while those that failed permanently are rejected without
+				requeueing, so that they can be [dead-lettered](\(urls.rabbitmq_dead_letter)).
+
+				The `prefetch_count` bounds the number of deliveries waiting to be
+				acknowledged.
+				"""
+		}
+		reconnects: {
+			title: "Reconnects"
+			body:  """
+				When the connection to the broker is lost, the source reconnects and
+				resumes consuming from the queue, backing off exponentially up to a
+				minute between failed attempts. Deliveries that were not acknowledged
+				before the connection was lost are redelivered by the broker.
+				"""
+		}
+	}
+}
@@ -0,0 +1,204 @@
+package metadata
+
+components: sources: gcp_pubsub: {
+	title: "GCP PubSub"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			proxy: enabled:      true
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			from: {
+				service: services.gcp_pubsub
+				interface: {
+					socket: {
+						api: {
+							title: "GCP PubSub streaming pull"
+							url:   urls.gcp_pubsub_streaming_pull
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: ["GCP"]
+		stateful: false
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		acknowledgements: configuration._acknowledgements
+		api_key: {
+			common:      false
+			description: "A [Google Cloud API key](\(urls.gcp_authentication_api_key)) used to authenticate access to the pubsub project and subscription. Either this or `credentials_path` must be set."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["${GCP_API_KEY}", "ef8d5de700e7989468166c40fc8a0ccd"]
+			}
+		}
+		credentials_path: {
+			common:      true
+			description: "The filename for a Google Cloud service account credentials JSON file used to authenticate access to the pubsub project and subscription. If this is unset, Vector checks the `GOOGLE_APPLICATION_CREDENTIALS` environment variable for a filename.\n\nIf no filename is named, Vector will attempt to fetch an instance service account for the compute instance the program is running on. If Vector is not running on a GCE instance, you must define a credentials file as above."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["/path/to/credentials.json"]
+			}
+		}
+		endpoint: {
+			common:      false
+			description: "The endpoint to consume from. Set it to the address of the [emulator](\(urls.gcp_pubsub_emulator)), along with `skip_authentication`, for local testing."
+			required:    false
+			type: string: {
+				default: "https://pubsub.googleapis.com"
+				examples: ["https://us-central1-pubsub.googleapis.com", "http://localhost:8681"]
+			}
+		}
+		skip_authentication: {
+			common:      false
+			description: "Skip loading credentials, for use with the emulator."
+			required:    false
+			type: bool: default: false
+		}
+		project: {
+			description: "The project name of the subscription."
+			required:    true
+			type: string: {
+				examples: ["vector-123456"]
+			}
+		}
+		subscription: {
+			description: "The subscription within the project to consume from."
+			required:    true
+			type: string: {
+				examples: ["vector-subscription"]
+			}
+		}
+		ack_deadline_seconds: {
+			common:      false
+			description: "The time Pub/Sub waits for a message to be acknowledged before redelivering it. Vector extends the deadline of messages for as long as their events are in flight. Must be between 10 and 600."
+			required:    false
+			type: uint: {
+				default: 600
+				unit:    "seconds"
+			}
+		}
+		max_outstanding_messages: {
+			common:      false
+			description: "The maximum number of messages that are delivered to Vector without being acknowledged."
+			required:    false
+			type: uint: {
+				default: 1000
+				unit:    null
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual Pub/Sub message"
+		fields: {
+			message: {
+				description: "The data of the message."
+				required:    true
+				type: string: {
+					examples: ["53.126.150.246 - - [01/Oct/2020:11:25:58 -0400] \"GET /disintermediate HTTP/2.0\" 401 20308"]
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The time the message was published."
+			}
+			message_id: {
+				description: "The ID assigned to the message by Pub/Sub."
+				required:    true
+				type: string: {
+					examples: ["2345678901"]
+				}
+			}
+			attributes: {
+				description: "The attributes of the message."
+				required:    true
+				type: object: {
+					examples: [{"app": "web"}]
+					options: {}
+				}
+			}
+		}
+	}
+
+	permissions: iam: [
+		{
+			platform: "gcp"
+			_service: "pubsub"
+
+			policies: [
+				{
+					_action: "subscriptions.consume"
+					required_for: ["operation"]
+				},
+			]
+		},
+	]
+
+	telemetry: metrics: {
+		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		connection_errors_total:         components.sources.internal_metrics.output.metrics.connection_errors_total
+		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: {
+		streaming_pull: {
+			title: "Streaming pull"
+			body:  """
+				Messages are consumed with [streaming pull](\(urls.gcp_pubsub_streaming_pull)),
+				which keeps a gRPC stream open to Pub/Sub. The stream is reopened when
+				Pub/Sub closes it or after an error.
+				"""
+		}
+		acknowledgements: {
+			title: "Acknowledgements and deadlines"
+			body:  """
+				Messages are acknowledged once their events have been read. With
+				`acknowledgements` enabled, they are only acknowledged after all of their
+				events have been delivered by the connected sinks, and the deadline of
+				each message is extended every `ack_deadline_seconds / 2` while it is in
+				flight. Messages that could not be delivered are made available for
+				redelivery right away.
+
+				Messages that are in flight when Vector shuts down are redelivered once
+				their deadline expires.
+				"""
+		}
+	}
+}
@@ -0,0 +1,143 @@
+package metadata
+
+components: sources: graphite: {
+	_port: 2003
+
+	title: "Graphite"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.graphite
+				interface: socket: {
+					api: {
+						title: "Graphite plaintext protocol"
+						url:   urls.graphite_plaintext
+					}
+					direction: "incoming"
+					port:      _port
+					protocols: ["tcp", "udp"]
+					ssl: "optional"
+				}
+			}
+			receive_buffer_bytes: enabled: true
+			keepalive: {
+				enabled:       true
+				relevant_when: "mode = `tcp`"
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description: "The address to listen for connections on, or `systemd#N` to use the Nth socket passed by systemd socket activation. If an address is used it _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["0.0.0.0:\(_port)", "systemd", "systemd#3"]
+			}
+		}
+		mode: {
+			description: "The type of socket to use."
+			required:    true
+			type: string: {
+				enum: {
+					tcp: "TCP Socket."
+					udp: "UDP Socket."
+				}
+			}
+		}
+		protocol: {
+			common:        false
+			description:   "The protocol the metrics are sent with."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			type: string: {
+				default: "plaintext"
+				enum: {
+					plaintext: "The [plaintext protocol](\(urls.graphite_plaintext)), one metric per line."
+					pickle:    "The [pickle protocol](\(urls.graphite_pickle)), lists of metrics pickled with any protocol version and prefixed by their length."
+				}
+			}
+		}
+		shutdown_timeout_secs: {
+			common:        false
+			description:   "The timeout before a connection is forcefully closed during shutdown."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			type: uint: {
+				default: 30
+				unit:    "seconds"
+			}
+		}
+	}
+
+	output: metrics: {
+		gauge: output._passthrough_gauge
+	}
+
+	how_it_works: {
+		line_format: {
+			title: "Line format"
+			body:  """
+				Each line of the [plaintext protocol](\(urls.graphite_plaintext)), `<path> <value> <timestamp>`,
+				is parsed into an absolute gauge named after the path. Paths of
+				[tagged series](\(urls.graphite_tags)), such as `disk.used;host=web01;mount=/var`, are split into
+				the metric name and its tags. Invalid lines are discarded.
+				"""
+		}
+		pickle: {
+			title: "Pickle protocol"
+			body:  """
+				With `protocol = "pickle"`, the source reads the [pickle protocol](\(urls.graphite_pickle)) sent by
+				Carbon relays and aggregators: frames holding a 4-byte big-endian length followed by a pickled list
+				of `(path, (timestamp, value))` tuples. Only the pickle instructions needed for lists, tuples,
+				strings and numbers are supported, so pickles constructing other objects are rejected instead of
+				being executed. A frame that can't be parsed is discarded as a whole. The pickle protocol is only
+				available over TCP.
+				"""
+		}
+		timestamps: {
+			title: "Timestamps"
+			body:  """
+				The timestamp is in seconds since the Unix epoch. When it is missing, or set to `-1`, the metric
+				is assigned a `null` timestamp, which is substituted with the current time by downstream sinks.
+				See the [metric data model](\(urls.vector_metric)) page for more info.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		connection_errors_total:         components.sources.internal_metrics.output.metrics.connection_errors_total
+		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
+		invalid_record_total:            components.sources.internal_metrics.output.metrics.invalid_record_total
+		invalid_record_bytes_total:      components.sources.internal_metrics.output.metrics.invalid_record_bytes_total
+		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
@@ -67,7 +67,7 @@ components: sources: host_metrics: {
 
 	configuration: {
 		collectors: {
-			description: "The list of host metric collector services to use. Defaults to all collectors."
+			description: "The list of host metric collector services to use. Defaults to all collectors except `process`."
 			common:      true
 			required:    false
 			type: array: {
@@ -82,6 +82,7 @@ components: sources: host_metrics: {
 						host:       "Metrics related to host"
 						memory:     "Metrics related to memory utilization."
 						network:    "Metrics related to network utilization."
+						process:    "Metrics related to individual processes (Linux only)."
 					}
 				}
 			}
@@ -174,6 +175,158 @@ components: sources: host_metrics: {
 				}
 			}
 		}
+		process: {
+			common: false
+			description: #"""
+				Options for the "process" metrics collector.
+
+				Note: this collector is only available on Linux systems. The file descriptor and I/O
+				metrics of processes owned by other users are only reported when Vector runs with
+				sufficient privileges.
+				"""#
+			required: false
+			type: object: options: {
+				names: {
+					common:      false
+					required:    false
+					description: "Lists of process name patterns to include or exclude."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of process name patterns for which to gather metrics.
+
+								Defaults to including all processes.
+
+								The patterns are matched as regular expressions against the process name, as found
+								in `/proc/<pid>/stat`.
+								"""
+							type: array: {
+								default: null
+								items: type: string: {
+									examples: ["^vector$", "^nginx"]
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of process name patterns for which not to gather metrics.
+
+								Defaults to excluding no processes.
+
+								The patterns are matched as regular expressions against the process name, as found
+								in `/proc/<pid>/stat`.
+								"""
+							type: array: {
+								default: null
+								items: type: string: {
+									examples: ["^kworker/"]
+								}
+							}
+						}
+					}
+				}
+				cmdlines: {
+					common:      false
+					required:    false
+					description: "Lists of process command line patterns to include or exclude."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of command line patterns for which to gather metrics.
+
+								Defaults to including all processes.
+
+								The patterns are matched as regular expressions against the command line, with its
+								arguments separated by spaces.
+								"""
+							type: array: {
+								default: null
+								items: type: string: {
+									examples: ["--config /etc/vector/"]
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of command line patterns for which not to gather metrics.
+
+								Defaults to excluding no processes.
+
+								The patterns are matched as regular expressions against the command line, with its
+								arguments separated by spaces.
+								"""
+							type: array: {
+								default: null
+								items: type: string: {
+									examples: ["^/usr/lib/systemd/"]
+								}
+							}
+						}
+					}
+				}
+				users: {
+					common:      false
+					required:    false
+					description: "Lists of user name patterns to include or exclude."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of user name patterns for which to gather process metrics.
+
+								Defaults to including all users.
+
+								The patterns are matched using globbing against the name of the user owning the
+								process, or its numeric ID if the user is unknown.
+								"""
+							type: array: {
+								default: ["*"]
+								items: type: string: {
+									examples: ["vector", "www-*"]
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of user name patterns for which not to gather process metrics.
+
+								Defaults to excluding no users.
+
+								The patterns are matched using globbing against the name of the user owning the
+								process, or its numeric ID if the user is unknown.
+								"""
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["root"]
+								}
+							}
+						}
+					}
+				}
+				max_processes: {
+					common:      false
+					required:    false
+					description: "The maximum number of processes to report metrics for. When more processes match the filters, the ones that used the most CPU time are reported."
+					type: uint: {
+						unit:    null
+						default: 100
+						examples: [10, 500]
+					}
+				}
+			}
+		}
 		disk: {
 			common:      false
 			description: #"Options for the "disk" metrics collector."#
@@ -493,6 +646,22 @@ components: sources: host_metrics: {
 		network_transmit_packets_drop_total: _host & _network_nomac & {description: "The number of packets dropped during transmits on this interface."}
 		network_transmit_packets_total:      _host & _network_nomac & {description: "The number of packets transmitted on this interface."}
 
+		// Host processes
+		process_cpu_seconds_total: _host & _process_counter & {
+			description: "The amount of CPU time used by this process, in seconds."
+			tags: mode: {
+				description: "Which mode the process was running in during the given time."
+				required:    true
+				examples: ["user", "system"]
+			}
+		}
+		process_io_read_bytes_total:    _host & _process_counter & {description: "The number of bytes this process caused to be read from storage."}
+		process_io_written_bytes_total: _host & _process_counter & {description: "The number of bytes this process caused to be written to storage."}
+		process_memory_rss_bytes:       _host & _process_gauge & {description:   "The resident set size of this process, in bytes."}
+		process_memory_virtual_bytes:   _host & _process_gauge & {description:   "The virtual memory size of this process, in bytes."}
+		process_open_fds:               _host & _process_gauge & {description:   "The number of open file descriptors of this process."}
+		process_threads:                _host & _process_gauge & {description:   "The number of threads of this process."}
+
 		// Helpers
 		_host: {
 			default_namespace: "host"
@@ -582,6 +751,34 @@ components: sources: host_metrics: {
 			}
 		}
 		_network_nomac: _network_gauge & {relevant_when: "OS is not macOS"}
+		_process_tags: _host_metrics_tags & {
+			collector: examples: ["process"]
+			pid: {
+				description: "The process ID."
+				required:    true
+				examples: ["1234"]
+			}
+			name: {
+				description: "The process name."
+				required:    true
+				examples: ["vector", "nginx"]
+			}
+			user: {
+				description: "The name of the user owning the process."
+				required:    true
+				examples: ["root", "vector"]
+			}
+		}
+		_process_counter: {
+			type:          "counter"
+			tags:          _process_tags
+			relevant_when: "OS is Linux"
+		}
+		_process_gauge: {
+			type:          "gauge"
+			tags:          _process_tags
+			relevant_when: "OS is Linux"
+		}
 	}
 
 	telemetry: metrics: {
@@ -0,0 +1,182 @@
+package metadata
+
+components: sources: influxdb: {
+	_port: 8086
+
+	title: "InfluxDB"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "batch"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.influxdb
+
+				interface: socket: {
+					api: {
+						title: "InfluxDB line protocol"
+						url:   urls.influxdb_line_protocol
+					}
+					direction: "incoming"
+					port:      _port
+					protocols: ["http", "udp"]
+					ssl: "optional"
+				}
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		acknowledgements: configuration._acknowledgements
+		address: {
+			description: "The address to accept HTTP connections on. The address _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["0.0.0.0:\(_port)"]
+			}
+		}
+		output: {
+			common:      true
+			description: "The type of events the points are converted to."
+			required:    false
+			type: string: {
+				default: "metrics"
+				enum: {
+					metrics: "A gauge for each numeric and boolean field of the points."
+					logs:    "A log event for each point."
+				}
+			}
+		}
+		token: {
+			common:      true
+			description: "The token the clients must authenticate with. Requests are not authenticated when it is not set."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["${INFLUXDB_TOKEN}"]
+			}
+		}
+		udp_address: {
+			common:      false
+			description: "The address to receive line protocol datagrams on. The UDP listener is disabled when it is not set."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["0.0.0.0:8089"]
+			}
+		}
+		udp_precision: {
+			common:        false
+			description:   "The precision of the timestamps of the points received over UDP."
+			relevant_when: "udp_address is set"
+			required:      false
+			type: string: {
+				default: "ns"
+				enum: {
+					ns: "Nanoseconds."
+					us: "Microseconds."
+					ms: "Milliseconds."
+					s:  "Seconds."
+					m:  "Minutes."
+					h:  "Hours."
+				}
+			}
+		}
+	}
+
+	output: {
+		logs: point: {
+			description: "A point, when `output` is set to `logs`."
+			fields: {
+				measurement: {
+					description: "The measurement of the point."
+					required:    true
+					type: string: {
+						examples: ["cpu"]
+					}
+				}
+				tags: {
+					description: "The tags of the point."
+					required:    true
+					type: object: {
+						examples: [{"host": "web01"}]
+						options: {}
+					}
+				}
+				fields: {
+					description: "The fields of the point, with their types."
+					required:    true
+					type: object: {
+						examples: [{"usage_idle": 98.5, "cores": 8}]
+						options: {}
+					}
+				}
+				timestamp: {
+					description: "The timestamp of the point, or the time it was received at when it has none."
+					required:    true
+					type: timestamp: {}
+				}
+			}
+		}
+		metrics: {
+			gauge: output._passthrough_gauge
+		}
+	}
+
+	how_it_works: {
+		endpoints: {
+			title: "Endpoints"
+			body:  """
+				Points are written with `POST` requests to the [v1](\(urls.influxdb_http_api_v1)) `/write` and
+				[v2](\(urls.influxdb_http_api_v2)) `/api/v2/write` endpoints, so that Telegraf and the InfluxDB
+				client libraries can send to Vector. The `precision` query parameter sets the precision of the
+				timestamps, and gzip compressed bodies are supported. The other query parameters, such as `db`,
+				`org` and `bucket`, are ignored. A request containing an invalid point is rejected as a whole.
+
+				When `token` is set, it must be given as an [authentication token](\(urls.influxdb_authentication_token))
+				in the `Authorization: Token <token>` header, or as the password of the basic authentication or of
+				the `p` query parameter of the v1 API.
+				"""
+		}
+		metric_conversion: {
+			title: "Metric conversion"
+			body:  """
+				Each numeric and boolean field of a point is converted to an absolute gauge, named after the field
+				and namespaced by the measurement, with the tags of the point. Booleans are converted to `1` and
+				`0`, and string fields are left out.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
+		http_bad_requests_total:         components.sources.internal_metrics.output.metrics.http_bad_requests_total
+		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total:         components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -127,6 +127,24 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		amqp_acknowledgement_errors_total: {
+			description:       "The total number of failures to acknowledge, requeue or reject an AMQP delivery."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		amqp_deliveries_rejected_total: {
+			description:       "The total number of AMQP deliveries returned to the broker because their events were not delivered."
+			type:              "counter"
+			default_namespace: "vector"
+			tags: _component_tags & {
+				requeue: {
+					description: "Whether the delivery was requeued (`true`) or rejected (`false`)."
+					required:    true
+					examples: ["true", "false"]
+				}
+			}
+		}
 		api_started_total: {
 			description:       "The number of times the Vector GraphQL API has been started."
 			type:              "counter"
@@ -356,6 +374,18 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		k8s_event_node_annotation_failures_total: {
+			description:       "The total number of failures to annotate Vector events with Kubernetes Node metadata."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		k8s_event_owner_annotation_failures_total: {
+			description:       "The total number of failures to annotate Vector events with the controller of the Kubernetes Pod."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		k8s_reflector_desyncs_total: {
 			description:       "The total number of desyncs for the reflector."
 			type:              "counter"
@@ -776,6 +806,42 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		join_events_matched_total: {
+			description:       "The total number of event pairs joined by the `join` transform."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		join_events_unmatched_total: {
+			description:       "The total number of events the `join` transform could not join with a counterpart."
+			type:              "counter"
+			default_namespace: "vector"
+			tags: _component_tags & {
+				reason: {
+					description: "The reason the event was not joined."
+					required:    true
+					enum: {
+						"expired":       "No counterpart arrived before `ttl_secs` elapsed."
+						"no_key":        "The join key could not be rendered."
+						"no_side":       "The event matched neither side's condition."
+						"pending_limit": "The `max_pending_keys` limit was reached."
+						"replaced":      "A newer event from the same side arrived with the same key."
+					}
+				}
+			}
+		}
+		join_pending_bytes: {
+			description:       "The size in bytes of the events currently waiting for a counterpart in the `join` transform."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		join_pending_keys: {
+			description:       "The number of keys currently waiting for a counterpart in the `join` transform."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		logging_driver_errors_total: {
 			description: """
 				The total number of logging driver errors encountered caused by not using either
@@ -803,6 +869,30 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		metric_math_series_expired_total: {
+			description:       "The total number of series whose state was discarded by the `metric_math` transform after they stopped receiving metrics."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		nats_acknowledgement_errors_total: {
+			description:       "The total number of failures to acknowledge or reject a NATS JetStream message."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		nats_publish_retries_total: {
+			description:       "The total number of JetStream publishes retried after no publish acknowledgement was received."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		netflow_missing_templates_total: {
+			description:       "The total number of NetFlow v9 or IPFIX data sets dropped because their template wasn't received yet."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		open_connections: {
 			description:       "The number of current open connections to Vector."
 			type:              "gauge"
@@ -870,6 +960,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		redis_acknowledgement_errors_total: {
+			description:       "The total number of failures to acknowledge a Redis stream entry with `XACK`."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		request_errors_total: {
 			description:       "The total number of requests errors for this component."
 			type:              "counter"
@@ -912,6 +1008,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		snmp_trap_authentication_failures_total: {
+			description:       "The total number of SNMP messages dropped because of an unknown community or failed USM authentication."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		streams_total: {
 			description:       "The total number of streams."
 			type:              "counter"
@@ -1037,6 +1139,25 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		vrl_function_call_duration_seconds: {
+			description: """
+				The cumulative time spent in calls to a VRL function while mapping a
+				single event. Only reported when profiling is enabled.
+				"""
+			type:              "histogram"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				function: _function
+			}
+		}
+		vrl_function_calls_total: {
+			description:       "The total number of calls to a VRL function. Only reported when profiling is enabled."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				function: _function
+			}
+		}
 
 		// Windows metrics
 		windows_service_does_not_exist_total: {
@@ -1160,6 +1281,11 @@ components: sources: internal_metrics: {
 			description: "The file that produced the error"
 			required:    false
 		}
+		_function: {
+			description: "The identifier of the VRL function."
+			required:    true
+			examples: ["parse_json", "to_int"]
+		}
 		_host: {
 			description: "The hostname of the originating system."
 			required:    true
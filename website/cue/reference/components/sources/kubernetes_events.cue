@@ -0,0 +1,312 @@
+package metadata
+
+components: sources: kubernetes_events: {
+	title: "Kubernetes Events"
+
+	description: """
+		Collects the events of a Kubernetes cluster, such as Pods being
+		scheduled, images being pulled or containers being killed, via the
+		Kubernetes API.
+		"""
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			from: {
+				service: services.kubernetes
+
+				interface: {
+					socket: {
+						api: {
+							title: "Kubernetes API"
+							url:   urls.kubernetes_api
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "optional"
+					}
+				}
+			}
+			proxy: enabled: true
+		}
+		multiline: enabled: false
+	}
+
+	support: {
+		requirements: [
+			"""
+				[Kubernetes](\(urls.kubernetes)) version `\(services.kubernetes.versions)` is required, or `>= 1.19` for
+				the `events.k8s.io/v1` API.
+				""",
+		]
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		api_version: {
+			common:      false
+			description: "The API the events are watched with."
+			required:    false
+			type: string: {
+				default: "v1"
+				enum: {
+					"v1":               "The core API, available in all Kubernetes versions."
+					"events.k8s.io/v1": "The events API, available since Kubernetes 1.19."
+				}
+			}
+		}
+		field_selector: {
+			common:      false
+			description: "Specifies the field selector to filter the events with."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["type=Warning", "involvedObject.kind=Pod"]
+			}
+		}
+		kube_config_file: {
+			common:      false
+			description: "Optional path to a kubeconfig file readable by Vector. If not set, Vector will try to connect to Kubernetes using in-cluster configuration."
+			required:    false
+			type: string: default: null
+		}
+		label_selector: {
+			common:      false
+			description: "Specifies the label selector to filter the events with."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["my_custom_label=my_value"]
+			}
+		}
+		namespaces: {
+			common:      true
+			description: "The namespaces to collect the events of. The events of all namespaces are collected if empty."
+			required:    false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["default", "kube-system"]
+				}
+			}
+		}
+	}
+
+	output: logs: event: {
+		description: "A Kubernetes event."
+		fields: {
+			message: {
+				description: "The description of what happened."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["Back-off restarting failed container"]
+				}
+			}
+			source_type: {
+				description: "The name of the source type."
+				required:    true
+				type: string: {
+					examples: ["kubernetes_events"]
+				}
+			}
+			timestamp: {
+				description: "The time the event was last observed at."
+				required:    true
+				type: timestamp: {}
+			}
+			"kubernetes.event.action": {
+				description: "What action was taken or failed, for events reported through the newer fields."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["Binding"]
+				}
+			}
+			"kubernetes.event.count": {
+				description: "How many times the event has occurred."
+				required:    false
+				common:      true
+				type: uint: {
+					default: null
+					examples: [5]
+					unit: null
+				}
+			}
+			"kubernetes.event.first_timestamp": {
+				description: "The time the event was first observed at."
+				required:    false
+				common:      false
+				type: timestamp: {}
+			}
+			"kubernetes.event.name": {
+				description: "The name of the event object."
+				required:    true
+				type: string: {
+					examples: ["vector-0.16b5e3c5d3b7a3f1"]
+				}
+			}
+			"kubernetes.event.namespace": {
+				description: "The namespace of the event object."
+				required:    true
+				type: string: {
+					examples: ["default"]
+				}
+			}
+			"kubernetes.event.reason": {
+				description: "The machine readable reason of the event."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["BackOff"]
+				}
+			}
+			"kubernetes.event.reporting_component": {
+				description: "The component that reported the event."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["kubelet"]
+				}
+			}
+			"kubernetes.event.reporting_instance": {
+				description: "The instance of the component that reported the event."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["node-1"]
+				}
+			}
+			"kubernetes.event.type": {
+				description: "The type of the event."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["Normal", "Warning"]
+				}
+			}
+			"kubernetes.event.uid": {
+				description: "The UID of the event object."
+				required:    true
+				type: string: {
+					examples: ["c1c8b2a4-2e2c-4a4e-9b38-6e6f1a1f4a7d"]
+				}
+			}
+			"kubernetes.involved_object.api_version": {
+				description: "The API version of the object the event is about."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["v1"]
+				}
+			}
+			"kubernetes.involved_object.field_path": {
+				description: "The part of the object the event is about, such as a container of a Pod."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["spec.containers{vector}"]
+				}
+			}
+			"kubernetes.involved_object.kind": {
+				description: "The kind of the object the event is about."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["Pod"]
+				}
+			}
+			"kubernetes.involved_object.name": {
+				description: "The name of the object the event is about."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["vector-0"]
+				}
+			}
+			"kubernetes.involved_object.namespace": {
+				description: "The namespace of the object the event is about."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["default"]
+				}
+			}
+			"kubernetes.involved_object.uid": {
+				description: "The UID of the object the event is about."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["9b8c2ba3-5d0f-4b3b-a3a6-0f9a0f2b6f3e"]
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		deduplication: {
+			title: "Deduplication"
+			body:  """
+				Kubernetes updates an event object whenever the event occurs again,
+				and every update is collected as a new log event with the increased
+				`kubernetes.event.count`. Objects are only collected again once their
+				resource version changes, so that the events aren't repeated when the
+				watch is restarted after the Kubernetes API went out of sync. Events
+				that last occurred before the source started, such as those still kept
+				by Kubernetes from the last hour, are skipped.
+				"""
+		}
+		kubernetes_api_access_control: {
+			title: "Kubernetes API access control"
+			body:  """
+				Vector requires access to the Kubernetes API to watch the events.
+				With [RBAC](\(urls.kubernetes_rbac)), make sure the Vector
+				`ServiceAccount` is granted the `watch` verb on the `events`
+				resource of the chosen API group, with a `ClusterRole` to collect
+				the events of all namespaces or with a `Role` in each of the
+				`namespaces`.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total:       components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                       components.sources.internal_metrics.output.metrics.events_in_total
+		k8s_reflector_desyncs_total:           components.sources.internal_metrics.output.metrics.k8s_reflector_desyncs_total
+		k8s_state_ops_total:                   components.sources.internal_metrics.output.metrics.k8s_state_ops_total
+		k8s_stream_chunks_processed_total:     components.sources.internal_metrics.output.metrics.k8s_stream_chunks_processed_total
+		k8s_stream_processed_bytes_total:      components.sources.internal_metrics.output.metrics.k8s_stream_processed_bytes_total
+		k8s_watch_requests_invoked_total:      components.sources.internal_metrics.output.metrics.k8s_watch_requests_invoked_total
+		k8s_watch_requests_failed_total:       components.sources.internal_metrics.output.metrics.k8s_watch_requests_failed_total
+		k8s_watch_stream_failed_total:         components.sources.internal_metrics.output.metrics.k8s_watch_stream_failed_total
+		k8s_watch_stream_items_obtained_total: components.sources.internal_metrics.output.metrics.k8s_watch_stream_items_obtained_total
+		k8s_watcher_http_error_total:          components.sources.internal_metrics.output.metrics.k8s_watcher_http_error_total
+		processed_bytes_total:                 components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
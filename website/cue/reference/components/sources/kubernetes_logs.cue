@@ -65,6 +65,14 @@ components: sources: kubernetes_logs: {
 							default: "kubernetes.container_image"
 						}
 					}
+					container_image_id: {
+						common:      false
+						description: "Event field for the ID of the Container image, including its digest."
+						required:    false
+						type: string: {
+							default: "kubernetes.container_image_id"
+						}
+					}
 					container_name: {
 						common:      false
 						description: "Event field for Container name."
@@ -166,6 +174,57 @@ components: sources: kubernetes_logs: {
 				}
 			}
 		}
+		annotate_node_labels: {
+			common:      false
+			description: "Annotate the events with the labels of the Node that Vector runs at. This requires watching the Node."
+			required:    false
+			type: bool: default: false
+		}
+		node_annotation_fields: {
+			common:      false
+			description: "Configuration for how the events are annotated with Node metadata, if `annotate_node_labels` is enabled."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					node_labels: {
+						common:      false
+						description: "Event field for Node labels."
+						required:    false
+						type: string: {
+							default: "kubernetes.node_labels"
+						}
+					}
+				}
+			}
+		}
+		owner_annotation_fields: {
+			common:      false
+			description: "Configuration for how the events are annotated with the controller of the Pod, if `resolve_pod_controllers` is enabled."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					pod_controller: {
+						common:      false
+						description: "Event field for the controller of the Pod, such as `Deployment/vector`."
+						required:    false
+						type: string: {
+							default: "kubernetes.pod_controller"
+						}
+					}
+				}
+			}
+		}
+		resolve_pod_controllers: {
+			common: false
+			description: """
+				Annotate the events with the controller of the Pod, resolving the `Deployment` of Pods managed by
+				a `ReplicaSet`. This requires watching all the `ReplicaSet`s of the cluster.
+				"""
+			required: false
+			type: bool: default: false
+		}
 		auto_partial_merge: {
 			common:      false
 			description: "Automatically merge partial messages into a single event. Partial here is in respect to messages that were split by the Kubernetes Container Runtime log driver."
@@ -298,6 +357,15 @@ components: sources: kubernetes_logs: {
 					examples: ["busybox:1.30"]
 				}
 			}
+			"kubernetes.container_image_id": {
+				description: "The ID of the container image, including its digest."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["docker-pullable://busybox@sha256:b5cfd4befc119a590ca1a81d6bb0fa1fb19f1fbebd0397f25fae164abe1e8a6a"]
+				}
+			}
 			"kubernetes.container_name": {
 				description: "Container name."
 				required:    false
@@ -316,6 +384,24 @@ components: sources: kubernetes_logs: {
 					options: {}
 				}
 			}
+			"kubernetes.node_labels": {
+				description: "Set of labels attached to the Node, if `annotate_node_labels` is enabled."
+				required:    false
+				common:      true
+				type: object: {
+					examples: [{"mylabel": "myvalue"}]
+					options: {}
+				}
+			}
+			"kubernetes.pod_controller": {
+				description: "The controller of the Pod, if `resolve_pod_controllers` is enabled."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["Deployment/coredns", "StatefulSet/vector"]
+				}
+			}
 			"kubernetes.pod_ip": {
 				description: "Pod IPv4 address."
 				required:    false
@@ -623,7 +709,11 @@ components: sources: kubernetes_logs: {
 				Vector requires access to the Kubernetes API.
 				Specifically, the [`kubernetes_logs` source](\(urls.vector_kubernetes_logs_source))
 				uses the `/api/v1/pods` endpoint to "watch" the pods from
-				all namespaces.
+				all namespaces, as well as the `/api/v1/namespaces` endpoint to
+				annotate the logs. With `annotate_node_labels` enabled, it also
+				watches the `/api/v1/nodes` endpoint, and with
+				`resolve_pod_controllers` enabled, the `/apis/apps/v1/replicasets`
+				endpoint. The shipped configuration grants access to all of them.
 
 				Modern Kubernetes clusters run with RBAC (role-based access control)
 				scheme. RBAC-enabled clusters require some configuration to grant Vector
@@ -647,21 +737,23 @@ components: sources: kubernetes_logs: {
 	}
 
 	telemetry: metrics: {
-		events_in_total:                        components.sources.internal_metrics.output.metrics.events_in_total
-		k8s_format_picker_edge_cases_total:     components.sources.internal_metrics.output.metrics.k8s_format_picker_edge_cases_total
-		k8s_docker_format_parse_failures_total: components.sources.internal_metrics.output.metrics.k8s_docker_format_parse_failures_total
-		k8s_event_annotation_failures_total:    components.sources.internal_metrics.output.metrics.k8s_event_annotation_failures_total
-		k8s_reflector_desyncs_total:            components.sources.internal_metrics.output.metrics.k8s_reflector_desyncs_total
-		k8s_state_ops_total:                    components.sources.internal_metrics.output.metrics.k8s_state_ops_total
-		k8s_stream_chunks_processed_total:      components.sources.internal_metrics.output.metrics.k8s_stream_chunks_processed_total
-		k8s_stream_processed_bytes_total:       components.sources.internal_metrics.output.metrics.k8s_stream_processed_bytes_total
-		k8s_watch_requests_invoked_total:       components.sources.internal_metrics.output.metrics.k8s_watch_requests_invoked_total
-		k8s_watch_requests_failed_total:        components.sources.internal_metrics.output.metrics.k8s_watch_requests_failed_total
-		k8s_watch_stream_failed_total:          components.sources.internal_metrics.output.metrics.k8s_watch_stream_failed_total
-		k8s_watch_stream_items_obtained_total:  components.sources.internal_metrics.output.metrics.k8s_watch_stream_items_obtained_total
-		k8s_watcher_http_error_total:           components.sources.internal_metrics.output.metrics.k8s_watcher_http_error_total
-		processed_bytes_total:                  components.sources.internal_metrics.output.metrics.processed_bytes_total
-		processed_events_total:                 components.sources.internal_metrics.output.metrics.processed_events_total
-		component_received_events_total:        components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                           components.sources.internal_metrics.output.metrics.events_in_total
+		k8s_format_picker_edge_cases_total:        components.sources.internal_metrics.output.metrics.k8s_format_picker_edge_cases_total
+		k8s_docker_format_parse_failures_total:    components.sources.internal_metrics.output.metrics.k8s_docker_format_parse_failures_total
+		k8s_event_annotation_failures_total:       components.sources.internal_metrics.output.metrics.k8s_event_annotation_failures_total
+		k8s_event_node_annotation_failures_total:  components.sources.internal_metrics.output.metrics.k8s_event_node_annotation_failures_total
+		k8s_event_owner_annotation_failures_total: components.sources.internal_metrics.output.metrics.k8s_event_owner_annotation_failures_total
+		k8s_reflector_desyncs_total:               components.sources.internal_metrics.output.metrics.k8s_reflector_desyncs_total
+		k8s_state_ops_total:                       components.sources.internal_metrics.output.metrics.k8s_state_ops_total
+		k8s_stream_chunks_processed_total:         components.sources.internal_metrics.output.metrics.k8s_stream_chunks_processed_total
+		k8s_stream_processed_bytes_total:          components.sources.internal_metrics.output.metrics.k8s_stream_processed_bytes_total
+		k8s_watch_requests_invoked_total:          components.sources.internal_metrics.output.metrics.k8s_watch_requests_invoked_total
+		k8s_watch_requests_failed_total:           components.sources.internal_metrics.output.metrics.k8s_watch_requests_failed_total
+		k8s_watch_stream_failed_total:             components.sources.internal_metrics.output.metrics.k8s_watch_stream_failed_total
+		k8s_watch_stream_items_obtained_total:     components.sources.internal_metrics.output.metrics.k8s_watch_stream_items_obtained_total
+		k8s_watcher_http_error_total:              components.sources.internal_metrics.output.metrics.k8s_watcher_http_error_total
+		processed_bytes_total:                     components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:                    components.sources.internal_metrics.output.metrics.processed_events_total
+		component_received_events_total:           components.sources.internal_metrics.output.metrics.component_received_events_total
 	}
 }
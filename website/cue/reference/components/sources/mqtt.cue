@@ -0,0 +1,107 @@
+package metadata
+
+components: sources: mqtt: {
+	title: "MQTT"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			tls:  components._mqtt.features.tls
+			from: components._mqtt.features.collect.from
+		}
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._mqtt.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._mqtt.configuration & {
+		topics: {
+			description: "The topic filters to subscribe to. The `+` and `#` wildcards match a single level and any number of levels respectively."
+			required:    true
+			type: array: items: type: string: {
+				examples: ["sensors/+/temperature", "devices/#"]
+			}
+		}
+		clean_session: {
+			common:      false
+			description: "Whether to start a clean session on every connect. When disabled, the broker keeps the subscriptions and queues messages published while Vector is disconnected, which requires a fixed `client_id`."
+			required:    false
+			type: bool: default: true
+		}
+		topic_key: {
+			common:      false
+			description: "The log field name to use for the topic the message was published to."
+			required:    false
+			type: string: {
+				default: "topic"
+				examples: ["topic", "mqtt_topic"]
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual MQTT message"
+		fields: {
+			message: {
+				description: "The raw payload of the MQTT message."
+				required:    true
+				type: string: {
+					examples: ["{\"temperature\": 21.5}"]
+				}
+			}
+			timestamp: fields._current_timestamp
+			topic: {
+				description: "The topic the message was published to."
+				required:    true
+				type: string: {
+					examples: ["sensors/living-room/temperature"]
+				}
+			}
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		connection_errors_total:         components.sources.internal_metrics.output.metrics.connection_errors_total
+		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		topics: {
+			title: "Topic filters"
+			body:  """
+				Each entry in `topics` is an [MQTT topic filter](\(urls.mqtt_topics)),
+				subscribed to with the configured `qos`. The topic of every received
+				message is stored in the `topic_key` field so that messages from
+				wildcard subscriptions can be told apart.
+				"""
+		}
+		sessions: {
+			title: "Persistent sessions"
+			body:  """
+				With `clean_session = false` the broker remembers the subscriptions of
+				the `client_id` and holds QoS 1 and 2 messages published while Vector
+				is disconnected. Vector only subscribes when the broker reports that no
+				session was resumed.
+				"""
+		}
+	}
+}
@@ -9,6 +9,7 @@ components: sources: nats: {
 			from: components._nats.features.collect.from
 		}
 		multiline: enabled: false
+		tls: components._nats.features.tls
 		codecs: {
 			enabled:         true
 			default_framing: "bytes"
@@ -31,9 +32,99 @@ components: sources: nats: {
 	}
 
 	configuration: components._nats.configuration & {
+		acknowledgements: configuration._acknowledgements
+		jetstream: {
+			common:      false
+			description: "Consume from a [JetStream](\(urls.nats_jetstream)) stream through a durable pull consumer instead of subscribing to `subject` directly. The consumer only receives the messages of the stream matching `subject`."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					stream: {
+						description: "The name of the stream to consume from."
+						required:    true
+						type: string: {
+							examples: ["LOGS"]
+						}
+					}
+					durable_name: {
+						common:      true
+						description: "The name of the durable consumer. It is created on the stream when it doesn't exist yet, and shared by all Vector instances using the same name."
+						required:    false
+						type: string: {
+							default: "vector"
+						}
+					}
+					deliver_policy: {
+						common:      false
+						description: "The first message delivered to a newly created consumer. An existing consumer resumes after its last acknowledged message."
+						required:    false
+						type: string: {
+							default: "all"
+							enum: {
+								all:               "Deliver all messages of the stream."
+								last:              "Deliver the last message of the stream."
+								last_per_subject:  "Deliver the last message of every subject of the stream."
+								new:               "Deliver only the messages published after the consumer is created."
+								by_start_sequence: "Deliver from the stream sequence `start_sequence`."
+								by_start_time:     "Deliver from the first message published at or after `start_time`."
+							}
+						}
+					}
+					start_sequence: {
+						common:        false
+						description:   "The stream sequence to start delivering from."
+						relevant_when: "deliver_policy = \"by_start_sequence\""
+						required:      false
+						type: uint: {
+							default: null
+							examples: [42]
+							unit: null
+						}
+					}
+					start_time: {
+						common:        false
+						description:   "The time to start delivering from."
+						relevant_when: "deliver_policy = \"by_start_time\""
+						required:      false
+						type: timestamp: {
+							default: null
+							examples: ["2021-11-01T00:00:00Z"]
+						}
+					}
+					ack_wait_secs: {
+						common:      false
+						description: "How long the server waits for a message to be acknowledged before redelivering it."
+						required:    false
+						type: uint: {
+							default: 30
+							unit:    "seconds"
+						}
+					}
+					max_ack_pending: {
+						common:      false
+						description: "The maximum number of messages waiting to be acknowledged before the server pauses delivery."
+						required:    false
+						type: uint: {
+							default: 1000
+							unit:    null
+						}
+					}
+					batch_size: {
+						common:      false
+						description: "The maximum number of messages requested from the consumer at once."
+						required:    false
+						type: uint: {
+							default: 100
+							unit:    null
+						}
+					}
+				}
+			}
+		}
 		queue: {
 			common:      false
-			description: "NATS Queue Group to join"
+			description: "NATS Queue Group to join. Can't be used together with `jetstream`."
 			required:    false
 			type: string: {
 				default: "vector"
@@ -56,11 +147,27 @@ components: sources: nats: {
 	}
 
 	telemetry: metrics: {
-		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
-		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
-		processed_events_total:          components.sources.internal_metrics.output.metrics.processed_events_total
-		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                   components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:             components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:            components.sources.internal_metrics.output.metrics.processed_events_total
+		component_received_events_total:   components.sources.internal_metrics.output.metrics.component_received_events_total
+		connection_errors_total:           components.sources.internal_metrics.output.metrics.connection_errors_total
+		nats_acknowledgement_errors_total: components.sources.internal_metrics.output.metrics.nats_acknowledgement_errors_total
 	}
 
-	how_it_works: components._nats.how_it_works
+	how_it_works: components._nats.how_it_works & {
+		jetstream: {
+			title: "JetStream"
+			body:  """
+				With `jetstream` set, messages are pulled from a durable consumer with
+				explicit acknowledgements, so they survive restarts of Vector. Messages
+				are acknowledged once their events have been read. With `acknowledgements`
+				enabled, they are only acknowledged after all of their events have been
+				delivered by the connected sinks. Messages that failed with a transient
+				error are redelivered right away, while those that failed permanently
+				are terminated and never redelivered. The timestamp of the events is
+				the time the message was stored in the stream.
+				"""
+		}
+	}
 }
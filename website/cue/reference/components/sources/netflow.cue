@@ -0,0 +1,186 @@
+package metadata
+
+components: sources: netflow: {
+	_port: 2055
+
+	title: "NetFlow"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.netflow
+				interface: socket: {
+					direction: "incoming"
+					port:      _port
+					protocols: ["udp"]
+					ssl: "disabled"
+				}
+			}
+			receive_buffer_bytes: enabled: true
+			tls: enabled:                  false
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description: "The UDP address to listen for flow packets on. It _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["0.0.0.0:\(_port)", "0.0.0.0:4739", "0.0.0.0:6343"]
+			}
+		}
+		host_key: {
+			category:    "Context"
+			common:      false
+			description: """
+				The key name added to each event representing the IP address of the exporter. This can also be globally set via the
+				[global `host_key` option](\(urls.vector_configuration)/global-options#log_schema.host_key).
+				"""
+			required:    false
+			type: string: {
+				default: "host"
+			}
+		}
+		max_length: {
+			common:      false
+			description: "The maximum size of incoming flow packets. Larger packets are truncated and usually fail to parse."
+			required:    false
+			type: uint: {
+				default: 65535
+				unit:    "bytes"
+			}
+		}
+		max_templates: {
+			common:      false
+			description: "The maximum number of NetFlow v9 and IPFIX templates kept across all exporters. Once reached, the least recently used templates are evicted."
+			required:    false
+			type: uint: {
+				default: 10000
+				unit:    null
+			}
+		}
+		template_expiry_secs: {
+			common:      false
+			description: "The time after which a template that was not sent again by its exporter is forgotten."
+			required:    false
+			type: uint: {
+				default: 1800
+				unit:    "seconds"
+			}
+		}
+	}
+
+	output: logs: flow: {
+		description: "An individual flow record, or an sFlow sample"
+		fields: {
+			flow_type: {
+				description: "The protocol of the packet the record was received in."
+				required:    true
+				type: string: {
+					enum: {
+						netflow_v5: "NetFlow version 5."
+						netflow_v9: "NetFlow version 9."
+						ipfix:      "IPFIX, also known as NetFlow version 10."
+						sflow_v5:   "sFlow version 5."
+					}
+				}
+			}
+			host: {
+				description: "The IP address of the exporter that sent the packet."
+				required:    true
+				type: string: {
+					examples: ["192.0.2.1"]
+				}
+			}
+			timestamp: {
+				description: "The export time of the packet, or the time it was received at for sFlow."
+				required:    true
+				type: timestamp: {}
+			}
+			"*": {
+				description: "The fields of the packet header and of the record, named after the [IPFIX information elements](\(urls.ipfix_information_elements)) in snake case."
+				required:    true
+				type: "*": {}
+			}
+		}
+	}
+
+	examples: [
+		{
+			title: "NetFlow v5 record"
+			configuration: {}
+			input: """
+				A NetFlow v5 packet with a single TCP flow from 10.0.0.1 to 10.0.0.2:80.
+				"""
+			output: log: {
+				flow_type:                  "netflow_v5"
+				host:                       "192.0.2.1"
+				timestamp:                  "2021-11-01T08:40:32Z"
+				version:                    5
+				sequence_number:            42
+				source_ipv4_address:        "10.0.0.1"
+				destination_ipv4_address:   "10.0.0.2"
+				source_transport_port:      12345
+				destination_transport_port: 80
+				protocol_identifier:        6
+				packet_delta_count:         10
+				octet_delta_count:          1500
+			}
+		},
+	]
+
+	how_it_works: {
+		protocols: {
+			title: "Supported protocols"
+			body:  """
+				The version of every packet is detected, so a single source can receive
+				[NetFlow v5](\(urls.netflow)), [NetFlow v9](\(urls.netflow_v9)),
+				[IPFIX](\(urls.ipfix)) and [sFlow v5](\(urls.sflow_v5)) on the same
+				port. Every flow record becomes an event that also carries the fields of
+				the packet header. For sFlow, every flow or counter sample becomes an
+				event, with the Ethernet, IP and transport headers of sampled packets
+				decoded where present.
+				"""
+		}
+		templates: {
+			title: "Templates"
+			body:  """
+				NetFlow v9 and IPFIX exporters describe their records with templates that
+				are only sent every so often. Templates are kept per exporter address and
+				observation domain until they were not sent again for `template_expiry_secs`,
+				and at most `max_templates` of them are kept, evicting the least recently
+				used. Records that arrive before their template are dropped and counted in
+				`netflow_missing_templates_total`. Fields without a known name are named
+				`field_<id>`, or `enterprise_<number>_<id>` for enterprise-specific ones.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
+		netflow_missing_templates_total: components.sources.internal_metrics.output.metrics.netflow_missing_templates_total
+		parse_errors_total:              components.sources.internal_metrics.output.metrics.parse_errors_total
+		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
@@ -0,0 +1,243 @@
+package metadata
+
+components: sources: redis: {
+	title: "Redis"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			from: {
+				service: services.redis
+				interface: {
+					socket: {
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		acknowledgements: configuration._acknowledgements
+		url: {
+			description: "The Redis URL to connect to. The url _must_ take the form of `protocol://server:port/db` where the protocol can either be `redis` or `rediss` for connections secured via TLS."
+			required:    true
+			type: string: {
+				examples: ["redis://127.0.0.1:6379/0"]
+			}
+		}
+		key: {
+			description: "The Redis key to read messages from. For the `channel` data type, this is the channel name or, with `channel.pattern` enabled, a glob-style channel pattern."
+			required:    true
+			type: string: {
+				examples: ["vector", "logs.*"]
+			}
+		}
+		data_type: {
+			common:      true
+			description: "The Redis data type (`list`, `channel` or `stream`) to read from."
+			required:    false
+			type: string: {
+				default: "list"
+				enum: {
+					list:    "Pop elements from a Redis `list`."
+					channel: "Subscribe to a Redis `channel`."
+					stream:  "Read a Redis `stream` as part of a consumer group."
+				}
+			}
+		}
+		list: {
+			common:      false
+			description: "Options for the Redis `list` data type."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					method: {
+						common:      false
+						description: "The method (`lpop` or `rpop`) to read messages with when `data_type` is list."
+						required:    false
+						type: string: {
+							default: "lpop"
+							enum: {
+								lpop: "Use the blocking `BLPOP` command to read messages from the head of the list."
+								rpop: "Use the blocking `BRPOP` command to read messages from the tail of the list."
+							}
+						}
+					}
+				}
+			}
+		}
+		channel: {
+			common:      false
+			description: "Options for the Redis `channel` data type."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					pattern: {
+						common:      false
+						description: "Whether `key` is a pattern to subscribe to with `PSUBSCRIBE`, rather than a single channel."
+						required:    false
+						type: bool: default: false
+					}
+				}
+			}
+		}
+		stream: {
+			common:      false
+			description: "Options for the Redis `stream` data type."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					group: {
+						common:      true
+						description: "The consumer group to read the stream as. It is created if it doesn't exist."
+						required:    false
+						type: string: {
+							default: "vector"
+							examples: ["vector", "log-processors"]
+						}
+					}
+					consumer: {
+						common:      false
+						description: "The name of this consumer within the group. Defaults to the host name."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["vector-1"]
+						}
+					}
+					start_id: {
+						common:      false
+						description: "The ID to start reading from when the consumer group is created. `$` reads only new entries, `0` reads the whole stream."
+						required:    false
+						type: string: {
+							default: "$"
+							examples: ["$", "0"]
+						}
+					}
+					field: {
+						common:      false
+						description: "The field of each stream entry holding the payload to decode."
+						required:    false
+						type: string: {
+							default: "message"
+							examples: ["message", "payload"]
+						}
+					}
+					count: {
+						common:      false
+						description: "The maximum number of entries to read per request."
+						required:    false
+						type: uint: {
+							default: 100
+							unit:    null
+						}
+					}
+				}
+			}
+		}
+		redis_key: {
+			common:      false
+			description: "The log field name to store the key, or the channel name for the `channel` data type, the message was read from. Not added if unset."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["redis_key"]
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual Redis message"
+		fields: {
+			message: {
+				description: "The raw payload of the message."
+				required:    true
+				type: string: {
+					examples: ["53.126.150.246 - - [01/Oct/2020:11:25:58 -0400] \"GET /disintermediate HTTP/2.0\" 401 20308"]
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The time the stream entry was added, or the time the message was received for the other data types."
+			}
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total:    components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                    components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:              components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total:            components.sources.internal_metrics.output.metrics.processing_errors_total
+		redis_acknowledgement_errors_total: components.sources.internal_metrics.output.metrics.redis_acknowledgement_errors_total
+	}
+
+	how_it_works: {
+		redis_rs: {
+			title: "redis-rs"
+			body:  """
+				The `redis` source uses [`redis-rs`](\(urls.redis_rs)) under the hood, which is a high level Redis library
+				for Rust. It provides convenient access to all Redis functionality through a very flexible but low-level
+				API.
+				"""
+		}
+		channels: {
+			title: "Channels"
+			body:  """
+				[Pub/sub](\(urls.redis_pubsub)) has no backlog, so messages published while
+				Vector is not subscribed, including while it reconnects after losing the
+				connection, are missed.
+				"""
+		}
+		streams: {
+			title: "Streams and acknowledgements"
+			body:  """
+				Streams are read as part of a [consumer group](\(urls.redis_consumer_groups)),
+				so that multiple Vector instances share the work of reading a stream. Entries
+				are acknowledged with `XACK` once their events have been read. With
+				`acknowledgements` enabled, they are only acknowledged after all of their
+				events have been delivered by the connected sinks.
+
+				Entries that were not acknowledged stay pending for the consumer, and are
+				read again when Vector restarts. Only the `stream` data type supports
+				`acknowledgements`, as popping from a list removes the element and
+				channels don't retain messages.
+				"""
+		}
+	}
+}
@@ -0,0 +1,408 @@
+package metadata
+
+components: sources: snmp_trap: {
+	_port: 162
+
+	title: "SNMP Trap"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		receive: {
+			from: {
+				service: services.snmp
+				interface: socket: {
+					direction: "incoming"
+					port:      _port
+					protocols: ["udp"]
+					ssl: "disabled"
+				}
+			}
+			receive_buffer_bytes: enabled: true
+			tls: enabled:                  false
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description: "The UDP address to listen for traps on. It _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["0.0.0.0:\(_port)", "0.0.0.0:1162"]
+			}
+		}
+		communities: {
+			common:      true
+			description: "The communities SNMPv1 and SNMPv2c messages are accepted from. Messages of all communities are accepted if empty."
+			required:    false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["public"]
+				}
+			}
+		}
+		host_key: {
+			category:    "Context"
+			common:      false
+			description: """
+				The key name added to each event representing the IP address of the agent that sent the trap. This can also
+				be globally set via the [global `host_key` option](\(urls.vector_configuration)/global-options#log_schema.host_key).
+				"""
+			required:    false
+			type: string: {
+				default: "host"
+			}
+		}
+		max_length: {
+			common:      false
+			description: "The maximum size of incoming SNMP messages. Larger messages are truncated and fail to parse."
+			required:    false
+			type: uint: {
+				default: 65535
+				unit:    "bytes"
+			}
+		}
+		mib_files: {
+			common:      false
+			description: "Paths of MIB modules used to resolve OIDs to names, such as `IF-MIB::ifIndex.3`. OIDs are kept numeric if empty."
+			required:    false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["/usr/share/snmp/mibs/IF-MIB.txt"]
+				}
+			}
+		}
+		users: {
+			common:      false
+			description: "The [USM](\(urls.snmp_usm)) users SNMPv3 messages are accepted from. SNMPv3 messages are rejected if empty."
+			required:    false
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: []
+					options: {
+						name: {
+							description: "The name of the user."
+							required:    true
+							type: string: {
+								examples: ["monitoring"]
+							}
+						}
+						auth: {
+							description: "The authentication settings of the user. Messages of users without them must be unauthenticated."
+							required:    false
+							common:      true
+							type: object: options: {
+								protocol: {
+									description: "The authentication protocol."
+									required:    true
+									type: string: enum: {
+										md5: "HMAC-MD5-96."
+										sha: "HMAC-SHA-96."
+									}
+								}
+								password: {
+									description: "The authentication password, at least 8 characters long."
+									required:    true
+									type: string: {
+										examples: ["${SNMP_AUTH_PASSWORD}"]
+									}
+								}
+							}
+						}
+						privacy: {
+							description: "The privacy settings of the user, requiring `auth`. Messages of users without them must be unencrypted."
+							required:    false
+							common:      false
+							type: object: options: {
+								protocol: {
+									description: "The privacy protocol."
+									required:    true
+									type: string: enum: {
+										des: "CBC-DES."
+										aes: "[CFB128-AES-128](\(urls.snmp_usm_aes))."
+									}
+								}
+								password: {
+									description: "The privacy password, at least 8 characters long."
+									required:    true
+									type: string: {
+										examples: ["${SNMP_PRIVACY_PASSWORD}"]
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	output: logs: trap: {
+		description: "An individual trap or inform notification."
+		fields: {
+			agent_address: {
+				description: "The address of the agent that generated an SNMPv1 trap."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["192.0.2.1"]
+				}
+			}
+			community: {
+				description: "The community of an SNMPv1 or SNMPv2c message."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["public"]
+				}
+			}
+			context_engine_id: {
+				description: "The hex encoded context engine ID of an SNMPv3 message."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["80001f8880e9630000d61ff449"]
+				}
+			}
+			context_name: {
+				description: "The context name of an SNMPv3 message."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: [""]
+				}
+			}
+			engine_id: {
+				description: "The hex encoded authoritative engine ID of an SNMPv3 message."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["80001f8880e9630000d61ff449"]
+				}
+			}
+			enterprise: {
+				description: "The enterprise OID of an SNMPv1 trap."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["1.3.6.1.4.1.8072.3.2.10"]
+				}
+			}
+			generic_trap: {
+				description: "The generic trap number of an SNMPv1 trap."
+				required:    false
+				common:      false
+				type: uint: {
+					default: null
+					examples: [2]
+					unit: null
+				}
+			}
+			host: {
+				description: "The IP address of the agent that sent the message."
+				required:    true
+				type: string: {
+					examples: ["192.0.2.1"]
+				}
+			}
+			pdu_type: {
+				description: "The kind of notification."
+				required:    true
+				type: string: {
+					enum: {
+						trap:   "A trap, which isn't acknowledged."
+						inform: "An inform, which is acknowledged."
+					}
+				}
+			}
+			request_id: {
+				description: "The request ID of an SNMPv2c or SNMPv3 notification."
+				required:    false
+				common:      false
+				type: int: {
+					default: null
+					examples: [1234]
+				}
+			}
+			source_type: {
+				description: "The name of the source type."
+				required:    true
+				type: string: {
+					examples: ["snmp_trap"]
+				}
+			}
+			specific_trap: {
+				description: "The specific trap number of an SNMPv1 trap."
+				required:    false
+				common:      false
+				type: uint: {
+					default: null
+					examples: [0]
+					unit: null
+				}
+			}
+			timestamp: {
+				description: "The time the message was received at."
+				required:    true
+				type: timestamp: {}
+			}
+			trap_name: {
+				description: "The name of `trap_oid`, if it's defined by one of the `mib_files`."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["IF-MIB::linkDown"]
+				}
+			}
+			trap_oid: {
+				description: "The OID of the notification. For SNMPv1 traps, it's derived from the enterprise and trap numbers as described in [RFC 3584](\(urls.snmp_v1_coexistence))."
+				required:    true
+				type: string: {
+					examples: ["1.3.6.1.6.3.1.1.5.3"]
+				}
+			}
+			uptime: {
+				description: "The uptime of the agent, in hundredths of a second."
+				required:    false
+				common:      true
+				type: uint: {
+					default: null
+					examples: [123456]
+					unit: null
+				}
+			}
+			user: {
+				description: "The USM user of an SNMPv3 message."
+				required:    false
+				common:      false
+				type: string: {
+					default: null
+					examples: ["monitoring"]
+				}
+			}
+			varbinds: {
+				description: """
+					The variable bindings of the notification, keyed by OID or by name if defined by one of the `mib_files`.
+					Octet strings that aren't text, and opaque values, are hex encoded.
+					"""
+				required: true
+				type: object: {
+					examples: [{"IF-MIB::ifIndex.3": 3, "IF-MIB::ifDescr.3": "eth0"}]
+					options: {}
+				}
+			}
+			version: {
+				description: "The SNMP version of the message."
+				required:    true
+				type: string: {
+					enum: {
+						"1":  "SNMPv1."
+						"2c": "SNMPv2c."
+						"3":  "SNMPv3."
+					}
+				}
+			}
+		}
+	}
+
+	examples: [
+		{
+			title: "SNMPv2c link down trap"
+			configuration: {
+				communities: ["public"]
+				mib_files: ["/usr/share/snmp/mibs/IF-MIB.txt"]
+			}
+			input: """
+				An SNMPv2c linkDown trap of the `public` community for interface 3.
+				"""
+			output: log: {
+				version:    "2c"
+				pdu_type:   "trap"
+				community:  "public"
+				request_id: 1234
+				uptime:     123456
+				trap_name:  "IF-MIB::linkDown"
+				trap_oid:   "1.3.6.1.6.3.1.1.5.3"
+				varbinds: {
+					"IF-MIB::ifIndex.3":       3
+					"IF-MIB::ifAdminStatus.3": 1
+					"IF-MIB::ifOperStatus.3":  2
+				}
+				source_type: "snmp_trap"
+				timestamp:   "2021-11-01T08:40:32Z"
+				host:        "192.0.2.1"
+			}
+		},
+	]
+
+	how_it_works: {
+		security: {
+			title: "Security"
+			body:  """
+				SNMPv1 and SNMPv2c messages are only authenticated by their community,
+				which is sent in clear text. SNMPv3 messages are authenticated and
+				decrypted with the [User-based Security Model](\(urls.snmp_usm)) using
+				the keys of the configured `users`, localized to the engine ID of each
+				agent. Messages that fail authentication are dropped and counted in
+				`snmp_trap_authentication_failures_total`. The boots and time of each
+				engine are learned from its authenticated messages, and messages that lag
+				more than 150 seconds behind them are rejected as replays.
+				"""
+		}
+		informs: {
+			title: "Informs"
+			body:  """
+				SNMPv2c informs are acknowledged with a response as soon as they are
+				decoded, before the event is delivered to the sinks. SNMPv3 informs
+				require the receiver to be the authoritative engine, which this source
+				isn't, so they're turned into events but not acknowledged.
+				"""
+		}
+		mibs: {
+			title: "MIB files"
+			body:  """
+				Only the OID assignments of the `mib_files` are read, which covers both
+				SMIv1 and SMIv2 modules. Modules may import from each other in any order.
+				OIDs are named after the closest defined ancestor, followed by the
+				remaining arcs such as the index of a table row.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total:         components.sources.internal_metrics.output.metrics.component_received_events_total
+		events_in_total:                         components.sources.internal_metrics.output.metrics.events_in_total
+		parse_errors_total:                      components.sources.internal_metrics.output.metrics.parse_errors_total
+		processed_bytes_total:                   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		send_errors_total:                       components.sources.internal_metrics.output.metrics.send_errors_total
+		snmp_trap_authentication_failures_total: components.sources.internal_metrics.output.metrics.snmp_trap_authentication_failures_total
+	}
+}
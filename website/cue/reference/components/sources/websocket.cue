@@ -0,0 +1,99 @@
+package metadata
+
+components: sources: websocket: {
+	_port: 8080
+
+	title: "WebSocket"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		deployment_roles: ["aggregator"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+		receive: {
+			from: {
+				service: services.websocket_client
+				interface: socket: {
+					direction: "incoming"
+					port:      _port
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+			receive_buffer_bytes: enabled: false
+			keepalive: enabled:            false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		address: {
+			description: "The address to accept WebSocket connections on. The address _must_ include a port."
+			required:    true
+			type: string: {
+				examples: ["0.0.0.0:\(_port)"]
+			}
+		}
+	}
+
+	output: logs: line: {
+		description: "An event decoded from a WebSocket message."
+		fields: {
+			host: {
+				description: "The IP address of the client the message was received from."
+				required:    true
+				type: string: {
+					examples: ["192.168.0.12"]
+				}
+			}
+			message:   fields._raw_line
+			timestamp: fields._current_timestamp
+		}
+	}
+
+	how_it_works: {
+		messages: {
+			title: "Messages"
+			body: """
+				Both text and binary WebSocket messages are decoded with the configured `framing` and `decoding`,
+				each message being framed on its own. Ping messages are answered automatically. When Vector shuts
+				down, the connections are closed with a close frame.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_received_events_total: components.sources.internal_metrics.output.metrics.component_received_events_total
+		connection_errors_total:         components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_established_total:    components.sources.internal_metrics.output.metrics.connection_established_total
+		connection_shutdown_total:       components.sources.internal_metrics.output.metrics.connection_shutdown_total
+		events_in_total:                 components.sources.internal_metrics.output.metrics.events_in_total
+		open_connections:                components.sources.internal_metrics.output.metrics.open_connections
+		processed_bytes_total:           components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+}
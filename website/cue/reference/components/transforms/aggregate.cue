@@ -27,6 +27,106 @@ components: transforms: aggregate: {
 	}
 
 	configuration: {
+		functions: {
+			common:      false
+			description: "The functions used to aggregate values, per metric type. Summaries can't be combined, and always keep the most recent value."
+			required:    false
+			type: object: options: {
+					counter: {
+						common:      false
+						description: "The function used to aggregate `counter` metrics."
+						required:    false
+						type: string: {
+							default: "sum"
+							enum: {
+								sum:  "Add the values together."
+								last: "Keep the most recent value."
+								max:  "Keep the largest value."
+								min:  "Keep the smallest value."
+								mean: "Calculate the average of the values."
+							}
+						}
+					}
+					distribution: {
+						common:      false
+						description: "The function used to aggregate `distribution` metrics."
+						required:    false
+						type: string: {
+							default: "merge"
+							enum: {
+								merge: "Merge the values together."
+								last:  "Keep the most recent value."
+							}
+						}
+					}
+					gauge: {
+						common:      false
+						description: "The function used to aggregate `gauge` metrics."
+						required:    false
+						type: string: {
+							default: "sum"
+							enum: {
+								sum:  "Add the values together."
+								last: "Keep the most recent value."
+								max:  "Keep the largest value."
+								min:  "Keep the smallest value."
+								mean: "Calculate the average of the values."
+							}
+						}
+					}
+					histogram: {
+						common:      false
+						description: "The function used to aggregate `histogram` metrics."
+						required:    false
+						type: string: {
+							default: "merge"
+							enum: {
+								merge: "Merge the values together."
+								last:  "Keep the most recent value."
+							}
+						}
+					}
+					set: {
+						common:      false
+						description: "The function used to aggregate `set` metrics."
+						required:    false
+						type: string: {
+							default: "merge"
+							enum: {
+								merge: "Merge the values together."
+								last:  "Keep the most recent value."
+							}
+						}
+					}
+					sketch: {
+						common:      false
+						description: "The function used to aggregate `sketch` metrics."
+						required:    false
+						type: string: {
+							default: "merge"
+							enum: {
+								merge: "Merge the values together."
+								last:  "Keep the most recent value."
+							}
+						}
+					}
+			}
+		}
+		group_by: {
+			common:      false
+			description: """
+				The tags to keep on aggregated metrics. Series that only differ in any other tags are aggregated
+				together. Incompatible with the `remove_tags` option.
+				"""
+			required: false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["deployment", "host"]
+					syntax: "literal"
+				}
+			}
+		}
 		interval_ms: {
 			common: true
 			description: """
@@ -39,6 +139,34 @@ components: transforms: aggregate: {
 				unit:    "milliseconds"
 			}
 		}
+		mode: {
+			common:      false
+			description: "The kind of the metrics emitted by the transform."
+			required:    false
+			type: string: {
+				default: "auto"
+				enum: {
+					auto:        "Emit metrics of the same kind as the ones received."
+					absolute:    "Emit `absolute` metrics, accumulating `incremental` metrics across intervals."
+					incremental: "Emit `incremental` metrics, calculating the change of `absolute` metrics since the previous interval."
+				}
+			}
+		}
+		remove_tags: {
+			common:      false
+			description: """
+				The tags to remove from aggregated metrics. Series that only differ in these tags are aggregated
+				together. Incompatible with the `group_by` option.
+				"""
+			required: false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["pod", "instance"]
+					syntax: "literal"
+				}
+			}
+		}
 	}
 
 	input: {
@@ -185,6 +313,31 @@ components: transforms: aggregate: {
 				"""
 		}
 
+		grouping: {
+			title: "Grouping and Functions"
+			body: """
+				The `group_by` and `remove_tags` options reduce the cardinality of the aggregated metrics by
+				aggregating series that only differ in some of their tags together. For example, removing the `pod`
+				tag rolls up the metrics of every pod into a single series per deployment.
+
+				Values of aggregated series are combined using the function configured for their type in
+				`functions`. `incremental` values are combined as they are received. `absolute` values replace the
+				previous value of the same original series, so only the most recent value of every original series
+				in the interval is combined. For example, removing the `pod` tag from `absolute` `counter` metrics
+				sums the latest count of every pod.
+				"""
+		}
+
+		output_mode: {
+			title: "Output Mode"
+			body: """
+				By default aggregated metrics keep the kind of the metrics they were created from. Setting `mode`
+				to `absolute` keeps a running total of `incremental` metrics across intervals, while setting it to
+				`incremental` emits the change of `absolute` metrics since the previous interval. In the latter
+				case, a series is only emitted once a previous value is known.
+				"""
+		}
+
 		advantages: {
 			title: "Advantages of Use"
 			body: """
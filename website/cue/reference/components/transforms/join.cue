@@ -0,0 +1,181 @@
+package metadata
+
+components: transforms: join: {
+	title: "Join"
+
+	description: """
+		Correlates events received from two groups of inputs by a shared key,
+		merging each pair of matching events into a single event.
+		"""
+
+	classes: {
+		commonly_used: false
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      true
+	}
+
+	features: {
+		reduce: {}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		key: {
+			description: "The key used to correlate events from both sides of the join."
+			required:    true
+			type: string: {
+				examples: ["{{ request_id }}", "{{ host }}-{{ transaction_id }}"]
+				syntax: "template"
+			}
+		}
+		left: {
+			description: "The first side of the join. Fields of the left event are inserted into the joined event first."
+			required:    true
+			type: object: options: {
+				inputs: {
+					description: "The inputs of the transform whose events belong to the left side. An input cannot belong to both sides."
+					required:    true
+					type: array: items: type: string: {
+						examples: ["requests"]
+						syntax: "literal"
+					}
+				}
+				prefix: {
+					common:      false
+					description: "When set, the fields of the left event are nested under this field instead of the root of the joined event."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["request"]
+						syntax: "literal"
+					}
+				}
+			}
+		}
+		max_pending_bytes: {
+			common:      false
+			description: "The maximum size of the events waiting for a counterpart. Events that would exceed this limit are unmatched."
+			required:    false
+			type: uint: {
+				default: 104857600
+				unit:    "bytes"
+			}
+		}
+		max_pending_keys: {
+			common:      false
+			description: "The maximum number of keys waiting for a counterpart. Events arriving once this limit is reached are unmatched."
+			required:    false
+			type: uint: {
+				default: 10000
+				unit:    null
+			}
+		}
+		reroute_unmatched: {
+			common:      false
+			description: """
+				Send events that could not be joined to the `unmatched` output, which can be referenced by other
+				components as `<transform_name>.unmatched`. When disabled, unmatched events are dropped.
+				"""
+			required: false
+			type: bool: default: false
+		}
+		right: {
+			description: "The second side of the join. Fields of the right event overwrite fields of the left event with the same name."
+			required:    true
+			type: object: options: {
+				inputs: {
+					description: "The inputs of the transform whose events belong to the right side. An input cannot belong to both sides."
+					required:    true
+					type: array: items: type: string: {
+						examples: ["responses"]
+						syntax: "literal"
+					}
+				}
+				prefix: {
+					common:      false
+					description: "When set, the fields of the right event are nested under this field instead of the root of the joined event."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["response"]
+						syntax: "literal"
+					}
+				}
+			}
+		}
+		ttl_secs: {
+			common:      false
+			description: "The maximum period of time an event waits for its counterpart before it is unmatched."
+			required:    false
+			type: uint: {
+				default: 30
+				unit:    "seconds"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	examples: [
+		{
+			title: "Join requests and responses"
+			configuration: {
+				key: "{{ id }}"
+				left: inputs: ["requests"]
+				right: inputs: ["responses"]
+				right: prefix: "response"
+			}
+			input: [
+				{log: {type: "request", id: "abcd1234", path: "/path"}},
+				{log: {type: "response", id: "abcd1234", status: 200}},
+			]
+			output: log: {
+				type: "request"
+				id:   "abcd1234"
+				path: "/path"
+				response: {type: "response", id: "abcd1234", status: 200}
+			}
+		},
+	]
+
+	how_it_works: {
+		correlation: {
+			title: "Correlation"
+			body: """
+				Each event is assigned to the left or the right side by the input it was received from, and
+				its key is rendered from the `key` template. The first event seen for a key waits until an event from the other side arrives with
+				the same key, at which point both are merged and emitted as a single event. A second event from
+				the same side replaces the waiting event, which is then unmatched.
+
+				The inputs of both sides must also be listed in the transform's `inputs`. They are matched by
+				their exact name, so wildcards are not supported in `left.inputs` and `right.inputs`.
+				"""
+		}
+		unmatched_events: {
+			title: "Unmatched Events"
+			body: """
+				Events received from an input of neither side, whose key cannot be rendered, or that do not find a counterpart
+				within `ttl_secs` are unmatched. Unmatched events are dropped unless `reroute_unmatched` is
+				enabled, in which case they are sent to the `unmatched` output. Events still waiting when Vector
+				shuts down are unmatched as well.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:      components.sources.internal_metrics.output.metrics.events_discarded_total
+		join_events_matched_total:   components.sources.internal_metrics.output.metrics.join_events_matched_total
+		join_events_unmatched_total: components.sources.internal_metrics.output.metrics.join_events_unmatched_total
+		join_pending_bytes:          components.sources.internal_metrics.output.metrics.join_pending_bytes
+		join_pending_keys:           components.sources.internal_metrics.output.metrics.join_pending_keys
+	}
+}
@@ -0,0 +1,162 @@
+package metadata
+
+components: transforms: metric_math: {
+	title: "Metric Math"
+
+	description: """
+		Derives new metric series, such as rates, deltas and ratios, from the
+		incoming metrics. Incoming metrics are passed through unchanged.
+		"""
+
+	classes: {
+		commonly_used: false
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      true
+	}
+
+	features: {
+		convert: {}
+	}
+
+	support: {
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		derive: {
+			description: "The series to derive from the incoming metrics."
+			required:    true
+			type: array: items: type: object: {
+				examples: []
+				options: {
+					expression: {
+						description: """
+							An arithmetic expression over the variables defined in `variables`, supporting `+`, `-`,
+							`*`, `/` and parentheses.
+							"""
+						required:      true
+						relevant_when: #"function = "expression""#
+						type: string: {
+							examples: ["errors / requests * 100"]
+							syntax: "literal"
+						}
+					}
+					function: {
+						description: "The function used to derive the series."
+						required:    true
+						type: string: {
+							enum: {
+								rate:           "The per-second rate of change of `metric`, derived as a `gauge`."
+								delta:          "The change of `metric` since its previous value, derived as a `gauge`."
+								increase:       "The increase of `metric` since its previous value, derived as an `incremental` `counter`. Decreases are treated as counter resets."
+								moving_average: "The average of the latest `samples` values of `metric`, derived as a `gauge`."
+								expression:     "The result of `expression` over the latest values of the metrics in `variables`, derived as a `gauge`."
+							}
+						}
+					}
+					metric: {
+						description:   "The name of the metric the series is derived from."
+						required:      true
+						relevant_when: #"function = "rate" or function = "delta" or function = "increase" or function = "moving_average""#
+						type: string: {
+							examples: ["http_requests_total"]
+							syntax: "literal"
+						}
+					}
+					name: {
+						description: "The name of the derived metric."
+						required:    true
+						type: string: {
+							examples: ["http_requests_per_second"]
+							syntax: "literal"
+						}
+					}
+					namespace: {
+						common:      false
+						description: "The namespace of the derived metric. Defaults to the namespace of the source metric."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["service"]
+							syntax: "literal"
+						}
+					}
+					samples: {
+						description:   "The number of values averaged."
+						required:      true
+						relevant_when: #"function = "moving_average""#
+						type: uint: {
+							examples: [5]
+							unit: null
+						}
+					}
+					variables: {
+						description:   "The variables available to `expression`, mapped to the name of the metric providing their value."
+						required:      true
+						relevant_when: #"function = "expression""#
+						type: object: {
+							examples: [{errors: "http_errors_total", requests: "http_requests_total"}]
+							options: {}
+						}
+					}
+				}
+			}
+		}
+		expire_after_secs: {
+			common:      false
+			description: "The period of time after which the state of a series that stopped receiving metrics is discarded."
+			required:    false
+			type: uint: {
+				default: 300
+				unit:    "seconds"
+			}
+		}
+	}
+
+	input: {
+		logs: false
+		metrics: {
+			counter:      true
+			distribution: false
+			gauge:        true
+			histogram:    false
+			set:          false
+			summary:      false
+		}
+	}
+
+	how_it_works: {
+		series: {
+			title: "Series"
+			body: """
+				Derived series are keyed by the namespace and tags of the metrics they are derived from: a rate
+				derived from a metric tagged with `host = "a"` only uses the previous values of that metric with
+				the same namespace and tags, and carries the same tags. Expressions combine the values of metrics
+				sharing exactly the same namespace and tags, and are only evaluated once a value was received for
+				every variable. Expressions that
+				don't evaluate to a finite number, for example due to a division by zero, are skipped.
+
+				The state of a series is discarded once it stopped receiving metrics for `expire_after_secs`.
+				"""
+		}
+
+		metric_kinds: {
+			title: "Metric Kinds"
+			body: """
+				`incremental` metrics are first converted into `absolute` ones by accumulating their values, in
+				the same way as sinks that only accept absolute metrics do. Every function then works on the
+				absolute value of a series: the `rate`, `delta` and `increase` functions calculate the change
+				from its previous value, so the first value of a series doesn't derive any metric. Rates use the
+				timestamps of the metrics, or the time they were received if they have none.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		metric_math_series_expired_total: components.sources.internal_metrics.output.metrics.metric_math_series_expired_total
+		processing_errors_total:          components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -69,6 +69,16 @@ components: transforms: reduce: {
 				}
 			}
 		}
+		max_events: {
+			common:      false
+			description: "The maximum number of events to combine into a single event. Once reached, the combined event is flushed and the next event starts a new one."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [1000]
+				unit: "events"
+			}
+		}
 		merge_strategies: {
 			common: false
 			description: """
@@ -84,6 +94,9 @@ components: transforms: reduce: {
 				   `[field-name]_end` is added with the last received
 				   timestamp value.
 				3. Numeric values are summed.
+
+				The `percentile` strategy takes the percentile to compute as an
+				argument, for example `{ percentile = 95 }`.
 				"""
 			required: false
 			type: object: {
@@ -112,6 +125,11 @@ components: transforms: reduce: {
 								max:            "The maximum of all numeric values."
 								min:            "The minimum of all numeric values."
 								flat_unique:    "Create a flattened array of all the unique values."
+								count:          "The number of values found."
+								count_distinct: "The number of unique values found."
+								avg:            "The mean of all numeric values."
+								first_non_null: "Keep the first value found that is not null."
+								percentile:     "The given percentile (between 0 and 100) of all numeric values, linearly interpolated between the closest ranks."
 							}
 						}
 					}
@@ -132,6 +150,58 @@ components: transforms: reduce: {
 				]
 			}
 		}
+		window: {
+			common: false
+			description: """
+				Groups events into time windows based on their timestamp, in addition to
+				`group_by`. A window is flushed once the watermark is past the end of the
+				window by more than `allowed_lateness_ms`, and is not subject to
+				`expire_after_ms`. The watermark is the most recent event timestamp,
+				advanced by the time elapsed since that event was received, so windows
+				are still flushed when events stop arriving. Events that only belong to
+				windows that were already flushed, and events without a timestamp, are
+				discarded.
+				Cannot be combined with `starts_when` or `ends_when`.
+				"""
+			required: false
+			type: object: options: {
+				allowed_lateness_ms: {
+					description: "How long after the end of a window, in event time, events are still added to it."
+					required:    false
+					type: uint: {
+						default: 0
+						examples: [5000]
+						unit: "milliseconds"
+					}
+				}
+				type: {
+					description: "The kind of window."
+					required:    true
+					type: string: enum: {
+						tumbling: "Fixed-size, non-overlapping windows. Every event belongs to exactly one window."
+						sliding:  "Fixed-size windows starting every `slide_ms`. Every event belongs to each window covering its timestamp."
+					}
+				}
+				duration_ms: {
+					description: "The length of each window."
+					required:    true
+					type: uint: {
+						examples: [60000]
+						unit: "milliseconds"
+					}
+				}
+				slide_ms: {
+					description:   "The interval between the start of successive windows. `duration_ms` can be at most 1000 times `slide_ms`."
+					relevant_when: #"type = "sliding""#
+					required:      false
+					type: uint: {
+						default: null
+						examples: [10000]
+						unit: "milliseconds"
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -251,6 +321,7 @@ components: transforms: reduce: {
 	]
 
 	telemetry: metrics: {
+		events_discarded_total:     components.sources.internal_metrics.output.metrics.events_discarded_total
 		stale_events_flushed_total: components.sources.internal_metrics.output.metrics.stale_events_flushed_total
 	}
 }
@@ -104,6 +104,18 @@ components: transforms: "remap": {
 				"""
 			type: bool: default: true
 		}
+		profile: {
+			common:   false
+			required: false
+			description: """
+				Profile the program while mapping events, and report the number of calls
+				to, and the time spent in, each VRL function as internal metrics. This
+				adds overhead to every mapped event and is meant for troubleshooting slow
+				programs. See the `vrl` CLI's `--profile` flag for a breakdown per root
+				expression.
+				"""
+			type: bool: default: false
+		}
 		reroute_dropped: {
 			common:   false
 			required: false
@@ -207,6 +219,8 @@ components: transforms: "remap": {
 	}
 
 	telemetry: metrics: {
-		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+		processing_errors_total:            components.sources.internal_metrics.output.metrics.processing_errors_total
+		vrl_function_call_duration_seconds: components.sources.internal_metrics.output.metrics.vrl_function_call_duration_seconds
+		vrl_function_calls_total:           components.sources.internal_metrics.output.metrics.vrl_function_calls_total
 	}
 }
@@ -0,0 +1,10 @@
+package metadata
+
+services: amqp: {
+	name:     "AMQP"
+	thing:    "an \(name) 0-9-1 broker"
+	url:      urls.amqp
+	versions: null
+
+	description: "[AMQP](\(urls.amqp)) is an open standard messaging protocol. Version 0-9-1 is implemented by brokers such as [RabbitMQ](\(urls.rabbitmq))."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: graphite: {
+	name:     "Graphite"
+	thing:    "a \(name) server"
+	url:      urls.graphite
+	versions: null
+
+	description: "[Graphite](\(urls.graphite)) is a monitoring tool storing numeric time series, fed by its Carbon daemon over the plaintext protocol."
+}
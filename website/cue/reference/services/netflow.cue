@@ -0,0 +1,10 @@
+package metadata
+
+services: netflow: {
+	name:     "NetFlow, IPFIX and sFlow"
+	thing:    "a flow exporter"
+	url:      urls.netflow
+	versions: null
+
+	description: "[NetFlow](\(urls.netflow)), its standardized successor [IPFIX](\(urls.ipfix)) and [sFlow](\(urls.sflow)) are protocols with which routers, switches and probes export summaries of the network traffic they observe."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: snmp: {
+	name:     "SNMP"
+	thing:    "an SNMP agent"
+	url:      urls.snmp
+	versions: null
+
+	description: "The [Simple Network Management Protocol (SNMP)](\(urls.snmp)) is used to monitor network devices, which notify managers of events such as interfaces going down with traps and informs."
+}
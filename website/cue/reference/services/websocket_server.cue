@@ -0,0 +1,8 @@
+package metadata
+
+services: websocket_server: {
+	name:     "WebSocket server"
+	thing:    "a \(name)"
+	url:      urls.websocket
+	versions: null
+}
@@ -5,6 +5,7 @@ urls: {
 	affine_type_system:                                       "\(wikipedia)/wiki/Substructural_type_system#Affine_type_systems"
 	adaptive_request_concurrency_post:                        "/blog/adaptive-request-concurrency/"
 	amazon_linux:                                             "https://aws.amazon.com/amazon-linux-ami/"
+	amqp:                                                     "https://www.amqp.org/"
 	ansi_escape_codes:                                        "\(wikipedia)/wiki/ANSI_escape_code"
 	apache:                                                   "https://httpd.apache.org"
 	apache_common:                                            "\(apache)/docs/current/logs.html#common"
@@ -99,6 +100,8 @@ urls: {
 	cgroups_limit_resources:                                  "https://the.binbashtheory.com/control-resources-cgroups/"
 	clickhouse:                                               "https://clickhouse.yandex/"
 	clickhouse_http:                                          "https://clickhouse.yandex/docs/en/interfaces/http/"
+	clickhouse_json_each_row:                                 "https://clickhouse.com/docs/en/interfaces/formats/#jsoneachrow"
+	clickhouse_row_binary:                                    "https://clickhouse.com/docs/en/interfaces/formats/#rowbinary"
 	cloudsmith:                                               "https://cloudsmith.io/~timber/repos/vector/packages/"
 	cloudsmith_apt:                                           "https://cloudsmith.io/~timber/repos/vector/setup/#formats-deb"
 	cloudsmith_yum:                                           "https://cloudsmith.io/~timber/repos/vector/setup/#formats-rpm"
@@ -166,6 +169,7 @@ urls: {
 	elasticsearch_id_field:                                   "https://www.elastic.co/guide/en/elasticsearch/reference/current/mapping-id-field.html"
 	elasticsearch_id_performance:                             "https://www.elastic.co/guide/en/elasticsearch/reference/master/tune-for-indexing-speed.html#_use_auto_generated_ids"
 	elasticsearch_ignore_malformed:                           "https://www.elastic.co/guide/en/elasticsearch/reference/current/ignore-malformed.html"
+	elasticsearch_nodes_info:                                 "https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-nodes-info.html"
 	encoding_charset_labels:                                  "https://encoding.spec.whatwg.org/#concept-encoding-get"
 	encoding_standard:                                        "https://encoding.spec.whatwg.org/"
 	endler_dev:                                               "https://endler.dev/"
@@ -193,7 +197,9 @@ urls: {
 	gcp_cloud_storage:                                        "\(gcp)/storage"
 	gcp_folders:                                              "\(gcp)/resource-manager/docs/creating-managing-folders"
 	gcp_pubsub:                                               "\(gcp)/pubsub/"
+	gcp_pubsub_emulator:                                      "\(gcp)/pubsub/docs/emulator"
 	gcp_pubsub_rest:                                          "\(gcp)/pubsub/docs/reference/rest/"
+	gcp_pubsub_streaming_pull:                                "\(gcp)/pubsub/docs/pull#streamingpull"
 	gcp_projects:                                             "\(gcp)/resource-manager/docs/creating-managing-projects"
 	gcp_resources:                                            "\(gcp)/monitoring/api/resources"
 	gcp_stackdriver:                                          "\(gcp)/products/operations"
@@ -213,6 +219,10 @@ urls: {
 	github_protected_branches:                                "https://help.github.com/en/github/administering-a-repository/about-protected-branches"
 	github_sign_commits:                                      "https://help.github.com/en/github/authenticating-to-github/signing-commits"
 	globbing:                                                 "\(wikipedia)/wiki/Glob_(programming)"
+	graphite:                                                 "https://graphiteapp.org/"
+	graphite_pickle:                                          "https://graphite.readthedocs.io/en/latest/feeding-carbon.html#the-pickle-protocol"
+	graphite_plaintext:                                       "https://graphite.readthedocs.io/en/latest/feeding-carbon.html#the-plaintext-protocol"
+	graphite_tags:                                            "https://graphite.readthedocs.io/en/latest/tags.html"
 	glog:                                                     "\(github)/google/glog"
 	graphql:                                                  "https://graphql.org"
 	graphql_playground:                                       "\(github)/graphql/graphql-playground"
@@ -252,6 +262,8 @@ urls: {
 	inode:                                                    "\(wikipedia)/wiki/Inode"
 	ip_aton:                                                  "https://linux.die.net/man/3/inet_aton"
 	ip_ntoa:                                                  "https://linux.die.net/man/3/inet_ntoa"
+	ipfix:                                                    "https://datatracker.ietf.org/doc/html/rfc7011"
+	ipfix_information_elements:                               "https://www.iana.org/assignments/ipfix/ipfix.xhtml"
 	iso_8601:                                                 "\(wikipedia)/wiki/ISO_8601"
 	iso3166_2:                                                "\(wikipedia)/wiki/ISO_3166-2"
 	issue_1694:                                               "\(vector_repo)/issues/1694"
@@ -278,6 +290,7 @@ urls: {
 	kubernetes_rbac:                                          "\(kubernetes)/docs/reference/access-authn-authz/rbac/"
 	kubernetes_request_verbs:                                 "\(kubernetes)/docs/reference/access-authn-authz/authorization/#determine-the-request-verb"
 	kubernetes_watch_api:                                     "\(kubernetes)/docs/reference/generated/kubernetes-api/v1.10/#watch-30"
+	lapin:                                                    "\(github)/amqp-rs/lapin"
 	leveldb:                                                  "\(github)/google/leveldb"
 	leveldb_sys_2:                                            "https://crates.io/crates/leveldb-sys"
 	leveldb_sys_3:                                            "\(github)/vectordotdev/leveldb-sys/tree/v3.0.0"
@@ -331,9 +344,15 @@ urls: {
 	mongodb:                                                  "https://www.mongodb.com"
 	mongodb_command_server_status:                            "https://docs.mongodb.com/manual/reference/command/serverStatus/"
 	mongodb_connection_string_uri_format:                     "https://docs.mongodb.com/manual/reference/connection-string/"
+	mqtt:                                                     "https://mqtt.org/"
+	mqtt_topics:                                              "https://www.hivemq.com/blog/mqtt-essentials-part-5-mqtt-topics-best-practices/"
 	musl_builder_docker_image:                                "\(vector_repo)/blob/master/scripts/ci-docker-images/builder-x86_64-unknown-linux-musl/Dockerfile"
 	nats:                                                     "https://nats.io/"
+	nats_credentials:                                         "https://docs.nats.io/using-nats/developer/connecting/creds"
+	nats_jetstream:                                           "https://docs.nats.io/nats-concepts/jetstream"
 	nats_rs:                                                  "\(github)/nats-io/nats.rs"
+	netflow:                                                  "https://www.cisco.com/c/en/us/products/ios-nx-os-software/ios-netflow/index.html"
+	netflow_v9:                                               "https://datatracker.ietf.org/doc/html/rfc3954"
 	new_bug_report:                                           "\(vector_repo)/issues/new?labels=type%3A+bug"
 	new_feature_request:                                      "\(vector_repo)/issues/new?labels=type%3A+new+feature"
 	new_relic:                                                "https://newrelic.com/"
@@ -350,10 +369,12 @@ urls: {
 	nix:                                                      "https://nixos.org/nix/"
 	nixos:                                                    "https://nixos.org/"
 	nixpkgs_9682:                                             "\(github)/NixOS/nixpkgs/issues/9682"
+	openmetrics:                                              "https://openmetrics.io/"
 	openssl:                                                  "https://www.openssl.org/"
 	order_of_ops:                                             "\(wikipedia)/wiki/Order_of_operations"
 	papertrail:                                               "https://www.papertrail.com/"
 	papertrail_syslog:                                        "https://help.papertrailapp.com/kb/how-it-works/http-api/#submitting-log-messages"
+	parquet:                                                  "https://parquet.apache.org/"
 	perl_windows:                                             "https://www.perl.org/get.html#win32"
 	percent_encoded_bytes:                                    "https://url.spec.whatwg.org/#percent-encoded-bytes"
 	percent_encoding_controls:                                "https://infra.spec.whatwg.org/#c0-control"
@@ -386,6 +407,10 @@ urls: {
 	protobuf:                                                 "https://developers.google.com/protocol-buffers"
 	pulsar:                                                   "https://pulsar.apache.org/"
 	pulsar_protocol:                                          "https://pulsar.apache.org/docs/en/develop-binary-protocol/"
+	rabbitmq:                                                 "https://www.rabbitmq.com/"
+	rabbitmq_dead_letter:                                     "https://www.rabbitmq.com/dlx.html"
+	rabbitmq_prefetch:                                        "https://www.rabbitmq.com/consumer-prefetch.html"
+	rabbitmq_uri_spec:                                        "https://www.rabbitmq.com/uri-spec.html"
 	raspbian:                                                 "https://www.raspbian.org/"
 	rdkafka:                                                  "\(github)/edenhill/librdkafka"
 	regex:                                                    "\(wikipedia)/wiki/Regular_expression"
@@ -406,6 +431,7 @@ urls: {
 	rfc_6891:                                                 "https://tools.ietf.org/html/rfc6891"
 	rhel:                                                     "https://www.redhat.com/en/technologies/linux-platforms/enterprise-linux"
 	rpm:                                                      "https://rpm.org/"
+	rumqttc:                                                  "\(github)/bytebeamio/rumqtt"
 	rust:                                                     "https://www.rust-lang.org/"
 	rust_date_time:                                           "https://docs.rs/chrono/latest/chrono/struct.DateTime.html"
 	rust_grok_library:                                        "\(github)/daschl/grok"
@@ -419,6 +445,8 @@ urls: {
 	rust_tokio:                                               "\(github)/tokio-rs/tokio"
 	rustup:                                                   "https://rustup.rs"
 	redis:                                                    "https://redis.io"
+	redis_consumer_groups:                                    "https://redis.io/topics/streams-intro#consumer-groups"
+	redis_pubsub:                                             "https://redis.io/topics/pubsub"
 	redis_rs:                                                 "https://github.com/mitsuhiko/redis-rs"
 	sematext:                                                 "https://sematext.com"
 	sematext_create_logs_app:                                 "https://apps.sematext.com/ui/integrations"
@@ -427,12 +455,18 @@ urls: {
 	sematext_monitoring:                                      "https://sematext.com/docs/monitoring/"
 	sematext_registration:                                    "https://apps.sematext.com/ui/registration"
 	semver:                                                   "https://semver.org/"
+	sflow:                                                    "https://sflow.org/"
+	sflow_v5:                                                 "https://sflow.org/sflow_version_5.txt"
 	sha1:                                                     "\(wikipedia)/wiki/SHA-1"
 	sha2:                                                     "\(wikipedia)/wiki/SHA-2"
 	sha3:                                                     "\(wikipedia)/wiki/SHA-3"
 	signal:                                                   "\(wikipedia)/wiki/Signal_(IPC)"
 	snake_case:                                               "\(wikipedia)/wiki/Snake_case"
 	snappy:                                                   "https://google.github.io/snappy/"
+	snmp:                                                     "https://datatracker.ietf.org/doc/html/rfc3411"
+	snmp_usm:                                                 "https://datatracker.ietf.org/doc/html/rfc3414"
+	snmp_usm_aes:                                             "https://datatracker.ietf.org/doc/html/rfc3826"
+	snmp_v1_coexistence:                                      "https://datatracker.ietf.org/doc/html/rfc3584"
 	socket:                                                   "\(wikipedia)/wiki/Network_socket"
 	splunk:                                                   "https://www.splunk.com"
 	splunk_hec:                                               "https://dev.splunk.com/enterprise/docs/dataapps/httpeventcollector/"
@@ -609,6 +643,7 @@ urls: {
 	vote_feature:                                             "\(vector_repo)/issues?q=is%3Aissue+is%3Aopen+sort%3Areactions-%2B1-desc+label%3A%22Type%3A+New+Feature%22"
 	wasm:                                                     "https://webassembly.org/"
 	wasm_languages:                                           "\(github)/appcypher/awesome-wasm-langs"
+	websocket:                                                "\(wikipedia)/wiki/WebSocket"
 	wikipedia:                                                "https://en.wikipedia.org"
 	windows:                                                  "https://www.microsoft.com/en-us/windows"
 	windows_installer:                                        "\(wikipedia)/wiki/Windows_Installer"